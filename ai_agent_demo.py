@@ -8,10 +8,12 @@ This script demonstrates a complete AI agent workflow that:
 3. Orchestrates multiple agents with cryptographic guarantees
 """
 
+import argparse
 import asyncio
 import json
 import sys
 from pathlib import Path
+from typing import Optional
 from rich.console import Console
 from rich.panel import Panel
 from rich.progress import Progress, SpinnerColumn, TextColumn
@@ -22,6 +24,9 @@ from rich.json import JSON
 sys.path.append(str(Path(__file__).parent))
 
 from ai_agents.agent_workflow import AgentWorkflow
+from ai_agents.scenarios import Scenario, run_scenarios
+from ai_agents.embeddings import ScenarioMemory
+from ai_agents.coverage import CoverageTracker
 
 console = Console()
 
@@ -158,19 +163,109 @@ async def run_demo_workflow(csv_filename: str, threshold: int = 1000):
         ))
         return None
 
+def display_claim_check(results: dict):
+    """Show each of the generator's claims next to what the journal committed"""
+    check = results.get("claim_check")
+    if not check:
+        return
+    table = Table(title="🧪 Generator Claims vs Journal")
+    table.add_column("Claim", style="cyan")
+    table.add_column("Journal", style="magenta")
+    table.add_column("Holds", style="green")
+    for claim in check["claims"]:
+        holds = {True: "✅", False: "🚩 hallucination", None: "unchecked"}[claim["holds"]]
+        table.add_row(claim["text"], f"{claim['subject']} = {claim['actual']}", holds)
+    console.print(table)
+
+async def run_generated_workflow(description: str, rows: int, threshold: int, report_path: Optional[str]):
+    """Generate CSV with the AI generator and check its claims against the proof"""
+    console.print(f"[bold yellow]Generating {rows} row(s): {description}[/bold yellow]")
+    results = await AgentWorkflow().run_generated_csv_workflow(description, rows, threshold)
+    if "agent_results" in results:
+        display_workflow_results(results)
+        display_claim_check(results)
+        if not results["claim_check"]["claims"]:
+            console.print("[italic]The generator made no checkable claim about its data[/italic]")
+    else:
+        console.print(f"[bold red]Error: {results.get('error')}[/bold red]")
+    if report_path:
+        Path(report_path).write_text(json.dumps(results, indent=2, default=str))
+        console.print(f"💾 Report written to {report_path}")
+
+DEMO_SCENARIOS = [
+    Scenario("good_data", "test_data.csv", threshold=1000),
+    Scenario("exceeds_threshold", "test_data_large.csv", threshold=1000),
+]
+
+async def run_parallel(
+    max_concurrency: int,
+    report_path: Optional[str],
+    dedupe: Optional[str] = None,
+    cover: bool = False
+):
+    """Run every demo scenario concurrently and display the aggregate"""
+    console.print(f"[bold yellow]Running {len(DEMO_SCENARIOS)} scenarios, up to {max_concurrency} at a time[/bold yellow]")
+    scenarios = [
+        Scenario(s.name, str(Path(__file__).parent / s.csv_file), s.threshold) for s in DEMO_SCENARIOS
+    ]
+    memory = ScenarioMemory() if dedupe else None
+    report = await run_scenarios(
+        scenarios, max_concurrency, memory=memory, on_duplicate=dedupe or "skip",
+        coverage=CoverageTracker(".zaik/coverage.json"), target_uncovered=cover
+    )
+
+    for entry in report["scenarios"]:
+        console.print(f"\n[bold yellow]Scenario: {entry['scenario']} ({entry['elapsed_seconds']}s)[/bold yellow]")
+        if "agent_results" in entry["result"]:
+            display_workflow_results(entry["result"])
+        else:
+            console.print(f"[bold red]Error: {entry['result'].get('error')}[/bold red]")
+
+    console.print(Panel(
+        f"Passed: {report['passed']}  Failed: {report['failed']}  "
+        f"Skipped as duplicates: {len(report['skipped_duplicates'])}\n"
+        f"Coverage: {report['coverage']['covered']}/{report['coverage']['total']} regions "
+        f"(uncovered: {', '.join(report['coverage']['uncovered']) or 'none'})\n"
+        f"Wall time: {report['wall_time_seconds']:.2f}s "
+        f"(scenarios summed: {report['summed_scenario_seconds']:.2f}s)",
+        title="📋 Scenario Summary"
+    ))
+    if report_path:
+        Path(report_path).write_text(json.dumps(report, indent=2, default=str))
+        console.print(f"💾 Report written to {report_path}")
+
 async def main():
     """Main demo function"""
+    parser = argparse.ArgumentParser(description=__doc__, formatter_class=argparse.RawDescriptionHelpFormatter)
+    parser.add_argument("--parallel", type=int, default=0, metavar="N",
+                        help="run the scenarios concurrently, at most N at a time")
+    parser.add_argument("--report", help="write the aggregated scenario report to this JSON file")
+    parser.add_argument("--generate", metavar="DESCRIPTION",
+                        help="have the AI generate the CSV instead, and check what it claims about it")
+    parser.add_argument("--rows", type=int, default=4, help="with --generate, data rows to ask for")
+    parser.add_argument("--threshold", type=int, default=1000, help="with --generate, the business threshold")
+    parser.add_argument("--dedupe", choices=["skip", "mutate"],
+                        help="with --parallel, skip or mutate scenarios nearly identical to ones already run")
+    parser.add_argument("--cover", action="store_true",
+                        help="with --parallel, add scenarios for invariant regions not yet exercised")
+    args = parser.parse_args()
+
     console.print("[bold cyan]🤖 AI Agent Workflow Demo with RISC Zero[/bold cyan]\n")
     
-    # Demo 1: Test with good CSV data (should pass)
-    console.print("[bold yellow]Demo 1: Testing with good CSV data (sum should pass threshold)[/bold yellow]")
-    await run_demo_workflow("test_data.csv", threshold=1000)
-    
-    console.print("\n" + "="*80 + "\n")
-    
-    # Demo 2: Test with CSV data that exceeds threshold (should fail business logic)
-    console.print("[bold yellow]Demo 2: Testing with CSV data that exceeds threshold[/bold yellow]")
-    await run_demo_workflow("test_data_large.csv", threshold=1000)
+    if args.generate:
+        await run_generated_workflow(args.generate, args.rows, args.threshold, args.report)
+    elif args.parallel:
+        await run_parallel(args.parallel, args.report, args.dedupe, args.cover)
+    else:
+        # Demo 1: Test with good CSV data (should pass)
+        console.print("[bold yellow]Demo 1: Testing with good CSV data (sum should pass threshold)[/bold yellow]")
+        await run_demo_workflow("test_data.csv", threshold=1000)
+        
+        console.print("\n" + "="*80 + "\n")
+        
+        # Demo 2: Test with CSV data that exceeds threshold (should fail business logic)
+        console.print("[bold yellow]Demo 2: Testing with CSV data that exceeds threshold[/bold yellow]")
+        await run_demo_workflow("test_data_large.csv", threshold=1000)
     
     console.print("\n🎉 [bold green]Demo completed![/bold green]")
     console.print("\n[italic]This demo showcased:[/italic]")
@@ -0,0 +1,113 @@
+"""
+Model parameters per agent type
+Each agent can run on its own model with its own sampling, e.g. the CSV
+generator at temperature 1.0 and the verification agent at 0.0. Settings
+come from the YAML file named by ZAIK_AGENT_MODELS:
+
+    default:
+      model: gpt-4o
+    csv_generator:
+      temperature: 1.0
+      top_p: 0.95
+    verification_agent:
+      temperature: 0.0
+      max_tokens: 800
+
+`default` applies to every agent and an agent's own entry overrides it key
+by key. A configured value wins over the one an agent's code passes, which
+is only its fallback; a model pinned in code (an ensemble member) is kept
+"""
+import os
+from dataclasses import dataclass, fields
+from enum import Enum
+from pathlib import Path
+from typing import Any, Dict, Optional, Union
+
+import yaml
+
+DEFAULT_MODEL = "gpt-4-turbo-preview"
+DEFAULT_TEMPERATURE = 0.1
+DEFAULT_MAX_TOKENS = 2000
+
+class AgentType(str, Enum):
+    CSV_ANALYZER = "csv_analyzer"
+    VERIFICATION_AGENT = "verification_agent"
+    ORCHESTRATOR = "orchestrator"
+    ENHANCED_ORCHESTRATOR = "enhanced_orchestrator"
+    DATA_QUALITY = "data_quality"
+    SECURITY = "security"
+    BUSINESS_LOGIC = "business_logic"
+    RISK_ASSESSMENT = "risk_assessment"
+    CSV_GENERATOR = "csv_generator"
+
+@dataclass(frozen=True)
+class ModelParams:
+    # None leaves the setting to the next level down
+    model: Optional[str] = None
+    temperature: Optional[float] = None
+    max_tokens: Optional[int] = None
+    top_p: Optional[float] = None
+
+    def over(self, fallback: "ModelParams") -> "ModelParams":
+        """These settings, with `fallback`'s wherever these have none"""
+        return ModelParams(**{
+            f.name: getattr(fallback, f.name) if getattr(self, f.name) is None else getattr(self, f.name)
+            for f in fields(self)
+        })
+
+    @classmethod
+    def parse(cls, where: str, entry: Any) -> "ModelParams":
+        if not isinstance(entry, dict):
+            raise ValueError(f"{where}: expected a mapping of model parameters")
+        known = {f.name for f in fields(cls)}
+        unknown = set(entry) - known
+        if unknown:
+            raise ValueError(f"{where}: unknown parameter(s) {', '.join(sorted(unknown))} (known: {', '.join(sorted(known))})")
+        params = cls(**entry)
+        number = lambda value: isinstance(value, (int, float)) and not isinstance(value, bool)
+        if params.model is not None and (not isinstance(params.model, str) or not params.model.strip()):
+            raise ValueError(f"{where}: model must be a non-empty string")
+        if params.temperature is not None and not (number(params.temperature) and 0.0 <= params.temperature <= 2.0):
+            raise ValueError(f"{where}: temperature must be between 0 and 2")
+        if params.top_p is not None and not (number(params.top_p) and 0.0 < params.top_p <= 1.0):
+            raise ValueError(f"{where}: top_p must be in (0, 1]")
+        if params.max_tokens is not None and not (
+            isinstance(params.max_tokens, int) and not isinstance(params.max_tokens, bool) and params.max_tokens > 0
+        ):
+            raise ValueError(f"{where}: max_tokens must be a positive integer")
+        return params
+
+class AgentModels:
+    def __init__(self, default: ModelParams = ModelParams(), agents: Optional[Dict[AgentType, ModelParams]] = None):
+        self.default = default
+        self.agents = agents or {}
+
+    @classmethod
+    def parse(cls, spec: Dict[str, Any], source: str = "agent models") -> "AgentModels":
+        default, agents = ModelParams(), {}
+        for name, entry in (spec or {}).items():
+            if name == "default":
+                default = ModelParams.parse(f"{source}: default", entry)
+                continue
+            try:
+                agent = AgentType(name)
+            except ValueError:
+                known = ", ".join(["default"] + [a.value for a in AgentType])
+                raise ValueError(f"{source}: unknown agent type '{name}' (known: {known})") from None
+            agents[agent] = ModelParams.parse(f"{source}: {name}", entry)
+        return cls(default, agents)
+
+    @classmethod
+    def load(cls, path: Union[str, Path]) -> "AgentModels":
+        return cls.parse(yaml.safe_load(Path(path).read_text()), str(path))
+
+    @classmethod
+    def from_env(cls) -> "AgentModels":
+        path = os.getenv("ZAIK_AGENT_MODELS")
+        return cls.load(path) if path else cls()
+
+    def for_agent(self, agent: Optional[Union[AgentType, str]]) -> ModelParams:
+        """Configured settings for `agent`; what neither its entry nor
+        `default` sets is None"""
+        own = self.agents.get(AgentType(agent)) if agent else None
+        return (own or ModelParams()).over(self.default)
@@ -9,8 +9,17 @@ from dataclasses import dataclass
 from datetime import datetime
 import pandas as pd
 
-from .openai_client import OpenAIClient, AgentMessage, AgentResponse, AgentPrompts
-from .risc0_verifier import RISC0Verifier, VerificationResult
+from .openai_client import OpenAIClient, AgentMessage, AgentResponse, AgentPrompts, verify_prompt
+from . import prompt_templates
+from .risc0_verifier import EXIT_IO, RISC0Verifier, VerificationResult
+from .claims import Claim, check_claims
+from .csv_guardrails import CsvRejected, validate_csv
+from .specialized_agents import CsvGeneratorAgent
+from .agent_models import AgentType
+from .pipeline import Pipeline, RetryPolicy, Stage, StageFailed, new_pipeline_id
+from .confidence import ESCALATE, ConfidenceThresholds, ReviewQueue
+from .ensemble import EnsembleVerifier
+from .deadline import EXIT_DEADLINE, Deadline, DeadlineExceeded
 
 @dataclass
 class WorkflowState:
@@ -20,18 +29,45 @@ class WorkflowState:
     timestamp: datetime
     verification_results: Optional[Dict[str, Any]] = None
 
+def mismatched_prompts(report: Dict[str, Any]) -> List[str]:
+    """Agents in a stored report whose prompt no longer matches its recorded hash"""
+    prompts = dict(report.get("prompts", {}))
+    narrative = report.get("decision_explanation") or {}
+    if narrative.get("prompt_hash"):
+        prompts["decision_explanation"] = narrative
+    return [
+        agent for agent, entry in prompts.items()
+        if not entry.get("prompt_hash") or not entry.get("prompt")
+        or not verify_prompt(entry["prompt"], entry["prompt_hash"])
+    ]
+
 class AgentWorkflow:
     """Orchestrates AI agents with RISC Zero verification"""
     
-    def __init__(self):
+    def __init__(
+        self,
+        thresholds: Optional[ConfidenceThresholds] = None,
+        review_queue: Optional[ReviewQueue] = None,
+        ensemble: Optional[EnsembleVerifier] = None
+    ):
         self.openai_client = OpenAIClient()
         self.risc0_verifier = RISC0Verifier()
         self.workflow_history: List[WorkflowState] = []
+        # Decisions below their action's confidence minimum go to the queue
+        self.thresholds = thresholds or ConfidenceThresholds.from_env()
+        self.review_queue = review_queue or ReviewQueue()
+        # When set, the verification recommendation is the ensemble's vote
+        self.ensemble = ensemble or EnsembleVerifier.from_env()
         
     async def run_csv_analysis_workflow(
         self, 
         csv_content: str, 
-        business_threshold: int = 1000
+        business_threshold: int = 1000,
+        explain_decision: bool = False,
+        workflow_id: Optional[str] = None,
+        state_dir: Optional[str] = None,
+        deadline: Optional[Deadline] = None,
+        claims: Optional[List[Claim]] = None
     ) -> Dict[str, Any]:
         """
         Complete AI agent workflow for CSV analysis with RISC Zero verification
@@ -40,42 +76,146 @@ class AgentWorkflow:
         1. CSV Analyzer Agent analyzes the data
         2. RISC Zero verifier processes and proves execution
         3. Verification Agent validates results
-        4. Orchestrator makes final decision
+        4. Orchestrator makes final decision, escalated for review when its
+           confidence is below the action's threshold
+        5. (optional) Verification Agent narrates the policy trace
+        
+        With `state_dir`, each step's output is persisted; passing the same
+        `workflow_id` again resumes after the last completed step.
+        
+        With a `deadline` (default: ZAIK_DEADLINE), AI calls and proving get
+        at most the time left, and a run that runs out returns the
+        deadline_exceeded outcome with the completed steps' outputs.
+        
+        With `claims` (what an AI said about the CSV, see `ai_agents.claims`),
+        each is compared with the committed journal under `claim_check`, and
+        contradicted ones are listed as hallucinations.
         """
         
-        workflow_id = f"workflow_{datetime.now().strftime('%Y%m%d_%H%M%S')}"
+        workflow_id = workflow_id or new_pipeline_id("workflow")
+        deadline = deadline or Deadline.from_env()
         
         print(f"🚀 Starting AI Agent Workflow: {workflow_id}")
         print("=" * 50)
         
+        pipeline = Pipeline([
+            Stage("csv_analysis",
+                  lambda _: self._csv_analysis_step(csv_content, deadline),
+                  ["csv_analyzer"],
+                  RetryPolicy(max_attempts=2, backoff_seconds=1.0)),
+            Stage("risc0_verification",
+                  lambda _: self._risc0_verification_step(csv_content, business_threshold, deadline),
+                  ["risc0_verifier"]),
+            Stage("verification_review",
+                  lambda out: self._verification_agent_step(out["csv_analysis"], out["risc0_verification"], deadline),
+                  ["verification_agent"],
+                  RetryPolicy(max_attempts=2, backoff_seconds=1.0)),
+            Stage("final_decision",
+                  lambda out: self._orchestrator_decision_step(
+                      out["csv_analysis"], out["risc0_verification"], out["verification_review"], deadline
+                  ),
+                  ["orchestrator"],
+                  RetryPolicy(max_attempts=2, backoff_seconds=1.0)),
+        ], state_dir=state_dir)
+        
         try:
-            # Step 1: CSV Analysis Agent
-            analysis_result = await self._csv_analysis_step(csv_content)
-            self._record_workflow_step("csv_analysis", analysis_result, ["csv_analyzer"])
-            
-            # Step 2: RISC Zero Verification
-            verification_result = await self._risc0_verification_step(csv_content, business_threshold)
-            self._record_workflow_step("risc0_verification", verification_result, ["risc0_verifier"])
-            
-            # Step 3: Verification Agent Review
-            verification_review = await self._verification_agent_step(analysis_result, verification_result)
-            self._record_workflow_step("verification_review", verification_review, ["verification_agent"])
-            
-            # Step 4: Orchestrator Decision
-            final_decision = await self._orchestrator_decision_step(
-                analysis_result, verification_result, verification_review
+            state = await pipeline.run(
+                workflow_id,
+                on_stage_complete=lambda stage, output: self._record_workflow_step(stage.name, output, stage.agents),
+                deadline=deadline
             )
-            self._record_workflow_step("final_decision", final_decision, ["orchestrator"])
+            outputs = state.outputs
             
             # Compile final report
-            return self._compile_final_report(workflow_id, analysis_result, verification_result, verification_review, final_decision)
+            report = self._compile_final_report(
+                workflow_id,
+                outputs["csv_analysis"],
+                outputs["risc0_verification"],
+                outputs["verification_review"],
+                outputs["final_decision"]
+            )
+            if report["final_decision"] == ESCALATE:
+                gate = outputs["final_decision"]["confidence_gate"]
+                queued = self.review_queue.enqueue(workflow_id, gate, {
+                    "column_a_sum": report["csv_details"]["column_a_sum"],
+                    "proof_kind": report["verification_guarantees"]["proof_kind"]
+                })
+                report["review"] = {"queue": str(self.review_queue.path), "newly_queued": queued, **gate}
+            # The narrative is optional, so it is dropped rather than failing
+            # a run that already has its decision
+            use_ai = explain_decision and not (deadline and deadline.expired())
+            report["decision_explanation"] = await self._decision_explanation_step(
+                outputs["risc0_verification"], outputs["final_decision"], use_ai=use_ai, deadline=deadline
+            )
+            if claims is not None:
+                report["claim_check"] = check_claims(claims, report["csv_details"])
+                for text in report["claim_check"]["hallucinations"]:
+                    print(f"   🚩 Claim not borne out by the journal: \"{text}\"")
+            return report
             
+        except DeadlineExceeded as e:
+            print(f"⏰ {e}")
+            self._record_workflow_step("error", {"error": str(e), "step_failed": e.stage}, ["system"])
+            return {
+                "success": False,
+                "outcome": "deadline_exceeded",
+                "error": str(e),
+                "step_failed": e.stage,
+                "partial_results": getattr(e, "outputs", {}),
+                "workflow_id": workflow_id
+            }
+        except StageFailed as e:
+            error_result = {"error": str(e.error), "step_failed": e.stage}
+            self._record_workflow_step("error", error_result, ["system"])
+            return {"success": False, "error": str(e), "step_failed": e.stage, "workflow_id": workflow_id}
         except Exception as e:
             error_result = {"error": str(e), "step_failed": "unknown"}
             self._record_workflow_step("error", error_result, ["system"])
             return {"success": False, "error": str(e), "workflow_id": workflow_id}
     
-    async def _csv_analysis_step(self, csv_content: str) -> Dict[str, Any]:
+    async def run_generated_csv_workflow(
+        self,
+        description: str,
+        rows: int = 10,
+        business_threshold: int = 1000,
+        **workflow_options
+    ) -> Dict[str, Any]:
+        """
+        Have the CSV generator write data for `description`, then run the
+        workflow on it with the generator's own claims about the data
+        ("4 rows summing under 1000") checked against the journal. Output
+        the guardrails cannot repair is rejected with exit code 5
+        (`csv_rejected`) before anything is proven.
+        """
+        print(f"🧪 Generating {rows} row(s): {description}")
+        generated = await CsvGeneratorAgent().generate_csv_data(description, rows)
+        generator = {
+            "description": description,
+            "raw_response": generated["raw_response"],
+            "extraction": generated["extraction"],
+            "prompt_hash": generated["prompt_hash"]
+        }
+        try:
+            checked = validate_csv(generated["csv"])
+        except CsvRejected as e:
+            failure = {"outcome": "io", "exit_code": EXIT_IO, "reason": "csv_rejected", "message": str(e)}
+            return {
+                "success": False,
+                "error": str(e),
+                "exit_code": EXIT_IO,
+                "failure_reason": failure,
+                "generator": {**generator, "guardrails": {"rejected": e.to_dict(), "repairs": []}}
+            }
+        report = await self.run_csv_analysis_workflow(
+            checked.csv, business_threshold, claims=generated["claims"], **workflow_options
+        )
+        report["generator"] = {**generator, "guardrails": {"rejected": None, "repairs": checked.repairs}}
+        return report
+    
+    def _ai_timeout(self, deadline: Optional[Deadline]) -> Optional[float]:
+        return deadline.timeout(self.openai_client.timeout) if deadline else None
+    
+    async def _csv_analysis_step(self, csv_content: str, deadline: Optional[Deadline] = None) -> Dict[str, Any]:
         """Step 1: AI agent analyzes CSV structure and content"""
         print("🤖 Step 1: CSV Analyzer Agent")
         
@@ -84,27 +224,13 @@ class AgentWorkflow:
         headers = lines[0].split(',') if lines else []
         data_rows = lines[1:] if len(lines) > 1 else []
         
-        analysis_prompt = f"""
-        Analyze this CSV data:
-        
-        Headers: {headers}
-        Row count: {len(data_rows)}
-        Sample rows: {data_rows[:3] if data_rows else []}
-        
-        Focus on:
-        1. Data structure and column types
-        2. Column A values and patterns
-        3. Potential data quality issues
-        4. Business logic implications
-        
-        Predict what the sum of column A will be.
-        """
-        
-        messages = [AgentMessage(role="user", content=analysis_prompt)]
-        response = await self.openai_client.chat_completion(
-            messages=messages,
-            system_prompt=AgentPrompts.CSV_ANALYZER,
-            temperature=0.1
+        response = await self.openai_client.complete_template(
+            prompt_templates.CSV_ANALYSIS,
+            agent=AgentType.CSV_ANALYZER,
+            headers=headers,
+            row_count=len(data_rows),
+            sample_rows=data_rows[:3] if data_rows else [],
+            timeout=self._ai_timeout(deadline)
         )
         
         print(f"   📊 Analysis: {response.content[:100]}...")
@@ -116,6 +242,9 @@ class AgentWorkflow:
             "reasoning": response.reasoning,
             "confidence": response.confidence,
             "next_actions": response.next_actions,
+            "prompt_hash": response.prompt_hash,
+            "prompt": response.prompt,
+            "template": response.template,
             "csv_stats": {
                 "headers": headers,
                 "row_count": len(data_rows),
@@ -123,12 +252,19 @@ class AgentWorkflow:
             }
         }
     
-    async def _risc0_verification_step(self, csv_content: str, threshold: int) -> Dict[str, Any]:
+    async def _risc0_verification_step(
+        self, csv_content: str, threshold: int, deadline: Optional[Deadline] = None
+    ) -> Dict[str, Any]:
         """Step 2: RISC Zero deterministic verification"""
         print("🔒 Step 2: RISC Zero Deterministic Verification")
         
-        # Run RISC Zero verification
-        verification_report = self.risc0_verifier.verify_csv_data(csv_content)
+        # Run RISC Zero verification off the event loop so concurrent
+        # workflows keep making AI calls while this one proves
+        verification_report = await asyncio.to_thread(
+            self.risc0_verifier.verify_csv_data, csv_content, None, threshold, deadline
+        )
+        if deadline and verification_report.get("exit_code") == EXIT_DEADLINE:
+            raise deadline.exceeded("risc0_verification")
         
         print(f"   ✅ Verification: {'PASSED' if verification_report['verification_successful'] else 'FAILED'}")
         print(f"   🔐 RISC Zero Proof: {'VALID' if verification_report['risc0_proof_valid'] else 'INVALID'}")
@@ -139,88 +275,92 @@ class AgentWorkflow:
             "agent": "risc0_verifier",
             "verification_report": verification_report,
             "deterministic_proof": verification_report['risc0_proof_valid'],
+            "proof_kind": verification_report['proof_details']['proof_kind'],
             "business_compliance": verification_report['business_logic_satisfied'],
             "cryptographic_guarantees": verification_report['snark_proof_valid']
         }
     
-    async def _verification_agent_step(self, analysis_result: Dict[str, Any], verification_result: Dict[str, Any]) -> Dict[str, Any]:
+    async def _verification_agent_step(
+        self,
+        analysis_result: Dict[str, Any],
+        verification_result: Dict[str, Any],
+        deadline: Optional[Deadline] = None
+    ) -> Dict[str, Any]:
         """Step 3: AI agent reviews verification results"""
         print("🔍 Step 3: Verification Agent Review")
         
-        review_prompt = f"""
-        Review these results:
-        
-        CSV Analysis:
-        - Agent Analysis: {analysis_result['analysis']}
-        - Confidence: {analysis_result['confidence']}
-        
-        RISC Zero Verification:
-        - Verification Successful: {verification_result['verification_report']['verification_successful']}
-        - RISC Zero Proof Valid: {verification_result['verification_report']['risc0_proof_valid']}
-        - Business Logic Satisfied: {verification_result['verification_report']['business_logic_satisfied']}
-        - Column A Sum: {verification_result['verification_report']['csv_details']['column_a_sum']}
-        - Cryptographic Guarantees: {verification_result['verification_report']['snark_proof_valid']}
-        
-        Provide your verification assessment:
-        1. Do the results align with the initial analysis?
-        2. Are the cryptographic proofs valid?
-        3. Should we accept or reject this computation?
-        4. What are the trust guarantees?
-        """
-        
-        messages = [AgentMessage(role="user", content=review_prompt)]
-        response = await self.openai_client.chat_completion(
-            messages=messages,
-            system_prompt=AgentPrompts.VERIFICATION_AGENT,
-            temperature=0.1
+        report = verification_result['verification_report']
+        params = dict(
+            analysis=analysis_result['analysis'],
+            analysis_confidence=analysis_result['confidence'],
+            verification_successful=report['verification_successful'],
+            proof_valid=report['risc0_proof_valid'],
+            proof_kind=report['proof_details']['proof_kind'],
+            business_logic_satisfied=report['business_logic_satisfied'],
+            column_a_sum=report['csv_details']['column_a_sum'],
+            cryptographic_guarantees=report['snark_proof_valid']
+        )
+        response = await self.openai_client.complete_template(
+            prompt_templates.VERIFICATION_REVIEW,
+            timeout=self._ai_timeout(deadline),
+            agent=AgentType.VERIFICATION_AGENT,
+            **params
         )
         
         print(f"   🔍 Review: {response.content[:100]}...")
         print(f"   🎯 Confidence: {response.confidence}")
+        if response.condensed:
+            print(f"   ✂️  Condensed to fit the prompt budget: {', '.join(response.condensed)}")
+        
+        recommendation = "accept" if report['verification_successful'] else "reject"
+        confidence = response.confidence
+        ensemble = None
+        if self.ensemble:
+            # A failed verification is never outvoted
+            ensemble = await self.ensemble.review(
+                prompt_templates.VERIFICATION_REVIEW, timeout=self._ai_timeout(deadline), **params
+            )
+            if ensemble["action"] != "accept":
+                recommendation = "reject"
+            confidence = ensemble["confidence"]
+            print(f"   🗳️  Ensemble: {ensemble['action']} ({ensemble['confidence']}, votes {ensemble['votes']})")
         
         return {
             "agent": "verification_agent",
             "review": response.content,
             "reasoning": response.reasoning,
-            "confidence": response.confidence,
-            "recommendation": "accept" if verification_result['verification_report']['verification_successful'] else "reject",
-            "trust_level": response.confidence if response.confidence else 0.8
+            "confidence": confidence,
+            "recommendation": recommendation,
+            "ensemble": ensemble,
+            "trust_level": confidence if confidence else 0.8,
+            "prompt_hash": response.prompt_hash,
+            "prompt": response.prompt,
+            "template": response.template,
+            "condensed": response.condensed
         }
     
     async def _orchestrator_decision_step(
         self, 
         analysis_result: Dict[str, Any], 
         verification_result: Dict[str, Any], 
-        verification_review: Dict[str, Any]
+        verification_review: Dict[str, Any],
+        deadline: Optional[Deadline] = None
     ) -> Dict[str, Any]:
         """Step 4: Orchestrator makes final workflow decision"""
         print("🎯 Step 4: Orchestrator Final Decision")
         
-        decision_prompt = f"""
-        Make a final workflow decision based on all agent inputs:
-        
-        CSV Analysis Confidence: {analysis_result['confidence']}
-        RISC Zero Verification: {verification_result['verification_report']['verification_successful']}
-        Verification Agent Recommendation: {verification_review['recommendation']}
-        Verification Agent Confidence: {verification_review['confidence']}
-        
-        Key Metrics:
-        - Deterministic Execution Proven: {verification_result['deterministic_proof']}
-        - Business Logic Compliance: {verification_result['business_compliance']}
-        - Cryptographic Guarantees: {verification_result['cryptographic_guarantees']}
-        
-        Provide final decision:
-        1. Accept or reject the computation
-        2. Overall confidence level
-        3. Key success factors
-        4. Risk assessment
-        """
-        
-        messages = [AgentMessage(role="user", content=decision_prompt)]
-        response = await self.openai_client.chat_completion(
-            messages=messages,
-            system_prompt=AgentPrompts.ORCHESTRATOR,
-            temperature=0.1
+        response = await self.openai_client.complete_template(
+            prompt_templates.FINAL_DECISION,
+            agent=AgentType.ORCHESTRATOR,
+            analysis_confidence=analysis_result['confidence'],
+            verification_successful=verification_result['verification_report']['verification_successful'],
+            recommendation=verification_review['recommendation'],
+            review_confidence=verification_review['confidence'],
+            deterministic_proof=verification_result['deterministic_proof'],
+            proof_kind=verification_result['proof_kind'],
+            business_compliance=verification_result['business_compliance'],
+            cryptographic_guarantees=verification_result['cryptographic_guarantees'],
+            timeout=self._ai_timeout(deadline)
         )
         
         # Determine final decision based on verification results
@@ -230,12 +370,18 @@ class AgentWorkflow:
             verification_review['recommendation'] == "accept"
         )
         
-        print(f"   🎯 Decision: {'ACCEPT' if final_accept else 'REJECT'}")
+        gate = self.thresholds.gate("accept" if final_accept else "reject", response.confidence)
+        decision = gate["decision"]
+        
+        print(f"   🎯 Decision: {decision.upper()}")
         print(f"   📊 Overall Confidence: {response.confidence}")
+        if decision == ESCALATE:
+            print(f"   🙋 Escalated for review: {gate['reason']}")
         
         return {
             "agent": "orchestrator",
-            "decision": "accept" if final_accept else "reject",
+            "decision": decision,
+            "confidence_gate": gate,
             "reasoning": response.content,
             "overall_confidence": response.confidence,
             "success_factors": [
@@ -243,10 +389,54 @@ class AgentWorkflow:
                 "Cryptographic guarantees maintained", 
                 "Business logic compliance verified",
                 "Multi-agent consensus achieved"
-            ] if final_accept else ["Verification failed"],
-            "risk_assessment": "low" if final_accept else "high"
+            ] if decision == "accept" else (
+                ["Confidence below threshold"] if decision == ESCALATE else ["Verification failed"]
+            ),
+            "risk_assessment": "low" if decision == "accept" else "high",
+            "prompt_hash": response.prompt_hash,
+            "prompt": response.prompt,
+            "template": response.template
         }
     
+    async def _decision_explanation_step(
+        self,
+        verification_result: Dict[str, Any],
+        final_decision: Dict[str, Any],
+        use_ai: bool = False,
+        deadline: Optional[Deadline] = None
+    ) -> Dict[str, Any]:
+        """Combine the verifier's policy trace with an optional AI narrative"""
+        policy_outcome = verification_result["verification_report"].get("policy_outcome", {})
+        explanation = {
+            "decision": final_decision["decision"],
+            "policy_trace": policy_outcome.get("trace", []),
+            "summary": policy_outcome.get("explanation"),
+            "narrative": None
+        }
+        
+        if not use_ai:
+            return explanation
+        
+        print("💬 Step 5: Decision Explanation")
+        trace_lines = "\n".join(f"        - {rule}" for rule in explanation["policy_trace"])
+        response = await self.openai_client.complete_template(
+            prompt_templates.DECISION_EXPLANATION,
+            agent=AgentType.VERIFICATION_AGENT,
+            decision=final_decision['decision'],
+            proof_kind=verification_result['proof_kind'],
+            summary=explanation['summary'],
+            rules=trace_lines,
+            timeout=self._ai_timeout(deadline)
+        )
+        explanation["narrative"] = response.content
+        explanation["prompt_hash"] = response.prompt_hash
+        explanation["prompt"] = response.prompt
+        explanation["template"] = response.template
+        self._record_workflow_step("decision_explanation", explanation, ["verification_agent"])
+        
+        print(f"   💬 Narrative: {response.content[:100]}...")
+        return explanation
+    
     def _record_workflow_step(self, step: str, data: Dict[str, Any], agents: List[str]):
         """Record a workflow step in history"""
         state = WorkflowState(
@@ -286,7 +476,8 @@ class AgentWorkflow:
                 },
                 "verification_agent": {
                     "recommendation": verification_review["recommendation"],
-                    "confidence": verification_review["confidence"]
+                    "confidence": verification_review["confidence"],
+                    "ensemble": verification_review.get("ensemble")
                 },
                 "orchestrator": {
                     "decision": final_decision["decision"],
@@ -295,6 +486,7 @@ class AgentWorkflow:
             },
             
             "verification_guarantees": {
+                "proof_kind": verification_result["proof_kind"],
                 "deterministic_execution": verification_result["deterministic_proof"],
                 "cryptographic_proof": verification_result["verification_report"]["risc0_proof_valid"],
                 "business_logic_compliance": verification_result["business_compliance"],
@@ -307,5 +499,19 @@ class AgentWorkflow:
                 "execution_time": (datetime.now() - self.workflow_history[0].timestamp).total_seconds() if self.workflow_history else 0
             },
             
-            "csv_details": verification_result["verification_report"]["csv_details"]
+            "csv_details": verification_result["verification_report"]["csv_details"],
+            
+            # What each AI verdict was based on; see `mismatched_prompts`
+            "prompts": {
+                agent: {
+                    "template": result.get("template"),
+                    "prompt_hash": result.get("prompt_hash"),
+                    "prompt": result.get("prompt")
+                }
+                for agent, result in [
+                    ("csv_analyzer", analysis_result),
+                    ("verification_agent", verification_review),
+                    ("orchestrator", final_decision)
+                ]
+            }
         }
\ No newline at end of file
@@ -0,0 +1,75 @@
+"""
+Canonical JSON (RFC 8785, JCS) for hashing structured values
+Object members are sorted by UTF-16 code units, there is no whitespace and
+numbers use the ECMAScript shortest form, so a hash depends only on the
+values - the same bytes as zaik::canonical on the Rust side
+"""
+import hashlib
+import json
+import math
+from decimal import Decimal
+from typing import Any
+
+def _number(value: float) -> str:
+    if not math.isfinite(value):
+        raise ValueError(f"{value} has no JSON representation")
+    if value == 0:
+        return "0"
+    # repr() gives the shortest round-trip digits
+    sign, digits, exponent = Decimal(repr(value)).normalize().as_tuple()
+    digits = "".join(map(str, digits))
+    k = len(digits)
+    n = exponent + k
+    if k <= n <= 21:
+        body = digits + "0" * (n - k)
+    elif 0 < n <= 21:
+        body = f"{digits[:n]}.{digits[n:]}"
+    elif -6 < n <= 0:
+        body = "0." + "0" * -n + digits
+    else:
+        mantissa = digits[0] + (f".{digits[1:]}" if k > 1 else "")
+        body = f"{mantissa}e{'+' if n > 0 else '-'}{abs(n - 1)}"
+    return ("-" if sign else "") + body
+
+def _encode(value: Any, out: list):
+    if value is None:
+        out.append("null")
+    elif value is True:
+        out.append("true")
+    elif value is False:
+        out.append("false")
+    elif isinstance(value, int):
+        out.append(str(value))
+    elif isinstance(value, float):
+        out.append(_number(value))
+    elif isinstance(value, str):
+        out.append(json.dumps(value, ensure_ascii=False))
+    elif isinstance(value, (list, tuple)):
+        out.append("[")
+        for index, item in enumerate(value):
+            if index:
+                out.append(",")
+            _encode(item, out)
+        out.append("]")
+    elif isinstance(value, dict):
+        if not all(isinstance(key, str) for key in value):
+            raise TypeError("canonical JSON object keys must be strings")
+        out.append("{")
+        for index, key in enumerate(sorted(value, key=lambda key: key.encode("utf-16-be"))):
+            if index:
+                out.append(",")
+            out.append(json.dumps(key, ensure_ascii=False))
+            out.append(":")
+            _encode(value[key], out)
+        out.append("}")
+    else:
+        raise TypeError(f"{type(value).__name__} is not JSON serializable")
+
+def canonical_json(value: Any) -> str:
+    out: list = []
+    _encode(value, out)
+    return "".join(out)
+
+def canonical_hash(value: Any) -> str:
+    """Hex SHA-256 of `value`'s canonical JSON"""
+    return hashlib.sha256(canonical_json(value).encode("utf-8")).hexdigest()
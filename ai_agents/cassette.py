@@ -0,0 +1,161 @@
+"""
+Record/replay of HTTP traffic
+An httpx transport that writes every request and response to a cassette
+file, or answers from one without touching the network, so the AI
+subsystems can be tested deterministically and offline. Configured with
+
+    ZAIK_CASSETTE=tests/cassettes/demo.json
+    ZAIK_CASSETTE_MODE=record | replay | once
+
+`once` replays when the cassette exists and records it otherwise. Request
+headers are never written, so API keys stay out of cassettes; requests are
+matched on method, URL and canonical JSON body
+"""
+import base64
+import json
+import os
+from pathlib import Path
+from typing import Any, Dict, List, Optional, Tuple
+
+import httpx
+
+from .canonical import canonical_json
+
+MODES = ("record", "replay", "once")
+# Response headers worth keeping; the rest describe the original connection
+KEPT_HEADERS = ("content-type",)
+
+_OPEN: Dict[Tuple[str, str], "Cassette"] = {}
+
+class CassetteMiss(Exception):
+    """Replay found no recorded interaction for a request"""
+
+def _body_key(body: bytes) -> str:
+    try:
+        return canonical_json(json.loads(body))
+    except (ValueError, UnicodeDecodeError):
+        return base64.b64encode(body).decode()
+
+def _encode(body: bytes) -> Dict[str, str]:
+    try:
+        return {"body": body.decode()}
+    except UnicodeDecodeError:
+        return {"body_base64": base64.b64encode(body).decode()}
+
+def _decode(entry: Dict[str, Any]) -> bytes:
+    if "body_base64" in entry:
+        return base64.b64decode(entry["body_base64"])
+    return entry.get("body", "").encode()
+
+class Cassette:
+    def __init__(self, path: str, mode: str = "once"):
+        if mode not in MODES:
+            raise ValueError(f"unknown cassette mode '{mode}' (known: {', '.join(MODES)})")
+        self.path = Path(path)
+        if mode == "once":
+            mode = "replay" if self.path.exists() else "record"
+        self.mode = mode
+        self.interactions: List[Dict[str, Any]] = []
+        if mode == "replay":
+            self.interactions = json.loads(self.path.read_text())["interactions"]
+        # Next unplayed interaction per request; a request repeated more
+        # often than recorded gets the last matching answer again
+        self._played: Dict[str, int] = {}
+
+    @classmethod
+    def from_env(cls) -> Optional["Cassette"]:
+        """The process-wide cassette, shared so every client in a run records
+        into (or replays from) the same file"""
+        path = os.getenv("ZAIK_CASSETTE")
+        if not path:
+            return None
+        mode = os.getenv("ZAIK_CASSETTE_MODE", "once")
+        if (path, mode) not in _OPEN:
+            _OPEN[(path, mode)] = cls(path, mode)
+        return _OPEN[(path, mode)]
+
+    @staticmethod
+    def key(method: str, url: str, body: bytes) -> str:
+        return f"{method.upper()} {url} {_body_key(body)}"
+
+    def play(self, method: str, url: str, body: bytes) -> Dict[str, Any]:
+        key = self.key(method, url, body)
+        matches = [
+            interaction["response"] for interaction in self.interactions
+            if self.key(interaction["request"]["method"], interaction["request"]["url"],
+                        _decode(interaction["request"])) == key
+        ]
+        if not matches:
+            raise CassetteMiss(f"no recorded interaction for {method.upper()} {url} in {self.path}")
+        index = self._played.get(key, 0)
+        self._played[key] = index + 1
+        return matches[min(index, len(matches) - 1)]
+
+    def record(self, method: str, url: str, body: bytes, status: int, headers: Dict[str, str], content: bytes):
+        self.interactions.append({
+            "request": {"method": method.upper(), "url": url, **_encode(body)},
+            "response": {
+                "status": status,
+                "headers": {name: value for name, value in headers.items() if name.lower() in KEPT_HEADERS},
+                **_encode(content)
+            }
+        })
+        self.path.parent.mkdir(parents=True, exist_ok=True)
+        self.path.write_text(json.dumps({"version": 1, "interactions": self.interactions}, indent=2))
+
+class CassetteTransport(httpx.AsyncBaseTransport, httpx.BaseTransport):
+    """Wraps a real transport when recording; never opens one when replaying"""
+
+    def __init__(self, cassette: Cassette, proxy: Optional[str] = None):
+        self.cassette = cassette
+        # A custom transport bypasses httpx's own proxy handling
+        self.proxy = proxy
+        self._async_inner: Optional[httpx.AsyncBaseTransport] = None
+        self._inner: Optional[httpx.BaseTransport] = None
+
+    def _replay(self, request: httpx.Request) -> httpx.Response:
+        entry = self.cassette.play(request.method, str(request.url), request.content)
+        return httpx.Response(entry["status"], headers=entry["headers"], content=_decode(entry), request=request)
+
+    def _keep(self, request: httpx.Request, response: httpx.Response) -> httpx.Response:
+        self.cassette.record(
+            request.method, str(request.url), request.content,
+            response.status_code, dict(response.headers), response.content
+        )
+        kept = {name: value for name, value in response.headers.items() if name.lower() in KEPT_HEADERS}
+        return httpx.Response(response.status_code, headers=kept, content=response.content, request=request)
+
+    async def handle_async_request(self, request: httpx.Request) -> httpx.Response:
+        await request.aread()
+        if self.cassette.mode == "replay":
+            return self._replay(request)
+        self._async_inner = self._async_inner or httpx.AsyncHTTPTransport(proxy=self.proxy)
+        response = await self._async_inner.handle_async_request(request)
+        await response.aread()
+        return self._keep(request, response)
+
+    def handle_request(self, request: httpx.Request) -> httpx.Response:
+        request.read()
+        if self.cassette.mode == "replay":
+            return self._replay(request)
+        self._inner = self._inner or httpx.HTTPTransport(proxy=self.proxy)
+        response = self._inner.handle_request(request)
+        response.read()
+        return self._keep(request, response)
+
+    async def aclose(self):
+        if self._async_inner:
+            await self._async_inner.aclose()
+
+    def close(self):
+        if self._inner:
+            self._inner.close()
+
+def async_client(cassette: Optional[Cassette] = None, proxy: Optional[str] = None) -> Optional[httpx.AsyncClient]:
+    """An httpx client routed through the configured cassette, or None"""
+    cassette = cassette or Cassette.from_env()
+    return httpx.AsyncClient(transport=CassetteTransport(cassette, proxy)) if cassette else None
+
+def client(cassette: Optional[Cassette] = None, proxy: Optional[str] = None) -> Optional[httpx.Client]:
+    cassette = cassette or Cassette.from_env()
+    return httpx.Client(transport=CassetteTransport(cassette, proxy)) if cassette else None
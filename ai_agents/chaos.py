@@ -0,0 +1,86 @@
+"""
+Failure injection for resilience tests
+Disabled unless ZAIK_CHAOS is set, e.g.
+
+    ZAIK_CHAOS="corrupt_bundle=0.5,prover_delay=10,malformed_ai_json=0.3,drop_delivery=1,seed=7"
+
+Probabilities are per opportunity; `prover_delay` is seconds added before
+every proving run. Every injected fault is printed and kept in
+`Chaos.injected` so a test can match faults to the behavior it observed
+"""
+import os
+import random
+import time
+from dataclasses import dataclass, fields
+from pathlib import Path
+from typing import Any, Dict, List, Optional
+
+@dataclass
+class ChaosConfig:
+    corrupt_bundle: float = 0.0
+    prover_delay: float = 0.0
+    malformed_ai_json: float = 0.0
+    drop_delivery: float = 0.0
+    seed: Optional[int] = None
+
+    @classmethod
+    def parse(cls, spec: str) -> "ChaosConfig":
+        known = {f.name for f in fields(cls)}
+        values: Dict[str, Any] = {}
+        for item in filter(None, (part.strip() for part in spec.split(","))):
+            name, _, value = item.partition("=")
+            if name not in known:
+                raise ValueError(f"unknown chaos setting '{name}' (known: {', '.join(sorted(known))})")
+            values[name] = int(value) if name == "seed" else float(value)
+        return cls(**values)
+
+class Chaos:
+    def __init__(self, config: ChaosConfig):
+        self.config = config
+        self.rng = random.Random(config.seed)
+        self.injected: List[Dict[str, Any]] = []
+
+    def _roll(self, probability: float) -> bool:
+        return probability > 0 and self.rng.random() < probability
+
+    def _record(self, fault: str, **detail):
+        self.injected.append({"fault": fault, **detail})
+        print(f"🐒 Chaos: {fault} {detail if detail else ''}".rstrip())
+
+    def before_proving(self, store_dir: Path):
+        """Delay the prover and/or damage a stored bundle it may reuse"""
+        if self.config.prover_delay > 0:
+            self._record("prover_delay", seconds=self.config.prover_delay)
+            time.sleep(self.config.prover_delay)
+        bundles = sorted(store_dir.glob("*.bundle")) if store_dir.is_dir() else []
+        if bundles and self._roll(self.config.corrupt_bundle):
+            bundle = self.rng.choice(bundles)
+            data = bytearray(bundle.read_bytes())
+            if data:
+                offset = self.rng.randrange(len(data))
+                data[offset] ^= 0xFF
+                bundle.write_bytes(bytes(data))
+                self._record("corrupt_bundle", bundle=bundle.name, offset=offset)
+
+    def ai_response(self, content: str) -> str:
+        """Possibly replace a model response with truncated JSON"""
+        if self._roll(self.config.malformed_ai_json):
+            self._record("malformed_ai_json")
+            return '{"content": "truncated response", "confidence": 0.'
+        return content
+
+    def drop_delivery(self, target: str) -> bool:
+        """Whether to silently drop an outgoing notification"""
+        if self._roll(self.config.drop_delivery):
+            self._record("drop_delivery", target=target)
+            return True
+        return False
+
+_current: Optional[Chaos] = None
+
+def current() -> Chaos:
+    """Process-wide chaos instance configured from ZAIK_CHAOS"""
+    global _current
+    if _current is None:
+        _current = Chaos(ChaosConfig.parse(os.getenv("ZAIK_CHAOS", "")))
+    return _current
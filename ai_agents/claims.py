@@ -0,0 +1,88 @@
+"""
+Checking what the CSV generator says about its own output
+A model that writes test data tends to describe it too ("4 rows summing
+under 1000"). `parse_claims` pulls the checkable statements out of that
+prose, and `check_claims` compares them with the journal the guest
+committed for the same CSV, so a description the proven data does not bear
+out is flagged as a hallucination instead of being passed along
+"""
+import re
+from dataclasses import asdict, dataclass
+from typing import Any, Dict, List, Optional
+
+# Phrase -> comparison; matched longest first, so "no more than" wins over "more than"
+COMPARATORS = {
+    "no more than": "<=", "not more than": "<=", "at most": "<=", "up to": "<=",
+    "less than": "<", "fewer than": "<", "under": "<", "below": "<",
+    "no less than": ">=", "at least": ">=",
+    "more than": ">", "greater than": ">", "exceeding": ">", "over": ">", "above": ">",
+}
+
+_NUMBER = r"(\d{1,3}(?:,\d{3})+|\d+)(?![\d,]\d)"
+_UNIT = r"\s+(?:data\s+)?(?:rows|row|entries|entry|records|record)\b"
+_COMPARATOR = "|".join(sorted(map(re.escape, COMPARATORS), key=len, reverse=True))
+# "over 4 rows" more often means across them than a bound on their number
+_ROW_COMPARATOR = "|".join(
+    sorted(map(re.escape, set(COMPARATORS) - {"over", "above", "under", "below", "up to"}), key=len, reverse=True)
+)
+# "sum of column A is 800", "summing under 1000", "total of at most 1,000"
+_SUM = re.compile(
+    rf"\b(?:sum|summing|sums|total|totals|totalling|totaling|adds up to|adding up to)\b"
+    rf"(?:\s+(?:of|for|in)\s+column\s+a)?(?:\s+(?:is|of|to|equals|=|comes to))?\s*(?:({_COMPARATOR})\s+)?{_NUMBER}(?!{_UNIT})",
+    re.IGNORECASE
+)
+# "4 rows", "exactly 4 data rows", "at most 10 entries"
+_ROWS = re.compile(
+    rf"(?:\b({_ROW_COMPARATOR})\s+)?(?:exactly\s+)?{_NUMBER}{_UNIT}",
+    re.IGNORECASE
+)
+
+@dataclass
+class Claim:
+    # Journal field checked, as `RISC0Verifier` reports it: "entry_count"
+    # or "column_a_sum"
+    subject: str
+    # One of ==, <, <=, > and >=
+    comparison: str
+    value: int
+    # The phrase the claim was read from
+    text: str
+
+    def holds(self, actual: int) -> bool:
+        return {
+            "==": actual == self.value, "<": actual < self.value, "<=": actual <= self.value,
+            ">": actual > self.value, ">=": actual >= self.value,
+        }[self.comparison]
+
+def _claim(subject: str, comparator: Optional[str], number: str, text: str) -> Claim:
+    comparison = COMPARATORS[comparator.lower()] if comparator else "=="
+    return Claim(subject, comparison, int(number.replace(",", "")), text.strip())
+
+def parse_claims(text: str) -> List[Claim]:
+    """Row-count and column A sum claims in `text`, in the order they appear"""
+    found = [(m.start(), _claim("column_a_sum", m.group(1), m.group(2), m.group(0))) for m in _SUM.finditer(text)]
+    found += [(m.start(), _claim("entry_count", m.group(1), m.group(2), m.group(0))) for m in _ROWS.finditer(text)]
+    return [claim for _, claim in sorted(found, key=lambda item: item[0])]
+
+def prose_around(response: str, payload: str) -> str:
+    """The lines of `response` that are not part of the extracted CSV"""
+    data = {line.strip() for line in payload.split("\n") if line.strip()}
+    return "\n".join(line for line in response.split("\n") if line.strip() not in data)
+
+def check_claims(claims: List[Claim], csv_details: Dict[str, Any]) -> Dict[str, Any]:
+    """
+    Compare each claim with the committed journal values in `csv_details`
+    (`report["csv_details"]`). A claim the journal contradicts is a
+    hallucination; with no journal values, nothing is checked
+    """
+    checked = []
+    for claim in claims:
+        actual = csv_details.get(claim.subject) if csv_details.get("hash") else None
+        checked.append({**asdict(claim), "actual": actual, "holds": None if actual is None else claim.holds(actual)})
+    hallucinations = [entry["text"] for entry in checked if entry["holds"] is False]
+    return {
+        "claims": checked,
+        "checked": sum(entry["holds"] is not None for entry in checked),
+        "hallucinations": hallucinations,
+        "hallucination_detected": bool(hallucinations)
+    }
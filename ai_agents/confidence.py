@@ -0,0 +1,77 @@
+"""
+Confidence thresholds for AI decisions
+An action is only taken automatically when the model's confidence reaches
+that action's minimum; anything below is escalated to a review queue
+instead. Thresholds come from ZAIK_CONFIDENCE, e.g.
+
+    ZAIK_CONFIDENCE="accept=0.9,reject=0.6"
+
+A missing confidence counts as 0
+"""
+import json
+import os
+import time
+from dataclasses import dataclass, field
+from pathlib import Path
+from typing import Any, Dict, List, Optional
+
+ESCALATE = "escalate"
+
+@dataclass
+class ConfidenceThresholds:
+    # Minimum confidence per action; unlisted actions are never escalated
+    minimums: Dict[str, float] = field(default_factory=lambda: {"accept": 0.9, "reject": 0.0})
+
+    @classmethod
+    def parse(cls, spec: str) -> "ConfidenceThresholds":
+        minimums = cls().minimums
+        for item in filter(None, (part.strip() for part in spec.split(","))):
+            action, _, value = item.partition("=")
+            if action not in minimums:
+                raise ValueError(f"unknown action '{action}' (known: {', '.join(sorted(minimums))})")
+            minimum = float(value)
+            if not 0.0 <= minimum <= 1.0:
+                raise ValueError(f"threshold for '{action}' must be between 0 and 1")
+            minimums[action] = minimum
+        return cls(minimums)
+
+    @classmethod
+    def from_env(cls) -> "ConfidenceThresholds":
+        return cls.parse(os.getenv("ZAIK_CONFIDENCE", ""))
+
+    def gate(self, action: str, confidence: Optional[float]) -> Dict[str, Any]:
+        """The action to take: `action` itself, or `escalate` with the reason"""
+        minimum = self.minimums.get(action, 0.0)
+        observed = confidence if isinstance(confidence, (int, float)) else 0.0
+        if observed >= minimum:
+            return {"decision": action, "proposed": action, "confidence": confidence, "minimum": minimum}
+        return {
+            "decision": ESCALATE,
+            "proposed": action,
+            "confidence": confidence,
+            "minimum": minimum,
+            "reason": f"{action} confidence {observed} is below the {minimum} threshold"
+        }
+
+class ReviewQueue:
+    """Escalated decisions awaiting a human, one JSON object per line"""
+
+    def __init__(self, path: str = "review_queue.jsonl"):
+        self.path = Path(path)
+
+    def pending(self) -> List[Dict[str, Any]]:
+        if not self.path.exists():
+            return []
+        return [json.loads(line) for line in self.path.read_text().splitlines() if line.strip()]
+
+    def enqueue(self, workflow_id: str, gate: Dict[str, Any], context: Dict[str, Any]) -> bool:
+        """Add an escalation; a resumed workflow that is already queued is not added twice"""
+        if any(entry["workflow_id"] == workflow_id for entry in self.pending()):
+            return False
+        entry = {"workflow_id": workflow_id, "queued_at": time.time(), **gate, "context": context}
+        self.path.parent.mkdir(parents=True, exist_ok=True)
+        with self.path.open("a") as queue:
+            queue.write(json.dumps(entry) + "\n")
+            queue.flush()
+            os.fsync(queue.fileno())
+        return True
@@ -0,0 +1,128 @@
+"""
+Coverage of the invariant's input space across a test session
+Each region is a kind of input the sum-under-threshold invariant has to get
+right: sums at and either side of the bound, empty and single-row files,
+zeros, values near u64 overflow, and repeated keys. Every scenario run is
+classified, hits are persisted, and `targeted_csv` builds data for a region
+nobody has exercised yet
+"""
+import json
+from dataclasses import dataclass
+from pathlib import Path
+from typing import Any, Callable, Dict, List, Optional
+
+U64_MAX = 2 ** 64 - 1
+# "Near" the threshold: within 5% or 1 either side
+NEAR = 0.05
+
+@dataclass
+class CsvShape:
+    header: List[str]
+    column_a: List[int]
+    keys: List[str]
+    threshold: int
+
+    @property
+    def total(self) -> int:
+        return sum(self.column_a)
+
+    @property
+    def margin(self) -> int:
+        return max(1, int(self.threshold * NEAR))
+
+def shape(csv_content: str, threshold: int) -> CsvShape:
+    """Column A as integers (unparseable cells skipped) and each row's key,
+    the first non-numeric cell or the whole row if there is none"""
+    lines = [line for line in csv_content.strip().split("\n") if line.strip()]
+    header = lines[0].split(",") if lines else []
+    column_a, keys = [], []
+    for line in lines[1:]:
+        cells = [cell.strip() for cell in line.split(",")]
+        if cells[0].isdigit():
+            column_a.append(int(cells[0]))
+        keys.append(next((cell for cell in cells[1:] if not cell.isdigit()), ",".join(cells)))
+    return CsvShape(header, column_a, keys, threshold)
+
+@dataclass
+class Region:
+    name: str
+    description: str
+    hit: Callable[[CsvShape], bool]
+
+REGIONS = [
+    Region("sum_at_threshold", "column A sum exactly equals the threshold",
+           lambda s: bool(s.column_a) and s.total == s.threshold),
+    Region("sum_just_below", "sum within 5% below the threshold",
+           lambda s: bool(s.column_a) and s.threshold - s.margin <= s.total < s.threshold),
+    Region("sum_just_above", "sum within 5% above the threshold",
+           lambda s: s.threshold < s.total <= s.threshold + s.margin),
+    Region("zero_rows", "a header and no data rows",
+           lambda s: bool(s.header) and not s.keys),
+    Region("single_row", "exactly one data row",
+           lambda s: len(s.keys) == 1),
+    Region("zero_values", "at least one column A value of 0",
+           lambda s: 0 in s.column_a),
+    Region("overflow_adjacent", "values or sum in the top half of the u64 range",
+           lambda s: any(v > U64_MAX // 2 for v in s.column_a) or s.total > U64_MAX // 2),
+    Region("duplicate_keys", "two data rows sharing a key",
+           lambda s: len(set(s.keys)) < len(s.keys)),
+]
+
+def classify(csv_content: str, threshold: int) -> List[str]:
+    """Names of the regions a scenario falls in"""
+    data = shape(csv_content, threshold)
+    return [region.name for region in REGIONS if region.hit(data)]
+
+def targeted_csv(region: str, threshold: int, header: str = "value_a,value_b,description") -> str:
+    """Smallest CSV that lands in `region` for `threshold`"""
+    third = threshold // 3
+    rows = {
+        "sum_at_threshold": [third, third, threshold - 2 * third],
+        "sum_just_below": [third, third, max(0, threshold - 2 * third - 1)],
+        "sum_just_above": [third, third, threshold - 2 * third + 1],
+        "zero_rows": [],
+        "single_row": [min(threshold, 100)],
+        "zero_values": [0, min(threshold, 100)],
+        "overflow_adjacent": [U64_MAX // 2 + 1],
+    }
+    if region == "duplicate_keys":
+        return f"{header}\n10,1,same key\n20,2,same key\n"
+    if region not in rows:
+        raise KeyError(f"unknown coverage region '{region}'")
+    return header + "\n" + "".join(f"{value},1,row {i + 1}\n" for i, value in enumerate(rows[region]))
+
+class CoverageTracker:
+    """Region hits for a test session, persisted so several runs add up"""
+
+    def __init__(self, path: Optional[str] = None):
+        self.path = Path(path) if path else None
+        self.hits: Dict[str, List[str]] = {region.name: [] for region in REGIONS}
+        if self.path and self.path.exists():
+            for name, scenarios in json.loads(self.path.read_text()).items():
+                self.hits.setdefault(name, []).extend(scenarios)
+
+    def observe(self, scenario: str, csv_content: str, threshold: int) -> List[str]:
+        regions = classify(csv_content, threshold)
+        for name in regions:
+            if scenario not in self.hits[name]:
+                self.hits[name].append(scenario)
+        if self.path:
+            self.path.parent.mkdir(parents=True, exist_ok=True)
+            self.path.write_text(json.dumps(self.hits, indent=2))
+        return regions
+
+    def uncovered(self) -> List[str]:
+        return [name for name, scenarios in self.hits.items() if not scenarios]
+
+    def summary(self) -> Dict[str, Any]:
+        covered = len(self.hits) - len(self.uncovered())
+        return {
+            "covered": covered,
+            "total": len(self.hits),
+            "ratio": round(covered / len(self.hits), 4),
+            "uncovered": self.uncovered(),
+            "regions": {
+                region.name: {"description": region.description, "scenarios": self.hits[region.name]}
+                for region in REGIONS
+            }
+        }
@@ -0,0 +1,79 @@
+"""
+OpenAI API keys with failover and rotation
+Keys come from OPENAI_API_KEYS (comma-separated, in order of preference) or
+OPENAI_API_KEY, and from ZAIK_AI_KEY_FILE (one key per line) when set. The
+file is re-read whenever it changes, so keys can be rotated under a
+long-running scheduler without restarting it. A key that runs out of quota
+or is rejected is skipped until the key list changes
+"""
+import os
+from pathlib import Path
+from typing import List, Optional, Set
+
+class NoUsableKey(Exception):
+    """Every configured key is exhausted or revoked"""
+
+def is_key_failure(error: Exception) -> bool:
+    """Errors another key could fix: quota exhausted, or the key rejected"""
+    status = getattr(error, "status_code", None)
+    code = getattr(error, "code", None)
+    return status == 401 or code == "insufficient_quota" or (status == 429 and "insufficient_quota" in str(error))
+
+def mask(key: Optional[str]) -> str:
+    return f"…{key[-4:]}" if key else "none"
+
+class KeyRing:
+    def __init__(self, keys: List[str], key_file: Optional[str] = None):
+        self._static = [key for key in keys if key]
+        self.key_file = Path(key_file) if key_file else None
+        self._file_mtime: Optional[float] = None
+        self._file_keys: List[str] = []
+        self.failed: Set[str] = set()
+        self._reload()
+
+    @classmethod
+    def from_env(cls) -> "KeyRing":
+        keys = os.getenv("OPENAI_API_KEYS") or os.getenv("OPENAI_API_KEY") or ""
+        return cls([key.strip() for key in keys.split(",")], os.getenv("ZAIK_AI_KEY_FILE"))
+
+    def _reload(self):
+        if not self.key_file:
+            return
+        mtime = self.key_file.stat().st_mtime if self.key_file.exists() else None
+        if mtime == self._file_mtime:
+            return
+        self._file_mtime = mtime
+        lines = self.key_file.read_text().splitlines() if mtime is not None else []
+        self._file_keys = [line.strip() for line in lines if line.strip() and not line.startswith("#")]
+        # New keys, new chances: failures were against the old list
+        self.failed.clear()
+        print(f"🔑 Loaded {len(self._file_keys)} API key(s) from {self.key_file}")
+
+    @property
+    def keys(self) -> List[str]:
+        """File keys first, so a rotated key takes over from the environment's"""
+        return list(dict.fromkeys(self._file_keys + self._static))
+
+    def current(self) -> Optional[str]:
+        """The preferred key that has not failed; None if no key is configured"""
+        self._reload()
+        keys = self.keys
+        if not keys:
+            return None
+        for key in keys:
+            if key not in self.failed:
+                return key
+        raise NoUsableKey(f"all {len(keys)} API key(s) are out of quota or rejected")
+
+    def fail_over(self, key: str, error: Exception) -> bool:
+        """Retire `key` after `error`; whether another key is left to try"""
+        self.failed.add(key)
+        remaining = [k for k in self.keys if k not in self.failed]
+        print(f"🔑 API key {mask(key)} failed ({type(error).__name__}); "
+              f"{'switching to ' + mask(remaining[0]) if remaining else 'no keys left'}")
+        return bool(remaining)
+
+    def rotate(self, keys: List[str]):
+        """Replace the configured keys at runtime"""
+        self._static = [key for key in keys if key]
+        self.failed.clear()
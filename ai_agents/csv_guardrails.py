@@ -0,0 +1,173 @@
+"""
+Guardrails for AI-generated CSV
+Checks generator output before it reaches the zkVM: trivial problems are
+repaired and listed, anything else raises CsvRejected with a stable code
+instead of failing inside the guest
+"""
+from dataclasses import dataclass, field
+from typing import List, Optional, Sequence
+
+@dataclass
+class CheckedCsv:
+    csv: str
+    header: List[str]
+    row_count: int
+    # Human-readable list of every change made to the input
+    repairs: List[str] = field(default_factory=list)
+
+class CsvRejected(Exception):
+    """Output that cannot be repaired; `code` is stable for callers to branch on"""
+
+    def __init__(self, code: str, message: str, line: Optional[int] = None):
+        super().__init__(f"{code}: {message}" + (f" (line {line})" if line else ""))
+        self.code = code
+        self.message = message
+        self.line = line
+
+    def to_dict(self):
+        return {"code": self.code, "message": self.message, "line": self.line}
+
+@dataclass
+class ExtractedCsv:
+    payload: str
+    # The model's response exactly as received, kept for audit
+    raw: str
+    # "fenced", "lines" or "verbatim": how the payload was isolated
+    method: str
+
+def _looks_like_csv(lines: List[str]) -> int:
+    """Lines in the longest run sharing one comma count, header included"""
+    best = run = 0
+    previous = None
+    for line in lines:
+        commas = line.count(",")
+        run = run + 1 if commas and commas == previous else (1 if commas else 0)
+        previous = commas
+        best = max(best, run)
+    return best
+
+def extract_csv_payload(response: str) -> ExtractedCsv:
+    """
+    Isolate the CSV in a model response. A ```csv block wins, then the
+    fenced block that looks most like CSV; without fences, the longest run
+    of lines with a consistent field count, so explanatory sentences before,
+    after or between blocks are dropped.
+    """
+    lines = response.replace("\r\n", "\n").split("\n")
+    blocks, opened, language = [], None, ""
+    for index, line in enumerate(lines):
+        if line.strip().startswith("```"):
+            if opened is None:
+                opened, language = index, line.strip()[3:].strip().lower()
+            else:
+                blocks.append((language, lines[opened + 1:index]))
+                opened = None
+    if blocks:
+        tagged = [body for language, body in blocks if language == "csv"]
+        body = tagged[0] if tagged else max((body for _, body in blocks), key=_looks_like_csv)
+        return ExtractedCsv("\n".join(body) + "\n", response, "fenced")
+
+    # Longest run of consecutive lines with the same non-zero comma count;
+    # sentences ("Sure, here it is:") break a run even if they have commas
+    def fields(line: str) -> int:
+        return 0 if line.rstrip().endswith((":", ".", "!", "?")) else line.count(",")
+
+    best_start, best_length, start = 0, 0, 0
+    for index in range(1, len(lines) + 1):
+        if index == len(lines) or fields(lines[index]) != fields(lines[start]) or not fields(lines[start]):
+            if fields(lines[start]) and index - start > best_length:
+                best_start, best_length = start, index - start
+            start = index
+    if best_length and best_length < len([line for line in lines if line.strip()]):
+        return ExtractedCsv("\n".join(lines[best_start:best_start + best_length]) + "\n", response, "lines")
+    return ExtractedCsv(response, response, "verbatim")
+
+def validate_csv(
+    text: str,
+    expected_header: Optional[Sequence[str]] = None,
+    numeric_columns: Sequence[int] = (0,),
+    min_rows: int = 1,
+    max_rows: Optional[int] = None
+) -> CheckedCsv:
+    """
+    Repair and validate `text`. Repairs: byte-order mark, CRLF line endings,
+    code fences and prose (see `extract_csv_payload`), blank lines, and
+    whitespace around cells. Rejections: no
+    header, a header other than `expected_header`, rows with the wrong
+    number of fields, non-`u64` values in `numeric_columns`, and row counts
+    outside [min_rows, max_rows].
+    """
+    repairs: List[str] = []
+    if text.startswith("\ufeff"):
+        text = text[1:]
+        repairs.append("removed byte-order mark")
+    if "\r" in text:
+        text = text.replace("\r\n", "\n").replace("\r", "\n")
+        repairs.append("normalized line endings")
+
+    extracted = extract_csv_payload(text)
+    if extracted.method != "verbatim":
+        repairs.append("removed markdown code fence" if extracted.method == "fenced" else "removed prose around the data")
+        text = extracted.payload
+    trailing_newline = text.endswith("\n")
+    lines = text.split("\n")
+    if trailing_newline and lines and not lines[-1]:
+        lines = lines[:-1]
+    if any(not line.strip() for line in lines):
+        repairs.append("removed blank lines")
+        lines = [line for line in lines if line.strip()]
+    if not lines:
+        raise CsvRejected("empty", "no CSV content")
+
+    # Prose has no field separators; the header is the first line that does
+    # and a truncated row is still numeric
+    if expected_header is not None:
+        wanted = ",".join(expected_header)
+        start = next((i for i, line in enumerate(lines) if line.replace(" ", "") == wanted.replace(" ", "")), None)
+        if start is None:
+            raise CsvRejected("header_mismatch", f"expected header '{wanted}'")
+    else:
+        start = next((i for i, line in enumerate(lines) if "," in line), None)
+        if start is None:
+            raise CsvRejected("missing_header", "no line looks like a CSV header")
+    if start:
+        repairs.append(f"removed {start} line(s) of prose before the header")
+    lines = lines[start:]
+
+    width = len(lines[0].split(","))
+    end = len(lines)
+    while end > 1 and width > 1 and "," not in lines[end - 1] and not lines[end - 1].strip().isdigit():
+        end -= 1
+    if end < len(lines):
+        repairs.append(f"removed {len(lines) - end} line(s) of prose after the data")
+    lines = lines[:end]
+
+    rows = [[cell.strip() for cell in line.split(",")] for line in lines]
+    if any(cell != raw for row, line in zip(rows, lines) for cell, raw in zip(row, line.split(","))):
+        repairs.append("trimmed whitespace around cells")
+
+    header = rows[0]
+    if any(not name for name in header):
+        raise CsvRejected("missing_header", "header has an empty column name", line=1)
+    for number, row in enumerate(rows[1:], start=2):
+        if len(row) != width:
+            raise CsvRejected("ragged_row", f"expected {width} fields, found {len(row)}", line=number)
+        for column in numeric_columns:
+            if column < width and not (row[column].isascii() and row[column].isdigit()
+                                       and int(row[column]) < 2 ** 64):
+                raise CsvRejected(
+                    "non_numeric", f"column {header[column]} value '{row[column]}' is not a u64", line=number
+                )
+
+    row_count = len(rows) - 1
+    if row_count < min_rows:
+        raise CsvRejected("too_few_rows", f"{row_count} data row(s), need at least {min_rows}")
+    if max_rows is not None and row_count > max_rows:
+        raise CsvRejected("too_many_rows", f"{row_count} data row(s), at most {max_rows} allowed")
+
+    return CheckedCsv(
+        csv="\n".join(",".join(row) for row in rows) + ("\n" if trailing_newline else ""),
+        header=header,
+        row_count=row_count,
+        repairs=repairs
+    )
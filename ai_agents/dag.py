@@ -0,0 +1,140 @@
+"""
+Declarative attestation workflows
+A YAML file lists nodes and their dependencies; independent branches run in
+parallel, so multi-dataset attestations need a file rather than a new script
+
+    name: nightly
+    nodes:
+      prove_sales:   {type: prove, csv: exports/sales.csv, threshold: 1000}
+      prove_refunds: {type: prove, csv: exports/refunds.csv, threshold: 500}
+      gate:          {type: verify, depends_on: [prove_sales, prove_refunds]}
+      report:        {type: notify, depends_on: [gate], output: reports/nightly.json}
+"""
+import asyncio
+import json
+from dataclasses import dataclass, field
+from pathlib import Path
+from typing import Any, Dict, List, Optional
+
+import yaml
+
+from . import chaos
+from .risc0_verifier import RISC0Verifier
+
+NODE_TYPES = ("prove", "verify", "notify")
+
+@dataclass
+class Node:
+    name: str
+    type: str
+    depends_on: List[str] = field(default_factory=list)
+    params: Dict[str, Any] = field(default_factory=dict)
+
+@dataclass
+class NodeResult:
+    status: str  # "passed", "failed" or "skipped"
+    output: Dict[str, Any] = field(default_factory=dict)
+
+class WorkflowDefinitionError(Exception):
+    pass
+
+class WorkflowDag:
+    def __init__(self, name: str, nodes: Dict[str, Node]):
+        self.name = name
+        self.nodes = nodes
+        self._validate()
+
+    @classmethod
+    def load(cls, path: str) -> "WorkflowDag":
+        spec = yaml.safe_load(Path(path).read_text())
+        nodes = {}
+        for name, node_spec in (spec.get("nodes") or {}).items():
+            node_spec = dict(node_spec)
+            nodes[name] = Node(
+                name=name,
+                type=node_spec.pop("type", None),
+                depends_on=node_spec.pop("depends_on", []),
+                params=node_spec
+            )
+        return cls(spec.get("name", Path(path).stem), nodes)
+
+    def _validate(self):
+        for node in self.nodes.values():
+            if node.type not in NODE_TYPES:
+                raise WorkflowDefinitionError(
+                    f"node '{node.name}' has unsupported type '{node.type}' (supported: {', '.join(NODE_TYPES)})"
+                )
+            for dep in node.depends_on:
+                if dep not in self.nodes:
+                    raise WorkflowDefinitionError(f"node '{node.name}' depends on unknown node '{dep}'")
+
+        # Depth-first search for cycles
+        visiting, done = set(), set()
+        def visit(name: str, path: List[str]):
+            if name in done:
+                return
+            if name in visiting:
+                raise WorkflowDefinitionError(f"dependency cycle: {' -> '.join(path + [name])}")
+            visiting.add(name)
+            for dep in self.nodes[name].depends_on:
+                visit(dep, path + [name])
+            visiting.discard(name)
+            done.add(name)
+        for name in self.nodes:
+            visit(name, [])
+
+class DagEngine:
+    """Runs each node as soon as its dependencies have passed"""
+
+    def __init__(self, verifier: Optional[RISC0Verifier] = None):
+        self.verifier = verifier or RISC0Verifier()
+
+    async def run(self, dag: WorkflowDag) -> Dict[str, NodeResult]:
+        print(f"🕸️  Running workflow: {dag.name} ({len(dag.nodes)} nodes)")
+        tasks: Dict[str, asyncio.Task] = {}
+
+        async def run_node(node: Node) -> NodeResult:
+            deps = {dep: await tasks[dep] for dep in node.depends_on}
+            if any(result.status != "passed" for result in deps.values()):
+                print(f"   ⏭️  {node.name}: skipped (dependency did not pass)")
+                return NodeResult("skipped")
+            try:
+                result = await getattr(self, f"_run_{node.type}")(node, deps)
+            except Exception as e:
+                result = NodeResult("failed", {"error": str(e)})
+            print(f"   {'✅' if result.status == 'passed' else '❌'} {node.name} ({node.type}): {result.status}")
+            return result
+
+        for node in dag.nodes.values():
+            tasks[node.name] = asyncio.ensure_future(run_node(node))
+        results = await asyncio.gather(*tasks.values())
+        return dict(zip(tasks.keys(), results))
+
+    async def _run_prove(self, node: Node, deps: Dict[str, NodeResult]) -> NodeResult:
+        csv_content = Path(node.params["csv"]).read_text()
+        threshold = int(node.params.get("threshold", 1000))
+        report = await asyncio.to_thread(self.verifier.verify_csv_data, csv_content, None, threshold)
+        return NodeResult("passed" if report["verification_successful"] else "failed", report)
+
+    async def _run_verify(self, node: Node, deps: Dict[str, NodeResult]) -> NodeResult:
+        # Gate: every upstream proof must be backed by a real receipt unless
+        # the node opts into dev-mode receipts
+        allowed = {"real"} | ({"dev-mode"} if node.params.get("allow_dev_mode") else set())
+        kinds = {
+            dep: result.output.get("proof_details", {}).get("proof_kind")
+            for dep, result in deps.items()
+        }
+        passed = all(kind in allowed for kind in kinds.values())
+        return NodeResult("passed" if passed else "failed", {"proof_kinds": kinds})
+
+    async def _run_notify(self, node: Node, deps: Dict[str, NodeResult]) -> NodeResult:
+        summary = {dep: {"status": result.status, **result.output} for dep, result in deps.items()}
+        output = node.params.get("output")
+        if chaos.current().drop_delivery(output or "stdout"):
+            return NodeResult("failed", {"error": "notification was not delivered"})
+        if output:
+            Path(output).parent.mkdir(parents=True, exist_ok=True)
+            Path(output).write_text(json.dumps(summary, indent=2, default=str))
+        else:
+            print(json.dumps(summary, indent=2, default=str))
+        return NodeResult("passed", {"notified": list(deps)})
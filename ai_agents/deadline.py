@@ -0,0 +1,65 @@
+"""
+End-to-end time budget for a workflow run
+A Deadline is created once per request and handed to every stage: AI calls
+get at most the remaining time as their timeout, the host prover gets it as
+`--deadline`, and the pipeline stops (keeping completed stages) once it is
+spent. The budget can come from ZAIK_DEADLINE, e.g. `30m`, `90s` or `2h`
+"""
+import os
+import re
+import time
+from typing import Optional
+
+# Exit code the host uses for a passed deadline
+EXIT_DEADLINE = 6
+
+class DeadlineExceeded(Exception):
+    def __init__(self, stage: str, budget: float, elapsed: float):
+        super().__init__(f"deadline of {budget:.1f}s exceeded during {stage} after {elapsed:.1f}s")
+        self.stage = stage
+        self.budget = budget
+        self.elapsed = elapsed
+
+    def to_failure(self):
+        """The host's failure JSON for the same outcome"""
+        return {"outcome": "deadline-exceeded", "exit_code": EXIT_DEADLINE,
+                "reason": "deadline_exceeded", "message": str(self)}
+
+def parse_duration(value: str) -> float:
+    """Seconds in `90`, `90s`, `500ms`, `30m` or `2h`"""
+    match = re.fullmatch(r"\s*([0-9.]+)\s*(ms|s|m|h)?\s*", value)
+    if not match:
+        raise ValueError(f"invalid duration '{value}': expected e.g. 90s, 30m or 2h")
+    number, unit = float(match.group(1)), match.group(2) or "s"
+    return number * {"ms": 0.001, "s": 1, "m": 60, "h": 3600}[unit]
+
+class Deadline:
+    def __init__(self, budget_seconds: float):
+        self.budget = budget_seconds
+        self.started = time.monotonic()
+
+    @classmethod
+    def from_env(cls) -> Optional["Deadline"]:
+        value = os.getenv("ZAIK_DEADLINE")
+        return cls(parse_duration(value)) if value else None
+
+    def elapsed(self) -> float:
+        return time.monotonic() - self.started
+
+    def remaining(self) -> float:
+        return max(0.0, self.budget - self.elapsed())
+
+    def expired(self) -> bool:
+        return self.remaining() == 0.0
+
+    def check(self, stage: str):
+        """Raise DeadlineExceeded if the budget is spent before `stage`"""
+        if self.expired():
+            raise self.exceeded(stage)
+
+    def exceeded(self, stage: str) -> DeadlineExceeded:
+        return DeadlineExceeded(stage, self.budget, self.elapsed())
+
+    def timeout(self, default: Optional[float] = None) -> float:
+        """A per-call timeout that cannot outlive the deadline"""
+        return min(default, self.remaining()) if default else self.remaining()
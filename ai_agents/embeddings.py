@@ -0,0 +1,103 @@
+"""
+Local embeddings for spotting near-duplicate test scenarios
+Text is embedded without any API call: hashed word and character-trigram
+counts, L2-normalized, so cosine similarity is a dot product. That is
+crude next to a learned model but deterministic, free, and good enough to
+notice that a proposed scenario is the same CSV and threshold run again
+"""
+import hashlib
+import json
+import math
+import random
+from dataclasses import dataclass
+from pathlib import Path
+from typing import Any, Dict, List, Optional, Tuple
+
+DIMENSIONS = 512
+
+def embed(text: str, dimensions: int = DIMENSIONS) -> List[float]:
+    vector = [0.0] * dimensions
+    normalized = " ".join(text.lower().split())
+    features = normalized.split(" ") + [normalized[i:i + 3] for i in range(max(0, len(normalized) - 2))]
+    for feature in features:
+        digest = hashlib.blake2b(feature.encode(), digest_size=8).digest()
+        bucket = int.from_bytes(digest[:4], "little") % dimensions
+        # The sign bit spreads collisions around zero instead of piling them up
+        vector[bucket] += 1.0 if digest[4] & 1 else -1.0
+    norm = math.sqrt(sum(value * value for value in vector))
+    return [value / norm for value in vector] if norm else vector
+
+def cosine(a: List[float], b: List[float]) -> float:
+    return sum(x * y for x, y in zip(a, b))
+
+def scenario_text(csv_content: str, threshold: int) -> str:
+    """What makes two CSV scenarios the same test: the data and the bound"""
+    return f"threshold {threshold}\n{csv_content.strip()}"
+
+@dataclass
+class Match:
+    name: str
+    similarity: float
+
+class ScenarioMemory:
+    """Embeddings of every scenario already run, one JSON object per line"""
+
+    def __init__(self, path: str = ".zaik/scenario_memory.jsonl"):
+        self.path = Path(path)
+        self.entries: List[Dict[str, Any]] = []
+        if self.path.exists():
+            self.entries = [json.loads(line) for line in self.path.read_text().splitlines() if line.strip()]
+
+    def nearest(self, text: str) -> Optional[Match]:
+        if not self.entries:
+            return None
+        vector = embed(text)
+        best = max(self.entries, key=lambda entry: cosine(vector, entry["embedding"]))
+        return Match(best["name"], round(cosine(vector, best["embedding"]), 4))
+
+    def remember(self, name: str, text: str):
+        entry = {"name": name, "embedding": embed(text)}
+        self.entries.append(entry)
+        self.path.parent.mkdir(parents=True, exist_ok=True)
+        with self.path.open("a") as memory:
+            memory.write(json.dumps(entry) + "\n")
+
+def mutate_csv(csv_content: str, rng: random.Random) -> str:
+    """Same header and shape, different data: column A values are rescaled
+    and the rows reordered, with one extra row"""
+    lines = csv_content.strip().split("\n")
+    header, rows = lines[0], [line.split(",") for line in lines[1:]]
+    factor = rng.uniform(0.5, 1.5)
+    for row in rows:
+        if row and row[0].strip().isdigit():
+            row[0] = str(max(0, round(int(row[0]) * factor)))
+    rng.shuffle(rows)
+    if rows:
+        extra = list(rng.choice(rows))
+        extra[0] = str(rng.randint(1, 500))
+        rows.append(extra)
+    return "\n".join([header] + [",".join(row) for row in rows]) + "\n"
+
+def find_novel(
+    memory: ScenarioMemory,
+    csv_content: str,
+    threshold: int,
+    similarity: float,
+    mutate: bool,
+    attempts: int = 3,
+    seed: Optional[int] = None
+) -> Tuple[Optional[str], Optional[Match]]:
+    """`csv_content` if no remembered scenario is at least `similarity` alike,
+    else a mutation that is (when `mutate`), else None. Also returns the
+    closest match to the original"""
+    match = memory.nearest(scenario_text(csv_content, threshold))
+    if match is None or match.similarity < similarity:
+        return csv_content, match
+    if mutate:
+        rng = random.Random(seed)
+        for _ in range(attempts):
+            candidate = mutate_csv(csv_content, rng)
+            nearest = memory.nearest(scenario_text(candidate, threshold))
+            if nearest is None or nearest.similarity < similarity:
+                return candidate, match
+    return None, match
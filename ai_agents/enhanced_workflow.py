@@ -7,6 +7,7 @@ from typing import Dict, List, Any, Optional
 from datetime import datetime
 
 from .agent_workflow import AgentWorkflow
+from .confidence import ESCALATE
 from .specialized_agents import (
     DataQualityAgent, 
     SecurityAgent, 
@@ -17,8 +18,8 @@ from .specialized_agents import (
 class EnhancedAgentWorkflow(AgentWorkflow):
     """Enhanced workflow with specialized agents"""
     
-    def __init__(self):
-        super().__init__()
+    def __init__(self, **kwargs):
+        super().__init__(**kwargs)
         self.data_quality_agent = DataQualityAgent()
         self.security_agent = SecurityAgent()
         self.business_logic_agent = BusinessLogicAgent()
@@ -115,7 +116,14 @@ class EnhancedAgentWorkflow(AgentWorkflow):
             results["final_decision"] = final_decision
             
             # Compile enhanced final report
-            return self._compile_enhanced_report(workflow_id, results)
+            report = self._compile_enhanced_report(workflow_id, results)
+            if final_decision["decision"] == ESCALATE:
+                gate = final_decision["confidence_gate"]
+                queued = self.review_queue.enqueue(workflow_id, gate, {
+                    "decision_factors": final_decision["decision_factors"]
+                })
+                report["review"] = {"queue": str(self.review_queue.path), "newly_queued": queued, **gate}
+            return report
             
         except Exception as e:
             error_result = {"error": str(e), "step_failed": "enhanced_workflow"}
@@ -155,11 +163,13 @@ class EnhancedAgentWorkflow(AgentWorkflow):
         """
         
         from .openai_client import AgentMessage, AgentPrompts
+        from .agent_models import AgentType
         messages = [AgentMessage(role="user", content=decision_prompt)]
         response = await self.openai_client.chat_completion(
             messages=messages,
             system_prompt=AgentPrompts.ORCHESTRATOR,
-            temperature=0.1
+            temperature=0.1,
+            agent=AgentType.ENHANCED_ORCHESTRATOR
         )
         
         # Enhanced decision logic
@@ -183,15 +193,19 @@ class EnhancedAgentWorkflow(AgentWorkflow):
         risk_acceptable = decision_factors["risk_manageable"] and decision_factors["security_adequate"]
         
         final_accept = critical_pass and risk_acceptable
+        gate = self.thresholds.gate("accept" if final_accept else "reject", response.confidence)
         
-        print(f"   🎯 Enhanced Decision: {'ACCEPT' if final_accept else 'REJECT'}")
+        print(f"   🎯 Enhanced Decision: {gate['decision'].upper()}")
         print(f"   📊 Decision Confidence: {response.confidence}")
+        if gate["decision"] == ESCALATE:
+            print(f"   🙋 Escalated for review: {gate['reason']}")
         print(f"   ⚖️  Critical Factors Pass: {critical_pass}")
         print(f"   🛡️  Risk Acceptable: {risk_acceptable}")
         
         return {
             "agent": "enhanced_orchestrator",
-            "decision": "accept" if final_accept else "reject",
+            "decision": gate["decision"],
+            "confidence_gate": gate,
             "reasoning": response.content,
             "overall_confidence": response.confidence,
             "decision_factors": decision_factors,
@@ -0,0 +1,82 @@
+"""
+Ensemble verification across several models
+Every member answers the same prompt; their recommended actions are combined
+by a confidence-weighted majority vote and each individual verdict is kept
+with the result. Members are set with ZAIK_ENSEMBLE, e.g.
+
+    ZAIK_ENSEMBLE="gpt-4o,gpt-4-turbo-preview,gpt-4o-mini"
+"""
+import asyncio
+import os
+from dataclasses import asdict, dataclass
+from typing import Any, Dict, List, Optional
+
+from .openai_client import OpenAIClient
+from .agent_models import AgentType
+from .evaluation import ACTIONS, predicted_action
+from . import prompt_templates
+
+@dataclass
+class Verdict:
+    model: str
+    # None when the member failed or named neither action
+    action: Optional[str]
+    confidence: Optional[float]
+    content: str
+    prompt_hash: Optional[str] = None
+    template: Optional[str] = None
+    error: Optional[str] = None
+
+    @property
+    def weight(self) -> float:
+        """A vote counts for its confidence; a missing confidence counts as 0"""
+        return float(self.confidence) if isinstance(self.confidence, (int, float)) else 0.0
+
+def combine(verdicts: List[Verdict]) -> Dict[str, Any]:
+    """Confidence-weighted vote. Ties and an empty vote resolve to reject.
+    The combined confidence is the winner's weight over all members, so
+    abstaining or failed members pull it down"""
+    weights = {action: sum(v.weight for v in verdicts if v.action == action) for action in ACTIONS}
+    action = "accept" if weights["accept"] > weights["reject"] else "reject"
+    return {
+        "action": action,
+        "confidence": round(weights[action] / len(verdicts), 4) if verdicts else 0.0,
+        "weights": {a: round(w, 4) for a, w in weights.items()},
+        "votes": {a: sum(1 for v in verdicts if v.action == a) for a in ACTIONS},
+        "abstained": sum(1 for v in verdicts if v.action is None),
+        "unanimous": bool(verdicts) and all(v.action == action for v in verdicts),
+        "verdicts": [asdict(v) for v in verdicts]
+    }
+
+class EnsembleVerifier:
+    def __init__(self, models: List[str]):
+        if not models:
+            raise ValueError("an ensemble needs at least one model")
+        # Each member keeps its model but samples as the verification agent
+        self.clients = [OpenAIClient(model=model, agent=AgentType.VERIFICATION_AGENT) for model in models]
+
+    @classmethod
+    def from_env(cls) -> Optional["EnsembleVerifier"]:
+        models = [m.strip() for m in os.getenv("ZAIK_ENSEMBLE", "").split(",") if m.strip()]
+        return cls(models) if models else None
+
+    async def _ask(self, client: OpenAIClient, template: prompt_templates.PromptTemplate, params) -> Verdict:
+        try:
+            response = await client.complete_template(template, **params)
+        except Exception as e:
+            return Verdict(client.model, None, None, "", template=template.id, error=str(e))
+        return Verdict(
+            model=client.model,
+            action=predicted_action(response),
+            confidence=response.confidence,
+            content=response.content,
+            prompt_hash=response.prompt_hash,
+            template=response.template
+        )
+
+    async def review(self, template: prompt_templates.PromptTemplate, **params) -> Dict[str, Any]:
+        """Ask every member concurrently and combine their verdicts"""
+        verdicts = await asyncio.gather(*(self._ask(client, template, params) for client in self.clients))
+        for verdict in verdicts:
+            print(f"   🗳️  {verdict.model}: {verdict.action or 'no decision'} ({verdict.confidence})")
+        return combine(list(verdicts))
@@ -0,0 +1,150 @@
+"""
+Evaluation harness for the AI verification agent
+Runs the verification review prompt over labeled verification results and
+scores the action the model recommends against the label, so a prompt or
+model change can be compared with the last run before it ships
+"""
+import asyncio
+import json
+import re
+from dataclasses import asdict, dataclass
+from pathlib import Path
+from typing import Any, Dict, List, Optional
+
+from .openai_client import OpenAIClient, AgentResponse
+from .agent_models import AgentType
+from . import prompt_templates
+
+ACTIONS = ("accept", "reject")
+
+@dataclass
+class LabeledCase:
+    name: str
+    # Whether the receipt verified against the expected image
+    proof_valid: bool
+    # Whether the proven result satisfies the business invariant
+    invariant_holds: bool
+    expected: str
+    proof_kind: str = "real"
+    column_a_sum: int = 1500
+    analysis: str = "Two columns; column A holds unsigned integers with no missing values."
+    analysis_confidence: float = 0.9
+
+    @property
+    def cell(self) -> str:
+        """Which quadrant of crypto pass/fail x invariant pass/fail the case is in"""
+        return (f"crypto_{'pass' if self.proof_valid else 'fail'}"
+                f"/invariant_{'pass' if self.invariant_holds else 'fail'}")
+
+    @classmethod
+    def from_dict(cls, data: Dict[str, Any]) -> "LabeledCase":
+        case = cls(**data)
+        if case.expected not in ACTIONS:
+            raise ValueError(f"case '{case.name}': expected must be one of {', '.join(ACTIONS)}")
+        return case
+
+def default_corpus() -> List[LabeledCase]:
+    """Every quadrant, each with a dev-mode and an edge-of-threshold variant.
+    Only a valid real proof of a satisfied invariant should be accepted"""
+    cases = []
+    for proof_valid in (True, False):
+        for invariant_holds in (True, False):
+            expected = "accept" if proof_valid and invariant_holds else "reject"
+            quadrant = f"{'valid' if proof_valid else 'invalid'}_proof_{'holds' if invariant_holds else 'violated'}"
+            cases.append(LabeledCase(quadrant, proof_valid, invariant_holds, expected))
+            cases.append(LabeledCase(
+                f"{quadrant}_at_threshold", proof_valid, invariant_holds, expected,
+                column_a_sum=1001 if invariant_holds else 1000
+            ))
+            # A dev-mode receipt carries no seal, so it never earns an accept
+            cases.append(LabeledCase(
+                f"{quadrant}_dev_mode", proof_valid, invariant_holds, "reject", proof_kind="dev-mode"
+            ))
+    return cases
+
+def load_corpus(path: str) -> List[LabeledCase]:
+    """A JSON list of cases using the `LabeledCase` field names"""
+    return [LabeledCase.from_dict(entry) for entry in json.loads(Path(path).read_text())]
+
+def predicted_action(response: AgentResponse) -> Optional[str]:
+    """The action the model recommends, or None when it names neither or both"""
+    text = " ".join([response.content, response.reasoning or "", " ".join(response.next_actions or [])]).lower()
+    named = [action for action in ACTIONS if re.search(rf"\b{action}", text)]
+    return named[0] if len(named) == 1 else None
+
+def score(results: List[Dict[str, Any]]) -> Dict[str, Any]:
+    """Accuracy overall and per quadrant, precision and recall per action.
+    An unparseable answer counts against recall and never as a hit"""
+    total = len(results)
+    correct = sum(1 for result in results if result["predicted"] == result["expected"])
+    per_action = {}
+    for action in ACTIONS:
+        predicted = [result for result in results if result["predicted"] == action]
+        labeled = [result for result in results if result["expected"] == action]
+        hits = sum(1 for result in predicted if result["expected"] == action)
+        per_action[action] = {
+            "precision": round(hits / len(predicted), 4) if predicted else None,
+            "recall": round(hits / len(labeled), 4) if labeled else None,
+            "support": len(labeled)
+        }
+    per_cell = {}
+    for result in results:
+        cell = per_cell.setdefault(result["cell"], {"cases": 0, "correct": 0})
+        cell["cases"] += 1
+        cell["correct"] += result["predicted"] == result["expected"]
+    return {
+        "cases": total,
+        "accuracy": round(correct / total, 4) if total else None,
+        "unparseable": sum(1 for result in results if result["predicted"] is None),
+        "per_action": per_action,
+        "per_cell": per_cell
+    }
+
+async def evaluate(
+    corpus: List[LabeledCase],
+    client: Optional[OpenAIClient] = None,
+    template: prompt_templates.PromptTemplate = prompt_templates.VERIFICATION_REVIEW,
+    max_concurrency: int = 4
+) -> Dict[str, Any]:
+    """Run `template` over every case and score the recommended actions"""
+    client = client or OpenAIClient(agent=AgentType.VERIFICATION_AGENT)
+    semaphore = asyncio.Semaphore(max(1, max_concurrency))
+
+    async def run(case: LabeledCase) -> Dict[str, Any]:
+        async with semaphore:
+            try:
+                response = await client.complete_template(
+                    template,
+                    analysis=case.analysis,
+                    analysis_confidence=case.analysis_confidence,
+                    verification_successful=case.proof_valid and case.invariant_holds,
+                    proof_valid=case.proof_valid,
+                    proof_kind=case.proof_kind,
+                    business_logic_satisfied=case.invariant_holds,
+                    column_a_sum=case.column_a_sum,
+                    cryptographic_guarantees=case.proof_valid and case.proof_kind == "real"
+                )
+                predicted, error = predicted_action(response), None
+            except Exception as e:
+                predicted, error = None, str(e)
+        mark = "✅" if predicted == case.expected else "❌"
+        print(f"   {mark} {case.name}: expected {case.expected}, got {predicted or 'no decision'}")
+        return {**asdict(case), "cell": case.cell, "predicted": predicted, "error": error}
+
+    results = await asyncio.gather(*(run(case) for case in corpus))
+    return {
+        "model": client.model,
+        "model_params": asdict(client.params_for()),
+        "template": template.id,
+        "template_digest": template.digest,
+        "metrics": score(list(results)),
+        "results": list(results)
+    }
+
+def regressions(report: Dict[str, Any], baseline: Dict[str, Any]) -> List[str]:
+    """Cases the baseline run got right and this run gets wrong"""
+    passed = {result["name"] for result in baseline["results"] if result["predicted"] == result["expected"]}
+    return [
+        result["name"] for result in report["results"]
+        if result["name"] in passed and result["predicted"] != result["expected"]
+    ]
@@ -0,0 +1,157 @@
+"""
+Decoder for the zaik journal byte layout, without risc0 serde
+The guest commits an 8-byte header (b"ZAIK", then the format as u32 LE)
+followed by every field in order, little-endian and unpadded. The layout is
+documented in the README (`host schema`) and pinned by the golden vector in
+core/golden/journal-v7.json
+"""
+import struct
+from typing import Any, Dict, List, Tuple
+
+JOURNAL_MAGIC = b"ZAIK"
+JOURNAL_FORMAT = 7
+
+COLUMN_HASH_MODES = ["joined", "transcript", "raw", "chunked"]
+COMPARISONS = ["lt", "le", "ge", "gt"]
+AGGREGATES = ["sum", "count", "min", "max", "duplicates", "decreases"]
+
+class JournalDecodeError(ValueError):
+    pass
+
+class _Reader:
+    def __init__(self, data: bytes):
+        self.data = data
+        self.offset = 0
+
+    def take(self, length: int) -> bytes:
+        if self.offset + length > len(self.data):
+            raise JournalDecodeError(f"journal truncated at byte {self.offset}")
+        chunk = self.data[self.offset:self.offset + length]
+        self.offset += length
+        return chunk
+
+    def u8(self) -> int:
+        return self.take(1)[0]
+
+    def u32(self) -> int:
+        return struct.unpack("<I", self.take(4))[0]
+
+    def u64(self) -> int:
+        return struct.unpack("<Q", self.take(8))[0]
+
+    def digest(self) -> str:
+        return self.take(32).hex()
+
+    def flag(self) -> bool:
+        value = self.u8()
+        if value > 1:
+            raise JournalDecodeError(f"flag byte {value} is neither 0 nor 1")
+        return value == 1
+
+    def label(self, labels: List[str]) -> str:
+        value = self.u8()
+        if value >= len(labels):
+            raise JournalDecodeError(f"tag {value} is out of range")
+        return labels[value]
+
+    def image_id(self) -> List[int]:
+        return [self.u32() for _ in range(8)]
+
+    def text(self) -> str:
+        try:
+            return self.take(self.u32()).decode("utf-8")
+        except UnicodeDecodeError as e:
+            raise JournalDecodeError(f"inline CSV is not UTF-8: {e}")
+
+    def dialect(self) -> Dict[str, Any]:
+        delimiter, quote = self.u8(), self.u8()
+        has_header = self.flag()
+        for name, byte in (("delimiter", delimiter), ("quote", quote)):
+            if byte >= 0x80 or byte in (0x0A, 0x0D):
+                raise JournalDecodeError(f"dialect {name} byte {byte} is not ASCII other than a line break")
+        if delimiter == quote:
+            raise JournalDecodeError("dialect delimiter and quote are the same byte")
+        return {"delimiter": chr(delimiter), "quote": chr(quote), "has_header": has_header}
+
+    def invariant(self) -> Dict[str, Any]:
+        return {
+            "column": self.u64(),
+            "aggregate": self.label(AGGREGATES),
+            "op": self.label(COMPARISONS),
+            "bound": self.u64(),
+            "value": self.u64(),
+            "commitment": self.digest(),
+        }
+
+    def invariants(self) -> List[Dict[str, Any]]:
+        count = self.u32()
+        if count > len(self.data) - self.offset:
+            raise JournalDecodeError(f"invariant count {count} exceeds the journal")
+        return [self.invariant() for _ in range(count)]
+
+    def cell_counts(self) -> List[Dict[str, int]]:
+        count = self.u32()
+        if count > len(self.data) - self.offset:
+            raise JournalDecodeError(f"cell count entries {count} exceed the journal")
+        return [{"empty": self.u64(), "unparseable": self.u64()} for _ in range(count)]
+
+# Field order and reader, as committed by the guest
+FIELDS: List[Tuple[str, str]] = [
+    ("csv_hash", "digest"),
+    ("entry_count", "u64"),
+    ("sum_disclosed", "flag"),
+    ("column_a_sum", "u64"),
+    ("column_hash_mode", "column_hash_mode"),
+    ("sum_comparison", "comparison"),
+    ("sum_threshold", "u64"),
+    ("sum_within_bound", "flag"),
+    ("invariants_passed", "u32"),
+    ("invariants", "invariants"),
+    ("chunk_image_id", "image_id"),
+    ("inline_limit", "u64"),
+    ("inline_csv", "text"),
+    ("aux_tables_hash", "digest"),
+    ("max_rows", "u64"),
+    ("truncated", "flag"),
+    ("dialect", "dialect"),
+    ("dataset_files", "u32"),
+    ("semantics_version", "u32"),
+    ("data_rows", "u64"),
+    ("cell_counts", "cell_counts"),
+    ("duplicate_headers", "flag"),
+    ("ragged_rows", "u64"),
+    ("sum_commitment", "digest"),
+    ("column_a_hash", "digest"),
+    ("row_merkle_root", "digest"),
+]
+
+def decode(data: bytes) -> Dict[str, Any]:
+    """Decode journal bytes into a dict keyed by field name
+
+    Digests are hex strings, enums their lowercase names and the chunk image
+    ID its eight u32 words. Raises JournalDecodeError unless `data` is
+    exactly one journal of the supported format
+    """
+    reader = _Reader(data)
+    if reader.take(4) != JOURNAL_MAGIC:
+        raise JournalDecodeError("not a zaik journal (bad magic)")
+    fmt = reader.u32()
+    if fmt != JOURNAL_FORMAT:
+        raise JournalDecodeError(f"journal format {fmt} is not supported (expected {JOURNAL_FORMAT})")
+    readers = {
+        "digest": reader.digest,
+        "u64": reader.u64,
+        "u32": reader.u32,
+        "flag": reader.flag,
+        "column_hash_mode": lambda: reader.label(COLUMN_HASH_MODES),
+        "comparison": lambda: reader.label(COMPARISONS),
+        "invariants": reader.invariants,
+        "image_id": reader.image_id,
+        "text": reader.text,
+        "dialect": reader.dialect,
+        "cell_counts": reader.cell_counts,
+    }
+    journal = {name: readers[kind]() for name, kind in FIELDS}
+    if reader.offset != len(data):
+        raise JournalDecodeError(f"{len(data) - reader.offset} trailing byte(s) after the journal")
+    return journal
@@ -4,11 +4,23 @@ OpenAI API client for AI agent workflows
 import os
 import asyncio
 from typing import Dict, List, Optional, Any
+import httpx
 from dotenv import load_dotenv
 from openai import AsyncOpenAI
 from pydantic import BaseModel
 import json
 
+from . import cassette
+from . import chaos
+from .credentials import KeyRing, is_key_failure
+from .agent_models import (
+    DEFAULT_MAX_TOKENS, DEFAULT_MODEL, DEFAULT_TEMPERATURE, AgentModels, AgentType, ModelParams
+)
+from .canonical import canonical_hash
+from . import prompt_templates
+from .prompt_templates import PromptTemplate
+from .prompt_budget import DEFAULT_PROMPT_BUDGET, fit_params
+
 load_dotenv()
 
 class AgentMessage(BaseModel):
@@ -21,20 +33,137 @@ class AgentResponse(BaseModel):
     reasoning: Optional[str] = None
     confidence: Optional[float] = None
     next_actions: Optional[List[str]] = None
+    # Exactly what the model was sent, and its canonical JSON hash
+    prompt: Optional[Dict[str, Any]] = None
+    prompt_hash: Optional[str] = None
+    # name@vN of the prompt template, when one was used
+    template: Optional[str] = None
+    # Template parameters condensed to fit the prompt budget
+    condensed: Optional[List[str]] = None
+
+def prompt_record(
+    model: str,
+    messages: List[Dict[str, str]],
+    temperature: float,
+    max_tokens: int,
+    template: Optional[str] = None,
+    top_p: Optional[float] = None
+) -> Dict[str, Any]:
+    """The full request behind one completion: messages and model parameters"""
+    record = {"model": model, "messages": messages, "temperature": temperature, "max_tokens": max_tokens}
+    if top_p is not None:
+        record["top_p"] = top_p
+    if template:
+        record["template"] = template
+    return record
+
+def verify_prompt(prompt: Dict[str, Any], prompt_hash: str) -> bool:
+    """Whether a stored prompt is the one its recorded hash commits to"""
+    return canonical_hash(prompt) == prompt_hash
 
 class OpenAIClient:
-    def __init__(self, model: str = "gpt-4-turbo-preview"):
-        self.client = AsyncOpenAI(api_key=os.getenv("OPENAI_API_KEY"))
-        self.model = model
+    def __init__(
+        self,
+        model: Optional[str] = None,
+        base_url: Optional[str] = None,
+        proxy: Optional[str] = None,
+        timeout: Optional[float] = None,
+        organization: Optional[str] = None,
+        project: Optional[str] = None,
+        keys: Optional[KeyRing] = None,
+        agent: Optional[AgentType] = None,
+        agent_models: Optional[AgentModels] = None,
+        prompt_budget: Optional[int] = None
+    ):
+        """
+        `base_url` points at any OpenAI-compatible gateway (default
+        OPENAI_BASE_URL); `proxy` is an http(s) proxy URL (default
+        ZAIK_AI_PROXY, otherwise the standard HTTPS_PROXY/NO_PROXY variables
+        apply); `timeout` is the default per-request limit in seconds
+        (default ZAIK_AI_TIMEOUT, else 60). `organization` and `project` are
+        sent as OpenAI-Organization/OpenAI-Project headers (default
+        OPENAI_ORG_ID/OPENAI_PROJECT_ID); `keys` defaults to `KeyRing.from_env()`.
+        
+        `agent` is the agent type requests are for unless one names another,
+        and `agent_models` its settings (default ZAIK_AGENT_MODELS, see
+        `ai_agents.agent_models`). A `model` given here is used for every
+        agent type. `prompt_budget` caps a rendered template prompt in
+        tokens (default ZAIK_PROMPT_BUDGET, else 6000; see
+        `ai_agents.prompt_budget`)
+        """
+        recording = cassette.Cassette.from_env()
+        # A replayed cassette answers without the API, so no key is needed
+        self._keyless = "replay" if recording and recording.mode == "replay" else None
+        self.keys = keys or KeyRing.from_env()
+        proxy = proxy or os.getenv("ZAIK_AI_PROXY")
+        http_client = cassette.async_client(recording, proxy)
+        if http_client is None and proxy:
+            http_client = httpx.AsyncClient(proxy=proxy)
+        self.base_url = base_url or os.getenv("OPENAI_BASE_URL")
+        self.timeout = timeout or float(os.getenv("ZAIK_AI_TIMEOUT", "60"))
+        self.prompt_budget = prompt_budget or int(os.getenv("ZAIK_PROMPT_BUDGET", str(DEFAULT_PROMPT_BUDGET)))
+        self._options = {
+            "base_url": self.base_url,
+            "timeout": self.timeout,
+            "http_client": http_client,
+            "organization": organization or os.getenv("OPENAI_ORG_ID"),
+            "project": project or os.getenv("OPENAI_PROJECT_ID")
+        }
+        # One SDK client per key, since the SDK fixes the key at construction
+        self._clients: Dict[Optional[str], Any] = {}
+        # Build the first one now, so a missing key fails here as before
+        _ = self.client
+        self.agent = agent
+        self.agent_models = agent_models or AgentModels.from_env()
+        self._pinned_model = model
+        self.model = model or self.agent_models.for_agent(agent).model or DEFAULT_MODEL
+    
+    @property
+    def client(self):
+        """SDK client for the key currently in use"""
+        key = self.keys.current() or self._keyless
+        if key not in self._clients:
+            self._clients[key] = AsyncOpenAI(api_key=key, **self._options)
+        return self._clients[key]
+    
+    def params_for(
+        self,
+        agent: Optional[AgentType] = None,
+        temperature: Optional[float] = None,
+        max_tokens: Optional[int] = None
+    ) -> ModelParams:
+        """What a request for `agent` is sent with: its configured settings,
+        then the values the caller passed, then the defaults"""
+        configured = self.agent_models.for_agent(agent or self.agent)
+        return ModelParams(model=self._pinned_model).over(configured).over(
+            ModelParams(temperature=temperature, max_tokens=max_tokens)
+        ).over(ModelParams(DEFAULT_MODEL, DEFAULT_TEMPERATURE, DEFAULT_MAX_TOKENS))
+    
+    async def _create(self, **request):
+        """A completion request, failing over to the next key when one runs
+        out of quota or is rejected"""
+        while True:
+            key = self.keys.current() or self._keyless
+            try:
+                return await self.client.chat.completions.create(**request)
+            except Exception as e:
+                if not (key and is_key_failure(e) and self.keys.fail_over(key, e)):
+                    raise
         
     async def chat_completion(
         self, 
         messages: List[AgentMessage], 
         system_prompt: Optional[str] = None,
-        temperature: float = 0.1,
-        max_tokens: int = 2000
+        temperature: Optional[float] = None,
+        max_tokens: Optional[int] = None,
+        template: Optional[str] = None,
+        timeout: Optional[float] = None,
+        agent: Optional[AgentType] = None
     ) -> AgentResponse:
-        """Generate a chat completion with structured response"""
+        """Generate a chat completion with structured response; `timeout`
+        overrides the client's default for this request. `temperature` and
+        `max_tokens` are fallbacks for what is configured for `agent` (see
+        `params_for`)"""
         
         # Build messages array
         openai_messages = []
@@ -48,15 +177,23 @@ class OpenAIClient:
                 "content": msg.content
             })
         
+        params = self.params_for(agent, temperature, max_tokens)
+        sampling = {"top_p": params.top_p} if params.top_p is not None else {}
         try:
-            response = await self.client.chat.completions.create(
-                model=self.model,
+            response = await self._create(
+                model=params.model,
                 messages=openai_messages,
-                temperature=temperature,
-                max_tokens=max_tokens
+                temperature=params.temperature,
+                max_tokens=params.max_tokens,
+                timeout=timeout or self.timeout,
+                **sampling
             )
             
-            content = response.choices[0].message.content
+            content = chaos.current().ai_response(response.choices[0].message.content)
+            prompt = prompt_record(
+                params.model, openai_messages, params.temperature, params.max_tokens, template, params.top_p
+            )
+            provenance = {"prompt": prompt, "prompt_hash": canonical_hash(prompt), "template": template}
             
             # Try to parse structured response
             try:
@@ -66,21 +203,50 @@ class OpenAIClient:
                         content=parsed.get("content", content),
                         reasoning=parsed.get("reasoning"),
                         confidence=parsed.get("confidence"),
-                        next_actions=parsed.get("next_actions", [])
+                        next_actions=parsed.get("next_actions", []),
+                        **provenance
                     )
             except json.JSONDecodeError:
                 pass
                 
-            return AgentResponse(content=content)
+            return AgentResponse(content=content, **provenance)
             
         except Exception as e:
             raise Exception(f"OpenAI API error: {str(e)}")
     
+    async def complete_template(
+        self,
+        template: PromptTemplate,
+        temperature: Optional[float] = None,
+        max_tokens: Optional[int] = None,
+        timeout: Optional[float] = None,
+        agent: Optional[AgentType] = None,
+        **params
+    ) -> AgentResponse:
+        """Render `template` with `params` and complete it, recording the
+        template version. Text parameters too large for the prompt budget are
+        condensed first, keeping their hashes, sums and flags"""
+        params, condensed = fit_params(template, params, self.prompt_budget)
+        messages = [AgentMessage(**message) for message in template.render(**params)]
+        response = await self.chat_completion(
+            messages=messages,
+            system_prompt=template.system,
+            temperature=temperature,
+            max_tokens=max_tokens,
+            template=template.id,
+            timeout=timeout,
+            agent=agent
+        )
+        response.condensed = condensed or None
+        return response
+    
     async def function_call(
         self,
         messages: List[AgentMessage],
         functions: List[Dict[str, Any]],
-        system_prompt: Optional[str] = None
+        system_prompt: Optional[str] = None,
+        timeout: Optional[float] = None,
+        agent: Optional[AgentType] = None
     ) -> Dict[str, Any]:
         """Make a function call using OpenAI's function calling"""
         
@@ -96,11 +262,12 @@ class OpenAIClient:
             })
         
         try:
-            response = await self.client.chat.completions.create(
-                model=self.model,
+            response = await self._create(
+                model=self.params_for(agent).model,
                 messages=openai_messages,
                 functions=functions,
-                function_call="auto"
+                function_call="auto",
+                timeout=timeout or self.timeout
             )
             
             message = response.choices[0].message
@@ -122,52 +289,8 @@ class OpenAIClient:
             raise Exception(f"OpenAI function call error: {str(e)}")
 
 class AgentPrompts:
-    """Predefined prompts for different agent roles"""
-    
-    CSV_ANALYZER = """
-    You are a CSV Data Analyzer Agent. Your role is to:
-    1. Analyze CSV data structure and content
-    2. Identify column types and data patterns
-    3. Suggest processing strategies
-    4. Flag potential data quality issues
-    
-    Always respond in JSON format:
-    {
-        "content": "Your analysis",
-        "reasoning": "Why you made these decisions",
-        "confidence": 0.95,
-        "next_actions": ["action1", "action2"]
-    }
-    """
-    
-    VERIFICATION_AGENT = """
-    You are a Verification Agent. Your role is to:
-    1. Review computational results
-    2. Validate business logic compliance
-    3. Assess proof quality and correctness
-    4. Make accept/reject decisions
-    
-    Always respond in JSON format:
-    {
-        "content": "Your verification result",
-        "reasoning": "Why you accept/reject",
-        "confidence": 0.95,
-        "next_actions": ["action1", "action2"]
-    }
-    """
-    
-    ORCHESTRATOR = """
-    You are an Orchestrator Agent. Your role is to:
-    1. Coordinate between different agents
-    2. Make workflow decisions
-    3. Handle error recovery
-    4. Optimize processing pipeline
+    """System prompts for each agent role (defined in prompt_templates)"""
     
-    Always respond in JSON format:
-    {
-        "content": "Your orchestration decision",
-        "reasoning": "Why you made this decision",
-        "confidence": 0.95,
-        "next_actions": ["action1", "action2"]
-    }
-    """
\ No newline at end of file
+    CSV_ANALYZER = prompt_templates.CSV_ANALYZER_SYSTEM
+    VERIFICATION_AGENT = prompt_templates.VERIFICATION_AGENT_SYSTEM
+    ORCHESTRATOR = prompt_templates.ORCHESTRATOR_SYSTEM
@@ -0,0 +1,116 @@
+"""
+Restartable pipelines of agent stages
+Each stage's output is persisted as it completes, so a rerun with the same
+pipeline ID resumes after the last finished stage instead of re-proving
+"""
+import asyncio
+import json
+from dataclasses import dataclass, field
+from datetime import datetime
+from pathlib import Path
+from typing import Any, Awaitable, Callable, Dict, List, Optional
+
+from .deadline import Deadline, DeadlineExceeded
+
+# A stage receives the outputs of every earlier stage, keyed by stage name
+StageFn = Callable[[Dict[str, Any]], Awaitable[Dict[str, Any]]]
+
+@dataclass
+class RetryPolicy:
+    max_attempts: int = 1
+    backoff_seconds: float = 0.0
+
+@dataclass
+class Stage:
+    name: str
+    run: StageFn
+    agents: List[str]
+    retry: RetryPolicy = field(default_factory=RetryPolicy)
+
+@dataclass
+class PipelineState:
+    pipeline_id: str
+    outputs: Dict[str, Dict[str, Any]] = field(default_factory=dict)
+    attempts: Dict[str, int] = field(default_factory=dict)
+    failed_stage: Optional[str] = None
+    error: Optional[str] = None
+
+    @classmethod
+    def load(cls, path: Path, pipeline_id: str) -> "PipelineState":
+        if not path.exists():
+            return cls(pipeline_id=pipeline_id)
+        return cls(**json.loads(path.read_text()))
+
+    def save(self, path: Path):
+        path.parent.mkdir(parents=True, exist_ok=True)
+        path.write_text(json.dumps(self.__dict__, indent=2, default=str))
+
+class StageFailed(Exception):
+    def __init__(self, stage: str, error: Exception):
+        super().__init__(f"stage '{stage}' failed: {error}")
+        self.stage = stage
+        self.error = error
+
+class Pipeline:
+    """Runs stages in order, retrying per stage and persisting progress"""
+
+    def __init__(self, stages: List[Stage], state_dir: Optional[str] = None):
+        self.stages = stages
+        self.state_dir = Path(state_dir) if state_dir else None
+
+    def _state_path(self, pipeline_id: str) -> Optional[Path]:
+        return self.state_dir / f"{pipeline_id}.json" if self.state_dir else None
+
+    async def run(
+        self,
+        pipeline_id: str,
+        on_stage_complete: Optional[Callable[[Stage, Dict[str, Any]], None]] = None,
+        deadline: Optional[Deadline] = None
+    ) -> PipelineState:
+        """Run every stage not already completed under `pipeline_id`. A passed
+        `deadline` is not retried: DeadlineExceeded propagates with the
+        completed stages' outputs attached as `outputs`"""
+        path = self._state_path(pipeline_id)
+        state = PipelineState.load(path, pipeline_id) if path else PipelineState(pipeline_id=pipeline_id)
+        state.failed_stage = None
+        state.error = None
+
+        for stage in self.stages:
+            if stage.name in state.outputs:
+                print(f"⏭️  Skipping completed stage: {stage.name}")
+                continue
+
+            for attempt in range(1, stage.retry.max_attempts + 1):
+                state.attempts[stage.name] = state.attempts.get(stage.name, 0) + 1
+                try:
+                    if deadline:
+                        deadline.check(stage.name)
+                    output = await stage.run(state.outputs)
+                    break
+                except DeadlineExceeded as e:
+                    state.failed_stage = stage.name
+                    state.error = str(e)
+                    if path:
+                        state.save(path)
+                    e.outputs = dict(state.outputs)
+                    raise
+                except Exception as e:
+                    if attempt == stage.retry.max_attempts:
+                        state.failed_stage = stage.name
+                        state.error = str(e)
+                        if path:
+                            state.save(path)
+                        raise StageFailed(stage.name, e) from e
+                    print(f"🔁 Retrying {stage.name} ({attempt}/{stage.retry.max_attempts}): {e}")
+                    await asyncio.sleep(stage.retry.backoff_seconds * attempt)
+
+            state.outputs[stage.name] = output
+            if path:
+                state.save(path)
+            if on_stage_complete:
+                on_stage_complete(stage, output)
+
+        return state
+
+def new_pipeline_id(prefix: str) -> str:
+    return f"{prefix}_{datetime.now().strftime('%Y%m%d_%H%M%S')}"
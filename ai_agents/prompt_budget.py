@@ -0,0 +1,134 @@
+"""
+Keeping template prompts within a token budget
+Prompt parameters can carry text of any size: a previous agent's analysis,
+a policy trace, rows of a wide CSV. `fit_params` shrinks the largest text
+parameters until the rendered prompt fits, and never touches numbers,
+booleans or short values. Shrinking a parameter keeps every line that holds
+a key field (a hash, a sum or count, a pass/fail flag, an error) and as much
+of the head and tail as fits around them, with a marker saying what was cut
+"""
+import re
+from typing import Any, Dict, List, Tuple
+
+from .prompt_templates import PromptTemplate
+
+DEFAULT_PROMPT_BUDGET = 6000
+# Rough English/JSON average; no tokenizer is needed to stay well clear of
+# the context window
+CHARS_PER_TOKEN = 4
+# Room left for the omission markers
+_MARKER_TOKENS = 16
+
+_KEY_LINE = re.compile(
+    r"\b[0-9a-fA-F]{16,}\b"
+    r"|\b(?:sum|total|count|rows?|entries|threshold|hash|digest|image|journal|exit|reason|outcome)\b"
+    r"|\b(?:true|false|valid|invalid|pass(?:ed)?|fail(?:ed|ure)?|satisfied|violated|error|exception|accept|reject)\b",
+    re.IGNORECASE
+)
+
+def estimate_tokens(text: str) -> int:
+    return -(-len(text) // CHARS_PER_TOKEN)
+
+def _omitted(chars: int, lines: int = 0) -> str:
+    return f"[... {lines} line(s), {chars} characters omitted ...]" if lines else f"[... {chars} characters omitted ...]"
+
+def _clip(line: str, chars: int) -> str:
+    """`line` cut to about `chars`, keeping its hashes whole"""
+    if len(line) <= chars:
+        return line
+    hashes = " ".join(re.findall(r"[0-9a-fA-F]{16,}", line))
+    head = line[:max(0, chars - len(hashes) - 8)]
+    return f"{head} ... {hashes}".rstrip() if hashes and hashes not in head else f"{head} ..."
+
+def condense(text: str, max_tokens: int) -> str:
+    """
+    `text` within about `max_tokens`: unchanged if it fits, otherwise its key
+    lines in their original order, then as many head and tail lines as the
+    rest of the budget holds
+    """
+    if estimate_tokens(text) <= max_tokens:
+        return text
+    budget = max(0, max_tokens - _MARKER_TOKENS) * CHARS_PER_TOKEN
+    lines = text.split("\n")
+    # A single huge line (a list of rows, a JSON blob) is cut as a line
+    if len(lines) == 1:
+        return f"{_clip(text, budget)} {_omitted(max(0, len(text) - budget))}"
+    kept = set()
+    used = 0
+    width = max(80, budget // 8)
+
+    def keep(index: int) -> bool:
+        nonlocal used
+        cost = min(len(lines[index]), width) + 1
+        if index in kept or used + cost > budget:
+            return False
+        kept.add(index)
+        used += cost
+        return True
+
+    for index, line in enumerate(lines):
+        if _KEY_LINE.search(line):
+            keep(index)
+    # Then the head and tail, alternating, until the budget runs out
+    head, tail = 0, len(lines) - 1
+    while head <= tail and (keep(head) | keep(tail)):
+        head, tail = head + 1, tail - 1
+
+    out, gap = [], []
+    for index, line in enumerate(lines):
+        if index in kept:
+            if gap:
+                out.append(_omitted(sum(len(skipped) + 1 for skipped in gap), len(gap)))
+                gap = []
+            out.append(_clip(line, width))
+        else:
+            gap.append(line)
+    if gap:
+        out.append(_omitted(sum(len(skipped) + 1 for skipped in gap), len(gap)))
+    return "\n".join(out)
+
+def fit_params(
+    template: PromptTemplate,
+    params: Dict[str, Any],
+    max_tokens: int = DEFAULT_PROMPT_BUDGET
+) -> Tuple[Dict[str, Any], List[str]]:
+    """
+    `params` with their largest text values condensed until `template`
+    renders within `max_tokens`, and the names of the ones condensed.
+    Numbers, booleans and None are always passed through. If the template
+    alone is over the budget, the text values are cut as far as they go
+    """
+    def rendered(values: Dict[str, Any]) -> int:
+        messages = template.render(**values)
+        return estimate_tokens(template.system) + sum(estimate_tokens(message["content"]) for message in messages)
+
+    if rendered(params) <= max_tokens:
+        return params, []
+    # Lists and dicts are rendered with str() anyway
+    text = {
+        name: value if isinstance(value, str) else str(value)
+        for name, value in params.items()
+        if not (value is None or isinstance(value, (bool, int, float)))
+    }
+    fixed = rendered({**params, **{name: "" for name in text}})
+    remaining = max(0, max_tokens - fixed)
+    # Share what is left evenly, giving small values all they need and
+    # splitting the rest among the larger ones
+    shares, pending = {}, sorted(text, key=lambda name: estimate_tokens(text[name]))
+    while pending:
+        share = remaining // len(pending)
+        name = pending[0]
+        if estimate_tokens(text[name]) <= share:
+            shares[name] = estimate_tokens(text[name])
+            remaining -= shares[name]
+            pending.pop(0)
+        else:
+            for name in pending:
+                shares[name] = share
+            break
+    fitted, condensed = dict(params), []
+    for name, value in text.items():
+        if estimate_tokens(value) > shares[name]:
+            fitted[name] = condense(value, shares[name])
+            condensed.append(name)
+    return fitted, condensed
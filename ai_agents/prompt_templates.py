@@ -0,0 +1,203 @@
+"""
+Versioned prompt templates
+Every prompt the workflow sends lives here rather than in string literals.
+A template is identified as name@vN; any change to its text must bump the
+version (or add a new one) so recorded decisions keep pointing at the
+wording they were made with. Parameters use string.Template `$name` syntax
+and must all be supplied
+"""
+from dataclasses import dataclass
+from string import Template
+from typing import Dict, List, Tuple
+
+from .canonical import canonical_hash
+
+CSV_ANALYZER_SYSTEM = """
+    You are a CSV Data Analyzer Agent. Your role is to:
+    1. Analyze CSV data structure and content
+    2. Identify column types and data patterns
+    3. Suggest processing strategies
+    4. Flag potential data quality issues
+
+    Always respond in JSON format:
+    {
+        "content": "Your analysis",
+        "reasoning": "Why you made these decisions",
+        "confidence": 0.95,
+        "next_actions": ["action1", "action2"]
+    }
+    """
+
+VERIFICATION_AGENT_SYSTEM = """
+    You are a Verification Agent. Your role is to:
+    1. Review computational results
+    2. Validate business logic compliance
+    3. Assess proof quality and correctness
+    4. Make accept/reject decisions
+
+    Always respond in JSON format:
+    {
+        "content": "Your verification result",
+        "reasoning": "Why you accept/reject",
+        "confidence": 0.95,
+        "next_actions": ["action1", "action2"]
+    }
+    """
+
+ORCHESTRATOR_SYSTEM = """
+    You are an Orchestrator Agent. Your role is to:
+    1. Coordinate between different agents
+    2. Make workflow decisions
+    3. Handle error recovery
+    4. Optimize processing pipeline
+
+    Always respond in JSON format:
+    {
+        "content": "Your orchestration decision",
+        "reasoning": "Why you made this decision",
+        "confidence": 0.95,
+        "next_actions": ["action1", "action2"]
+    }
+    """
+
+@dataclass(frozen=True)
+class PromptTemplate:
+    name: str
+    version: int
+    system: str
+    user: str
+    # Few-shot (user, assistant) pairs sent ahead of the rendered prompt
+    examples: Tuple[Tuple[str, str], ...] = ()
+
+    @property
+    def id(self) -> str:
+        return f"{self.name}@v{self.version}"
+
+    @property
+    def digest(self) -> str:
+        """Hash of the template text, to catch edits made without a version bump"""
+        return canonical_hash({
+            "name": self.name,
+            "version": self.version,
+            "system": self.system,
+            "user": self.user,
+            "examples": [list(example) for example in self.examples]
+        })
+
+    def render(self, **params) -> List[Dict[str, str]]:
+        """Few-shot examples followed by the filled-in user prompt"""
+        messages = []
+        for question, answer in self.examples:
+            messages.append({"role": "user", "content": question})
+            messages.append({"role": "assistant", "content": answer})
+        messages.append({"role": "user", "content": Template(self.user).substitute(params)})
+        return messages
+
+CSV_ANALYSIS = PromptTemplate(
+    name="csv_analysis",
+    version=1,
+    system=CSV_ANALYZER_SYSTEM,
+    user="""
+        Analyze this CSV data:
+
+        Headers: $headers
+        Row count: $row_count
+        Sample rows: $sample_rows
+
+        Focus on:
+        1. Data structure and column types
+        2. Column A values and patterns
+        3. Potential data quality issues
+        4. Business logic implications
+
+        Predict what the sum of column A will be.
+        """,
+    examples=((
+        "Analyze this CSV data:\nHeaders: ['A', 'B']\nRow count: 3\nSample rows: ['100,x', '200,y', '50,z']\n"
+        "Predict what the sum of column A will be.",
+        '{"content": "Two columns: A holds unsigned integers, B short labels. No missing values. '
+        'Predicted column A sum: 350.", "reasoning": "All three rows are shown, so the sum is exact.", '
+        '"confidence": 0.95, "next_actions": ["prove column A sum"]}'
+    ),)
+)
+
+VERIFICATION_REVIEW = PromptTemplate(
+    name="verification_review",
+    version=1,
+    system=VERIFICATION_AGENT_SYSTEM,
+    user="""
+        Review these results:
+
+        CSV Analysis:
+        - Agent Analysis: $analysis
+        - Confidence: $analysis_confidence
+
+        RISC Zero Verification:
+        - Verification Successful: $verification_successful
+        - RISC Zero Proof Valid: $proof_valid
+        - Proof Kind: $proof_kind (only "real" is backed by a cryptographic seal)
+        - Business Logic Satisfied: $business_logic_satisfied
+        - Column A Sum: $column_a_sum
+        - Cryptographic Guarantees: $cryptographic_guarantees
+
+        Provide your verification assessment:
+        1. Do the results align with the initial analysis?
+        2. Are the cryptographic proofs valid?
+        3. Should we accept or reject this computation?
+        4. What are the trust guarantees?
+        """
+)
+
+FINAL_DECISION = PromptTemplate(
+    name="final_decision",
+    version=1,
+    system=ORCHESTRATOR_SYSTEM,
+    user="""
+        Make a final workflow decision based on all agent inputs:
+
+        CSV Analysis Confidence: $analysis_confidence
+        RISC Zero Verification: $verification_successful
+        Verification Agent Recommendation: $recommendation
+        Verification Agent Confidence: $review_confidence
+
+        Key Metrics:
+        - Deterministic Execution Proven: $deterministic_proof
+        - Proof Kind: $proof_kind
+        - Business Logic Compliance: $business_compliance
+        - Cryptographic Guarantees: $cryptographic_guarantees
+
+        Provide final decision:
+        1. Accept or reject the computation
+        2. Overall confidence level
+        3. Key success factors
+        4. Risk assessment
+        """
+)
+
+DECISION_EXPLANATION = PromptTemplate(
+    name="decision_explanation",
+    version=1,
+    system=VERIFICATION_AGENT_SYSTEM,
+    user="""
+        Explain this verification decision to a non-technical reviewer.
+
+        Decision: $decision
+        Proof Kind: $proof_kind
+        Policy Summary: $summary
+        Policy Rules:
+$rules
+
+        Only use the rule outcomes and values listed above; do not speculate about other checks.
+        """
+)
+
+TEMPLATES: Dict[str, PromptTemplate] = {
+    template.id: template
+    for template in [CSV_ANALYSIS, VERIFICATION_REVIEW, FINAL_DECISION, DECISION_EXPLANATION]
+}
+
+def get_template(template_id: str) -> PromptTemplate:
+    """Look up a template by name@vN, e.g. to re-render a recorded decision"""
+    if template_id not in TEMPLATES:
+        raise KeyError(f"unknown prompt template '{template_id}' (known: {', '.join(sorted(TEMPLATES))})")
+    return TEMPLATES[template_id]
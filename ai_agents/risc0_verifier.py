@@ -6,10 +6,23 @@ import json
 import hashlib
 import os
 import tempfile
-from typing import Dict, Any, Optional, Tuple
+from typing import Dict, Any, List, Optional, Tuple
 from dataclasses import dataclass
 from pathlib import Path
 
+from . import chaos
+from .csv_guardrails import CsvRejected, validate_csv
+from .deadline import EXIT_DEADLINE, Deadline
+
+# Stable exit codes emitted by the host verifier
+EXIT_ACCEPT = 0
+EXIT_CONDITIONAL = 2
+EXIT_POLICY_REJECT = 3
+EXIT_CRYPTO_FAILURE = 4
+EXIT_IO = 5
+EXIT_UNEXPECTED_DATA = 7
+EXIT_INVESTIGATE = 8
+
 @dataclass
 class VerificationResult:
     success: bool
@@ -20,7 +33,13 @@ class VerificationResult:
     verification_passed: bool
     business_invariant_passed: bool
     snark_proof_valid: bool
+    proof_kind: str = "unknown"
+    policy_version: Optional[str] = None
+    policy_trace: Optional[List[str]] = None
+    explanation: Optional[str] = None
     proof_hash: Optional[str] = None
+    exit_code: Optional[int] = None
+    failure_reason: Optional[Dict[str, Any]] = None
     error_message: Optional[str] = None
 
 class RISC0Verifier:
@@ -62,7 +81,9 @@ class RISC0Verifier:
         self, 
         csv_content: str, 
         threshold: int = 1000,
-        use_dev_mode: bool = True
+        use_dev_mode: bool = True,
+        deadline: Optional[Deadline] = None,
+        expected_hashes: Optional[List[str]] = None
     ) -> VerificationResult:
         """
         Run the RISC Zero verification on CSV content
@@ -71,35 +92,54 @@ class RISC0Verifier:
             csv_content: The CSV data as a string
             threshold: Business logic threshold for column A sum
             use_dev_mode: Whether to use RISC0_DEV_MODE for faster execution
+            deadline: Passed to the host as --deadline; the host exits 6 once it passes
+            expected_hashes: CSV hashes Agent B published; any other CSV is
+                refused with exit code 7 before proving
         
         Returns:
             VerificationResult with all verification details
         """
         try:
+            csv_hash = self.compute_csv_hash(csv_content)
+            if expected_hashes is not None and csv_hash not in expected_hashes:
+                failure = {"outcome": "unexpected-data", "exit_code": EXIT_UNEXPECTED_DATA,
+                           "reason": "unexpected_csv_hash",
+                           "message": f"CSV hash {csv_hash} is not among the expected hashes (preflight)"}
+                return self._parse_verification_output("", json.dumps(failure), EXIT_UNEXPECTED_DATA, csv_content)
+            
             self.ensure_binary_exists()
             
             # Create temporary CSV file
             temp_csv_path = self.create_temp_csv(csv_content)
             
             try:
-                # Modify the Rust code to use the temporary CSV file
-                self._update_csv_path_in_rust(temp_csv_path, threshold)
-                
-                # Recompile with new CSV path
                 env = {**os.environ}
                 if use_dev_mode:
                     env["RISC0_DEV_MODE"] = "1"
                 else:
                     env["RISC0_DEV_MODE"] = "0"
                 
-                # Run the verifier
-                result = subprocess.run(
-                    [str(self.rust_binary)],
-                    cwd=self.project_root,
-                    capture_output=True,
-                    text=True,
-                    env=env
-                )
+                chaos.current().before_proving(self.project_root / ".zaik" / "proofs")
+                
+                command = [str(self.rust_binary), temp_csv_path, "--threshold", str(threshold)]
+                if deadline:
+                    deadline.check("proving")
+                    command += ["--deadline", f"{deadline.remaining():.3f}s"]
+                
+                # Run the verifier; the host enforces the deadline itself, the
+                # grace period only covers a host that stops responding
+                try:
+                    result = subprocess.run(
+                        command,
+                        cwd=self.project_root,
+                        capture_output=True,
+                        text=True,
+                        env=env,
+                        timeout=deadline.remaining() + 30 if deadline else None
+                    )
+                except subprocess.TimeoutExpired:
+                    failure = deadline.exceeded("proving").to_failure()
+                    return self._parse_verification_output("", json.dumps(failure), EXIT_DEADLINE, csv_content)
                 
                 # Parse the output
                 return self._parse_verification_output(
@@ -126,35 +166,6 @@ class RISC0Verifier:
                 error_message=str(e)
             )
     
-    def _update_csv_path_in_rust(self, csv_path: str, threshold: int):
-        """Temporarily update the Rust code to use the provided CSV path"""
-        host_main_path = self.project_root / "host" / "src" / "main.rs"
-        
-        # Read current content
-        with open(host_main_path, 'r') as f:
-            content = f.read()
-        
-        # Replace CSV path and threshold
-        updated_content = content.replace(
-            'let csv_file_path = "test_data.csv";',
-            f'let csv_file_path = "{csv_path}";'
-        ).replace(
-            'let sum_threshold = 1000u64;',
-            f'let sum_threshold = {threshold}u64;'
-        )
-        
-        # Write updated content
-        with open(host_main_path, 'w') as f:
-            f.write(updated_content)
-        
-        # Recompile
-        subprocess.run(
-            ["cargo", "build", "--release"],
-            cwd=self.project_root,
-            capture_output=True,
-            env={**os.environ, "RISC0_DEV_MODE": "1"}
-        )
-    
     def _parse_verification_output(
         self, 
         stdout: str, 
@@ -164,7 +175,9 @@ class RISC0Verifier:
     ) -> VerificationResult:
         """Parse the output from the RISC Zero verifier"""
         
-        if return_code != 0 and "FAILURE" not in stdout:
+        failure_reason = self._parse_failure_reason(stderr)
+        
+        if return_code not in (EXIT_ACCEPT, EXIT_CONDITIONAL, EXIT_POLICY_REJECT, EXIT_CRYPTO_FAILURE, EXIT_INVESTIGATE):
             return VerificationResult(
                 success=False,
                 csv_hash="",
@@ -174,7 +187,9 @@ class RISC0Verifier:
                 verification_passed=False,
                 business_invariant_passed=False,
                 snark_proof_valid=False,
-                error_message=f"Verifier failed: {stderr}"
+                exit_code=return_code,
+                failure_reason=failure_reason,
+                error_message=f"Verifier failed: {failure_reason['message'] if failure_reason else stderr}"
             )
         
         try:
@@ -186,18 +201,37 @@ class RISC0Verifier:
             verification_passed = False
             business_invariant_passed = False
             snark_proof_valid = False
+            proof_kind = "unknown"
+            policy_version = None
+            policy_trace = []
+            explanation = None
             proof_hash = None
+            in_policy_trace = False
             
             lines = stdout.split('\n')
             for line in lines:
-                if "CSV hash:" in line:
+                if in_policy_trace:
+                    if line.startswith("  - "):
+                        policy_trace.append(line[4:].strip())
+                        continue
+                    in_policy_trace = False
+                
+                if "Policy trace:" in line:
+                    in_policy_trace = True
+                elif "Explanation:" in line:
+                    explanation = line.split('Explanation: ', 1)[1].strip()
+                elif "CSV hash:" in line:
                     csv_hash = line.split('"')[1] if '"' in line else line.split(': ')[1]
                 elif "Column A sum:" in line:
-                    column_a_sum = int(line.split(': ')[1])
+                    column_a_sum = int(line.split(': ')[1].split()[0])
                 elif "Column A hash:" in line:
                     column_a_hash = line.split(' ')[4]
                 elif "Entry count:" in line:
                     entry_count = int(line.split(': ')[1])
+                elif "Policy version:" in line:
+                    policy_version = line.split(': ')[1].strip()
+                elif "Proof kind:" in line:
+                    proof_kind = line.split(': ')[1].strip()
                 elif "Receipt verification: PASSED" in line:
                     verification_passed = True
                 elif "Business invariant" in line and "PASSED" in line:
@@ -207,7 +241,8 @@ class RISC0Verifier:
                 elif "SNARK proof hash:" in line:
                     proof_hash = line.split(': ')[1]
             
-            success = "SUCCESS: All checks passed!" in stdout
+            # A sum held by the trend check passed every rule but is not accepted
+            success = "SUCCESS: All checks passed!" in stdout and return_code != EXIT_INVESTIGATE
             
             return VerificationResult(
                 success=success,
@@ -218,8 +253,14 @@ class RISC0Verifier:
                 verification_passed=verification_passed,
                 business_invariant_passed=business_invariant_passed,
                 snark_proof_valid=snark_proof_valid,
+                proof_kind=proof_kind,
+                policy_version=policy_version or (failure_reason or {}).get("policy_version"),
+                policy_trace=policy_trace,
+                explanation=explanation,
                 proof_hash=proof_hash,
-                error_message=None if success else "Verification failed"
+                exit_code=return_code,
+                failure_reason=failure_reason,
+                error_message=None if success else (failure_reason or {}).get("message", "Verification failed")
             )
             
         except Exception as e:
@@ -235,9 +276,27 @@ class RISC0Verifier:
                 error_message=f"Failed to parse output: {str(e)}"
             )
     
-    def verify_csv_data(self, csv_content: str, expected_sum: Optional[int] = None) -> Dict[str, Any]:
+    def _parse_failure_reason(self, stderr: str) -> Optional[Dict[str, Any]]:
+        """Extract the failure-reason JSON line the verifier writes to stderr"""
+        for line in reversed(stderr.strip().split('\n')):
+            line = line.strip()
+            if line.startswith("{") and '"exit_code"' in line:
+                try:
+                    return json.loads(line)
+                except json.JSONDecodeError:
+                    return None
+        return None
+    
+    def verify_csv_data(
+        self,
+        csv_content: str,
+        expected_sum: Optional[int] = None,
+        threshold: int = 1000,
+        deadline: Optional[Deadline] = None,
+        expected_hashes: Optional[List[str]] = None
+    ) -> Dict[str, Any]:
         """High-level verification function that returns a detailed report"""
-        result = self.run_verification(csv_content)
+        result = self.run_verification(csv_content, threshold, deadline=deadline, expected_hashes=expected_hashes)
         
         report = {
             "verification_successful": result.success,
@@ -251,12 +310,23 @@ class RISC0Verifier:
                 "entry_count": result.entry_count
             },
             "proof_details": {
+                "proof_kind": result.proof_kind,
+                "policy_version": result.policy_version,
                 "proof_hash": result.proof_hash,
                 "deterministic_execution": result.verification_passed,
                 "cryptographic_guarantees": result.snark_proof_valid
             }
         }
         
+        report["policy_outcome"] = {
+            "trace": result.policy_trace or [],
+            "explanation": result.explanation
+        }
+        
+        report["exit_code"] = result.exit_code
+        if result.failure_reason:
+            report["failure_reason"] = result.failure_reason
+        
         if result.error_message:
             report["error"] = result.error_message
             
@@ -267,4 +337,32 @@ class RISC0Verifier:
                 "matches": expected_sum == result.column_a_sum
             }
         
+        return report
+    
+    def verify_generated_csv(
+        self,
+        generated: str,
+        threshold: int = 1000,
+        expected_header: Optional[List[str]] = None,
+        max_rows: Optional[int] = None
+    ) -> Dict[str, Any]:
+        """
+        Run AI-generated CSV through the guardrails before proving it. The
+        repaired CSV is what gets proven; irreparable output is rejected
+        with exit code 5 (`csv_rejected`) without starting the prover.
+        """
+        try:
+            checked = validate_csv(generated, expected_header=expected_header, max_rows=max_rows)
+        except CsvRejected as e:
+            failure = {"outcome": "io", "exit_code": EXIT_IO, "reason": "csv_rejected", "message": str(e)}
+            return {
+                "verification_successful": False,
+                "exit_code": EXIT_IO,
+                "failure_reason": failure,
+                "guardrails": {"rejected": e.to_dict(), "repairs": []},
+                "error": str(e)
+            }
+        
+        report = self.verify_csv_data(checked.csv, None, threshold)
+        report["guardrails"] = {"rejected": None, "repairs": checked.repairs}
         return report
\ No newline at end of file
@@ -0,0 +1,120 @@
+"""
+Concurrent scenario runs
+Each scenario gets its own workflow, and at most `max_concurrency` run at
+once, so one scenario's AI calls overlap another's proving. With a
+`ScenarioMemory`, scenarios nearly identical to one already run are skipped
+or mutated first; with a `CoverageTracker`, each scenario's invariant regions
+are recorded and scenarios can be added for regions nothing has hit
+"""
+import asyncio
+import time
+from dataclasses import dataclass
+from pathlib import Path
+from typing import Any, Dict, List, Optional
+
+from .agent_workflow import AgentWorkflow
+from .embeddings import ScenarioMemory, find_novel, scenario_text
+from .coverage import CoverageTracker, targeted_csv
+
+@dataclass
+class Scenario:
+    name: str
+    csv_file: str
+    threshold: int = 1000
+    # Set for mutated scenarios, whose data no longer matches `csv_file`
+    csv_content: Optional[str] = None
+
+async def run_scenario(scenario: Scenario) -> Dict[str, Any]:
+    started = time.monotonic()
+    try:
+        csv_content = scenario.csv_content or Path(scenario.csv_file).read_text()
+        result = await AgentWorkflow().run_csv_analysis_workflow(csv_content, scenario.threshold)
+    except Exception as e:
+        result = {"success": False, "error": str(e)}
+    return {
+        "scenario": scenario.name,
+        "csv_file": scenario.csv_file,
+        "threshold": scenario.threshold,
+        "elapsed_seconds": round(time.monotonic() - started, 2),
+        "result": result
+    }
+
+def deduplicate(
+    scenarios: List[Scenario],
+    memory: ScenarioMemory,
+    on_duplicate: str = "skip",
+    similarity: float = 0.95
+) -> Dict[str, Any]:
+    """Drop or mutate scenarios at least `similarity` alike to one already in
+    `memory` (or earlier in the list); the ones kept are remembered"""
+    if on_duplicate not in ("skip", "mutate"):
+        raise ValueError("on_duplicate must be 'skip' or 'mutate'")
+    kept, skipped, mutated = [], [], []
+    for scenario in scenarios:
+        original = scenario.csv_content or Path(scenario.csv_file).read_text()
+        content, match = find_novel(
+            memory, original, scenario.threshold, similarity, mutate=on_duplicate == "mutate"
+        )
+        if content is None:
+            print(f"⏭️  Skipping {scenario.name}: {match.similarity:.2f} similar to {match.name}")
+            skipped.append({"scenario": scenario.name, "similar_to": match.name, "similarity": match.similarity})
+            continue
+        if content is not original:
+            print(f"🧬 Mutated {scenario.name}: {match.similarity:.2f} similar to {match.name}")
+            mutated.append({"scenario": scenario.name, "similar_to": match.name, "similarity": match.similarity})
+            scenario = Scenario(f"{scenario.name}~mutated", scenario.csv_file, scenario.threshold, content)
+        memory.remember(scenario.name, scenario_text(content, scenario.threshold))
+        kept.append(scenario)
+    return {"scenarios": kept, "skipped": skipped, "mutated": mutated}
+
+async def run_scenarios(
+    scenarios: List[Scenario],
+    max_concurrency: int = 1,
+    memory: Optional[ScenarioMemory] = None,
+    on_duplicate: str = "skip",
+    coverage: Optional[CoverageTracker] = None,
+    target_uncovered: bool = False
+) -> Dict[str, Any]:
+    """Run every scenario, at most `max_concurrency` at a time, and aggregate
+    the results in scenario order. With `target_uncovered`, a generated
+    scenario is appended for every coverage region still unexercised"""
+    dedup = {"skipped": [], "mutated": []}
+    if memory is not None:
+        dedup = deduplicate(scenarios, memory, on_duplicate)
+        scenarios = dedup["scenarios"]
+    if coverage is not None:
+        for scenario in scenarios:
+            coverage.observe(scenario.name, scenario.csv_content or Path(scenario.csv_file).read_text(),
+                             scenario.threshold)
+        if target_uncovered:
+            threshold = scenarios[0].threshold if scenarios else 1000
+            for region in coverage.uncovered():
+                content = targeted_csv(region, threshold)
+                print(f"🎯 Targeting uncovered region: {region}")
+                targeted = Scenario(f"cover_{region}", f"<generated:{region}>", threshold, content)
+                coverage.observe(targeted.name, content, threshold)
+                scenarios = scenarios + [targeted]
+    semaphore = asyncio.Semaphore(max(1, max_concurrency))
+    started = time.monotonic()
+
+    async def bounded(scenario: Scenario) -> Dict[str, Any]:
+        async with semaphore:
+            print(f"▶️  Scenario started: {scenario.name}")
+            entry = await run_scenario(scenario)
+            print(f"⏹️  Scenario finished: {scenario.name} ({entry['elapsed_seconds']}s)")
+            return entry
+
+    entries = await asyncio.gather(*(bounded(scenario) for scenario in scenarios))
+    wall_time = time.monotonic() - started
+    serial_time = sum(entry["elapsed_seconds"] for entry in entries)
+    return {
+        "max_concurrency": max_concurrency,
+        "wall_time_seconds": round(wall_time, 2),
+        "summed_scenario_seconds": round(serial_time, 2),
+        "passed": sum(1 for entry in entries if entry["result"].get("success")),
+        "failed": sum(1 for entry in entries if not entry["result"].get("success")),
+        "skipped_duplicates": dedup["skipped"],
+        "mutated_duplicates": dedup["mutated"],
+        "coverage": coverage.summary() if coverage is not None else None,
+        "scenarios": entries
+    }
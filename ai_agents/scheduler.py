@@ -0,0 +1,199 @@
+"""
+Recurring attestations
+Runs YAML workflows (see dag.py) on cron schedules, appends every run to a
+JSON-lines history, and raises alerts for failed or missed runs
+
+    history: .zaik/schedule_history.jsonl
+    jobs:
+      nightly_export:
+        cron: "0 2 * * *"          # minute hour day-of-month month day-of-week
+        workflow: workflows/nightly.yaml
+"""
+import asyncio
+import json
+import os
+import signal
+import sys
+from dataclasses import dataclass
+from datetime import datetime, timedelta
+from pathlib import Path
+from typing import Dict, List, Optional, Set
+
+import yaml
+
+from .dag import DagEngine, WorkflowDag
+
+class CronExpression:
+    """Five-field cron expression supporting `*`, `a-b`, `a,b` and `/step`"""
+
+    RANGES = [(0, 59), (0, 23), (1, 31), (1, 12), (0, 6)]
+
+    def __init__(self, expression: str):
+        fields = expression.split()
+        if len(fields) != 5:
+            raise ValueError(f"cron expression needs 5 fields, got {len(fields)}: '{expression}'")
+        self.expression = expression
+        self.minutes, self.hours, self.days, self.months, self.weekdays = (
+            self._parse_field(field, low, high) for field, (low, high) in zip(fields, self.RANGES)
+        )
+        # Like cron, a restricted day-of-month and day-of-week match either
+        self._days_any = fields[2] == "*"
+        self._weekdays_any = fields[4] == "*"
+
+    @staticmethod
+    def _parse_field(field: str, low: int, high: int) -> Set[int]:
+        values = set()
+        for part in field.split(","):
+            base, _, step = part.partition("/")
+            if base == "*":
+                start, end = low, high
+            elif "-" in base:
+                start, end = (int(v) for v in base.split("-"))
+            else:
+                start = end = int(base)
+                if step:
+                    end = high
+            if not low <= start <= end <= high:
+                raise ValueError(f"cron field '{field}' is outside {low}-{high}")
+            values.update(range(start, end + 1, int(step) if step else 1))
+        return values
+
+    def matches(self, moment: datetime) -> bool:
+        # cron counts Sunday as 0, Python's weekday() counts Monday as 0
+        weekday = (moment.weekday() + 1) % 7
+        day_ok = moment.day in self.days
+        weekday_ok = weekday in self.weekdays
+        if self._days_any or self._weekdays_any:
+            day_matches = day_ok and weekday_ok
+        else:
+            day_matches = day_ok or weekday_ok
+        return (
+            moment.minute in self.minutes
+            and moment.hour in self.hours
+            and moment.month in self.months
+            and day_matches
+        )
+
+    def next_after(self, moment: datetime) -> datetime:
+        candidate = moment.replace(second=0, microsecond=0) + timedelta(minutes=1)
+        # Every expression fires at least once in four years (Feb 29)
+        for _ in range(4 * 366 * 24 * 60):
+            if self.matches(candidate):
+                return candidate
+            candidate += timedelta(minutes=1)
+        raise ValueError(f"cron expression never fires: '{self.expression}'")
+
+@dataclass
+class Job:
+    name: str
+    cron: CronExpression
+    workflow: str
+
+class Scheduler:
+    def __init__(self, jobs: List[Job], history_path: str, engine: Optional[DagEngine] = None):
+        self.jobs = jobs
+        self.history_path = Path(history_path)
+        self.engine = engine or DagEngine()
+        self.stopping = asyncio.Event()
+
+    @classmethod
+    def load(cls, path: str) -> "Scheduler":
+        spec = yaml.safe_load(Path(path).read_text())
+        jobs = [
+            Job(name, CronExpression(job["cron"]), job["workflow"])
+            for name, job in (spec.get("jobs") or {}).items()
+        ]
+        return cls(jobs, spec.get("history", ".zaik/schedule_history.jsonl"))
+
+    def _record(self, entry: Dict):
+        self.history_path.parent.mkdir(parents=True, exist_ok=True)
+        with self.history_path.open("a") as history:
+            history.write(json.dumps(entry) + "\n")
+            history.flush()
+            os.fsync(history.fileno())
+
+    def request_stop(self, reason: str = "stop requested"):
+        """Start no new runs; the run in progress is allowed to finish"""
+        if not self.stopping.is_set():
+            print(f"🛑 {reason}; draining the run in progress before exiting")
+            self.stopping.set()
+
+    def install_signal_handlers(self):
+        """Drain on SIGINT/SIGTERM instead of dying mid-proof"""
+        loop = asyncio.get_running_loop()
+        for sig in (signal.SIGINT, signal.SIGTERM):
+            loop.add_signal_handler(sig, self.request_stop, f"Received {sig.name}")
+
+    def _alert(self, job: Job, status: str, detail: str):
+        print(f"🚨 {job.name}: {status} - {detail}", file=sys.stderr)
+
+    def last_runs(self) -> Dict[str, datetime]:
+        """Most recent scheduled time recorded for each job"""
+        runs: Dict[str, datetime] = {}
+        if not self.history_path.exists():
+            return runs
+        for line in self.history_path.read_text().splitlines():
+            entry = json.loads(line)
+            scheduled = datetime.fromisoformat(entry["scheduled_for"])
+            if entry["job"] not in runs or scheduled > runs[entry["job"]]:
+                runs[entry["job"]] = scheduled
+        return runs
+
+    def record_missed(self, job: Job, since: datetime, until: datetime) -> int:
+        """Alert on and record every slot of `job` in (since, until)"""
+        missed = 0
+        slot = job.cron.next_after(since)
+        while slot < until:
+            missed += 1
+            self._record({"job": job.name, "scheduled_for": slot.isoformat(), "status": "missed"})
+            slot = job.cron.next_after(slot)
+        if missed:
+            self._alert(job, "missed", f"{missed} run(s) between {since.isoformat()} and {until.isoformat()}")
+        return missed
+
+    async def run_job(self, job: Job, scheduled_for: datetime) -> str:
+        started = datetime.now()
+        print(f"⏰ Running {job.name} (scheduled for {scheduled_for.isoformat()})")
+        try:
+            results = await self.engine.run(WorkflowDag.load(job.workflow))
+            failed = [name for name, result in results.items() if result.status != "passed"]
+            status = "failed" if failed else "passed"
+            detail = {"failed_nodes": failed}
+        except Exception as e:
+            status, detail = "failed", {"error": str(e)}
+        self._record({
+            "job": job.name,
+            "scheduled_for": scheduled_for.isoformat(),
+            "started_at": started.isoformat(),
+            "finished_at": datetime.now().isoformat(),
+            "status": status,
+            **detail
+        })
+        if status == "failed":
+            self._alert(job, "failed", json.dumps(detail))
+        return status
+
+    async def run_forever(self):
+        now = datetime.now()
+        last = self.last_runs()
+        due: Dict[str, datetime] = {}
+        for job in self.jobs:
+            # Slots that passed while the scheduler was not running
+            if job.name in last:
+                self.record_missed(job, last[job.name], now)
+            due[job.name] = job.cron.next_after(now)
+            print(f"📅 {job.name}: next run at {due[job.name].isoformat()}")
+
+        while self.jobs and not self.stopping.is_set():
+            job = min(self.jobs, key=lambda j: due[j.name])
+            delay = max(0.0, (due[job.name] - datetime.now()).total_seconds())
+            try:
+                await asyncio.wait_for(self.stopping.wait(), timeout=delay)
+                break
+            except asyncio.TimeoutError:
+                pass
+            await self.run_job(job, due[job.name])
+            # A run that overran later slots counts them as missed
+            now = datetime.now()
+            self.record_missed(job, due[job.name], now)
+            due[job.name] = job.cron.next_after(max(now, due[job.name]))
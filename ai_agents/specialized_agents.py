@@ -7,12 +7,15 @@ from dataclasses import dataclass
 import json
 
 from .openai_client import OpenAIClient, AgentMessage, AgentResponse
+from .agent_models import AgentType
+from .csv_guardrails import extract_csv_payload
+from .claims import parse_claims, prose_around
 
 class DataQualityAgent:
     """Specialized agent for data quality assessment"""
     
     def __init__(self):
-        self.client = OpenAIClient()
+        self.client = OpenAIClient(agent=AgentType.DATA_QUALITY)
         self.system_prompt = """
         You are a Data Quality Assessment Agent. Your expertise is in:
         1. Detecting data anomalies and outliers
@@ -78,7 +81,7 @@ class SecurityAgent:
     """Specialized agent for security assessment"""
     
     def __init__(self):
-        self.client = OpenAIClient()
+        self.client = OpenAIClient(agent=AgentType.SECURITY)
         self.system_prompt = """
         You are a Security Assessment Agent. Your role is to:
         1. Identify potential security risks in data processing
@@ -140,7 +143,7 @@ class BusinessLogicAgent:
     """Specialized agent for business logic validation"""
     
     def __init__(self):
-        self.client = OpenAIClient()
+        self.client = OpenAIClient(agent=AgentType.BUSINESS_LOGIC)
         self.system_prompt = """
         You are a Business Logic Validation Agent. Your expertise is in:
         1. Validating business rules and constraints
@@ -198,7 +201,7 @@ class RiskAssessmentAgent:
     """Specialized agent for comprehensive risk assessment"""
     
     def __init__(self):
-        self.client = OpenAIClient()
+        self.client = OpenAIClient(agent=AgentType.RISK_ASSESSMENT)
         self.system_prompt = """
         You are a Risk Assessment Agent. Your role is to:
         1. Analyze overall system and process risks
@@ -269,4 +272,52 @@ class RiskAssessmentAgent:
         """Determine if mitigation is required"""
         content_lower = content.lower()
         mitigation_keywords = ["mitigation", "action required", "immediate attention", "high risk"]
-        return any(keyword in content_lower for keyword in mitigation_keywords)
\ No newline at end of file
+        return any(keyword in content_lower for keyword in mitigation_keywords)
+
+class CsvGeneratorAgent:
+    """Generates synthetic CSV test data"""
+    
+    def __init__(self):
+        self.client = OpenAIClient(agent=AgentType.CSV_GENERATOR)
+        self.system_prompt = """
+        You are a Test Data Generator Agent. You produce CSV files that:
+        1. Start with a header row
+        2. Use unsigned integers in numeric columns
+        3. Match the requested description and row count
+        
+        Respond with the CSV, then one sentence stating how many data rows
+        it has and what column A sums to.
+        """
+    
+    async def generate_csv_data(self, description: str, rows: int = 10) -> Dict[str, Any]:
+        """
+        Generate CSV matching `description`; the raw response is kept for
+        audit. What the model says about the data outside the CSV is parsed
+        into `claims` (see `ai_agents.claims`), to be checked once proven
+        """
+        
+        prompt = f"""
+        Generate a CSV file with {rows} data rows.
+        
+        Description: {description}
+        
+        Column A must hold unsigned integers.
+        """
+        
+        messages = [AgentMessage(role="user", content=prompt)]
+        response = await self.client.chat_completion(
+            messages=messages,
+            system_prompt=self.system_prompt,
+            temperature=0.3
+        )
+        extracted = extract_csv_payload(response.content)
+        prose = "" if extracted.method == "verbatim" else prose_around(extracted.raw, extracted.payload)
+        
+        return {
+            "agent": "csv_generator",
+            "csv": extracted.payload,
+            "claims": parse_claims(prose),
+            "raw_response": extracted.raw,
+            "extraction": extracted.method,
+            "prompt_hash": response.prompt_hash
+        }
@@ -0,0 +1,75 @@
+"""
+Transcripts of interactive sessions
+Every command typed into `ai_interactive.py` is appended to a JSON Lines
+transcript as soon as it finishes: the command line, the full result and
+the part of it expected to come out the same when run again (hashes, sums,
+flags, the prompt hash of an AI question). Replaying a transcript re-runs
+its commands and compares those parts, so an exploratory session can be
+kept as a regression test. AI answers are not compared; replay under a
+cassette (see `ai_agents.cassette`) to reproduce them too
+"""
+import json
+import os
+from datetime import datetime
+from pathlib import Path
+from typing import Any, Dict, List, Optional, Tuple, Union
+
+SCHEMA = "zaik.transcript.v1"
+DEFAULT_DIR = ".zaik/transcripts"
+
+class Transcript:
+    def __init__(self, path: Union[str, Path]):
+        self.path = Path(path)
+        self.commands = 0
+
+    @classmethod
+    def start(cls, directory: Optional[Union[str, Path]] = None) -> "Transcript":
+        """A new transcript named after the current time, in `directory`
+        (default ZAIK_TRANSCRIPT_DIR, else .zaik/transcripts)"""
+        directory = Path(directory or os.getenv("ZAIK_TRANSCRIPT_DIR", DEFAULT_DIR))
+        directory.mkdir(parents=True, exist_ok=True)
+        started = datetime.now()
+        path = directory / f"session-{started.strftime('%Y%m%d-%H%M%S')}.jsonl"
+        # Two sessions started within the same second get separate files
+        suffix = 1
+        while path.exists():
+            suffix += 1
+            path = directory / f"session-{started.strftime('%Y%m%d-%H%M%S')}-{suffix}.jsonl"
+        transcript = cls(path)
+        transcript._append({"schema": SCHEMA, "started_at": started.isoformat()})
+        return transcript
+
+    def _append(self, entry: Dict[str, Any]):
+        with self.path.open("a") as f:
+            f.write(json.dumps(entry, default=str) + "\n")
+
+    def record(self, line: str, result: Dict[str, Any], expect: Dict[str, Any]):
+        """Append one command with its result and the fields replay checks"""
+        self.commands += 1
+        self._append({
+            "seq": self.commands,
+            "at": datetime.now().isoformat(),
+            "line": line,
+            "result": result,
+            "expect": expect
+        })
+
+def load(path: Union[str, Path]) -> Tuple[Dict[str, Any], List[Dict[str, Any]]]:
+    """The header and command entries of a transcript"""
+    lines = [json.loads(line) for line in Path(path).read_text().splitlines() if line.strip()]
+    if not lines or lines[0].get("schema") != SCHEMA:
+        raise ValueError(f"{path}: not a {SCHEMA} transcript")
+    return lines[0], lines[1:]
+
+def differences(recorded: Dict[str, Any], replayed: Dict[str, Any], where: str = "") -> List[str]:
+    """Where `replayed` disagrees with the `recorded` expectations, as
+    dotted paths with both values"""
+    found = []
+    for key, expected in recorded.items():
+        path = f"{where}.{key}" if where else key
+        actual = replayed.get(key)
+        if isinstance(expected, dict) and isinstance(actual, dict):
+            found += differences(expected, actual, path)
+        elif json.loads(json.dumps(actual, default=str)) != expected:
+            found.append(f"{path}: recorded {expected!r}, replayed {actual!r}")
+    return found
@@ -0,0 +1,216 @@
+#!/usr/bin/env python3
+"""
+Interactive shell for exploring CSV files with the AI agents and RISC Zero
+
+Every session is saved as a transcript under .zaik/transcripts/ (see
+ai_agents/transcript.py); `replay` runs one again and reports what changed.
+
+    python3 ai_interactive.py
+    python3 ai_interactive.py --replay .zaik/transcripts/session-20260101-120000.jsonl
+"""
+
+import argparse
+import asyncio
+import cmd
+import hashlib
+import shlex
+import sys
+from pathlib import Path
+from typing import Any, Dict, List, Optional, Tuple
+
+sys.path.append(str(Path(__file__).parent))
+
+from ai_agents import transcript
+from ai_agents.agent_models import AgentType
+
+class Session(cmd.Cmd):
+    intro = "🤖 ZAIK interactive shell. Type help or ? to list commands.\n"
+    prompt = "zaik> "
+
+    def __init__(self, record: Optional[transcript.Transcript] = None):
+        super().__init__()
+        self.transcript = record
+        self.csv: Optional[str] = None
+        self._client = None
+        self._verifier = None
+
+    @property
+    def client(self):
+        if self._client is None:
+            from ai_agents.openai_client import OpenAIClient
+            self._client = OpenAIClient(agent=AgentType.CSV_ANALYZER)
+        return self._client
+
+    @property
+    def verifier(self):
+        if self._verifier is None:
+            from ai_agents.risc0_verifier import RISC0Verifier
+            self._verifier = RISC0Verifier()
+        return self._verifier
+
+    def emptyline(self):
+        pass
+
+    def default(self, line: str):
+        print(f"❌ Unknown command '{line.split()[0]}'; type help for the list")
+
+    def execute(self, name: str, arg: str, record: bool = True) -> Tuple[Dict[str, Any], Dict[str, Any]]:
+        """Run one command and save it to the transcript; returns its result
+        and the fields replay checks"""
+        try:
+            result, expect = getattr(self, f"_{name}")(arg.strip())
+        except Exception as e:
+            print(f"❌ {e}")
+            result, expect = {"error": str(e)}, {"failed": True}
+        if record and self.transcript:
+            self.transcript.record(f"{name} {arg}".strip(), result, expect)
+        return result, expect
+
+    def do_load(self, arg: str):
+        """load PATH: read a CSV file for the following commands"""
+        self.execute("load", arg)
+
+    def do_ask(self, arg: str):
+        """ask QUESTION: ask the CSV analyzer about the loaded CSV"""
+        self.execute("ask", arg)
+
+    def do_prove(self, arg: str):
+        """prove [THRESHOLD]: prove column A's sum of the loaded CSV (threshold 1000)"""
+        self.execute("prove", arg)
+
+    def do_workflow(self, arg: str):
+        """workflow [THRESHOLD]: run the full agent workflow on the loaded CSV"""
+        self.execute("workflow", arg)
+
+    def do_replay(self, arg: str):
+        """replay PATH: run a saved transcript's commands again and report what changed"""
+        self.execute("replay", arg)
+
+    def _require_csv(self):
+        if self.csv is None:
+            raise ValueError("no CSV loaded; use load PATH first")
+
+    def _threshold(self, arg: str) -> int:
+        return int(arg) if arg else 1000
+
+    def _load(self, arg: str):
+        if not arg:
+            raise ValueError("usage: load PATH")
+        path = shlex.split(arg)[0]
+        self.csv = Path(path).read_text()
+        rows = len([line for line in self.csv.strip().split("\n")[1:] if line.strip()])
+        print(f"📄 Loaded {path}: {rows} data rows")
+        result = {"path": path, "rows": rows, "csv_hash": hashlib.sha256(self.csv.encode()).hexdigest()}
+        return result, {"rows": rows, "csv_hash": result["csv_hash"]}
+
+    def _ask(self, arg: str):
+        self._require_csv()
+        if not arg:
+            raise ValueError("usage: ask QUESTION")
+        from ai_agents.openai_client import AgentMessage, AgentPrompts
+        from ai_agents.prompt_budget import condense
+        data = condense(self.csv, self.client.prompt_budget // 2)
+        messages = [AgentMessage(role="user", content=f"CSV data:\n{data}\n\nQuestion: {arg}")]
+        response = asyncio.run(self.client.chat_completion(
+            messages=messages, system_prompt=AgentPrompts.CSV_ANALYZER, agent=AgentType.CSV_ANALYZER
+        ))
+        print(f"💬 {response.content}")
+        if response.confidence is not None:
+            print(f"🎯 Confidence: {response.confidence}")
+        result = {"answer": response.content, "confidence": response.confidence, "prompt_hash": response.prompt_hash}
+        return result, {"prompt_hash": response.prompt_hash}
+
+    def _prove(self, arg: str):
+        self._require_csv()
+        report = self.verifier.verify_csv_data(self.csv, None, self._threshold(arg))
+        print(f"🔒 Verification: {'PASSED' if report['verification_successful'] else 'FAILED'}")
+        if report.get("csv_details", {}).get("column_a_sum") is not None:
+            print(f"📊 Column A Sum: {report['csv_details']['column_a_sum']}")
+        if report.get("error"):
+            print(f"⚠️  {report['error']}")
+        expect = {
+            key: report.get(key)
+            for key in ("verification_successful", "risc0_proof_valid", "business_logic_satisfied", "exit_code")
+        }
+        expect["csv_details"] = report.get("csv_details")
+        expect["failure_reason"] = (report.get("failure_reason") or {}).get("reason")
+        return report, expect
+
+    def _workflow(self, arg: str):
+        self._require_csv()
+        from ai_agents.agent_workflow import AgentWorkflow
+        results = asyncio.run(AgentWorkflow().run_csv_analysis_workflow(self.csv, self._threshold(arg)))
+        if "final_decision" in results:
+            print(f"🎯 Final Decision: {results['final_decision'].upper()}")
+        else:
+            print(f"❌ Workflow failed: {results.get('error')}")
+        # The decision rests on AI confidence; the proof does not
+        expect = {
+            "csv_details": results.get("csv_details"),
+            "verification_guarantees": results.get("verification_guarantees")
+        }
+        return results, expect
+
+    def _replay(self, arg: str):
+        if not arg:
+            raise ValueError("usage: replay PATH")
+        path = shlex.split(arg)[0]
+        changed = replay(self, path)
+        return {"transcript": path, "changed": changed}, {"changed": changed}
+
+    def do_transcript(self, arg: str):
+        """transcript: show where this session is being saved"""
+        print(f"📝 {self.transcript.path}" if self.transcript else "📝 This session is not being saved")
+
+    def do_quit(self, arg: str):
+        """quit: leave the shell"""
+        if self.transcript:
+            print(f"📝 Session saved to {self.transcript.path} ({self.transcript.commands} commands)")
+        return True
+
+    do_exit = do_quit
+
+    def do_EOF(self, arg: str):
+        print()
+        return self.do_quit(arg)
+
+def replay(session: Session, path: str) -> List[int]:
+    """Re-run the commands of the transcript at `path` in `session`; returns
+    the numbers of the commands whose checked fields changed. Only the replay
+    itself goes into the session's transcript"""
+    _, entries = transcript.load(path)
+    print(f"🔁 Replaying {len(entries)} commands from {path}")
+    changed = []
+    for entry in entries:
+        name, _, arg = entry["line"].partition(" ")
+        # A replay inside a transcript is not replayed again
+        if name == "replay":
+            continue
+        print(f"\n{Session.prompt}{entry['line']}")
+        _, expect = session.execute(name, arg, record=False)
+        found = transcript.differences(entry["expect"], expect)
+        for difference in found:
+            print(f"   ❌ {difference}")
+        if found:
+            changed.append(entry["seq"])
+    print(f"\n{'❌' if changed else '✅'} {len(changed)} of {len(entries)} commands changed"
+          + (f": {', '.join(map(str, changed))}" if changed else ""))
+    return changed
+
+def main():
+    parser = argparse.ArgumentParser(description=__doc__, formatter_class=argparse.RawDescriptionHelpFormatter)
+    parser.add_argument("--replay", metavar="TRANSCRIPT",
+                        help="replay a saved session and exit 1 if any checked result changed")
+    parser.add_argument("--transcript-dir", help="where transcripts are saved (default .zaik/transcripts)")
+    parser.add_argument("--no-transcript", action="store_true", help="do not save this session")
+    args = parser.parse_args()
+
+    record = None if args.no_transcript else transcript.Transcript.start(args.transcript_dir)
+    session = Session(record)
+    if args.replay:
+        _, expect = session.execute("replay", shlex.quote(args.replay))
+        sys.exit(1 if expect.get("changed") or expect.get("failed") else 0)
+    session.cmdloop()
+
+if __name__ == "__main__":
+    main()
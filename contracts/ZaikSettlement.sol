@@ -0,0 +1,37 @@
+// SPDX-License-Identifier: Apache-2.0
+pragma solidity ^0.8.20;
+
+import {ZaikJournal} from "./ZaikJournal.sol";
+import {ZaikVerifier} from "./ZaikVerifier.sol";
+
+/// @notice Example settlement for the on-chain path: pays a fixed reward to
+/// the beneficiary once per dataset whose receipt the generated
+/// `ZaikVerifier` accepts. `relay` submits to `settle` and waits for
+/// `Settled`. Generate `ZaikJournal.sol` and `ZaikVerifier.sol` next to
+/// this file with `host codegen --out-dir contracts`.
+contract ZaikSettlement {
+    ZaikVerifier public immutable zaik;
+    address payable public immutable beneficiary;
+    uint256 public immutable reward;
+
+    /// @notice Datasets already settled, by CSV hash.
+    mapping(bytes32 => bool) public settled;
+
+    event Settled(bytes32 indexed csvHash, bytes32 journalDigest, address relayer);
+
+    constructor(ZaikVerifier _zaik, address payable _beneficiary, uint256 _reward) payable {
+        zaik = _zaik;
+        beneficiary = _beneficiary;
+        reward = _reward;
+    }
+
+    /// @notice Settle the dataset a verified, policy-compliant journal covers.
+    function settle(bytes calldata seal, bytes calldata journal) external {
+        ZaikJournal.Journal memory j = zaik.check(seal, journal);
+        require(!settled[j.csvHash], "ZaikSettlement: already settled");
+        settled[j.csvHash] = true;
+        emit Settled(j.csvHash, sha256(journal), msg.sender);
+        (bool paid, ) = beneficiary.call{value: reward}("");
+        require(paid, "ZaikSettlement: payment failed");
+    }
+}
@@ -0,0 +1,93 @@
+//! Empty and unparseable cell accounting.
+//!
+//! Sums, counts and invariants only see cells that parse as `u64`; anything
+//! else is skipped. The guest also counts, per column, the cells it skipped
+//! and why, so a verifier can tell a clean file from one whose bad rows
+//! quietly vanished from the sum.
+
+use crate::dialect::Dialect;
+use crate::schema::{JournalBytes, JournalValue, ToJournalValue};
+use alloc::format;
+use alloc::vec;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+/// Most columns accounted for; cells of later columns are not counted.
+pub const MAX_ACCOUNTED_COLUMNS: usize = 32;
+
+/// Cells of one column that did not parse as `u64`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CellCounts {
+    /// Missing from a short row, or blank.
+    pub empty: u64,
+    /// Present and not blank, but not a `u64`.
+    pub unparseable: u64,
+}
+
+impl CellCounts {
+    /// Percentage of `rows` cells that were empty, rounded up so a single
+    /// bad cell never reads as 0%.
+    pub fn empty_percent(&self, rows: u64) -> u64 {
+        percent(self.empty, rows)
+    }
+
+    /// As [`empty_percent`](Self::empty_percent), for unparseable cells.
+    pub fn unparseable_percent(&self, rows: u64) -> u64 {
+        percent(self.unparseable, rows)
+    }
+}
+
+fn percent(cells: u64, rows: u64) -> u64 {
+    if rows == 0 {
+        return 0;
+    }
+    (cells as u128 * 100).div_ceil(rows as u128) as u64
+}
+
+impl ToJournalValue for CellCounts {
+    fn to_journal_value(&self) -> JournalValue {
+        JournalValue::Text(format!("{} empty, {} unparseable", self.empty, self.unparseable))
+    }
+}
+
+impl JournalBytes for CellCounts {
+    const LAYOUT: &'static str = "u64 LE empty, u64 LE unparseable";
+
+    fn write_bytes(&self, out: &mut Vec<u8>) {
+        self.empty.write_bytes(out);
+        self.unparseable.write_bytes(out);
+    }
+
+    fn read_bytes(input: &mut &[u8]) -> Option<Self> {
+        Some(CellCounts {
+            empty: u64::read_bytes(input)?,
+            unparseable: u64::read_bytes(input)?,
+        })
+    }
+}
+
+/// Number of data rows in `csv_data` and the counts of each column, up to
+/// [`MAX_ACCOUNTED_COLUMNS`]. The columns are those of the header, or of
+/// the first row when the dialect has none.
+pub fn count(csv_data: &str, dialect: Dialect) -> (u64, Vec<CellCounts>) {
+    let Some(first) = csv_data.lines().next() else {
+        return (0, Vec::new());
+    };
+    let width = (0..MAX_ACCOUNTED_COLUMNS)
+        .take_while(|&column| dialect.field(first, column).is_some())
+        .count();
+    let mut counts = vec![CellCounts::default(); width];
+    let mut rows = 0;
+    for row in dialect.rows(csv_data) {
+        rows += 1;
+        for (column, counts) in counts.iter_mut().enumerate() {
+            match dialect.field(row, column) {
+                Some(cell) if cell.trim().is_empty() => counts.empty += 1,
+                None => counts.empty += 1,
+                Some(cell) if cell.parse::<u64>().is_err() => counts.unparseable += 1,
+                Some(_) => {}
+            }
+        }
+    }
+    (rows, counts)
+}
@@ -0,0 +1,301 @@
+//! Content-defined chunking, so a file that mostly repeats an earlier one
+//! only needs its changed chunks proven again.
+//!
+//! The data rows (everything after the header line) are cut with FastCDC,
+//! each cut moved forward to the end of its line. Cut points depend only on
+//! the bytes around them, so an edit changes the chunks it touches and every
+//! other chunk hashes, and proves, exactly as before. Each chunk is proven
+//! on its own with [`process_chunk`]; [`compose`] then checks the chunk
+//! journals and combines them into one [`AgentResult`] for the whole file.
+//!
+//! Column A's hash and the file hash cannot be chained through independent
+//! chunks, so in this mode `csv_hash` is the digest of the chunk manifest
+//! ([`csv_hash`]) and `column_a_hash` a digest over the chunks' column
+//! hashes. Both are recomputable from the file. There is no row Merkle root
+//! (it is all zero), no extra invariants, no inline data, no aux tables, no
+//! row limit, no dialect but the default and no datasets.
+
+use crate::dialect::Dialect;
+use crate::shape;
+use crate::{
+    decimal, AgentResult, ColumnAggregate, ColumnHashMode, InlineCsv, ProcessingOptions, SumBound, SumOpening,
+    SEMANTICS_VERSION,
+};
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// No cut is made before this many bytes into a chunk.
+pub const MIN_SIZE: usize = 16 * 1024;
+/// Chunks are normalized around this size.
+pub const AVG_SIZE: usize = 64 * 1024;
+/// A cut is forced here, then moved to the end of the line.
+pub const MAX_SIZE: usize = 256 * 1024;
+
+/// FastCDC masks: harder to match before `AVG_SIZE`, easier after. The
+/// gear hash shifts left, so its top bits cover the most recent bytes.
+const MASK_SMALL: u64 = !0 << (64 - 18);
+const MASK_LARGE: u64 = !0 << (64 - 14);
+
+const GEAR: [u64; 256] = gear_table();
+
+/// Fixed pseudo-random gear values (splitmix64), so every build cuts the
+/// same file at the same places.
+const fn gear_table() -> [u64; 256] {
+    let mut table = [0u64; 256];
+    let mut state: u64 = 0x7a61_696b_6364_6331;
+    let mut i = 0;
+    while i < 256 {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        table[i] = z ^ (z >> 31);
+        i += 1;
+    }
+    table
+}
+
+/// FastCDC cut point for the chunk starting at `data[0]`.
+fn next_cut(data: &[u8]) -> usize {
+    if data.len() <= MIN_SIZE {
+        return data.len();
+    }
+    let normal = data.len().min(AVG_SIZE);
+    let max = data.len().min(MAX_SIZE);
+    let mut hash: u64 = 0;
+    let mut i = MIN_SIZE;
+    while i < max {
+        hash = (hash << 1).wrapping_add(GEAR[data[i] as usize]);
+        let mask = if i < normal { MASK_SMALL } else { MASK_LARGE };
+        if hash & mask == 0 {
+            return i + 1;
+        }
+        i += 1;
+    }
+    max
+}
+
+/// The header line (with its newline) and the data rows cut into chunks
+/// that each end at a line boundary.
+pub fn split(csv_data: &str) -> (&str, Vec<&str>) {
+    let header_end = csv_data.find('\n').map_or(csv_data.len(), |i| i + 1);
+    let (header, mut rest) = csv_data.split_at(header_end);
+    let mut chunks = Vec::new();
+    while !rest.is_empty() {
+        let cut = next_cut(rest.as_bytes());
+        let end = match rest.as_bytes()[cut - 1..].iter().position(|&b| b == b'\n') {
+            Some(newline) => cut + newline,
+            None => rest.len(),
+        };
+        let (chunk, tail) = rest.split_at(end);
+        chunks.push(chunk);
+        rest = tail;
+    }
+    (header, chunks)
+}
+
+/// Input for proving one chunk.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChunkInput {
+    /// Whole data rows, each ending in a newline except possibly the last
+    /// chunk's final row. Sent as a frame, like a whole CSV.
+    #[serde(skip)]
+    pub data: String,
+}
+
+/// Journal of a chunk proof.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkResult {
+    /// SHA-256 of the chunk's bytes.
+    pub chunk_hash: [u8; 32],
+    /// Rows in the chunk, parseable or not.
+    pub rows: usize,
+    /// Rows whose column A parsed.
+    pub count: usize,
+    pub sum: u64,
+    /// Transcript of the chunk's column A values: each canonical decimal,
+    /// prefixed with its length.
+    pub column_a_hash: [u8; 32],
+}
+
+/// Aggregate one chunk of data rows (no header).
+pub fn process_chunk(data: &str) -> ChunkResult {
+    let mut sum: u64 = 0;
+    let mut count = 0;
+    let mut rows = 0;
+    let mut buf = [0u8; 20];
+    let mut hasher = Sha256::new();
+    hasher.update(b"zaik.column_a.chunk.v1");
+    for line in data.lines() {
+        rows += 1;
+        let Some(value) = line.split(',').next().and_then(|cell| cell.parse::<u64>().ok()) else {
+            continue;
+        };
+        let bytes = decimal(value, &mut buf);
+        hasher.update((bytes.len() as u32).to_le_bytes());
+        hasher.update(bytes);
+        sum += value;
+        count += 1;
+    }
+    ChunkResult {
+        chunk_hash: Sha256::digest(data.as_bytes()).into(),
+        rows,
+        count,
+        sum,
+        column_a_hash: hasher.finalize().into(),
+    }
+}
+
+/// Digest of a file's header and its chunks' hashes, in order.
+pub fn manifest_hash<'a>(header: &str, chunk_hashes: impl IntoIterator<Item = &'a [u8; 32]>) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(b"zaik.csv.chunked.v1");
+    hasher.update((header.len() as u64).to_le_bytes());
+    hasher.update(header.as_bytes());
+    for chunk_hash in chunk_hashes {
+        hasher.update(chunk_hash);
+    }
+    hasher.finalize().into()
+}
+
+/// The `csv_hash` a chunked proof of `csv_data` commits.
+pub fn csv_hash(csv_data: &str) -> [u8; 32] {
+    let (header, chunks) = split(csv_data);
+    let hashes: Vec<[u8; 32]> = chunks.iter().map(|chunk| Sha256::digest(chunk.as_bytes()).into()).collect();
+    manifest_hash(header, &hashes)
+}
+
+/// Column A across chunks, in order.
+pub fn aggregate(chunks: &[ChunkResult]) -> ColumnAggregate {
+    let mut hasher = Sha256::new();
+    hasher.update(b"zaik.column_a.chunked.v1");
+    for chunk in chunks {
+        hasher.update(chunk.column_a_hash);
+    }
+    ColumnAggregate {
+        sum: chunks.iter().map(|chunk| chunk.sum).sum(),
+        hash: hasher.finalize().into(),
+        count: chunks.iter().map(|chunk| chunk.count).sum(),
+    }
+}
+
+/// Input for combining proven chunks. The guest verifies one receipt from
+/// `chunk_image_id` per entry of `chunks` before composing.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChunkedInput {
+    pub header: String,
+    /// Image the chunk receipts were proven with; committed so a verifier
+    /// can check it is one it trusts.
+    pub chunk_image_id: [u32; 8],
+    pub chunks: Vec<ChunkResult>,
+    pub options: ProcessingOptions,
+    pub sum_bound: SumBound,
+    pub sum_salt: [u8; 32],
+}
+
+/// Journal for the whole file from its chunks' journals.
+pub fn compose(input: &ChunkedInput) -> AgentResult {
+    let aggregate = aggregate(&input.chunks);
+    let opening = SumOpening {
+        sum: aggregate.sum,
+        salt: input.sum_salt,
+    };
+    let sum_disclosed = !input.options.conceal_sum;
+    AgentResult {
+        csv_hash: manifest_hash(&input.header, input.chunks.iter().map(|chunk| &chunk.chunk_hash)),
+        entry_count: aggregate.count,
+        sum_disclosed,
+        column_a_sum: if sum_disclosed { aggregate.sum } else { 0 },
+        column_hash_mode: ColumnHashMode::Chunked,
+        sum_comparison: input.sum_bound.op,
+        sum_threshold: input.sum_bound.threshold,
+        sum_within_bound: input.sum_bound.holds(aggregate.sum),
+        invariants_passed: 0,
+        invariants: Vec::new(),
+        chunk_image_id: input.chunk_image_id,
+        inline_limit: 0,
+        inline_csv: InlineCsv::default(),
+        aux_tables_hash: [0u8; 32],
+        max_rows: 0,
+        truncated: false,
+        dialect: Dialect::CSV,
+        dataset_files: 0,
+        semantics_version: SEMANTICS_VERSION,
+        data_rows: input.chunks.iter().map(|chunk| chunk.rows as u64).sum(),
+        cell_counts: Vec::new(),
+        duplicate_headers: shape::check(&input.header, Dialect::CSV).duplicate_headers,
+        ragged_rows: 0,
+        sum_commitment: opening.commitment(),
+        column_a_hash: aggregate.hash,
+        row_merkle_root: [0u8; 32],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::format;
+
+    /// About 230 KiB of rows that differ enough for FastCDC to find cuts.
+    fn fixed_csv() -> String {
+        let mut csv = String::from("amount,note\n");
+        for row in 0u64..24_000 {
+            let value = row.wrapping_mul(2_654_435_761) % 100_000;
+            csv.push_str(&format!("{},r{}\n", value, row % 97));
+        }
+        csv
+    }
+
+    #[test]
+    fn cuts_fall_on_line_ends_at_fixed_points() {
+        let csv = fixed_csv();
+        let (header, chunks) = split(&csv);
+        assert_eq!(header, "amount,note\n");
+        let lengths: Vec<usize> = chunks.iter().map(|chunk| chunk.len()).collect();
+        assert_eq!(lengths, [72123, 80940, 72897, 8893]);
+        assert_eq!(header.len() + lengths.iter().sum::<usize>(), csv.len());
+        for chunk in &chunks {
+            assert!(chunk.ends_with('\n'));
+            assert!(chunk.len() <= MAX_SIZE);
+        }
+        for chunk in &chunks[..chunks.len() - 1] {
+            assert!(chunk.len() > MIN_SIZE);
+        }
+    }
+
+    #[test]
+    fn an_edit_only_changes_the_chunks_around_it() {
+        let csv = fixed_csv();
+        let edited = csv.replacen("\n4", "\n5", 1);
+        let (_, before) = split(&csv);
+        let (_, after) = split(&edited);
+        assert_ne!(before[0], after[0]);
+        assert_eq!(before[1..], after[1..]);
+    }
+
+    #[test]
+    fn input_up_to_min_size_is_one_chunk() {
+        assert_eq!(next_cut(&[b'x'; MIN_SIZE]), MIN_SIZE);
+        assert_eq!(next_cut(b"1\n"), 2);
+
+        let csv = "a\n1\n2\n3";
+        assert_eq!(split(csv), ("a\n", alloc::vec!["1\n2\n3"]));
+        assert_eq!(split("a\n"), ("a\n", Vec::new()));
+        assert_eq!(split(""), ("", Vec::new()));
+    }
+
+    #[test]
+    fn a_row_longer_than_max_size_is_kept_whole() {
+        let huge = "9".repeat(MAX_SIZE + 1000);
+        assert_eq!(next_cut(huge.as_bytes()), MAX_SIZE);
+
+        let csv = format!("a\n{}\n1\n2\n", huge);
+        let (_, chunks) = split(&csv);
+        assert_eq!(chunks, [format!("{}\n", huge).as_str(), "1\n2\n"]);
+        // With no newline at all, the rest of the file is the last chunk
+        let (_, chunks) = split(&csv[..csv.len() - 5]);
+        assert_eq!(chunks, [huge.as_str()]);
+    }
+}
@@ -0,0 +1,123 @@
+//! One logical dataset split across several CSV files, such as daily
+//! exports, proven as a single CSV.
+//!
+//! The guest checks every file against its SHA-256 and proves the files'
+//! concatenation ([`concat`]). The journal's `csv_hash` is then the
+//! dataset's [`root`], a Merkle root over the file hashes in order, and
+//! `dataset_files` counts the files, so one receipt attests the whole set
+//! and a verifier holding the manifest can recompute what it covers.
+//! `chunked` mode does not support datasets.
+
+use crate::dialect::Dialect;
+use crate::{invariant, merkle_root, AgentResult, ColumnHashMode, CsvProcessingInput, InputError};
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// One file of a dataset. Only the hash goes through serde; the bytes
+/// follow the input as a frame, like a whole CSV.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DatasetFile {
+    /// SHA-256 of the file's CSV bytes.
+    pub hash: [u8; 32],
+    #[serde(skip)]
+    pub data: String,
+}
+
+impl DatasetFile {
+    pub fn new(data: String) -> Self {
+        Self {
+            hash: crate::csv_hash(&data),
+            data,
+        }
+    }
+}
+
+/// Input for proving a dataset. `input` carries the settings of a
+/// single-file run, with `csv_hash` set to the dataset's [`root`] and no
+/// `csv_data`; the files take its place.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatasetInput {
+    pub files: Vec<DatasetFile>,
+    pub input: CsvProcessingInput,
+}
+
+impl DatasetInput {
+    /// Check what [`process`] would reject, without processing the rows.
+    pub fn validate(&self) -> Result<(), InputError> {
+        let settings = &self.input;
+        if settings.options.column_hash == ColumnHashMode::Chunked {
+            return Err(InputError::Unsupported("a dataset in chunked mode"));
+        }
+        if settings.invariants.len() > invariant::MAX_INVARIANTS {
+            return Err(InputError::TooManyInvariants(settings.invariants.len()));
+        }
+        let hashes: Vec<[u8; 32]> = self.files.iter().map(|file| file.hash).collect();
+        let headers_match = self.files.iter().all(|file| {
+            file.data.lines().next() == self.files.first().and_then(|first| first.data.lines().next())
+        });
+        if self.files.is_empty()
+            || self.files.iter().any(|file| crate::csv_hash(&file.data) != file.hash)
+            || root(&hashes) != settings.csv_hash
+            || (settings.options.dialect.has_header && !headers_match)
+        {
+            return Err(InputError::DatasetMismatch);
+        }
+        Ok(())
+    }
+}
+
+/// Merkle root over `file_hashes`, in order, bound to how many there are.
+/// Leaves and nodes are built as for [`row_merkle_root`](crate::row_merkle_root).
+pub fn root(file_hashes: &[[u8; 32]]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(b"zaik.dataset.v1");
+    hasher.update((file_hashes.len() as u32).to_le_bytes());
+    hasher.update(merkle_root(file_hashes.iter().map(|hash| hash.as_slice())));
+    hasher.finalize().into()
+}
+
+/// The files as one CSV: every line of each, ended by `\n`, with the
+/// header of every file after the first left out. `None` if there are no
+/// files or a file's header differs from the first one's.
+pub fn concat(files: &[DatasetFile], dialect: Dialect) -> Option<String> {
+    let header = match files.first()?.data.lines().next() {
+        Some(line) if dialect.has_header => Some(line),
+        _ => None,
+    };
+    let mut csv_data = String::with_capacity(files.iter().map(|file| file.data.len() + 1).sum());
+    for (index, file) in files.iter().enumerate() {
+        let mut lines = file.data.lines();
+        if index > 0 && header.is_some() && lines.next() != header {
+            return None;
+        }
+        for line in lines {
+            csv_data.push_str(line);
+            csv_data.push('\n');
+        }
+    }
+    Some(csv_data)
+}
+
+/// Run the guest computation over a dataset: check every file's hash and
+/// the root, then process the concatenation. Returns `None` if a hash or
+/// the root does not match, the headers differ, or the mode is `chunked`.
+pub fn process(input: &DatasetInput) -> Option<AgentResult> {
+    let settings = &input.input;
+    if settings.options.column_hash == ColumnHashMode::Chunked {
+        return None;
+    }
+    if input.files.iter().any(|file| crate::csv_hash(&file.data) != file.hash) {
+        return None;
+    }
+    let hashes: Vec<[u8; 32]> = input.files.iter().map(|file| file.hash).collect();
+    if root(&hashes) != settings.csv_hash {
+        return None;
+    }
+    let csv_data = concat(&input.files, settings.options.dialect)?;
+    Some(AgentResult {
+        dataset_files: input.files.len() as u32,
+        ..crate::attest(settings, &csv_data)
+    })
+}
@@ -0,0 +1,147 @@
+//! How CSV text splits into rows and fields.
+//!
+//! Every line is a row; the first is the header unless the dialect says
+//! there is none. Fields split on the delimiter except between quotes, and
+//! a field enclosed in quotes is read without them (a doubled quote inside
+//! stays doubled, which never matters for the numbers that get summed).
+//! Quoted fields cannot span lines. The default is the comma-separated,
+//! double-quoted, headed CSV every earlier release assumed, so files
+//! without quotes parse exactly as before.
+
+use crate::schema::{self, JournalBytes, JournalValue, ToJournalValue};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::iter::Skip;
+use core::str::Lines;
+use serde::{Deserialize, Serialize};
+
+/// Delimiter, quote and header of a CSV file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Dialect {
+    /// Field separator, e.g. `,`, `;`, tab or `|`.
+    pub delimiter: u8,
+    /// Encloses fields that contain the delimiter.
+    pub quote: u8,
+    /// Whether the first line names the columns rather than holding data.
+    pub has_header: bool,
+}
+
+impl Default for Dialect {
+    fn default() -> Self {
+        Self::CSV
+    }
+}
+
+impl Dialect {
+    /// Comma-separated, double-quoted, with a header.
+    pub const CSV: Dialect = Dialect {
+        delimiter: b',',
+        quote: b'"',
+        has_header: true,
+    };
+
+    /// Reject a dialect the line-based parser cannot honor.
+    pub fn check(&self) -> Result<(), String> {
+        for (name, byte) in [("delimiter", self.delimiter), ("quote", self.quote)] {
+            if !byte.is_ascii() || byte == b'\n' || byte == b'\r' {
+                return Err(format!("the {} must be one ASCII character other than a line break", name));
+            }
+        }
+        if self.delimiter == self.quote {
+            return Err("the delimiter and the quote must differ".into());
+        }
+        Ok(())
+    }
+
+    /// The data rows of `csv_data`: every line but the header.
+    pub fn rows<'a>(&self, csv_data: &'a str) -> Skip<Lines<'a>> {
+        csv_data.lines().skip(self.has_header as usize)
+    }
+
+    /// Field `index` (zero-based) of `row`, without enclosing quotes.
+    pub fn field<'a>(&self, row: &'a str, index: usize) -> Option<&'a str> {
+        let mut current = 0;
+        let mut start = 0;
+        let mut quoted = false;
+        // Both bytes are ASCII, so every split lands on a char boundary
+        for (at, byte) in row.bytes().enumerate() {
+            if byte == self.quote {
+                quoted = !quoted;
+            } else if byte == self.delimiter && !quoted {
+                if current == index {
+                    return Some(self.unquote(&row[start..at]));
+                }
+                current += 1;
+                start = at + 1;
+            }
+        }
+        (current == index).then(|| self.unquote(&row[start..]))
+    }
+
+    /// Every field of `row` in order, without enclosing quotes, split as
+    /// [`field`](Self::field) splits them.
+    pub fn fields<'a>(&self, row: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        let dialect = *self;
+        let mut rest = Some(row);
+        let mut quoted = false;
+        core::iter::from_fn(move || {
+            let current = rest?;
+            for (at, byte) in current.bytes().enumerate() {
+                if byte == dialect.quote {
+                    quoted = !quoted;
+                } else if byte == dialect.delimiter && !quoted {
+                    rest = Some(&current[at + 1..]);
+                    return Some(dialect.unquote(&current[..at]));
+                }
+            }
+            rest = None;
+            Some(dialect.unquote(current))
+        })
+    }
+
+    fn unquote<'a>(&self, cell: &'a str) -> &'a str {
+        let quote = self.quote as char;
+        cell.strip_prefix(quote).and_then(|inner| inner.strip_suffix(quote)).unwrap_or(cell)
+    }
+}
+
+/// A byte as typed: printable ASCII as itself, anything else escaped.
+fn show(byte: u8) -> String {
+    match byte {
+        b'\t' => "\\t".into(),
+        0x21..=0x7e => format!("{}", byte as char),
+        other => format!("\\x{:02x}", other),
+    }
+}
+
+impl ToJournalValue for Dialect {
+    fn to_journal_value(&self) -> JournalValue {
+        JournalValue::Text(format!(
+            "delimiter '{}', quote '{}', {}",
+            show(self.delimiter),
+            show(self.quote),
+            if self.has_header { "header" } else { "no header" }
+        ))
+    }
+}
+
+impl JournalBytes for Dialect {
+    const LAYOUT: &'static str = "u8 delimiter, u8 quote, u8: 0 no header, 1 header";
+
+    fn write_bytes(&self, out: &mut Vec<u8>) {
+        out.push(self.delimiter);
+        out.push(self.quote);
+        self.has_header.write_bytes(out);
+    }
+
+    fn read_bytes(input: &mut &[u8]) -> Option<Self> {
+        let bytes = schema::take(input, 2)?;
+        let dialect = Dialect {
+            delimiter: bytes[0],
+            quote: bytes[1],
+            has_header: bool::read_bytes(input)?,
+        };
+        dialect.check().ok().map(|()| dialect)
+    }
+}
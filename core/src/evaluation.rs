@@ -0,0 +1,231 @@
+//! Scoring a model's predictions against labels, proven by the third guest.
+//!
+//! An [`Evaluation`] names the column holding each row's prediction in one
+//! file and its label in another. The `zaik_evaluate` guest takes both
+//! files with their hashes and commits an [`EvaluationResult`]: the two
+//! hashes, the evaluation's hash and what scoring gave. An agent reporting
+//! a model's accuracy hands over that receipt, and anyone holding the
+//! labels, the predictions or both can check the numbers are theirs.
+//!
+//! Scoring is fixed here, so the evaluation hash pins it down:
+//!
+//! - Data rows pair up in order; blank lines are skipped. Both files must
+//!   have the same number of rows, and with an ID column the IDs of each
+//!   pair must be equal.
+//! - Cells are trimmed of ASCII whitespace and compared as strings, so `1`
+//!   and `1.0` are different classes.
+//! - An empty or missing prediction counts as wrong and as missing. An
+//!   empty or missing label stops the evaluation.
+//! - With a positive class, true positives, false positives and false
+//!   negatives are counted as well, for precision and recall.
+
+use crate::dialect::Dialect;
+use crate::fault::GuestFault;
+use crate::schema::JournalBytes;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Which columns of the two files are scored, and how they are read.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Evaluation {
+    /// Dialect of both files.
+    pub dialect: Dialect,
+    /// Zero-based column of the row ID in both files; `None` pairs rows by
+    /// position alone.
+    pub id_column: Option<u32>,
+    /// Zero-based column of the prediction in the predictions file.
+    pub prediction_column: u32,
+    /// Zero-based column of the label in the labels file.
+    pub label_column: u32,
+    /// Class counted as positive for precision and recall.
+    pub positive: Option<String>,
+}
+
+impl Evaluation {
+    /// Reject a bad dialect or a positive class no trimmed cell can equal.
+    pub fn validate(&self) -> Result<(), String> {
+        self.dialect.check()?;
+        if let Some(positive) = &self.positive {
+            if positive.is_empty() || trim(positive) != positive {
+                return Err(format!("positive class '{}' is empty or has surrounding whitespace", positive));
+            }
+        }
+        Ok(())
+    }
+
+    /// SHA-256 over the dialect, the columns and the positive class.
+    pub fn hash(&self) -> [u8; 32] {
+        let mut encoded = Vec::new();
+        self.dialect.write_bytes(&mut encoded);
+        self.id_column.is_some().write_bytes(&mut encoded);
+        self.id_column.unwrap_or(0).write_bytes(&mut encoded);
+        self.prediction_column.write_bytes(&mut encoded);
+        self.label_column.write_bytes(&mut encoded);
+        let positive = self.positive.as_deref().unwrap_or_default();
+        self.positive.is_some().write_bytes(&mut encoded);
+        (positive.len() as u32).write_bytes(&mut encoded);
+        encoded.extend_from_slice(positive.as_bytes());
+        let mut hasher = Sha256::new();
+        hasher.update(b"zaik.evaluation.v1");
+        hasher.update(&encoded);
+        hasher.finalize().into()
+    }
+
+    /// Score `predictions` against `labels`, or say which row does not pair
+    /// up.
+    pub fn score(&self, predictions: &str, labels: &str) -> Result<EvaluationCounts, String> {
+        let data_rows = |data| self.dialect.rows(data).filter(|row: &&str| !row.trim().is_empty());
+        let cell = |row, column: u32| self.dialect.field(row, column as usize).map(trim).unwrap_or_default();
+        let mut counts = EvaluationCounts::default();
+        let mut predicted = data_rows(predictions);
+        let mut expected = data_rows(labels);
+        loop {
+            let (prediction_row, label_row) = match (predicted.next(), expected.next()) {
+                (Some(prediction_row), Some(label_row)) => (prediction_row, label_row),
+                (None, None) => return Ok(counts),
+                (_, None) => return Err(format!("the predictions have more rows than the {} labels", counts.rows)),
+                (None, _) => return Err(format!("the labels have more rows than the {} predictions", counts.rows)),
+            };
+            counts.rows += 1;
+            if let Some(column) = self.id_column {
+                if cell(prediction_row, column) != cell(label_row, column) {
+                    return Err(format!("data row {} has a different ID in each file", counts.rows));
+                }
+            }
+            let label = cell(label_row, self.label_column);
+            if label.is_empty() {
+                return Err(format!("data row {} has no label", counts.rows));
+            }
+            let prediction = cell(prediction_row, self.prediction_column);
+            if prediction.is_empty() {
+                counts.missing += 1;
+            } else if prediction == label {
+                counts.correct += 1;
+            }
+            if let Some(positive) = self.positive.as_deref() {
+                match (prediction == positive, label == positive) {
+                    (true, true) => counts.true_positives += 1,
+                    (true, false) => counts.false_positives += 1,
+                    (false, true) => counts.false_negatives += 1,
+                    (false, false) => {}
+                }
+            }
+        }
+    }
+}
+
+fn trim(cell: &str) -> &str {
+    cell.trim_matches(|c: char| c.is_ascii_whitespace())
+}
+
+/// What [`Evaluation::score`] counted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EvaluationCounts {
+    pub rows: u64,
+    pub correct: u64,
+    pub missing: u64,
+    pub true_positives: u64,
+    pub false_positives: u64,
+    pub false_negatives: u64,
+}
+
+impl EvaluationCounts {
+    /// `correct / rows` in basis points, rounded down; 0 with no rows.
+    pub fn accuracy_bps(&self) -> u32 {
+        (u128::from(self.correct) * 10_000).checked_div(u128::from(self.rows)).unwrap_or(0) as u32
+    }
+}
+
+/// Input for the evaluation guest. Both files follow the input as frames,
+/// predictions first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvaluationInput {
+    pub predictions_hash: [u8; 32],
+    pub labels_hash: [u8; 32],
+    #[serde(skip)]
+    pub predictions: String,
+    #[serde(skip)]
+    pub labels: String,
+    pub evaluation: Evaluation,
+}
+
+impl EvaluationInput {
+    pub fn new(predictions: String, labels: String, evaluation: Evaluation) -> Self {
+        Self {
+            predictions_hash: crate::csv_hash(&predictions),
+            labels_hash: crate::csv_hash(&labels),
+            predictions,
+            labels,
+            evaluation,
+        }
+    }
+
+    /// Payloads sent after the input as frames.
+    pub fn frames(&self) -> Vec<&[u8]> {
+        alloc::vec![self.predictions.as_bytes(), self.labels.as_bytes()]
+    }
+
+    /// Fill in the payloads [`frames`](Self::frames) carried, reading each
+    /// with `read_frame`.
+    pub fn read_frames(&mut self, mut read_frame: impl FnMut() -> Vec<u8>) -> Result<(), core::str::Utf8Error> {
+        let mut read_text = || String::from_utf8(read_frame()).map_err(|e| e.utf8_error());
+        self.predictions = read_text()?;
+        self.labels = read_text()?;
+        Ok(())
+    }
+}
+
+crate::journal_schema! {
+    /// Journal committed by the evaluation guest.
+    pub struct EvaluationResult {
+        /// SHA-256 of the predictions file.
+        predictions_hash: [u8; 32] as "Predictions hash" in Public,
+        /// SHA-256 of the labels file.
+        labels_hash: [u8; 32] as "Labels hash" in Public,
+        /// [`Evaluation::hash`] of the evaluation scored.
+        evaluation_hash: [u8; 32] as "Evaluation hash" in Public,
+        /// Data rows scored.
+        rows: u64 as "Rows" in Public,
+        /// Rows whose prediction equals the label.
+        correct: u64 as "Correct" in Public,
+        /// `correct / rows` in basis points, rounded down.
+        accuracy_bps: u32 as "Accuracy (bps)" in Public,
+        /// Rows with no prediction, counted as wrong.
+        missing: u64 as "Missing predictions" in Public,
+        /// Positive predictions with a positive label; 0 without a positive
+        /// class.
+        true_positives: u64 as "True positives" in Public,
+        /// Positive predictions with another label.
+        false_positives: u64 as "False positives" in Public,
+        /// Positive labels with another prediction.
+        false_negatives: u64 as "False negatives" in Public,
+    }
+}
+
+/// Check both hashes and score the predictions. This is the whole of the
+/// evaluation guest.
+pub fn process(input: &EvaluationInput) -> Result<EvaluationResult, GuestFault> {
+    if crate::csv_hash(&input.predictions) != input.predictions_hash || crate::csv_hash(&input.labels) != input.labels_hash {
+        return Err(GuestFault::CsvHashMismatch);
+    }
+    input.evaluation.validate().map_err(|_| GuestFault::EvaluationInvalid)?;
+    let counts = input
+        .evaluation
+        .score(&input.predictions, &input.labels)
+        .map_err(|_| GuestFault::EvaluationMismatch)?;
+    Ok(EvaluationResult {
+        predictions_hash: input.predictions_hash,
+        labels_hash: input.labels_hash,
+        evaluation_hash: input.evaluation.hash(),
+        rows: counts.rows,
+        correct: counts.correct,
+        accuracy_bps: counts.accuracy_bps(),
+        missing: counts.missing,
+        true_positives: counts.true_positives,
+        false_positives: counts.false_positives,
+        false_negatives: counts.false_negatives,
+    })
+}
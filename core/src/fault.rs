@@ -0,0 +1,126 @@
+//! Conditions the guest aborts on.
+//!
+//! A zkVM guest can only fail by panicking, and the executor hands the host
+//! the panic message as text. The guest panics with [`GuestFault::message`],
+//! so the host can tell which condition failed with
+//! [`GuestFault::from_panic`] instead of showing an opaque prover error.
+
+use core::fmt;
+
+/// Why the guest refused to commit a journal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GuestFault {
+    /// A CSV, chunk or dataset frame is not valid UTF-8.
+    FrameNotUtf8,
+    /// The CSV does not hash to the `csv_hash` it was proven against.
+    CsvHashMismatch,
+    /// A chunk journal has no receipt among the assumptions.
+    ChunkReceiptMissing,
+    /// A dataset file does not match its hash, the hashes do not match the
+    /// root, or the files' headers differ.
+    DatasetMismatch,
+    /// A transform has no fields, too many, a repeated or empty name, or a
+    /// bad dialect.
+    TransformInvalid,
+    /// A migration is invalid or does not apply to the old file's header.
+    MigrationInvalid,
+    /// The new file is not the old file migrated.
+    MigrationMismatch,
+    /// A window step's previous journal has no receipt, or its spec, image
+    /// or window entries do not match it.
+    WindowMismatch,
+    /// A window row's timestamp or value does not parse, or its timestamp
+    /// is older than the log's newest.
+    WindowRowInvalid,
+    /// An evaluation has a bad dialect or positive class.
+    EvaluationInvalid,
+    /// The predictions and labels do not pair up: their row counts or IDs
+    /// differ, or a label is missing.
+    EvaluationMismatch,
+}
+
+impl GuestFault {
+    pub const ALL: [GuestFault; 11] = [
+        GuestFault::FrameNotUtf8,
+        GuestFault::CsvHashMismatch,
+        GuestFault::ChunkReceiptMissing,
+        GuestFault::DatasetMismatch,
+        GuestFault::TransformInvalid,
+        GuestFault::MigrationInvalid,
+        GuestFault::MigrationMismatch,
+        GuestFault::WindowMismatch,
+        GuestFault::WindowRowInvalid,
+        GuestFault::EvaluationInvalid,
+        GuestFault::EvaluationMismatch,
+    ];
+
+    /// The message the guest panics with.
+    pub fn message(self) -> &'static str {
+        match self {
+            GuestFault::FrameNotUtf8 => "CSV frame is not UTF-8",
+            GuestFault::CsvHashMismatch => "CSV hash mismatch",
+            GuestFault::ChunkReceiptMissing => "chunk receipt missing",
+            GuestFault::DatasetMismatch => "dataset file or root mismatch",
+            GuestFault::TransformInvalid => "transform invalid",
+            GuestFault::MigrationInvalid => "migration does not apply",
+            GuestFault::MigrationMismatch => "migrated file mismatch",
+            GuestFault::WindowMismatch => "window state mismatch",
+            GuestFault::WindowRowInvalid => "window row invalid",
+            GuestFault::EvaluationInvalid => "evaluation invalid",
+            GuestFault::EvaluationMismatch => "predictions and labels mismatch",
+        }
+    }
+
+    /// Stable identifier used in failure reports.
+    pub fn reason(self) -> &'static str {
+        match self {
+            GuestFault::FrameNotUtf8 => "guest_frame_not_utf8",
+            GuestFault::CsvHashMismatch => "guest_csv_hash_mismatch",
+            GuestFault::ChunkReceiptMissing => "guest_chunk_receipt_missing",
+            GuestFault::DatasetMismatch => "guest_dataset_mismatch",
+            GuestFault::TransformInvalid => "guest_transform_invalid",
+            GuestFault::MigrationInvalid => "guest_migration_invalid",
+            GuestFault::MigrationMismatch => "guest_migration_mismatch",
+            GuestFault::WindowMismatch => "guest_window_mismatch",
+            GuestFault::WindowRowInvalid => "guest_window_row_invalid",
+            GuestFault::EvaluationInvalid => "guest_evaluation_invalid",
+            GuestFault::EvaluationMismatch => "guest_evaluation_mismatch",
+        }
+    }
+
+    /// The fault a guest panic message reports, if it is one of ours.
+    pub fn from_panic(message: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|fault| message.contains(fault.message()))
+    }
+
+    /// Abort the guest with this fault's message.
+    pub fn abort(self) -> ! {
+        panic!("{}", self.message())
+    }
+}
+
+impl fmt::Display for GuestFault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            GuestFault::FrameNotUtf8 => "the input carried CSV bytes that are not valid UTF-8",
+            GuestFault::CsvHashMismatch => "the CSV does not match the csv_hash it was proven against",
+            GuestFault::ChunkReceiptMissing => "a chunk journal has no matching chunk receipt",
+            GuestFault::DatasetMismatch => {
+                "a dataset file does not match its hash or the root, or the files' headers differ"
+            }
+            GuestFault::TransformInvalid => "the transform has no fields, too many, or a repeated or empty name",
+            GuestFault::MigrationInvalid => "the migration is invalid or does not apply to the old file's header",
+            GuestFault::MigrationMismatch => "the new file is not the old file migrated",
+            GuestFault::WindowMismatch => {
+                "the previous window step has no receipt, or its spec, image or entries do not match"
+            }
+            GuestFault::WindowRowInvalid => {
+                "a window row's timestamp or value does not parse, or goes back in time"
+            }
+            GuestFault::EvaluationInvalid => "the evaluation has a bad dialect or positive class",
+            GuestFault::EvaluationMismatch => {
+                "the predictions and labels differ in rows or IDs, or a label is missing"
+            }
+        })
+    }
+}
@@ -0,0 +1,238 @@
+//! Additional invariants evaluated by the guest in the same run.
+//!
+//! Each [`Invariant`] names a column, an aggregate over it, and a bound. The
+//! guest commits one [`InvariantResult`] per invariant plus a bitmask of the
+//! ones that held, so a single receipt can serve several verifiers that each
+//! care about a different rule.
+
+use crate::dialect::Dialect;
+use crate::schema::{self, JournalBytes, JournalValue, ToJournalValue};
+use crate::ComparisonOp;
+use alloc::collections::BTreeSet;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::str::FromStr;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Most invariants one run can carry; the pass bitmask is a `u32`.
+pub const MAX_INVARIANTS: usize = 32;
+
+/// Aggregate computed over a column's `u64` cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Aggregate {
+    Sum,
+    /// Number of cells that parse as `u64`.
+    Count,
+    /// Smallest value; zero for an empty column.
+    Min,
+    /// Largest value; zero for an empty column.
+    Max,
+    /// Cells whose value already appeared earlier in the column. Keeps every
+    /// distinct value in memory.
+    Duplicates,
+    /// Cells smaller than the cell before them; zero for a non-decreasing
+    /// column.
+    Decreases,
+}
+
+impl Aggregate {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Aggregate::Sum => "sum",
+            Aggregate::Count => "count",
+            Aggregate::Min => "min",
+            Aggregate::Max => "max",
+            Aggregate::Duplicates => "duplicates",
+            Aggregate::Decreases => "decreases",
+        }
+    }
+}
+
+impl FromStr for Aggregate {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sum" => Ok(Aggregate::Sum),
+            "count" => Ok(Aggregate::Count),
+            "min" => Ok(Aggregate::Min),
+            "max" => Ok(Aggregate::Max),
+            "duplicates" => Ok(Aggregate::Duplicates),
+            "decreases" => Ok(Aggregate::Decreases),
+            other => Err(format!(
+                "unknown aggregate '{}' (expected sum, count, min, max, duplicates or decreases)",
+                other
+            )),
+        }
+    }
+}
+
+/// `aggregate(column) <op> bound`, with `column` a zero-based field index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Invariant {
+    pub column: usize,
+    pub aggregate: Aggregate,
+    pub op: ComparisonOp,
+    pub bound: u64,
+}
+
+impl Invariant {
+    /// Compute the aggregate over `csv_data`, skipping the header and cells
+    /// that are not `u64`.
+    pub fn measure(&self, csv_data: &str, dialect: Dialect) -> u64 {
+        let cells = dialect
+            .rows(csv_data)
+            .filter_map(|line| dialect.field(line, self.column)?.parse::<u64>().ok());
+        match self.aggregate {
+            Aggregate::Sum => cells.fold(0u64, |sum, value| sum.saturating_add(value)),
+            Aggregate::Count => cells.count() as u64,
+            Aggregate::Min => cells.min().unwrap_or(0),
+            Aggregate::Max => cells.max().unwrap_or(0),
+            Aggregate::Duplicates => {
+                let mut seen = BTreeSet::new();
+                cells.filter(|value| !seen.insert(*value)).count() as u64
+            }
+            Aggregate::Decreases => {
+                let mut previous = None;
+                cells
+                    .filter(|value| previous.replace(*value).is_some_and(|previous| *value < previous))
+                    .count() as u64
+            }
+        }
+    }
+
+    pub fn holds(&self, value: u64) -> bool {
+        self.op.holds(value, self.bound)
+    }
+}
+
+impl core::fmt::Display for Invariant {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{}(column {}) {} {}",
+            self.aggregate.as_str(),
+            self.column,
+            self.op.symbol(),
+            self.bound
+        )
+    }
+}
+
+/// What the guest committed for one invariant.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InvariantResult {
+    pub invariant: Invariant,
+    /// The measured aggregate; zero when the run conceals values.
+    pub value: u64,
+    /// See [`commitment`].
+    pub commitment: [u8; 32],
+}
+
+impl ToJournalValue for InvariantResult {
+    fn to_journal_value(&self) -> JournalValue {
+        JournalValue::Text(format!("{} (value {})", self.invariant, self.value))
+    }
+}
+
+impl JournalBytes for Aggregate {
+    const LAYOUT: &'static str = "u8: 0 sum, 1 count, 2 min, 3 max, 4 duplicates, 5 decreases";
+
+    fn write_bytes(&self, out: &mut Vec<u8>) {
+        out.push(match self {
+            Aggregate::Sum => 0,
+            Aggregate::Count => 1,
+            Aggregate::Min => 2,
+            Aggregate::Max => 3,
+            Aggregate::Duplicates => 4,
+            Aggregate::Decreases => 5,
+        });
+    }
+
+    fn read_bytes(input: &mut &[u8]) -> Option<Self> {
+        match schema::take(input, 1)?[0] {
+            0 => Some(Aggregate::Sum),
+            1 => Some(Aggregate::Count),
+            2 => Some(Aggregate::Min),
+            3 => Some(Aggregate::Max),
+            4 => Some(Aggregate::Duplicates),
+            5 => Some(Aggregate::Decreases),
+            _ => None,
+        }
+    }
+}
+
+/// Column, aggregate, comparison and bound, then the value and its
+/// commitment: 58 bytes.
+impl JournalBytes for InvariantResult {
+    const LAYOUT: &'static str =
+        "u64 LE column, u8 aggregate, u8 comparison, u64 LE bound, u64 LE value, 32-byte commitment";
+
+    fn write_bytes(&self, out: &mut Vec<u8>) {
+        self.invariant.column.write_bytes(out);
+        self.invariant.aggregate.write_bytes(out);
+        self.invariant.op.write_bytes(out);
+        self.invariant.bound.write_bytes(out);
+        self.value.write_bytes(out);
+        self.commitment.write_bytes(out);
+    }
+
+    fn read_bytes(input: &mut &[u8]) -> Option<Self> {
+        Some(InvariantResult {
+            invariant: Invariant {
+                column: usize::read_bytes(input)?,
+                aggregate: Aggregate::read_bytes(input)?,
+                op: ComparisonOp::read_bytes(input)?,
+                bound: u64::read_bytes(input)?,
+            },
+            value: u64::read_bytes(input)?,
+            commitment: <[u8; 32]>::read_bytes(input)?,
+        })
+    }
+}
+
+/// SHA-256 binding invariant `index`'s measured value to the run's salt,
+/// so a concealed value can be opened to chosen parties.
+pub fn commitment(salt: &[u8; 32], index: usize, value: u64) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(b"zaik.invariant.v1");
+    hasher.update(salt);
+    hasher.update((index as u32).to_le_bytes());
+    hasher.update(value.to_le_bytes());
+    hasher.finalize().into()
+}
+
+/// Evaluate every invariant. Returns the pass bitmask (bit `i` set when
+/// invariant `i` held) and the per-invariant results.
+///
+/// Panics on more than [`MAX_INVARIANTS`]; hosts check the count before
+/// proving.
+pub fn evaluate(
+    csv_data: &str,
+    dialect: Dialect,
+    invariants: &[Invariant],
+    salt: &[u8; 32],
+    conceal: bool,
+) -> (u32, Vec<InvariantResult>) {
+    assert!(invariants.len() <= MAX_INVARIANTS, "at most {} invariants", MAX_INVARIANTS);
+    let mut passed = 0u32;
+    let results = invariants
+        .iter()
+        .enumerate()
+        .map(|(index, invariant)| {
+            let value = invariant.measure(csv_data, dialect);
+            if invariant.holds(value) {
+                passed |= 1 << index;
+            }
+            InvariantResult {
+                invariant: *invariant,
+                value: if conceal { 0 } else { value },
+                commitment: commitment(salt, index, value),
+            }
+        })
+        .collect();
+    (passed, results)
+}
@@ -0,0 +1,884 @@
+//! CSV parsing, aggregation, and journal types shared by the zkVM guest and
+//! the host. Everything here is `no_std` so the guest proves exactly the code
+//! the host uses to build inputs, decode journals, and simulate runs.
+#![no_std]
+
+extern crate alloc;
+
+pub mod cells;
+pub mod chunk;
+pub mod dataset;
+pub mod dialect;
+pub mod evaluation;
+pub mod fault;
+pub mod invariant;
+pub mod migration;
+pub mod schema;
+pub mod shape;
+pub mod transform;
+pub mod window;
+
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use cells::CellCounts;
+use core::str::FromStr;
+use dialect::Dialect;
+use invariant::{Invariant, InvariantResult};
+use schema::{JournalBytes, JournalValue, ToJournalValue};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Input written to the guest.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CsvProcessingInput {
+    pub csv_hash: [u8; 32],
+    /// Sent to the guest as a raw frame after the input (see
+    /// [`GuestInput::frames`]), not through serde.
+    #[serde(skip)]
+    pub csv_data: String,
+    pub options: ProcessingOptions,
+    /// Bound the guest checks column A's sum against.
+    pub sum_bound: SumBound,
+    /// Further invariants evaluated in the same run, at most
+    /// [`invariant::MAX_INVARIANTS`].
+    pub invariants: Vec<Invariant>,
+    /// Blinds `sum_commitment`. Only needs to be secret when the sum is
+    /// concealed.
+    pub sum_salt: [u8; 32],
+    /// Largest canonical CSV (see [`canonical_csv`]) committed verbatim in
+    /// the journal, at most [`MAX_INLINE_BYTES`]; 0 never inlines.
+    pub inline_limit: u64,
+    /// Side tables (FX rates, allowlists, …) the run is bound to through
+    /// `aux_tables_hash`.
+    pub aux_tables: Vec<AuxTable>,
+    /// Most data rows the guest processes; later rows are skipped and the
+    /// journal marked `truncated`. 0 processes every row.
+    pub max_rows: u64,
+}
+
+impl CsvProcessingInput {
+    /// Input proving `csv_data` with its hash computed in `options`' column
+    /// hash mode, so the two cannot disagree. Every bound and limit is off
+    /// (`sum <= u64::MAX`, no invariants, no inlining, every row); set them
+    /// with struct update syntax.
+    pub fn from_data(csv_data: String, options: ProcessingOptions) -> Self {
+        Self {
+            csv_hash: options.column_hash.csv_hash(&csv_data),
+            csv_data,
+            options,
+            sum_bound: SumBound {
+                op: ComparisonOp::Le,
+                threshold: u64::MAX,
+            },
+            invariants: Vec::new(),
+            sum_salt: [0u8; 32],
+            inline_limit: 0,
+            aux_tables: Vec::new(),
+            max_rows: 0,
+        }
+    }
+
+    /// As [`from_data`](Self::from_data), for a caller that already holds
+    /// the hash, such as one recorded when the file was received. Fails if
+    /// `csv_data` does not hash to it.
+    pub fn from_parts(csv_hash: [u8; 32], csv_data: String, options: ProcessingOptions) -> Result<Self, InputError> {
+        let input = Self {
+            csv_hash,
+            ..Self::from_data(csv_data, options)
+        };
+        input.validate()?;
+        Ok(input)
+    }
+
+    /// Check what the guest would abort on, so a hand-built input fails
+    /// before a proving run is spent on it.
+    pub fn validate(&self) -> Result<(), InputError> {
+        let actual = self.options.column_hash.csv_hash(&self.csv_data);
+        if actual != self.csv_hash {
+            return Err(InputError::HashMismatch {
+                expected: self.csv_hash,
+                actual,
+            });
+        }
+        if self.invariants.len() > invariant::MAX_INVARIANTS {
+            return Err(InputError::TooManyInvariants(self.invariants.len()));
+        }
+        Ok(())
+    }
+}
+
+/// Why the guest would refuse an input; see [`GuestInput::validate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputError {
+    /// `csv_hash` is not the hash of `csv_data` in its column hash mode.
+    HashMismatch { expected: [u8; 32], actual: [u8; 32] },
+    /// More than [`invariant::MAX_INVARIANTS`] invariants.
+    TooManyInvariants(usize),
+    /// A dataset file does not match its hash or the root, or the files'
+    /// headers differ.
+    DatasetMismatch,
+    /// The input combines settings the guest does not support together.
+    Unsupported(&'static str),
+    /// A window step the guest would abort with this fault.
+    Window(fault::GuestFault),
+}
+
+impl core::fmt::Display for InputError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            InputError::HashMismatch { .. } => f.write_str("csv_hash does not match the CSV data"),
+            InputError::TooManyInvariants(count) => {
+                write!(f, "{} invariants, at most {} are supported", count, invariant::MAX_INVARIANTS)
+            }
+            InputError::DatasetMismatch => {
+                f.write_str("a dataset file does not match its hash or the root, or the files' headers differ")
+            }
+            InputError::Unsupported(what) => write!(f, "{} is not supported", what),
+            InputError::Window(fault) => write!(f, "{}", fault),
+        }
+    }
+}
+
+/// A named binary table handed to the guest alongside the CSV. Only the
+/// name goes through serde; the bytes follow the CSV as a frame.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuxTable {
+    pub name: String,
+    #[serde(skip)]
+    pub data: Vec<u8>,
+}
+
+/// Digest binding the names and bytes of `tables`, in order; all zero when
+/// there are none.
+pub fn aux_tables_hash(tables: &[AuxTable]) -> [u8; 32] {
+    if tables.is_empty() {
+        return [0u8; 32];
+    }
+    let mut hasher = Sha256::new();
+    hasher.update(b"zaik.aux_tables.v1");
+    for table in tables {
+        hasher.update((table.name.len() as u32).to_le_bytes());
+        hasher.update(table.name.as_bytes());
+        hasher.update((table.data.len() as u64).to_le_bytes());
+        hasher.update(&table.data);
+    }
+    hasher.finalize().into()
+}
+
+/// Upper bound on `inline_limit`, keeping journals small enough to post and
+/// store alongside the receipt.
+pub const MAX_INLINE_BYTES: u64 = 64 * 1024;
+
+/// Version of the rules the guest computes a journal by: how lines split
+/// into fields, which cells parse as numbers, and how sums, counts and
+/// digests are taken. Bumped whenever the same CSV under the same options
+/// would commit a different journal, so a verifier can tell a change of
+/// rules from a change of data.
+pub const SEMANTICS_VERSION: u32 = 1;
+
+/// Semantics versions this build's verifiers accept.
+pub const SUPPORTED_SEMANTICS: &[u32] = &[SEMANTICS_VERSION];
+
+/// What the guest is asked to do: prove a whole file, prove one chunk of
+/// one, combine proven chunks (see [`chunk`]), prove several files as one
+/// dataset (see [`dataset`]), or append a batch to a sliding window (see
+/// [`window`]).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum GuestInput {
+    Csv(CsvProcessingInput),
+    Chunk(chunk::ChunkInput),
+    Chunked(chunk::ChunkedInput),
+    Dataset(dataset::DatasetInput),
+    Window(Box<window::WindowInput>),
+}
+
+impl GuestInput {
+    /// Byte payloads that follow the serialized input as length-prefixed
+    /// frames, in order: the CSV (or each dataset file) then each aux
+    /// table, a chunk's rows, or a window step's batch.
+    /// Framing skips the word-per-byte serde encoding and its copies.
+    pub fn frames(&self) -> Vec<&[u8]> {
+        match self {
+            GuestInput::Csv(input) => core::iter::once(input.csv_data.as_bytes())
+                .chain(input.aux_tables.iter().map(|table| table.data.as_slice()))
+                .collect(),
+            GuestInput::Chunk(input) => alloc::vec![input.data.as_bytes()],
+            GuestInput::Chunked(_) => Vec::new(),
+            GuestInput::Window(input) => alloc::vec![input.rows.as_bytes()],
+            GuestInput::Dataset(input) => input
+                .files
+                .iter()
+                .map(|file| file.data.as_bytes())
+                .chain(input.input.aux_tables.iter().map(|table| table.data.as_slice()))
+                .collect(),
+        }
+    }
+
+    /// Fill in the payloads [`frames`](Self::frames) carried, reading each
+    /// with `read_frame`. CSV text must be UTF-8; it is validated in place.
+    pub fn read_frames(&mut self, mut read_frame: impl FnMut() -> Vec<u8>) -> Result<(), core::str::Utf8Error> {
+        match self {
+            GuestInput::Csv(input) => {
+                input.csv_data = String::from_utf8(read_frame()).map_err(|e| e.utf8_error())?;
+                for table in &mut input.aux_tables {
+                    table.data = read_frame();
+                }
+            }
+            GuestInput::Chunk(input) => {
+                input.data = String::from_utf8(read_frame()).map_err(|e| e.utf8_error())?;
+            }
+            GuestInput::Chunked(_) => {}
+            GuestInput::Window(input) => {
+                input.rows = String::from_utf8(read_frame()).map_err(|e| e.utf8_error())?;
+            }
+            GuestInput::Dataset(input) => {
+                for file in &mut input.files {
+                    file.data = String::from_utf8(read_frame()).map_err(|e| e.utf8_error())?;
+                }
+                for table in &mut input.input.aux_tables {
+                    table.data = read_frame();
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl GuestInput {
+    /// Check the input the way the guest will, short of running it. Chunk
+    /// inputs carry no hash of their own and always pass; a window step is
+    /// run natively, as it is cheap next to proving it.
+    pub fn validate(&self) -> Result<(), InputError> {
+        match self {
+            GuestInput::Csv(input) => input.validate(),
+            GuestInput::Chunk(_) | GuestInput::Chunked(_) => Ok(()),
+            GuestInput::Dataset(input) => input.validate(),
+            GuestInput::Window(input) => window::step(input).map(|_| ()).map_err(InputError::Window),
+        }
+    }
+}
+
+impl From<CsvProcessingInput> for GuestInput {
+    fn from(input: CsvProcessingInput) -> Self {
+        GuestInput::Csv(input)
+    }
+}
+
+/// Knobs that change what the guest commits. They are part of the input, and
+/// echoed in the journal, so a verifier knows how to recompute the digests.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProcessingOptions {
+    pub column_hash: ColumnHashMode,
+    /// Commit only to the sum, leaving `column_a_sum` zero in the journal.
+    pub conceal_sum: bool,
+    /// How the CSV splits into rows and fields.
+    pub dialect: Dialect,
+}
+
+/// How `column_a_hash` is computed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ColumnHashMode {
+    /// SHA-256 of the parsed values joined by commas. Buffers every value.
+    #[default]
+    Joined,
+    /// Running SHA-256 transcript of length-prefixed values. Memory stays
+    /// constant in the number of rows.
+    Transcript,
+    /// Like `Transcript`, but over the cells' original bytes (leading zeros,
+    /// `+` signs and all), so the digest can be recomputed from the source
+    /// file without re-parsing.
+    RawBytes,
+    /// Proven chunk by chunk so unchanged chunks can be reused; see
+    /// [`chunk`]. Changes what `csv_hash` and `column_a_hash` cover.
+    Chunked,
+}
+
+impl ColumnHashMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ColumnHashMode::Joined => "joined",
+            ColumnHashMode::Transcript => "transcript",
+            ColumnHashMode::RawBytes => "raw",
+            ColumnHashMode::Chunked => "chunked",
+        }
+    }
+
+    /// The `csv_hash` a journal in this mode commits for `csv_data`.
+    pub fn csv_hash(&self, csv_data: &str) -> [u8; 32] {
+        match self {
+            ColumnHashMode::Chunked => chunk::csv_hash(csv_data),
+            _ => csv_hash(csv_data),
+        }
+    }
+}
+
+impl FromStr for ColumnHashMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "joined" => Ok(ColumnHashMode::Joined),
+            "transcript" => Ok(ColumnHashMode::Transcript),
+            "raw" => Ok(ColumnHashMode::RawBytes),
+            "chunked" => Ok(ColumnHashMode::Chunked),
+            other => Err(format!(
+                "unknown column hash mode '{}' (expected joined, transcript, raw or chunked)",
+                other
+            )),
+        }
+    }
+}
+
+impl ToJournalValue for ColumnHashMode {
+    fn to_journal_value(&self) -> JournalValue {
+        JournalValue::Label(self.as_str())
+    }
+}
+
+impl JournalBytes for ColumnHashMode {
+    const LAYOUT: &'static str = "u8: 0 joined, 1 transcript, 2 raw, 3 chunked";
+
+    fn write_bytes(&self, out: &mut Vec<u8>) {
+        out.push(match self {
+            ColumnHashMode::Joined => 0,
+            ColumnHashMode::Transcript => 1,
+            ColumnHashMode::RawBytes => 2,
+            ColumnHashMode::Chunked => 3,
+        });
+    }
+
+    fn read_bytes(input: &mut &[u8]) -> Option<Self> {
+        match schema::take(input, 1)?[0] {
+            0 => Some(ColumnHashMode::Joined),
+            1 => Some(ColumnHashMode::Transcript),
+            2 => Some(ColumnHashMode::RawBytes),
+            3 => Some(ColumnHashMode::Chunked),
+            _ => None,
+        }
+    }
+}
+
+/// How a value is compared with its bound. Carried in the input and echoed
+/// in the journal, so the guest, the host policy and any downstream
+/// verifier all apply the same comparison.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ComparisonOp {
+    /// Strictly below the bound.
+    Lt,
+    /// At or below the bound.
+    #[default]
+    Le,
+    /// At or above the bound.
+    Ge,
+    /// Strictly above the bound.
+    Gt,
+}
+
+impl ComparisonOp {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ComparisonOp::Lt => "lt",
+            ComparisonOp::Le => "le",
+            ComparisonOp::Ge => "ge",
+            ComparisonOp::Gt => "gt",
+        }
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            ComparisonOp::Lt => "<",
+            ComparisonOp::Le => "<=",
+            ComparisonOp::Ge => ">=",
+            ComparisonOp::Gt => ">",
+        }
+    }
+
+    pub fn holds(&self, value: u64, bound: u64) -> bool {
+        match self {
+            ComparisonOp::Lt => value < bound,
+            ComparisonOp::Le => value <= bound,
+            ComparisonOp::Ge => value >= bound,
+            ComparisonOp::Gt => value > bound,
+        }
+    }
+}
+
+impl FromStr for ComparisonOp {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "lt" | "<" => Ok(ComparisonOp::Lt),
+            "le" | "<=" => Ok(ComparisonOp::Le),
+            "ge" | ">=" => Ok(ComparisonOp::Ge),
+            "gt" | ">" => Ok(ComparisonOp::Gt),
+            other => Err(format!("unknown comparison '{}' (expected lt, le, ge or gt)", other)),
+        }
+    }
+}
+
+impl ToJournalValue for ComparisonOp {
+    fn to_journal_value(&self) -> JournalValue {
+        JournalValue::Label(self.symbol())
+    }
+}
+
+impl JournalBytes for ComparisonOp {
+    const LAYOUT: &'static str = "u8: 0 lt, 1 le, 2 ge, 3 gt";
+
+    fn write_bytes(&self, out: &mut Vec<u8>) {
+        out.push(match self {
+            ComparisonOp::Lt => 0,
+            ComparisonOp::Le => 1,
+            ComparisonOp::Ge => 2,
+            ComparisonOp::Gt => 3,
+        });
+    }
+
+    fn read_bytes(input: &mut &[u8]) -> Option<Self> {
+        match schema::take(input, 1)?[0] {
+            0 => Some(ComparisonOp::Lt),
+            1 => Some(ComparisonOp::Le),
+            2 => Some(ComparisonOp::Ge),
+            3 => Some(ComparisonOp::Gt),
+            _ => None,
+        }
+    }
+}
+
+/// Column A's sum must satisfy `sum <op> threshold`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SumBound {
+    pub op: ComparisonOp,
+    pub threshold: u64,
+}
+
+impl SumBound {
+    pub fn holds(&self, sum: u64) -> bool {
+        self.op.holds(sum, self.threshold)
+    }
+}
+
+journal_schema! {
+    /// Journal committed by the guest. Public fields can be read straight off
+    /// the receipt; committed fields are digests of values that are only
+    /// revealed to chosen parties, so the same layout serves transparent and
+    /// privacy-preserving deployments.
+    pub struct AgentResult {
+        /// SHA-256 of the raw CSV bytes the guest processed; in `chunked`
+        /// mode, the digest of the file's chunk manifest, and for a dataset
+        /// the root over its files' hashes (see [`dataset::root`]).
+        csv_hash: [u8; 32] as "CSV hash" in Public,
+        /// Number of rows that contributed to the sum.
+        entry_count: usize as "Entry count" in Public,
+        /// Whether `column_a_sum` holds the sum or was concealed.
+        sum_disclosed: bool as "Sum disclosed" in Public,
+        /// Sum of every parseable column A value; zero unless `sum_disclosed`.
+        column_a_sum: u64 as "Column A sum" in Public,
+        /// How `column_a_hash` was computed: `joined`, `transcript`, `raw` or `chunked`.
+        column_hash_mode: ColumnHashMode as "Column hash mode" in Public,
+        /// Comparison the guest applied between the sum and `sum_threshold`.
+        sum_comparison: ComparisonOp as "Sum comparison" in Public,
+        /// Bound the sum was checked against.
+        sum_threshold: u64 as "Sum threshold" in Public,
+        /// Whether the sum satisfied the bound, evaluated inside the guest.
+        sum_within_bound: bool as "Sum within bound" in Public,
+        /// Bit `i` is set when `invariants[i]` held.
+        invariants_passed: u32 as "Invariants passed" in Public,
+        /// Each extra invariant with its measured value (zero unless
+        /// `sum_disclosed`) and a commitment to that value.
+        invariants: Vec<InvariantResult> as "Invariants" in Public,
+        /// Image the composed chunk receipts were proven with; all zero
+        /// unless `column_hash_mode` is `chunked`.
+        chunk_image_id: [u32; 8] as "Chunk image ID" in Public,
+        /// Size limit the guest was given for inlining the CSV; 0 if off.
+        inline_limit: u64 as "Inline limit" in Public,
+        /// The canonical CSV, when it fit within `inline_limit`; empty otherwise.
+        inline_csv: InlineCsv as "Inline CSV" in Public,
+        /// Digest of the aux tables the run was given; see
+        /// [`aux_tables_hash`]. All zero without any.
+        aux_tables_hash: [u8; 32] as "Aux tables hash" in Public,
+        /// Row limit the guest enforced; 0 if every row was processed.
+        max_rows: u64 as "Max rows" in Public,
+        /// Whether rows past `max_rows` were skipped. Every count, sum and
+        /// digest but `csv_hash` then covers only the rows processed.
+        truncated: bool as "Truncated" in Public,
+        /// Delimiter, quote and header the guest parsed the CSV with.
+        dialect: Dialect as "Dialect" in Public,
+        /// Files of the dataset `csv_hash` is the root of, proven as one
+        /// CSV; 0 for a single file.
+        dataset_files: u32 as "Dataset files" in Public,
+        /// Parsing and aggregation rules the journal was computed by; see
+        /// [`SEMANTICS_VERSION`].
+        semantics_version: u32 as "Semantics version" in Public,
+        /// Data rows processed, whether or not column A parsed in them.
+        data_rows: u64 as "Data rows" in Public,
+        /// Empty and unparseable cells of each column, indexed by column;
+        /// see [`cells`]. Empty in `chunked` mode, which does not account
+        /// cells.
+        cell_counts: Vec<CellCounts> as "Cell counts" in Public,
+        /// Whether two header names are the same; see [`shape`].
+        duplicate_headers: bool as "Duplicate headers" in Public,
+        /// Data rows whose field count differs from the header's. Zero in
+        /// `chunked` mode, which does not check rows.
+        ragged_rows: u64 as "Ragged rows" in Public,
+        /// SHA-256 of the sum and its salt; see [`SumOpening`].
+        sum_commitment: [u8; 32] as "Sum commitment" in Committed,
+        /// Digest of the parsed column A values, computed per `column_hash_mode`.
+        column_a_hash: [u8; 32] as "Column A hash" in Committed,
+        /// Merkle root over the SHA-256 of every data row; see
+        /// [`row_merkle_root`]. All zero in `chunked` mode.
+        row_merkle_root: [u8; 32] as "Row Merkle root" in Committed,
+    }
+}
+
+/// CSV bytes carried in the journal so a verifier can audit the data
+/// without fetching it separately.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct InlineCsv(pub String);
+
+/// Shown by size; the bytes themselves are read off the decoded journal.
+impl ToJournalValue for InlineCsv {
+    fn to_journal_value(&self) -> JournalValue {
+        if self.0.is_empty() {
+            JournalValue::Label("none")
+        } else {
+            JournalValue::Text(format!("{} bytes", self.0.len()))
+        }
+    }
+}
+
+impl JournalBytes for InlineCsv {
+    const LAYOUT: &'static str = "u32 LE length, then UTF-8";
+
+    fn write_bytes(&self, out: &mut Vec<u8>) {
+        (self.0.len() as u32).write_bytes(out);
+        out.extend_from_slice(self.0.as_bytes());
+    }
+
+    fn read_bytes(input: &mut &[u8]) -> Option<Self> {
+        let len = u32::read_bytes(input)? as usize;
+        let text = core::str::from_utf8(schema::take(input, len)?).ok()?;
+        Some(InlineCsv(text.into()))
+    }
+}
+
+impl AgentResult {
+    /// Whether the inlined CSV reproduces the journal's row root, column A
+    /// digest, count and (when disclosed) sum; `None` if nothing was
+    /// inlined. `csv_hash` covers the original bytes, which may differ from
+    /// the canonical ones in line endings only.
+    pub fn inline_matches(&self) -> Option<bool> {
+        let data = &self.inline_csv.0;
+        if data.is_empty() || self.column_hash_mode == ColumnHashMode::Chunked {
+            return None;
+        }
+        let aggregate = aggregate_column_a(data, self.column_hash_mode, self.dialect);
+        Some(
+            canonical_csv(data) == *data
+                && data.len() as u64 <= self.inline_limit
+                && row_merkle_root(data, self.dialect) == self.row_merkle_root
+                && aggregate.hash == self.column_a_hash
+                && aggregate.count == self.entry_count
+                && (!self.sum_disclosed || aggregate.sum == self.column_a_sum),
+        )
+    }
+}
+
+/// The values behind `sum_commitment`, handed to parties allowed to see the
+/// sum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SumOpening {
+    pub sum: u64,
+    pub salt: [u8; 32],
+}
+
+impl SumOpening {
+    pub fn commitment(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(b"zaik.sum.v1");
+        hasher.update(self.salt);
+        hasher.update(self.sum.to_le_bytes());
+        hasher.finalize().into()
+    }
+
+    /// The sum, if this opening matches the journal's commitment.
+    pub fn open(&self, journal: &AgentResult) -> Option<u64> {
+        (self.commitment() == journal.sum_commitment).then_some(self.sum)
+    }
+}
+
+/// Column A statistics committed to the journal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnAggregate {
+    pub sum: u64,
+    pub hash: [u8; 32],
+    pub count: usize,
+}
+
+/// SHA-256 of the raw CSV bytes.
+pub fn csv_hash(csv_data: &str) -> [u8; 32] {
+    Sha256::digest(csv_data.as_bytes()).into()
+}
+
+/// The header (if `dialect` has one) and at most `max_rows` data rows of
+/// `csv_data`, and whether any line was left out; 0 keeps every row.
+pub fn limit_rows(csv_data: &str, max_rows: u64, dialect: Dialect) -> (&str, bool) {
+    if max_rows == 0 {
+        return (csv_data, false);
+    }
+    // The header's newline plus one per kept row
+    let lines = max_rows + dialect.has_header as u64;
+    let mut ended = 0u64;
+    for (index, _) in csv_data.match_indices('\n') {
+        ended += 1;
+        if ended >= lines {
+            return (&csv_data[..index + 1], !csv_data[index + 1..].trim().is_empty());
+        }
+    }
+    (csv_data, false)
+}
+
+/// `csv_data` with every line, the last included, ended by a single `\n`
+/// (so `\r\n` files inline the same as `\n` ones). Every digest but
+/// `csv_hash` is the same over the canonical form as over the original.
+pub fn canonical_csv(csv_data: &str) -> String {
+    let mut canonical = String::with_capacity(canonical_len(csv_data) as usize);
+    for line in csv_data.lines() {
+        canonical.push_str(line);
+        canonical.push('\n');
+    }
+    canonical
+}
+
+fn canonical_len(csv_data: &str) -> u64 {
+    csv_data.lines().map(|line| line.len() as u64 + 1).sum()
+}
+
+/// Sum column A (the first field of every data row) and hash the parsed
+/// values according to `mode`. Rows whose first field is not a `u64` are
+/// skipped. `chunked` mode only reads the default dialect.
+pub fn aggregate_column_a(csv_data: &str, mode: ColumnHashMode, dialect: Dialect) -> ColumnAggregate {
+    match mode {
+        ColumnHashMode::Joined => aggregate_joined(csv_data, dialect),
+        ColumnHashMode::Transcript | ColumnHashMode::RawBytes => aggregate_transcript(csv_data, mode, dialect),
+        ColumnHashMode::Chunked => {
+            let (_, chunks) = chunk::split(csv_data);
+            let results: Vec<chunk::ChunkResult> = chunks.into_iter().map(chunk::process_chunk).collect();
+            chunk::aggregate(&results)
+        }
+    }
+}
+
+/// Column A cells that parse as `u64`, with their original text (quotes
+/// removed), skipping the header.
+fn column_a_cells(csv_data: &str, dialect: Dialect) -> impl Iterator<Item = (&str, u64)> + '_ {
+    dialect.rows(csv_data).filter_map(move |line| {
+        let cell = dialect.field(line, 0)?;
+        Some((cell, cell.parse::<u64>().ok()?))
+    })
+}
+
+/// Decimal digits of `value`, written into `buf` without allocating (the
+/// guest's bump allocator never frees).
+pub(crate) fn decimal(mut value: u64, buf: &mut [u8; 20]) -> &[u8] {
+    let mut start = buf.len();
+    loop {
+        start -= 1;
+        buf[start] = b'0' + (value % 10) as u8;
+        value /= 10;
+        if value == 0 {
+            return &buf[start..];
+        }
+    }
+}
+
+/// Each value is absorbed as its length (u32 LE) followed by its bytes,
+/// either the canonical decimal digits or the cell exactly as written, so no
+/// value buffer is kept.
+fn aggregate_transcript(csv_data: &str, mode: ColumnHashMode, dialect: Dialect) -> ColumnAggregate {
+    let mut sum: u64 = 0;
+    let mut count = 0;
+    let mut buf = [0u8; 20];
+    let mut hasher = Sha256::new();
+    hasher.update(match mode {
+        ColumnHashMode::RawBytes => b"zaik.column_a.raw.v1".as_slice(),
+        _ => b"zaik.column_a.transcript.v1".as_slice(),
+    });
+    for (cell, value) in column_a_cells(csv_data, dialect) {
+        let bytes = match mode {
+            ColumnHashMode::RawBytes => cell.as_bytes(),
+            _ => decimal(value, &mut buf),
+        };
+        hasher.update((bytes.len() as u32).to_le_bytes());
+        hasher.update(bytes);
+        sum += value;
+        count += 1;
+    }
+    ColumnAggregate {
+        sum,
+        hash: hasher.finalize().into(),
+        count,
+    }
+}
+
+fn aggregate_joined(csv_data: &str, dialect: Dialect) -> ColumnAggregate {
+    let mut sum: u64 = 0;
+    let mut values = Vec::new();
+    let mut count = 0;
+    
+    // Column A is the first field of every data row
+    for line in dialect.rows(csv_data) {
+        if let Some(first_field) = dialect.field(line, 0) {
+            if let Ok(value) = first_field.parse::<u64>() {
+                sum += value;
+                values.push(value.to_string());
+                count += 1;
+            }
+        }
+    }
+    
+    // Compute SHA256 of column A values concatenated
+    let mut hasher = Sha256::new();
+    hasher.update(values.join(",").as_bytes());
+    
+    ColumnAggregate {
+        sum,
+        hash: hasher.finalize().into(),
+        count,
+    }
+}
+
+/// Merkle root over the data rows (every line but the header).
+///
+/// Leaves are `SHA-256(0x00 || row)` and nodes `SHA-256(0x01 || left ||
+/// right)`. Subtrees are merged as rows arrive, like a binary counter, and
+/// the remaining peaks are folded right to left at the end, so memory is
+/// logarithmic in the row count. An empty CSV has the all-zero root.
+pub fn row_merkle_root(csv_data: &str, dialect: Dialect) -> [u8; 32] {
+    merkle_root(dialect.rows(csv_data).map(str::as_bytes))
+}
+
+/// Merkle root over `leaves`, built as [`row_merkle_root`] describes.
+pub fn merkle_root<'a>(leaves: impl Iterator<Item = &'a [u8]>) -> [u8; 32] {
+    fn node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update([1u8]);
+        hasher.update(left);
+        hasher.update(right);
+        hasher.finalize().into()
+    }
+
+    // peaks[i] holds a complete subtree of 2^i leaves, if any
+    let mut peaks: Vec<Option<[u8; 32]>> = Vec::new();
+    for leaf in leaves {
+        let mut hasher = Sha256::new();
+        hasher.update([0u8]);
+        hasher.update(leaf);
+        let mut carry: [u8; 32] = hasher.finalize().into();
+        let mut level = 0;
+        loop {
+            if level == peaks.len() {
+                peaks.push(None);
+            }
+            match peaks[level].take() {
+                Some(left) => {
+                    carry = node(&left, &carry);
+                    level += 1;
+                }
+                None => {
+                    peaks[level] = Some(carry);
+                    break;
+                }
+            }
+        }
+    }
+
+    let mut root: Option<[u8; 32]> = None;
+    for peak in peaks.into_iter().flatten() {
+        root = Some(match root {
+            Some(right) => node(&peak, &right),
+            None => peak,
+        });
+    }
+    root.unwrap_or([0u8; 32])
+}
+
+/// Run the full guest computation: check the input hash, aggregate, and
+/// check the sum against its bound.
+/// Returns `None` if `csv_hash` does not match `csv_data`.
+///
+/// In `chunked` mode the whole file is chunked and composed in one run, as
+/// a native stand-in for proving it in pieces; with no chunk receipts the
+/// chunk image ID stays zero and invariants are not evaluated.
+pub fn process(input: &CsvProcessingInput) -> Option<AgentResult> {
+    if input.options.column_hash == ColumnHashMode::Chunked {
+        let (header, chunks) = chunk::split(&input.csv_data);
+        let composed = chunk::compose(&chunk::ChunkedInput {
+            header: header.to_string(),
+            chunk_image_id: [0u32; 8],
+            chunks: chunks.into_iter().map(chunk::process_chunk).collect(),
+            options: input.options,
+            sum_bound: input.sum_bound,
+            sum_salt: input.sum_salt,
+        });
+        return (composed.csv_hash == input.csv_hash).then_some(composed);
+    }
+    if csv_hash(&input.csv_data) != input.csv_hash {
+        return None;
+    }
+    Some(attest(input, &input.csv_data))
+}
+
+/// The journal for `csv_data` under `input`'s settings, committing
+/// `input.csv_hash` as given; the caller has checked it.
+pub(crate) fn attest(input: &CsvProcessingInput, csv_data: &str) -> AgentResult {
+    let dialect = input.options.dialect;
+    let (csv_data, truncated) = limit_rows(csv_data, input.max_rows, dialect);
+    let aggregate = aggregate_column_a(csv_data, input.options.column_hash, dialect);
+    let opening = SumOpening {
+        sum: aggregate.sum,
+        salt: input.sum_salt,
+    };
+    let sum_disclosed = !input.options.conceal_sum;
+    let (invariants_passed, invariants) =
+        invariant::evaluate(csv_data, dialect, &input.invariants, &input.sum_salt, !sum_disclosed);
+    let (data_rows, cell_counts) = cells::count(csv_data, dialect);
+    let shape = shape::check(csv_data, dialect);
+    AgentResult {
+        csv_hash: input.csv_hash,
+        entry_count: aggregate.count,
+        sum_disclosed,
+        column_a_sum: if sum_disclosed { aggregate.sum } else { 0 },
+        column_hash_mode: input.options.column_hash,
+        sum_comparison: input.sum_bound.op,
+        sum_threshold: input.sum_bound.threshold,
+        sum_within_bound: input.sum_bound.holds(aggregate.sum),
+        invariants_passed,
+        invariants,
+        chunk_image_id: [0u32; 8],
+        inline_limit: input.inline_limit,
+        inline_csv: if canonical_len(csv_data) <= input.inline_limit {
+            InlineCsv(canonical_csv(csv_data))
+        } else {
+            InlineCsv::default()
+        },
+        aux_tables_hash: aux_tables_hash(&input.aux_tables),
+        max_rows: input.max_rows,
+        truncated,
+        dialect,
+        dataset_files: 0,
+        semantics_version: SEMANTICS_VERSION,
+        data_rows,
+        cell_counts,
+        duplicate_headers: shape.duplicate_headers,
+        ragged_rows: shape.ragged_rows,
+        sum_commitment: opening.commitment(),
+        column_a_hash: aggregate.hash,
+        row_merkle_root: row_merkle_root(csv_data, dialect),
+    }
+}
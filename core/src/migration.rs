@@ -0,0 +1,253 @@
+//! Schema migrations between two versions of a dataset, proven by the
+//! transform guest's migration mode.
+//!
+//! A [`Migration`] renames header columns and appends new columns with a
+//! default value. The guest takes the old and the new file with their
+//! hashes and proves the new file is exactly what the migration makes of
+//! the old one, committing a [`MigrationResult`]. This is about the data's
+//! schema; moving bundles between releases is the host's `migrate`.
+//!
+//! The migrated file is fixed byte for byte, so the migration hash pins it
+//! down:
+//!
+//! - The header is rewritten with each column's name, renamed where the
+//!   migration says, then the added columns. Names are written without
+//!   quotes unless they contain the delimiter.
+//! - Each data row is kept as it is, with the added columns' defaults
+//!   appended. Blank lines stay blank.
+//! - Every line ends with `\n`, whatever the old file used.
+
+use crate::dialect::Dialect;
+use crate::fault::GuestFault;
+use crate::schema::JournalBytes;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Most renames and added columns a migration may declare, together.
+pub const MAX_CHANGES: usize = 64;
+
+/// A column renamed from `from` to `to`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rename {
+    pub from: String,
+    pub to: String,
+}
+
+/// A column appended to every row with `default` as its value.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddedColumn {
+    pub name: String,
+    #[serde(default)]
+    pub default: String,
+}
+
+/// A declared schema migration.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Migration {
+    /// Dialect of both files; it must have a header.
+    pub dialect: Dialect,
+    pub renames: Vec<Rename>,
+    pub added: Vec<AddedColumn>,
+}
+
+impl Migration {
+    /// Reject a migration the guest would refuse regardless of the files:
+    /// a dialect without a header, more than [`MAX_CHANGES`] changes, a
+    /// column renamed twice, an empty new name, or a name or default that
+    /// would change how the migrated file splits.
+    pub fn validate(&self) -> Result<(), String> {
+        self.dialect.check()?;
+        if !self.dialect.has_header {
+            return Err("a migration renames header columns, so the files need a header".into());
+        }
+        if self.renames.len() + self.added.len() > MAX_CHANGES {
+            return Err(format!("a migration may declare at most {} changes", MAX_CHANGES));
+        }
+        for (i, rename) in self.renames.iter().enumerate() {
+            if self.renames[..i].iter().any(|earlier| earlier.from == rename.from) {
+                return Err(format!("column '{}' is renamed twice", rename.from));
+            }
+        }
+        // A name is quoted when it holds the delimiter; a default never is
+        let unwritable = |value: &str, delimiter: bool| {
+            value.bytes().any(|b| {
+                b == self.dialect.quote || b == b'\n' || b == b'\r' || (delimiter && b == self.dialect.delimiter)
+            })
+        };
+        let names = self.renames.iter().map(|rename| &rename.to).chain(self.added.iter().map(|added| &added.name));
+        for name in names {
+            if name.is_empty() || unwritable(name, false) {
+                return Err(format!("new column name '{}' is empty or contains the quote or a line break", name));
+            }
+        }
+        for added in &self.added {
+            if unwritable(&added.default, true) {
+                return Err(format!(
+                    "default '{}' contains the delimiter, the quote or a line break",
+                    added.default
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// SHA-256 over the dialect, the renames and the added columns, in
+    /// order.
+    pub fn hash(&self) -> [u8; 32] {
+        let mut encoded = Vec::new();
+        self.dialect.write_bytes(&mut encoded);
+        let write_str = |out: &mut Vec<u8>, value: &str| {
+            (value.len() as u32).write_bytes(out);
+            out.extend_from_slice(value.as_bytes());
+        };
+        (self.renames.len() as u32).write_bytes(&mut encoded);
+        for rename in &self.renames {
+            write_str(&mut encoded, &rename.from);
+            write_str(&mut encoded, &rename.to);
+        }
+        (self.added.len() as u32).write_bytes(&mut encoded);
+        for added in &self.added {
+            write_str(&mut encoded, &added.name);
+            write_str(&mut encoded, &added.default);
+        }
+        let mut hasher = Sha256::new();
+        hasher.update(b"zaik.migration.v1");
+        hasher.update(&encoded);
+        hasher.finalize().into()
+    }
+
+    /// Migrate `old_data`, handing each line of the new file (newline
+    /// included) to `emit`. Returns the new file's data rows and columns,
+    /// or why the migration does not apply to the old header.
+    pub fn apply(&self, old_data: &str, mut emit: impl FnMut(&str)) -> Result<(u64, u32), String> {
+        let mut lines = old_data.lines();
+        let header = lines.next().ok_or("the old file is empty")?;
+        let mut names: Vec<&str> = self.dialect.fields(header).collect();
+        for rename in &self.renames {
+            let column = names
+                .iter()
+                .position(|name| *name == rename.from)
+                .ok_or_else(|| format!("the old file has no column '{}'", rename.from))?;
+            names[column] = &rename.to;
+        }
+        names.extend(self.added.iter().map(|added| added.name.as_str()));
+        let changed = self.renames.iter().map(|rename| &rename.to).chain(self.added.iter().map(|added| &added.name));
+        for name in changed {
+            if names.iter().filter(|other| *other == name).count() > 1 {
+                return Err(format!("the migrated file would have two columns named '{}'", name));
+            }
+        }
+
+        let mut line = String::new();
+        for (i, name) in names.iter().enumerate() {
+            if i > 0 {
+                line.push(self.dialect.delimiter as char);
+            }
+            if name.bytes().any(|b| b == self.dialect.delimiter) {
+                let quote = self.dialect.quote as char;
+                line.push(quote);
+                line.push_str(name);
+                line.push(quote);
+            } else {
+                line.push_str(name);
+            }
+        }
+        line.push('\n');
+        emit(&line);
+
+        let mut rows = 0;
+        for row in lines {
+            line.clear();
+            if !row.trim().is_empty() {
+                line.push_str(row);
+                for added in &self.added {
+                    line.push(self.dialect.delimiter as char);
+                    line.push_str(&added.default);
+                }
+                rows += 1;
+            }
+            line.push('\n');
+            emit(&line);
+        }
+        Ok((rows, names.len() as u32))
+    }
+
+    /// The new file [`apply`](Self::apply) produces.
+    pub fn output(&self, old_data: &str) -> Result<String, String> {
+        let mut output = String::new();
+        self.apply(old_data, |line| output.push_str(line))?;
+        Ok(output)
+    }
+}
+
+/// Input for the transform guest's migration mode. Both files follow the
+/// input as frames, old first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrationInput {
+    pub old_hash: [u8; 32],
+    pub new_hash: [u8; 32],
+    #[serde(skip)]
+    pub old_data: String,
+    #[serde(skip)]
+    pub new_data: String,
+    pub migration: Migration,
+}
+
+impl MigrationInput {
+    pub fn new(old_data: String, new_data: String, migration: Migration) -> Self {
+        Self {
+            old_hash: crate::csv_hash(&old_data),
+            new_hash: crate::csv_hash(&new_data),
+            old_data,
+            new_data,
+            migration,
+        }
+    }
+}
+
+crate::journal_schema! {
+    /// Journal committed by the transform guest's migration mode.
+    pub struct MigrationResult {
+        /// SHA-256 of the old file.
+        old_hash: [u8; 32] as "Old file hash" in Public,
+        /// SHA-256 of the new file, proven to be the old one migrated.
+        new_hash: [u8; 32] as "New file hash" in Public,
+        /// [`Migration::hash`] of the migration applied.
+        migration_hash: [u8; 32] as "Migration hash" in Public,
+        /// Non-blank data rows carried over.
+        rows: u64 as "Rows" in Public,
+        /// Columns in the new file's header.
+        columns: u32 as "Columns" in Public,
+    }
+}
+
+/// Check both hashes and that the new file is the old one migrated,
+/// comparing as the migrated lines are produced.
+pub fn process(input: &MigrationInput) -> Result<MigrationResult, GuestFault> {
+    if crate::csv_hash(&input.old_data) != input.old_hash || crate::csv_hash(&input.new_data) != input.new_hash {
+        return Err(GuestFault::CsvHashMismatch);
+    }
+    input.migration.validate().map_err(|_| GuestFault::MigrationInvalid)?;
+    let mut rest = input.new_data.as_bytes();
+    let mut matches = true;
+    let (rows, columns) = input
+        .migration
+        .apply(&input.old_data, |line| match rest.strip_prefix(line.as_bytes()) {
+            Some(after) if matches => rest = after,
+            _ => matches = false,
+        })
+        .map_err(|_| GuestFault::MigrationInvalid)?;
+    if !matches || !rest.is_empty() {
+        return Err(GuestFault::MigrationMismatch);
+    }
+    Ok(MigrationResult {
+        old_hash: input.old_hash,
+        new_hash: input.new_hash,
+        migration_hash: input.migration.hash(),
+        rows,
+        columns,
+    })
+}
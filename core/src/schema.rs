@@ -0,0 +1,362 @@
+//! Single source of truth for the journal layout.
+//!
+//! [`journal_schema!`](crate::journal_schema) declares the journal struct
+//! once. The guest commits it in the explicit byte layout of
+//! [`JournalSchema::to_bytes`] and the host decodes the same struct, so
+//! commit and decode order cannot drift apart. The generated
+//! [`JournalSchema`] impl also drives the host's console output, JSON
+//! rendering, and documentation.
+//!
+//! The byte layout needs no risc0 serde to read: an 8-byte header
+//! ([`JOURNAL_MAGIC`], then [`JOURNAL_FORMAT`] as u32 LE) followed by every
+//! field in declaration order, each encoded as its [`JournalBytes::LAYOUT`]
+//! describes. Integers are little-endian and nothing is padded.
+
+use alloc::vec::Vec;
+use core::fmt;
+
+/// First bytes of every journal.
+pub const JOURNAL_MAGIC: [u8; 4] = *b"ZAIK";
+/// Byte layout version, bumped whenever a field is added, removed or
+/// re-encoded.
+pub const JOURNAL_FORMAT: u32 = 7;
+
+/// Which part of the journal a field belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JournalSection {
+    /// Readable by anyone holding the receipt.
+    Public,
+    /// A digest binding a value that is only revealed to chosen parties.
+    Committed,
+}
+
+impl JournalSection {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JournalSection::Public => "public",
+            JournalSection::Committed => "committed",
+        }
+    }
+}
+
+/// Description of one journal field, in commit order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JournalField {
+    pub name: &'static str,
+    pub label: &'static str,
+    pub ty: &'static str,
+    pub section: JournalSection,
+    pub doc: &'static str,
+    /// How the field is encoded; see [`JournalBytes::LAYOUT`].
+    pub layout: &'static str,
+}
+
+/// A journal field value, normalized for display and JSON.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JournalValue {
+    Digest([u8; 32]),
+    Count(u64),
+    Flag(bool),
+    Label(&'static str),
+    Text(alloc::string::String),
+    List(alloc::vec::Vec<JournalValue>),
+}
+
+impl fmt::Display for JournalValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JournalValue::Digest(bytes) => {
+                for byte in bytes {
+                    write!(f, "{:02x}", byte)?;
+                }
+                Ok(())
+            }
+            JournalValue::Count(value) => write!(f, "{}", value),
+            JournalValue::Flag(value) => write!(f, "{}", value),
+            JournalValue::Label(value) => f.write_str(value),
+            JournalValue::Text(value) => f.write_str(value),
+            JournalValue::List(values) => {
+                f.write_str("[")?;
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str("; ")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                f.write_str("]")
+            }
+        }
+    }
+}
+
+/// Conversion of a journal field type into a [`JournalValue`].
+pub trait ToJournalValue {
+    fn to_journal_value(&self) -> JournalValue;
+}
+
+impl ToJournalValue for [u8; 32] {
+    fn to_journal_value(&self) -> JournalValue {
+        JournalValue::Digest(*self)
+    }
+}
+
+/// Image IDs, shown as the digest risc0 prints for them.
+impl ToJournalValue for [u32; 8] {
+    fn to_journal_value(&self) -> JournalValue {
+        let mut bytes = [0u8; 32];
+        for (i, word) in self.iter().enumerate() {
+            bytes[i * 4..i * 4 + 4].copy_from_slice(&word.to_le_bytes());
+        }
+        JournalValue::Digest(bytes)
+    }
+}
+
+impl ToJournalValue for u64 {
+    fn to_journal_value(&self) -> JournalValue {
+        JournalValue::Count(*self)
+    }
+}
+
+impl ToJournalValue for u32 {
+    fn to_journal_value(&self) -> JournalValue {
+        JournalValue::Count(u64::from(*self))
+    }
+}
+
+impl ToJournalValue for usize {
+    fn to_journal_value(&self) -> JournalValue {
+        JournalValue::Count(*self as u64)
+    }
+}
+
+impl ToJournalValue for bool {
+    fn to_journal_value(&self) -> JournalValue {
+        JournalValue::Flag(*self)
+    }
+}
+
+impl<T: ToJournalValue> ToJournalValue for alloc::vec::Vec<T> {
+    fn to_journal_value(&self) -> JournalValue {
+        JournalValue::List(self.iter().map(ToJournalValue::to_journal_value).collect())
+    }
+}
+
+/// Explicit byte encoding of a journal field.
+pub trait JournalBytes: Sized {
+    /// The encoding in words, for the documented layout.
+    const LAYOUT: &'static str;
+
+    fn write_bytes(&self, out: &mut Vec<u8>);
+
+    /// Read the value off the front of `input`; `None` if it is truncated
+    /// or out of range.
+    fn read_bytes(input: &mut &[u8]) -> Option<Self>;
+}
+
+/// The first `len` bytes of `input`, advancing past them.
+pub fn take<'a>(input: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+    if input.len() < len {
+        return None;
+    }
+    let (head, rest) = input.split_at(len);
+    *input = rest;
+    Some(head)
+}
+
+impl JournalBytes for [u8; 32] {
+    const LAYOUT: &'static str = "32 bytes";
+
+    fn write_bytes(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(self);
+    }
+
+    fn read_bytes(input: &mut &[u8]) -> Option<Self> {
+        take(input, 32)?.try_into().ok()
+    }
+}
+
+impl JournalBytes for [u32; 8] {
+    const LAYOUT: &'static str = "8 × u32 LE (32 bytes)";
+
+    fn write_bytes(&self, out: &mut Vec<u8>) {
+        for word in self {
+            word.write_bytes(out);
+        }
+    }
+
+    fn read_bytes(input: &mut &[u8]) -> Option<Self> {
+        let mut words = [0u32; 8];
+        for word in &mut words {
+            *word = u32::read_bytes(input)?;
+        }
+        Some(words)
+    }
+}
+
+impl JournalBytes for u64 {
+    const LAYOUT: &'static str = "u64 LE";
+
+    fn write_bytes(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_le_bytes());
+    }
+
+    fn read_bytes(input: &mut &[u8]) -> Option<Self> {
+        Some(u64::from_le_bytes(take(input, 8)?.try_into().ok()?))
+    }
+}
+
+impl JournalBytes for u32 {
+    const LAYOUT: &'static str = "u32 LE";
+
+    fn write_bytes(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_le_bytes());
+    }
+
+    fn read_bytes(input: &mut &[u8]) -> Option<Self> {
+        Some(u32::from_le_bytes(take(input, 4)?.try_into().ok()?))
+    }
+}
+
+/// Always 8 bytes, whatever the target's pointer width.
+impl JournalBytes for usize {
+    const LAYOUT: &'static str = "u64 LE";
+
+    fn write_bytes(&self, out: &mut Vec<u8>) {
+        (*self as u64).write_bytes(out);
+    }
+
+    fn read_bytes(input: &mut &[u8]) -> Option<Self> {
+        usize::try_from(u64::read_bytes(input)?).ok()
+    }
+}
+
+impl JournalBytes for bool {
+    const LAYOUT: &'static str = "u8: 0 false, 1 true";
+
+    fn write_bytes(&self, out: &mut Vec<u8>) {
+        out.push(*self as u8);
+    }
+
+    fn read_bytes(input: &mut &[u8]) -> Option<Self> {
+        match take(input, 1)?[0] {
+            0 => Some(false),
+            1 => Some(true),
+            _ => None,
+        }
+    }
+}
+
+impl<T: JournalBytes> JournalBytes for Vec<T> {
+    const LAYOUT: &'static str = "u32 LE count, then each entry";
+
+    fn write_bytes(&self, out: &mut Vec<u8>) {
+        (self.len() as u32).write_bytes(out);
+        for item in self {
+            item.write_bytes(out);
+        }
+    }
+
+    fn read_bytes(input: &mut &[u8]) -> Option<Self> {
+        let count = u32::read_bytes(input)? as usize;
+        // Every entry takes at least a byte, so a bogus count fails here
+        // instead of reserving memory for it
+        if count > input.len() {
+            return None;
+        }
+        (0..count).map(|_| T::read_bytes(input)).collect()
+    }
+}
+
+/// Implemented by structs declared with [`journal_schema!`](crate::journal_schema).
+pub trait JournalSchema: Sized {
+    /// Fields in the order the guest commits them.
+    const FIELDS: &'static [JournalField];
+
+    /// Field values, in the same order as [`Self::FIELDS`].
+    fn values(&self) -> Vec<JournalValue>;
+
+    /// The journal in its explicit byte layout, header included.
+    fn to_bytes(&self) -> Vec<u8>;
+
+    /// Decode [`to_bytes`](Self::to_bytes) output; `None` unless `bytes`
+    /// is exactly one well-formed journal of the current format.
+    fn from_bytes(bytes: &[u8]) -> Option<Self>;
+}
+
+/// Write the journal header.
+pub fn write_header(out: &mut Vec<u8>) {
+    out.extend_from_slice(&JOURNAL_MAGIC);
+    JOURNAL_FORMAT.write_bytes(out);
+}
+
+/// Check and skip the journal magic; returns the format that follows.
+pub fn read_header(input: &mut &[u8]) -> Option<u32> {
+    if take(input, 4)? != JOURNAL_MAGIC {
+        return None;
+    }
+    u32::read_bytes(input)
+}
+
+/// Declare a journal struct and its [`JournalSchema`] in one place.
+///
+/// Each field takes a console label after `as` and a [`JournalSection`]
+/// after `in`; doc comments become the field's documentation.
+#[macro_export]
+macro_rules! journal_schema {
+    (
+        $(#[$meta:meta])*
+        pub struct $name:ident {
+            $(
+                $(#[doc = $doc:literal])*
+                $field:ident : $ty:ty as $label:literal in $section:ident
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+        pub struct $name {
+            $(
+                $(#[doc = $doc])*
+                pub $field: $ty,
+            )*
+        }
+
+        impl $crate::schema::JournalSchema for $name {
+            const FIELDS: &'static [$crate::schema::JournalField] = &[
+                $(
+                    $crate::schema::JournalField {
+                        name: stringify!($field),
+                        label: $label,
+                        ty: stringify!($ty),
+                        section: $crate::schema::JournalSection::$section,
+                        doc: concat!($($doc),*),
+                        layout: <$ty as $crate::schema::JournalBytes>::LAYOUT,
+                    },
+                )*
+            ];
+
+            fn values(&self) -> alloc::vec::Vec<$crate::schema::JournalValue> {
+                use $crate::schema::ToJournalValue;
+                alloc::vec![$(self.$field.to_journal_value()),*]
+            }
+
+            fn to_bytes(&self) -> alloc::vec::Vec<u8> {
+                use $crate::schema::JournalBytes;
+                let mut out = alloc::vec::Vec::new();
+                $crate::schema::write_header(&mut out);
+                $(self.$field.write_bytes(&mut out);)*
+                out
+            }
+
+            fn from_bytes(mut bytes: &[u8]) -> Option<Self> {
+                use $crate::schema::JournalBytes;
+                let input = &mut bytes;
+                ($crate::schema::read_header(input)? == $crate::schema::JOURNAL_FORMAT).then_some(())?;
+                let journal = Self {
+                    $($field: <$ty>::read_bytes(input)?,)*
+                };
+                input.is_empty().then_some(journal)
+            }
+        }
+    };
+}
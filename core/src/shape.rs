@@ -0,0 +1,40 @@
+//! Structural checks on the CSV: duplicate header names and ragged rows.
+//!
+//! Column A is read by position, so a file whose rows do not line up with
+//! its header still sums, just over the wrong cells. The guest commits
+//! what it found so a verifier's policy can reject such a file instead.
+
+use crate::dialect::Dialect;
+use alloc::collections::BTreeSet;
+
+/// What [`check`] found.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Shape {
+    /// Two header names are the same once surrounding blanks are trimmed.
+    /// Always false without a header.
+    pub duplicate_headers: bool,
+    /// Data rows with more or fewer fields than the header, or than the
+    /// first row when the dialect has no header.
+    pub ragged_rows: u64,
+}
+
+/// Check the header of `csv_data` for duplicate names and count its
+/// ragged rows.
+pub fn check(csv_data: &str, dialect: Dialect) -> Shape {
+    let Some(first) = csv_data.lines().next() else {
+        return Shape::default();
+    };
+    let width = dialect.fields(first).count();
+    let duplicate_headers = dialect.has_header && {
+        let mut seen = BTreeSet::new();
+        !dialect.fields(first).all(|name| seen.insert(name.trim()))
+    };
+    let ragged_rows = dialect
+        .rows(csv_data)
+        .filter(|row| dialect.fields(row).count() != width)
+        .count() as u64;
+    Shape {
+        duplicate_headers,
+        ragged_rows,
+    }
+}
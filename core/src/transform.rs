@@ -0,0 +1,307 @@
+//! Normalizing CSV rows into JSON records, proven by the second guest.
+//!
+//! A [`Transform`] maps chosen columns to named, typed JSON fields. The
+//! `zaik_transform` guest applies it to a CSV and commits a
+//! [`TransformResult`]: the hashes of the CSV, the transform and the JSON
+//! Lines it produced, so anyone holding the output can check it is exactly
+//! what the declared transform makes of that CSV.
+//!
+//! Normalization is fixed here, not configurable, so the transform hash
+//! pins it down:
+//!
+//! - Each data row becomes one JSON object on its own line, with the fields
+//!   in declared order. Blank lines are skipped.
+//! - Cells are trimmed of ASCII whitespace. An empty or missing cell is
+//!   `null`.
+//! - `text` cells are JSON strings; a doubled quote becomes one.
+//! - `integer` cells are parsed as i64 and written in canonical decimal.
+//! - `boolean` cells accept `true`/`false`, `yes`/`no` and `1`/`0`, in any
+//!   case.
+//! - An `integer` or `boolean` cell that does not parse is `null` and
+//!   counted in `invalid_cells`.
+//! - Strings are escaped minimally: quotes, backslashes and control
+//!   characters only, so the output of a CSV is a single byte string.
+
+use crate::dialect::Dialect;
+use crate::fault::GuestFault;
+use crate::migration::MigrationInput;
+use crate::schema::JournalBytes;
+use alloc::collections::BTreeSet;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Most fields a transform may declare.
+pub const MAX_FIELDS: usize = 64;
+
+/// JSON type a column is normalized to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldKind {
+    Text,
+    Integer,
+    Boolean,
+}
+
+impl FieldKind {
+    fn tag(self) -> u8 {
+        match self {
+            FieldKind::Text => 0,
+            FieldKind::Integer => 1,
+            FieldKind::Boolean => 2,
+        }
+    }
+}
+
+/// One output field: which column it is read from and how.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Field {
+    /// Zero-based column index.
+    pub column: u32,
+    /// Key of the field in every record.
+    pub name: String,
+    pub kind: FieldKind,
+}
+
+/// A declared CSV-to-JSON transformation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Transform {
+    pub dialect: Dialect,
+    pub fields: Vec<Field>,
+}
+
+/// Cells seen while transforming.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TransformCounts {
+    pub records: u64,
+    /// Empty or missing cells.
+    pub null_cells: u64,
+    /// Cells that did not parse as their field's kind.
+    pub invalid_cells: u64,
+}
+
+impl Transform {
+    /// Reject a transform the guest would refuse: no fields, more than
+    /// [`MAX_FIELDS`], an empty or repeated name, or a bad dialect.
+    pub fn validate(&self) -> Result<(), String> {
+        self.dialect.check()?;
+        if self.fields.is_empty() || self.fields.len() > MAX_FIELDS {
+            return Err(format!("a transform needs between 1 and {} fields", MAX_FIELDS));
+        }
+        let mut names = BTreeSet::new();
+        for field in &self.fields {
+            if field.name.is_empty() {
+                return Err(format!("the field for column {} has no name", field.column));
+            }
+            if !names.insert(field.name.as_str()) {
+                return Err(format!("field '{}' is declared twice", field.name));
+            }
+        }
+        Ok(())
+    }
+
+    /// SHA-256 over the dialect and each field's column, name and kind, in
+    /// order.
+    pub fn hash(&self) -> [u8; 32] {
+        let mut encoded = Vec::new();
+        self.dialect.write_bytes(&mut encoded);
+        (self.fields.len() as u32).write_bytes(&mut encoded);
+        for field in &self.fields {
+            field.column.write_bytes(&mut encoded);
+            (field.name.len() as u32).write_bytes(&mut encoded);
+            encoded.extend_from_slice(field.name.as_bytes());
+            encoded.push(field.kind.tag());
+        }
+        let mut hasher = Sha256::new();
+        hasher.update(b"zaik.transform.v1");
+        hasher.update(&encoded);
+        hasher.finalize().into()
+    }
+
+    /// Transform every data row of `csv_data`, handing each record line
+    /// (newline included) to `emit`.
+    pub fn apply(&self, csv_data: &str, mut emit: impl FnMut(&str)) -> TransformCounts {
+        let mut counts = TransformCounts::default();
+        let mut line = String::new();
+        for row in self.dialect.rows(csv_data).filter(|row| !row.trim().is_empty()) {
+            line.clear();
+            line.push('{');
+            for (i, field) in self.fields.iter().enumerate() {
+                if i > 0 {
+                    line.push(',');
+                }
+                write_string(&mut line, &field.name);
+                line.push(':');
+                let cell = self
+                    .dialect
+                    .field(row, field.column as usize)
+                    .map(|cell| cell.trim_matches(|c: char| c.is_ascii_whitespace()))
+                    .unwrap_or_default();
+                if cell.is_empty() {
+                    counts.null_cells += 1;
+                    line.push_str("null");
+                    continue;
+                }
+                match field.kind {
+                    FieldKind::Text => {
+                        let doubled = format!("{0}{0}", self.dialect.quote as char);
+                        write_string(&mut line, &cell.replace(&doubled, &doubled[..1]));
+                    }
+                    FieldKind::Integer => match cell.parse::<i64>() {
+                        Ok(value) => {
+                            let _ = write!(line, "{}", value);
+                        }
+                        Err(_) => {
+                            counts.invalid_cells += 1;
+                            line.push_str("null");
+                        }
+                    },
+                    FieldKind::Boolean => match parse_bool(cell) {
+                        Some(value) => line.push_str(if value { "true" } else { "false" }),
+                        None => {
+                            counts.invalid_cells += 1;
+                            line.push_str("null");
+                        }
+                    },
+                }
+            }
+            line.push_str("}\n");
+            counts.records += 1;
+            emit(&line);
+        }
+        counts
+    }
+
+    /// The JSON Lines [`apply`](Self::apply) produces, and its counts.
+    pub fn output(&self, csv_data: &str) -> (String, TransformCounts) {
+        let mut output = String::new();
+        let counts = self.apply(csv_data, |line| output.push_str(line));
+        (output, counts)
+    }
+}
+
+fn parse_bool(cell: &str) -> Option<bool> {
+    const TRUE: [&str; 3] = ["true", "yes", "1"];
+    const FALSE: [&str; 3] = ["false", "no", "0"];
+    if TRUE.iter().any(|word| cell.eq_ignore_ascii_case(word)) {
+        Some(true)
+    } else if FALSE.iter().any(|word| cell.eq_ignore_ascii_case(word)) {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/// Append `value` as a JSON string.
+fn write_string(out: &mut String, value: &str) {
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Input for the transform guest's transform mode.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransformInput {
+    pub csv_hash: [u8; 32],
+    /// Sent as a raw frame after the input, not through serde.
+    #[serde(skip)]
+    pub csv_data: String,
+    pub transform: Transform,
+}
+
+impl TransformInput {
+    pub fn new(csv_data: String, transform: Transform) -> Self {
+        Self {
+            csv_hash: crate::csv_hash(&csv_data),
+            csv_data,
+            transform,
+        }
+    }
+}
+
+/// Input written to the transform guest, by mode.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum TransformGuestInput {
+    /// Transform a CSV into JSON Lines.
+    Transform(TransformInput),
+    /// Check a new file is an old one migrated; see [`crate::migration`].
+    Migration(MigrationInput),
+}
+
+impl TransformGuestInput {
+    /// Payloads sent after the input as frames: the CSV, or the old then
+    /// the new file.
+    pub fn frames(&self) -> Vec<&[u8]> {
+        match self {
+            TransformGuestInput::Transform(input) => alloc::vec![input.csv_data.as_bytes()],
+            TransformGuestInput::Migration(input) => alloc::vec![input.old_data.as_bytes(), input.new_data.as_bytes()],
+        }
+    }
+
+    /// Fill in the payloads [`frames`](Self::frames) carried, reading each
+    /// with `read_frame`.
+    pub fn read_frames(&mut self, mut read_frame: impl FnMut() -> Vec<u8>) -> Result<(), core::str::Utf8Error> {
+        let mut read_text = || String::from_utf8(read_frame()).map_err(|e| e.utf8_error());
+        match self {
+            TransformGuestInput::Transform(input) => input.csv_data = read_text()?,
+            TransformGuestInput::Migration(input) => {
+                input.old_data = read_text()?;
+                input.new_data = read_text()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+crate::journal_schema! {
+    /// Journal committed by the transform guest in transform mode.
+    pub struct TransformResult {
+        /// SHA-256 of the raw CSV bytes the guest transformed.
+        csv_hash: [u8; 32] as "CSV hash" in Public,
+        /// [`Transform::hash`] of the transform applied.
+        transform_hash: [u8; 32] as "Transform hash" in Public,
+        /// SHA-256 of the JSON Lines output.
+        output_hash: [u8; 32] as "Output hash" in Public,
+        /// Number of records written, one per non-blank data row.
+        records: u64 as "Records" in Public,
+        /// Cells that were empty or missing and became `null`.
+        null_cells: u64 as "Null cells" in Public,
+        /// Cells that did not parse as their field's kind and became `null`.
+        invalid_cells: u64 as "Invalid cells" in Public,
+    }
+}
+
+/// Check `input` and transform it, hashing the output as it is produced.
+/// This is the whole of the transform guest.
+pub fn process(input: &TransformInput) -> Result<TransformResult, GuestFault> {
+    if crate::csv_hash(&input.csv_data) != input.csv_hash {
+        return Err(GuestFault::CsvHashMismatch);
+    }
+    input.transform.validate().map_err(|_| GuestFault::TransformInvalid)?;
+    let mut hasher = Sha256::new();
+    let counts = input.transform.apply(&input.csv_data, |line| hasher.update(line.as_bytes()));
+    Ok(TransformResult {
+        csv_hash: input.csv_hash,
+        transform_hash: input.transform.hash(),
+        output_hash: hasher.finalize().into(),
+        records: counts.records,
+        null_cells: counts.null_cells,
+        invalid_cells: counts.invalid_cells,
+    })
+}
+
@@ -0,0 +1,251 @@
+//! Rolling aggregates over an append-only log, proven one batch at a time.
+//!
+//! Each step appends a batch of rows to the log and slides a window over
+//! it: the last N rows, or the rows of the last N seconds before the newest
+//! timestamp. The guest verifies the previous step's receipt with
+//! `env::verify`, so every [`WindowResult`] stands for the whole log up to
+//! it, and commits:
+//!
+//! - `log_hash`, a hash chain over every row ever appended, so the log
+//!   cannot be rewritten between steps;
+//! - `window_commitment`, a digest of the `(timestamp, value)` entries now
+//!   in the window. The entries themselves stay with the prover, who hands
+//!   them to the next step, which checks them against it;
+//! - `evicted_hash`, a hash chain over every entry that left the window, in
+//!   order, so what was dropped can be audited as well as what was kept;
+//! - the window's count, sum and time span, and whether the sum is within
+//!   the step's cap.
+//!
+//! Timestamps are Unix seconds and must not decrease along the log; values
+//! are `u64`. A row where either does not parse stops the step.
+
+use crate::dialect::Dialect;
+use crate::fault::GuestFault;
+use crate::schema::JournalBytes;
+use alloc::collections::VecDeque;
+use alloc::string::String;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Which rows are kept and where their timestamp and value are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowSpec {
+    pub dialect: Dialect,
+    /// Zero-based column of the Unix timestamp.
+    pub time_column: u32,
+    /// Zero-based column of the value aggregated.
+    pub value_column: u32,
+    /// Keep the last `rows` rows; 0 for a time window.
+    pub rows: u64,
+    /// Keep the rows less than `seconds` older than the newest; 0 for a
+    /// row window.
+    pub seconds: u64,
+}
+
+impl WindowSpec {
+    /// Reject a spec with both or neither window size, or a bad dialect.
+    pub fn validate(&self) -> Result<(), String> {
+        self.dialect.check()?;
+        if (self.rows == 0) == (self.seconds == 0) {
+            return Err("a window is either a number of rows or of seconds".into());
+        }
+        Ok(())
+    }
+
+    /// SHA-256 over every field, so a step cannot continue a log under
+    /// another spec.
+    pub fn hash(&self) -> [u8; 32] {
+        let mut encoded = Vec::new();
+        self.dialect.write_bytes(&mut encoded);
+        self.time_column.write_bytes(&mut encoded);
+        self.value_column.write_bytes(&mut encoded);
+        self.rows.write_bytes(&mut encoded);
+        self.seconds.write_bytes(&mut encoded);
+        let mut hasher = Sha256::new();
+        hasher.update(b"zaik.window.spec.v1");
+        hasher.update(&encoded);
+        hasher.finalize().into()
+    }
+
+    fn expired(&self, oldest: &WindowEntry, kept: usize, newest: u64) -> bool {
+        if self.rows > 0 {
+            kept as u64 > self.rows
+        } else {
+            oldest.timestamp.saturating_add(self.seconds) <= newest
+        }
+    }
+}
+
+/// One row in the window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowEntry {
+    pub timestamp: u64,
+    pub value: u64,
+}
+
+impl WindowEntry {
+    fn write(&self, hasher: &mut Sha256) {
+        hasher.update(self.timestamp.to_le_bytes());
+        hasher.update(self.value.to_le_bytes());
+    }
+}
+
+/// Digest of the entries in a window, oldest first.
+pub fn commitment<'a>(entries: impl ExactSizeIterator<Item = &'a WindowEntry>) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(b"zaik.window.v1");
+    hasher.update((entries.len() as u32).to_le_bytes());
+    for entry in entries {
+        entry.write(&mut hasher);
+    }
+    hasher.finalize().into()
+}
+
+/// Input for one step. The batch follows the input as a frame.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WindowInput {
+    pub spec: WindowSpec,
+    /// Bound the window's sum is checked against after this step.
+    pub cap: u64,
+    /// Image the previous step was proven with; its receipt must be among
+    /// the assumptions.
+    pub image_id: [u32; 8],
+    /// The previous step's journal; `None` starts a log.
+    pub previous: Option<WindowResult>,
+    /// The entries `previous` committed to, oldest first.
+    pub window: Vec<WindowEntry>,
+    /// SHA-256 of `rows`.
+    pub rows_hash: [u8; 32],
+    /// The batch appended, in the spec's dialect.
+    #[serde(skip)]
+    pub rows: String,
+}
+
+crate::journal_schema! {
+    /// Journal committed by one sliding-window step.
+    pub struct WindowResult {
+        /// Image the previous step was proven with; all zero for the first.
+        image_id: [u32; 8] as "Previous step image" in Public,
+        /// [`WindowSpec::hash`] of the spec every step used.
+        spec_hash: [u8; 32] as "Window spec hash" in Public,
+        /// Rows kept, for a row window; 0 otherwise.
+        window_rows: u64 as "Window rows" in Public,
+        /// Seconds kept, for a time window; 0 otherwise.
+        window_seconds: u64 as "Window seconds" in Public,
+        /// Hash chain over every row appended since the log started.
+        log_hash: [u8; 32] as "Log hash" in Public,
+        /// Rows appended since the log started.
+        log_rows: u64 as "Log rows" in Public,
+        /// SHA-256 of this step's batch.
+        batch_hash: [u8; 32] as "Batch hash" in Public,
+        /// Digest of the entries in the window; see [`commitment`].
+        window_commitment: [u8; 32] as "Window commitment" in Public,
+        /// Hash chain over every entry evicted from the window, in order.
+        evicted_hash: [u8; 32] as "Evicted hash" in Public,
+        /// Entries evicted since the log started.
+        evicted_rows: u64 as "Evicted rows" in Public,
+        /// Entries in the window.
+        window_count: u64 as "Window count" in Public,
+        /// Sum of the window's values, saturating at `u64::MAX`.
+        window_sum: u64 as "Window sum" in Public,
+        /// Oldest timestamp in the window; 0 when it is empty.
+        window_start: u64 as "Window start" in Public,
+        /// Newest timestamp in the log; 0 before the first row.
+        window_end: u64 as "Window end" in Public,
+        /// Bound the sum was checked against.
+        cap: u64 as "Cap" in Public,
+        /// Whether `window_sum <= cap`, evaluated inside the guest.
+        within_cap: bool as "Within cap" in Public,
+    }
+}
+
+fn link(tag: &[u8], head: [u8; 32], item: impl FnOnce(&mut Sha256)) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(tag);
+    hasher.update(head);
+    item(&mut hasher);
+    hasher.finalize().into()
+}
+
+/// Append the batch and slide the window. Returns the journal and the
+/// entries now in the window, for the next step. The guest has already
+/// verified `previous` came from a receipt.
+pub fn step(input: &WindowInput) -> Result<(WindowResult, Vec<WindowEntry>), GuestFault> {
+    let spec = &input.spec;
+    if crate::csv_hash(&input.rows) != input.rows_hash {
+        return Err(GuestFault::CsvHashMismatch);
+    }
+    spec.validate().map_err(|_| GuestFault::WindowMismatch)?;
+    let spec_hash = spec.hash();
+    let (image_id, mut log_hash, mut log_rows, mut evicted_hash, mut evicted_rows, mut newest) = match &input.previous {
+        None if input.window.is_empty() => ([0; 8], [0; 32], 0, [0; 32], 0, None),
+        None => return Err(GuestFault::WindowMismatch),
+        Some(previous) => {
+            if previous.spec_hash != spec_hash
+                || previous.window_commitment != commitment(input.window.iter())
+                || (previous.image_id != [0; 8] && previous.image_id != input.image_id)
+            {
+                return Err(GuestFault::WindowMismatch);
+            }
+            (
+                input.image_id,
+                previous.log_hash,
+                previous.log_rows,
+                previous.evicted_hash,
+                previous.evicted_rows,
+                (previous.log_rows > 0).then_some(previous.window_end),
+            )
+        }
+    };
+
+    let mut window: VecDeque<WindowEntry> = input.window.iter().copied().collect();
+    for row in spec.dialect.rows(&input.rows).filter(|row| !row.trim().is_empty()) {
+        let cell = |column: u32| {
+            spec.dialect
+                .field(row, column as usize)
+                .and_then(|cell| cell.trim().parse::<u64>().ok())
+        };
+        let (Some(timestamp), Some(value)) = (cell(spec.time_column), cell(spec.value_column)) else {
+            return Err(GuestFault::WindowRowInvalid);
+        };
+        if newest.is_some_and(|newest| timestamp < newest) {
+            return Err(GuestFault::WindowRowInvalid);
+        }
+        newest = Some(timestamp);
+        log_hash = link(b"zaik.window.log.v1", log_hash, |hasher| {
+            hasher.update(Sha256::digest(row.as_bytes()))
+        });
+        log_rows += 1;
+        window.push_back(WindowEntry { timestamp, value });
+        while let Some(oldest) = window.front().copied() {
+            if !spec.expired(&oldest, window.len(), timestamp) {
+                break;
+            }
+            evicted_hash = link(b"zaik.window.evicted.v1", evicted_hash, |hasher| oldest.write(hasher));
+            evicted_rows += 1;
+            window.pop_front();
+        }
+    }
+
+    let window_sum = window.iter().fold(0u64, |sum, entry| sum.saturating_add(entry.value));
+    let result = WindowResult {
+        image_id,
+        spec_hash,
+        window_rows: spec.rows,
+        window_seconds: spec.seconds,
+        log_hash,
+        log_rows,
+        batch_hash: input.rows_hash,
+        window_commitment: commitment(window.iter()),
+        evicted_hash,
+        evicted_rows,
+        window_count: window.len() as u64,
+        window_sum,
+        window_start: window.front().map_or(0, |entry| entry.timestamp),
+        window_end: newest.unwrap_or(0),
+        cap: input.cap,
+        within_cap: window_sum <= input.cap,
+    };
+    Ok((result, window.into()))
+}
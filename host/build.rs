@@ -0,0 +1,60 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Record the toolchain this host was built with, for bundle metadata, and
+/// the locked crates of the host and guest, for environment manifests.
+fn main() {
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let version = Command::new(rustc)
+        .arg("-V")
+        .output()
+        .ok()
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|version| version.trim().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=ZAIK_RUSTC_VERSION={}", version);
+    println!("cargo:rustc-env=ZAIK_TARGET={}", env::var("TARGET").unwrap_or_default());
+    println!("cargo:rustc-env=ZAIK_PROFILE={}", env::var("PROFILE").unwrap_or_default());
+
+    // Set by release builds from `rzup show`; the guest is built out of band
+    println!("cargo:rerun-if-env-changed=ZAIK_GUEST_TOOLCHAIN");
+    println!(
+        "cargo:rustc-env=ZAIK_GUEST_TOOLCHAIN={}",
+        env::var("ZAIK_GUEST_TOOLCHAIN").unwrap_or_else(|_| "unknown".to_string())
+    );
+
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").expect("set by cargo"));
+    let out_dir = PathBuf::from(env::var("OUT_DIR").expect("set by cargo"));
+    for (lock, out) in [
+        (manifest_dir.join("../Cargo.lock"), "host-packages.txt"),
+        (manifest_dir.join("../methods/guest/Cargo.lock"), "guest-packages.txt"),
+    ] {
+        println!("cargo:rerun-if-changed={}", lock.display());
+        fs::write(out_dir.join(out), locked_packages(&lock)).expect("OUT_DIR is writable");
+    }
+}
+
+/// `name version checksum` per locked package, `-` for a missing checksum;
+/// empty without a lockfile.
+fn locked_packages(lock: &Path) -> String {
+    let Ok(contents) = fs::read_to_string(lock) else {
+        return String::new();
+    };
+    let mut packages = String::new();
+    for package in contents.split("[[package]]").skip(1) {
+        let field = |key: &str| {
+            package.lines().find_map(|line| {
+                let value = line.strip_prefix(key)?.trim_start().strip_prefix('=')?.trim();
+                Some(value.trim_matches('"').to_string())
+            })
+        };
+        let (Some(name), Some(version)) = (field("name"), field("version")) else {
+            continue;
+        };
+        let checksum = field("checksum").unwrap_or_else(|| "-".to_string());
+        packages.push_str(&format!("{} {} {}\n", name, version, checksum));
+    }
+    packages
+}
@@ -0,0 +1,12 @@
+//! Bundle files: the current layout and every layout `migrate` still reads.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use zaik::migrate;
+use zaik::store::ProofBundle;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = ProofBundle::from_bytes(data);
+    let _ = migrate::migrate(data);
+});
@@ -0,0 +1,11 @@
+//! Journal bytes, which come out of a receipt exactly as the prover wrote them.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use zaik::journal;
+use zaik::zaik_core::AgentResult;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = journal::decode::<AgentResult>(data);
+});
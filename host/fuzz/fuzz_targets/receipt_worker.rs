@@ -0,0 +1,11 @@
+//! Requests to the `receipt-worker` binary.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use std::io;
+use zaik::backend;
+
+fuzz_target!(|data: &[u8]| {
+    backend::serve(data, io::sink()).expect("a sink never fails");
+});
@@ -0,0 +1,19 @@
+//! JSON files found next to a bundle.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use zaik::attestation::Attestation;
+use zaik::environment::EnvironmentManifest;
+use zaik::protocol::Reveal;
+use zaik::signing::{Decision, DetachedSignature};
+use zaik::transcript::Transcript;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = serde_json::from_slice::<Decision>(data);
+    let _ = serde_json::from_slice::<DetachedSignature>(data);
+    let _ = serde_json::from_slice::<Transcript>(data);
+    let _ = serde_json::from_slice::<EnvironmentManifest>(data);
+    let _ = serde_json::from_slice::<Reveal>(data);
+    let _ = Attestation::parse(data);
+});
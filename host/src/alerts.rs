@@ -0,0 +1,276 @@
+//! Paging humans when a decision goes wrong.
+//!
+//! A policy's `[alerts]` table says how severe each outcome is:
+//! ```toml
+//! [alerts]
+//! policy-reject = "warning"
+//! crypto-failure = "critical"
+//! ```
+//!
+//! and a separate sink file, kept out of the policy so the policy version
+//! does not depend on who is on call, says where each severity goes:
+//! ```toml
+//! [[pagerduty]]
+//! routing_key_env = "ZAIK_PAGERDUTY_KEY"
+//! min_severity = "critical"
+//!
+//! [[email]]
+//! server = "smtp.example.com"          # STARTTLS, port 587 unless set
+//! username_env = "ZAIK_SMTP_USER"      # optional, with password_env
+//! password_env = "ZAIK_SMTP_PASSWORD"
+//! from = "zaik@example.com"
+//! to = ["oncall@example.com"]
+//! min_severity = "warning"
+//! ```
+//!
+//! Secrets are named by environment variable, never written in the file.
+
+use crate::outcome::Outcome;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// PagerDuty's severities, least severe first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+    Critical,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+            Severity::Critical => "critical",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A policy's `[alerts]` table: the severity of each outcome worth an
+/// alert. Outcomes left out raise none.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct AlertSeverities {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conditional: Option<Severity>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub policy_reject: Option<Severity>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub crypto_failure: Option<Severity>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub io: Option<Severity>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deadline_exceeded: Option<Severity>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unexpected_data: Option<Severity>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub investigate: Option<Severity>,
+}
+
+impl AlertSeverities {
+    pub fn severity(&self, outcome: Outcome) -> Option<Severity> {
+        match outcome {
+            Outcome::Accept => None,
+            Outcome::Conditional => self.conditional,
+            Outcome::PolicyReject => self.policy_reject,
+            Outcome::CryptoFailure => self.crypto_failure,
+            Outcome::Io => self.io,
+            Outcome::DeadlineExceeded => self.deadline_exceeded,
+            Outcome::UnexpectedData => self.unexpected_data,
+            Outcome::Investigate => self.investigate,
+        }
+    }
+}
+
+/// One decision worth an alert.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Alert {
+    pub severity: Severity,
+    pub outcome: Outcome,
+    /// Stable failure reason, e.g. `invariant_violated`.
+    pub reason: String,
+    pub message: String,
+    /// Hex CSV hash; empty if the CSV could not be read or the alert is
+    /// not about one file.
+    pub csv_hash: String,
+    /// The CSV file the decision was about, or the store an SLO covers.
+    pub source: String,
+    pub policy_version: String,
+}
+
+impl Alert {
+    pub fn summary(&self) -> String {
+        format!("zaik {}: {} ({})", self.severity, self.reason, self.message)
+    }
+
+    /// Alerts for the same file and reason collapse into one incident.
+    pub fn dedup_key(&self) -> String {
+        format!("zaik:{}:{}", self.csv_hash, self.reason)
+    }
+}
+
+/// Sends to PagerDuty's Events API v2.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PagerDutySink {
+    /// Environment variable holding the integration's routing key.
+    pub routing_key_env: String,
+    pub min_severity: Severity,
+    #[serde(default = "PagerDutySink::default_url")]
+    pub url: String,
+}
+
+impl PagerDutySink {
+    fn default_url() -> String {
+        "https://events.pagerduty.com/v2/enqueue".to_string()
+    }
+}
+
+/// Sends mail over SMTP with STARTTLS.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EmailSink {
+    pub server: String,
+    #[serde(default = "EmailSink::default_port")]
+    pub port: u16,
+    #[serde(default)]
+    pub username_env: Option<String>,
+    #[serde(default)]
+    pub password_env: Option<String>,
+    pub from: String,
+    pub to: Vec<String>,
+    pub min_severity: Severity,
+}
+
+impl EmailSink {
+    fn default_port() -> u16 {
+        587
+    }
+}
+
+/// Where alerts go, loaded from the `--alerts` file.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AlertSinks {
+    #[serde(default)]
+    pub pagerduty: Vec<PagerDutySink>,
+    #[serde(default)]
+    pub email: Vec<EmailSink>,
+}
+
+impl AlertSinks {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let sinks: Self = toml::from_str(&fs::read_to_string(path)?)?;
+        sinks.validate()?;
+        Ok(sinks)
+    }
+
+    /// Fail on a sink that could never deliver, so a bad file is caught
+    /// before the run rather than when someone needs paging.
+    pub fn validate(&self) -> Result<(), Box<dyn Error>> {
+        for sink in &self.pagerduty {
+            env(&sink.routing_key_env)?;
+        }
+        for sink in &self.email {
+            if sink.to.is_empty() {
+                return Err(format!("email sink via {} has no recipients", sink.server).into());
+            }
+            if sink.username_env.is_some() != sink.password_env.is_some() {
+                return Err(format!("email sink via {} needs both username_env and password_env", sink.server).into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Send `alert` to every sink that takes its severity.
+    #[cfg(feature = "prove")]
+    pub fn dispatch(&self, alert: &Alert) -> Vec<Delivery> {
+        let mut deliveries = Vec::new();
+        for sink in self.pagerduty.iter().filter(|sink| alert.severity >= sink.min_severity) {
+            deliveries.push(Delivery {
+                sink: format!("PagerDuty ({})", sink.routing_key_env),
+                result: send_pagerduty(sink, alert),
+            });
+        }
+        for sink in self.email.iter().filter(|sink| alert.severity >= sink.min_severity) {
+            deliveries.push(Delivery {
+                sink: format!("email to {}", sink.to.join(", ")),
+                result: send_email(sink, alert),
+            });
+        }
+        deliveries
+    }
+}
+
+/// How sending an alert to one sink went.
+#[derive(Debug)]
+pub struct Delivery {
+    pub sink: String,
+    pub result: Result<(), Box<dyn Error>>,
+}
+
+fn env(name: &str) -> Result<String, Box<dyn Error>> {
+    std::env::var(name)
+        .ok()
+        .filter(|value| !value.is_empty())
+        .ok_or_else(|| format!("environment variable {} is not set", name).into())
+}
+
+#[cfg(feature = "prove")]
+fn send_pagerduty(sink: &PagerDutySink, alert: &Alert) -> Result<(), Box<dyn Error>> {
+    let event = serde_json::json!({
+        "routing_key": env(&sink.routing_key_env)?,
+        "event_action": "trigger",
+        "dedup_key": alert.dedup_key(),
+        "payload": {
+            "summary": alert.summary(),
+            "source": "zaik",
+            "severity": alert.severity,
+            "component": alert.source,
+            "class": alert.reason,
+            "custom_details": alert,
+        },
+    });
+    reqwest::blocking::Client::builder()
+        .timeout(std::time::Duration::from_secs(30))
+        .build()?
+        .post(&sink.url)
+        .json(&event)
+        .send()?
+        .error_for_status()?;
+    Ok(())
+}
+
+#[cfg(feature = "prove")]
+fn send_email(sink: &EmailSink, alert: &Alert) -> Result<(), Box<dyn Error>> {
+    use lettre::transport::smtp::authentication::Credentials;
+    use lettre::{Message, SmtpTransport, Transport};
+
+    let mut message = Message::builder().from(sink.from.parse()?).subject(alert.summary());
+    for to in &sink.to {
+        message = message.to(to.parse()?);
+    }
+    let body = format!(
+        "Outcome: {}\nReason: {}\n{}\n\nSource: {}\nCSV hash: {}\nPolicy version: {}\n",
+        serde_json::to_value(alert.outcome)?.as_str().unwrap_or_default(),
+        alert.reason,
+        alert.message,
+        alert.source,
+        alert.csv_hash,
+        alert.policy_version,
+    );
+    let mut transport = SmtpTransport::starttls_relay(&sink.server)?.port(sink.port);
+    if let (Some(username), Some(password)) = (&sink.username_env, &sink.password_env) {
+        transport = transport.credentials(Credentials::new(env(username)?, env(password)?));
+    }
+    transport.build().send(&message.body(body)?)?;
+    Ok(())
+}
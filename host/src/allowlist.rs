@@ -0,0 +1,55 @@
+//! Guest image IDs pinned in a file, for verifiers built without the guest.
+//!
+//! ```toml
+//! [[image]]
+//! id = "0b6a…"           # hex image ID, as printed in a bundle's metadata
+//! note = "v0.1.0 release build"
+//! ```
+//!
+//! Image IDs are taken from a reviewed guest build and distributed out of
+//! band; verifying against them only needs the receipt, not the ELF.
+
+use risc0_zkvm::sha::Digest;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PinnedImage {
+    /// Hex image ID.
+    pub id: String,
+    /// Where the ID came from, for humans reviewing the file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageAllowlist {
+    #[serde(rename = "image")]
+    pub images: Vec<PinnedImage>,
+}
+
+impl ImageAllowlist {
+    /// Read an allowlist, rejecting malformed or missing image IDs.
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let allowlist: ImageAllowlist = toml::from_str(&fs::read_to_string(path)?)?;
+        if allowlist.images.is_empty() {
+            return Err("allowlist pins no image IDs".into());
+        }
+        allowlist.digests()?;
+        Ok(allowlist)
+    }
+
+    pub fn digests(&self) -> Result<Vec<Digest>, Box<dyn Error>> {
+        self.images
+            .iter()
+            .map(|image| {
+                hex::decode(&image.id)
+                    .ok()
+                    .and_then(|bytes| Digest::try_from(bytes.as_slice()).ok())
+                    .ok_or_else(|| format!("'{}' is not a 32-byte hex image ID", image.id).into())
+            })
+            .collect()
+    }
+}
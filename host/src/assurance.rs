@@ -0,0 +1,86 @@
+//! Assurance tiers: how much evidence a sum needs before it is accepted.
+//!
+//! Every sum needs a verified RISC Zero receipt. A policy with an
+//! [`Assurance`] table additionally requires, for sums above its
+//! `high_value` mark, that the receipt is wrapped in Groth16, so the
+//! business invariant can be checked on-chain as well, and that a human has
+//! reviewed the run. Both show up as rules in the policy outcome, and the
+//! tier a sum fell in is recorded with the decision.
+
+use crate::policy::RuleOutcome;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A policy's tiers, in its `[assurance]` table:
+/// ```toml
+/// [assurance]
+/// high_value = 100000   # larger sums need a Groth16 receipt and human review
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Assurance {
+    pub high_value: u64,
+}
+
+/// Which tier a sum fell in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AssuranceTier {
+    /// A verified receipt is enough.
+    #[default]
+    Standard,
+    /// Above `high_value`: Groth16 and human review are required too.
+    HighValue,
+}
+
+impl fmt::Display for AssuranceTier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            AssuranceTier::Standard => "standard",
+            AssuranceTier::HighValue => "high-value",
+        })
+    }
+}
+
+/// What the verifier holds besides the receipt's own verification.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AssuranceEvidence {
+    /// The receipt is a Groth16 receipt.
+    pub groth16: bool,
+    /// A human reviewed the run and said so, e.g. with `--human-reviewed`.
+    pub human_reviewed: bool,
+}
+
+impl Assurance {
+    pub fn tier(&self, column_a_sum: u64) -> AssuranceTier {
+        if column_a_sum > self.high_value {
+            AssuranceTier::HighValue
+        } else {
+            AssuranceTier::Standard
+        }
+    }
+
+    /// The tier `column_a_sum` falls in and the rules it adds; none for
+    /// the standard tier.
+    pub fn evaluate(&self, column_a_sum: u64, evidence: AssuranceEvidence) -> (AssuranceTier, Vec<RuleOutcome>) {
+        let tier = self.tier(column_a_sum);
+        if tier == AssuranceTier::Standard {
+            return (tier, Vec::new());
+        }
+        let requirement = format!("required above a sum of {}", self.high_value);
+        let rules = vec![
+            RuleOutcome {
+                rule: "groth16_receipt".to_string(),
+                passed: evidence.groth16,
+                actual: if evidence.groth16 { "Groth16 receipt" } else { "not wrapped in Groth16" }.to_string(),
+                requirement: format!("Groth16 receipt {}", requirement),
+            },
+            RuleOutcome {
+                rule: "human_review".to_string(),
+                passed: evidence.human_reviewed,
+                actual: if evidence.human_reviewed { "reviewed" } else { "not reviewed" }.to_string(),
+                requirement: format!("human review {}", requirement),
+            },
+        ];
+        (tier, rules)
+    }
+}
@@ -0,0 +1,409 @@
+//! in-toto attestations of proof bundles, so supply-chain tooling that
+//! already consumes in-toto statements (SLSA verifiers, policy engines,
+//! transparency logs) can carry zaik proofs.
+//!
+//! A bundle is exported as an [in-toto Statement v1] whose subject is the
+//! proven CSV, identified by the journal's `csv_hash`, and whose predicate
+//! names the bundle file, the guest image, the journal and the policy.
+//! Signed statements travel in a [DSSE] envelope signed with an
+//! [`AgentKey`]. The statement only points at the proof; importing one
+//! checks every field it states against the bundle, which is then verified
+//! as usual. An envelope is only opened for signers the importer trusts,
+//! and a bare statement only when the importer accepts unsigned ones.
+//!
+//! [in-toto Statement v1]: https://github.com/in-toto/attestation/blob/main/spec/v1/statement.md
+//! [DSSE]: https://github.com/secure-systems-lab/dsse/blob/master/protocol.md
+
+use crate::metadata::BundleMetadata;
+use crate::proof::ProofKind;
+use crate::signing::AgentKey;
+use crate::store::ProofBundle;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use ring::signature::{UnparsedPublicKey, ED25519};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use zaik_core::{AgentResult, ColumnHashMode};
+
+pub const STATEMENT_TYPE: &str = "https://in-toto.io/Statement/v1";
+pub const PREDICATE_TYPE: &str = "https://github.com/ronantakizawa/zaik/attestation/csv-proof/v1";
+/// DSSE payload type of an in-toto statement.
+pub const PAYLOAD_TYPE: &str = "application/vnd.in-toto+json";
+
+/// An in-toto statement about one proven CSV.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Statement {
+    #[serde(rename = "_type")]
+    pub statement_type: String,
+    pub subject: Vec<Subject>,
+    #[serde(rename = "predicateType")]
+    pub predicate_type: String,
+    pub predicate: ProofPredicate,
+}
+
+/// An in-toto resource descriptor: a name and its digests by algorithm.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Subject {
+    pub name: String,
+    pub digest: BTreeMap<String, String>,
+}
+
+/// What the bundle proves about the subject.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProofPredicate {
+    /// The bundle file, by SHA-256, and its store key digest.
+    pub bundle: Subject,
+    /// Hex guest image ID the receipt verifies against.
+    pub image_id: String,
+    pub proof_kind: ProofKind,
+    /// Hex policy hash the bundle was proven under.
+    pub policy_version: String,
+    /// Hex SHA-256 of the journal bytes.
+    pub journal_digest: String,
+    /// The journal as `host` renders it to JSON.
+    pub journal: Value,
+    pub metadata: BundleMetadata,
+}
+
+/// Digest algorithm a journal's `csv_hash` is named under. It is the
+/// file's SHA-256 only for a single file proven whole; chunked and dataset
+/// runs commit digests of their own, under names of their own.
+fn subject_algorithm(journal: &AgentResult) -> &'static str {
+    if journal.dataset_files > 0 {
+        "zaikDatasetRoot"
+    } else if journal.column_hash_mode == ColumnHashMode::Chunked {
+        "zaikChunkManifest"
+    } else {
+        "sha256"
+    }
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
+
+impl Statement {
+    /// A statement about `bundle`, read from `bundle_bytes`, naming the
+    /// CSV `subject_name`.
+    pub fn for_bundle(bundle: &ProofBundle, bundle_bytes: &[u8], subject_name: &str) -> Result<Self, Box<dyn Error>> {
+        let journal: AgentResult = crate::journal::decode(&bundle.receipt.journal.bytes)?;
+        let digest = |algorithm: &str, value: String| BTreeMap::from([(algorithm.to_string(), value)]);
+        Ok(Self {
+            statement_type: STATEMENT_TYPE.to_string(),
+            subject: vec![Subject {
+                name: subject_name.to_string(),
+                digest: digest(subject_algorithm(&journal), hex::encode(journal.csv_hash)),
+            }],
+            predicate_type: PREDICATE_TYPE.to_string(),
+            predicate: ProofPredicate {
+                bundle: Subject {
+                    name: format!("{}.bundle", bundle.key.digest()),
+                    digest: digest("sha256", sha256_hex(bundle_bytes)),
+                },
+                image_id: bundle.metadata.image_id.clone(),
+                proof_kind: bundle.proof_kind,
+                policy_version: hex::encode(bundle.key.policy_hash),
+                journal_digest: sha256_hex(&bundle.receipt.journal.bytes),
+                journal: crate::journal::to_json(&journal),
+                metadata: bundle.metadata.clone(),
+            },
+        })
+    }
+
+    /// Check that the statement is about `bundle`, read from
+    /// `bundle_bytes`: the same file, journal and CSV, and that every other
+    /// predicate field says what the bundle does.
+    pub fn check(&self, bundle: &ProofBundle, bundle_bytes: &[u8]) -> Result<(), AttestationError> {
+        if self.statement_type != STATEMENT_TYPE || self.predicate_type != PREDICATE_TYPE {
+            return Err(AttestationError::UnknownType(format!(
+                "{} / {}",
+                self.statement_type, self.predicate_type
+            )));
+        }
+        if self.predicate.bundle.digest.get("sha256") != Some(&sha256_hex(bundle_bytes)) {
+            return Err(AttestationError::BundleMismatch);
+        }
+        if self.predicate.journal_digest != sha256_hex(&bundle.receipt.journal.bytes) {
+            return Err(AttestationError::JournalMismatch);
+        }
+        let journal: AgentResult = crate::journal::decode(&bundle.receipt.journal.bytes)
+            .map_err(|_| AttestationError::JournalMismatch)?;
+        let csv_hash = hex::encode(journal.csv_hash);
+        let algorithm = subject_algorithm(&journal);
+        if !self
+            .subject
+            .iter()
+            .any(|subject| subject.digest.get(algorithm) == Some(&csv_hash))
+        {
+            return Err(AttestationError::SubjectMismatch);
+        }
+        let predicate = &self.predicate;
+        let mismatched = if predicate.bundle.name != format!("{}.bundle", bundle.key.digest()) {
+            Some("bundle")
+        } else if predicate.image_id != bundle.metadata.image_id {
+            Some("imageId")
+        } else if predicate.proof_kind != bundle.proof_kind {
+            Some("proofKind")
+        } else if predicate.policy_version != hex::encode(bundle.key.policy_hash) {
+            Some("policyVersion")
+        } else if predicate.journal != crate::journal::to_json(&journal) {
+            Some("journal")
+        } else if predicate.metadata != bundle.metadata {
+            Some("metadata")
+        } else {
+            None
+        };
+        match mismatched {
+            Some(field) => Err(AttestationError::PredicateMismatch(field)),
+            None => Ok(()),
+        }
+    }
+
+    pub fn to_json(&self) -> Vec<u8> {
+        serde_json::to_vec(self).expect("Statement is always serializable")
+    }
+}
+
+/// A DSSE envelope around a signed statement.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Envelope {
+    pub payload_type: String,
+    /// Base64 of the statement's JSON.
+    pub payload: String,
+    pub signatures: Vec<EnvelopeSignature>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnvelopeSignature {
+    /// Hex Ed25519 public key of the signer.
+    pub keyid: String,
+    /// Base64 Ed25519 signature over the PAE of the payload.
+    pub sig: String,
+}
+
+/// DSSE pre-authentication encoding: what is actually signed.
+fn pae(payload_type: &str, payload: &[u8]) -> Vec<u8> {
+    let mut encoded = format!("DSSEv1 {} {} {} ", payload_type.len(), payload_type, payload.len()).into_bytes();
+    encoded.extend_from_slice(payload);
+    encoded
+}
+
+impl Envelope {
+    pub fn sign(statement: &Statement, key: &AgentKey) -> Self {
+        let payload = statement.to_json();
+        Self {
+            payload_type: PAYLOAD_TYPE.to_string(),
+            payload: BASE64.encode(&payload),
+            signatures: vec![EnvelopeSignature {
+                keyid: key.public_key(),
+                sig: BASE64.encode(key.sign_message(&pae(PAYLOAD_TYPE, &payload))),
+            }],
+        }
+    }
+
+    /// The statement inside, once a signature by one of the `trusted` hex
+    /// public keys verifies.
+    pub fn open(&self, trusted: &[String]) -> Result<Statement, AttestationError> {
+        if trusted.is_empty() {
+            return Err(AttestationError::NoTrustedSigner);
+        }
+        if self.payload_type != PAYLOAD_TYPE {
+            return Err(AttestationError::UnknownType(self.payload_type.clone()));
+        }
+        let payload = BASE64
+            .decode(&self.payload)
+            .map_err(|e| AttestationError::Malformed(e.to_string()))?;
+        let message = pae(&self.payload_type, &payload);
+        let verified = self
+            .signatures
+            .iter()
+            .filter(|signature| trusted.contains(&signature.keyid))
+            .any(|signature| {
+                let (Ok(public_key), Ok(sig)) = (hex::decode(&signature.keyid), BASE64.decode(&signature.sig)) else {
+                    return false;
+                };
+                UnparsedPublicKey::new(&ED25519, public_key).verify(&message, &sig).is_ok()
+            });
+        if !verified {
+            return Err(AttestationError::BadSignature);
+        }
+        serde_json::from_slice(&payload).map_err(|e| AttestationError::Malformed(e.to_string()))
+    }
+}
+
+/// A statement file as found: bare or wrapped in a DSSE envelope.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Attestation {
+    Signed(Envelope),
+    Unsigned(Box<Statement>),
+}
+
+impl Attestation {
+    pub fn parse(bytes: &[u8]) -> Result<Self, AttestationError> {
+        serde_json::from_slice(bytes).map_err(|e| AttestationError::Malformed(e.to_string()))
+    }
+
+    /// The statement, once the envelope's signature by one of the
+    /// `trusted` keys verifies. A bare statement is refused unless
+    /// `allow_unsigned`.
+    pub fn statement(&self, trusted: &[String], allow_unsigned: bool) -> Result<Statement, AttestationError> {
+        match self {
+            Attestation::Signed(envelope) => envelope.open(trusted),
+            Attestation::Unsigned(_) if !allow_unsigned => Err(AttestationError::Unsigned),
+            Attestation::Unsigned(statement) => Ok(statement.as_ref().clone()),
+        }
+    }
+}
+
+/// Why an attestation was not accepted for a bundle.
+#[derive(Debug)]
+pub enum AttestationError {
+    Malformed(String),
+    /// Not an in-toto statement of a zaik proof.
+    UnknownType(String),
+    /// No signature by the expected signer verifies.
+    BadSignature,
+    /// An envelope was given but no signer to check it against.
+    NoTrustedSigner,
+    /// A bare statement where a signed one is required.
+    Unsigned,
+    /// The statement names a different bundle file.
+    BundleMismatch,
+    /// The statement names a different journal.
+    JournalMismatch,
+    /// No subject carries the journal's `csv_hash`.
+    SubjectMismatch,
+    /// A predicate field, by its JSON name, differs from the bundle's.
+    PredicateMismatch(&'static str),
+}
+
+impl AttestationError {
+    /// Stable identifier used in failure reports.
+    pub fn reason(&self) -> &'static str {
+        match self {
+            AttestationError::Malformed(_) => "attestation_malformed",
+            AttestationError::UnknownType(_) => "attestation_type_unknown",
+            AttestationError::BadSignature => "attestation_signature_invalid",
+            AttestationError::NoTrustedSigner => "attestation_signer_missing",
+            AttestationError::Unsigned => "attestation_unsigned",
+            AttestationError::BundleMismatch => "attestation_bundle_mismatch",
+            AttestationError::JournalMismatch => "attestation_journal_mismatch",
+            AttestationError::SubjectMismatch => "attestation_subject_mismatch",
+            AttestationError::PredicateMismatch(_) => "attestation_predicate_mismatch",
+        }
+    }
+}
+
+impl fmt::Display for AttestationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AttestationError::Malformed(reason) => write!(f, "attestation is malformed: {}", reason),
+            AttestationError::UnknownType(found) => write!(f, "not a zaik in-toto attestation: {}", found),
+            AttestationError::BadSignature => write!(f, "no signature by the expected signer verifies"),
+            AttestationError::NoTrustedSigner => write!(f, "no trusted signer to check the envelope against"),
+            AttestationError::Unsigned => write!(f, "attestation is not signed"),
+            AttestationError::BundleMismatch => write!(f, "attestation is about a different bundle file"),
+            AttestationError::JournalMismatch => write!(f, "attestation names a different journal"),
+            AttestationError::SubjectMismatch => write!(f, "no attestation subject matches the journal's CSV hash"),
+            AttestationError::PredicateMismatch(field) => {
+                write!(f, "attestation predicate's {} does not match the bundle", field)
+            }
+        }
+    }
+}
+
+impl Error for AttestationError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::ProofKey;
+    use risc0_zkvm::{FakeReceipt, InnerReceipt, Receipt, ReceiptClaim};
+    use std::fs;
+    use zaik_core::schema::JournalSchema;
+    use zaik_core::{CsvProcessingInput, ProcessingOptions, SumOpening};
+
+    fn bundle() -> (ProofBundle, Vec<u8>) {
+        let input = CsvProcessingInput::from_data("value_a\n100\n250\n".into(), ProcessingOptions::default());
+        let journal = zaik_core::process(&input).unwrap().to_bytes();
+        let image_id = [1u32; 8];
+        let claim = ReceiptClaim::ok(image_id, journal.clone());
+        let bundle = ProofBundle {
+            key: ProofKey {
+                csv_hash: input.csv_hash,
+                image_id,
+                policy_hash: [2; 32],
+                options: input.options,
+            },
+            proof_kind: ProofKind::DevMode,
+            receipt: Receipt::new(InnerReceipt::Fake(FakeReceipt::new(claim)), journal),
+            sum_opening: SumOpening { sum: 350, salt: [0; 32] },
+            metadata: BundleMetadata::unknown(image_id),
+        };
+        let bytes = bincode::serialize(&bundle).unwrap();
+        (bundle, bytes)
+    }
+
+    fn key(name: &str) -> AgentKey {
+        let path = std::env::temp_dir().join(format!("zaik-attestation-{}-{}.key", name, std::process::id()));
+        let _ = fs::remove_file(&path);
+        let key = AgentKey::generate(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        key
+    }
+
+    #[test]
+    fn envelopes_open_only_for_trusted_signers() {
+        let (bundle, bytes) = bundle();
+        let statement = Statement::for_bundle(&bundle, &bytes, "march.csv").unwrap();
+        let (signer, other) = (key("signer"), key("other"));
+        let signed = Attestation::Signed(Envelope::sign(&statement, &signer));
+
+        let opened = signed.statement(&[other.public_key(), signer.public_key()], false).unwrap();
+        opened.check(&bundle, &bytes).unwrap();
+        assert!(matches!(signed.statement(&[other.public_key()], false), Err(AttestationError::BadSignature)));
+        assert!(matches!(signed.statement(&[], true), Err(AttestationError::NoTrustedSigner)));
+
+        let unsigned = Attestation::Unsigned(Box::new(statement.clone()));
+        assert!(matches!(unsigned.statement(&[signer.public_key()], false), Err(AttestationError::Unsigned)));
+        assert_eq!(unsigned.statement(&[], true).unwrap(), statement);
+    }
+
+    #[test]
+    fn tampered_predicates_are_rejected() {
+        let (bundle, bytes) = bundle();
+        let statement = Statement::for_bundle(&bundle, &bytes, "march.csv").unwrap();
+        type Tamper = fn(&mut ProofPredicate);
+        let tamperings: [(&str, Tamper); 6] = [
+            ("bundle", |predicate| predicate.bundle.name = "other.bundle".into()),
+            ("imageId", |predicate| predicate.image_id = "00".repeat(32)),
+            ("proofKind", |predicate| predicate.proof_kind = ProofKind::Real),
+            ("policyVersion", |predicate| predicate.policy_version = "03".repeat(32)),
+            ("journal", |predicate| predicate.journal["column_a_sum"] = 1.into()),
+            ("metadata", |predicate| predicate.metadata.host_version = "9.9.9".into()),
+        ];
+        for (field, tamper) in tamperings {
+            let mut tampered = statement.clone();
+            tamper(&mut tampered.predicate);
+            match tampered.check(&bundle, &bytes) {
+                Err(AttestationError::PredicateMismatch(found)) => assert_eq!(found, field),
+                other => panic!("{} tampered: {:?}", field, other),
+            }
+        }
+
+        // Swapping a tampered statement into a signed envelope breaks its signature
+        let signer = key("tamper");
+        let mut envelope = Envelope::sign(&statement, &signer);
+        let mut tampered = statement.clone();
+        tampered.predicate.proof_kind = ProofKind::Real;
+        envelope.payload = BASE64.encode(tampered.to_json());
+        assert!(matches!(envelope.open(&[signer.public_key()]), Err(AttestationError::BadSignature)));
+    }
+}
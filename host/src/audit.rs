@@ -0,0 +1,331 @@
+//! Row-sampling spot checks against the journal's committed row root.
+//!
+//! Agent B derives row indices from the receipt digest, so Agent A cannot
+//! pick which rows get checked, then asks Agent A for those rows and their
+//! Merkle paths. Each path must hash to `row_merkle_root` in the verified
+//! journal. This is a cheap audit of the underlying data layered on top of
+//! the proof: it shows the rows exist as committed, not that the proven
+//! computation over them was correct (the receipt already shows that).
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeSet;
+use std::fmt;
+use zaik_core::dialect::Dialect;
+
+/// Which side of the running hash a sibling sits on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Side {
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PathStep {
+    pub side: Side,
+    /// Hex SHA-256 of the sibling subtree.
+    pub sibling: String,
+}
+
+/// One data row and its inclusion path, leaf to root.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RowOpening {
+    /// Zero-based data row index (the header is not a row).
+    pub index: usize,
+    pub row: String,
+    pub path: Vec<PathStep>,
+}
+
+/// Why a spot check failed.
+#[derive(Debug)]
+pub enum AuditError {
+    /// Agent A was asked for a row past the end of its CSV.
+    RowOutOfRange { index: usize, rows: usize },
+    /// The rows returned are not the rows that were requested.
+    WrongRows { requested: Vec<usize>, returned: Vec<usize> },
+    /// A path does not have the shape of `index` in a tree of the reported size.
+    MalformedPath { index: usize },
+    /// A row does not hash to the committed root.
+    RootMismatch { index: usize },
+}
+
+impl fmt::Display for AuditError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuditError::RowOutOfRange { index, rows } => {
+                write!(f, "row {} requested but the CSV has {} data rows", index, rows)
+            }
+            AuditError::WrongRows { requested, returned } => {
+                write!(f, "requested rows {:?} but received {:?}", requested, returned)
+            }
+            AuditError::MalformedPath { index } => write!(f, "inclusion path for row {} is malformed", index),
+            AuditError::RootMismatch { index } => write!(f, "row {} is not included under the committed root", index),
+        }
+    }
+}
+
+impl std::error::Error for AuditError {}
+
+fn leaf(row: &str) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([0u8]);
+    hasher.update(row.as_bytes());
+    hasher.finalize().into()
+}
+
+fn node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([1u8]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+/// Peak subtrees of a tree with `rows` leaves, as `(first leaf, size)`,
+/// largest first. This is the shape [`zaik_core::row_merkle_root`] builds.
+fn peaks(rows: usize) -> Vec<(usize, usize)> {
+    let mut peaks = Vec::new();
+    let mut start = 0;
+    for bit in (0..usize::BITS).rev() {
+        let size = 1usize << bit;
+        if rows & size != 0 {
+            peaks.push((start, size));
+            start += size;
+        }
+    }
+    peaks
+}
+
+/// Sides of every step on the path from leaf `index` to the root.
+fn path_shape(index: usize, rows: usize) -> Vec<Side> {
+    let peaks = peaks(rows);
+    let peak = peaks
+        .iter()
+        .position(|&(start, size)| index >= start && index < start + size)
+        .expect("index is within the tree");
+    let (start, size) = peaks[peak];
+
+    // Inside the perfect peak subtree
+    let mut sides = Vec::new();
+    let offset = index - start;
+    for level in 0..size.trailing_zeros() {
+        sides.push(if offset >> level & 1 == 0 { Side::Right } else { Side::Left });
+    }
+    // Peaks are folded right to left: everything smaller is one right
+    // sibling, then each larger peak joins on the left
+    if peak + 1 < peaks.len() {
+        sides.push(Side::Right);
+    }
+    sides.extend((0..peak).map(|_| Side::Left));
+    sides
+}
+
+/// Up to `samples` distinct row indices below `rows`, derived from `seed`.
+pub fn sample_indices(seed: &[u8; 32], rows: usize, samples: usize) -> Vec<usize> {
+    let mut indices = BTreeSet::new();
+    let mut counter: u64 = 0;
+    while indices.len() < samples.min(rows) {
+        let mut hasher = Sha256::new();
+        hasher.update(b"zaik.audit.v1");
+        hasher.update(seed);
+        hasher.update(counter.to_le_bytes());
+        let digest: [u8; 32] = hasher.finalize().into();
+        let value = u64::from_le_bytes(digest[..8].try_into().expect("8 bytes"));
+        indices.insert((value % rows as u64) as usize);
+        counter += 1;
+    }
+    indices.into_iter().collect()
+}
+
+/// Number of data rows, the leaf count of the row tree.
+pub fn row_count(csv_data: &str, dialect: Dialect) -> usize {
+    dialect.rows(csv_data).count()
+}
+
+/// Agent A's side: the requested rows with their inclusion paths.
+pub fn open_rows(csv_data: &str, dialect: Dialect, indices: &[usize]) -> Result<Vec<RowOpening>, AuditError> {
+    let rows: Vec<&str> = dialect.rows(csv_data).collect();
+    open_leaves(&rows, indices)
+}
+
+/// Inclusion paths for `indices` in the tree over `rows`, which need not
+/// be CSV rows; the root is [`zaik_core::merkle_root`] over the same rows.
+pub fn open_leaves(rows: &[&str], indices: &[usize]) -> Result<Vec<RowOpening>, AuditError> {
+    if let Some(&index) = indices.iter().find(|&&index| index >= rows.len()) {
+        return Err(AuditError::RowOutOfRange { index, rows: rows.len() });
+    }
+
+    // Every level of each peak, so siblings can be looked up directly
+    let leaves: Vec<[u8; 32]> = rows.iter().map(|row| leaf(row)).collect();
+    let mut peak_roots = Vec::new();
+    let mut peak_levels = Vec::new();
+    for (start, size) in peaks(rows.len()) {
+        let mut levels = vec![leaves[start..start + size].to_vec()];
+        while levels.last().expect("non-empty").len() > 1 {
+            let next = levels.last().expect("non-empty").chunks(2).map(|pair| node(&pair[0], &pair[1])).collect();
+            levels.push(next);
+        }
+        peak_roots.push(levels.last().expect("non-empty")[0]);
+        peak_levels.push((start, levels));
+    }
+
+    let openings = indices
+        .iter()
+        .map(|&index| {
+            let peak = peak_levels
+                .iter()
+                .rposition(|(start, _)| index >= *start)
+                .expect("index is within the tree");
+            let (start, levels) = &peak_levels[peak];
+            let mut siblings = Vec::new();
+            let mut offset = index - start;
+            for level in &levels[..levels.len() - 1] {
+                siblings.push(level[offset ^ 1]);
+                offset /= 2;
+            }
+            if peak + 1 < peak_roots.len() {
+                let mut right = *peak_roots.last().expect("non-empty");
+                for smaller in peak_roots[peak + 1..peak_roots.len() - 1].iter().rev() {
+                    right = node(smaller, &right);
+                }
+                siblings.push(right);
+            }
+            siblings.extend(peak_roots[..peak].iter().rev());
+
+            RowOpening {
+                index,
+                row: rows[index].to_string(),
+                path: path_shape(index, rows.len())
+                    .into_iter()
+                    .zip(siblings)
+                    .map(|(side, sibling)| PathStep { side, sibling: hex::encode(sibling) })
+                    .collect(),
+            }
+        })
+        .collect();
+    Ok(openings)
+}
+
+impl RowOpening {
+    /// Check this row against `root` in a tree of `rows` leaves.
+    pub fn verify(&self, root: &[u8; 32], rows: usize) -> Result<(), AuditError> {
+        let malformed = || AuditError::MalformedPath { index: self.index };
+        if self.index >= rows {
+            return Err(malformed());
+        }
+        let shape = path_shape(self.index, rows);
+        if shape.len() != self.path.len() || shape.iter().zip(&self.path).any(|(side, step)| *side != step.side) {
+            return Err(malformed());
+        }
+
+        let mut hash = leaf(&self.row);
+        for step in &self.path {
+            let sibling: [u8; 32] = hex::decode(&step.sibling)
+                .ok()
+                .and_then(|bytes| bytes.try_into().ok())
+                .ok_or_else(malformed)?;
+            hash = match step.side {
+                Side::Left => node(&sibling, &hash),
+                Side::Right => node(&hash, &sibling),
+            };
+        }
+        if hash == *root {
+            Ok(())
+        } else {
+            Err(AuditError::RootMismatch { index: self.index })
+        }
+    }
+}
+
+/// Agent B's side: check that `openings` are exactly the `requested` rows
+/// and that each is included under `root`.
+///
+/// `rows` is the row count Agent A reported; the journal does not commit
+/// it, so a path only verifies if it has the shape that count implies.
+pub fn verify_openings(
+    root: &[u8; 32],
+    rows: usize,
+    requested: &[usize],
+    openings: &[RowOpening],
+) -> Result<(), AuditError> {
+    let returned: Vec<usize> = openings.iter().map(|opening| opening.index).collect();
+    if returned != requested {
+        return Err(AuditError::WrongRows {
+            requested: requested.to_vec(),
+            returned,
+        });
+    }
+    openings.iter().try_for_each(|opening| opening.verify(root, rows))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn csv(rows: usize) -> String {
+        let mut csv = "a,b\n".to_string();
+        for row in 0..rows {
+            csv.push_str(&format!("{},{}\n", row, row * 7));
+        }
+        csv
+    }
+
+    #[test]
+    fn openings_verify_against_the_committed_root() {
+        // Perfect trees and ones with several peaks
+        for rows in [1, 2, 5, 8, 13] {
+            let csv = csv(rows);
+            let root = zaik_core::row_merkle_root(&csv, Dialect::CSV);
+            assert_eq!(row_count(&csv, Dialect::CSV), rows);
+            let indices: Vec<usize> = (0..rows).collect();
+            let openings = open_rows(&csv, Dialect::CSV, &indices).unwrap();
+            verify_openings(&root, rows, &indices, &openings).unwrap();
+        }
+    }
+
+    #[test]
+    fn sampled_indices_are_distinct_in_range_and_seeded() {
+        let indices = sample_indices(&[7; 32], 100, 10);
+        assert_eq!(indices.len(), 10);
+        assert!(indices.windows(2).all(|pair| pair[0] < pair[1]) && indices[9] < 100);
+        assert_eq!(indices, sample_indices(&[7; 32], 100, 10));
+        assert_ne!(indices, sample_indices(&[8; 32], 100, 10));
+        assert_eq!(sample_indices(&[7; 32], 3, 10), [0, 1, 2]);
+    }
+
+    #[test]
+    fn tampered_rows_and_paths_are_rejected() {
+        let csv = csv(13);
+        let root = zaik_core::row_merkle_root(&csv, Dialect::CSV);
+        let openings = open_rows(&csv, Dialect::CSV, &[3, 12]).unwrap();
+
+        let mut row = openings[0].clone();
+        row.row = "3,22".into();
+        assert!(matches!(row.verify(&root, 13), Err(AuditError::RootMismatch { index: 3 })));
+
+        let mut sibling = openings[0].clone();
+        sibling.path[0].sibling = hex::encode([0u8; 32]);
+        assert!(matches!(sibling.verify(&root, 13), Err(AuditError::RootMismatch { index: 3 })));
+
+        // A path of the wrong shape for the claimed index or row count
+        let mut moved = openings[0].clone();
+        moved.index = 4;
+        assert!(matches!(moved.verify(&root, 13), Err(AuditError::MalformedPath { index: 4 })));
+        assert!(matches!(openings[0].verify(&root, 8), Err(AuditError::MalformedPath { index: 3 })));
+        assert!(matches!(openings[1].verify(&root, 12), Err(AuditError::MalformedPath { index: 12 })));
+    }
+
+    #[test]
+    fn reordered_or_substituted_openings_are_rejected() {
+        let csv = csv(13);
+        let root = zaik_core::row_merkle_root(&csv, Dialect::CSV);
+        let openings = open_rows(&csv, Dialect::CSV, &[3, 12]).unwrap();
+
+        let reordered = [openings[1].clone(), openings[0].clone()];
+        assert!(matches!(verify_openings(&root, 13, &[3, 12], &reordered), Err(AuditError::WrongRows { .. })));
+        let substituted = open_rows(&csv, Dialect::CSV, &[3, 11]).unwrap();
+        assert!(matches!(verify_openings(&root, 13, &[3, 12], &substituted), Err(AuditError::WrongRows { .. })));
+        assert!(matches!(open_rows(&csv, Dialect::CSV, &[13]), Err(AuditError::RowOutOfRange { index: 13, rows: 13 })));
+    }
+}
@@ -0,0 +1,260 @@
+//! Append-only audit log of what agents decided about each other.
+//!
+//! Every entry is a JSON line in `audit.jsonl` in a proof store, hash
+//! chained like the budget ledger (see [`crate::budgets`]): its `hash`
+//! commits to the previous entry's hash, its sequence number, its kind, its
+//! time and the canonical JSON of its record (see [`crate::canonical`]), so
+//! editing, dropping or reordering entries breaks every hash after them.
+//!
+//! The entry hashes are also the leaves of a Merkle tree built like the row
+//! tree (see [`crate::audit`]). Whoever holds the root can check a single
+//! entry, such as an arbiter's ruling, with an [`Inclusion`] proof and
+//! without the rest of the log.
+
+use crate::audit::{self, AuditError, RowOpening};
+use crate::canonical;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// One recorded event.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// Position in the log, from 0.
+    pub seq: u64,
+    /// What the record is, e.g. `ruling`.
+    pub kind: String,
+    /// Seconds since the Unix epoch.
+    pub at: u64,
+    pub record: Value,
+    /// Hex hash of the entry before; all zero for the first.
+    pub prev: String,
+    /// Hex hash of this entry; see the module docs.
+    pub hash: String,
+}
+
+impl AuditEntry {
+    fn compute_hash(&self) -> Result<[u8; 32], serde_json::Error> {
+        let mut hasher = Sha256::new();
+        hasher.update(b"zaik.audit-log.v1");
+        hasher.update(self.prev.as_bytes());
+        hasher.update(self.seq.to_le_bytes());
+        hasher.update(self.at.to_le_bytes());
+        hasher.update((self.kind.len() as u64).to_le_bytes());
+        hasher.update(self.kind.as_bytes());
+        hasher.update(canonical::digest(&self.record)?);
+        Ok(hasher.finalize().into())
+    }
+}
+
+/// Why a log or an entry's inclusion did not verify.
+#[derive(Debug)]
+pub enum AuditLogError {
+    /// An entry is missing, repeated or out of place.
+    OutOfOrder { seq: u64, expected: u64 },
+    /// An entry does not follow the one before it.
+    BrokenChain { seq: u64 },
+    /// An entry's contents do not hash to its recorded hash.
+    Tampered { seq: u64 },
+    /// The log has no entry with this sequence number.
+    NoSuchEntry { seq: u64 },
+    /// The entry is not under the given root.
+    NotIncluded(AuditError),
+}
+
+impl fmt::Display for AuditLogError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuditLogError::OutOfOrder { seq, expected } => {
+                write!(f, "entry {} found where entry {} belongs", seq, expected)
+            }
+            AuditLogError::BrokenChain { seq } => write!(f, "entry {} does not follow the entry before it", seq),
+            AuditLogError::Tampered { seq } => write!(f, "entry {} does not match its hash", seq),
+            AuditLogError::NoSuchEntry { seq } => write!(f, "the log has no entry {}", seq),
+            AuditLogError::NotIncluded(e) => write!(f, "entry is not in the log: {}", e),
+        }
+    }
+}
+
+impl Error for AuditLogError {}
+
+/// Check that `entries` form an unbroken chain from the first entry;
+/// returns the head hash (all zero for an empty log).
+pub fn verify(entries: &[AuditEntry]) -> Result<[u8; 32], AuditLogError> {
+    let mut head = [0u8; 32];
+    for (expected, entry) in (0u64..).zip(entries) {
+        if entry.seq != expected {
+            return Err(AuditLogError::OutOfOrder { seq: entry.seq, expected });
+        }
+        if entry.prev != hex::encode(head) {
+            return Err(AuditLogError::BrokenChain { seq: entry.seq });
+        }
+        let hash = entry.compute_hash().map_err(|_| AuditLogError::Tampered { seq: entry.seq })?;
+        if entry.hash != hex::encode(hash) {
+            return Err(AuditLogError::Tampered { seq: entry.seq });
+        }
+        head = hash;
+    }
+    Ok(head)
+}
+
+/// Merkle root over the entry hashes.
+pub fn root(entries: &[AuditEntry]) -> [u8; 32] {
+    zaik_core::merkle_root(entries.iter().map(|entry| entry.hash.as_bytes()))
+}
+
+/// One entry and its path to the root of a log of `size` entries.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Inclusion {
+    pub entry: AuditEntry,
+    pub size: usize,
+    pub opening: RowOpening,
+}
+
+impl Inclusion {
+    /// Check that the entry is intact and sits at its place under `root`.
+    pub fn verify(&self, root: &[u8; 32]) -> Result<(), AuditLogError> {
+        let seq = self.entry.seq;
+        let hash = self.entry.compute_hash().map_err(|_| AuditLogError::Tampered { seq })?;
+        if self.entry.hash != hex::encode(hash) || self.opening.row != self.entry.hash {
+            return Err(AuditLogError::Tampered { seq });
+        }
+        if self.opening.index as u64 != seq {
+            return Err(AuditLogError::OutOfOrder { seq: self.opening.index as u64, expected: seq });
+        }
+        self.opening.verify(root, self.size).map_err(AuditLogError::NotIncluded)
+    }
+}
+
+/// Inclusion proof for entry `seq` of `entries`.
+pub fn prove(entries: &[AuditEntry], seq: u64) -> Result<Inclusion, AuditLogError> {
+    let entry = entries
+        .get(seq as usize)
+        .filter(|entry| entry.seq == seq)
+        .ok_or(AuditLogError::NoSuchEntry { seq })?;
+    let hashes: Vec<&str> = entries.iter().map(|entry| entry.hash.as_str()).collect();
+    let opening = audit::open_leaves(&hashes, &[seq as usize])
+        .map_err(AuditLogError::NotIncluded)?
+        .remove(0);
+    Ok(Inclusion { entry: entry.clone(), size: entries.len(), opening })
+}
+
+/// The log of one store.
+pub struct AuditLog {
+    path: PathBuf,
+}
+
+impl AuditLog {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Every entry, oldest first; none if nothing was recorded yet.
+    pub fn entries(&self) -> Result<Vec<AuditEntry>, Box<dyn Error>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        fs::read_to_string(&self.path)?
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| Ok(serde_json::from_str(line)?))
+            .collect()
+    }
+
+    /// Record `record` as a `kind` entry after checking the log so far; the
+    /// entry is synced to disk before this returns, so a process stopped
+    /// right after still has it. Callers sharing a store must not append
+    /// concurrently.
+    pub fn append<T: Serialize>(&self, kind: &str, record: &T) -> Result<AuditEntry, Box<dyn Error>> {
+        let entries = self.entries()?;
+        let head = verify(&entries)?;
+        let mut entry = AuditEntry {
+            seq: entries.len() as u64,
+            kind: kind.to_string(),
+            at: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+            record: serde_json::to_value(record)?,
+            prev: hex::encode(head),
+            hash: String::new(),
+        };
+        entry.hash = hex::encode(entry.compute_hash()?);
+        let mut file = fs::OpenOptions::new().create(true).append(true).open(&self.path)?;
+        writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+        file.sync_data()?;
+        Ok(entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log(name: &str, entries: usize) -> (PathBuf, Vec<AuditEntry>) {
+        let path = std::env::temp_dir().join(format!("zaik-audit-log-{}-{}.jsonl", name, std::process::id()));
+        let _ = fs::remove_file(&path);
+        let log = AuditLog::new(&path);
+        for n in 0..entries {
+            log.append(name, &serde_json::json!({ "n": n })).unwrap();
+        }
+        (path.clone(), log.entries().unwrap())
+    }
+
+    #[test]
+    fn appended_entries_chain_and_prove_inclusion() {
+        let (path, entries) = log("append", 5);
+        assert_eq!(verify(&entries).unwrap(), <[u8; 32]>::try_from(hex::decode(&entries[4].hash).unwrap()).unwrap());
+        let root = root(&entries);
+        for seq in 0..5 {
+            prove(&entries, seq).unwrap().verify(&root).unwrap();
+        }
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn tampered_entries_are_rejected() {
+        let (path, entries) = log("tampered", 4);
+        let mut tampered = entries.clone();
+        tampered[1].record = serde_json::json!({ "n": 100 });
+        assert!(matches!(verify(&tampered), Err(AuditLogError::Tampered { seq: 1 })));
+
+        // Rehashing the edited entry does not help: the next one no longer follows it
+        tampered[1].hash = hex::encode(tampered[1].compute_hash().unwrap());
+        assert!(matches!(verify(&tampered), Err(AuditLogError::BrokenChain { seq: 2 })));
+
+        let root = root(&entries);
+        let mut inclusion = prove(&entries, 1).unwrap();
+        inclusion.entry.record = serde_json::json!({ "n": 100 });
+        assert!(matches!(inclusion.verify(&root), Err(AuditLogError::Tampered { seq: 1 })));
+        // An intact entry from another log is not under this root
+        let (other_path, other) = log("other", 4);
+        assert!(matches!(prove(&other, 1).unwrap().verify(&root), Err(AuditLogError::NotIncluded(_))));
+        fs::remove_file(&path).unwrap();
+        fs::remove_file(&other_path).unwrap();
+    }
+
+    #[test]
+    fn reordered_or_truncated_logs_are_rejected() {
+        let (path, entries) = log("reordered", 4);
+        let mut reordered = entries.clone();
+        reordered.swap(1, 2);
+        assert!(matches!(verify(&reordered), Err(AuditLogError::OutOfOrder { seq: 2, expected: 1 })));
+
+        // Renumbered to hide the swap, the chain still breaks
+        reordered[1].seq = 1;
+        reordered[2].seq = 2;
+        assert!(matches!(verify(&reordered), Err(AuditLogError::BrokenChain { seq: 1 })));
+        assert!(matches!(verify(&entries[1..]), Err(AuditLogError::OutOfOrder { seq: 1, expected: 0 })));
+
+        // An inclusion proof moved to another position does not verify
+        let mut moved = prove(&entries, 2).unwrap();
+        moved.opening.index = 1;
+        assert!(matches!(moved.verify(&root(&entries)), Err(AuditLogError::OutOfOrder { .. })));
+        assert!(matches!(prove(&entries, 4), Err(AuditLogError::NoSuchEntry { seq: 4 })));
+        fs::remove_file(&path).unwrap();
+    }
+}
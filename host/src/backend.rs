@@ -0,0 +1,232 @@
+//! Where receipt verification runs.
+//!
+//! Checking a receipt's seal is the part of accepting a bundle that works
+//! hardest on attacker-supplied bytes. A [`VerifierBackend`] decides where
+//! that happens: in-process ([`NativeBackend`]), in a separate process with
+//! an empty environment ([`ProcessBackend`]), or in a WebAssembly sandbox
+//! run by an external runtime ([`WasmBackend`]). Out-of-process backends
+//! speak the worker protocol of [`serve`]: the hex image ID and a newline,
+//! then the bincode receipt on stdin; one JSON [`WorkerVerdict`] on
+//! stdout. The `receipt-worker` binary implements it.
+
+use crate::untrusted::{self, MAX_BUNDLE_BYTES};
+use risc0_zkvm::sha::Digest;
+use risc0_zkvm::Receipt;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::{self, BufRead, Read, Write};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::str::FromStr;
+
+/// Why a backend did not vouch for a receipt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BackendError {
+    /// The receipt does not verify against the image ID.
+    Invalid(String),
+    /// The backend itself failed: it could not be started, crashed, or
+    /// answered outside the protocol. Says nothing about the receipt.
+    Unavailable(String),
+}
+
+impl fmt::Display for BackendError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BackendError::Invalid(reason) => f.write_str(reason),
+            BackendError::Unavailable(reason) => write!(f, "verifier backend failed: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for BackendError {}
+
+/// Runs receipt verification somewhere.
+pub trait VerifierBackend: Send + Sync {
+    /// Short name for reports, e.g. `native`.
+    fn name(&self) -> &'static str;
+
+    /// Check that `receipt` is valid for `image_id`.
+    fn verify(&self, receipt: &Receipt, image_id: Digest) -> Result<(), BackendError>;
+}
+
+/// Verification in the calling process, the default.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NativeBackend;
+
+impl VerifierBackend for NativeBackend {
+    fn name(&self) -> &'static str {
+        "native"
+    }
+
+    fn verify(&self, receipt: &Receipt, image_id: Digest) -> Result<(), BackendError> {
+        receipt.verify(image_id).map_err(|e| BackendError::Invalid(e.to_string()))
+    }
+}
+
+/// What a worker answers.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkerVerdict {
+    pub verified: bool,
+    /// Why the receipt did not verify.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Verification in a separate process: `program` with `args`, started for
+/// every receipt with an empty environment but for `RISC0_DEV_MODE`, so
+/// dev-mode receipts verify there exactly when they would in-process.
+#[derive(Debug, Clone)]
+pub struct ProcessBackend {
+    pub program: PathBuf,
+    pub args: Vec<String>,
+}
+
+impl ProcessBackend {
+    pub fn new(program: impl Into<PathBuf>) -> Self {
+        Self {
+            program: program.into(),
+            args: Vec::new(),
+        }
+    }
+
+    fn run(&self, receipt: &Receipt, image_id: Digest) -> Result<(), BackendError> {
+        let unavailable = |e: String| BackendError::Unavailable(format!("{}: {}", self.program.display(), e));
+        let encoded = bincode::serialize(receipt).map_err(|e| unavailable(e.to_string()))?;
+        let mut child = Command::new(&self.program)
+            .args(&self.args)
+            .env_clear()
+            .envs(std::env::var_os("RISC0_DEV_MODE").map(|value| ("RISC0_DEV_MODE", value)))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| unavailable(e.to_string()))?;
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let written = writeln!(stdin, "{}", image_id).and_then(|()| stdin.write_all(&encoded));
+        drop(stdin);
+        let output = child.wait_with_output().map_err(|e| unavailable(e.to_string()))?;
+        written.map_err(|e| unavailable(e.to_string()))?;
+        if !output.status.success() {
+            return Err(unavailable(format!("exited with {}", output.status)));
+        }
+        let verdict: WorkerVerdict = serde_json::from_slice(&output.stdout)
+            .map_err(|e| unavailable(format!("answer is not a verdict: {}", e)))?;
+        match verdict {
+            WorkerVerdict { verified: true, .. } => Ok(()),
+            WorkerVerdict { error, .. } => {
+                Err(BackendError::Invalid(error.unwrap_or_else(|| "rejected by the worker".to_string())))
+            }
+        }
+    }
+}
+
+impl VerifierBackend for ProcessBackend {
+    fn name(&self) -> &'static str {
+        "process"
+    }
+
+    fn verify(&self, receipt: &Receipt, image_id: Digest) -> Result<(), BackendError> {
+        self.run(receipt, image_id)
+    }
+}
+
+/// Verification in a WebAssembly module run by an external WASI runtime,
+/// e.g. `wasmtime run <module>`. The module is given no directories,
+/// network or environment; it only sees the receipt on stdin.
+#[derive(Debug, Clone)]
+pub struct WasmBackend {
+    process: ProcessBackend,
+}
+
+impl WasmBackend {
+    pub fn new(runtime: impl Into<PathBuf>, module: impl Into<PathBuf>) -> Self {
+        let module: PathBuf = module.into();
+        Self {
+            process: ProcessBackend {
+                program: runtime.into(),
+                args: vec!["run".to_string(), module.display().to_string()],
+            },
+        }
+    }
+}
+
+impl VerifierBackend for WasmBackend {
+    fn name(&self) -> &'static str {
+        "wasm"
+    }
+
+    fn verify(&self, receipt: &Receipt, image_id: Digest) -> Result<(), BackendError> {
+        self.process.run(receipt, image_id)
+    }
+}
+
+/// Which backend a command line asked for.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BackendKind {
+    #[default]
+    Native,
+    Process,
+    Wasm,
+}
+
+impl fmt::Display for BackendKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            BackendKind::Native => "native",
+            BackendKind::Process => "process",
+            BackendKind::Wasm => "wasm",
+        })
+    }
+}
+
+impl FromStr for BackendKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "native" => Ok(BackendKind::Native),
+            "process" => Ok(BackendKind::Process),
+            "wasm" => Ok(BackendKind::Wasm),
+            other => Err(format!("unknown verifier backend '{}' (expected native, process or wasm)", other)),
+        }
+    }
+}
+
+/// Answer one request of the worker protocol: read the image ID and the
+/// receipt from `input`, verify natively, and write the verdict to
+/// `output`. Fails only if `input` cannot be read or `output` written;
+/// a malformed request, or one over [`MAX_BUNDLE_BYTES`], is answered as
+/// a receipt that does not verify.
+pub fn serve(input: impl Read, mut output: impl Write) -> io::Result<()> {
+    let mut input = io::BufReader::new(input.take(MAX_BUNDLE_BYTES + 1));
+    let mut line = String::new();
+    input.read_line(&mut line)?;
+    let mut encoded = Vec::new();
+    input.read_to_end(&mut encoded)?;
+    let request = if (line.len() + encoded.len()) as u64 > MAX_BUNDLE_BYTES {
+        Err(format!("request is over the {} byte limit", MAX_BUNDLE_BYTES))
+    } else {
+        answer(line.trim(), &encoded)
+    };
+    let verdict = match request {
+        Ok(()) => WorkerVerdict {
+            verified: true,
+            error: None,
+        },
+        Err(error) => WorkerVerdict {
+            verified: false,
+            error: Some(error),
+        },
+    };
+    serde_json::to_writer(&mut output, &verdict)?;
+    output.flush()
+}
+
+fn answer(image_id: &str, encoded: &[u8]) -> Result<(), String> {
+    let image_id = hex::decode(image_id)
+        .ok()
+        .and_then(|bytes| Digest::try_from(bytes.as_slice()).ok())
+        .ok_or_else(|| format!("'{}' is not a 32-byte hex image ID", image_id))?;
+    let receipt: Receipt = untrusted::decode(encoded).map_err(|e| format!("receipt does not decode: {}", e))?;
+    NativeBackend.verify(&receipt, image_id).map_err(|e| e.to_string())
+}
@@ -0,0 +1,155 @@
+//! Date-ranged proving of historical exports, with the attested periods
+//! chained into a single commitment.
+
+use crate::proof::ProofKind;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::str::FromStr;
+
+/// A month (`2023-01`) or a day (`2023-01-31`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Period {
+    Month { year: u32, month: u32 },
+    Day { year: u32, month: u32, day: u32 },
+}
+
+fn days_in_month(year: u32, month: u32) -> u32 {
+    match month {
+        4 | 6 | 9 | 11 => 30,
+        2 if (year.is_multiple_of(4) && !year.is_multiple_of(100)) || year.is_multiple_of(400) => 29,
+        2 => 28,
+        _ => 31,
+    }
+}
+
+impl Period {
+    pub fn next(&self) -> Period {
+        match *self {
+            Period::Month { year, month: 12 } => Period::Month { year: year + 1, month: 1 },
+            Period::Month { year, month } => Period::Month { year, month: month + 1 },
+            Period::Day { year, month, day } if day < days_in_month(year, month) => {
+                Period::Day { year, month, day: day + 1 }
+            }
+            Period::Day { year, month: 12, .. } => Period::Day { year: year + 1, month: 1, day: 1 },
+            Period::Day { year, month, .. } => Period::Day { year, month: month + 1, day: 1 },
+        }
+    }
+
+    /// Every period from `from` to `to`, inclusive.
+    pub fn range(from: Period, to: Period) -> Result<Vec<Period>, String> {
+        if std::mem::discriminant(&from) != std::mem::discriminant(&to) {
+            return Err(format!("{} and {} are not the same granularity", from, to));
+        }
+        if from > to {
+            return Err(format!("{} is after {}", from, to));
+        }
+        let mut periods = vec![from];
+        while *periods.last().expect("starts non-empty") < to {
+            periods.push(periods.last().expect("starts non-empty").next());
+        }
+        Ok(periods)
+    }
+}
+
+impl FromStr for Period {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid period '{}' (expected YYYY-MM or YYYY-MM-DD)", s);
+        let parts = s
+            .split('-')
+            .map(|part| part.parse::<u32>().map_err(|_| invalid()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let period = match parts[..] {
+            [year, month] => Period::Month { year, month },
+            [year, month, day] => Period::Day { year, month, day },
+            _ => return Err(invalid()),
+        };
+        let valid = match period {
+            Period::Month { month, .. } => (1..=12).contains(&month),
+            Period::Day { year, month, day } => {
+                (1..=12).contains(&month) && (1..=days_in_month(year, month)).contains(&day)
+            }
+        };
+        if valid {
+            Ok(period)
+        } else {
+            Err(invalid())
+        }
+    }
+}
+
+impl fmt::Display for Period {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Period::Month { year, month } => write!(f, "{:04}-{:02}", year, month),
+            Period::Day { year, month, day } => write!(f, "{:04}-{:02}-{:02}", year, month, day),
+        }
+    }
+}
+
+/// Outcome of proving one period's file.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum PeriodStatus {
+    Attested {
+        csv_hash: String,
+        journal_digest: String,
+        proof_kind: ProofKind,
+        /// Chain head after linking this period.
+        chain: String,
+    },
+    Rejected { explanation: String },
+    Missing,
+    Failed { reason: String, message: String },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PeriodRecord {
+    pub period: String,
+    pub file: String,
+    #[serde(flatten)]
+    pub status: PeriodStatus,
+}
+
+/// Hash chain over attested periods: each link commits to the previous
+/// head, the period, and that period's journal digest, so the final head
+/// attests to the whole series in order.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StateChain {
+    head: [u8; 32],
+}
+
+impl StateChain {
+    pub fn link(&mut self, period: &Period, journal_digest: &[u8; 32]) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(b"zaik.backfill.v1");
+        hasher.update(self.head);
+        hasher.update(period.to_string().as_bytes());
+        hasher.update(journal_digest);
+        self.head = hasher.finalize().into();
+        self.head
+    }
+
+    pub fn head(&self) -> [u8; 32] {
+        self.head
+    }
+}
+
+/// Consolidated backfill result.
+#[derive(Debug, Clone, Serialize)]
+pub struct BackfillReport {
+    pub from: String,
+    pub to: String,
+    pub policy_version: String,
+    pub attested: usize,
+    pub chain_head: String,
+    pub periods: Vec<PeriodRecord>,
+}
+
+impl BackfillReport {
+    pub fn count(&self, matches: impl Fn(&PeriodStatus) -> bool) -> usize {
+        self.periods.iter().filter(|record| matches(&record.status)).count()
+    }
+}
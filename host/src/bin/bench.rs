@@ -0,0 +1,145 @@
+//! Prove/verify benchmarks across receipt kinds and dataset sizes.
+//!
+//! The proving backend is whatever `default_prover()` selects for the
+//! environment (local CPU, a CUDA/Metal build, or Bonsai when
+//! `BONSAI_API_URL`/`BONSAI_API_KEY` are set), so compare backends by
+//! running the suite once per environment; each row records the backend.
+
+use clap::{Parser, ValueEnum};
+use zaik::session::{ProofSession, ProverOptions};
+use zaik_guest::{GUEST_CODE_FOR_ZK_PROOF_ELF, GUEST_CODE_FOR_ZK_PROOF_ID};
+use risc0_zkvm::{default_prover, ReceiptKind};
+use serde::Serialize;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Instant;
+use zaik_core::{CsvProcessingInput, GuestInput, ProcessingOptions};
+
+#[derive(Debug, Parser)]
+struct Cli {
+    /// Dataset sizes, in data rows
+    #[arg(long, value_delimiter = ',', default_values_t = [10, 100, 1000])]
+    rows: Vec<usize>,
+
+    /// Receipt kinds to produce
+    #[arg(long, value_delimiter = ',', default_values = ["composite", "succinct"])]
+    kinds: Vec<Kind>,
+
+    /// Write the report here; `.csv` writes CSV, anything else JSON
+    #[arg(long, default_value = "bench.json")]
+    out: PathBuf,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Kind {
+    Composite,
+    Succinct,
+    Groth16,
+}
+
+impl From<Kind> for ReceiptKind {
+    fn from(kind: Kind) -> Self {
+        match kind {
+            Kind::Composite => ReceiptKind::Composite,
+            Kind::Succinct => ReceiptKind::Succinct,
+            Kind::Groth16 => ReceiptKind::Groth16,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct BenchRow {
+    rows: usize,
+    receipt_kind: Kind,
+    backend: String,
+    prove_ms: Option<u128>,
+    verify_ms: Option<u128>,
+    receipt_bytes: Option<usize>,
+    total_cycles: Option<u64>,
+    error: Option<String>,
+}
+
+/// Synthetic CSV with `rows` data rows in the demo's layout.
+fn dataset(rows: usize) -> String {
+    let mut csv = String::from("value_a,value_b,description\n");
+    for i in 0..rows {
+        csv.push_str(&format!("{},{},row {}\n", i % 1000, (i * 7) % 1000, i));
+    }
+    csv
+}
+
+fn bench(rows: usize, kind: Kind, backend: &str) -> BenchRow {
+    let csv_data = dataset(rows);
+    let input = GuestInput::from(CsvProcessingInput::from_data(csv_data, ProcessingOptions::default()));
+    let mut row = BenchRow {
+        rows,
+        receipt_kind: kind,
+        backend: backend.to_string(),
+        prove_ms: None,
+        verify_ms: None,
+        receipt_bytes: None,
+        total_cycles: None,
+        error: None,
+    };
+
+    let options = ProverOptions {
+        receipt_kind: Some(kind.into()),
+        ..ProverOptions::default()
+    };
+    let started = Instant::now();
+    let prove_info = match ProofSession::new(&input, GUEST_CODE_FOR_ZK_PROOF_ELF).with_options(options).prove() {
+        Ok(prove_info) => prove_info,
+        Err(e) => {
+            row.error = Some(e.to_string());
+            return row;
+        }
+    };
+    row.prove_ms = Some(started.elapsed().as_millis());
+    row.total_cycles = Some(prove_info.stats.total_cycles);
+    row.receipt_bytes = bincode::serialize(&prove_info.receipt).map(|bytes| bytes.len()).ok();
+
+    let started = Instant::now();
+    match prove_info.receipt.verify(GUEST_CODE_FOR_ZK_PROOF_ID) {
+        Ok(()) => row.verify_ms = Some(started.elapsed().as_millis()),
+        Err(e) => row.error = Some(format!("verification failed: {}", e)),
+    }
+    row
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let cli = Cli::parse();
+    let backend = default_prover().get_name();
+    println!("⏱️  Benchmarking on the {} prover", backend);
+
+    let mut report = Vec::new();
+    for &rows in &cli.rows {
+        for &kind in &cli.kinds {
+            let row = bench(rows, kind, &backend);
+            match &row.error {
+                None => println!(
+                    "  - {} rows, {:?}: prove {} ms, verify {} ms, {} bytes",
+                    rows,
+                    kind,
+                    row.prove_ms.unwrap_or_default(),
+                    row.verify_ms.unwrap_or_default(),
+                    row.receipt_bytes.unwrap_or_default()
+                ),
+                Some(error) => println!("  - {} rows, {:?}: ❌ {}", rows, kind, error),
+            }
+            report.push(row);
+        }
+    }
+
+    if cli.out.extension().is_some_and(|ext| ext == "csv") {
+        let mut writer = csv::Writer::from_path(&cli.out)?;
+        for row in &report {
+            writer.serialize(row)?;
+        }
+        writer.flush()?;
+    } else {
+        fs::write(&cli.out, serde_json::to_string_pretty(&report)?)?;
+    }
+    println!("💾 Wrote benchmark report: {}", cli.out.display());
+    Ok(())
+}
@@ -0,0 +1,207 @@
+//! Terminal dashboard for operators running provers against a shared store.
+//!
+//! ```text
+//! dashboard --store .zaik/proofs --events /var/log/zaik/prover-1.jsonl --events /var/log/zaik/prover-2.jsonl
+//! ```
+//!
+//! Shows the provers' current jobs (from the files they write with
+//! `--events`), how many bundles await a decision, dead-lettered webhook
+//! notifications, recent decisions and verification failures. Refreshes
+//! every `--interval`; `q` or Esc quits.
+
+use clap::Parser;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Paragraph, Row, Table};
+use ratatui::{DefaultTerminal, Frame};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+use zaik::dashboard::{DecisionRow, JobStage, Snapshot};
+use zaik::deadline;
+use zaik::outcome::{FailureReason, Outcome};
+use zaik::store::ProofStore;
+
+/// Watch a proof store and its provers.
+#[derive(Debug, Parser)]
+struct Cli {
+    /// Directory of proof bundles and their decisions
+    #[arg(long, default_value = ".zaik/proofs")]
+    store: PathBuf,
+
+    /// A prover's `--events` file; repeat for several provers
+    #[arg(long)]
+    events: Vec<PathBuf>,
+
+    /// How often to refresh, e.g. 2s
+    #[arg(long, default_value = "2s", value_parser = deadline::parse_duration)]
+    interval: Duration,
+
+    /// Treat an unfinished job whose events file has not changed for this long as stalled
+    #[arg(long, default_value = "10m", value_parser = deadline::parse_duration)]
+    stale: Duration,
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let store = match ProofStore::open(&cli.store) {
+        Ok(store) => store,
+        Err(e) => {
+            let failure = FailureReason::new(Outcome::Io, "store_unavailable", e.to_string());
+            eprintln!("{}", failure.to_json());
+            std::process::exit(failure.exit_code);
+        }
+    };
+    let mut terminal = ratatui::init();
+    let result = run(&mut terminal, &cli, &store);
+    ratatui::restore();
+    if let Err(failure) = result {
+        eprintln!("{}", failure.to_json());
+        std::process::exit(failure.exit_code);
+    }
+}
+
+fn run(terminal: &mut DefaultTerminal, cli: &Cli, store: &ProofStore) -> Result<(), FailureReason> {
+    let io = |e: std::io::Error| FailureReason::new(Outcome::Io, "terminal_unavailable", e.to_string());
+    loop {
+        // An unreadable store is shown rather than fatal; it may be mid-gc
+        let snapshot = Snapshot::collect(store, &cli.events, cli.stale).map_err(|e| e.to_string());
+        terminal.draw(|frame| draw(frame, &cli.store, &snapshot)).map_err(io)?;
+
+        let refreshed = Instant::now();
+        while refreshed.elapsed() < cli.interval {
+            if !event::poll(cli.interval.saturating_sub(refreshed.elapsed())).map_err(io)? {
+                break;
+            }
+            if let Event::Key(key) = event::read().map_err(io)? {
+                if key.kind == KeyEventKind::Press && matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+fn draw(frame: &mut Frame, store: &Path, snapshot: &Result<Snapshot, String>) {
+    let [header, jobs, decisions, failures] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Percentage(30),
+        Constraint::Percentage(40),
+        Constraint::Fill(1),
+    ])
+    .areas(frame.area());
+
+    let snapshot = match snapshot {
+        Ok(snapshot) => snapshot,
+        Err(e) => {
+            let error = Paragraph::new(format!("❌ {}: {}", store.display(), e))
+                .block(Block::bordered().title(" zaik "));
+            frame.render_widget(error, header);
+            return;
+        }
+    };
+
+    let failure_count = snapshot.verification_failures().count();
+    let summary = Line::from(format!(
+        "Active jobs: {}   Awaiting decision: {}   Dead-lettered webhooks: {}   Decisions: {}   Verification failures: {}",
+        snapshot.active_jobs().count(),
+        snapshot.awaiting_decision,
+        snapshot.dead_letters,
+        snapshot.decisions.len(),
+        failure_count,
+    ));
+    let title = format!(" zaik · {} · q to quit ", store.display());
+    frame.render_widget(Paragraph::new(summary).block(Block::bordered().title(title)), header);
+
+    let job_rows = snapshot.jobs.iter().map(|job| {
+        let (stage, color) = match job.stage {
+            JobStage::Executing => ("executing", Color::Yellow),
+            JobStage::Proving => ("proving", Color::Cyan),
+            JobStage::Finished => ("finished", Color::Green),
+            JobStage::Stalled => ("stalled", Color::Red),
+        };
+        let segments = match job.segments {
+            Some(total) => format!("{}/{}", job.segments_executed, total),
+            None => job.segments_executed.to_string(),
+        };
+        Row::new(vec![
+            job.events.display().to_string(),
+            stage.to_string(),
+            segments,
+            job.user_cycles.to_string(),
+            age(snapshot.taken, job.updated),
+        ])
+        .style(Style::default().fg(color))
+    });
+    let jobs_table = Table::new(
+        job_rows,
+        [
+            Constraint::Fill(1),
+            Constraint::Length(10),
+            Constraint::Length(10),
+            Constraint::Length(14),
+            Constraint::Length(10),
+        ],
+    )
+    .header(header_row(["Events file", "Stage", "Segments", "User cycles", "Updated"]))
+    .block(Block::bordered().title(" Proving jobs "));
+    frame.render_widget(jobs_table, jobs);
+
+    let recent = decision_table(snapshot.taken, snapshot.decisions.iter(), " Recent decisions ");
+    frame.render_widget(recent, decisions);
+    let failed = decision_table(snapshot.taken, snapshot.verification_failures(), " Verification failures ");
+    frame.render_widget(failed, failures);
+}
+
+fn decision_table<'a>(now: SystemTime, rows: impl Iterator<Item = &'a DecisionRow>, title: &'a str) -> Table<'a> {
+    let rows = rows.map(|row| {
+        let color = match row.outcome {
+            Outcome::Accept => Color::Green,
+            Outcome::Conditional => Color::Yellow,
+            _ => Color::Red,
+        };
+        let bundle = row.bundle.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+        Row::new(vec![
+            age(now, row.decided),
+            outcome_name(row.outcome),
+            bundle,
+            row.policy_version.chars().take(12).collect(),
+            row.explanation.clone(),
+        ])
+        .style(Style::default().fg(color))
+    });
+    Table::new(
+        rows,
+        [
+            Constraint::Length(10),
+            Constraint::Length(18),
+            Constraint::Length(24),
+            Constraint::Length(13),
+            Constraint::Fill(1),
+        ],
+    )
+    .header(header_row(["Decided", "Outcome", "Bundle", "Policy", "Explanation"]))
+    .block(Block::bordered().title(title))
+}
+
+fn header_row<'a>(titles: [&'a str; 5]) -> Row<'a> {
+    Row::new(titles).style(Style::default().add_modifier(Modifier::BOLD))
+}
+
+fn outcome_name(outcome: Outcome) -> String {
+    serde_json::to_value(outcome)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+/// `42s ago`, `5m ago` or `3h ago`.
+fn age(now: SystemTime, then: SystemTime) -> String {
+    let secs = now.duration_since(then).unwrap_or_default().as_secs();
+    match secs {
+        0..=59 => format!("{}s ago", secs),
+        60..=3599 => format!("{}m ago", secs / 60),
+        _ => format!("{}h ago", secs / 3600),
+    }
+}
@@ -0,0 +1,38 @@
+//! Receipt verification worker for out-of-process verifier backends.
+//!
+//! Reads one request of the worker protocol (see `zaik::backend`) on stdin
+//! and writes the verdict to stdout. It needs no files, network or
+//! environment, so it can run under a hardened process sandbox, or be
+//! built for a WASI target and run by a WebAssembly runtime:
+//!
+//! ```text
+//! cargo build --release -p zaik-cli --no-default-features --bin receipt-worker
+//! verify <bundle> ... --backend process --worker target/release/receipt-worker
+//! ```
+//!
+//! A SIGTERM or SIGINT that arrives while the request is being read stops
+//! the worker at once. Once it is read, the verdict is finished and written
+//! before the worker exits, so the caller never gets half of one.
+
+use std::io::{self, Read};
+use zaik::shutdown::StopFlag;
+use zaik::untrusted;
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("receipt-worker: {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn run() -> io::Result<()> {
+    // One byte over the limit, so `serve` still sees and refuses an oversized request
+    let mut request = Vec::new();
+    io::stdin().lock().take(untrusted::MAX_BUNDLE_BYTES + 1).read_to_end(&mut request)?;
+    let stop = StopFlag::register()?;
+    zaik::backend::serve(request.as_slice(), io::stdout().lock())?;
+    if stop.is_set() {
+        eprintln!("receipt-worker: stopped after writing the verdict");
+    }
+    Ok(())
+}
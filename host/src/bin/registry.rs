@@ -0,0 +1,224 @@
+//! HTTP API over the history and marketplace of a proof store.
+//!
+//! ```text
+//! registry --store .zaik/proofs --listen 127.0.0.1:8080
+//! curl 'http://127.0.0.1:8080/v1/proofs?status=rejected&from=1714521600&limit=20'
+//! curl -X POST http://127.0.0.1:8080/v1/offers -d @offer.json
+//! ```
+//!
+//! `GET /v1/proofs` lists bundles newest first, filtered by `status`,
+//! `outcome`, `from` and `to` and paged with `limit` and `cursor` (see
+//! [`zaik::history::Query`]). `GET /v1/proofs/<digest>` returns one bundle's
+//! record.
+//!
+//! `GET /v1/provers` lists every prover's stats and reputation score (see
+//! [`zaik::reputation`]); `GET /v1/provers/<key>` returns one prover's.
+//!
+//! `POST /v1/requests` and `POST /v1/offers` list a
+//! [`zaik::marketplace::ProofRequest`] or [`zaik::marketplace::ProofOffer`]
+//! and answer with the matches the new listing made; `GET` on either, or on
+//! `/v1/matches`, returns what is recorded.
+//!
+//! Every answer is JSON; errors are `{"error": "…"}`. There is no
+//! authentication, so listen on loopback or behind a proxy that adds it.
+
+use clap::Parser;
+use serde_json::json;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use zaik::history::{self, Query};
+use zaik::marketplace::{ProofOffer, ProofRequest};
+use zaik::outcome::{FailureReason, Outcome};
+use zaik::reputation;
+use zaik::store::ProofStore;
+
+/// Largest request head read from a client, on top of its body.
+const MAX_REQUEST_BYTES: u64 = 16 * 1024;
+
+/// Largest listing accepted in a request body.
+const MAX_BODY_BYTES: u64 = 64 * 1024;
+
+/// Serve the proof history of a store over HTTP.
+#[derive(Debug, Parser)]
+struct Cli {
+    /// Directory of proof bundles and their decisions
+    #[arg(long, default_value = ".zaik/proofs")]
+    store: PathBuf,
+
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    listen: SocketAddr,
+}
+
+fn main() {
+    let cli = Cli::parse();
+    if let Err(failure) = run(&cli) {
+        eprintln!("{}", failure.to_json());
+        std::process::exit(failure.exit_code);
+    }
+}
+
+fn run(cli: &Cli) -> Result<(), FailureReason> {
+    let store = ProofStore::open(&cli.store)
+        .map_err(|e| FailureReason::new(Outcome::Io, "store_unavailable", e.to_string()))?;
+    let registry = Arc::new(Registry {
+        store,
+        market: Mutex::new(()),
+    });
+    let listener = TcpListener::bind(cli.listen)
+        .map_err(|e| FailureReason::new(Outcome::Io, "listen_failed", e.to_string()))?;
+    println!("📡 Serving {} on http://{}/v1/proofs", cli.store.display(), cli.listen);
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else {
+            continue;
+        };
+        let registry = Arc::clone(&registry);
+        thread::spawn(move || {
+            if let Err(e) = handle(stream, &registry) {
+                println!("⚠️  Request failed: {}", e);
+            }
+        });
+    }
+    Ok(())
+}
+
+struct Registry {
+    store: ProofStore,
+    /// Held while a listing is added and matched, so matches never race
+    market: Mutex<()>,
+}
+
+/// A response: status code and JSON body.
+type Response = (u16, serde_json::Value);
+
+fn handle(mut stream: TcpStream, registry: &Registry) -> io::Result<()> {
+    let timeout = Some(Duration::from_secs(10));
+    stream.set_read_timeout(timeout)?;
+    stream.set_write_timeout(timeout)?;
+    let mut head = BufReader::new((&stream).take(MAX_REQUEST_BYTES + MAX_BODY_BYTES));
+    let mut request_line = String::new();
+    head.read_line(&mut request_line)?;
+    // Only the body length is needed from the headers
+    let mut header = String::new();
+    let mut content_length = 0;
+    while head.read_line(&mut header)? > 0 && header.trim_end() != "" {
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(u64::MAX);
+            }
+        }
+        header.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = (parts.next().unwrap_or_default(), parts.next().unwrap_or_default());
+    let (status, body) = match method {
+        "GET" => route(&registry.store, target),
+        "POST" if content_length > MAX_BODY_BYTES => (413, json!({ "error": "listing is too large" })),
+        "POST" => {
+            let mut body = Vec::new();
+            (&mut head).take(content_length).read_to_end(&mut body)?;
+            post(registry, target, &body)
+        }
+        _ => (405, json!({ "error": "only GET and POST are supported" })),
+    };
+    println!("📡 {} {} → {}", method, target, status);
+    let body = serde_json::to_vec(&body).expect("JSON values are always serializable");
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        reason_phrase(status),
+        body.len()
+    )?;
+    stream.write_all(&body)?;
+    stream.flush()
+}
+
+fn route(store: &ProofStore, target: &str) -> Response {
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let internal = |e: Box<dyn std::error::Error>| (500, json!({ "error": e.to_string() }));
+    match path.strip_prefix("/v1/proofs") {
+        Some("" | "/") => {
+            let query = match Query::parse(query) {
+                Ok(query) => query,
+                Err(e) => return (400, json!({ "error": e })),
+            };
+            match history::query(store, &query) {
+                Ok(page) => (200, json!(page)),
+                Err(e) => internal(e),
+            }
+        }
+        Some(digest) => match history::find(store, digest.trim_start_matches('/')) {
+            Ok(Some(record)) => (200, json!(record)),
+            Ok(None) => (404, json!({ "error": "no such bundle" })),
+            Err(e) => internal(e),
+        },
+        None => match path.strip_prefix("/v1/provers") {
+            Some("" | "/") => match reputation::stats(store) {
+                Ok(provers) => (200, json!({ "provers": provers })),
+                Err(e) => internal(e),
+            },
+            Some(prover) => match reputation::of(store, prover.trim_start_matches('/')) {
+                Ok(stats) => (200, json!(stats)),
+                Err(e) => internal(e),
+            },
+            None => market(store, path),
+        },
+    }
+}
+
+fn market(store: &ProofStore, path: &str) -> Response {
+    let market = store.marketplace();
+    let listed = match path {
+        "/v1/requests" => market.requests().map(|requests| json!({ "requests": requests })),
+        "/v1/offers" => market.offers().map(|offers| json!({ "offers": offers })),
+        "/v1/matches" => market.matches().map(|matches| json!({ "matches": matches })),
+        _ => {
+            let error = "not found; the API is under /v1/proofs, /v1/provers, /v1/requests, /v1/offers and /v1/matches";
+            return (404, json!({ "error": error }));
+        }
+    };
+    match listed {
+        Ok(body) => (200, body),
+        Err(e) => (500, json!({ "error": e.to_string() })),
+    }
+}
+
+fn post(registry: &Registry, target: &str, body: &[u8]) -> Response {
+    let market = registry.store.marketplace();
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    let _listing = registry.market.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let listed = match target {
+        "/v1/requests" => serde_json::from_slice::<ProofRequest>(body)
+            .map_err(|e| e.to_string())
+            .and_then(|request| market.post_request(&request, now).map_err(|e| e.to_string())),
+        "/v1/offers" => serde_json::from_slice::<ProofOffer>(body)
+            .map_err(|e| e.to_string())
+            .and_then(|offer| market.post_offer(&offer).map_err(|e| e.to_string())),
+        _ => return (404, json!({ "error": "listings are posted to /v1/requests or /v1/offers" })),
+    };
+    if let Err(e) = listed {
+        return (400, json!({ "error": e }));
+    }
+    match market.match_pending(now) {
+        Ok(matches) => (201, json!({ "matches": matches })),
+        Err(e) => (500, json!({ "error": e.to_string() })),
+    }
+}
+
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Payload Too Large",
+        _ => "Internal Server Error",
+    }
+}
@@ -0,0 +1,388 @@
+//! Relays accepted decisions to the settlement contract.
+//!
+//! Watches a proof store for bundles whose signed decision is an accept,
+//! submits each bundle's Groth16 seal and journal to the contract, waits
+//! for the transaction's settlement event, and then runs a settlement hook:
+//!
+//! ```text
+//! relay --store .zaik/proofs --rpc http://127.0.0.1:8545 --contract 0x… --from 0x… \
+//!     --trust-prover <hex> --trust-verifier <hex> --on-settled ./pay.sh
+//! ```
+//!
+//! Transactions are sent with `eth_sendTransaction`, so the node (or a
+//! signer proxy in front of it) must hold the `--from` key. Relayed bundles
+//! are recorded in `relayed.jsonl` in the store and are not sent twice.
+//!
+//! SIGTERM or SIGINT stops it taking new bundles: a settlement in flight is
+//! waited for, recorded and synced to disk before the process exits, so it
+//! is not sent again on the next start. A second signal exits at once.
+//!
+//! With `--probes 0.0.0.0:9091` it also serves `/healthz`, which fails once
+//! the relay loop stops making progress, and `/readyz`, which fails while the
+//! store is unwritable or the node does not answer `eth_blockNumber`.
+
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::error::Error;
+use std::fs;
+use std::io::Write;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use zaik::deadline;
+use zaik::ethereum;
+use zaik::health::{self, Check, HealthReport, Heartbeat};
+use zaik::journal;
+use zaik::outcome::{FailureReason, Outcome};
+use zaik::shutdown::StopFlag;
+use zaik::signing::{self, Decision, DetachedSignature};
+use zaik::store::ProofBundle;
+use zaik::telemetry::{self, Tracer};
+use zaik::untrusted;
+use zaik_core::AgentResult;
+
+/// Submit accepted bundles on-chain and trigger settlement.
+#[derive(Debug, Parser)]
+struct Cli {
+    /// Directory of proof bundles and their decisions
+    #[arg(long, default_value = ".zaik/proofs")]
+    store: PathBuf,
+
+    /// Ethereum JSON-RPC endpoint
+    #[arg(long, default_value = "http://127.0.0.1:8545")]
+    rpc: String,
+
+    /// Settlement contract address
+    #[arg(long)]
+    contract: String,
+
+    /// Account the node sends transactions from
+    #[arg(long)]
+    from: String,
+
+    /// Contract function taking (seal, journal)
+    #[arg(long, default_value = "settle(bytes,bytes)")]
+    function: String,
+
+    /// Event the contract emits on settlement; its first indexed topic must be the CSV hash
+    #[arg(long, default_value = "Settled(bytes32,bytes32,address)")]
+    event: String,
+
+    /// Accepted verifier public key (hex); repeat to allow several
+    #[arg(long = "trust-verifier", required = true)]
+    trusted_verifiers: Vec<String>,
+
+    /// Accepted prover public key (hex); repeat to allow several
+    #[arg(long = "trust-prover", required = true)]
+    trusted_provers: Vec<String>,
+
+    /// Shell command run after each settlement, with ZAIK_* variables set
+    #[arg(long)]
+    on_settled: Option<String>,
+
+    /// How often to scan the store, e.g. 15s
+    #[arg(long, default_value = "15s", value_parser = deadline::parse_duration)]
+    interval: Duration,
+
+    /// How long to wait for a transaction to be mined
+    #[arg(long, default_value = "5m", value_parser = deadline::parse_duration)]
+    confirm_timeout: Duration,
+
+    /// Relay what is pending once and exit
+    #[arg(long)]
+    once: bool,
+
+    /// Export a span per settlement to this OpenTelemetry collector (default: $OTEL_EXPORTER_OTLP_ENDPOINT)
+    #[arg(long, value_name = "URL")]
+    otlp: Option<String>,
+
+    /// Serve /healthz and /readyz on this address, e.g. 0.0.0.0:9091
+    #[arg(long, value_name = "ADDR")]
+    probes: Option<SocketAddr>,
+}
+
+/// How long a single settlement may take beyond one scan interval and the
+/// confirmation timeout before the loop counts as stuck.
+const STALL_GRACE: Duration = Duration::from_secs(300);
+
+/// One settled bundle, as recorded in `relayed.jsonl`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Settlement {
+    bundle: String,
+    csv_hash: String,
+    tx_hash: String,
+    block: u64,
+    settled_at: u64,
+}
+
+fn main() {
+    let cli = Cli::parse();
+    if let Err(failure) = run(&cli) {
+        eprintln!("{}", failure.to_json());
+        std::process::exit(failure.exit_code);
+    }
+}
+
+fn run(cli: &Cli) -> Result<(), FailureReason> {
+    let relayer = Relayer::new(cli);
+    let stop = StopFlag::register()
+        .map_err(|e| FailureReason::new(Outcome::Io, "signal_handler_failed", e.to_string()))?;
+    let heartbeat = Heartbeat::new(cli.interval + cli.confirm_timeout + STALL_GRACE);
+    if let Some(listen) = cli.probes {
+        let (store, rpc, client) = (cli.store.clone(), cli.rpc.clone(), relayer.client.clone());
+        let stopping = stop.clone();
+        let address = health::serve_probes(listen, heartbeat.clone(), move || {
+            let mut checks = vec![health::check_store(&store), check_rpc(&client, &rpc)];
+            if stopping.is_set() {
+                checks.push(Check::fail("shutdown", "stopping after the settlement in flight"));
+            }
+            HealthReport::new(checks)
+        })
+        .map_err(|e| FailureReason::new(Outcome::Io, "probes_unavailable", e.to_string()))?;
+        println!("🩺 Probes on http://{}/healthz and /readyz", address);
+    }
+    loop {
+        let failures = relayer.relay_pending(&heartbeat, &stop)?;
+        heartbeat.beat();
+        if cli.once {
+            return match failures {
+                0 => Ok(()),
+                n => Err(FailureReason::new(Outcome::Io, "relay_failed", format!("{} bundle(s) could not be relayed", n))),
+            };
+        }
+        if stop.sleep(cli.interval) {
+            println!("🛑 Stopped; unrelayed bundles are left for the next start");
+            return Ok(());
+        }
+    }
+}
+
+struct Relayer<'a> {
+    cli: &'a Cli,
+    client: reqwest::blocking::Client,
+    ledger: PathBuf,
+}
+
+impl<'a> Relayer<'a> {
+    fn new(cli: &'a Cli) -> Self {
+        Self {
+            cli,
+            client: reqwest::blocking::Client::new(),
+            ledger: cli.store.join("relayed.jsonl"),
+        }
+    }
+
+    /// Relay every accepted, not yet settled bundle; returns how many failed.
+    fn relay_pending(&self, heartbeat: &Heartbeat, stop: &StopFlag) -> Result<usize, FailureReason> {
+        let io = |e: Box<dyn Error>| FailureReason::new(Outcome::Io, "store_unreadable", e.to_string());
+        let settled = self.settled().map_err(io)?;
+        let mut failures = 0;
+        for bundle_path in accepted_bundles(&self.cli.store).map_err(io)? {
+            let name = bundle_path.display().to_string();
+            if stop.is_set() {
+                break;
+            }
+            if settled.iter().any(|settlement| settlement.bundle == name) {
+                continue;
+            }
+            heartbeat.beat();
+            let tracer = Tracer::new("zaik-relay");
+            let mut span = tracer.span("settle");
+            match self.relay(&bundle_path, &tracer) {
+                Ok(settlement) => {
+                    println!("✅ Settled {} in block {} ({})", name, settlement.block, settlement.tx_hash);
+                    span.attribute("zaik.tx_hash", &settlement.tx_hash);
+                    span.attribute("zaik.block", settlement.block);
+                    span.ok();
+                    self.record(&settlement).map_err(io)?;
+                    self.run_hook(&settlement);
+                }
+                Err(e) => {
+                    println!("❌ {}: {}", name, e);
+                    span.fail(e.to_string());
+                    failures += 1;
+                }
+            }
+            if let Some(endpoint) = telemetry::endpoint(self.cli.otlp.as_deref()) {
+                if let Err(e) = tracer.export(&endpoint) {
+                    println!("⚠️  Trace export to {} failed: {}", endpoint, e);
+                }
+            }
+        }
+        Ok(failures)
+    }
+
+    fn relay(&self, bundle_path: &Path, tracer: &Tracer) -> Result<Settlement, Box<dyn Error>> {
+        let bundle_bytes = untrusted::read(bundle_path, untrusted::MAX_BUNDLE_BYTES)?;
+        let decision_path = Decision::path_for(bundle_path);
+        let decision: Decision =
+            serde_json::from_slice(&untrusted::read(&decision_path, untrusted::MAX_SIDECAR_BYTES)?)?;
+        signing::verify_chain(
+            &bundle_bytes,
+            &DetachedSignature::load(&DetachedSignature::path_for(bundle_path))?,
+            &decision,
+            &DetachedSignature::load(&DetachedSignature::path_for(&decision_path))?,
+            &self.cli.trusted_provers,
+            &self.cli.trusted_verifiers,
+        )?;
+
+        let bundle = ProofBundle::from_bytes(&bundle_bytes)?;
+        tracer.bind_bundle(&bundle.key.digest());
+        let journal: AgentResult = journal::decode(&bundle.receipt.journal.bytes)?;
+        let seal = ethereum::encode_seal(&bundle.receipt)?;
+        let call = ethereum::encode_bytes_call(
+            ethereum::selector(&self.cli.function),
+            &[&seal, &bundle.receipt.journal.bytes],
+        );
+        println!("📤 Submitting {} ({} bytes of calldata)", bundle_path.display(), call.len());
+        let tx_hash = self.rpc(
+            "eth_sendTransaction",
+            json!([{ "from": self.cli.from, "to": self.cli.contract, "data": format!("0x{}", hex::encode(call)) }]),
+        )?;
+        let tx_hash = tx_hash.as_str().ok_or("eth_sendTransaction returned no hash")?.to_string();
+
+        let receipt = self.wait_for_receipt(&tx_hash)?;
+        if receipt["status"].as_str() != Some("0x1") {
+            return Err(format!("transaction {} reverted", tx_hash).into());
+        }
+        let topic = format!("0x{}", hex::encode(ethereum::event_topic(&self.cli.event)));
+        let csv_topic = format!("0x{}", hex::encode(journal.csv_hash));
+        let emitted = receipt["logs"].as_array().into_iter().flatten().any(|log| {
+            let topics = log["topics"].as_array();
+            log["address"].as_str().is_some_and(|address| address.eq_ignore_ascii_case(&self.cli.contract))
+                && topics.and_then(|topics| topics.first()).and_then(Value::as_str) == Some(topic.as_str())
+                && topics.and_then(|topics| topics.get(1)).and_then(Value::as_str) == Some(csv_topic.as_str())
+        });
+        if !emitted {
+            return Err(format!("transaction {} emitted no {} for this CSV", tx_hash, self.cli.event).into());
+        }
+
+        Ok(Settlement {
+            bundle: bundle_path.display().to_string(),
+            csv_hash: hex::encode(journal.csv_hash),
+            block: parse_quantity(&receipt["blockNumber"]).ok_or("receipt has no block number")?,
+            tx_hash,
+            settled_at: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+        })
+    }
+
+    fn wait_for_receipt(&self, tx_hash: &str) -> Result<Value, Box<dyn Error>> {
+        let started = Instant::now();
+        loop {
+            let receipt = self.rpc("eth_getTransactionReceipt", json!([tx_hash]))?;
+            if !receipt.is_null() {
+                return Ok(receipt);
+            }
+            if started.elapsed() > self.cli.confirm_timeout {
+                return Err(format!("transaction {} was not mined within {:?}", tx_hash, self.cli.confirm_timeout).into());
+            }
+            thread::sleep(Duration::from_secs(2));
+        }
+    }
+
+    fn rpc(&self, method: &str, params: Value) -> Result<Value, Box<dyn Error>> {
+        let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let response = self
+            .client
+            .post(&self.cli.rpc)
+            .header("Content-Type", "application/json")
+            .body(serde_json::to_vec(&request)?)
+            .send()?
+            .error_for_status()?
+            .bytes()?;
+        let response: Value = serde_json::from_slice(&response)?;
+        if let Some(error) = response.get("error") {
+            return Err(format!("{} failed: {}", method, error).into());
+        }
+        Ok(response["result"].clone())
+    }
+
+    fn settled(&self) -> Result<Vec<Settlement>, Box<dyn Error>> {
+        if !self.ledger.exists() {
+            return Ok(Vec::new());
+        }
+        fs::read_to_string(&self.ledger)?
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| Ok(serde_json::from_str(line)?))
+            .collect()
+    }
+
+    fn record(&self, settlement: &Settlement) -> Result<(), Box<dyn Error>> {
+        let mut file = fs::OpenOptions::new().create(true).append(true).open(&self.ledger)?;
+        writeln!(file, "{}", serde_json::to_string(settlement)?)?;
+        file.sync_data()?;
+        Ok(())
+    }
+
+    /// Run the settlement hook; a failing hook is reported, not retried,
+    /// since the settlement itself is already on-chain.
+    fn run_hook(&self, settlement: &Settlement) {
+        let Some(command) = &self.cli.on_settled else {
+            return;
+        };
+        let status = Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .env("ZAIK_BUNDLE", &settlement.bundle)
+            .env("ZAIK_CSV_HASH", &settlement.csv_hash)
+            .env("ZAIK_TX_HASH", &settlement.tx_hash)
+            .env("ZAIK_BLOCK", settlement.block.to_string())
+            .status();
+        match status {
+            Ok(status) if status.success() => println!("🔔 Settlement hook ran for {}", settlement.tx_hash),
+            Ok(status) => println!("⚠️  Settlement hook exited with {}", status),
+            Err(e) => println!("⚠️  Settlement hook could not run: {}", e),
+        }
+    }
+}
+
+/// Bundles in `store` with a recorded decision of `accept`, oldest first.
+fn accepted_bundles(store: &Path) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let mut bundles = Vec::new();
+    for entry in fs::read_dir(store)? {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("bundle") {
+            continue;
+        }
+        let Ok(decision) = untrusted::read_to_string(&Decision::path_for(&path), untrusted::MAX_SIDECAR_BYTES) else {
+            continue;
+        };
+        let decision: Decision = serde_json::from_str(&decision)?;
+        if decision.outcome == Outcome::Accept {
+            bundles.push((fs::metadata(&path)?.modified()?, path));
+        }
+    }
+    bundles.sort();
+    Ok(bundles.into_iter().map(|(_, path)| path).collect())
+}
+
+/// The node at `rpc` answers `eth_blockNumber`.
+fn check_rpc(client: &reqwest::blocking::Client, rpc: &str) -> Check {
+    match block_number(client, rpc) {
+        Ok(block) => Check::pass("rpc", format!("{} is at block {}", rpc, block)),
+        Err(e) => Check::fail("rpc", format!("{}: {}", rpc, e)),
+    }
+}
+
+fn block_number(client: &reqwest::blocking::Client, rpc: &str) -> Result<u64, Box<dyn Error>> {
+    let request = json!({ "jsonrpc": "2.0", "id": 1, "method": "eth_blockNumber", "params": [] });
+    let response = client
+        .post(rpc)
+        .header("Content-Type", "application/json")
+        .body(serde_json::to_vec(&request)?)
+        .timeout(Duration::from_secs(5))
+        .send()?
+        .error_for_status()?
+        .bytes()?;
+    let response: Value = serde_json::from_slice(&response)?;
+    Ok(parse_quantity(&response["result"]).ok_or("eth_blockNumber returned no block number")?)
+}
+
+/// A JSON-RPC hex quantity such as `"0x1b4"`.
+fn parse_quantity(value: &Value) -> Option<u64> {
+    u64::from_str_radix(value.as_str()?.strip_prefix("0x")?, 16).ok()
+}
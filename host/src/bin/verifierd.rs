@@ -0,0 +1,268 @@
+//! Agent B as a daemon.
+//!
+//! Watches a proof store for signed bundles that have no decision yet,
+//! verifies each against pinned guest image IDs and the policy, and writes
+//! a decision signed with the verifier key:
+//!
+//! ```text
+//! verifierd --store .zaik/proofs --allowlist images.toml --policy policy.toml --verifier-key verifier.key
+//! kill -HUP <pid>   # reload policy.toml
+//! ```
+//!
+//! On SIGHUP the policy file is read and checked again and swapped in
+//! without a restart; a file that fails to load leaves the running policy
+//! in place. Every decision records the version it was made under.
+//!
+//! SIGTERM or SIGINT stops it taking new bundles: the bundle being decided
+//! is finished and its signed decision written before the process exits,
+//! and `/readyz` fails meanwhile. A second signal exits at once.
+//!
+//! With `--probes 0.0.0.0:9090` it also serves `/healthz`, which fails once
+//! the scan loop stops making progress, and `/readyz`, which fails while the
+//! store, the verifier key or the policy file is unusable.
+
+use clap::Parser;
+use zaik::allowlist::ImageAllowlist;
+use zaik::deadline;
+use zaik::explain;
+use zaik::health::{self, Check, HealthReport, Heartbeat};
+use zaik::outcome::{FailureReason, Outcome};
+use zaik::policy::{Policy, SharedPolicy};
+use zaik::relying_party::RelyingParty;
+use zaik::reputation;
+use zaik::shutdown::StopFlag;
+use zaik::signing::{AgentKey, Decision, DetachedSignature, SignerRole};
+use zaik::store::{self, ProofBundle, ProofStore};
+use zaik::untrusted;
+use risc0_zkvm::sha::Digest;
+use std::error::Error;
+use std::fs;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+/// Verify new bundles in a proof store and sign a decision for each.
+#[derive(Debug, Parser)]
+struct Cli {
+    /// Proof store to watch
+    #[arg(long, default_value = ".zaik/proofs")]
+    store: PathBuf,
+
+    /// TOML file of trusted guest image IDs
+    #[arg(long)]
+    allowlist: PathBuf,
+
+    /// TOML policy decisions are made under; reloaded on SIGHUP
+    #[arg(long)]
+    policy: PathBuf,
+
+    /// Ed25519 key (PKCS#8) the decisions are signed with
+    #[arg(long)]
+    verifier_key: PathBuf,
+
+    /// Accept dev-mode receipts (test deployments only)
+    #[arg(long)]
+    allow_dev_mode: bool,
+
+    /// How often to scan the store, e.g. 15s
+    #[arg(long, default_value = "15s", value_parser = deadline::parse_duration)]
+    interval: Duration,
+
+    /// Decide what is pending once and exit
+    #[arg(long)]
+    once: bool,
+
+    /// Serve /healthz and /readyz on this address, e.g. 0.0.0.0:9090
+    #[arg(long, value_name = "ADDR")]
+    probes: Option<SocketAddr>,
+}
+
+/// How long a single bundle may take beyond one scan interval before the
+/// loop counts as stuck.
+const STALL_GRACE: Duration = Duration::from_secs(300);
+
+fn main() {
+    let cli = Cli::parse();
+    if let Err(failure) = run(&cli) {
+        eprintln!("{}", failure.to_json());
+        std::process::exit(failure.exit_code);
+    }
+}
+
+fn run(cli: &Cli) -> Result<(), FailureReason> {
+    let io = |reason: &str, e: Box<dyn Error>| FailureReason::new(Outcome::Io, reason, e.to_string());
+    let image_ids = ImageAllowlist::load(&cli.allowlist)
+        .and_then(|allowlist| allowlist.digests())
+        .map_err(|e| io("allowlist_unreadable", e))?;
+    let key = AgentKey::load(&cli.verifier_key).map_err(|e| io("signing_failed", e))?;
+    let policy = SharedPolicy::load(&cli.policy).map_err(|e| io("policy_unreadable", e))?;
+    println!("📋 Policy version: {}", policy.current().version());
+    reload_on_sighup(&policy).map_err(|e| io("signal_handler_failed", e))?;
+    let stop = StopFlag::register().map_err(|e| io("signal_handler_failed", e.into()))?;
+
+    let heartbeat = Heartbeat::new(cli.interval + STALL_GRACE);
+    if let Some(listen) = cli.probes {
+        let (store, verifier_key, policy_path) = (cli.store.clone(), cli.verifier_key.clone(), cli.policy.clone());
+        let stopping = stop.clone();
+        let address = health::serve_probes(listen, heartbeat.clone(), move || {
+            let mut checks = vec![
+                health::check_store(&store),
+                health::check_key("verifier_key", &verifier_key),
+                health::check_policy(&policy_path),
+            ];
+            if stopping.is_set() {
+                checks.push(Check::fail("shutdown", "stopping after the bundle in hand"));
+            }
+            HealthReport::new(checks)
+        })
+        .map_err(|e| io("probes_unavailable", e.into()))?;
+        println!("🩺 Probes on http://{}/healthz and /readyz", address);
+    }
+
+    loop {
+        let failures = decide_pending(cli, &image_ids, &key, &policy, &heartbeat, &stop)
+            .map_err(|e| io("store_unreadable", e))?;
+        heartbeat.beat();
+        if cli.once {
+            return match failures {
+                0 => Ok(()),
+                n => Err(FailureReason::new(Outcome::Io, "decision_failed", format!("{} bundle(s) could not be decided", n))),
+            };
+        }
+        if stop.sleep(cli.interval) {
+            println!("🛑 Stopped; undecided bundles are left for the next start");
+            return Ok(());
+        }
+    }
+}
+
+/// Reload `policy` from its file whenever the process gets SIGHUP.
+#[cfg(unix)]
+fn reload_on_sighup(policy: &SharedPolicy) -> Result<(), Box<dyn Error>> {
+    use signal_hook::consts::SIGHUP;
+    use signal_hook::iterator::Signals;
+
+    let mut signals = Signals::new([SIGHUP])?;
+    let policy = policy.clone();
+    thread::spawn(move || {
+        for _ in signals.forever() {
+            match policy.reload() {
+                Ok(version) => println!("🔄 Policy reloaded: version {}", version),
+                Err(e) => {
+                    println!("⚠️  Policy reload failed, keeping version {}: {}", policy.current().version(), e)
+                }
+            }
+        }
+    });
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn reload_on_sighup(_policy: &SharedPolicy) -> Result<(), Box<dyn Error>> {
+    println!("⚠️  SIGHUP policy reloads need a Unix host; restart to change the policy");
+    Ok(())
+}
+
+/// Decide every signed bundle without a decision; returns how many failed.
+fn decide_pending(
+    cli: &Cli,
+    image_ids: &[Digest],
+    key: &AgentKey,
+    policy: &SharedPolicy,
+    heartbeat: &Heartbeat,
+    stop: &StopFlag,
+) -> Result<usize, Box<dyn Error>> {
+    let mut failures = 0;
+    for bundle_path in pending_bundles(&cli.store)? {
+        if stop.is_set() {
+            break;
+        }
+        heartbeat.beat();
+        // One policy per decision, even if a reload lands meanwhile
+        let policy = policy.current();
+        match decide(cli, image_ids, key, &policy, &bundle_path) {
+            Ok(decision) => println!(
+                "📝 {}: {:?} under policy {}",
+                bundle_path.display(),
+                decision.outcome,
+                decision.policy_version
+            ),
+            Err(e) => {
+                println!("❌ {}: {}", bundle_path.display(), e);
+                failures += 1;
+            }
+        }
+    }
+    Ok(failures)
+}
+
+fn decide(
+    cli: &Cli,
+    image_ids: &[Digest],
+    key: &AgentKey,
+    policy: &Policy,
+    bundle_path: &Path,
+) -> Result<Decision, Box<dyn Error>> {
+    let bundle_bytes = untrusted::read(bundle_path, untrusted::MAX_BUNDLE_BYTES)?;
+    let prover_signature = DetachedSignature::load(&DetachedSignature::path_for(bundle_path))?;
+    prover_signature.verify(SignerRole::Prover, &bundle_bytes)?;
+    let bundle = ProofBundle::from_bytes(&bundle_bytes)?;
+
+    let mut relying_party = RelyingParty::new(policy.clone());
+    for image_id in image_ids {
+        relying_party = relying_party.allow_image(*image_id);
+    }
+    if cli.allow_dev_mode {
+        relying_party = relying_party.allow_dev_mode();
+    }
+    if policy.reputation.is_some() {
+        let stats = reputation::of(&ProofStore::open(&cli.store)?, &prover_signature.signer)?;
+        relying_party = relying_party.prover_score(stats.score);
+    }
+    let (outcome, explanation, assurance_tier) = match relying_party.accept(&bundle) {
+        Ok(acceptance) => (
+            Outcome::Accept,
+            explain::explain(&acceptance.policy_outcome),
+            policy.assurance.map(|_| acceptance.assurance_tier),
+        ),
+        Err(rejection) => {
+            let failure = rejection.to_failure();
+            (failure.outcome, failure.message, None)
+        }
+    };
+    let decision = Decision {
+        bundle_digest: prover_signature.digest,
+        prover: prover_signature.signer,
+        policy_version: policy.version(),
+        outcome,
+        explanation,
+        reveal: None,
+        assurance_tier,
+    };
+    // Signature first, decision last: a crash in between leaves the bundle
+    // pending rather than a decision nobody signed
+    let path = Decision::path_for(bundle_path);
+    key.sign(SignerRole::Verifier, &decision.to_bytes()).save(&DetachedSignature::path_for(&path))?;
+    store::write_atomic(&path, &decision.to_bytes())?;
+    Ok(decision)
+}
+
+/// Bundles in `store` with a prover signature and no signed decision,
+/// oldest first.
+fn pending_bundles(store: &Path) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let mut bundles = Vec::new();
+    for entry in fs::read_dir(store)? {
+        let path = entry?.path();
+        let decision = Decision::path_for(&path);
+        if path.extension().and_then(|ext| ext.to_str()) != Some("bundle")
+            || decision.exists() && DetachedSignature::path_for(&decision).exists()
+            || !DetachedSignature::path_for(&path).exists()
+        {
+            continue;
+        }
+        bundles.push((fs::metadata(&path)?.modified()?, path));
+    }
+    bundles.sort();
+    Ok(bundles.into_iter().map(|(_, path)| path).collect())
+}
@@ -0,0 +1,219 @@
+//! Standalone bundle verifier.
+//!
+//! Checks a bundle against image IDs pinned in an allowlist file instead of
+//! the guest built into the host, so it compiles without the RISC Zero
+//! guest toolchain:
+//!
+//! ```text
+//! cargo build --release -p zaik-cli --no-default-features --bin verify
+//! verify <bundle> --allowlist images.toml --policy policy.toml
+//! ```
+//!
+//! Exit codes and the stderr failure line match the host.
+
+use clap::Parser;
+use zaik::allowlist::ImageAllowlist;
+use zaik::backend::{BackendKind, ProcessBackend, WasmBackend};
+use zaik::expected::ExpectedHashes;
+use zaik::environment::RecordedManifest;
+use zaik::explain;
+use zaik::outcome::{FailureReason, Outcome};
+use zaik::policy::Policy;
+use zaik::relying_party::RelyingParty;
+use zaik::reputation;
+use zaik::signing::{DetachedSignature, SignerRole};
+use zaik::store::{ProofBundle, ProofStore};
+use zaik::telemetry::{self, Tracer};
+use zaik::timestamp::{self, TsaCertificate};
+use zaik::untrusted;
+use std::fs;
+use std::path::PathBuf;
+
+/// Verify a proof bundle against pinned guest image IDs and a policy.
+#[derive(Debug, Parser)]
+struct Cli {
+    /// Bundle to verify
+    bundle: PathBuf,
+
+    /// TOML file of trusted guest image IDs
+    #[arg(long)]
+    allowlist: PathBuf,
+
+    /// TOML policy the bundle must have been proven under
+    #[arg(long)]
+    policy: PathBuf,
+
+    /// TOML file of expected CSV hashes; bundles for any other data are rejected
+    #[arg(long)]
+    expect: Option<PathBuf>,
+
+    /// Accept dev-mode receipts (test deployments only)
+    #[arg(long)]
+    allow_dev_mode: bool,
+
+    /// A human reviewed this bundle, as a policy [assurance] tier may require
+    #[arg(long)]
+    human_reviewed: bool,
+
+    /// Proof store whose recorded decisions score the bundle's prover, for a policy with [reputation]
+    #[arg(long, value_name = "DIR")]
+    reputation_store: Option<PathBuf>,
+
+    /// Fail unless an RFC 3161 timestamp (<bundle>.tst) signed by --tsa-cert covers the bundle
+    #[arg(long, requires = "tsa_cert")]
+    require_timestamp: bool,
+
+    /// PEM or DER certificate of the timestamp authority whose signature a timestamp must carry
+    #[arg(long, value_name = "PATH")]
+    tsa_cert: Option<PathBuf>,
+
+    /// Write the CSV inlined in the journal, if any, to this file
+    #[arg(long, value_name = "PATH")]
+    extract_inline: Option<PathBuf>,
+
+    /// Where the receipt is verified: native (in this process), process or wasm
+    #[arg(long, default_value = "native")]
+    backend: BackendKind,
+
+    /// Worker program for `--backend process`, or WebAssembly module for `--backend wasm`
+    #[arg(long, value_name = "PATH", required_if_eq_any([("backend", "process"), ("backend", "wasm")]))]
+    worker: Option<PathBuf>,
+
+    /// WASI runtime that runs the module for `--backend wasm`, as `<runtime> run <module>`
+    #[arg(long, value_name = "PATH", default_value = "wasmtime")]
+    wasm_runtime: PathBuf,
+
+    /// Export a span for this verification to this OpenTelemetry collector (default: $OTEL_EXPORTER_OTLP_ENDPOINT)
+    #[arg(long, value_name = "URL")]
+    otlp: Option<String>,
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let tracer = Tracer::new("zaik-verify");
+    let mut span = tracer.span("verify");
+    let result = run(&cli, &tracer);
+    match &result {
+        Ok(()) => span.ok(),
+        Err(failure) => {
+            span.attribute("zaik.reason", &failure.reason);
+            span.fail(failure.message.clone());
+        }
+    }
+    if let Some(endpoint) = telemetry::endpoint(cli.otlp.as_deref()) {
+        if let Err(e) = tracer.export(&endpoint) {
+            println!("⚠️  Trace export to {} failed: {}", endpoint, e);
+        }
+    }
+    if let Err(failure) = result {
+        eprintln!("{}", failure.to_json());
+        std::process::exit(failure.exit_code);
+    }
+}
+
+fn run(cli: &Cli, tracer: &Tracer) -> Result<(), FailureReason> {
+    let io = |reason: &str, e: Box<dyn std::error::Error>| FailureReason::new(Outcome::Io, reason, e.to_string());
+    let allowlist = ImageAllowlist::load(&cli.allowlist).map_err(|e| io("allowlist_unreadable", e))?;
+    let policy = Policy::load(&cli.policy).map_err(|e| io("policy_unreadable", e))?;
+    let bundle = ProofBundle::load(&cli.bundle).map_err(|e| io("bundle_unreadable", e))?;
+    tracer.bind_bundle(&bundle.key.digest());
+
+    let mut relying_party = RelyingParty::new(policy);
+    for image_id in allowlist.digests().map_err(|e| io("allowlist_unreadable", e))? {
+        relying_party = relying_party.allow_image(image_id);
+    }
+    if let Some(path) = &cli.expect {
+        relying_party = relying_party.expect(ExpectedHashes::load(path).map_err(|e| io("expected_hashes_unreadable", e))?);
+    }
+    if cli.allow_dev_mode {
+        relying_party = relying_party.allow_dev_mode();
+    }
+    if cli.human_reviewed {
+        relying_party = relying_party.human_reviewed();
+    }
+    if let Some(score) = prover_score(cli).map_err(|e| io("reputation_unavailable", e))? {
+        relying_party = relying_party.prover_score(score);
+    }
+    match (cli.backend, &cli.worker) {
+        (BackendKind::Process, Some(worker)) => relying_party = relying_party.backend(ProcessBackend::new(worker)),
+        (BackendKind::Wasm, Some(module)) => {
+            relying_party = relying_party.backend(WasmBackend::new(&cli.wasm_runtime, module))
+        }
+        _ => {}
+    }
+    if let Some(recorded) = RecordedManifest::load(&cli.bundle).map_err(|e| io("environment_unreadable", e))? {
+        relying_party = relying_party.environment(recorded);
+    }
+
+    println!("🔍 Verifying {} ({} backend)", cli.bundle.display(), cli.backend);
+    let acceptance = relying_party.accept(&bundle).map_err(|rejection| rejection.to_failure())?;
+    println!("✅ Accepted {} receipt", acceptance.proof_kind);
+    println!("  - Guest image: {}", bundle.metadata.image_id);
+    println!("  - Column A sum: {} ({} tier)", acceptance.column_a_sum, acceptance.assurance_tier);
+    println!("  - {}", explain::explain(&acceptance.policy_outcome));
+    for note in &acceptance.compatibility {
+        println!("  - ⚠️  {}", note);
+    }
+    if let Some(path) = &cli.extract_inline {
+        let inline = &acceptance.journal.inline_csv.0;
+        if inline.is_empty() {
+            return Err(FailureReason::new(Outcome::Io, "inline_missing", "the journal carries no inline CSV"));
+        }
+        fs::write(path, inline).map_err(|e| FailureReason::new(Outcome::Io, "inline_unwritable", e.to_string()))?;
+        println!("  - 📄 Inline CSV ({} bytes) written to {}", inline.len(), path.display());
+    }
+    check_timestamp(cli)
+}
+
+/// Score of the prover that signed the bundle, from `--reputation-store`;
+/// none for an unsigned bundle or without the store.
+fn prover_score(cli: &Cli) -> Result<Option<u64>, Box<dyn std::error::Error>> {
+    let Some(store) = &cli.reputation_store else {
+        return Ok(None);
+    };
+    let Ok(signature) = DetachedSignature::load(&DetachedSignature::path_for(&cli.bundle)) else {
+        return Ok(None);
+    };
+    let bundle_bytes = untrusted::read(&cli.bundle, untrusted::MAX_BUNDLE_BYTES)?;
+    signature.verify(SignerRole::Prover, &bundle_bytes)?;
+    let stats = reputation::of(&ProofStore::open(store)?, &signature.signer)?;
+    println!(
+        "⭐ Prover {} scores {} ({} of {} accepted)",
+        signature.signer, stats.score, stats.accepted, stats.decided
+    );
+    Ok(Some(stats.score))
+}
+
+/// Report the bundle's timestamp; a token that does not cover it always
+/// fails, and so does one `--tsa-cert` did not sign. Without a certificate
+/// only the imprint is checked, and the time is reported as unverified.
+fn check_timestamp(cli: &Cli) -> Result<(), FailureReason> {
+    let token = match untrusted::read(&timestamp::path_for(&cli.bundle), untrusted::MAX_SIDECAR_BYTES) {
+        Ok(token) => token,
+        Err(_) if !cli.require_timestamp => return Ok(()),
+        Err(e) => {
+            return Err(FailureReason::new(
+                Outcome::CryptoFailure,
+                "timestamp_missing",
+                format!("no timestamp at {}: {}", timestamp::path_for(&cli.bundle).display(), e),
+            ))
+        }
+    };
+    let bundle_bytes = untrusted::read(&cli.bundle, untrusted::MAX_BUNDLE_BYTES)
+        .map_err(|e| FailureReason::new(Outcome::Io, "bundle_unreadable", e.to_string()))?;
+    let invalid =
+        |e: timestamp::TimestampError| FailureReason::new(Outcome::CryptoFailure, "timestamp_invalid", e.to_string());
+    let Some(tsa_cert) = &cli.tsa_cert else {
+        let info = timestamp::check_imprint(&token, &bundle_bytes).map_err(invalid)?;
+        println!(
+            "  - ⚠️  Timestamp imprint matches, claims {} (signature not checked without --tsa-cert)",
+            info.time
+        );
+        return Ok(());
+    };
+    let tsa = TsaCertificate::load(tsa_cert)
+        .map_err(|e| FailureReason::new(Outcome::Io, "tsa_cert_unreadable", e.to_string()))?;
+    let info = timestamp::verify(&token, &bundle_bytes, &tsa).map_err(invalid)?;
+    println!("  - 🕰️  Timestamped {} (TSA policy {}, serial {})", info.time, info.policy, info.serial);
+    Ok(())
+}
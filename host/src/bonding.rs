@@ -0,0 +1,235 @@
+//! Prover bonds: stake a prover agent puts up and loses by misbehaving.
+//!
+//! A prover posts a bond against its signing key; anyone holding
+//! [`SlashingEvidence`] against it, a bundle the prover signed that fails a
+//! check only a dishonest or broken prover fails, can have part of the bond
+//! slashed. The [`Ledger`] trait is what a real settlement layer would
+//! implement; [`MockLedger`] keeps balances as JSON lines in `bonds.jsonl`
+//! in a proof store, for trying the flow out without one:
+//!
+//! ```text
+//! cargo run --release -- bond post --prover <key> --amount 1000
+//! cargo run --release -- bond evidence .zaik/proofs/<digest>.bundle --policy policy.toml
+//! cargo run --release -- bond slash .zaik/proofs/<digest>.evidence.json --amount 500
+//! ```
+//!
+//! Each bundle is slashed at most once, and never for more than is bonded.
+
+use crate::protocol::{EvidenceError, Offence, SlashingEvidence};
+use risc0_zkvm::sha::Digest;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// One movement on the ledger.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum LedgerEntry {
+    Posted {
+        /// Hex public key the prover signs bundles with.
+        prover: String,
+        amount: u64,
+        /// Seconds since the Unix epoch.
+        at: u64,
+    },
+    Slashed {
+        prover: String,
+        amount: u64,
+        /// Hex SHA-256 of the bundle the evidence is about.
+        bundle_digest: String,
+        /// Stable reason of the check the bundle failed.
+        reason: String,
+        at: u64,
+    },
+}
+
+/// A slash that went through.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Slash {
+    pub offence: Offence,
+    /// What was taken, at most the bond.
+    pub amount: u64,
+    /// What is left bonded.
+    pub remaining: u64,
+}
+
+/// Why a slash did not go through.
+#[derive(Debug)]
+pub enum BondError {
+    Evidence(EvidenceError),
+    /// This bundle was slashed for before.
+    AlreadySlashed { bundle_digest: String },
+    /// The prover has nothing bonded.
+    NoBond { prover: String },
+    Io(Box<dyn Error>),
+}
+
+impl fmt::Display for BondError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BondError::Evidence(e) => write!(f, "{}", e),
+            BondError::AlreadySlashed { bundle_digest } => {
+                write!(f, "bundle {} was already slashed for", bundle_digest)
+            }
+            BondError::NoBond { prover } => write!(f, "prover {} has no bond", prover),
+            BondError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for BondError {}
+
+impl From<Box<dyn Error>> for BondError {
+    fn from(e: Box<dyn Error>) -> Self {
+        BondError::Io(e)
+    }
+}
+
+/// Where bonds are held.
+pub trait Ledger {
+    /// What `prover` has bonded now.
+    fn balance(&self, prover: &str) -> Result<u64, Box<dyn Error>>;
+
+    /// Add `amount` to `prover`'s bond; returns the new balance.
+    fn post(&self, prover: &str, amount: u64) -> Result<u64, Box<dyn Error>>;
+
+    /// Take up to `amount` from the bond of the prover `offence` names.
+    fn forfeit(&self, offence: &Offence, amount: u64) -> Result<Slash, BondError>;
+
+    /// Verify `evidence` against the trusted `image_ids` and slash the
+    /// prover it convicts.
+    fn slash(&self, evidence: &SlashingEvidence, image_ids: &[Digest], amount: u64) -> Result<Slash, BondError> {
+        let offence = evidence.verify(image_ids).map_err(BondError::Evidence)?;
+        self.forfeit(&offence, amount)
+    }
+}
+
+/// A [`Ledger`] kept in a local JSON lines file.
+pub struct MockLedger {
+    path: PathBuf,
+}
+
+impl MockLedger {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Every movement, oldest first.
+    pub fn entries(&self) -> Result<Vec<LedgerEntry>, Box<dyn Error>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        fs::read_to_string(&self.path)?
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| Ok(serde_json::from_str(line)?))
+            .collect()
+    }
+
+    fn record(&self, entry: &LedgerEntry) -> Result<(), Box<dyn Error>> {
+        let mut file = fs::OpenOptions::new().create(true).append(true).open(&self.path)?;
+        writeln!(file, "{}", serde_json::to_string(entry)?)?;
+        Ok(())
+    }
+}
+
+impl Ledger for MockLedger {
+    fn balance(&self, prover: &str) -> Result<u64, Box<dyn Error>> {
+        Ok(self.entries()?.iter().fold(0u64, |balance, entry| match entry {
+            LedgerEntry::Posted { prover: p, amount, .. } if p == prover => balance.saturating_add(*amount),
+            LedgerEntry::Slashed { prover: p, amount, .. } if p == prover => balance.saturating_sub(*amount),
+            _ => balance,
+        }))
+    }
+
+    fn post(&self, prover: &str, amount: u64) -> Result<u64, Box<dyn Error>> {
+        if amount == 0 {
+            return Err("a bond must be more than 0".into());
+        }
+        self.record(&LedgerEntry::Posted { prover: prover.to_string(), amount, at: now() })?;
+        self.balance(prover)
+    }
+
+    fn forfeit(&self, offence: &Offence, amount: u64) -> Result<Slash, BondError> {
+        let slashed_before = self.entries()?.iter().any(|entry| {
+            matches!(entry, LedgerEntry::Slashed { bundle_digest, .. } if *bundle_digest == offence.bundle_digest)
+        });
+        if slashed_before {
+            return Err(BondError::AlreadySlashed { bundle_digest: offence.bundle_digest.clone() });
+        }
+        let balance = self.balance(&offence.prover)?;
+        if balance == 0 {
+            return Err(BondError::NoBond { prover: offence.prover.clone() });
+        }
+        let amount = amount.min(balance);
+        self.record(&LedgerEntry::Slashed {
+            prover: offence.prover.clone(),
+            amount,
+            bundle_digest: offence.bundle_digest.clone(),
+            reason: offence.reason.clone(),
+            at: now(),
+        })?;
+        Ok(Slash { offence: offence.clone(), amount, remaining: balance - amount })
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policy::Policy;
+    use crate::signing::{AgentKey, SignerRole};
+
+    fn ledger(name: &str) -> MockLedger {
+        let path = std::env::temp_dir().join(format!("zaik-bonds-{}-{}.jsonl", name, std::process::id()));
+        let _ = fs::remove_file(&path);
+        MockLedger::new(path)
+    }
+
+    fn offence(bundle_digest: &str) -> Offence {
+        Offence {
+            prover: "aa".into(),
+            bundle_digest: bundle_digest.into(),
+            reason: "bound_mismatch".into(),
+            detail: String::new(),
+        }
+    }
+
+    #[test]
+    fn slash_is_capped_at_the_bond_and_taken_once_per_bundle() {
+        let ledger = ledger("forfeit");
+        assert!(matches!(ledger.forfeit(&offence("01"), 10), Err(BondError::NoBond { .. })));
+        ledger.post("aa", 300).unwrap();
+        assert_eq!(ledger.post("aa", 200).unwrap(), 500);
+
+        let slash = ledger.forfeit(&offence("01"), 400).unwrap();
+        assert_eq!((slash.amount, slash.remaining), (400, 100));
+        assert!(matches!(ledger.forfeit(&offence("01"), 400), Err(BondError::AlreadySlashed { .. })));
+        assert_eq!(ledger.forfeit(&offence("02"), 400).unwrap().amount, 100);
+        assert_eq!(ledger.balance("aa").unwrap(), 0);
+        let _ = fs::remove_file(&ledger.path);
+    }
+
+    #[test]
+    fn evidence_the_prover_did_not_sign_slashes_nothing() {
+        let ledger = ledger("unsigned");
+        let key_path = std::env::temp_dir().join(format!("zaik-bonds-key-{}", std::process::id()));
+        let _ = fs::remove_file(&key_path);
+        let key = AgentKey::generate(&key_path).unwrap();
+        ledger.post(&key.public_key(), 100).unwrap();
+
+        let signature = key.sign(SignerRole::Prover, b"some other bundle");
+        let evidence = SlashingEvidence::new(b"not a bundle", signature, Policy::new(1000));
+        assert!(matches!(ledger.slash(&evidence, &[], 100), Err(BondError::Evidence(EvidenceError::Signature(_)))));
+        assert_eq!(ledger.balance(&key.public_key()).unwrap(), 100);
+        let _ = fs::remove_file(&ledger.path);
+        let _ = fs::remove_file(&key_path);
+    }
+}
@@ -0,0 +1,258 @@
+//! Cumulative caps on column A sums over a reporting period.
+//!
+//! Each proof accepted under a policy with a [`Budget`] adds its verified
+//! sum to the running total of its tenant and period, and a proof whose sum
+//! would push that total over the cap is rejected. The [`BudgetLedger`]
+//! checkpoints every total in a hash chain per tenant and period: each link
+//! commits to the previous checkpoint, the proof's journal digest and the
+//! new total, so the total can be recomputed from the accepted journals and
+//! editing any entry breaks every checkpoint after it.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A policy's cap, in its `[budget]` table:
+/// ```toml
+/// [budget]
+/// cap = 50000   # most one tenant's sums may add up to in a period
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Budget {
+    pub cap: u64,
+}
+
+/// One accepted proof, as recorded in the ledger.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BudgetEntry {
+    pub tenant: String,
+    /// Reporting period, e.g. `2024-03`.
+    pub period: String,
+    /// Hex CSV hash of the proven file.
+    pub csv_hash: String,
+    /// Hex SHA-256 of the proof's journal.
+    pub journal_digest: String,
+    /// Column A sum the proof attested.
+    pub sum: u64,
+    /// Running total for the tenant and period, this entry included.
+    pub total: u64,
+    /// Chain head after this entry; see [`BudgetChain`].
+    pub checkpoint: String,
+    /// Policy version the proof was accepted under.
+    pub policy: String,
+    /// Seconds since the Unix epoch.
+    pub recorded_at: u64,
+}
+
+/// Hash chain over one tenant's totals in one period.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BudgetChain {
+    head: [u8; 32],
+}
+
+impl BudgetChain {
+    pub fn link(&mut self, tenant: &str, period: &str, journal_digest: &[u8; 32], total: u64) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(b"zaik.budget.v1");
+        hasher.update(self.head);
+        // Length-prefixed, so tenant and period cannot run into each other
+        for part in [tenant, period] {
+            hasher.update((part.len() as u64).to_le_bytes());
+            hasher.update(part.as_bytes());
+        }
+        hasher.update(journal_digest);
+        hasher.update(total.to_le_bytes());
+        self.head = hasher.finalize().into();
+        self.head
+    }
+
+    pub fn head(&self) -> [u8; 32] {
+        self.head
+    }
+}
+
+/// A tenant's verified position in a period.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Standing {
+    pub total: u64,
+    pub entries: usize,
+    /// All zero before the first entry.
+    pub checkpoint: [u8; 32],
+}
+
+/// Why a proof was not admitted or the ledger does not verify.
+#[derive(Debug)]
+pub enum BudgetError {
+    /// The proof's sum would take the period total over the cap.
+    Exceeded { total: u64, sum: u64, cap: u64 },
+    /// An entry's total or checkpoint does not follow from the ones before.
+    Broken { tenant: String, period: String, entry: usize },
+    Io(Box<dyn Error>),
+}
+
+impl fmt::Display for BudgetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BudgetError::Exceeded { total, sum, cap } => write!(
+                f,
+                "sum {} would take the period total from {} past the budget of {}",
+                sum, total, cap
+            ),
+            BudgetError::Broken { tenant, period, entry } => write!(
+                f,
+                "budget ledger entry {} for {} in {} does not follow from the entries before it",
+                entry, tenant, period
+            ),
+            BudgetError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for BudgetError {}
+
+impl From<Box<dyn Error>> for BudgetError {
+    fn from(e: Box<dyn Error>) -> Self {
+        BudgetError::Io(e)
+    }
+}
+
+/// Append-only JSON-lines record of accepted proofs and running totals.
+pub struct BudgetLedger {
+    path: PathBuf,
+}
+
+impl BudgetLedger {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Every recorded entry, oldest first; none if the ledger is new.
+    pub fn entries(&self) -> Result<Vec<BudgetEntry>, Box<dyn Error>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        fs::read_to_string(&self.path)?
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| Ok(serde_json::from_str(line)?))
+            .collect()
+    }
+
+    /// Every `(tenant, period)` with entries, in order of first appearance.
+    pub fn accounts(&self) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+        let mut accounts = Vec::new();
+        for entry in self.entries()? {
+            let account = (entry.tenant, entry.period);
+            if !accounts.contains(&account) {
+                accounts.push(account);
+            }
+        }
+        Ok(accounts)
+    }
+
+    /// Replay `tenant`'s entries for `period`, checking every total and
+    /// checkpoint.
+    pub fn standing(&self, tenant: &str, period: &str) -> Result<Standing, BudgetError> {
+        let mut chain = BudgetChain::default();
+        let mut standing = Standing::default();
+        for entry in self.entries()?.iter().filter(|e| e.tenant == tenant && e.period == period) {
+            let broken = || BudgetError::Broken {
+                tenant: tenant.to_string(),
+                period: period.to_string(),
+                entry: standing.entries,
+            };
+            let journal_digest: [u8; 32] = hex::decode(&entry.journal_digest)
+                .ok()
+                .and_then(|bytes| bytes.try_into().ok())
+                .ok_or_else(broken)?;
+            let total = standing.total.checked_add(entry.sum).ok_or_else(broken)?;
+            let checkpoint = chain.link(tenant, period, &journal_digest, total);
+            if entry.total != total || entry.checkpoint != hex::encode(checkpoint) {
+                return Err(broken());
+            }
+            standing = Standing {
+                total,
+                entries: standing.entries + 1,
+                checkpoint,
+            };
+        }
+        Ok(standing)
+    }
+
+    /// Whether a proof of `csv_hash` fits in `tenant`'s budget for `period`.
+    /// A file already counted in the period is not counted again.
+    pub fn admit(
+        &self,
+        budget: &Budget,
+        tenant: &str,
+        period: &str,
+        csv_hash: &str,
+        sum: u64,
+    ) -> Result<Standing, BudgetError> {
+        let standing = self.standing(tenant, period)?;
+        if self.counted(tenant, period, csv_hash)? {
+            return Ok(standing);
+        }
+        match standing.total.checked_add(sum) {
+            Some(total) if total <= budget.cap => Ok(standing),
+            _ => Err(BudgetError::Exceeded {
+                total: standing.total,
+                sum,
+                cap: budget.cap,
+            }),
+        }
+    }
+
+    /// Add an accepted proof to `tenant`'s total for `period`, unless its
+    /// file is already counted there. Returns the new entry, if any.
+    pub fn record(
+        &self,
+        tenant: &str,
+        period: &str,
+        csv_hash: &str,
+        journal_digest: &[u8; 32],
+        sum: u64,
+        policy: &str,
+    ) -> Result<Option<BudgetEntry>, BudgetError> {
+        if self.counted(tenant, period, csv_hash)? {
+            return Ok(None);
+        }
+        let standing = self.standing(tenant, period)?;
+        let total = standing
+            .total
+            .checked_add(sum)
+            .ok_or_else(|| BudgetError::Io("period total overflows u64".into()))?;
+        let mut chain = BudgetChain { head: standing.checkpoint };
+        let entry = BudgetEntry {
+            tenant: tenant.to_string(),
+            period: period.to_string(),
+            csv_hash: csv_hash.to_string(),
+            journal_digest: hex::encode(journal_digest),
+            sum,
+            total,
+            checkpoint: hex::encode(chain.link(tenant, period, journal_digest, total)),
+            policy: policy.to_string(),
+            recorded_at: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+        };
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| BudgetError::Io(e.into()))?;
+        let line = serde_json::to_string(&entry).map_err(|e| BudgetError::Io(e.into()))?;
+        writeln!(file, "{}", line).map_err(|e| BudgetError::Io(e.into()))?;
+        Ok(Some(entry))
+    }
+
+    fn counted(&self, tenant: &str, period: &str, csv_hash: &str) -> Result<bool, Box<dyn Error>> {
+        Ok(self
+            .entries()?
+            .iter()
+            .any(|e| e.tenant == tenant && e.period == period && e.csv_hash == csv_hash))
+    }
+}
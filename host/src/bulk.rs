@@ -0,0 +1,131 @@
+//! Verifying a whole directory of bundles at once, for periodic re-audits
+//! of an archive.
+//!
+//! Every bundle goes through a full [`RelyingParty::accept`]: its receipt is
+//! verified again and the policy re-evaluated, whatever was decided when it
+//! was stored. Bundles are spread over worker threads; the report lists
+//! them in the order given.
+
+use crate::outcome::{FailureReason, Outcome};
+use crate::relying_party::RelyingParty;
+use crate::store::ProofBundle;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// What happened to one bundle.
+#[derive(Debug, Clone, Serialize)]
+pub struct BundleVerdict {
+    pub bundle: String,
+    pub outcome: Outcome,
+    /// Stable reason for anything but an accept, e.g. `invariant_violated`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    pub elapsed_ms: u64,
+}
+
+impl BundleVerdict {
+    pub fn passed(&self) -> bool {
+        self.outcome == Outcome::Accept
+    }
+}
+
+/// Summary of one bulk run.
+#[derive(Debug, Clone, Serialize)]
+pub struct BulkReport {
+    pub policy_version: String,
+    /// Seconds since the Unix epoch.
+    pub checked_at: u64,
+    pub total: usize,
+    pub passed: usize,
+    pub failed: usize,
+    /// Failed bundles per reason.
+    pub failures: BTreeMap<String, usize>,
+    pub bundles: Vec<BundleVerdict>,
+}
+
+impl BulkReport {
+    pub fn new(policy_version: String, bundles: Vec<BundleVerdict>) -> Self {
+        let mut failures = BTreeMap::new();
+        for verdict in bundles.iter().filter(|verdict| !verdict.passed()) {
+            *failures.entry(verdict.reason.clone().unwrap_or_default()).or_insert(0) += 1;
+        }
+        let passed = bundles.iter().filter(|verdict| verdict.passed()).count();
+        Self {
+            policy_version,
+            checked_at: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+            total: bundles.len(),
+            passed,
+            failed: bundles.len() - passed,
+            failures,
+            bundles,
+        }
+    }
+
+    /// The first bundle that did not pass, in the order given.
+    pub fn first_failure(&self) -> Option<&BundleVerdict> {
+        self.bundles.iter().find(|verdict| !verdict.passed())
+    }
+}
+
+/// Verify each of `bundles` on up to `jobs` threads. `party` builds the
+/// relying party for a bundle, so per-bundle inputs such as its recorded
+/// environment manifest can be attached.
+pub fn verify_all<F>(bundles: &[PathBuf], jobs: usize, party: F) -> Vec<BundleVerdict>
+where
+    F: Fn(&Path) -> Result<RelyingParty, FailureReason> + Sync,
+{
+    let next = AtomicUsize::new(0);
+    let mut verdicts: Vec<(usize, BundleVerdict)> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..jobs.max(1))
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut done = Vec::new();
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(path) = bundles.get(index) else {
+                            return done;
+                        };
+                        done.push((index, verify_one(path, &party)));
+                    }
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("bundle verification panicked"))
+            .collect()
+    });
+    verdicts.sort_by_key(|(index, _)| *index);
+    verdicts.into_iter().map(|(_, verdict)| verdict).collect()
+}
+
+fn verify_one<F>(path: &Path, party: &F) -> BundleVerdict
+where
+    F: Fn(&Path) -> Result<RelyingParty, FailureReason>,
+{
+    let started = Instant::now();
+    let result = ProofBundle::load(path)
+        .map_err(|e| FailureReason::new(Outcome::Io, "bundle_unreadable", e.to_string()))
+        .and_then(|bundle| {
+            party(path)?
+                .accept(&bundle)
+                .map_err(|rejection| rejection.to_failure())
+        });
+    let (outcome, reason, message) = match result {
+        Ok(_) => (Outcome::Accept, None, None),
+        Err(failure) => (failure.outcome, Some(failure.reason), Some(failure.message)),
+    };
+    BundleVerdict {
+        bundle: path.display().to_string(),
+        outcome,
+        reason,
+        message,
+        elapsed_ms: started.elapsed().as_millis().min(u64::MAX as u128) as u64,
+    }
+}
@@ -0,0 +1,159 @@
+//! Canonical JSON (RFC 8785, JCS) for anything that gets hashed.
+//!
+//! Object members are sorted by their UTF-16 code units, there is no
+//! whitespace, and numbers use the ECMAScript shortest form, so a digest
+//! depends only on the values and not on struct field order or the
+//! serializer's formatting choices.
+
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+/// Serialize `value` as canonical JSON.
+pub fn to_vec<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, serde_json::Error> {
+    let mut out = String::new();
+    write_value(&mut out, &serde_json::to_value(value)?)?;
+    Ok(out.into_bytes())
+}
+
+/// SHA-256 of `value`'s canonical JSON.
+pub fn digest<T: Serialize + ?Sized>(value: &T) -> Result<[u8; 32], serde_json::Error> {
+    Ok(Sha256::digest(to_vec(value)?).into())
+}
+
+fn write_value(out: &mut String, value: &Value) -> Result<(), serde_json::Error> {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Number(number) => match number.as_f64() {
+            Some(_) if number.is_u64() || number.is_i64() => out.push_str(&number.to_string()),
+            Some(float) => out.push_str(&es6_number(float)),
+            None => out.push_str(&number.to_string()),
+        },
+        Value::String(s) => out.push_str(&serde_json::to_string(s)?),
+        Value::Array(items) => {
+            out.push('[');
+            for (index, item) in items.iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+                write_value(out, item)?;
+            }
+            out.push(']');
+        }
+        Value::Object(members) => {
+            let mut members: Vec<_> = members.iter().collect();
+            members.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+            out.push('{');
+            for (index, (key, item)) in members.into_iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+                out.push_str(&serde_json::to_string(key)?);
+                out.push(':');
+                write_value(out, item)?;
+            }
+            out.push('}');
+        }
+    }
+    Ok(())
+}
+
+/// ECMAScript `Number.prototype.toString` for a finite float.
+fn es6_number(value: f64) -> String {
+    if value == 0.0 {
+        return "0".to_string();
+    }
+    // `{:e}` gives the shortest round-trip digits, e.g. `-1.25e-7`
+    let scientific = format!("{:e}", value);
+    let (mantissa, exponent) = scientific.split_once('e').expect("{:e} has an exponent");
+    let (sign, mantissa) = mantissa.strip_prefix('-').map_or(("", mantissa), |rest| ("-", rest));
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+    let k = digits.len() as i32;
+    let n = exponent.parse::<i32>().expect("{:e} exponent is an integer") + 1;
+
+    let body = if k <= n && n <= 21 {
+        format!("{}{}", digits, "0".repeat((n - k) as usize))
+    } else if 0 < n && n <= 21 {
+        format!("{}.{}", &digits[..n as usize], &digits[n as usize..])
+    } else if -6 < n && n <= 0 {
+        format!("0.{}{}", "0".repeat(-n as usize), digits)
+    } else {
+        let exponent = if n > 0 { format!("+{}", n - 1) } else { (n - 1).to_string() };
+        match digits.split_at(1) {
+            (first, "") => format!("{}e{}", first, exponent),
+            (first, rest) => format!("{}.{}e{}", first, rest, exponent),
+        }
+    };
+    format!("{}{}", sign, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn canonical(value: &Value) -> String {
+        String::from_utf8(to_vec(value).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn keys_sort_by_utf16_code_units() {
+        // RFC 8785 section 3.2.3: the emoji's surrogate pair sorts before U+FB33
+        let value: Value = serde_json::from_str(concat!(
+            r#"{"\u20ac": 1, "\r": 2, "\ufb33": 3, "1": 4, "\ud83d\ude00": 5, "\u0080": 6, "\u00f6": 7, "#,
+            r#""a": {"b": 1, "a": 2}}"#,
+        ))
+        .unwrap();
+        assert_eq!(
+            canonical(&value),
+            "{\"\\r\":2,\"1\":4,\"a\":{\"a\":2,\"b\":1},\"\u{80}\":6,\"ö\":7,\"€\":1,\"😀\":5,\"\u{fb33}\":3}"
+        );
+    }
+
+    #[test]
+    fn numbers_use_the_ecmascript_form() {
+        let cases = [
+            (json!(0.0), "0"),
+            (json!(-0.0), "0"),
+            (json!(4.5), "4.5"),
+            (json!(0.002), "0.002"),
+            (json!(0.000001), "0.000001"),
+            (json!(1e-7), "1e-7"),
+            (json!(1e-27), "1e-27"),
+            (json!(1e21), "1e+21"),
+            (json!(1e30), "1e+30"),
+            (json!(123456789012345680000.0), "123456789012345680000"),
+            (json!(333333333.3333333), "333333333.3333333"),
+            (json!(-1.25e-7), "-1.25e-7"),
+            (json!(5e-324), "5e-324"),
+            (json!(1.7976931348623157e308), "1.7976931348623157e+308"),
+            (json!(9007199254740992u64), "9007199254740992"),
+            (json!(-42), "-42"),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(canonical(&value), expected, "{:?}", value);
+        }
+    }
+
+    #[test]
+    fn strings_escape_only_what_json_requires() {
+        let value = json!("\u{20ac}$\u{000f}\nA'B\"\\\\\"/\u{1f}\t");
+        assert_eq!(canonical(&value), "\"€$\\u000f\\nA'B\\\"\\\\\\\\\\\"/\\u001f\\t\"");
+    }
+
+    #[test]
+    fn rfc_8785_example_canonicalizes_and_digests_stably() {
+        let value = json!({
+            "numbers": [333333333.3333333, 1e30, 4.50, 2e-3, 1e-27],
+            "string": "\u{20ac}$\u{000f}\nA'B\"\\\\\"/",
+            "literals": [null, true, false],
+        });
+        let expected = concat!(
+            r#"{"literals":[null,true,false],"numbers":[333333333.3333333,1e+30,4.5,0.002,1e-27],"#,
+            r#""string":"€$\u000f\nA'B\"\\\\\"/"}"#,
+        );
+        assert_eq!(canonical(&value), expected);
+        assert_eq!(digest(&value).unwrap(), <[u8; 32]>::from(Sha256::digest(expected.as_bytes())));
+    }
+}
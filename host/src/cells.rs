@@ -0,0 +1,108 @@
+//! Limits on the empty and unparseable cells a journal admits to.
+//!
+//! The guest skips cells that are not `u64`s when it sums and counts, and
+//! commits how many it skipped per column (see [`zaik_core::cells`]). A
+//! policy's `[[cell_limits]]` entries turn those counts into rules, so a
+//! file whose bad rows would otherwise vanish from the sum is rejected.
+
+use crate::policy::RuleOutcome;
+use serde::{Deserialize, Serialize};
+use zaik_core::cells::CellCounts;
+use zaik_core::AgentResult;
+
+/// One entry of a policy's `[[cell_limits]]`:
+/// ```toml
+/// [[cell_limits]]
+/// column = 0                    # leave out to limit every column
+/// max_unparseable_percent = 1   # reject if more than 1% of cells are not numbers
+/// max_empty_percent = 5
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CellLimit {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub column: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_unparseable_percent: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_empty_percent: Option<u64>,
+}
+
+/// Which kind of skipped cell a limit applies to.
+#[derive(Clone, Copy)]
+enum Kind {
+    Empty,
+    Unparseable,
+}
+
+impl Kind {
+    fn rule(self) -> &'static str {
+        match self {
+            Kind::Empty => "empty_cells",
+            Kind::Unparseable => "unparseable_cells",
+        }
+    }
+
+    fn cells(self, counts: &CellCounts) -> u64 {
+        match self {
+            Kind::Empty => counts.empty,
+            Kind::Unparseable => counts.unparseable,
+        }
+    }
+
+    fn percent(self, counts: &CellCounts, rows: u64) -> u64 {
+        match self {
+            Kind::Empty => counts.empty_percent(rows),
+            Kind::Unparseable => counts.unparseable_percent(rows),
+        }
+    }
+}
+
+impl CellLimit {
+    /// One rule per percentage set, checked against the journal's counts.
+    /// Without a column, the worst accounted column decides. A column the
+    /// guest did not account for, or a journal with rows but no counts at
+    /// all (`chunked` mode), fails.
+    pub fn evaluate(&self, journal: &AgentResult) -> Vec<RuleOutcome> {
+        [
+            (Kind::Unparseable, self.max_unparseable_percent),
+            (Kind::Empty, self.max_empty_percent),
+        ]
+        .into_iter()
+        .filter_map(|(kind, limit)| Some(self.check(kind, limit?, journal)))
+        .collect()
+    }
+
+    fn check(&self, kind: Kind, limit: u64, journal: &AgentResult) -> RuleOutcome {
+        let scope = match self.column {
+            Some(column) => format!("column {}", column),
+            None => "every column".to_string(),
+        };
+        let outcome = |passed: bool, actual: String| RuleOutcome {
+            rule: kind.rule().to_string(),
+            passed,
+            actual,
+            requirement: format!("at most {}% of cells in {}", limit, scope),
+        };
+        let rows = journal.data_rows;
+        let worst = match self.column {
+            Some(column) => journal.cell_counts.get(column).map(|counts| (column, counts)),
+            None => journal
+                .cell_counts
+                .iter()
+                .enumerate()
+                .max_by_key(|(_, counts)| kind.cells(counts)),
+        };
+        match worst {
+            Some((column, counts)) => {
+                let percent = kind.percent(counts, rows);
+                outcome(
+                    percent <= limit,
+                    format!("column {}: {} of {} cells ({}%)", column, kind.cells(counts), rows, percent),
+                )
+            }
+            None if self.column.is_none() && rows == 0 => outcome(true, "no data rows".to_string()),
+            None if self.column.is_none() => outcome(false, "cells not accounted".to_string()),
+            None => outcome(false, format!("{} not accounted", scope)),
+        }
+    }
+}
@@ -0,0 +1,157 @@
+//! Naming policy columns by header instead of by index.
+//!
+//! `column` in `[[invariants]]` and `[[templates]]` may be a header name:
+//! ```toml
+//! [[invariants]]
+//! column = "amount"
+//! aggregate = "max"
+//! op = "le"
+//! bound = 500
+//! ```
+//!
+//! The host resolves names against the CSV's header row before the policy
+//! is hashed or handed to the guest, so the policy version and the journal
+//! only ever hold indices. A name matches a header exactly, else ignoring
+//! case; failing both, the closest header that differs only in spacing,
+//! punctuation or a typo is offered as a fuzzy match, which the caller must
+//! confirm before it is used.
+
+use std::error::Error;
+use std::fmt;
+use toml::Value;
+use zaik_core::dialect::Dialect;
+
+/// Policy tables whose entries carry a `column`.
+const COLUMN_TABLES: [&str; 3] = ["invariants", "templates", "cell_limits"];
+
+/// How a name was matched to a header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchKind {
+    Exact,
+    CaseInsensitive,
+    /// Close but not equal; needs confirming.
+    Fuzzy,
+}
+
+/// A named column and the header it resolved to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnMatch {
+    /// The name as written in the policy.
+    pub name: String,
+    pub index: usize,
+    pub header: String,
+    pub kind: MatchKind,
+}
+
+impl fmt::Display for ColumnMatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let how = match self.kind {
+            MatchKind::Exact => "exact",
+            MatchKind::CaseInsensitive => "ignoring case",
+            MatchKind::Fuzzy => "fuzzy",
+        };
+        write!(f, "'{}' -> column {} '{}' ({})", self.name, self.index, self.header, how)
+    }
+}
+
+/// The header row of `csv_data`, each name trimmed.
+pub fn header(csv_data: &str, dialect: &Dialect) -> Result<Vec<String>, Box<dyn Error>> {
+    if !dialect.has_header {
+        return Err("the policy names columns, but the CSV is read without a header row".into());
+    }
+    let line = csv_data.lines().next().ok_or("the CSV is empty, so it has no header to name columns by")?;
+    Ok((0..)
+        .map_while(|index| dialect.field(line, index))
+        .map(|name| name.trim().to_string())
+        .collect())
+}
+
+/// Lowercase letters and digits only, so `Amount (EUR)` reads `amounteur`.
+fn normalize(name: &str) -> Vec<char> {
+    name.chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase).collect()
+}
+
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitute = previous[j] + usize::from(ca != cb);
+            current.push(substitute.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+fn listing(header: &[String], indices: impl Iterator<Item = usize>) -> String {
+    indices
+        .map(|index| format!("{} '{}'", index, header[index]))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The one header `name` refers to.
+pub fn resolve(name: &str, header: &[String]) -> Result<ColumnMatch, String> {
+    let pick = |indices: Vec<usize>, kind: MatchKind| match indices.as_slice() {
+        [] => Ok(None),
+        [index] => Ok(Some(ColumnMatch {
+            name: name.to_string(),
+            index: *index,
+            header: header[*index].clone(),
+            kind,
+        })),
+        _ => Err(format!(
+            "column '{}' is ambiguous: it matches {}",
+            name,
+            listing(header, indices.iter().copied())
+        )),
+    };
+    let matching = |test: &dyn Fn(usize) -> bool| (0..header.len()).filter(|&index| test(index)).collect();
+
+    if let Some(found) = pick(matching(&|index| header[index] == name), MatchKind::Exact)? {
+        return Ok(found);
+    }
+    let lowered = name.to_lowercase();
+    if let Some(found) = pick(matching(&|index| header[index].to_lowercase() == lowered), MatchKind::CaseInsensitive)? {
+        return Ok(found);
+    }
+    // A typo per four characters, and at least one, counts as close
+    let wanted = normalize(name);
+    let allowed = (wanted.len() / 4).max(1);
+    let distances: Vec<usize> = header.iter().map(|h| edit_distance(&wanted, &normalize(h))).collect();
+    let closest = distances.iter().copied().min().filter(|&distance| distance <= allowed);
+    pick(matching(&|index| Some(distances[index]) == closest), MatchKind::Fuzzy)?
+        .ok_or_else(|| format!("no header matches column '{}'; the header is {}", name, listing(header, 0..header.len())))
+}
+
+/// Column names still left in a parsed policy file, in file order.
+pub fn named(policy: &Value) -> Vec<String> {
+    COLUMN_TABLES
+        .iter()
+        .filter_map(|table| policy.get(table)?.as_array())
+        .flatten()
+        .filter_map(|entry| entry.get("column")?.as_str().map(str::to_string))
+        .collect()
+}
+
+/// Replace every named column in a parsed policy file with the index it
+/// resolves to in `header`. Returns the matches so fuzzy ones can be
+/// confirmed.
+pub fn resolve_policy(policy: &mut Value, header: &[String]) -> Result<Vec<ColumnMatch>, String> {
+    let mut matches = Vec::new();
+    for table in COLUMN_TABLES {
+        let Some(entries) = policy.get_mut(table).and_then(Value::as_array_mut) else {
+            continue;
+        };
+        for entry in entries {
+            let Some(name) = entry.get("column").and_then(Value::as_str) else {
+                continue;
+            };
+            let found = resolve(name, header)?;
+            entry["column"] = Value::Integer(found.index as i64);
+            matches.push(found);
+        }
+    }
+    Ok(matches)
+}
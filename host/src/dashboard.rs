@@ -0,0 +1,157 @@
+//! What the `dashboard` binary shows: a point-in-time view of a proof store
+//! and the progress files of the provers writing to it.
+//!
+//! The host runs per invocation, so there is no server to query. Proving
+//! jobs are read from the files given to `--events`, decisions from the
+//! `.decision.json` files next to the bundles, and the queues from the
+//! bundles still awaiting a decision and the webhook dead-letter file.
+
+use crate::outcome::Outcome;
+use crate::signing::Decision;
+use crate::store::ProofStore;
+use crate::webhook;
+use serde_json::Value;
+use std::cmp::Reverse;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// How far the last run in an events file got.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobStage {
+    Executing,
+    Proving,
+    Finished,
+    /// Unfinished, but the file has not changed for longer than the stale
+    /// window; the prover most likely died.
+    Stalled,
+}
+
+/// The last run recorded in one events file.
+#[derive(Debug, Clone)]
+pub struct Job {
+    pub events: PathBuf,
+    pub stage: JobStage,
+    /// Segments executed so far, out of `segments` once execution finishes.
+    pub segments_executed: usize,
+    pub segments: Option<usize>,
+    pub user_cycles: u64,
+    pub updated: SystemTime,
+}
+
+impl Job {
+    /// Read the last run from `path`; `None` if no run has started.
+    pub fn load(path: &Path, stale: Duration, now: SystemTime) -> Result<Option<Self>, Box<dyn Error>> {
+        let updated = fs::metadata(path)?.modified()?;
+        let events: Vec<Value> = fs::read_to_string(path)?
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect();
+        // Event files are appended to, so only the events after the last start count
+        let Some(start) = events.iter().rposition(|event| event["event"] == "execution_started") else {
+            return Ok(None);
+        };
+        let mut job = Job {
+            events: path.to_path_buf(),
+            stage: JobStage::Executing,
+            segments_executed: 0,
+            segments: None,
+            user_cycles: 0,
+            updated,
+        };
+        for event in &events[start..] {
+            match event["event"].as_str() {
+                Some("segment_executed") => {
+                    job.segments_executed += 1;
+                    job.user_cycles += event["user_cycles"].as_u64().unwrap_or(0);
+                }
+                Some("execution_finished") => job.segments = event["segments"].as_u64().map(|n| n as usize),
+                Some("proving_started") => job.stage = JobStage::Proving,
+                Some("proving_finished") => job.stage = JobStage::Finished,
+                _ => {}
+            }
+        }
+        let idle = now.duration_since(updated).unwrap_or_default();
+        if job.stage != JobStage::Finished && idle > stale {
+            job.stage = JobStage::Stalled;
+        }
+        Ok(Some(job))
+    }
+
+    pub fn active(&self) -> bool {
+        matches!(self.stage, JobStage::Executing | JobStage::Proving)
+    }
+}
+
+/// One recorded decision.
+#[derive(Debug, Clone)]
+pub struct DecisionRow {
+    pub bundle: PathBuf,
+    pub outcome: Outcome,
+    pub policy_version: String,
+    pub explanation: String,
+    pub decided: SystemTime,
+}
+
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub taken: SystemTime,
+    pub jobs: Vec<Job>,
+    /// Bundles with no recorded decision yet.
+    pub awaiting_decision: usize,
+    /// Decision notifications waiting in the dead-letter file.
+    pub dead_letters: usize,
+    /// Every recorded decision, newest first.
+    pub decisions: Vec<DecisionRow>,
+}
+
+impl Snapshot {
+    /// Read `store` and the events files. Files that cannot be read are
+    /// skipped, so a half-written decision does not blank the view.
+    pub fn collect(store: &ProofStore, events: &[PathBuf], stale: Duration) -> Result<Self, Box<dyn Error>> {
+        let taken = SystemTime::now();
+        let jobs = events
+            .iter()
+            .filter_map(|path| Job::load(path, stale, taken).ok().flatten())
+            .collect();
+
+        let mut awaiting_decision = 0;
+        let mut decisions = Vec::new();
+        for bundle in store.bundles()? {
+            let path = Decision::path_for(&bundle);
+            let recorded = fs::read_to_string(&path)
+                .ok()
+                .and_then(|decision| serde_json::from_str::<Decision>(&decision).ok());
+            let Some(decision) = recorded else {
+                awaiting_decision += 1;
+                continue;
+            };
+            decisions.push(DecisionRow {
+                bundle,
+                outcome: decision.outcome,
+                policy_version: decision.policy_version,
+                explanation: decision.explanation,
+                decided: fs::metadata(&path)?.modified()?,
+            });
+        }
+        decisions.sort_by_key(|row| Reverse(row.decided));
+
+        Ok(Self {
+            taken,
+            jobs,
+            awaiting_decision,
+            dead_letters: webhook::dead_letters(&store.webhook_dead_letters())?.len(),
+            decisions,
+        })
+    }
+
+    pub fn active_jobs(&self) -> impl Iterator<Item = &Job> {
+        self.jobs.iter().filter(|job| job.active())
+    }
+
+    /// Decisions where the receipt or journal did not verify, newest first.
+    pub fn verification_failures(&self) -> impl Iterator<Item = &DecisionRow> {
+        self.decisions.iter().filter(|row| row.outcome == Outcome::CryptoFailure)
+    }
+}
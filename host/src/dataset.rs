@@ -0,0 +1,109 @@
+//! Manifests of datasets split across several CSV files.
+//!
+//! ```toml
+//! root = "5be1…"          # hex dataset root; the journal's csv_hash
+//!
+//! [[file]]
+//! path = "exports/2024-03-01.csv"
+//! sha256 = "9f2c…"        # hex SHA-256 of the (decompressed) CSV
+//!
+//! [[file]]
+//! path = "exports/2024-03-02.csv"
+//! sha256 = "41d0…"
+//! ```
+//!
+//! `host dataset` writes one from the files, in the order given. Relative
+//! paths are read from the manifest's directory. The prover proves the files
+//! as one CSV (see [`zaik_core::dataset`]); a verifier holding the manifest
+//! recomputes the root from the listed hashes and compares it with the
+//! journal's `csv_hash`.
+
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use zaik_core::dataset;
+
+/// One file of a dataset, in proving order.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestFile {
+    pub path: PathBuf,
+    /// Hex SHA-256 of the file's CSV, after decompression.
+    pub sha256: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DatasetManifest {
+    /// Hex [`dataset::root`] over the files' hashes.
+    pub root: String,
+    #[serde(rename = "file")]
+    pub files: Vec<ManifestFile>,
+}
+
+impl DatasetManifest {
+    /// A manifest listing `files` with their hashes, in order.
+    pub fn new(files: Vec<(PathBuf, [u8; 32])>) -> Self {
+        let hashes: Vec<[u8; 32]> = files.iter().map(|(_, hash)| *hash).collect();
+        Self {
+            root: hex::encode(dataset::root(&hashes)),
+            files: files
+                .into_iter()
+                .map(|(path, hash)| ManifestFile {
+                    path,
+                    sha256: hex::encode(hash),
+                })
+                .collect(),
+        }
+    }
+
+    /// Read a manifest, rejecting one whose root does not match its files.
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let manifest: DatasetManifest = toml::from_str(&fs::read_to_string(path)?)?;
+        if manifest.files.is_empty() {
+            return Err("the dataset manifest lists no files".into());
+        }
+        if hex::encode(dataset::root(&manifest.hashes()?)) != manifest.root {
+            return Err("the dataset root does not match the listed file hashes".into());
+        }
+        Ok(manifest)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        fs::write(path, toml::to_string(self)?)?;
+        Ok(())
+    }
+
+    pub fn hashes(&self) -> Result<Vec<[u8; 32]>, Box<dyn Error>> {
+        self.files
+            .iter()
+            .map(|file| {
+                hex::decode(&file.sha256)
+                    .ok()
+                    .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+                    .ok_or_else(|| format!("'{}' is not a 32-byte hex SHA-256", file.sha256).into())
+            })
+            .collect()
+    }
+
+    /// The dataset root, as the journal commits it.
+    pub fn root(&self) -> Result<[u8; 32], Box<dyn Error>> {
+        Ok(dataset::root(&self.hashes()?))
+    }
+
+    /// Read every file, relative paths from `base`, checking each against
+    /// its listed hash.
+    #[cfg(feature = "prove")]
+    pub fn read(&self, base: &Path) -> Result<Vec<dataset::DatasetFile>, Box<dyn Error>> {
+        self.files
+            .iter()
+            .map(|file| {
+                let path = base.join(&file.path);
+                let read = dataset::DatasetFile::new(crate::input::read_csv(&path)?);
+                if hex::encode(read.hash) != file.sha256 {
+                    return Err(format!("{} no longer matches its hash in the dataset manifest", path.display()).into());
+                }
+                Ok(read)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,108 @@
+//! End-to-end time budget for a request.
+//!
+//! A [`Deadline`] is created once, where the request enters (the CLI's
+//! `--deadline`, or the remaining budget an orchestrator passes down), and
+//! checked between stages. Proving, the one stage that can run for a long
+//! time on its own, is abandoned when the budget runs out rather than
+//! checked afterwards.
+
+use crate::outcome::{FailureReason, Outcome};
+use std::error::Error;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy)]
+pub struct Deadline {
+    started: Instant,
+    budget: Duration,
+}
+
+impl Deadline {
+    /// A deadline `budget` from now.
+    pub fn after(budget: Duration) -> Self {
+        Self {
+            started: Instant::now(),
+            budget,
+        }
+    }
+
+    pub fn budget(&self) -> Duration {
+        self.budget
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// Time left, zero once expired.
+    pub fn remaining(&self) -> Duration {
+        self.budget.saturating_sub(self.elapsed())
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.remaining().is_zero()
+    }
+
+    /// Fail if the budget is spent before `stage` starts.
+    pub fn check(&self, stage: &str) -> Result<(), DeadlineExceeded> {
+        if self.is_expired() {
+            return Err(self.exceeded(stage));
+        }
+        Ok(())
+    }
+
+    /// The error for running out of time in `stage`.
+    pub fn exceeded(&self, stage: &str) -> DeadlineExceeded {
+        DeadlineExceeded {
+            stage: stage.to_string(),
+            budget: self.budget,
+            elapsed: self.elapsed(),
+        }
+    }
+}
+
+/// The request ran out of time; `stage` is where it stopped.
+#[derive(Debug, Clone)]
+pub struct DeadlineExceeded {
+    pub stage: String,
+    pub budget: Duration,
+    pub elapsed: Duration,
+}
+
+impl DeadlineExceeded {
+    pub fn to_failure(&self) -> FailureReason {
+        FailureReason::new(Outcome::DeadlineExceeded, "deadline_exceeded", self.to_string())
+    }
+}
+
+impl fmt::Display for DeadlineExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "deadline of {:.1}s exceeded during {} after {:.1}s",
+            self.budget.as_secs_f64(),
+            self.stage,
+            self.elapsed.as_secs_f64()
+        )
+    }
+}
+
+impl Error for DeadlineExceeded {}
+
+/// Parse `90`, `90s`, `500ms`, `30m` or `2h`; a bare number is seconds.
+pub fn parse_duration(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let split = value.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("invalid duration '{}': expected e.g. 90s, 30m or 2h", value))?;
+    let seconds = match unit {
+        "" | "s" => number,
+        "ms" => number / 1000.0,
+        "m" => number * 60.0,
+        "h" => number * 3600.0,
+        other => return Err(format!("invalid duration unit '{}': use ms, s, m or h", other)),
+    };
+    Duration::try_from_secs_f64(seconds).map_err(|e| format!("invalid duration '{}': {}", value, e))
+}
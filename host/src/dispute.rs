@@ -0,0 +1,408 @@
+//! Disputes: Agent A contests a rejection before a third, arbiter agent.
+//!
+//! A [`Dispute`] carries what the rejection was about: the bundle and its
+//! prover signature, Agent B's signed [`Decision`], the policy the decision
+//! names by version and, optionally, a counter-proof, a second bundle for
+//! the same CSV and policy proven again (e.g. with a newer guest image).
+//! The [`Arbiter`] checks the signatures, re-verifies the bundle and then
+//! the counter-proof against the image IDs it trusts, and signs a
+//! [`Ruling`] that overturns the rejection if either holds up and upholds
+//! it otherwise. Rulings are recorded in the store's audit log (see
+//! [`crate::audit_log`]). Files sit next to the bundle:
+//!
+//! ```text
+//! <digest>.dispute.json                Agent A
+//! <digest>.ruling.json, .json.sig      arbiter
+//! ```
+//!
+//! Where several arbiters rule on the same dispute, [`quorum`] is the
+//! verdict enough trusted ones agree on.
+
+use crate::outcome::{FailureReason, Outcome};
+use crate::policy::Policy;
+use crate::relying_party::RelyingParty;
+use crate::signing::{self, AgentKey, ChainError, Decision, DetachedSignature, SignerRole};
+use crate::store::ProofBundle;
+use crate::untrusted;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use risc0_zkvm::sha::Digest;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::slice;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Agent A's case against a rejection.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Dispute {
+    /// The rejected bundle file, base64.
+    pub bundle: String,
+    pub prover_signature: DetachedSignature,
+    pub decision: Decision,
+    pub decision_signature: DetachedSignature,
+    /// The policy whose version the decision names.
+    pub policy: Policy,
+    /// A bundle for the same key proven again, base64.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub counter_proof: Option<String>,
+    /// Agent A's reason for disputing, for the record.
+    #[serde(default)]
+    pub statement: String,
+}
+
+/// How an arbiter ruled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    /// The rejection stands.
+    Upheld,
+    /// The bundle or its counter-proof is accepted after all.
+    Overturned,
+}
+
+/// An arbiter's signed answer to a dispute.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ruling {
+    /// Hex SHA-256 of the disputed bundle file.
+    pub bundle_digest: String,
+    pub prover: String,
+    /// Key that signed the disputed decision.
+    pub verifier: String,
+    pub policy_version: String,
+    /// The disputed decision's outcome.
+    pub rejected_as: Outcome,
+    pub verdict: Verdict,
+    /// Which proof carried an overturn (`bundle` or `counter_proof`), or the
+    /// stable reason the bundle still fails.
+    pub reason: String,
+    pub explanation: String,
+    /// Hex public key of the arbiter.
+    pub arbiter: String,
+    /// Seconds since the Unix epoch.
+    pub ruled_at: u64,
+}
+
+impl Ruling {
+    /// Path of the ruling on the bundle at `bundle_path`.
+    pub fn path_for(bundle_path: &Path) -> PathBuf {
+        bundle_path.with_extension("ruling.json")
+    }
+
+    /// The exact bytes the arbiter signs.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec_pretty(self).expect("Ruling is always serializable")
+    }
+}
+
+/// Why a dispute could not be ruled on.
+#[derive(Debug)]
+pub enum DisputeError {
+    Malformed(String),
+    /// The bundle, decision and signatures do not chain.
+    Chain(ChainError),
+    /// The decision accepted the bundle; there is nothing to dispute.
+    NotRejected,
+    /// The policy is not the one the decision or the bundle names.
+    PolicyMismatch,
+    /// The counter-proof is for a different CSV, policy or options.
+    CounterProofMismatch,
+    Io(Box<dyn Error>),
+}
+
+impl fmt::Display for DisputeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DisputeError::Malformed(e) => write!(f, "dispute is malformed: {}", e),
+            DisputeError::Chain(e) => write!(f, "disputed decision is not validly signed: {}", e),
+            DisputeError::NotRejected => write!(f, "the decision accepted the bundle"),
+            DisputeError::PolicyMismatch => write!(f, "the policy is not the one the decision was made under"),
+            DisputeError::CounterProofMismatch => write!(f, "the counter-proof is not for the disputed CSV and policy"),
+            DisputeError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for DisputeError {}
+
+impl From<Box<dyn Error>> for DisputeError {
+    fn from(e: Box<dyn Error>) -> Self {
+        DisputeError::Io(e)
+    }
+}
+
+impl DisputeError {
+    pub fn to_failure(&self) -> FailureReason {
+        let (outcome, reason) = match self {
+            DisputeError::Malformed(_) => (Outcome::Io, "dispute_malformed"),
+            DisputeError::Chain(_) => (Outcome::CryptoFailure, "signature_chain_invalid"),
+            DisputeError::NotRejected => (Outcome::PolicyReject, "nothing_to_dispute"),
+            DisputeError::PolicyMismatch => (Outcome::PolicyReject, "policy_mismatch"),
+            DisputeError::CounterProofMismatch => (Outcome::PolicyReject, "counter_proof_mismatch"),
+            DisputeError::Io(_) => (Outcome::Io, "dispute_unavailable"),
+        };
+        FailureReason::new(outcome, reason, self.to_string())
+    }
+}
+
+impl Dispute {
+    /// Agent A's side: gather the rejected bundle at `bundle_path`, its
+    /// signature, Agent B's signed decision and the optional counter-proof.
+    pub fn open(
+        bundle_path: &Path,
+        policy: Policy,
+        counter_proof: Option<&Path>,
+        statement: &str,
+    ) -> Result<Self, DisputeError> {
+        let read = |path: &Path, limit| untrusted::read(path, limit).map_err(|e| DisputeError::Io(e.into()));
+        let decision_path = Decision::path_for(bundle_path);
+        let decision: Decision = serde_json::from_slice(&read(&decision_path, untrusted::MAX_SIDECAR_BYTES)?)
+            .map_err(|e| DisputeError::Malformed(e.to_string()))?;
+        if decision.outcome == Outcome::Accept {
+            return Err(DisputeError::NotRejected);
+        }
+        let counter_proof = match counter_proof {
+            Some(path) => Some(BASE64.encode(read(path, untrusted::MAX_BUNDLE_BYTES)?)),
+            None => None,
+        };
+        Ok(Self {
+            bundle: BASE64.encode(read(bundle_path, untrusted::MAX_BUNDLE_BYTES)?),
+            prover_signature: DetachedSignature::load(&DetachedSignature::path_for(bundle_path))?,
+            decision,
+            decision_signature: DetachedSignature::load(&DetachedSignature::path_for(&decision_path))?,
+            policy,
+            counter_proof,
+            statement: statement.to_string(),
+        })
+    }
+
+    /// Path of the dispute over the bundle at `bundle_path`.
+    pub fn path_for(bundle_path: &Path) -> PathBuf {
+        bundle_path.with_extension("dispute.json")
+    }
+
+    /// Read a dispute, which carries up to two bundles in base64.
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        Ok(serde_json::from_slice(&untrusted::read(path, 3 * untrusted::MAX_BUNDLE_BYTES)?)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// A third agent that re-verifies disputed bundles.
+pub struct Arbiter {
+    key: AgentKey,
+    image_ids: Vec<Digest>,
+    trusted_verifiers: Vec<String>,
+    allow_dev_mode: bool,
+}
+
+impl Arbiter {
+    /// An arbiter trusting guest `image_ids` and ruling only on decisions
+    /// signed by `trusted_verifiers`.
+    pub fn new(key: AgentKey, image_ids: Vec<Digest>, trusted_verifiers: Vec<String>) -> Self {
+        Self { key, image_ids, trusted_verifiers, allow_dev_mode: false }
+    }
+
+    /// Accept dev-mode receipts (test deployments only).
+    pub fn allow_dev_mode(mut self) -> Self {
+        self.allow_dev_mode = true;
+        self
+    }
+
+    /// Rule on `dispute`, returning the ruling and the arbiter's signature
+    /// over it.
+    pub fn rule(&self, dispute: &Dispute) -> Result<(Ruling, DetachedSignature), DisputeError> {
+        let decode = |encoded: &str| -> Result<(Vec<u8>, ProofBundle), DisputeError> {
+            let bytes = BASE64.decode(encoded).map_err(|e| DisputeError::Malformed(e.to_string()))?;
+            let bundle = ProofBundle::from_bytes(&bytes).map_err(|e| DisputeError::Malformed(e.to_string()))?;
+            Ok((bytes, bundle))
+        };
+        let (bundle_bytes, bundle) = decode(&dispute.bundle)?;
+        // Any prover may dispute, but only over a decision a trusted verifier made
+        signing::verify_chain(
+            &bundle_bytes,
+            &dispute.prover_signature,
+            &dispute.decision,
+            &dispute.decision_signature,
+            slice::from_ref(&dispute.prover_signature.signer),
+            &self.trusted_verifiers,
+        )
+        .map_err(DisputeError::Chain)?;
+        if dispute.decision.outcome == Outcome::Accept {
+            return Err(DisputeError::NotRejected);
+        }
+        let policy = &dispute.policy;
+        if policy.version() != dispute.decision.policy_version || policy.hash() != bundle.key.policy_hash {
+            return Err(DisputeError::PolicyMismatch);
+        }
+
+        let (verdict, reason, explanation) = match self.relying_party(&dispute.policy).accept(&bundle) {
+            Ok(_) => {
+                let explanation = "the bundle is accepted on re-verification".to_string();
+                (Verdict::Overturned, "bundle".to_string(), explanation)
+            }
+            Err(rejection) => match &dispute.counter_proof {
+                Some(counter_proof) => {
+                    let (_, counter) = decode(counter_proof)?;
+                    let key = (counter.key.csv_hash, counter.key.policy_hash, counter.key.options);
+                    if key != (bundle.key.csv_hash, bundle.key.policy_hash, bundle.key.options) {
+                        return Err(DisputeError::CounterProofMismatch);
+                    }
+                    match self.relying_party(&dispute.policy).accept(&counter) {
+                        Ok(_) => (
+                            Verdict::Overturned,
+                            "counter_proof".to_string(),
+                            format!("the bundle still fails ({}), but the counter-proof is accepted", rejection),
+                        ),
+                        Err(counter_rejection) => (
+                            Verdict::Upheld,
+                            rejection.to_failure().reason,
+                            format!("{}; the counter-proof fails too: {}", rejection, counter_rejection),
+                        ),
+                    }
+                }
+                None => (Verdict::Upheld, rejection.to_failure().reason, rejection.to_string()),
+            },
+        };
+        let ruling = Ruling {
+            bundle_digest: dispute.prover_signature.digest.clone(),
+            prover: dispute.prover_signature.signer.clone(),
+            verifier: dispute.decision_signature.signer.clone(),
+            policy_version: dispute.decision.policy_version.clone(),
+            rejected_as: dispute.decision.outcome,
+            verdict,
+            reason,
+            explanation,
+            arbiter: self.key.public_key(),
+            ruled_at: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+        };
+        let signature = self.key.sign(SignerRole::Arbiter, &ruling.to_bytes());
+        Ok((ruling, signature))
+    }
+
+    fn relying_party(&self, policy: &Policy) -> RelyingParty {
+        let mut relying_party = RelyingParty::new(policy.clone());
+        for image_id in &self.image_ids {
+            relying_party = relying_party.allow_image(*image_id);
+        }
+        if self.allow_dev_mode {
+            relying_party = relying_party.allow_dev_mode();
+        }
+        relying_party
+    }
+}
+
+/// The verdict at least `threshold` distinct trusted arbiters signed for
+/// the bundle `bundle_digest`; `None` if no verdict has that many, or if
+/// both do. Rulings with a bad signature or by an untrusted arbiter are not
+/// counted, and an arbiter that signed both verdicts counts for neither.
+pub fn quorum(
+    rulings: &[(Ruling, DetachedSignature)],
+    bundle_digest: &str,
+    trusted_arbiters: &[String],
+    threshold: usize,
+) -> Option<Verdict> {
+    // Each arbiter's verdict, or `None` once it has signed both
+    let mut ballots: BTreeMap<&str, Option<Verdict>> = BTreeMap::new();
+    for (ruling, signature) in rulings {
+        let counts = ruling.bundle_digest == bundle_digest
+            && signature.signer == ruling.arbiter
+            && trusted_arbiters.contains(&signature.signer)
+            && signature.verify(SignerRole::Arbiter, &ruling.to_bytes()).is_ok();
+        if !counts {
+            continue;
+        }
+        let ballot = ballots.entry(&ruling.arbiter).or_insert(Some(ruling.verdict));
+        if *ballot != Some(ruling.verdict) {
+            *ballot = None;
+        }
+    }
+    let mut reached = [Verdict::Upheld, Verdict::Overturned].into_iter().filter(|verdict| {
+        threshold > 0 && ballots.values().filter(|ballot| **ballot == Some(*verdict)).count() >= threshold
+    });
+    match (reached.next(), reached.next()) {
+        (Some(verdict), None) => Some(verdict),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(name: &str) -> AgentKey {
+        let path = std::env::temp_dir().join(format!("zaik-dispute-{}-{}.key", name, std::process::id()));
+        let _ = fs::remove_file(&path);
+        let key = AgentKey::generate(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        key
+    }
+
+    fn ruling(arbiter: &AgentKey, verdict: Verdict) -> (Ruling, DetachedSignature) {
+        let ruling = Ruling {
+            bundle_digest: "01".into(),
+            prover: "aa".into(),
+            verifier: "bb".into(),
+            policy_version: "test".into(),
+            rejected_as: Outcome::PolicyReject,
+            verdict,
+            reason: "bundle".into(),
+            explanation: String::new(),
+            arbiter: arbiter.public_key(),
+            ruled_at: 0,
+        };
+        let signature = arbiter.sign(SignerRole::Arbiter, &ruling.to_bytes());
+        (ruling, signature)
+    }
+
+    #[test]
+    fn quorum_counts_distinct_trusted_arbiters_with_valid_signatures() {
+        let (a, b, c, outsider) = (key("a"), key("b"), key("c"), key("outsider"));
+        let trusted = [a.public_key(), b.public_key(), c.public_key()];
+        let mut forged = ruling(&c, Verdict::Upheld);
+        forged.0.verdict = Verdict::Overturned;
+        let rulings = [
+            ruling(&a, Verdict::Overturned),
+            ruling(&a, Verdict::Overturned),
+            ruling(&outsider, Verdict::Overturned),
+            forged,
+            ruling(&b, Verdict::Overturned),
+        ];
+        assert_eq!(quorum(&rulings, "01", &trusted, 2), Some(Verdict::Overturned));
+        // Repeats, outsiders and forged rulings do not make a third vote
+        assert_eq!(quorum(&rulings, "01", &trusted, 3), None);
+        assert_eq!(quorum(&rulings, "02", &trusted, 1), None);
+    }
+
+    #[test]
+    fn quorum_ignores_arbiters_on_both_sides_and_refuses_a_tie() {
+        let (a, b, c, d) = (key("tie-a"), key("tie-b"), key("tie-c"), key("tie-d"));
+        let trusted = [a.public_key(), b.public_key(), c.public_key(), d.public_key()];
+        // `a` signed both verdicts: it is no vote for either
+        let rulings = [
+            ruling(&a, Verdict::Overturned),
+            ruling(&a, Verdict::Upheld),
+            ruling(&b, Verdict::Overturned),
+            ruling(&c, Verdict::Upheld),
+        ];
+        assert_eq!(quorum(&rulings, "01", &trusted, 2), None);
+        assert_eq!(quorum(&rulings, "01", &trusted, 1), None);
+
+        // Two against two reaches the threshold on both sides: no verdict
+        let tied = [
+            ruling(&a, Verdict::Overturned),
+            ruling(&b, Verdict::Overturned),
+            ruling(&c, Verdict::Upheld),
+            ruling(&d, Verdict::Upheld),
+        ];
+        assert_eq!(quorum(&tied, "01", &trusted, 2), None);
+        assert_eq!(quorum(&tied[1..], "01", &trusted, 2), Some(Verdict::Upheld));
+    }
+}
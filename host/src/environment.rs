@@ -0,0 +1,191 @@
+//! Environment manifests: an SBOM-style record of exactly what produced a
+//! proof, written and signed next to its bundle.
+//!
+//! [`BundleMetadata`](crate::metadata::BundleMetadata) says which versions
+//! wrote a bundle; a manifest lists every locked crate of the host and the
+//! guest, the guest toolchain, and the SHA-256 of the guest ELF, so two
+//! provers with the same manifest digest ran the same code. A policy with
+//! an `[environment]` table only accepts bundles whose manifest is signed
+//! by a trusted prover and whose digest it lists as approved.
+
+use crate::canonical;
+use crate::policy::RuleOutcome;
+use crate::signing::{DetachedSignature, SignerRole};
+use crate::untrusted;
+use risc0_zkvm::sha::Digest as Digest32;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+/// One locked crate.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Package {
+    pub name: String,
+    pub version: String,
+    /// Registry checksum from the lockfile; `None` for path dependencies.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<String>,
+}
+
+/// What a proof was produced with.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnvironmentManifest {
+    pub host_version: String,
+    pub risc0_version: String,
+    /// Hex guest image ID.
+    pub image_id: String,
+    /// Hex SHA-256 of the guest ELF.
+    pub elf_sha256: String,
+    /// `rustc -V` output, target triple and profile of the host build.
+    pub host_toolchain: String,
+    /// risc0 Rust toolchain the guest was built with, as recorded by the
+    /// release build in `ZAIK_GUEST_TOOLCHAIN`; `unknown` otherwise.
+    pub guest_toolchain: String,
+    /// Locked crates of the host workspace.
+    pub host_packages: Vec<Package>,
+    /// Locked crates of the guest.
+    pub guest_packages: Vec<Package>,
+}
+
+impl EnvironmentManifest {
+    /// The manifest of this build, running the guest `elf` with `image_id`.
+    pub fn current(elf: &[u8], image_id: [u32; 8]) -> Self {
+        Self {
+            host_version: env!("CARGO_PKG_VERSION").to_string(),
+            risc0_version: risc0_zkvm::VERSION.to_string(),
+            image_id: Digest32::from(image_id).to_string(),
+            elf_sha256: hex::encode(Sha256::digest(elf)),
+            host_toolchain: format!(
+                "{} ({}, {})",
+                env!("ZAIK_RUSTC_VERSION"),
+                env!("ZAIK_TARGET"),
+                env!("ZAIK_PROFILE")
+            ),
+            guest_toolchain: env!("ZAIK_GUEST_TOOLCHAIN").to_string(),
+            host_packages: packages(include_str!(concat!(env!("OUT_DIR"), "/host-packages.txt"))),
+            guest_packages: packages(include_str!(concat!(env!("OUT_DIR"), "/guest-packages.txt"))),
+        }
+    }
+
+    /// Where the manifest for the bundle at `bundle_path` is kept.
+    pub fn path_for(bundle_path: &Path) -> PathBuf {
+        bundle_path.with_extension("env.json")
+    }
+
+    /// Hex SHA-256 of the manifest's canonical JSON, the digest policies
+    /// approve.
+    pub fn digest(&self) -> String {
+        hex::encode(canonical::digest(self).expect("EnvironmentManifest is always serializable"))
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec_pretty(self).expect("EnvironmentManifest is always serializable")
+    }
+}
+
+fn packages(locked: &str) -> Vec<Package> {
+    locked
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split(' ');
+            let (name, version, checksum) = (fields.next()?, fields.next()?, fields.next()?);
+            Some(Package {
+                name: name.to_string(),
+                version: version.to_string(),
+                checksum: (checksum != "-").then(|| checksum.to_string()),
+            })
+        })
+        .collect()
+}
+
+/// A bundle's manifest as found next to it, with the prover's signature
+/// over the file if there is one.
+#[derive(Debug, Clone)]
+pub struct RecordedManifest {
+    pub manifest: EnvironmentManifest,
+    /// The manifest file as signed.
+    pub bytes: Vec<u8>,
+    pub signature: Option<DetachedSignature>,
+}
+
+impl RecordedManifest {
+    /// The manifest recorded for the bundle at `bundle_path`; `None` when
+    /// the bundle has none.
+    pub fn load(bundle_path: &Path) -> Result<Option<Self>, Box<dyn Error>> {
+        let path = EnvironmentManifest::path_for(bundle_path);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let bytes = untrusted::read(&path, untrusted::MAX_SIDECAR_BYTES)?;
+        let signature_path = DetachedSignature::path_for(&path);
+        Ok(Some(Self {
+            manifest: serde_json::from_slice(&bytes)?,
+            signature: signature_path.exists().then(|| DetachedSignature::load(&signature_path)).transpose()?,
+            bytes,
+        }))
+    }
+
+    /// Hex key of the prover whose signature over the manifest verifies.
+    pub fn signer(&self) -> Option<&str> {
+        let signature = self.signature.as_ref()?;
+        signature.verify(SignerRole::Prover, &self.bytes).ok()?;
+        Some(&signature.signer)
+    }
+}
+
+/// A policy's `[environment]` table:
+/// ```toml
+/// [environment]
+/// signers = ["<prover public key hex>"]   # empty: any valid signature
+///
+/// [[environment.approved]]
+/// name = "release-0.1.0"
+/// digest = "<manifest digest, as printed by `host environment`>"
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnvironmentPolicy {
+    #[serde(default)]
+    pub signers: Vec<String>,
+    pub approved: Vec<ApprovedEnvironment>,
+}
+
+/// An environment set a policy accepts proofs from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApprovedEnvironment {
+    pub name: String,
+    /// Hex [`EnvironmentManifest::digest`].
+    pub digest: String,
+}
+
+impl EnvironmentPolicy {
+    /// Whether `recorded`, the manifest of a bundle proven with
+    /// `image_id`, is signed by a trusted prover and approved.
+    pub fn evaluate(&self, recorded: Option<&RecordedManifest>, image_id: &str) -> RuleOutcome {
+        let requirement = format!("signed manifest of one of {} approved environment(s)", self.approved.len());
+        let outcome = |passed: bool, actual: String| RuleOutcome {
+            rule: "approved_environment".to_string(),
+            passed,
+            actual,
+            requirement: requirement.clone(),
+        };
+        let Some(recorded) = recorded else {
+            return outcome(false, "no environment manifest".to_string());
+        };
+        match recorded.signer() {
+            None => return outcome(false, "manifest unsigned or signature invalid".to_string()),
+            Some(signer) if !self.signers.is_empty() && !self.signers.iter().any(|trusted| trusted == signer) => {
+                return outcome(false, format!("manifest signed by untrusted {}", signer));
+            }
+            Some(_) => {}
+        }
+        if recorded.manifest.image_id != image_id {
+            return outcome(false, format!("manifest describes image {}", recorded.manifest.image_id));
+        }
+        let digest = recorded.manifest.digest();
+        match self.approved.iter().find(|approved| approved.digest == digest) {
+            Some(approved) => outcome(true, format!("environment {} ({})", approved.name, digest)),
+            None => outcome(false, format!("environment {} not approved", digest)),
+        }
+    }
+}
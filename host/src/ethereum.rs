@@ -0,0 +1,103 @@
+//! The few Ethereum encodings the relayer needs: Keccak-256, function
+//! selectors and event topics, ABI-encoded `(bytes, bytes)` calls, and the
+//! seal format the RISC Zero verifier router expects.
+
+use risc0_zkvm::{InnerReceipt, Receipt};
+use std::error::Error;
+
+/// Keccak-256 as Ethereum uses it (the original padding, not SHA3-256's).
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+    const RATE: usize = 136;
+    let mut state = [0u64; 25];
+    let mut absorb = |block: &[u8]| {
+        for (lane, word) in state.iter_mut().zip(block.as_chunks::<8>().0) {
+            *lane ^= u64::from_le_bytes(*word);
+        }
+        keccak::f1600(&mut state);
+    };
+    let (blocks, rest) = data.as_chunks::<RATE>();
+    for block in blocks {
+        absorb(block);
+    }
+    let mut last = [0u8; RATE];
+    last[..rest.len()].copy_from_slice(rest);
+    last[rest.len()] ^= 0x01;
+    last[RATE - 1] ^= 0x80;
+    absorb(&last);
+
+    let mut digest = [0u8; 32];
+    for (out, lane) in digest.as_chunks_mut::<8>().0.iter_mut().zip(state) {
+        out.copy_from_slice(&lane.to_le_bytes());
+    }
+    digest
+}
+
+/// First four bytes of the signature's hash, e.g. `settle(bytes,bytes)`.
+pub fn selector(signature: &str) -> [u8; 4] {
+    let hash = keccak256(signature.as_bytes());
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+/// `topics[0]` of the event with this signature.
+pub fn event_topic(signature: &str) -> [u8; 32] {
+    keccak256(signature.as_bytes())
+}
+
+/// Calldata for a function whose parameters are all `bytes`.
+pub fn encode_bytes_call(selector: [u8; 4], args: &[&[u8]]) -> Vec<u8> {
+    let word = |value: usize| {
+        let mut word = [0u8; 32];
+        word[24..].copy_from_slice(&(value as u64).to_be_bytes());
+        word
+    };
+    let mut head = Vec::new();
+    let mut tail = Vec::new();
+    for arg in args {
+        head.extend_from_slice(&word(32 * args.len() + tail.len()));
+        tail.extend_from_slice(&word(arg.len()));
+        tail.extend_from_slice(arg);
+        tail.resize(tail.len().div_ceil(32) * 32, 0);
+    }
+    let mut call = selector.to_vec();
+    call.extend(head);
+    call.extend(tail);
+    call
+}
+
+/// The seal a RISC Zero verifier router accepts for `receipt`: the first
+/// four bytes of the verifier parameters digest, then the Groth16 seal.
+pub fn encode_seal(receipt: &Receipt) -> Result<Vec<u8>, Box<dyn Error>> {
+    match &receipt.inner {
+        InnerReceipt::Groth16(groth16) => {
+            let mut seal = groth16.verifier_parameters.as_bytes()[..4].to_vec();
+            seal.extend_from_slice(&groth16.seal);
+            Ok(seal)
+        }
+        _ => Err("only Groth16 receipts can be verified on-chain".into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keccak256_matches_known_answers() {
+        let hex = |data: &[u8]| hex::encode(keccak256(data));
+        assert_eq!(hex(b""), "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470");
+        assert_eq!(hex(b"abc"), "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45");
+        // One byte short of the rate, both padding bits land in the same byte
+        assert_eq!(hex(&[b'a'; 135]), "34367dc248bbd832f4e3e69dfaac2f92638bd0bbd18f2912ba4ef454919cf446");
+        // Exactly the rate, the padding takes a block of its own
+        assert_eq!(hex(&[b'a'; 136]), "a6c4d403279fe3e0af03729caada8374b5ca54d8065329a3ebcaeb4b60aa386e");
+    }
+
+    #[test]
+    fn selectors_and_topics_match_erc20() {
+        assert_eq!(selector("transfer(address,uint256)"), [0xa9, 0x05, 0x9c, 0xbb]);
+        assert_eq!(
+            hex::encode(event_topic("Transfer(address,address,uint256)")),
+            "ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"
+        );
+    }
+}
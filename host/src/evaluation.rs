@@ -0,0 +1,172 @@
+//! Evaluation receipts for the evaluation guest (see
+//! [`zaik_core::evaluation`]).
+//!
+//! `host evaluate prove` scores a predictions file against a labels file
+//! and writes, next to the predictions, a `.receipt` holding the receipt and
+//! the evaluation it was proven with. That file is what one agent hands
+//! another to attest a model's results; [`verify`] checks it against the
+//! evaluation image, then against whichever of the two files, the expected
+//! evaluation and the accuracy floor the verifier holds.
+
+use crate::outcome::{FailureReason, Outcome};
+use crate::proof::ProofKind;
+use crate::store;
+use crate::untrusted;
+use risc0_zkvm::sha::Digest;
+use risc0_zkvm::Receipt;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use zaik_core::evaluation::{Evaluation, EvaluationResult};
+use zaik_core::schema::JournalSchema;
+
+/// What `host evaluate prove` writes next to the predictions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvaluationReceipt {
+    pub evaluation: Evaluation,
+    pub receipt: Receipt,
+}
+
+impl EvaluationReceipt {
+    /// `<predictions>.receipt`.
+    pub fn path_for(predictions: &Path) -> PathBuf {
+        let mut path = predictions.as_os_str().to_owned();
+        path.push(".receipt");
+        PathBuf::from(path)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let bytes = untrusted::read(path, untrusted::MAX_BUNDLE_BYTES)?;
+        Ok(untrusted::decode(&bytes)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        store::write_atomic(path, &bincode::serialize(self)?)?;
+        Ok(())
+    }
+}
+
+/// What a verifier holds to check an evaluation against.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EvaluationRequirements<'a> {
+    /// The predictions file scored.
+    pub predictions: Option<&'a str>,
+    /// The labels file scored against.
+    pub labels: Option<&'a str>,
+    /// The evaluation that must have been scored.
+    pub evaluation: Option<&'a Evaluation>,
+    /// Lowest accuracy accepted, in basis points.
+    pub min_accuracy_bps: Option<u32>,
+    pub allow_dev_mode: bool,
+}
+
+/// Why an evaluation receipt was not accepted.
+#[derive(Debug)]
+pub enum EvaluationRejection {
+    /// Dev-mode receipts are not accepted.
+    NotCryptographic(ProofKind),
+    ReceiptInvalid(String),
+    /// The journal is not an evaluation journal of this format.
+    JournalUnreadable,
+    /// The journal was committed for a different evaluation than the one
+    /// stored with the receipt.
+    EvaluationMismatch,
+    /// The evaluation is not the one the verifier requires.
+    SpecMismatch,
+    /// The predictions do not hash to what the journal commits.
+    PredictionsMismatch,
+    /// The labels do not hash to what the journal commits.
+    LabelsMismatch,
+    /// The proven accuracy is under the required floor.
+    AccuracyTooLow { accuracy_bps: u32, min_accuracy_bps: u32 },
+}
+
+impl fmt::Display for EvaluationRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EvaluationRejection::NotCryptographic(kind) => write!(f, "receipt is {}, not a cryptographic proof", kind),
+            EvaluationRejection::ReceiptInvalid(reason) => write!(f, "receipt does not verify: {}", reason),
+            EvaluationRejection::JournalUnreadable => write!(f, "journal is not an evaluation journal of this format"),
+            EvaluationRejection::EvaluationMismatch => {
+                write!(f, "journal was committed for a different evaluation than the receipt file holds")
+            }
+            EvaluationRejection::SpecMismatch => write!(f, "receipt was proven with a different evaluation than required"),
+            EvaluationRejection::PredictionsMismatch => write!(f, "predictions do not match the hash the journal commits"),
+            EvaluationRejection::LabelsMismatch => write!(f, "labels do not match the hash the journal commits"),
+            EvaluationRejection::AccuracyTooLow {
+                accuracy_bps,
+                min_accuracy_bps,
+            } => write!(
+                f,
+                "accuracy {} is under the required {}",
+                format_bps(*accuracy_bps),
+                format_bps(*min_accuracy_bps)
+            ),
+        }
+    }
+}
+
+impl Error for EvaluationRejection {}
+
+impl EvaluationRejection {
+    /// The outcome and stable reason a CLI reports for this rejection.
+    pub fn to_failure(&self) -> FailureReason {
+        let (outcome, reason) = match self {
+            EvaluationRejection::NotCryptographic(_) => (Outcome::Conditional, "non_cryptographic_receipt"),
+            EvaluationRejection::ReceiptInvalid(_) => (Outcome::CryptoFailure, "receipt_verification_failed"),
+            EvaluationRejection::JournalUnreadable => (Outcome::CryptoFailure, "journal_decode_failed"),
+            EvaluationRejection::EvaluationMismatch => (Outcome::CryptoFailure, "journal_mismatch"),
+            EvaluationRejection::SpecMismatch => (Outcome::PolicyReject, "evaluation_mismatch"),
+            EvaluationRejection::PredictionsMismatch => (Outcome::UnexpectedData, "predictions_mismatch"),
+            EvaluationRejection::LabelsMismatch => (Outcome::UnexpectedData, "labels_mismatch"),
+            EvaluationRejection::AccuracyTooLow { .. } => (Outcome::PolicyReject, "accuracy_below_minimum"),
+        };
+        FailureReason::new(outcome, reason, self.to_string())
+    }
+}
+
+/// Basis points as a percentage, e.g. `9250` as `92.50%`.
+pub fn format_bps(bps: u32) -> String {
+    format!("{}.{:02}%", bps / 100, bps % 100)
+}
+
+/// Check `proof` against `image_id` and `requirements`.
+pub fn verify(
+    proof: &EvaluationReceipt,
+    image_id: impl Into<Digest>,
+    requirements: EvaluationRequirements<'_>,
+) -> Result<EvaluationResult, EvaluationRejection> {
+    let kind = ProofKind::of(&proof.receipt);
+    if !kind.is_cryptographic() && !requirements.allow_dev_mode {
+        return Err(EvaluationRejection::NotCryptographic(kind));
+    }
+    proof
+        .receipt
+        .verify(image_id)
+        .map_err(|e| EvaluationRejection::ReceiptInvalid(e.to_string()))?;
+    let journal =
+        EvaluationResult::from_bytes(&proof.receipt.journal.bytes).ok_or(EvaluationRejection::JournalUnreadable)?;
+    if journal.evaluation_hash != proof.evaluation.hash() {
+        return Err(EvaluationRejection::EvaluationMismatch);
+    }
+    if requirements.evaluation.is_some_and(|evaluation| evaluation.hash() != journal.evaluation_hash) {
+        return Err(EvaluationRejection::SpecMismatch);
+    }
+    if requirements
+        .predictions
+        .is_some_and(|predictions| zaik_core::csv_hash(predictions) != journal.predictions_hash)
+    {
+        return Err(EvaluationRejection::PredictionsMismatch);
+    }
+    if requirements.labels.is_some_and(|labels| zaik_core::csv_hash(labels) != journal.labels_hash) {
+        return Err(EvaluationRejection::LabelsMismatch);
+    }
+    if let Some(min_accuracy_bps) = requirements.min_accuracy_bps.filter(|min| journal.accuracy_bps < *min) {
+        return Err(EvaluationRejection::AccuracyTooLow {
+            accuracy_bps: journal.accuracy_bps,
+            min_accuracy_bps,
+        });
+    }
+    Ok(journal)
+}
@@ -0,0 +1,106 @@
+//! CSV hashes a verifier publishes before anything is proven.
+//!
+//! ```toml
+//! [[dataset]]
+//! csv_hash = "9f2c…"     # hex, as `zaik expect` prints it
+//! note = "2024-03 ledger export"
+//! ```
+//!
+//! The prover checks its input against the file before proving and the
+//! journal against it before handing the bundle over; the verifier rejects
+//! any bundle for a hash outside it with [`Outcome::UnexpectedData`].
+
+use crate::outcome::{FailureReason, Outcome};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExpectedDataset {
+    /// Hex `csv_hash` the journal must commit.
+    pub csv_hash: String,
+    /// What the data is, for humans reviewing the file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExpectedHashes {
+    #[serde(rename = "dataset", default)]
+    pub datasets: Vec<ExpectedDataset>,
+}
+
+impl ExpectedHashes {
+    /// Read a published list, rejecting malformed or missing hashes.
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let expected: ExpectedHashes = toml::from_str(&fs::read_to_string(path)?)?;
+        if expected.datasets.is_empty() {
+            return Err("no expected CSV hashes are listed".into());
+        }
+        expected.hashes()?;
+        Ok(expected)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        fs::write(path, toml::to_string(self)?)?;
+        Ok(())
+    }
+
+    pub fn hashes(&self) -> Result<Vec<[u8; 32]>, Box<dyn Error>> {
+        self.datasets
+            .iter()
+            .map(|dataset| {
+                hex::decode(&dataset.csv_hash)
+                    .ok()
+                    .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+                    .ok_or_else(|| format!("'{}' is not a 32-byte hex CSV hash", dataset.csv_hash).into())
+            })
+            .collect()
+    }
+
+    /// Add `csv_hash` unless it is already listed; whether it was added.
+    pub fn add(&mut self, csv_hash: &[u8; 32], note: Option<String>) -> bool {
+        let csv_hash = hex::encode(csv_hash);
+        if self.datasets.iter().any(|dataset| dataset.csv_hash == csv_hash) {
+            return false;
+        }
+        self.datasets.push(ExpectedDataset { csv_hash, note });
+        true
+    }
+
+    /// Fail unless `csv_hash` is listed; `stage` says who is checking.
+    pub fn check(&self, csv_hash: &[u8; 32], stage: &'static str) -> Result<(), UnexpectedHash> {
+        let listed = self.datasets.iter().any(|dataset| dataset.csv_hash == hex::encode(csv_hash));
+        if listed {
+            Ok(())
+        } else {
+            Err(UnexpectedHash {
+                csv_hash: *csv_hash,
+                stage,
+            })
+        }
+    }
+}
+
+/// A CSV hash nobody published.
+#[derive(Debug, Clone)]
+pub struct UnexpectedHash {
+    pub csv_hash: [u8; 32],
+    pub stage: &'static str,
+}
+
+impl UnexpectedHash {
+    pub fn to_failure(&self) -> FailureReason {
+        FailureReason::new(Outcome::UnexpectedData, "unexpected_csv_hash", self.to_string())
+    }
+}
+
+impl fmt::Display for UnexpectedHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CSV hash {} is not among the expected hashes ({})", hex::encode(self.csv_hash), self.stage)
+    }
+}
+
+impl Error for UnexpectedHash {}
@@ -0,0 +1,31 @@
+use crate::policy::{PolicyOutcome, RuleOutcome};
+
+/// One line per rule, e.g. `sum_threshold: PASSED (column A sum 800, required <= 1000)`.
+pub fn trace(outcome: &PolicyOutcome) -> Vec<String> {
+    outcome.rules.iter().map(describe).collect()
+}
+
+/// Plain-language summary of why the policy accepted or rejected.
+pub fn explain(outcome: &PolicyOutcome) -> String {
+    let failed: Vec<String> = outcome.failed_rules().map(describe).collect();
+    if failed.is_empty() {
+        format!("Accepted: all {} policy rule(s) passed.", outcome.rules.len())
+    } else {
+        format!(
+            "Rejected: {} of {} policy rule(s) failed: {}.",
+            failed.len(),
+            outcome.rules.len(),
+            failed.join("; ")
+        )
+    }
+}
+
+fn describe(rule: &RuleOutcome) -> String {
+    format!(
+        "{}: {} ({}, required {})",
+        rule.rule,
+        if rule.passed { "PASSED" } else { "FAILED" },
+        rule.actual,
+        rule.requirement
+    )
+}
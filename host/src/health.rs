@@ -0,0 +1,252 @@
+//! Readiness checks for the things a proving or verifying run depends on.
+//!
+//! Each check is independent, so one report lists every missing dependency
+//! rather than the first. The host runs per invocation, so `host health` is
+//! meant to be run as an orchestrator's exec probe. The daemons serve HTTP
+//! probes instead (see [`serve_probes`]): liveness from a [`Heartbeat`] their
+//! work loop keeps, readiness from these checks.
+
+use crate::policy::Policy;
+use crate::signing::AgentKey;
+use serde::Serialize;
+use serde_json::json;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Largest probe request head read from a client.
+const MAX_PROBE_REQUEST_BYTES: u64 = 8 * 1024;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Check {
+    /// Stable identifier, e.g. `store`.
+    pub name: String,
+    pub ok: bool,
+    pub detail: String,
+}
+
+impl Check {
+    pub fn pass(name: &str, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            ok: true,
+            detail: detail.into(),
+        }
+    }
+
+    pub fn fail(name: &str, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            ok: false,
+            detail: detail.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthReport {
+    /// Whether every check passed.
+    pub ready: bool,
+    pub checks: Vec<Check>,
+}
+
+impl HealthReport {
+    pub fn new(checks: Vec<Check>) -> Self {
+        Self {
+            ready: checks.iter().all(|check| check.ok),
+            checks,
+        }
+    }
+
+    pub fn failing(&self) -> impl Iterator<Item = &Check> {
+        self.checks.iter().filter(|check| !check.ok)
+    }
+}
+
+/// The store directory exists (or can be created) and accepts writes.
+pub fn check_store(root: &Path) -> Check {
+    let probe = root.join(".health");
+    let result = fs::create_dir_all(root)
+        .and_then(|_| fs::write(&probe, b"ok"))
+        .and_then(|_| fs::remove_file(&probe));
+    match result {
+        Ok(()) => Check::pass("store", format!("{} is writable", root.display())),
+        Err(e) => Check::fail("store", format!("{}: {}", root.display(), e)),
+    }
+}
+
+/// `path` holds a loadable agent key; `name` says whose.
+pub fn check_key(name: &str, path: &Path) -> Check {
+    match AgentKey::load(path) {
+        Ok(key) => Check::pass(name, format!("{} ({})", path.display(), key.public_key())),
+        Err(e) => Check::fail(name, format!("{}: {}", path.display(), e)),
+    }
+}
+
+/// The policy file parses and passes validation.
+pub fn check_policy(path: &Path) -> Check {
+    match Policy::load(path) {
+        Ok(policy) => Check::pass("policy", format!("{} (version {})", path.display(), policy.version())),
+        Err(e) => Check::fail("policy", format!("{}: {}", path.display(), e)),
+    }
+}
+
+/// Remote proving credentials are complete when remote proving is selected.
+pub fn check_bonsai() -> Option<Check> {
+    let selected = std::env::var("RISC0_PROVER").is_ok_and(|prover| prover.eq_ignore_ascii_case("bonsai"));
+    let url = std::env::var("BONSAI_API_URL").is_ok();
+    let key = std::env::var("BONSAI_API_KEY").is_ok();
+    if !selected && !url && !key {
+        return None;
+    }
+    Some(match (url, key) {
+        (true, true) => Check::pass("bonsai", "BONSAI_API_URL and BONSAI_API_KEY are set"),
+        (false, _) => Check::fail("bonsai", "BONSAI_API_URL is not set"),
+        (true, false) => Check::fail("bonsai", "BONSAI_API_KEY is not set"),
+    })
+}
+
+/// When a daemon's work loop last made progress, for its liveness probe.
+#[derive(Debug, Clone)]
+pub struct Heartbeat {
+    /// Milliseconds since the Unix epoch.
+    last: Arc<AtomicU64>,
+    stale_after: Duration,
+}
+
+impl Heartbeat {
+    /// A heartbeat that beats now and goes stale once `stale_after` passes
+    /// without another beat.
+    pub fn new(stale_after: Duration) -> Self {
+        Self {
+            last: Arc::new(AtomicU64::new(now_millis())),
+            stale_after,
+        }
+    }
+
+    pub fn beat(&self) {
+        self.last.store(now_millis(), Ordering::Relaxed);
+    }
+
+    pub fn check(&self) -> Check {
+        let age = Duration::from_millis(now_millis().saturating_sub(self.last.load(Ordering::Relaxed)));
+        if age <= self.stale_after {
+            Check::pass("loop", format!("made progress {} ms ago", age.as_millis()))
+        } else {
+            Check::fail("loop", format!("made no progress for {} s", age.as_secs()))
+        }
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis() as u64
+}
+
+/// Serve `GET /healthz` and `GET /readyz` on `listen` from a background
+/// thread; returns the address bound. Liveness answers from `heartbeat`
+/// alone, so only a stuck loop gets the process restarted; readiness runs
+/// `readiness`, so a missing dependency only takes it out of rotation.
+/// Both answer 200 with the [`HealthReport`] when it is ready, 503 when not.
+pub fn serve_probes(
+    listen: SocketAddr,
+    heartbeat: Heartbeat,
+    readiness: impl Fn() -> HealthReport + Send + 'static,
+) -> io::Result<SocketAddr> {
+    let listener = TcpListener::bind(listen)?;
+    let address = listener.local_addr()?;
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let _ = answer_probe(stream, &heartbeat, &readiness);
+        }
+    });
+    Ok(address)
+}
+
+fn answer_probe(mut stream: TcpStream, heartbeat: &Heartbeat, readiness: &dyn Fn() -> HealthReport) -> io::Result<()> {
+    let timeout = Some(Duration::from_secs(5));
+    stream.set_read_timeout(timeout)?;
+    stream.set_write_timeout(timeout)?;
+    let mut head = BufReader::new((&stream).take(MAX_PROBE_REQUEST_BYTES));
+    let mut request_line = String::new();
+    head.read_line(&mut request_line)?;
+    // Probes carry no body; the headers are read so the close is clean
+    let mut header = String::new();
+    while head.read_line(&mut header)? > 0 && header.trim_end() != "" {
+        header.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let report = match (parts.next(), parts.next()) {
+        (Some("GET"), Some("/healthz")) => Some(HealthReport::new(vec![heartbeat.check()])),
+        (Some("GET"), Some("/readyz")) => Some(readiness()),
+        _ => None,
+    };
+    let (status, reason, body) = match report {
+        Some(report) if report.ready => (200, "OK", serde_json::to_value(report)),
+        Some(report) => (503, "Service Unavailable", serde_json::to_value(report)),
+        None => (404, "Not Found", Ok(json!({ "error": "only GET /healthz and GET /readyz are served" }))),
+    };
+    let body = serde_json::to_vec(&body?)?;
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        reason,
+        body.len()
+    )?;
+    stream.write_all(&body)?;
+    stream.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get(address: SocketAddr, path: &str) -> (u16, serde_json::Value) {
+        let mut stream = TcpStream::connect(address).unwrap();
+        write!(stream, "GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        let status = head.split_whitespace().nth(1).unwrap().parse().unwrap();
+        (status, serde_json::from_str(body).unwrap())
+    }
+
+    #[test]
+    fn liveness_follows_the_heartbeat_and_readiness_the_checks() {
+        let heartbeat = Heartbeat::new(Duration::from_secs(60));
+        let ready = Arc::new(std::sync::atomic::AtomicBool::new(true));
+        let readiness = {
+            let ready = Arc::clone(&ready);
+            move || match ready.load(Ordering::Relaxed) {
+                true => HealthReport::new(vec![Check::pass("store", "writable")]),
+                false => HealthReport::new(vec![Check::fail("store", "read-only")]),
+            }
+        };
+        let address = serve_probes("127.0.0.1:0".parse().unwrap(), heartbeat, readiness).unwrap();
+
+        assert_eq!(get(address, "/healthz").0, 200);
+        assert_eq!(get(address, "/readyz").0, 200);
+        // A lost dependency takes the process out of rotation without failing liveness
+        ready.store(false, Ordering::Relaxed);
+        let (status, body) = get(address, "/readyz");
+        assert_eq!((status, body["checks"][0]["name"].as_str()), (503, Some("store")));
+        assert_eq!(get(address, "/healthz").0, 200);
+        assert_eq!(get(address, "/metrics").0, 404);
+    }
+
+    #[test]
+    fn a_heartbeat_goes_stale_without_beats() {
+        let heartbeat = Heartbeat::new(Duration::from_millis(20));
+        assert!(heartbeat.check().ok);
+        thread::sleep(Duration::from_millis(50));
+        assert!(!heartbeat.check().ok);
+        heartbeat.beat();
+        assert!(heartbeat.check().ok);
+    }
+}
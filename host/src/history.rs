@@ -0,0 +1,296 @@
+//! Proof history: the bundles in a store and the decisions recorded on
+//! them, filtered and paged for dashboards and auditors.
+//!
+//! This backs the `registry` binary's `GET /v1/proofs`. Records are built
+//! from the files in the store alone (bundle names and times, and the
+//! `.decision.json` next to each bundle), so a query never decodes a
+//! receipt. They are ordered newest first by when the bundle was written,
+//! and pages are chained with an opaque cursor that stays valid while new
+//! bundles arrive.
+
+use crate::assurance::AssuranceTier;
+use crate::outcome::Outcome;
+use crate::signing::Decision;
+use crate::store::ProofStore;
+use crate::untrusted;
+use serde::Serialize;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Largest page a query may ask for.
+pub const MAX_LIMIT: usize = 500;
+
+/// Page size when a query does not say.
+pub const DEFAULT_LIMIT: usize = 50;
+
+/// Where a bundle stands, coarser than [`Outcome`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    /// No decision recorded yet.
+    Pending,
+    Accepted,
+    Conditional,
+    /// Held for a human; see [`Outcome::Investigate`].
+    Investigate,
+    /// Any other outcome: a failed policy, receipt, deadline or read.
+    Rejected,
+}
+
+impl Status {
+    pub fn of(outcome: Option<Outcome>) -> Self {
+        match outcome {
+            None => Status::Pending,
+            Some(Outcome::Accept) => Status::Accepted,
+            Some(Outcome::Conditional) => Status::Conditional,
+            Some(Outcome::Investigate) => Status::Investigate,
+            Some(
+                Outcome::PolicyReject
+                | Outcome::CryptoFailure
+                | Outcome::Io
+                | Outcome::DeadlineExceeded
+                | Outcome::UnexpectedData,
+            ) => Status::Rejected,
+        }
+    }
+}
+
+impl FromStr for Status {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(Status::Pending),
+            "accepted" => Ok(Status::Accepted),
+            "conditional" => Ok(Status::Conditional),
+            "investigate" => Ok(Status::Investigate),
+            "rejected" => Ok(Status::Rejected),
+            other => Err(format!(
+                "unknown status '{}' (expected pending, accepted, conditional, investigate or rejected)",
+                other
+            )),
+        }
+    }
+}
+
+/// One bundle in the store.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ProofRecord {
+    /// The bundle's [`ProofKey`](crate::store::ProofKey) digest, its file name.
+    pub digest: String,
+    pub status: Status,
+    /// The recorded decision's outcome; absent while pending.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outcome: Option<Outcome>,
+    /// Seconds since the Unix epoch when the bundle was written.
+    pub proven_at: u64,
+    /// Seconds since the Unix epoch when the decision was recorded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decided_at: Option<u64>,
+    /// The rest is copied from the decision.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bundle_sha256: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prover: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub policy_version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explanation: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub assurance_tier: Option<AssuranceTier>,
+}
+
+impl ProofRecord {
+    /// The record for the bundle at `bundle_path`. A decision that cannot
+    /// be read or parsed leaves the bundle pending.
+    pub fn load(bundle_path: &Path) -> Result<Self, Box<dyn Error>> {
+        let digest = bundle_path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .ok_or("bundle file name is not UTF-8")?
+            .to_string();
+        let proven_at = seconds(fs::metadata(bundle_path)?.modified()?);
+        let decision_path = Decision::path_for(bundle_path);
+        let decision = untrusted::read(&decision_path, untrusted::MAX_SIDECAR_BYTES)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice::<Decision>(&bytes).ok());
+        let Some(decision) = decision else {
+            return Ok(Self {
+                digest,
+                status: Status::Pending,
+                outcome: None,
+                proven_at,
+                decided_at: None,
+                bundle_sha256: None,
+                prover: None,
+                policy_version: None,
+                explanation: None,
+                assurance_tier: None,
+            });
+        };
+        Ok(Self {
+            digest,
+            status: Status::of(Some(decision.outcome)),
+            outcome: Some(decision.outcome),
+            proven_at,
+            decided_at: fs::metadata(&decision_path)?.modified().ok().map(seconds),
+            bundle_sha256: Some(decision.bundle_digest),
+            prover: Some(decision.prover),
+            policy_version: Some(decision.policy_version),
+            explanation: Some(decision.explanation),
+            assurance_tier: decision.assurance_tier,
+        })
+    }
+
+    /// Where this record sits in the newest-first order, as a cursor.
+    fn cursor(&self) -> Cursor {
+        Cursor {
+            proven_at: self.proven_at,
+            digest: self.digest.clone(),
+        }
+    }
+}
+
+fn seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+}
+
+/// Position after which the next page starts: `<proven_at>.<digest>` of the
+/// last record served.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Cursor {
+    proven_at: u64,
+    digest: String,
+}
+
+impl fmt::Display for Cursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.proven_at, self.digest)
+    }
+}
+
+impl FromStr for Cursor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("'{}' is not a cursor from a previous page", s);
+        let (proven_at, digest) = s.split_once('.').ok_or_else(invalid)?;
+        if digest.is_empty() || !digest.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(invalid());
+        }
+        Ok(Self {
+            proven_at: proven_at.parse().map_err(|_| invalid())?,
+            digest: digest.to_string(),
+        })
+    }
+}
+
+/// Filters and paging of one history query, e.g. the query string of
+/// `GET /v1/proofs?status=rejected&from=1714521600&to=1717200000&limit=20`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Query {
+    pub status: Option<Status>,
+    /// Exact outcome, for telling apart the kinds of [`Status::Rejected`].
+    pub outcome: Option<Outcome>,
+    /// Bundles written at or after, in seconds since the Unix epoch.
+    pub from: Option<u64>,
+    /// Bundles written before, in seconds since the Unix epoch.
+    pub to: Option<u64>,
+    pub limit: usize,
+    pub after: Option<Cursor>,
+}
+
+impl Default for Query {
+    fn default() -> Self {
+        Self {
+            status: None,
+            outcome: None,
+            from: None,
+            to: None,
+            limit: DEFAULT_LIMIT,
+            after: None,
+        }
+    }
+}
+
+impl Query {
+    /// Parse `status`, `outcome`, `from`, `to`, `limit` and `cursor` from a
+    /// URL query string. Unknown parameters are refused so that a typo is
+    /// not silently an unfiltered query.
+    pub fn parse(query: &str) -> Result<Self, String> {
+        let mut parsed = Self::default();
+        for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            let number = |value: &str| value.parse::<u64>().map_err(|_| format!("{} must be a number, not '{}'", name, value));
+            match name {
+                "status" => parsed.status = Some(value.parse()?),
+                "outcome" => {
+                    parsed.outcome = Some(
+                        serde_json::from_value(serde_json::Value::from(value))
+                            .map_err(|_| format!("unknown outcome '{}'", value))?,
+                    )
+                }
+                "from" => parsed.from = Some(number(value)?),
+                "to" => parsed.to = Some(number(value)?),
+                "limit" => match number(value)? {
+                    limit @ 1.. if limit as usize <= MAX_LIMIT => parsed.limit = limit as usize,
+                    _ => return Err(format!("limit must be between 1 and {}", MAX_LIMIT)),
+                },
+                "cursor" => parsed.after = Some(value.parse()?),
+                other => return Err(format!("unknown query parameter '{}'", other)),
+            }
+        }
+        Ok(parsed)
+    }
+
+    fn matches(&self, record: &ProofRecord) -> bool {
+        self.status.is_none_or(|status| record.status == status)
+            && self.outcome.is_none_or(|outcome| record.outcome == Some(outcome))
+            && self.from.is_none_or(|from| record.proven_at >= from)
+            && self.to.is_none_or(|to| record.proven_at < to)
+            && self.after.as_ref().is_none_or(|after| record.cursor() < *after)
+    }
+}
+
+/// One page of results.
+#[derive(Debug, Clone, Serialize)]
+pub struct Page {
+    pub proofs: Vec<ProofRecord>,
+    /// Pass as `cursor` for the next page; absent on the last one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+/// Run `query` over every bundle in `store`. Bundles removed while the
+/// store is read are skipped.
+pub fn query(store: &ProofStore, query: &Query) -> Result<Page, Box<dyn Error>> {
+    let mut records: Vec<ProofRecord> = store
+        .bundles()?
+        .iter()
+        .filter_map(|path| ProofRecord::load(path).ok())
+        .filter(|record| query.matches(record))
+        .collect();
+    records.sort_by_key(|record| std::cmp::Reverse(record.cursor()));
+    let next_cursor = (records.len() > query.limit).then(|| records[query.limit - 1].cursor().to_string());
+    records.truncate(query.limit);
+    Ok(Page {
+        proofs: records,
+        next_cursor,
+    })
+}
+
+/// The record for the bundle with `digest`, if the store has it.
+pub fn find(store: &ProofStore, digest: &str) -> Result<Option<ProofRecord>, Box<dyn Error>> {
+    if digest.is_empty() || !digest.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Ok(None);
+    }
+    let path = store.bundle_path(digest);
+    if !path.exists() {
+        return Ok(None);
+    }
+    Ok(Some(ProofRecord::load(&path)?))
+}
@@ -0,0 +1,135 @@
+//! Console, JSON, and Markdown renderings of a journal, all generated from
+//! its [`JournalSchema`].
+
+use serde_json::{json, Map, Value};
+use std::error::Error;
+use zaik_core::cells::CellCounts;
+use zaik_core::dialect::Dialect;
+use zaik_core::invariant::{Aggregate, Invariant};
+use zaik_core::schema::{JournalSchema, JournalValue, JOURNAL_FORMAT};
+use zaik_core::{ColumnHashMode, ComparisonOp, CsvProcessingInput, ProcessingOptions, SumBound};
+
+/// Decode journal bytes committed in the explicit layout of
+/// [`JournalSchema::to_bytes`].
+pub fn decode<J: JournalSchema>(bytes: &[u8]) -> Result<J, Box<dyn Error>> {
+    J::from_bytes(bytes).ok_or_else(|| "journal does not follow the zaik byte layout".into())
+}
+
+/// `Label: value` lines in commit order.
+pub fn lines<J: JournalSchema>(journal: &J) -> Vec<String> {
+    J::FIELDS
+        .iter()
+        .zip(journal.values())
+        .map(|(field, value)| format!("{}: {}", field.label, value))
+        .collect()
+}
+
+/// JSON object keyed by field name; digests are hex strings.
+pub fn to_json<J: JournalSchema>(journal: &J) -> Value {
+    let mut object = Map::new();
+    for (field, value) in J::FIELDS.iter().zip(journal.values()) {
+        object.insert(field.name.to_string(), json_value(value));
+    }
+    Value::Object(object)
+}
+
+fn json_value(value: JournalValue) -> Value {
+    match value {
+        JournalValue::Digest(_) => Value::String(value.to_string()),
+        JournalValue::Count(count) => Value::from(count),
+        JournalValue::Flag(flag) => Value::Bool(flag),
+        JournalValue::Label(label) => Value::from(label),
+        JournalValue::Text(text) => Value::String(text),
+        JournalValue::List(values) => Value::Array(values.into_iter().map(json_value).collect()),
+    }
+}
+
+/// A field whose value differs between two journals.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldDiff {
+    pub name: &'static str,
+    pub expected: String,
+    pub actual: String,
+}
+
+/// Fields of `actual` that differ from `expected`, in commit order.
+pub fn diff<J: JournalSchema>(expected: &J, actual: &J) -> Vec<FieldDiff> {
+    J::FIELDS
+        .iter()
+        .zip(expected.values().into_iter().zip(actual.values()))
+        .filter(|(_, (expected, actual))| expected != actual)
+        .map(|(field, (expected, actual))| FieldDiff {
+            name: field.name,
+            expected: expected.to_string(),
+            actual: actual.to_string(),
+        })
+        .collect()
+}
+
+/// Golden vector for non-Rust decoders: a fixed sample journal, its bytes
+/// in hex, and its JSON rendering. The sample sets every field to a value
+/// other than its default (the chunk image ID is patterned rather than
+/// proven) so each encoding is exercised.
+pub fn golden() -> Value {
+    let csv_data = "value_a;value_b\n100;7\n\"250\";3\n40;9\n".to_string();
+    let input = CsvProcessingInput {
+        csv_hash: zaik_core::csv_hash(&csv_data),
+        csv_data,
+        options: ProcessingOptions {
+            column_hash: ColumnHashMode::Transcript,
+            conceal_sum: false,
+            dialect: Dialect {
+                delimiter: b';',
+                quote: b'"',
+                has_header: true,
+            },
+        },
+        sum_bound: SumBound {
+            op: ComparisonOp::Ge,
+            threshold: 300,
+        },
+        invariants: vec![Invariant {
+            column: 1,
+            aggregate: Aggregate::Max,
+            op: ComparisonOp::Lt,
+            bound: 10,
+        }],
+        sum_salt: [7u8; 32],
+        inline_limit: 64,
+        aux_tables: Vec::new(),
+        max_rows: 2,
+    };
+    let mut journal = zaik_core::process(&input).expect("the sample hash is computed from its data");
+    journal.chunk_image_id = [1, 2, 3, 4, 5, 6, 7, 8];
+    journal.aux_tables_hash = [0xa5; 32];
+    journal.dataset_files = 3;
+    journal.cell_counts[1] = CellCounts {
+        empty: 1,
+        unparseable: 2,
+    };
+    journal.ragged_rows = 1;
+    json!({
+        "format": JOURNAL_FORMAT,
+        "bytes": hex::encode(journal.to_bytes()),
+        "journal": to_json(&journal),
+    })
+}
+
+/// Markdown table documenting the journal layout.
+pub fn markdown<J: JournalSchema>() -> String {
+    let mut doc = String::from(
+        "| # | Field | Type | Bytes | Section | Description |\n|---|-------|------|-------|---------|-------------|\n",
+    );
+    for (index, field) in J::FIELDS.iter().enumerate() {
+        doc.push_str(&format!(
+            "| {} | `{}` | `{}` | {} | {} | {} |\n",
+            index,
+            field.name,
+            field.ty,
+            field.layout,
+            field.section.as_str(),
+            field.doc.trim()
+        ));
+    }
+    doc
+}
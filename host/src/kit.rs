@@ -0,0 +1,245 @@
+//! Offline verification kits: everything an auditor needs to re-verify a
+//! set of bundles on a machine with no network access.
+//!
+//! ```text
+//! zaik-kit/
+//! ├── bin/verify          # standalone verifier (see `src/bin/verify.rs`)
+//! ├── images.toml         # pinned guest image IDs; see `crate::allowlist`
+//! ├── verifier.toml       # risc0 version and receipt verifier parameters
+//! ├── policy.toml         # policy the bundles must have been proven under
+//! ├── bundles/            # bundles with their signatures, timestamps,
+//! │                       # decisions, transcripts, reveals and
+//! │                       # environment manifests
+//! ├── verify.sh           # runs bin/verify on every bundle
+//! ├── README.txt          # instructions for the auditor
+//! └── SHA256SUMS          # SHA-256 of every file above, for `sha256sum -c`
+//! ```
+//!
+//! The auditor checks `SHA256SUMS` against a digest received out of band,
+//! then runs `verify.sh`. Nothing in the kit is fetched or built.
+
+use crate::allowlist::{ImageAllowlist, PinnedImage};
+use crate::environment::EnvironmentManifest;
+use crate::signing::{Decision, DetachedSignature};
+use crate::transcript::Transcript;
+use crate::{protocol, timestamp};
+use risc0_zkvm::sha::{Digest, Digestible};
+use risc0_zkvm::{Groth16ReceiptVerifierParameters, SuccinctReceiptVerifierParameters};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::error::Error;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// What goes into a kit.
+#[derive(Debug, Clone)]
+pub struct KitContents {
+    /// The standalone `verify` binary to ship.
+    pub verifier: PathBuf,
+    /// Guest image IDs the bundles may have been proven with.
+    pub images: Vec<PinnedImage>,
+    pub policy: PathBuf,
+    pub bundles: Vec<PathBuf>,
+}
+
+/// Receipt verification parameters the kit's verifier was built with, so
+/// an auditor can compare them with the ones risc0 publishes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerifierParameters {
+    pub risc0_version: String,
+    /// Hex digest of the succinct receipt verifier parameters.
+    pub succinct: String,
+    /// Hex digest of the Groth16 receipt verifier parameters, which pin the
+    /// Groth16 verifying key.
+    pub groth16: String,
+}
+
+impl VerifierParameters {
+    /// The parameters of this build.
+    pub fn current() -> Self {
+        Self {
+            risc0_version: risc0_zkvm::VERSION.to_string(),
+            succinct: hex::encode(SuccinctReceiptVerifierParameters::default().digest()),
+            groth16: hex::encode(Groth16ReceiptVerifierParameters::default().digest()),
+        }
+    }
+}
+
+/// One file of a kit and its SHA-256.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KitFile {
+    /// Path relative to the kit directory, with `/` separators.
+    pub path: String,
+    pub sha256: [u8; 32],
+}
+
+/// A written kit.
+#[derive(Debug, Clone)]
+pub struct Kit {
+    /// Every file but `SHA256SUMS`, in path order.
+    pub files: Vec<KitFile>,
+    /// SHA-256 of `SHA256SUMS`, to hand the auditor out of band.
+    pub digest: [u8; 32],
+}
+
+/// Pin `image_id` in a kit, with a note on where it came from.
+pub fn pinned(image_id: impl Into<Digest>, note: &str) -> PinnedImage {
+    PinnedImage {
+        id: hex::encode(image_id.into()),
+        note: Some(note.to_string()),
+    }
+}
+
+/// Write a kit into `out`, which must not exist yet.
+pub fn export(contents: &KitContents, out: &Path) -> Result<Kit, Box<dyn Error>> {
+    if out.exists() {
+        return Err(format!("{} already exists; choose a new directory for the kit", out.display()).into());
+    }
+    if contents.bundles.is_empty() {
+        return Err("no bundles to put in the kit".into());
+    }
+    fs::create_dir_all(out.join("bin"))?;
+    fs::create_dir_all(out.join("bundles"))?;
+
+    copy_executable(&contents.verifier, &out.join("bin").join("verify"))?;
+    let allowlist = ImageAllowlist {
+        images: contents.images.clone(),
+    };
+    allowlist.digests()?;
+    fs::write(out.join("images.toml"), toml::to_string(&allowlist)?)?;
+    fs::write(out.join("verifier.toml"), toml::to_string(&VerifierParameters::current())?)?;
+    fs::copy(&contents.policy, out.join("policy.toml"))?;
+    for bundle in &contents.bundles {
+        for path in sidecars(bundle) {
+            if path == *bundle || path.exists() {
+                let name = path.file_name().ok_or_else(|| format!("{} is not a file", path.display()))?;
+                fs::copy(&path, out.join("bundles").join(name))?;
+            }
+        }
+    }
+    write_script(&out.join("verify.sh"))?;
+    fs::write(out.join("README.txt"), README)?;
+
+    let files = hash_files(out)?;
+    let mut sums = String::new();
+    for file in &files {
+        writeln!(sums, "{}  {}", hex::encode(file.sha256), file.path)?;
+    }
+    fs::write(out.join("SHA256SUMS"), &sums)?;
+    Ok(Kit {
+        files,
+        digest: sha256(sums.as_bytes()),
+    })
+}
+
+/// The bundle and every file recorded next to it.
+fn sidecars(bundle: &Path) -> Vec<PathBuf> {
+    let decision = Decision::path_for(bundle);
+    let reveal = protocol::Reveal::path_for(bundle);
+    let environment = EnvironmentManifest::path_for(bundle);
+    let transcript = Transcript::path_for(bundle);
+    vec![
+        bundle.to_path_buf(),
+        DetachedSignature::path_for(bundle),
+        timestamp::path_for(bundle),
+        DetachedSignature::path_for(&decision),
+        DetachedSignature::path_for(&reveal),
+        DetachedSignature::path_for(&environment),
+        DetachedSignature::path_for(&transcript),
+        decision,
+        reveal,
+        environment,
+        transcript,
+    ]
+}
+
+/// Every file under `dir`, hashed, with paths relative to it.
+fn hash_files(dir: &Path) -> Result<Vec<KitFile>, Box<dyn Error>> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(next) = pending.pop() {
+        for entry in fs::read_dir(&next)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                pending.push(path);
+                continue;
+            }
+            let relative = path.strip_prefix(dir)?;
+            files.push(KitFile {
+                path: relative
+                    .components()
+                    .map(|part| part.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/"),
+                sha256: sha256(&fs::read(&path)?),
+            });
+        }
+    }
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(files)
+}
+
+fn sha256(bytes: &[u8]) -> [u8; 32] {
+    use sha2::Digest as _;
+    Sha256::digest(bytes).into()
+}
+
+fn copy_executable(from: &Path, to: &Path) -> Result<(), Box<dyn Error>> {
+    fs::copy(from, to).map_err(|e| format!("verifier binary {}: {}", from.display(), e))?;
+    set_executable(to)
+}
+
+fn write_script(path: &Path) -> Result<(), Box<dyn Error>> {
+    fs::write(path, SCRIPT)?;
+    set_executable(path)
+}
+
+#[cfg(unix)]
+fn set_executable(path: &Path) -> Result<(), Box<dyn Error>> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755))?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn set_executable(_path: &Path) -> Result<(), Box<dyn Error>> {
+    Ok(())
+}
+
+const SCRIPT: &str = r#"#!/bin/sh
+# Re-verify every bundle in this kit; exits non-zero if any fails.
+cd "$(dirname "$0")" || exit 1
+status=0
+for bundle in bundles/*.bundle; do
+    ./bin/verify "$bundle" --allowlist images.toml --policy policy.toml || status=1
+done
+exit $status
+"#;
+
+const README: &str = "\
+zaik offline verification kit
+=============================
+
+This directory re-verifies a set of zaik proof bundles without network
+access or a build toolchain.
+
+1. Check the kit. Compare the SHA-256 of SHA256SUMS with the digest you
+   received out of band, then check every file it lists:
+
+       sha256sum SHA256SUMS
+       sha256sum -c SHA256SUMS
+
+2. Review what the bundles are checked against:
+   - images.toml: the guest image IDs accepted;
+   - policy.toml: the business rules every bundle must satisfy;
+   - verifier.toml: the risc0 version and receipt verifier parameter
+     digests bin/verify was built with.
+
+3. Run ./verify.sh. Each bundle prints its verdict. A failure prints one
+   JSON line with an outcome, reason and exit code, as the zaik host does.
+
+Signatures (.sig), timestamps (.tst), decisions, verification transcripts
+(.transcript.json), reveals and environment manifests (.env.json) recorded
+with a bundle are in bundles/ next to it.
+";
@@ -0,0 +1,63 @@
+//! Host side of zaik: proof bundles, policies, verification and signing.
+//!
+//! Journal types live in [`zaik_core`], re-exported here so a verifier only
+//! needs this crate; proving needs the default `prove` feature.
+
+pub use zaik_core;
+
+pub mod alerts;
+pub mod allowlist;
+pub mod assurance;
+pub mod attestation;
+pub mod audit;
+pub mod audit_log;
+pub mod backend;
+pub mod backfill;
+pub mod bonding;
+pub mod budgets;
+pub mod bulk;
+pub mod canonical;
+pub mod cells;
+pub mod columns;
+pub mod dashboard;
+pub mod dataset;
+pub mod deadline;
+pub mod dispute;
+pub mod environment;
+pub mod evaluation;
+pub mod ethereum;
+pub mod expected;
+pub mod explain;
+pub mod health;
+pub mod history;
+#[cfg(feature = "prove")]
+pub mod input;
+pub mod journal;
+pub mod kit;
+pub mod marketplace;
+pub mod metadata;
+pub mod migrate;
+pub mod outcome;
+pub mod policy;
+pub mod privacy;
+pub mod proof;
+pub mod protocol;
+pub mod relying_party;
+pub mod reputation;
+pub mod rollout;
+#[cfg(feature = "prove")]
+pub mod session;
+pub mod shutdown;
+pub mod signing;
+pub mod slo;
+pub mod solidity;
+pub mod store;
+pub mod telemetry;
+pub mod templates;
+pub mod timestamp;
+pub mod transcript;
+pub mod transform;
+pub mod trend;
+pub mod untrusted;
+pub mod webhook;
+pub mod window;
@@ -1,144 +1,3932 @@
-use methods::{
-    GUEST_CODE_FOR_ZK_PROOF_ELF, GUEST_CODE_FOR_ZK_PROOF_ID
+use clap::{Args, Parser, Subcommand};
+use zaik::alerts::{Alert, AlertSinks};
+use zaik::allowlist::ImageAllowlist;
+use zaik::assurance::{AssuranceEvidence, AssuranceTier};
+use zaik::attestation::{Attestation, AttestationError, Envelope, Statement};
+use zaik::audit;
+use zaik::audit_log;
+use zaik::backfill::{BackfillReport, Period, PeriodRecord, PeriodStatus, StateChain};
+use zaik::bonding::{BondError, Ledger, LedgerEntry};
+use zaik::budgets::{Budget, BudgetError};
+use zaik::bulk::{self, BulkReport};
+use zaik::columns::{self, ColumnMatch, MatchKind};
+use zaik::dataset::DatasetManifest;
+use zaik::deadline::{self, Deadline};
+use zaik::dispute::{Arbiter, Dispute, Ruling};
+use zaik::environment::{EnvironmentManifest, RecordedManifest};
+use zaik::evaluation::{self as evaluation_proof, EvaluationReceipt, EvaluationRequirements};
+use zaik::expected::ExpectedHashes;
+use zaik::explain;
+use zaik::health::{self, Check, HealthReport};
+use zaik::input;
+use zaik::journal;
+use zaik::kit::{self, KitContents};
+use zaik::metadata::BundleMetadata;
+use zaik::migrate::{self, BundleLayout};
+use zaik::outcome::{FailureReason, Outcome};
+use zaik::policy::{Policy, PolicyOutcome};
+use zaik::privacy::{self, BudgetAction, Release};
+use zaik::proof::ProofKind;
+use zaik::protocol::{self, Reveal, RevealRequest, RevealScope, SlashingEvidence};
+use zaik::relying_party::{Rejection, RelyingParty};
+use zaik::reputation;
+use zaik::rollout;
+use zaik::signing::{self, AgentKey, Decision, DetachedSignature, SignerRole};
+use zaik::slo;
+use zaik::solidity;
+use zaik::session::{self, JsonLinesEvents, ProofSession, ProveError, ProverEvent, ProverEvents, ProverOptions};
+use zaik::store::{self, ProofBundle, ProofKey, ProofStore, RetentionPolicy};
+use zaik::telemetry::{self, Span, Tracer};
+use zaik::timestamp::{self, TsaCertificate};
+use zaik::transcript::{Step, Transcript};
+use zaik::transform::{self as transform_proof, MigrationReceipt, MigrationSpec, TransformReceipt, TransformSpec};
+use zaik::trend::Trend;
+use zaik::untrusted;
+use zaik::window::{self as window_proof, WindowReceipt, WindowRequirements, WindowState};
+use zaik::webhook::{self, Notification, Webhook};
+use zaik_guest::{
+    GUEST_CODE_FOR_ZK_PROOF_ELF, GUEST_CODE_FOR_ZK_PROOF_ID, ZAIK_EVALUATE_ELF, ZAIK_EVALUATE_ID, ZAIK_TRANSFORM_ELF,
+    ZAIK_TRANSFORM_ID,
 };
-use risc0_zkvm::{default_prover, ExecutorEnv, Receipt};
-use serde::{Deserialize, Serialize};
-use sha2::{Sha256, Digest};
+use risc0_zkvm::sha::Digestible;
+use risc0_zkvm::{default_executor, default_prover, ExecutorEnv, InnerReceipt, Receipt, ReceiptKind};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
 use std::fs;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use zaik_core::chunk::{self, ChunkInput, ChunkResult, ChunkedInput};
+use zaik_core::dataset::{self, DatasetFile, DatasetInput};
+use zaik_core::dialect::Dialect;
+use zaik_core::evaluation::{Evaluation, EvaluationInput, EvaluationResult};
+use zaik_core::schema::{JournalSchema, JOURNAL_FORMAT};
+use zaik_core::migration::{MigrationInput, MigrationResult};
+use zaik_core::transform::{TransformGuestInput, TransformInput, TransformResult};
+use zaik_core::window::{self, WindowInput, WindowSpec};
+use zaik_core::{
+    AgentResult, AuxTable, ColumnHashMode, ComparisonOp, CsvProcessingInput, GuestInput, ProcessingOptions, SumBound,
+    SumOpening, SUPPORTED_SEMANTICS,
+};
+
+/// Prove column A of a CSV file inside the zkVM and verify the result.
+#[derive(Debug, Parser)]
+#[command(args_conflicts_with_subcommands = true)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    prove: ProveArgs,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Run the guest aggregation natively and print the journal it would commit
+    Simulate(SimulateArgs),
+    /// Print the journal layout as Markdown
+    Schema {
+        /// Print the golden byte-layout vector as JSON instead
+        #[arg(long)]
+        golden: bool,
+    },
+    /// Prove a date-ranged series of files and chain the attested periods
+    Backfill(BackfillArgs),
+    /// Archive or delete expired proof bundles, keeping their journals
+    Gc(GcArgs),
+    /// Generate an Ed25519 agent key for signing bundles or decisions
+    Keygen {
+        /// Where to write the PKCS#8 key
+        out: PathBuf,
+    },
+    /// Check a bundle's prover signature, decision, and verifier signature
+    VerifyChain(VerifyChainArgs),
+    /// Re-verify every bundle in a directory against a policy, in parallel
+    VerifyAll(VerifyAllArgs),
+    /// Re-execute the guest for a bundle and diff the journal it commits
+    Replay(ReplayArgs),
+    /// Rewrap a bundle from an earlier release in the current layout
+    Migrate(MigrateArgs),
+    /// Check the prover, store, keys and policy a run depends on; exit 0 when ready
+    Health(HealthArgs),
+    /// Publish the CSV hashes Agent B expects, for `--expect` on either side
+    Expect(ExpectArgs),
+    /// Emit Solidity that decodes journals and enforces a policy on-chain
+    Codegen(CodegenArgs),
+    /// Show and check the running totals counted against period budgets
+    Budget(BudgetArgs),
+    /// Check proving time and bundle size against service-level objectives
+    Slo(SloArgs),
+    /// Send dead-lettered decision notifications again
+    WebhookRetry {
+        /// Directory of proof bundles whose dead letters to send
+        #[arg(long, default_value = ".zaik/proofs")]
+        store: PathBuf,
+    },
+    /// Write a manifest proving several CSV files as one dataset, for `--dataset`
+    Dataset(DatasetArgs),
+    /// Write a directory an auditor can use to re-verify bundles offline
+    ExportKit(ExportKitArgs),
+    /// Wrap bundles in in-toto attestations, or check one against its bundle
+    Attest {
+        #[command(subcommand)]
+        command: AttestCommand,
+    },
+    /// Post prover bonds and slash them with evidence of misbehavior, on the mock ledger
+    Bond {
+        #[command(subcommand)]
+        command: BondCommand,
+    },
+    /// Contest a rejection, or rule on a contested one as an arbiter
+    Dispute {
+        #[command(subcommand)]
+        command: DisputeCommand,
+    },
+    /// Check the store's audit log, or prove one entry is in it
+    AuditLog {
+        #[command(subcommand)]
+        command: AuditLogCommand,
+    },
+    /// Print this build's environment manifest and the digest a policy approves it by
+    Environment {
+        /// Print the whole manifest as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Work with policy files
+    Policy {
+        #[command(subcommand)]
+        command: PolicyCommand,
+    },
+    /// Prove a CSV-to-JSON transformation with the transform guest, or check one
+    Transform {
+        #[command(subcommand)]
+        command: TransformCommand,
+    },
+    /// Prove a model's accuracy against a labeled file with the evaluation guest, or check it
+    Evaluate {
+        #[command(subcommand)]
+        command: EvaluateCommand,
+    },
+    /// Prove rolling aggregates over an append-only log, or check the latest step
+    Window {
+        #[command(subcommand)]
+        command: WindowCommand,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum PolicyCommand {
+    /// Evaluate a proposed policy over stored bundles and report which decisions would change
+    Test(PolicyTestArgs),
+    /// Resolve the columns a policy names against a CSV's header, optionally writing it out by index
+    Resolve(PolicyResolveArgs),
+}
+
+#[derive(Debug, Subcommand)]
+enum EvaluateCommand {
+    /// Score predictions against labels and prove the result
+    Prove(EvaluateProveArgs),
+    /// Check an evaluation receipt, against the predictions, labels and an accuracy floor when given
+    Verify(EvaluateVerifyArgs),
+}
+
+#[derive(Debug, Args)]
+struct EvaluateProveArgs {
+    /// CSV of the model's predictions; the receipt goes to <predictions>.receipt
+    predictions: PathBuf,
+
+    /// CSV of the true labels, one row per prediction
+    labels: PathBuf,
+
+    /// Zero-based column of the prediction
+    #[arg(long, default_value_t = 0)]
+    prediction_column: u32,
+
+    /// Zero-based column of the label
+    #[arg(long, default_value_t = 0)]
+    label_column: u32,
+
+    /// Zero-based column of a row ID both files carry, checked to line up
+    #[arg(long)]
+    id_column: Option<u32>,
+
+    /// Class counted as positive, for precision and recall
+    #[arg(long)]
+    positive: Option<String>,
+
+    /// Field delimiter of both files
+    #[arg(long, default_value = ",", value_parser = parse_dialect_char)]
+    delimiter: u8,
+
+    /// The files have no header line
+    #[arg(long)]
+    no_header: bool,
+}
+
+#[derive(Debug, Args)]
+struct EvaluateVerifyArgs {
+    /// Receipt written by `evaluate prove`
+    receipt: PathBuf,
+
+    /// Predictions the receipt must have scored
+    #[arg(long)]
+    predictions: Option<PathBuf>,
+
+    /// Labels the receipt must have scored against
+    #[arg(long)]
+    labels: Option<PathBuf>,
+
+    /// Lowest accuracy accepted, as a fraction, e.g. 0.9
+    #[arg(long, value_parser = parse_accuracy)]
+    min_accuracy: Option<u32>,
+
+    /// Accept dev-mode receipts (test deployments only)
+    #[arg(long)]
+    allow_dev_mode: bool,
+}
+
+#[derive(Debug, Subcommand)]
+enum WindowCommand {
+    /// Append a batch of rows to the log and prove the window over it
+    Append(WindowAppendArgs),
+    /// Check the latest step's receipt: the whole log, and the window's sum against its cap
+    Verify(WindowVerifyArgs),
+}
+
+#[derive(Debug, Args)]
+struct WindowAppendArgs {
+    /// CSV batch of rows to append, oldest first
+    batch: PathBuf,
+
+    /// The prover's state file; the receipt for verifiers goes to <state>.receipt
+    #[arg(long, default_value = "window.state")]
+    state: PathBuf,
+
+    /// Keep the last N rows (first step only)
+    #[arg(long, conflicts_with = "seconds")]
+    rows: Option<u64>,
+
+    /// Keep the rows of the last N seconds before the newest, e.g. 86400 (first step only)
+    #[arg(long)]
+    seconds: Option<u64>,
+
+    /// Zero-based column of the Unix timestamp (first step only) [default: 0]
+    #[arg(long)]
+    time_column: Option<u32>,
+
+    /// Zero-based column of the value summed (first step only) [default: 1]
+    #[arg(long)]
+    value_column: Option<u32>,
+
+    /// Field delimiter of the batches (first step only) [default: ,]
+    #[arg(long, value_parser = parse_dialect_char)]
+    delimiter: Option<u8>,
+
+    /// The batches have no header line (first step only)
+    #[arg(long)]
+    no_header: bool,
+
+    /// Largest window sum that passes
+    #[arg(long, default_value_t = u64::MAX)]
+    cap: u64,
+}
+
+impl WindowAppendArgs {
+    /// The spec the flags give, checked against the log's when it has one.
+    fn spec(&self, existing: Option<WindowSpec>) -> Result<WindowSpec, FailureReason> {
+        let invalid = |message: String| FailureReason::new(Outcome::Io, "window_spec_invalid", message);
+        let Some(existing) = existing else {
+            let spec = WindowSpec {
+                dialect: Dialect {
+                    delimiter: self.delimiter.unwrap_or(Dialect::CSV.delimiter),
+                    quote: Dialect::CSV.quote,
+                    has_header: !self.no_header,
+                },
+                time_column: self.time_column.unwrap_or(0),
+                value_column: self.value_column.unwrap_or(1),
+                rows: self.rows.unwrap_or(0),
+                seconds: self.seconds.unwrap_or(0),
+            };
+            spec.validate().map_err(|e| invalid(format!("{}; pass --rows or --seconds", e)))?;
+            return Ok(spec);
+        };
+        let differs = self.rows.is_some_and(|rows| rows != existing.rows)
+            || self.seconds.is_some_and(|seconds| seconds != existing.seconds)
+            || self.time_column.is_some_and(|column| column != existing.time_column)
+            || self.value_column.is_some_and(|column| column != existing.value_column)
+            || self.delimiter.is_some_and(|delimiter| delimiter != existing.dialect.delimiter)
+            || (self.no_header && existing.dialect.has_header);
+        if differs {
+            return Err(invalid(format!(
+                "{} keeps a log under another spec; a spec is fixed when the log starts",
+                self.state.display()
+            )));
+        }
+        Ok(existing)
+    }
+}
+
+#[derive(Debug, Args)]
+struct WindowVerifyArgs {
+    /// Receipt written next to the prover's state
+    receipt: PathBuf,
+
+    /// Require a window of this many rows
+    #[arg(long, conflicts_with = "seconds")]
+    rows: Option<u64>,
+
+    /// Require a window of this many seconds
+    #[arg(long)]
+    seconds: Option<u64>,
+
+    /// Require the sum to have been checked against this cap or a tighter one
+    #[arg(long)]
+    max_cap: Option<u64>,
+
+    /// Accept dev-mode receipts (test deployments only)
+    #[arg(long)]
+    allow_dev_mode: bool,
+}
+
+#[derive(Debug, Subcommand)]
+enum TransformCommand {
+    /// Transform a CSV into JSON Lines and prove the output
+    Prove(TransformProveArgs),
+    /// Check a transform receipt against its output, and the spec and CSV when given
+    Verify(TransformVerifyArgs),
+    /// Prove a new version of a CSV is an old one with its schema migrated
+    Migrate(TransformMigrateArgs),
+    /// Check a migration receipt against the new file, and the spec and old file when given
+    VerifyMigration(TransformVerifyMigrationArgs),
+}
+
+#[derive(Debug, Args)]
+struct TransformProveArgs {
+    /// CSV file to transform
+    csv: PathBuf,
+
+    /// TOML transform spec
+    #[arg(long)]
+    spec: PathBuf,
+
+    /// JSON Lines output to write; the receipt goes to <out>.receipt
+    #[arg(long, default_value = "records.jsonl")]
+    out: PathBuf,
+}
+
+#[derive(Debug, Args)]
+struct TransformVerifyArgs {
+    /// JSON Lines output of `transform prove`
+    output: PathBuf,
+
+    /// Receipt to check [default: <output>.receipt]
+    #[arg(long)]
+    receipt: Option<PathBuf>,
+
+    /// Spec the output must have been produced with
+    #[arg(long)]
+    spec: Option<PathBuf>,
+
+    /// CSV the output must have been produced from
+    #[arg(long)]
+    csv: Option<PathBuf>,
+
+    /// Accept dev-mode receipts (test deployments only)
+    #[arg(long)]
+    allow_dev_mode: bool,
+}
+
+#[derive(Debug, Args)]
+struct TransformMigrateArgs {
+    /// The dataset before the migration
+    old: PathBuf,
+
+    /// The dataset after it; the receipt goes to <new>.receipt
+    new: PathBuf,
+
+    /// TOML migration spec
+    #[arg(long)]
+    spec: PathBuf,
+
+    /// Write the migrated old file to <new> first, instead of proving an existing one
+    #[arg(long)]
+    write: bool,
+}
+
+#[derive(Debug, Args)]
+struct TransformVerifyMigrationArgs {
+    /// The dataset after the migration
+    new: PathBuf,
+
+    /// Receipt to check [default: <new>.receipt]
+    #[arg(long)]
+    receipt: Option<PathBuf>,
+
+    /// Spec the migration must have been proven with
+    #[arg(long)]
+    spec: Option<PathBuf>,
+
+    /// The dataset before the migration
+    #[arg(long)]
+    old: Option<PathBuf>,
+
+    /// Accept dev-mode receipts (test deployments only)
+    #[arg(long)]
+    allow_dev_mode: bool,
+}
+
+#[derive(Debug, Subcommand)]
+enum AttestCommand {
+    /// Write an in-toto statement about a bundle, DSSE-signed when given a key
+    Export(AttestExportArgs),
+    /// Check an in-toto statement or DSSE envelope against a bundle and verify the bundle
+    Import(AttestImportArgs),
+}
+
+#[derive(Debug, Subcommand)]
+enum BondCommand {
+    /// Add to a prover's bond
+    Post(BondPostArgs),
+    /// Write slashing evidence against a signed bundle the prover's policy rejects
+    Evidence(BondEvidenceArgs),
+    /// Verify slashing evidence and slash the prover's bond
+    Slash(BondSlashArgs),
+    /// Print every prover's bond and the ledger's movements
+    Show {
+        /// Proof store holding the ledger
+        #[arg(long, default_value = ".zaik/proofs")]
+        store: PathBuf,
+    },
+}
+
+#[derive(Debug, Args)]
+struct BondPostArgs {
+    /// Hex public key the prover signs bundles with
+    #[arg(long)]
+    prover: String,
+
+    #[arg(long)]
+    amount: u64,
+
+    /// Proof store holding the ledger
+    #[arg(long, default_value = ".zaik/proofs")]
+    store: PathBuf,
+}
+
+#[derive(Debug, Args)]
+struct BondEvidenceArgs {
+    /// Signed proof bundle; its `.sig` must be next to it
+    bundle: PathBuf,
+
+    /// Policy the bundle's key claims
+    #[arg(long)]
+    policy: PathBuf,
+
+    /// Where to write the evidence (default: the bundle path with `.evidence.json`)
+    #[arg(long)]
+    out: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+struct BondSlashArgs {
+    /// Slashing evidence, from `bond evidence`
+    evidence: PathBuf,
+
+    /// Most to slash; capped at the prover's bond
+    #[arg(long)]
+    amount: u64,
+
+    /// Trust the image IDs in this allowlist instead of this build's guest
+    #[arg(long)]
+    allowlist: Option<PathBuf>,
+
+    /// Proof store holding the ledger
+    #[arg(long, default_value = ".zaik/proofs")]
+    store: PathBuf,
+}
+
+#[derive(Debug, Subcommand)]
+enum DisputeCommand {
+    /// Write a dispute over a rejected bundle, as its prover
+    Open(DisputeOpenArgs),
+    /// Re-verify a disputed bundle and sign a ruling, recorded in the audit log
+    Rule(DisputeRuleArgs),
+}
+
+#[derive(Debug, Args)]
+struct DisputeOpenArgs {
+    /// Rejected bundle; its `.sig`, decision and decision `.sig` must be next to it
+    bundle: PathBuf,
+
+    /// Policy the decision was made under
+    #[arg(long)]
+    policy: PathBuf,
+
+    /// Bundle for the same CSV and policy proven again
+    #[arg(long)]
+    counter_proof: Option<PathBuf>,
+
+    /// Why the rejection is disputed, for the record
+    #[arg(long, default_value = "")]
+    statement: String,
+
+    /// Where to write the dispute (default: the bundle path with `.dispute.json`)
+    #[arg(long)]
+    out: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+struct DisputeRuleArgs {
+    /// Dispute to rule on, from `dispute open`
+    dispute: PathBuf,
+
+    /// Arbiter's Ed25519 key (PKCS#8) the ruling is signed with
+    #[arg(long)]
+    arbiter_key: PathBuf,
+
+    /// Hex public key of a verifier whose decisions may be disputed (repeatable)
+    #[arg(long = "trust-verifier", required = true)]
+    trusted_verifiers: Vec<String>,
+
+    /// Trust the image IDs in this allowlist instead of this build's guest
+    #[arg(long)]
+    allowlist: Option<PathBuf>,
+
+    /// Accept dev-mode receipts (test deployments only)
+    #[arg(long)]
+    allow_dev_mode: bool,
+
+    /// Proof store whose audit log records the ruling
+    #[arg(long, default_value = ".zaik/proofs")]
+    store: PathBuf,
+
+    /// Where to write the ruling (default: next to the dispute, with `.ruling.json`)
+    #[arg(long)]
+    out: Option<PathBuf>,
+}
+
+#[derive(Debug, Subcommand)]
+enum AuditLogCommand {
+    /// Check the log's hash chain and print its head and Merkle root
+    Verify {
+        /// Proof store holding the log
+        #[arg(long, default_value = ".zaik/proofs")]
+        store: PathBuf,
+    },
+    /// Print a Merkle inclusion proof for one entry
+    Prove {
+        /// Sequence number of the entry
+        seq: u64,
+
+        /// Proof store holding the log
+        #[arg(long, default_value = ".zaik/proofs")]
+        store: PathBuf,
+    },
+}
+
+#[derive(Debug, Args)]
+struct AttestExportArgs {
+    /// Proof bundle to attest
+    bundle: PathBuf,
+
+    /// Agent key to sign a DSSE envelope with; the bare statement is written without one
+    #[arg(long)]
+    signing_key: Option<PathBuf>,
+
+    /// Name of the subject, usually the CSV's file name (default: the bundle's key digest)
+    #[arg(long)]
+    subject_name: Option<String>,
+
+    /// Where to write the attestation (default: the bundle path with `.intoto.json`)
+    #[arg(long)]
+    out: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+struct AttestImportArgs {
+    /// Statement or DSSE envelope to check
+    attestation: PathBuf,
+
+    /// Bundle the attestation is about
+    #[arg(long)]
+    bundle: PathBuf,
+
+    /// Hex public key trusted to sign the envelope; repeat to trust several
+    #[arg(long = "signer", value_name = "HEX")]
+    signers: Vec<String>,
+
+    /// Accept a bare statement that carries no signature
+    #[arg(long)]
+    allow_unsigned: bool,
+}
+
+#[derive(Debug, Args)]
+struct DatasetArgs {
+    /// CSV files making up the dataset, in the order they are proven
+    #[arg(required = true)]
+    csv_files: Vec<PathBuf>,
+
+    /// Manifest file to write
+    #[arg(long, default_value = "dataset.toml")]
+    out: PathBuf,
+}
+
+#[derive(Debug, Args)]
+struct ExportKitArgs {
+    /// Bundles to include; a directory includes every bundle in it
+    #[arg(required = true)]
+    bundles: Vec<PathBuf>,
+
+    /// Policy the bundles must have been proven under
+    #[arg(long)]
+    policy: PathBuf,
+
+    /// Pin the image IDs in this allowlist instead of this build's guest
+    #[arg(long)]
+    allowlist: Option<PathBuf>,
+
+    /// Standalone verifier to ship (default: `verify` next to this binary)
+    #[arg(long)]
+    verifier: Option<PathBuf>,
+
+    /// Directory to write the kit to; must not exist
+    #[arg(long, default_value = "zaik-kit")]
+    out: PathBuf,
+}
+
+#[derive(Debug, Args)]
+struct PolicyResolveArgs {
+    /// TOML policy file naming columns by header
+    policy: PathBuf,
+
+    /// CSV file whose header the names refer to
+    csv_file: String,
+
+    /// Field delimiter of the CSV, e.g. ';', '|' or tab
+    #[arg(long, default_value = ",", value_parser = parse_dialect_char)]
+    delimiter: u8,
+
+    /// Quote character enclosing fields that contain the delimiter
+    #[arg(long, default_value = "\"", value_parser = parse_dialect_char)]
+    quote: u8,
+
+    /// Use the closest header for a name that matches none, without asking
+    #[arg(long)]
+    accept_fuzzy_columns: bool,
+
+    /// Write the policy with every column given by index, for commands without the CSV
+    #[arg(long)]
+    out: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+struct PolicyTestArgs {
+    /// Proposed TOML policy file
+    policy: PathBuf,
+
+    /// Directory of proof bundles to evaluate it against
+    #[arg(long, default_value = ".zaik/proofs")]
+    against: PathBuf,
+
+    /// Print the report as JSON
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Debug, Args)]
+struct BudgetArgs {
+    /// Directory of proof bundles whose budget ledger to read
+    #[arg(long, default_value = ".zaik/proofs")]
+    store: PathBuf,
+
+    /// Only this tenant
+    #[arg(long)]
+    tenant: Option<String>,
+
+    /// Only this reporting period
+    #[arg(long)]
+    period: Option<Period>,
+}
+
+#[derive(Debug, Args)]
+struct SloArgs {
+    /// Directory of proof bundles whose proofs to judge
+    #[arg(long, default_value = ".zaik/proofs")]
+    store: PathBuf,
+
+    /// TOML file of objectives; see `zaik::slo`
+    #[arg(long)]
+    objectives: PathBuf,
+
+    /// Print the report as JSON
+    #[arg(long)]
+    json: bool,
+
+    /// Also write the metrics in Prometheus text format to this file
+    #[arg(long, value_name = "FILE")]
+    prometheus: Option<PathBuf>,
+
+    /// Alert sinks to notify when an objective is breached
+    #[arg(long, value_name = "FILE")]
+    alerts: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+struct CodegenArgs {
+    #[command(flatten)]
+    policy: PolicyArgs,
+
+    /// Directory to write ZaikJournal.sol and ZaikVerifier.sol to
+    #[arg(long, default_value = "contracts")]
+    out_dir: PathBuf,
+
+    /// Image ID the contract accepts (hex); defaults to this build's guest
+    #[arg(long)]
+    image_id: Option<String>,
+}
+
+#[derive(Debug, Args)]
+struct ExpectArgs {
+    /// CSV files the verifier expects proofs for
+    #[arg(required = true)]
+    csv_files: Vec<PathBuf>,
+
+    /// Expected-hash file to create or extend
+    #[arg(long, default_value = "expected.toml")]
+    out: PathBuf,
+
+    /// Note recorded with each hash
+    #[arg(long)]
+    note: Option<String>,
+
+    /// Column hash mode the proofs will use (`chunked` changes the CSV hash)
+    #[arg(long, default_value = "joined")]
+    column_hash: ColumnHashMode,
+
+    /// The files are dataset manifests; expect their roots
+    #[arg(long, conflicts_with = "column_hash")]
+    dataset: bool,
+}
+
+#[derive(Debug, Args)]
+struct ReplayArgs {
+    /// Bundle to replay
+    bundle: PathBuf,
+
+    /// The CSV the bundle was proven over
+    #[arg(long)]
+    csv: PathBuf,
+
+    /// Run the host build of the guest logic instead of the executor
+    #[arg(long)]
+    native: bool,
+}
+
+#[derive(Debug, Args)]
+struct MigrateArgs {
+    /// Bundle written by an earlier release
+    bundle: PathBuf,
+
+    /// Where to write the upgraded bundle [default: <key digest>.bundle next to the input]
+    #[arg(long)]
+    out: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+struct HealthArgs {
+    /// Directory of proof bundles
+    #[arg(long, default_value = ".zaik/proofs")]
+    store: PathBuf,
+
+    /// TOML policy file the service runs with
+    #[arg(long)]
+    policy: Option<PathBuf>,
+
+    /// Prover key the service signs bundles with
+    #[arg(long)]
+    prover_key: Option<PathBuf>,
+
+    /// Verifier key the service signs decisions with
+    #[arg(long)]
+    verifier_key: Option<PathBuf>,
+
+    /// Print the report as JSON
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Debug, Args)]
+struct VerifyChainArgs {
+    /// Bundle file; its .sig, .decision.json and .decision.json.sig are read alongside
+    bundle: PathBuf,
+
+    /// Accepted prover public key (hex); repeat to allow several
+    #[arg(long = "trust-prover", required = true)]
+    trusted_provers: Vec<String>,
+
+    /// Accepted verifier public key (hex); repeat to allow several
+    #[arg(long = "trust-verifier", required = true)]
+    trusted_verifiers: Vec<String>,
+
+    /// PEM or DER certificate of the timestamp authority whose signature a timestamp must carry
+    #[arg(long, value_name = "PATH")]
+    tsa_cert: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+struct VerifyAllArgs {
+    /// Directory of proof bundles
+    dir: PathBuf,
+
+    /// TOML policy the bundles must have been proven under
+    #[arg(long)]
+    policy: PathBuf,
+
+    /// Write the full report as JSON to this file
+    #[arg(long)]
+    report: Option<PathBuf>,
+
+    /// Trust the image IDs in this allowlist instead of this build's guest
+    #[arg(long)]
+    allowlist: Option<PathBuf>,
+
+    /// Accept dev-mode receipts (test deployments only)
+    #[arg(long)]
+    allow_dev_mode: bool,
+
+    /// Bundles verified at once [default: available cores]
+    #[arg(long)]
+    jobs: Option<usize>,
+}
+
+#[derive(Debug, Args)]
+struct PolicyArgs {
+    /// Business invariant: column A sum must be <= this value
+    #[arg(long, default_value_t = 1000)]
+    threshold: u64,
+
+    /// Compare the sum with --threshold using le (<=), lt (<), ge (>=) or gt (>)
+    #[arg(long, default_value = "le", conflicts_with = "policy")]
+    comparison: ComparisonOp,
+
+    /// TOML policy file to use instead of --threshold
+    #[arg(long, conflicts_with = "threshold")]
+    policy: Option<PathBuf>,
+
+    /// Use the closest header for a policy column name that matches none, without asking
+    #[arg(long, requires = "policy")]
+    accept_fuzzy_columns: bool,
+}
+
+impl PolicyArgs {
+    fn load(&self) -> Result<Policy, FailureReason> {
+        match &self.policy {
+            Some(path) => Policy::load(path)
+                .map_err(|e| FailureReason::new(Outcome::Io, "policy_unreadable", e.to_string())),
+            None => Ok(Policy {
+                comparison: self.comparison,
+                ..Policy::new(self.threshold)
+            }),
+        }
+    }
+
+    /// Load the policy, resolving columns it names against `csv_file`'s
+    /// header, or with `dataset` the header of the manifest's files.
+    fn load_for(&self, csv_file: &str, dataset: bool, dialect: &Dialect) -> Result<Policy, FailureReason> {
+        match &self.policy {
+            Some(path) => resolve_policy(path, csv_file, dataset, dialect, self.accept_fuzzy_columns)
+                .map(|(policy, _)| policy),
+            None => self.load(),
+        }
+    }
+}
+
+/// Load `path`, resolving named columns against `csv_file`'s header and
+/// confirming fuzzy matches unless `accept_fuzzy` is set.
+fn resolve_policy(
+    path: &Path,
+    csv_file: &str,
+    dataset: bool,
+    dialect: &Dialect,
+    accept_fuzzy: bool,
+) -> Result<(Policy, Vec<ColumnMatch>), FailureReason> {
+    let header = || columns::header(&read_csv_data(csv_file, dataset, dialect)?, dialect);
+    let (policy, matches) = Policy::load_resolving(path, header)
+        .map_err(|e| FailureReason::new(Outcome::Io, "policy_unreadable", e.to_string()))?;
+    for found in &matches {
+        if found.kind == MatchKind::Fuzzy && !accept_fuzzy && !confirm_column(found) {
+            return Err(FailureReason::new(
+                Outcome::Io,
+                "column_unconfirmed",
+                format!(
+                    "column '{}' only resembles column {} '{}'; fix the name, or pass --accept-fuzzy-columns to use it",
+                    found.name, found.index, found.header
+                ),
+            ));
+        }
+        println!("🧭 Column {}", found);
+    }
+    Ok((policy, matches))
+}
+
+/// Ask on the terminal whether to use a fuzzy column match; no when stdin
+/// is not a terminal.
+fn confirm_column(found: &ColumnMatch) -> bool {
+    if !std::io::stdin().is_terminal() {
+        return false;
+    }
+    eprint!(
+        "❓ No header is named '{}'; use column {} '{}'? [y/N] ",
+        found.name, found.index, found.header
+    );
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer).is_ok() && matches!(answer.trim(), "y" | "Y" | "yes")
+}
+
+#[derive(Debug, Args)]
+struct ProcessingArgs {
+    /// How the guest hashes column A: joined, transcript (constant memory), raw (source bytes)
+    /// or chunked (proven in reusable chunks)
+    #[arg(long, default_value = "joined")]
+    column_hash: ColumnHashMode,
+
+    /// Keep column A's sum out of the public journal, committing to it instead
+    #[arg(long)]
+    conceal_sum: bool,
+
+    /// Hand the guest a binary side table, bound into the journal (repeatable)
+    #[arg(long = "aux-table", value_name = "NAME=PATH", value_parser = parse_aux_table)]
+    aux_tables: Vec<(String, PathBuf)>,
+
+    /// Field delimiter the guest splits rows on, e.g. ';', '|' or tab
+    #[arg(long, default_value = ",", value_parser = parse_dialect_char)]
+    delimiter: u8,
+
+    /// Quote character enclosing fields that contain the delimiter
+    #[arg(long, default_value = "\"", value_parser = parse_dialect_char)]
+    quote: u8,
+
+    /// The first line is data, not a header
+    #[arg(long)]
+    no_header: bool,
+}
+
+/// One ASCII character, or `tab` (or `\t`) for a tab.
+fn parse_dialect_char(value: &str) -> Result<u8, String> {
+    match value {
+        "tab" | "\\t" | "\t" => Ok(b'\t'),
+        _ if value.len() == 1 && value.is_ascii() => Ok(value.as_bytes()[0]),
+        _ => Err(format!("invalid dialect character '{}': expected one ASCII character or 'tab'", value)),
+    }
+}
+
+/// A fraction in [0, 1] as basis points, rounded to the nearest.
+fn parse_accuracy(value: &str) -> Result<u32, String> {
+    match value.parse::<f64>() {
+        Ok(fraction) if (0.0..=1.0).contains(&fraction) => Ok((fraction * 10_000.0).round() as u32),
+        _ => Err(format!("invalid accuracy '{}': expected a fraction between 0 and 1", value)),
+    }
+}
+
+fn parse_aux_table(value: &str) -> Result<(String, PathBuf), String> {
+    match value.split_once('=') {
+        Some((name, path)) if !name.is_empty() && !path.is_empty() => Ok((name.to_string(), PathBuf::from(path))),
+        _ => Err(format!("invalid aux table '{}': expected NAME=PATH", value)),
+    }
+}
+
+impl ProcessingArgs {
+    fn options(&self) -> ProcessingOptions {
+        ProcessingOptions {
+            column_hash: self.column_hash,
+            conceal_sum: self.conceal_sum,
+            dialect: Dialect {
+                delimiter: self.delimiter,
+                quote: self.quote,
+                has_header: !self.no_header,
+            },
+        }
+    }
+
+    /// The aux tables, read from disk in the order given.
+    fn aux_tables(&self) -> Result<Vec<AuxTable>, Box<dyn std::error::Error>> {
+        self.aux_tables
+            .iter()
+            .map(|(name, path)| {
+                let data = fs::read(path).map_err(|e| format!("aux table {}: {}", path.display(), e))?;
+                Ok(AuxTable { name: name.clone(), data })
+            })
+            .collect()
+    }
+
+    /// The dialect must be one the guest can parse. Chunked journals carry
+    /// no row root, extra invariants, inline data, aux tables or row limit,
+    /// and only read the default dialect.
+    fn check_supported(&self, policy: &Policy, opens_rows: bool) -> Result<(), FailureReason> {
+        let dialect = self.options().dialect;
+        dialect
+            .check()
+            .map_err(|e| FailureReason::new(Outcome::Io, "dialect_invalid", e))?;
+        if self.column_hash != ColumnHashMode::Chunked {
+            return Ok(());
+        }
+        let unsupported = if !policy.proven_invariants().is_empty() {
+            "policies with extra invariants"
+        } else if policy.inline_limit() > 0 {
+            "inline data"
+        } else if !self.aux_tables.is_empty() {
+            "--aux-table"
+        } else if policy.row_limit() > 0 {
+            "max_rows"
+        } else if dialect != Dialect::CSV {
+            "--delimiter, --quote or --no-header"
+        } else if opens_rows {
+            "--audit-rows or --escrow rows:N"
+        } else {
+            return Ok(());
+        };
+        Err(FailureReason::new(
+            Outcome::Io,
+            "chunked_unsupported",
+            format!("--column-hash chunked does not support {}", unsupported),
+        ))
+    }
+}
+
+/// Guest input for `csv_data`. A concealed sum gets a random salt so its
+/// commitment cannot be brute-forced; a disclosed one needs no blinding.
+fn guest_input(
+    csv_data: String,
+    options: ProcessingOptions,
+    policy: &Policy,
+    aux_tables: Vec<AuxTable>,
+) -> CsvProcessingInput {
+    CsvProcessingInput {
+        sum_bound: policy.sum_bound(),
+        invariants: policy.proven_invariants(),
+        sum_salt: if options.conceal_sum { rand::random() } else { [0u8; 32] },
+        inline_limit: policy.inline_limit(),
+        aux_tables,
+        max_rows: policy.row_limit(),
+        ..CsvProcessingInput::from_data(csv_data, options)
+    }
+}
+
+#[derive(Debug, Args)]
+struct ProveArgs {
+    /// CSV file to process
+    #[arg(default_value = "test_data.csv")]
+    csv_file: String,
+
+    #[command(flatten)]
+    proving: ProvingArgs,
+
+    /// TOML file of CSV hashes the verifier expects; any other CSV is refused
+    #[arg(long)]
+    expect: Option<PathBuf>,
+
+    /// Sign the bundle with this prover key (detached .sig next to the bundle)
+    #[arg(long)]
+    prover_key: Option<PathBuf>,
+
+    /// Record the decision next to the bundle, signed with this verifier key
+    #[arg(long)]
+    verifier_key: Option<PathBuf>,
+
+    /// Timestamp the bundle with this RFC 3161 authority (.tst next to the bundle)
+    #[arg(long, value_name = "URL")]
+    tsa: Option<String>,
+
+    /// POST each decision to this URL, signed with HMAC-SHA256 if ZAIK_WEBHOOK_SECRET is set
+    #[arg(long, value_name = "URL")]
+    webhook: Option<String>,
+
+    /// Alert sinks (PagerDuty, SMTP) for outcomes the policy's [alerts] table rates
+    #[arg(long, value_name = "FILE")]
+    alerts: Option<PathBuf>,
+
+    /// Export the run's spans to this OpenTelemetry collector (default: $OTEL_EXPORTER_OTLP_ENDPOINT)
+    #[arg(long, value_name = "URL")]
+    otlp: Option<String>,
+
+    /// Delivery attempts before a notification is dead-lettered
+    #[arg(long, value_name = "N", default_value_t = 5)]
+    webhook_attempts: u32,
+
+    /// Spot-check this many rows against the committed row root
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    audit_rows: usize,
+
+    /// Tenant whose budget the proof counts against, with a policy [budget]
+    #[arg(long, default_value = "default")]
+    tenant: String,
+
+    /// Reporting period the proof counts against, YYYY-MM or YYYY-MM-DD; needed with a policy [budget]
+    #[arg(long)]
+    period: Option<Period>,
+
+    /// Accept only once Agent A reveals the CSV (csv) or N sampled rows (rows:N); needs both keys
+    #[arg(long, value_name = "WHAT")]
+    escrow: Option<RevealScope>,
+
+    /// A human reviewed this run, as a policy [assurance] tier may require of high-value sums
+    #[arg(long)]
+    human_reviewed: bool,
+}
+
+#[derive(Debug, Args)]
+struct ProvingArgs {
+    #[command(flatten)]
+    policy: PolicyArgs,
+
+    #[command(flatten)]
+    processing: ProcessingArgs,
+
+    /// Directory of previously generated proof bundles
+    #[arg(long, default_value = ".zaik/proofs")]
+    store: PathBuf,
+
+    /// Prove again even if a matching bundle is already stored
+    #[arg(long)]
+    force: bool,
+
+    /// Append proving progress events to this file as JSON lines
+    #[arg(long)]
+    events: Option<PathBuf>,
+
+    /// Cap each segment at 2^N cycles, bounding prover memory
+    #[arg(long, value_name = "N")]
+    segment_po2: Option<u32>,
+
+    /// Abort if the guest runs for more than this many cycles
+    #[arg(long, value_name = "CYCLES")]
+    max_cycles: Option<u64>,
+
+    /// Give up once the whole run has taken this long, e.g. 90s, 30m or 2h
+    #[arg(long, value_name = "DURATION", value_parser = deadline::parse_duration)]
+    deadline: Option<Duration>,
+
+    /// Wrap the receipt in Groth16 so it can be verified on-chain (needs Docker or Bonsai)
+    #[arg(long)]
+    groth16: bool,
+
+    /// Read each CSV argument as a dataset manifest and prove its files as one CSV
+    #[arg(long)]
+    dataset: bool,
+
+    /// Prove up to N independent receipts at once, such as the chunks of a chunked run
+    #[arg(long, value_name = "N", default_value_t = 1)]
+    jobs: usize,
+}
+
+/// The files of the dataset manifest at `path`, read and checked against
+/// their hashes.
+fn load_dataset(path: &str) -> Result<Vec<DatasetFile>, Box<dyn std::error::Error>> {
+    let path = Path::new(path);
+    let manifest = DatasetManifest::load(path).map_err(|e| format!("dataset manifest {}: {}", path.display(), e))?;
+    manifest.read(path.parent().unwrap_or(Path::new("")))
+}
+
+/// The CSV at `csv_file`, or with `dataset` the files of the manifest at
+/// `csv_file` as the one CSV the guest proves.
+fn read_csv_data(csv_file: &str, dataset: bool, dialect: &Dialect) -> Result<String, Box<dyn std::error::Error>> {
+    if !dataset {
+        return input::read_csv(csv_file);
+    }
+    let files = load_dataset(csv_file)?;
+    Ok(dataset::concat(&files, *dialect).ok_or("the dataset's files do not share the same header")?)
+}
+
+impl ProvingArgs {
+    /// The CSV proven for `csv_file` and the `csv_hash` its journal
+    /// commits; for a dataset, the concatenated files and their root.
+    fn read_input(&self, csv_file: &str) -> Result<(String, [u8; 32]), FailureReason> {
+        let unreadable = |e: Box<dyn std::error::Error>| FailureReason::new(Outcome::Io, "csv_unreadable", e.to_string());
+        if !self.dataset {
+            let csv_data = input::read_csv(csv_file).map_err(unreadable)?;
+            let csv_hash = self.processing.column_hash.csv_hash(&csv_data);
+            return Ok((csv_data, csv_hash));
+        }
+        let files = load_dataset(csv_file).map_err(unreadable)?;
+        let hashes: Vec<[u8; 32]> = files.iter().map(|file| file.hash).collect();
+        let csv_data = dataset::concat(&files, self.processing.options().dialect)
+            .ok_or_else(|| unreadable("the dataset's files do not share the same header".into()))?;
+        Ok((csv_data, dataset::root(&hashes)))
+    }
+
+    /// Datasets are proven whole, so they cannot be chunked, and only
+    /// their concatenation exists to reveal.
+    fn check_dataset(&self, reveals_csv: bool) -> Result<(), FailureReason> {
+        let unsupported = if !self.dataset {
+            return Ok(());
+        } else if self.processing.column_hash == ColumnHashMode::Chunked {
+            "--column-hash chunked"
+        } else if reveals_csv {
+            "--escrow csv"
+        } else {
+            return Ok(());
+        };
+        Err(FailureReason::new(
+            Outcome::Io,
+            "dataset_unsupported",
+            format!("--dataset does not support {}", unsupported),
+        ))
+    }
+
+    fn prover_options(&self) -> ProverOptions {
+        ProverOptions {
+            segment_limit_po2: self.segment_po2,
+            session_limit: self.max_cycles,
+            receipt_kind: self.groth16.then_some(ReceiptKind::Groth16),
+        }
+    }
+
+    /// The run's deadline, counted from now.
+    fn deadline(&self) -> Option<Deadline> {
+        self.deadline.map(Deadline::after)
+    }
+
+    fn open_store(&self) -> Result<ProofStore, FailureReason> {
+        ProofStore::open(&self.store)
+            .map_err(|e| FailureReason::new(Outcome::Io, "store_unavailable", e.to_string()))
+    }
+
+    fn open_events(&self) -> Result<Option<JsonLinesEvents<fs::File>>, FailureReason> {
+        let Some(path) = &self.events else {
+            return Ok(None);
+        };
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| FailureReason::new(Outcome::Io, "events_unwritable", e.to_string()))?;
+        Ok(Some(JsonLinesEvents::new(file)))
+    }
+}
+
+#[derive(Debug, Args)]
+struct GcArgs {
+    /// Directory of proof bundles
+    #[arg(long, default_value = ".zaik/proofs")]
+    store: PathBuf,
+
+    /// Keep bundles modified within this many days
+    #[arg(long, default_value_t = 30)]
+    keep_days: u64,
+
+    /// Move expired bundles here (cold storage) instead of deleting them
+    #[arg(long)]
+    archive: Option<PathBuf>,
+
+    /// Report what would happen without touching any file
+    #[arg(long)]
+    dry_run: bool,
+}
+
+#[derive(Debug, Args)]
+struct BackfillArgs {
+    /// First period, YYYY-MM or YYYY-MM-DD
+    #[arg(long)]
+    from: Period,
+
+    /// Last period (inclusive), same granularity as --from
+    #[arg(long)]
+    to: Period,
+
+    /// File path with `{date}` standing for each period, e.g. exports/{date}.csv
+    #[arg(long)]
+    pattern: String,
+
+    /// Write the consolidated report to this file as JSON
+    #[arg(long)]
+    report: Option<PathBuf>,
+
+    #[command(flatten)]
+    proving: ProvingArgs,
+}
+
+impl BackfillArgs {
+    fn period_file(&self, period: &Period) -> String {
+        self.pattern.replace("{date}", &period.to_string())
+    }
+}
+
+#[derive(Debug, Args)]
+struct SimulateArgs {
+    /// CSV file to simulate
+    csv_file: String,
+
+    #[command(flatten)]
+    policy: PolicyArgs,
+
+    #[command(flatten)]
+    processing: ProcessingArgs,
+
+    /// Print the simulated journal as JSON
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Debug)]
+struct VerificationResult {
+    result: AgentResult,
+    column_a_sum: u64,
+    /// Why [`RelyingParty::accept`] refused the bundle, if it did.
+    rejection: Option<Rejection>,
+    proof_kind: ProofKind,
+    assurance_tier: AssuranceTier,
+    verification_passed: bool,
+    business_invariant_passed: bool,
+    policy_outcome: PolicyOutcome,
+    sum_threshold: u64,
+    /// Every check above as it ran, for the transcript.
+    steps: Vec<Step>,
+}
+
+struct AgentA;
+struct AgentB;
+
+impl AgentA {
+    fn process_csv(
+        csv_file_path: &str,
+        policy: &Policy,
+        args: &ProvingArgs,
+        store: &ProofStore,
+        events: Option<&(dyn ProverEvents + Sync)>,
+        deadline: Option<Deadline>,
+    ) -> Result<ProofBundle, Box<dyn std::error::Error>> {
+        let options = args.processing.options();
+        println!("🤖 Agent A: Processing CSV file: {}", csv_file_path);
+        
+        // Read CSV file (or every file of a dataset) and side tables
+        let (csv_data, compressed, files) = if args.dataset {
+            let files = load_dataset(csv_file_path)?;
+            println!("🗂️  Dataset of {} file(s)", files.len());
+            let csv_data = dataset::concat(&files, options.dialect)
+                .ok_or("the dataset's files do not share the same header")?;
+            (csv_data, None, Some(files))
+        } else {
+            let (csv_data, compressed) = input::load(csv_file_path)?;
+            (csv_data, compressed, None)
+        };
+        if let Some(compressed) = &compressed {
+            println!("🗜️  Decompressed {} input: {}", compressed.compression, compressed.compressed_sha256);
+        }
+        let aux_tables = args.processing.aux_tables()?;
+        let aux_hash = zaik_core::aux_tables_hash(&aux_tables);
+        
+        // Compute CSV hash (of the chunk manifest when proving in chunks,
+        // the root over the files for a dataset)
+        let csv_hash = match &files {
+            Some(files) => dataset::root(&files.iter().map(|file| file.hash).collect::<Vec<_>>()),
+            None => options.column_hash.csv_hash(&csv_data),
+        };
+        
+        println!("📊 CSV hash: {:?}", hex::encode(csv_hash));
+        
+        // Reuse an existing bundle proven over the same inputs
+        let key = ProofKey {
+            csv_hash,
+            image_id: GUEST_CODE_FOR_ZK_PROOF_ID,
+            policy_hash: policy.hash(),
+            options,
+        };
+        // The key does not cover aux tables; the journal does
+        if !args.force {
+            if let Some(bundle) = store.get(&key)? {
+                let journal_aux_hash = journal::decode::<AgentResult>(&bundle.receipt.journal.bytes)
+                    .ok()
+                    .map(|j| j.aux_tables_hash);
+                let groth16 = matches!(bundle.receipt.inner, InnerReceipt::Groth16(_));
+                if bundle.proof_kind == ProofKind::current()
+                    && journal_aux_hash == Some(aux_hash)
+                    && (groth16 || !args.groth16)
+                {
+                    println!("♻️  Reusing stored proof bundle {}", key.digest());
+                    return Ok(bundle);
+                }
+            }
+        }
+        
+        // Create input for guest
+        let input = CsvProcessingInput {
+            csv_hash,
+            ..guest_input(csv_data, options, policy, aux_tables)
+        };
+        let sum_opening = SumOpening {
+            sum: zaik_core::aggregate_column_a(
+                zaik_core::limit_rows(&input.csv_data, input.max_rows, options.dialect).0,
+                options.column_hash,
+                options.dialect,
+            )
+            .sum,
+            salt: input.sum_salt,
+        };
+        
+        // Prove changed chunks first; the guest then composes their receipts
+        let started = Instant::now();
+        let (input, assumptions) = if let Some(files) = files {
+            // The files stand in for the concatenation
+            let input = CsvProcessingInput {
+                csv_data: String::new(),
+                ..input
+            };
+            (GuestInput::Dataset(DatasetInput { files, input }), Vec::new())
+        } else if options.column_hash == ColumnHashMode::Chunked {
+            Self::prove_chunks(&input, args, store, events, deadline)?
+        } else {
+            (GuestInput::Csv(input), Vec::new())
+        };
+        
+        // Generate proof, reporting progress as it goes
+        let mut session = ProofSession::new(&input, GUEST_CODE_FOR_ZK_PROOF_ELF)
+            .with_options(args.prover_options())
+            .with_deadline(deadline)
+            .with_assumptions(assumptions)
+            .observe(&print_progress);
+        if let Some(events) = events {
+            session = session.observe(events);
+        }
+        let prove_info = session.prove()?;
+        
+        println!("✅ Proof generated successfully!");
+        
+        let bundle = ProofBundle {
+            key,
+            proof_kind: ProofKind::of(&prove_info.receipt),
+            receipt: prove_info.receipt,
+            sum_opening,
+            metadata: BundleMetadata::new(GUEST_CODE_FOR_ZK_PROOF_ID).with_input(compressed),
+        };
+        let path = store.put(&bundle)?;
+        println!("💾 Stored proof bundle: {}", path.display());
+        
+        let sample = slo::Sample::new(
+            &bundle.metadata.image_id,
+            &bundle.key.digest(),
+            started.elapsed().as_millis() as u64,
+            fs::metadata(&path)?.len(),
+            prove_info.stats.total_cycles,
+        );
+        if let Err(e) = store.slo_log().record(&sample) {
+            println!("⚠️  Proof not logged for SLO tracking: {}", e);
+        }
+        Ok(bundle)
+    }
+
+    /// Prove every chunk of `input` that has no stored receipt, and return
+    /// the composition input with the chunk receipts it verifies.
+    fn prove_chunks(
+        input: &CsvProcessingInput,
+        args: &ProvingArgs,
+        store: &ProofStore,
+        events: Option<&(dyn ProverEvents + Sync)>,
+        deadline: Option<Deadline>,
+    ) -> Result<(GuestInput, Vec<Receipt>), Box<dyn std::error::Error>> {
+        let (header, chunks) = chunk::split(&input.csv_data);
+        let mut found = Vec::with_capacity(chunks.len());
+        let mut missing = Vec::new();
+        for data in &chunks {
+            let chunk_hash: [u8; 32] = Sha256::digest(data.as_bytes()).into();
+            let stored = match store.get_chunk(GUEST_CODE_FOR_ZK_PROOF_ID, &chunk_hash)? {
+                Some(receipt) if !args.force && ProofKind::of(&receipt) == ProofKind::current() => {
+                    let result: ChunkResult = receipt.journal.decode()?;
+                    (result.chunk_hash == chunk_hash).then_some((result, receipt))
+                }
+                _ => None,
+            };
+            if stored.is_none() {
+                missing.push(GuestInput::Chunk(ChunkInput { data: data.to_string() }));
+            }
+            found.push((chunk_hash, stored));
+        }
+        let reused = chunks.len() - missing.len();
+        if missing.len() > 1 && args.jobs > 1 {
+            println!("🧩 Proving {} chunk(s) on up to {} workers", missing.len(), args.jobs);
+        }
+        
+        // Every chunk proven is stored before a failure is reported, so a
+        // retry only proves the chunks that failed
+        let mut proven = session::prove_many(
+            &missing,
+            GUEST_CODE_FOR_ZK_PROOF_ELF,
+            args.prover_options(),
+            deadline,
+            events,
+            args.jobs,
+        )
+        .into_iter();
+        let mut results = Vec::with_capacity(chunks.len());
+        let mut receipts = Vec::with_capacity(chunks.len());
+        let mut failure = None;
+        for (chunk_hash, stored) in found {
+            let (result, receipt) = match stored {
+                Some(stored) => stored,
+                None => match proven.next().expect("one result per missing chunk") {
+                    Ok(prove_info) => {
+                        store.put_chunk(GUEST_CODE_FOR_ZK_PROOF_ID, &chunk_hash, &prove_info.receipt)?;
+                        (prove_info.receipt.journal.decode()?, prove_info.receipt)
+                    }
+                    Err(e) => {
+                        failure.get_or_insert(e);
+                        continue;
+                    }
+                },
+            };
+            results.push(result);
+            receipts.push(receipt);
+        }
+        if let Some(e) = failure {
+            return Err(e.into());
+        }
+        println!("🧩 {} chunk(s): {} reused, {} proven", chunks.len(), reused, chunks.len() - reused);
+        
+        let composition = ChunkedInput {
+            header: header.to_string(),
+            chunk_image_id: GUEST_CODE_FOR_ZK_PROOF_ID,
+            chunks: results,
+            options: input.options,
+            sum_bound: input.sum_bound,
+            sum_salt: input.sum_salt,
+        };
+        Ok((GuestInput::Chunked(composition), receipts))
+    }
+}
+
+fn print_progress(event: &ProverEvent) {
+    match event {
+        ProverEvent::ExecutionStarted => println!("⚙️  Executing guest..."),
+        ProverEvent::SegmentExecuted { index, po2, user_cycles } => {
+            println!("  - Segment {}: {} user cycles (2^{})", index, user_cycles, po2)
+        }
+        ProverEvent::ExecutionFinished { segments, user_cycles, elapsed_ms } => println!(
+            "  - Executed {} segment(s), {} user cycles in {} ms",
+            segments, user_cycles, elapsed_ms
+        ),
+        ProverEvent::ProvingStarted { segments } => {
+            println!("⚡ Generating zkVM proof over {} segment(s)...", segments)
+        }
+        ProverEvent::ProvingFinished { total_cycles, paging_cycles, elapsed_ms, .. } => println!(
+            "  - Proved {} total cycles ({} paging) in {} ms",
+            total_cycles, paging_cycles, elapsed_ms
+        ),
+    }
+}
+
+impl AgentB {
+    /// Decide on `bundle` with [`RelyingParty::accept`], and trace each
+    /// check behind the decision for the report and the transcript.
+    fn verify_and_check_invariant(
+        bundle: &ProofBundle,
+        policy: &Policy,
+        human_reviewed: bool,
+        prover_score: Option<u64>,
+        environment: Option<&RecordedManifest>,
+    ) -> Result<VerificationResult, Box<dyn std::error::Error>> {
+        println!("🔍 Agent B: Verifying receipt and checking business invariant...");
+        let receipt = &bundle.receipt;
+        let sum_opening = &bundle.sum_opening;
+        let image_id = risc0_zkvm::sha::Digest::from(GUEST_CODE_FOR_ZK_PROOF_ID).to_string();
+        let mut steps = Vec::new();
+        
+        // Dev-mode receipts are checked all the same; `decide` reports them as conditional
+        let mut relying_party = RelyingParty::new(policy.clone())
+            .allow_image(GUEST_CODE_FOR_ZK_PROOF_ID)
+            .allow_dev_mode();
+        if human_reviewed {
+            relying_party = relying_party.human_reviewed();
+        }
+        if let Some(score) = prover_score {
+            relying_party = relying_party.prover_score(score);
+        }
+        if let Some(environment) = environment {
+            relying_party = relying_party.environment(environment.clone());
+        }
+        
+        // Verify the receipt, as the relying party does before anything it reads from it
+        let started = Instant::now();
+        let proof_kind = ProofKind::of(receipt);
+        let rejection = relying_party.accept(bundle).err();
+        let verified = match &rejection {
+            Some(
+                rejection @ (Rejection::ImageNotAllowed(_)
+                | Rejection::PolicyMismatch { .. }
+                | Rejection::ReceiptInvalid(_)
+                | Rejection::BackendUnavailable(_)),
+            ) => Err(rejection.to_string()),
+            _ => Ok(()),
+        };
+        let verification_passed = verified.is_ok();
+        println!("🔐 Receipt verification: {}", if verification_passed { "PASSED" } else { "FAILED" });
+        println!("🏷️  Proof kind: {}", proof_kind);
+        steps.push(Step::finished(
+            "receipt",
+            format!("{} receipt against image {}", proof_kind, image_id),
+            verification_passed,
+            match &verified {
+                Ok(()) => "verified".to_string(),
+                Err(e) => e.clone(),
+            },
+            started,
+        ));
+        if let InnerReceipt::Groth16(_) = &receipt.inner {
+            steps.push(Step::finished(
+                "snark",
+                "Groth16 seal",
+                verification_passed,
+                if verification_passed { "seal verified" } else { "seal did not verify" },
+                started,
+            ));
+        }
+        
+        // Extract result from journal
+        let started = Instant::now();
+        let result: AgentResult = journal::decode(&receipt.journal.bytes)?;
+        steps.push(Step::finished(
+            "journal_decode",
+            format!("{} bytes", receipt.journal.bytes.len()),
+            true,
+            format!("format {}, CSV hash {}", JOURNAL_FORMAT, hex::encode(result.csv_hash)),
+            started,
+        ));
+        
+        // Composed chunks must come from this guest too
+        let started = Instant::now();
+        let chunk_image_passed = result.column_hash_mode != ColumnHashMode::Chunked
+            || result.chunk_image_id == GUEST_CODE_FOR_ZK_PROOF_ID;
+        let verification_passed = verification_passed && chunk_image_passed;
+        steps.push(Step::finished(
+            "image_id",
+            image_id.clone(),
+            verified.is_ok() && chunk_image_passed,
+            if result.column_hash_mode == ColumnHashMode::Chunked {
+                format!("chunks proven with {}", risc0_zkvm::sha::Digest::from(result.chunk_image_id))
+            } else {
+                "receipt claims this image".to_string()
+            },
+            started,
+        ));
+        
+        println!("📈 Extracted result:");
+        print_journal(&result);
+        if result.truncated {
+            println!("✂️  Guest stopped after {} rows; the rest of the CSV was not processed", result.max_rows);
+        }
+        
+        // Sums and digests only mean what this build thinks under rules it knows
+        let started = Instant::now();
+        let semantics_supported = SUPPORTED_SEMANTICS.contains(&result.semantics_version);
+        println!("📐 Semantics version {}: {}",
+                result.semantics_version,
+                if semantics_supported { "SUPPORTED" } else { "UNSUPPORTED" });
+        steps.push(Step::finished(
+            "semantics",
+            format!("supported {:?}", SUPPORTED_SEMANTICS),
+            semantics_supported,
+            format!("version {}", result.semantics_version),
+            started,
+        ));
+        
+        // Inlined data must reproduce the journal's digests
+        let started = Instant::now();
+        let verification_passed = match result.inline_matches() {
+            Some(matches) => {
+                println!("📄 Inline CSV ({} bytes) matches journal: {}",
+                        result.inline_csv.0.len(),
+                        if matches { "PASSED" } else { "FAILED" });
+                steps.push(Step::finished(
+                    "inline_data",
+                    format!("{} inlined bytes", result.inline_csv.0.len()),
+                    matches,
+                    if matches { "reproduces the journal" } else { "does not reproduce the journal" },
+                    started,
+                ));
+                verification_passed && matches
+            }
+            None => verification_passed,
+        };
+        
+        // A concealed sum is only known through an opening of its commitment
+        let started = Instant::now();
+        let sum_opening_passed = sum_opening.open(&result).is_some();
+        println!("🔓 Sum opening: {}", if sum_opening_passed { "PASSED" } else { "FAILED" });
+        let column_a_sum = if result.sum_disclosed { result.column_a_sum } else { sum_opening.sum };
+        steps.push(Step::finished(
+            "sum_opening",
+            format!("commitment {}", hex::encode(result.sum_commitment)),
+            sum_opening_passed,
+            if sum_opening_passed { "opening matches" } else { "opening does not match" },
+            started,
+        ));
+        
+        // Check business invariant (sum under threshold)
+        let started = Instant::now();
+        let sum_threshold = policy.sum_threshold;
+        let mut policy_outcome = policy.evaluate(column_a_sum, &result);
+        
+        // High-value sums need more than the receipt
+        let evidence = AssuranceEvidence {
+            groth16: matches!(receipt.inner, InnerReceipt::Groth16(_)),
+            human_reviewed,
+        };
+        let (assurance_tier, assurance_rules) = policy.assurance(column_a_sum, evidence);
+        policy_outcome.rules.extend(assurance_rules);
+        if policy.assurance.is_some() {
+            println!("🏅 Assurance tier: {}", assurance_tier);
+        }
+        
+        // Some verifiers only trust proofs from environments they approved
+        policy_outcome.rules.extend(policy.environment(environment, &image_id));
+        
+        // Provers with a poor record need a human to sign off
+        if policy.reputation.is_some() {
+            println!("⭐ Prover reputation: {}", prover_score.map_or("unknown".to_string(), |score| score.to_string()));
+        }
+        policy_outcome.rules.extend(policy.reputation(prover_score, human_reviewed));
+        steps.extend(Step::rules(&policy_outcome, started));
+        let business_invariant_passed = policy_outcome.passed();
+        println!("💼 Business invariant (sum {} {}): {}", 
+                policy.comparison.symbol(),
+                sum_threshold, 
+                if business_invariant_passed { "PASSED" } else { "FAILED" });
+        print_policy_trace(&policy_outcome);
+        
+        // The guest checked the same bound and invariants, and agreed on the sum
+        let started = Instant::now();
+        let bound_consistent = policy.matches_journal(&result, policy.sum_bound().holds(column_a_sum));
+        println!("🧮 Guest bound and invariant checks match policy: {}", if bound_consistent { "PASSED" } else { "FAILED" });
+        steps.push(Step::finished(
+            "guest_settings",
+            format!("policy {}", policy.version()),
+            bound_consistent,
+            format!(
+                "guest checked sum {} {} and {} invariant(s)",
+                result.sum_comparison.symbol(),
+                result.sum_threshold,
+                result.invariants.len()
+            ),
+            started,
+        ));
+        
+        Ok(VerificationResult {
+            result,
+            column_a_sum,
+            rejection,
+            proof_kind,
+            assurance_tier,
+            verification_passed,
+            business_invariant_passed,
+            policy_outcome,
+            sum_threshold,
+            steps,
+        })
+    }
+}
+
+/// Report who produced a bundle and anything that differs from this build.
+fn print_metadata(metadata: &BundleMetadata) {
+    println!("  - Host {}, risc0-zkvm {}", metadata.host_version, metadata.risc0_version);
+    println!("  - Guest image: {}", metadata.image_id);
+    println!("  - Toolchain: {}", metadata.toolchain);
+    println!("  - Build fingerprint: {}", metadata.fingerprint);
+    if let Some(input) = &metadata.input {
+        println!("  - Input: {} file {}, CSV {}", input.compression, input.compressed_sha256, input.csv_sha256);
+    }
+    for note in metadata.compatibility(&BundleMetadata::new(GUEST_CODE_FOR_ZK_PROOF_ID)) {
+        println!("  - ⚠️  {}", note);
+    }
+}
+
+fn print_journal<J: JournalSchema>(result: &J) {
+    for line in journal::lines(result) {
+        println!("  - {}", line);
+    }
+}
+
+fn print_policy_trace(outcome: &PolicyOutcome) {
+    println!("🧾 Policy trace:");
+    for line in explain::trace(outcome) {
+        println!("  - {}", line);
+    }
+}
+
+fn main() {
+    // Initialize tracing
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::filter::EnvFilter::from_default_env())
+        .init();
+    
+    let cli = Cli::parse();
+    let result = match &cli.command {
+        Some(Command::Simulate(args)) => simulate(args),
+        Some(Command::Schema { golden: false }) => {
+            print!("{}", journal::markdown::<AgentResult>());
+            Ok(())
+        }
+        Some(Command::Schema { golden: true }) => {
+            println!("{}", serde_json::to_string_pretty(&journal::golden()).expect("JSON values always serialize"));
+            Ok(())
+        }
+        Some(Command::Backfill(args)) => backfill(args),
+        Some(Command::Gc(args)) => gc(args),
+        Some(Command::Keygen { out }) => keygen(out),
+        Some(Command::VerifyChain(args)) => verify_chain(args),
+        Some(Command::VerifyAll(args)) => verify_all(args),
+        Some(Command::Replay(args)) => replay(args),
+        Some(Command::Migrate(args)) => migrate(args),
+        Some(Command::Health(args)) => health(args),
+        Some(Command::Expect(args)) => expect(args),
+        Some(Command::Codegen(args)) => codegen(args),
+        Some(Command::Budget(args)) => budget(args),
+        Some(Command::Slo(args)) => slo(args),
+        Some(Command::WebhookRetry { store }) => webhook_retry(store),
+        Some(Command::Dataset(args)) => create_dataset(args),
+        Some(Command::ExportKit(args)) => export_kit(args),
+        Some(Command::Attest { command: AttestCommand::Export(args) }) => attest_export(args),
+        Some(Command::Attest { command: AttestCommand::Import(args) }) => attest_import(args),
+        Some(Command::Bond { command: BondCommand::Post(args) }) => bond_post(args),
+        Some(Command::Bond { command: BondCommand::Evidence(args) }) => bond_evidence(args),
+        Some(Command::Bond { command: BondCommand::Slash(args) }) => bond_slash(args),
+        Some(Command::Bond { command: BondCommand::Show { store } }) => bond_show(store),
+        Some(Command::Dispute { command: DisputeCommand::Open(args) }) => dispute_open(args),
+        Some(Command::Dispute { command: DisputeCommand::Rule(args) }) => dispute_rule(args),
+        Some(Command::AuditLog { command: AuditLogCommand::Verify { store } }) => audit_log_verify(store),
+        Some(Command::AuditLog { command: AuditLogCommand::Prove { seq, store } }) => audit_log_prove(*seq, store),
+        Some(Command::Environment { json }) => {
+            print_environment(*json);
+            Ok(())
+        }
+        Some(Command::Policy { command: PolicyCommand::Test(args) }) => policy_test(args),
+        Some(Command::Policy { command: PolicyCommand::Resolve(args) }) => policy_resolve(args),
+        Some(Command::Evaluate { command: EvaluateCommand::Prove(args) }) => evaluate_prove(args),
+        Some(Command::Evaluate { command: EvaluateCommand::Verify(args) }) => evaluate_verify(args),
+        Some(Command::Window { command: WindowCommand::Append(args) }) => window_append(args),
+        Some(Command::Window { command: WindowCommand::Verify(args) }) => window_verify(args),
+        Some(Command::Transform { command: TransformCommand::Prove(args) }) => transform_prove(args),
+        Some(Command::Transform { command: TransformCommand::Verify(args) }) => transform_verify(args),
+        Some(Command::Transform { command: TransformCommand::Migrate(args) }) => transform_migrate(args),
+        Some(Command::Transform { command: TransformCommand::VerifyMigration(args) }) => transform_verify_migration(args),
+        None => run(&cli.prove),
+    };
+    
+    if let Err(failure) = result {
+        eprintln!("{}", failure.to_json());
+        std::process::exit(failure.exit_code);
+    }
+}
+
+fn run(args: &ProveArgs) -> Result<(), FailureReason> {
+    println!("🚀 Starting RISC Zero CSV Processing Demo");
+    println!("==========================================");
+    
+    // Configuration
+    let policy = args.proving.policy.load_for(
+        &args.csv_file,
+        args.proving.dataset,
+        &args.proving.processing.options().dialect,
+    )?;
+    let opens_rows = args.audit_rows > 0 || matches!(args.escrow, Some(RevealScope::Rows { .. }));
+    args.proving.processing.check_supported(&policy, opens_rows)?;
+    args.proving.check_dataset(matches!(args.escrow, Some(RevealScope::Csv)))?;
+    if policy.budget.is_some() && args.period.is_none() {
+        return Err(FailureReason::new(
+            Outcome::Io,
+            "budget_period_missing",
+            "the policy sets a [budget]; pass --period to say which reporting period the proof counts against",
+        ));
+    }
+    if args.escrow.is_some() && (args.prover_key.is_none() || args.verifier_key.is_none()) {
+        return Err(FailureReason::new(
+            Outcome::Io,
+            "escrow_unsigned",
+            "--escrow needs --prover-key and --verifier-key to sign the bundle and the reveal request",
+        ));
+    }
+    
+    let sinks = args
+        .alerts
+        .as_deref()
+        .map(AlertSinks::load)
+        .transpose()
+        .map_err(|e| FailureReason::new(Outcome::Io, "alert_sinks_invalid", e.to_string()))?;
+
+    let tracer = Tracer::new("zaik-host");
+    let mut span = tracer.span("zaik.run");
+    span.attribute("zaik.policy.version", policy.version());
+    let result = run_with_policy(args, &policy, &span).map_err(|failure| failure.with_policy_version(policy.version()));
+    if let (Err(failure), Some(sinks)) = (&result, &sinks) {
+        raise_alert(args, &policy, sinks, failure);
+    }
+    match &result {
+        Ok(()) => span.ok(),
+        Err(failure) => {
+            span.attribute("zaik.reason", &failure.reason);
+            span.attribute("zaik.exit_code", failure.exit_code);
+            span.fail(failure.message.clone());
+        }
+    }
+    if let Some(endpoint) = telemetry::endpoint(args.otlp.as_deref()) {
+        export_trace(&tracer, &endpoint);
+    }
+    result
+}
+
+/// Send the run's spans to the collector; a collector that is down costs
+/// the trace, not the run.
+fn export_trace(tracer: &Tracer, endpoint: &str) {
+    match tracer.export(endpoint) {
+        Ok(0) => {}
+        Ok(spans) => println!(
+            "🔭 Trace {} exported to {} ({} spans)",
+            tracer.trace_id().unwrap_or_default(),
+            endpoint,
+            spans
+        ),
+        Err(e) => println!("⚠️  Trace export to {} failed: {}", endpoint, e),
+    }
+}
+
+/// Page whoever the sinks name for the failure's severity. Delivery
+/// problems are reported but never change the outcome.
+fn raise_alert(args: &ProveArgs, policy: &Policy, sinks: &AlertSinks, failure: &FailureReason) {
+    let Some(severity) = policy.alerts.as_ref().and_then(|alerts| alerts.severity(failure.outcome)) else {
+        return;
+    };
+    let csv_hash = args
+        .proving
+        .read_input(&args.csv_file)
+        .map(|(_, csv_hash)| hex::encode(csv_hash))
+        .unwrap_or_default();
+    let alert = Alert {
+        severity,
+        outcome: failure.outcome,
+        reason: failure.reason.clone(),
+        message: failure.message.clone(),
+        csv_hash,
+        source: args.csv_file.clone(),
+        policy_version: policy.version(),
+    };
+    for delivery in sinks.dispatch(&alert) {
+        match delivery.result {
+            Ok(()) => println!("🚨 {} alert sent to {}", severity, delivery.sink),
+            Err(e) => println!("⚠️  {} alert to {} failed: {}", severity, delivery.sink, e),
+        }
+    }
+}
+
+fn run_with_policy(args: &ProveArgs, policy: &Policy, span: &Span) -> Result<(), FailureReason> {
+    let deadline = args.proving.deadline();
+    let store = args.proving.open_store()?;
+    let events = args.proving.open_events()?;
+    
+    // Only data the verifier asked for is proven
+    let expected = args
+        .expect
+        .as_deref()
+        .map(ExpectedHashes::load)
+        .transpose()
+        .map_err(|e| FailureReason::new(Outcome::Io, "expected_hashes_unreadable", e.to_string()))?;
+    if let Some(expected) = &expected {
+        let (_, csv_hash) = args.proving.read_input(&args.csv_file)?;
+        expected.check(&csv_hash, "preflight").map_err(|unexpected| unexpected.to_failure())?;
+        println!("📌 CSV hash is among the {} expected hash(es)", expected.datasets.len());
+    }
+    
+    // Disclosure limits are checked before anything is proven
+    let release = check_privacy(&args.csv_file, policy, &args.proving, &store)?;
+    
+    // Agent A: Process CSV and generate proof (or reuse a stored one)
+    let prove = span.child("prove");
+    let bundle = AgentA::process_csv(
+        &args.csv_file,
+        policy,
+        &args.proving,
+        &store,
+        events.as_ref().map(|e| e as &(dyn ProverEvents + Sync)),
+        deadline,
+    )
+    .map_err(proving_failure)?;
+    span.tracer().bind_bundle(&bundle.key.digest());
+    prove.ok();
+    
+    println!("\n📋 Receipt Summary:");
+    println!("  - Receipt generated successfully");
+    print_metadata(&bundle.metadata);
+    
+    // The bundle is only handed over if its journal commits an expected hash
+    if let Some(expected) = &expected {
+        let journal: AgentResult = journal::decode(&bundle.receipt.journal.bytes)
+            .map_err(|e| FailureReason::new(Outcome::CryptoFailure, "journal_decode_failed", e.to_string()))?;
+        expected
+            .check(&journal.csv_hash, "produced receipt")
+            .map_err(|unexpected| unexpected.to_failure())?;
+    }
+    
+    // Hand-over: what Agent B receives is the stored, signed bundle
+    let mut transfer = span.child("transfer");
+    let bundle_path = store.path(&bundle.key);
+    if let Some(key) = &args.prover_key {
+        sign_file(key, SignerRole::Prover, &bundle_path)?;
+    }
+    if let Some(url) = &args.tsa {
+        timestamp_file(url, &bundle_path)?;
+    }
+    let environment = record_environment(args.prover_key.as_deref(), &bundle_path)?;
+    transfer.attribute("zaik.signed", args.prover_key.is_some());
+    transfer.attribute("zaik.timestamped", args.tsa.is_some());
+    transfer.ok();
+    
+    // A proof that finished in time is kept, so a rerun only has to verify
+    let check_deadline = |stage: &str| match deadline {
+        Some(deadline) => deadline.check(stage).map_err(|exceeded| {
+            let mut failure = exceeded.to_failure();
+            failure.message = format!("{}; proof bundle kept at {}", failure.message, bundle_path.display());
+            failure
+        }),
+        None => Ok(()),
+    };
+    let mut verify = span.child("verify");
+    check_deadline("verification")?;
+    
+    // Agent B: Verify receipt and check business invariant
+    let prover_score = match (&policy.reputation, &args.prover_key) {
+        (Some(_), Some(key)) => {
+            let prover = AgentKey::load(key).map_err(signing_failure)?.public_key();
+            let stats = reputation::of(&store, &prover)
+                .map_err(|e| FailureReason::new(Outcome::Io, "store_unreadable", e.to_string()))?;
+            Some(stats.score)
+        }
+        _ => None,
+    };
+    let verification_result = AgentB::verify_and_check_invariant(
+        &bundle,
+        policy,
+        args.human_reviewed,
+        prover_score,
+        Some(&environment),
+    )
+        .map_err(|e| FailureReason::new(Outcome::CryptoFailure, "journal_decode_failed", e.to_string()))?;
+    
+    if args.audit_rows > 0 && verification_result.verification_passed {
+        check_deadline("row_audit")?;
+        let (csv_data, _) = args.proving.read_input(&args.csv_file)?;
+        audit_rows(&csv_data, &bundle.receipt, &verification_result.result, args.audit_rows)?;
+    }
+    
+    let mut decision = decide(&verification_result, policy);
+    verify.attribute("zaik.verification_passed", verification_result.verification_passed);
+    verify.ok();
+    if let (Ok(()), Some(budget), Some(period)) = (&decision, &policy.budget, args.period) {
+        decision = check_budget(&store, budget, &args.tenant, &period, &verification_result);
+    }
+    if let (Ok(()), Some(trend)) = (&decision, &policy.trend) {
+        decision = check_trend(&store, trend, &args.tenant, args.period.as_ref(), &verification_result);
+    }
+    if let (Ok(()), Some(scope)) = (&decision, args.escrow) {
+        decision = escrow(args, &bundle_path, &bundle.receipt, policy, &verification_result, scope);
+    }
+    if let Some(key) = &args.verifier_key {
+        record_decision(key, &bundle_path, policy, &verification_result, &decision, None)?;
+        record_transcript(key, &bundle_path, policy, &verification_result, &decision)?;
+    }
+    if let (Ok(()), Some(release)) = (&decision, &release) {
+        let recorded = store
+            .disclosure_ledger()
+            .record(release)
+            .map_err(|e| FailureReason::new(Outcome::Io, "ledger_unwritable", e.to_string()))?;
+        if recorded {
+            println!("🛡️  Release recorded in the disclosure ledger");
+        }
+    }
+    if let Some(url) = &args.webhook {
+        notify(url, args.webhook_attempts, &store, &bundle_path, policy, &verification_result, &decision);
+    }
+    let journal_digest: [u8; 32] = Sha256::digest(&bundle.receipt.journal.bytes).into();
+    if decision.is_ok() {
+        store
+            .sum_history()
+            .record(
+                &args.tenant,
+                args.period.as_ref(),
+                &hex::encode(verification_result.result.csv_hash),
+                &journal_digest,
+                verification_result.column_a_sum,
+            )
+            .map_err(|e| FailureReason::new(Outcome::Io, "history_unwritable", e.to_string()))?;
+    }
+    if let (Ok(()), Some(_), Some(period)) = (&decision, &policy.budget, args.period) {
+        let recorded = store
+            .budget_ledger()
+            .record(
+                &args.tenant,
+                &period.to_string(),
+                &hex::encode(verification_result.result.csv_hash),
+                &journal_digest,
+                verification_result.column_a_sum,
+                &policy.version(),
+            )
+            .map_err(|e| FailureReason::new(Outcome::Io, "budget_ledger_unwritable", e.to_string()))?;
+        if let Some(entry) = recorded {
+            println!(
+                "💰 Budget checkpoint for {} in {}: total {} ({})",
+                entry.tenant, entry.period, entry.total, entry.checkpoint
+            );
+        }
+    }
+    decision
+}
+
+/// Post the decision to the webhook. Delivery problems are reported but
+/// never change the decision; undeliverable notifications are kept for
+/// `webhook-retry`.
+fn notify(
+    url: &str,
+    attempts: u32,
+    store: &ProofStore,
+    bundle_path: &Path,
+    policy: &Policy,
+    verification_result: &VerificationResult,
+    decision: &Result<(), FailureReason>,
+) {
+    let (outcome, reason, explanation) = match decision {
+        Ok(()) => (Outcome::Accept, None, explain::explain(&verification_result.policy_outcome)),
+        Err(failure) => (failure.outcome, Some(failure.reason.clone()), failure.message.clone()),
+    };
+    let notification = Notification {
+        csv_hash: hex::encode(verification_result.result.csv_hash),
+        bundle: bundle_path.display().to_string(),
+        policy_version: policy.version(),
+        outcome,
+        reason,
+        explanation,
+        decided_at: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs(),
+    };
+    let webhook = Webhook {
+        max_attempts: attempts.max(1),
+        ..Webhook::new(url, webhook_secret(), store.webhook_dead_letters())
+    };
+    match webhook.send(&notification) {
+        Ok(tries) => println!("📨 Decision posted to {} ({} attempt(s))", url, tries),
+        Err(e) => println!("⚠️  {}", e),
+    }
+}
+
+/// The shared webhook secret, from the environment so it stays out of
+/// process listings.
+fn webhook_secret() -> Option<Vec<u8>> {
+    std::env::var("ZAIK_WEBHOOK_SECRET").ok().filter(|secret| !secret.is_empty()).map(String::into_bytes)
+}
+
+/// Reject a proof whose sum would take its tenant's period total over
+/// the policy's budget.
+fn check_budget(
+    store: &ProofStore,
+    budget: &Budget,
+    tenant: &str,
+    period: &Period,
+    verification_result: &VerificationResult,
+) -> Result<(), FailureReason> {
+    let sum = verification_result.column_a_sum;
+    let admitted = store.budget_ledger().admit(
+        budget,
+        tenant,
+        &period.to_string(),
+        &hex::encode(verification_result.result.csv_hash),
+        sum,
+    );
+    match admitted {
+        Ok(standing) => {
+            println!("💰 Budget for {} in {}: {} + {} of {}", tenant, period, standing.total, sum, budget.cap);
+            Ok(())
+        }
+        Err(e @ BudgetError::Exceeded { .. }) => {
+            println!("❌ Budget for {} in {}: {}", tenant, period, e);
+            Err(FailureReason::new(Outcome::PolicyReject, "budget_exceeded", e.to_string()))
+        }
+        Err(e @ BudgetError::Broken { .. }) => {
+            Err(FailureReason::new(Outcome::CryptoFailure, "budget_chain_broken", e.to_string()))
+        }
+        Err(e @ BudgetError::Io(_)) => Err(FailureReason::new(Outcome::Io, "budget_ledger_unreadable", e.to_string())),
+    }
+}
+
+/// Hold a sum that breaks sharply from the tenant's earlier sums for
+/// investigation, even though every rule passed.
+fn check_trend(
+    store: &ProofStore,
+    trend: &Trend,
+    tenant: &str,
+    period: Option<&Period>,
+    verification_result: &VerificationResult,
+) -> Result<(), FailureReason> {
+    let history = store
+        .sum_history()
+        .baseline_sums(tenant, period, &hex::encode(verification_result.result.csv_hash))
+        .map_err(|e| FailureReason::new(Outcome::Io, "history_unreadable", e.to_string()))?;
+    let check = trend.check(verification_result.column_a_sum, &history);
+    if check.anomalous {
+        println!("🔎 Trend for {}: {}; holding for investigation", tenant, check);
+        return Err(FailureReason::new(Outcome::Investigate, "trend_anomaly", check.to_string()));
+    }
+    println!("📈 Trend for {}: {}", tenant, check);
+    Ok(())
+}
+
+/// Check the release a proof of `csv_file` would make against the policy's
+/// privacy budget. Returns the release to record once it is accepted, or
+/// `None` when there is no budget or nothing is disclosed.
+fn check_privacy(
+    csv_file: &str,
+    policy: &Policy,
+    proving: &ProvingArgs,
+    store: &ProofStore,
+) -> Result<Option<Release>, FailureReason> {
+    let Some(budget) = &policy.privacy else {
+        return Ok(None);
+    };
+    if proving.processing.conceal_sum {
+        println!("🛡️  Sum concealed; nothing counts against the privacy budget");
+        return Ok(None);
+    }
+    let (csv_data, csv_hash) = proving.read_input(csv_file)?;
+    let csv_hash = hex::encode(csv_hash);
+    let dialect = proving.processing.options().dialect;
+    let rows = privacy::row_ids(zaik_core::limit_rows(&csv_data, policy.row_limit(), dialect).0, dialect);
+    let prior = store
+        .disclosure_ledger()
+        .releases()
+        .map_err(|e| FailureReason::new(Outcome::Io, "ledger_unreadable", e.to_string()))?;
+    
+    let violations = privacy::assess(budget, &prior, &csv_hash, &rows);
+    println!("🛡️  Privacy budget: {} prior release(s), {} violation(s)", prior.len(), violations.len());
+    for violation in &violations {
+        println!("  - ⚠️  {}", violation);
+    }
+    if let (BudgetAction::Deny, Some(violation)) = (budget.action, violations.first()) {
+        return Err(FailureReason::new(Outcome::PolicyReject, "privacy_budget_exceeded", violation.to_string()));
+    }
+    
+    let mut rows: Vec<String> = rows.into_iter().collect();
+    rows.sort();
+    Ok(Some(Release {
+        csv_hash,
+        policy: policy.version(),
+        recorded_at: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs(),
+        rows,
+    }))
+}
+
+/// Report the final results and turn them into an outcome.
+fn decide(verification_result: &VerificationResult, policy: &Policy) -> Result<(), FailureReason> {
+    println!("\n🎯 Final Results:");
+    println!("==================");
+    println!("✅ zkVM Proof verification: {}", verification_result.verification_passed);
+    println!("🏷️  Proof kind: {}", verification_result.proof_kind);
+    println!("📜 Policy version: {}", policy.version());
+    println!("✅ Business invariant: {}", verification_result.business_invariant_passed);
+    println!("📊 Column A sum: {} (threshold: {})", 
+             verification_result.column_a_sum, 
+             verification_result.sum_threshold);
+    println!("💬 Explanation: {}", explain::explain(&verification_result.policy_outcome));
+    
+    if let Some(rejection) = &verification_result.rejection {
+        println!("❌ FAILURE: Some checks failed!");
+        return Err(rejection.to_failure());
+    }
+    
+    println!("🎉 SUCCESS: All checks passed!");
+    if verification_result.proof_kind.is_cryptographic() {
+        println!("   - ✅ Deterministic execution proven with RISC Zero zkVM");
+    } else {
+        println!("   - ⚠️  Receipt is {}; no cryptographic proof was produced", verification_result.proof_kind);
+    }
+    println!("   - ✅ Business invariant verified within zkVM");
+    println!("   - ✅ CSV processing completed trustlessly");
+    
+    if !verification_result.proof_kind.is_cryptographic() {
+        return Err(Rejection::NotCryptographic(verification_result.proof_kind).to_failure());
+    }
+    
+    Ok(())
+}
+
+/// Agent B samples rows from the receipt digest and checks Agent A's
+/// openings against the journal's row root.
+fn audit_rows(csv_data: &str, receipt: &Receipt, result: &AgentResult, samples: usize) -> Result<(), FailureReason> {
+    let audit_failure = |e: &dyn std::fmt::Display| FailureReason::new(Outcome::CryptoFailure, "row_audit_failed", e.to_string());
+    let seed: [u8; 32] = receipt
+        .claim()
+        .map_err(|e| audit_failure(&e))?
+        .digest()
+        .into();
+    
+    // Agent A reports its row count; Agent B picks the rows
+    // The row root only covers the rows the guest processed
+    let (csv_data, _) = zaik_core::limit_rows(csv_data, result.max_rows, result.dialect);
+    let rows = audit::row_count(csv_data, result.dialect);
+    let requested = audit::sample_indices(&seed, rows, samples);
+    println!("🔎 Agent B: Auditing {} of {} rows: {:?}", requested.len(), rows, requested);
+    
+    let openings = audit::open_rows(csv_data, result.dialect, &requested).map_err(|e| audit_failure(&e))?;
+    match audit::verify_openings(&result.row_merkle_root, rows, &requested, &openings) {
+        Ok(()) => {
+            println!("🔎 Row audit: PASSED");
+            Ok(())
+        }
+        Err(e) => {
+            println!("🔎 Row audit: FAILED ({})", e);
+            Err(audit_failure(&e))
+        }
+    }
+}
+
+/// Agent B signs a conditional accept asking for `scope`, Agent A answers
+/// it, and Agent B checks the answer against the journal.
+fn escrow(
+    args: &ProveArgs,
+    bundle_path: &Path,
+    receipt: &Receipt,
+    policy: &Policy,
+    verification_result: &VerificationResult,
+    scope: RevealScope,
+) -> Result<(), FailureReason> {
+    let verifier_key = args.verifier_key.as_deref().expect("--escrow is checked to have a verifier key");
+    let request = RevealRequest::new(receipt, &verification_result.result, scope)
+        .map_err(|e| FailureReason::new(Outcome::CryptoFailure, "receipt_verification_failed", e.to_string()))?;
+    let pending = Err(FailureReason::new(
+        Outcome::Conditional,
+        "reveal_requested",
+        format!("accepted once the prover reveals {}", scope),
+    ));
+    record_decision(verifier_key, bundle_path, policy, verification_result, &pending, Some(request))?;
+    println!("🔐 Agent B: Conditional accept, requesting {}", scope);
+    
+    // Agent A only answers a request signed by this verifier
+    let verifier = AgentKey::load(verifier_key).map_err(signing_failure)?.public_key();
+    let prover_key = args.prover_key.as_deref().expect("--escrow is checked to have a prover key");
+    let prover = AgentKey::load(prover_key).map_err(signing_failure)?.public_key();
+    let (csv_data, _) = args.proving.read_input(&args.csv_file)?;
+    protocol::answer(bundle_path, &csv_data, std::slice::from_ref(&prover), std::slice::from_ref(&verifier))
+        .map_err(|e| e.to_failure())?;
+    println!("🔓 Agent A: Revealed {}: {}", scope, Reveal::path_for(bundle_path).display());
+    
+    protocol::collect(bundle_path, &prover, &verifier).map_err(|e| e.to_failure())?;
+    println!("🔐 Agent B: Reveal matches the journal's commitments");
+    Ok(())
+}
+
+fn signing_failure(e: Box<dyn std::error::Error>) -> FailureReason {
+    FailureReason::new(Outcome::Io, "signing_failed", e.to_string())
+}
+
+/// Write a detached signature over `path` next to it.
+fn sign_file(key_path: &Path, role: SignerRole, path: &Path) -> Result<(), FailureReason> {
+    let key = AgentKey::load(key_path).map_err(signing_failure)?;
+    let contents = fs::read(path).map_err(|e| signing_failure(e.into()))?;
+    let signature_path = DetachedSignature::path_for(path);
+    key.sign(role, &contents).save(&signature_path).map_err(signing_failure)?;
+    println!("✍️  Signed {}: {}", path.display(), signature_path.display());
+    Ok(())
+}
+
+/// Write the environment manifest of the bundle at `bundle_path` unless it
+/// already has one from when it was proven, signing it with `prover_key`.
+fn record_environment(prover_key: Option<&Path>, bundle_path: &Path) -> Result<RecordedManifest, FailureReason> {
+    let unwritable = |e: Box<dyn std::error::Error>| FailureReason::new(Outcome::Io, "environment_unwritable", e.to_string());
+    let path = EnvironmentManifest::path_for(bundle_path);
+    if !path.exists() {
+        let manifest = EnvironmentManifest::current(GUEST_CODE_FOR_ZK_PROOF_ELF, GUEST_CODE_FOR_ZK_PROOF_ID);
+        fs::write(&path, manifest.to_bytes()).map_err(|e| unwritable(e.into()))?;
+        println!("🧾 Environment manifest {}: {}", manifest.digest(), path.display());
+    }
+    if let Some(key) = prover_key {
+        sign_file(key, SignerRole::Prover, &path)?;
+    }
+    RecordedManifest::load(bundle_path)
+        .map_err(unwritable)?
+        .ok_or_else(|| unwritable("environment manifest vanished".into()))
+}
+
+/// Obtain an RFC 3161 token over `path` and store it next to it.
+fn timestamp_file(url: &str, path: &Path) -> Result<(), FailureReason> {
+    let failed = |e: Box<dyn std::error::Error>| FailureReason::new(Outcome::Io, "timestamp_failed", e.to_string());
+    let contents = fs::read(path).map_err(|e| failed(e.into()))?;
+    let (token, info) = timestamp::obtain(url, &contents).map_err(failed)?;
+    let token_path = timestamp::path_for(path);
+    fs::write(&token_path, token).map_err(|e| failed(e.into()))?;
+    println!("🕰️  Timestamped {} at {}: {}", path.display(), info.time, token_path.display());
+    Ok(())
+}
+
+/// Write the decision for the bundle at `bundle_path` and sign it.
+fn record_decision(
+    key_path: &Path,
+    bundle_path: &Path,
+    policy: &Policy,
+    verification_result: &VerificationResult,
+    decision: &Result<(), FailureReason>,
+    reveal: Option<RevealRequest>,
+) -> Result<(), FailureReason> {
+    let Ok(prover_signature) = DetachedSignature::load(&DetachedSignature::path_for(bundle_path)) else {
+        println!("⚠️  Bundle has no prover signature; decision not recorded");
+        return Ok(());
+    };
+    let bundle_bytes = fs::read(bundle_path).map_err(|e| signing_failure(e.into()))?;
+    prover_signature
+        .verify(SignerRole::Prover, &bundle_bytes)
+        .map_err(|e| FailureReason::new(Outcome::CryptoFailure, "signature_chain_invalid", e.to_string()))?;
+    let (outcome, explanation) = match decision {
+        Ok(()) => (Outcome::Accept, explain::explain(&verification_result.policy_outcome)),
+        Err(failure) => (failure.outcome, failure.message.clone()),
+    };
+    let record = Decision {
+        bundle_digest: prover_signature.digest,
+        prover: prover_signature.signer,
+        policy_version: policy.version(),
+        outcome,
+        explanation,
+        reveal,
+        assurance_tier: policy.assurance.map(|_| verification_result.assurance_tier),
+    };
+    let path = Decision::path_for(bundle_path);
+    fs::write(&path, record.to_bytes()).map_err(|e| signing_failure(e.into()))?;
+    sign_file(key_path, SignerRole::Verifier, &path)
+}
+
+/// Write the transcript of every check behind `decision` for the bundle at
+/// `bundle_path` and sign it.
+fn record_transcript(
+    key_path: &Path,
+    bundle_path: &Path,
+    policy: &Policy,
+    verification_result: &VerificationResult,
+    decision: &Result<(), FailureReason>,
+) -> Result<(), FailureReason> {
+    let bundle_bytes = fs::read(bundle_path).map_err(|e| signing_failure(e.into()))?;
+    let (outcome, reason) = match decision {
+        Ok(()) => (Outcome::Accept, None),
+        Err(failure) => (failure.outcome, Some(failure.reason.clone())),
+    };
+    let transcript = Transcript::new(
+        hex::encode(Sha256::digest(&bundle_bytes)),
+        risc0_zkvm::sha::Digest::from(GUEST_CODE_FOR_ZK_PROOF_ID).to_string(),
+        policy.version(),
+        verification_result.steps.clone(),
+        outcome,
+        reason,
+    );
+    let path = Transcript::path_for(bundle_path);
+    fs::write(&path, transcript.to_bytes()).map_err(|e| signing_failure(e.into()))?;
+    println!("📜 Verification transcript ({} steps): {}", transcript.steps.len(), path.display());
+    sign_file(key_path, SignerRole::Verifier, &path)
+}
+
+fn expect(args: &ExpectArgs) -> Result<(), FailureReason> {
+    let unreadable = |e: Box<dyn std::error::Error>| {
+        FailureReason::new(Outcome::Io, "expected_hashes_unreadable", e.to_string())
+    };
+    let mut expected = if args.out.exists() {
+        ExpectedHashes::load(&args.out).map_err(unreadable)?
+    } else {
+        ExpectedHashes::default()
+    };
+    for csv_file in &args.csv_files {
+        let csv_hash = if args.dataset {
+            DatasetManifest::load(csv_file)
+                .and_then(|manifest| manifest.root())
+                .map_err(|e| FailureReason::new(Outcome::Io, "dataset_unreadable", e.to_string()))?
+        } else {
+            let csv_data = input::read_csv(csv_file)
+                .map_err(|e| FailureReason::new(Outcome::Io, "csv_unreadable", e.to_string()))?;
+            args.column_hash.csv_hash(&csv_data)
+        };
+        let added = expected.add(&csv_hash, args.note.clone());
+        println!("📌 {} {}{}", hex::encode(csv_hash), csv_file.display(), if added { "" } else { " (already expected)" });
+    }
+    expected
+        .save(&args.out)
+        .map_err(|e| FailureReason::new(Outcome::Io, "expected_hashes_unwritable", e.to_string()))?;
+    println!("💾 Wrote {} expected hash(es) to {}", expected.datasets.len(), args.out.display());
+    Ok(())
+}
+
+fn codegen(args: &CodegenArgs) -> Result<(), FailureReason> {
+    let policy = args.policy.load()?;
+    let image_id = match &args.image_id {
+        Some(image_id) => hex::decode(image_id)
+            .ok()
+            .and_then(|bytes| risc0_zkvm::sha::Digest::try_from(bytes.as_slice()).ok())
+            .ok_or_else(|| {
+                FailureReason::new(Outcome::Io, "image_id_invalid", format!("'{}' is not a 32-byte hex image ID", image_id))
+            })?,
+        None => risc0_zkvm::sha::Digest::from(GUEST_CODE_FOR_ZK_PROOF_ID),
+    };
+    let library = solidity::journal_library::<AgentResult>()
+        .map_err(|e| FailureReason::new(Outcome::Io, "codegen_unsupported", e.to_string()))?;
+    let contract = solidity::verifier_contract(&policy, image_id);
+    
+    let unwritable = |e: std::io::Error| FailureReason::new(Outcome::Io, "codegen_unwritable", e.to_string());
+    fs::create_dir_all(&args.out_dir).map_err(unwritable)?;
+    for (name, source) in [("ZaikJournal.sol", library), ("ZaikVerifier.sol", contract)] {
+        let path = args.out_dir.join(name);
+        fs::write(&path, source).map_err(unwritable)?;
+        println!("📝 Wrote {}", path.display());
+    }
+    println!("  - Image ID: {}", image_id);
+    println!("  - Policy version: {}", policy.version());
+    Ok(())
+}
+
+fn budget(args: &BudgetArgs) -> Result<(), FailureReason> {
+    let store = ProofStore::open(&args.store)
+        .map_err(|e| FailureReason::new(Outcome::Io, "store_unavailable", e.to_string()))?;
+    let ledger = store.budget_ledger();
+    let accounts = ledger
+        .accounts()
+        .map_err(|e| FailureReason::new(Outcome::Io, "budget_ledger_unreadable", e.to_string()))?;
+    let period = args.period.map(|period| period.to_string());
+    let accounts: Vec<_> = accounts
+        .into_iter()
+        .filter(|(tenant, _)| args.tenant.as_ref().is_none_or(|wanted| wanted == tenant))
+        .filter(|(_, account_period)| period.as_ref().is_none_or(|wanted| wanted == account_period))
+        .collect();
+    
+    println!("💰 Budget ledger {}: {} account(s)", args.store.display(), accounts.len());
+    for (tenant, period) in &accounts {
+        let standing = ledger.standing(tenant, period).map_err(|e| match e {
+            BudgetError::Io(_) => FailureReason::new(Outcome::Io, "budget_ledger_unreadable", e.to_string()),
+            _ => FailureReason::new(Outcome::CryptoFailure, "budget_chain_broken", e.to_string()),
+        })?;
+        println!(
+            "  - {} {}: total {} over {} proof(s), checkpoint {}",
+            tenant,
+            period,
+            standing.total,
+            standing.entries,
+            hex::encode(standing.checkpoint)
+        );
+    }
+    Ok(())
+}
+
+fn slo(args: &SloArgs) -> Result<(), FailureReason> {
+    let objectives = slo::Objectives::load(&args.objectives)
+        .map_err(|e| FailureReason::new(Outcome::Io, "objectives_unreadable", e.to_string()))?;
+    let sinks = args
+        .alerts
+        .as_deref()
+        .map(AlertSinks::load)
+        .transpose()
+        .map_err(|e| FailureReason::new(Outcome::Io, "alert_sinks_invalid", e.to_string()))?;
+    let store = ProofStore::open(&args.store)
+        .map_err(|e| FailureReason::new(Outcome::Io, "store_unavailable", e.to_string()))?;
+    let samples = store
+        .slo_log()
+        .samples()
+        .map_err(|e| FailureReason::new(Outcome::Io, "slo_log_unreadable", e.to_string()))?;
+    let report = slo::evaluate(&objectives, &samples);
+    
+    if let Some(path) = &args.prometheus {
+        store::write_atomic(path, slo::to_prometheus(&report).as_bytes())
+            .map_err(|e| FailureReason::new(Outcome::Io, "metrics_unwritable", e.to_string()))?;
+    }
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report).expect("SloReport is always serializable"));
+    } else {
+        println!("⏱️  {} proof(s) in the window of {}", report.samples, objectives.window);
+        println!("  - Proving time: p50 {} ms, p95 {} ms", report.p50_ms, report.p95_ms);
+        println!("  - Bundle size: p95 {} bytes", report.p95_bundle_bytes);
+        for (name, burn) in [("Proving time", &report.latency), ("Bundle size", &report.size)] {
+            if let Some(burn) = burn {
+                println!("  - {} burn rate: {:.2} ({} over {})", name, burn.burn_rate, burn.missed, burn.objective);
+            }
+        }
+        if let Some(change) = &report.guest_change {
+            println!(
+                "  - Last guest change: p95 {} ms → {} ms ({:.2}x)",
+                change.previous_p95_ms, change.p95_ms, change.growth
+            );
+        }
+        for breach in &report.breaches {
+            println!("  - ❌ {}", breach);
+        }
+    }
+    if report.breaches.is_empty() {
+        return Ok(());
+    }
+    
+    let failure = FailureReason::new(Outcome::Conditional, "slo_breached", report.breaches.join("; "));
+    if let Some(sinks) = &sinks {
+        let alert = Alert {
+            severity: objectives.severity,
+            outcome: failure.outcome,
+            reason: failure.reason.clone(),
+            message: failure.message.clone(),
+            csv_hash: String::new(),
+            source: args.store.display().to_string(),
+            policy_version: String::new(),
+        };
+        for delivery in sinks.dispatch(&alert) {
+            match delivery.result {
+                Ok(()) => println!("🚨 {} alert sent to {}", alert.severity, delivery.sink),
+                Err(e) => println!("⚠️  {} alert to {} failed: {}", alert.severity, delivery.sink, e),
+            }
+        }
+    }
+    Err(failure)
+}
+
+fn webhook_retry(store: &Path) -> Result<(), FailureReason> {
+    let store = ProofStore::open(store)
+        .map_err(|e| FailureReason::new(Outcome::Io, "store_unavailable", e.to_string()))?;
+    let path = store.webhook_dead_letters();
+    let pending = webhook::dead_letters(&path)
+        .map_err(|e| FailureReason::new(Outcome::Io, "dead_letters_unreadable", e.to_string()))?;
+    println!("📨 {} dead-lettered notification(s) in {}", pending.len(), path.display());
+    // Each letter goes to the URL it was meant for
+    let (delivered, remaining) = Webhook::new("", webhook_secret(), &path)
+        .redeliver()
+        .map_err(|e| FailureReason::new(Outcome::Io, "dead_letters_unreadable", e.to_string()))?;
+    println!("  - Delivered: {}", delivered);
+    println!("  - Still undeliverable: {}", remaining);
+    match remaining {
+        0 => Ok(()),
+        n => Err(FailureReason::new(Outcome::Io, "webhook_undeliverable", format!("{} notification(s) still undeliverable", n))),
+    }
+}
 
-#[derive(Debug, Serialize, Deserialize)]
-struct CsvProcessingInput {
-    csv_hash: [u8; 32],
-    csv_data: String,
+fn create_dataset(args: &DatasetArgs) -> Result<(), FailureReason> {
+    // Relative paths are read from the manifest's directory
+    let beside = args.out.parent().is_none_or(|dir| dir.as_os_str().is_empty());
+    let mut files = Vec::with_capacity(args.csv_files.len());
+    for csv_file in &args.csv_files {
+        let unreadable = |e: Box<dyn std::error::Error>| {
+            FailureReason::new(Outcome::Io, "csv_unreadable", format!("{}: {}", csv_file.display(), e))
+        };
+        let csv_hash = zaik_core::csv_hash(&input::read_csv(csv_file).map_err(unreadable)?);
+        let path = if beside { csv_file.clone() } else { fs::canonicalize(csv_file).map_err(|e| unreadable(e.into()))? };
+        println!("📄 {} {}", hex::encode(csv_hash), csv_file.display());
+        files.push((path, csv_hash));
+    }
+    let manifest = DatasetManifest::new(files);
+    manifest
+        .save(&args.out)
+        .map_err(|e| FailureReason::new(Outcome::Io, "dataset_unwritable", e.to_string()))?;
+    println!("🗂️  Wrote {} ({} file(s), root {})", args.out.display(), manifest.files.len(), manifest.root);
+    Ok(())
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct AgentResult {
-    csv_hash: [u8; 32],
-    column_a_sum: u64,
-    column_a_hash: [u8; 32],
-    entry_count: usize,
+fn export_kit(args: &ExportKitArgs) -> Result<(), FailureReason> {
+    let io = |reason: &str, e: Box<dyn std::error::Error>| FailureReason::new(Outcome::Io, reason, e.to_string());
+    // The kit's verifier loads the policy the same way
+    Policy::load(&args.policy).map_err(|e| io("policy_unreadable", e))?;
+    let images = match &args.allowlist {
+        Some(path) => ImageAllowlist::load(path).map_err(|e| io("allowlist_unreadable", e))?.images,
+        None => vec![kit::pinned(
+            GUEST_CODE_FOR_ZK_PROOF_ID,
+            &format!("zaik {} guest", env!("CARGO_PKG_VERSION")),
+        )],
+    };
+    let verifier = match &args.verifier {
+        Some(path) => path.clone(),
+        None => std::env::current_exe()
+            .map(|exe| exe.with_file_name(format!("verify{}", std::env::consts::EXE_SUFFIX)))
+            .map_err(|e| io("verifier_missing", e.into()))?,
+    };
+    if !verifier.is_file() {
+        return Err(FailureReason::new(
+            Outcome::Io,
+            "verifier_missing",
+            format!(
+                "no verifier at {}; build it with `cargo build --release -p zaik-cli --no-default-features --bin verify` \
+                 or pass --verifier",
+                verifier.display()
+            ),
+        ));
+    }
+    let mut bundles = Vec::new();
+    for path in &args.bundles {
+        if path.is_dir() {
+            let store = ProofStore::open(path).map_err(|e| io("store_unreadable", e))?;
+            bundles.extend(store.bundles().map_err(|e| io("store_unreadable", e))?);
+        } else {
+            bundles.push(path.clone());
+        }
+    }
+    let contents = KitContents {
+        verifier,
+        images,
+        policy: args.policy.clone(),
+        bundles,
+    };
+    let written = kit::export(&contents, &args.out).map_err(|e| io("kit_unwritable", e))?;
+    println!("🧳 Wrote {} ({} bundle(s), {} file(s))", args.out.display(), contents.bundles.len(), written.files.len());
+    println!("🔑 SHA256SUMS digest: {}", hex::encode(written.digest));
+    println!("   Hand this digest to the auditor separately from the kit");
+    Ok(())
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct VerificationResult {
-    result: AgentResult,
-    verification_passed: bool,
-    business_invariant_passed: bool,
-    sum_threshold: u64,
+fn attest_export(args: &AttestExportArgs) -> Result<(), FailureReason> {
+    let io = |reason: &str, e: Box<dyn std::error::Error>| FailureReason::new(Outcome::Io, reason, e.to_string());
+    let bundle_bytes = fs::read(&args.bundle).map_err(|e| io("bundle_unreadable", e.into()))?;
+    let bundle = ProofBundle::load(&args.bundle).map_err(|e| io("bundle_unreadable", e))?;
+    let subject_name = args.subject_name.clone().unwrap_or_else(|| bundle.key.digest());
+    let statement = Statement::for_bundle(&bundle, &bundle_bytes, &subject_name)
+        .map_err(|e| FailureReason::new(Outcome::CryptoFailure, "journal_decode_failed", e.to_string()))?;
+    let attestation = match &args.signing_key {
+        Some(path) => {
+            let key = AgentKey::load(path).map_err(|e| io("signing_key_unreadable", e))?;
+            Attestation::Signed(Envelope::sign(&statement, &key))
+        }
+        None => Attestation::Unsigned(Box::new(statement)),
+    };
+    let out = args.out.clone().unwrap_or_else(|| args.bundle.with_extension("intoto.json"));
+    let json = serde_json::to_string_pretty(&attestation).expect("Attestation is always serializable");
+    fs::write(&out, json).map_err(|e| io("attestation_unwritable", e.into()))?;
+    println!(
+        "📜 Wrote {} ({})",
+        out.display(),
+        if matches!(attestation, Attestation::Signed(_)) { "DSSE envelope" } else { "unsigned statement" }
+    );
+    Ok(())
 }
 
-struct AgentA;
-struct AgentB;
+fn bond_post(args: &BondPostArgs) -> Result<(), FailureReason> {
+    let io = |reason: &str, e: Box<dyn std::error::Error>| FailureReason::new(Outcome::Io, reason, e.to_string());
+    let ledger = ProofStore::open(&args.store).map_err(|e| io("store_unreadable", e))?.bond_ledger();
+    let balance = ledger.post(&args.prover, args.amount).map_err(|e| io("bond_not_posted", e))?;
+    println!("🔐 Bonded {} for {} (now {})", args.amount, args.prover, balance);
+    Ok(())
+}
 
-impl AgentA {
-    fn process_csv(csv_file_path: &str) -> Result<Receipt, Box<dyn std::error::Error>> {
-        println!("🤖 Agent A: Processing CSV file: {}", csv_file_path);
-        
-        // Read CSV file
-        let csv_data = fs::read_to_string(csv_file_path)?;
-        
-        // Compute CSV hash
-        let mut hasher = Sha256::new();
-        hasher.update(csv_data.as_bytes());
-        let csv_hash: [u8; 32] = hasher.finalize().into();
-        
-        println!("📊 CSV hash: {:?}", hex::encode(csv_hash));
-        
-        // Create input for guest
-        let input = CsvProcessingInput {
-            csv_hash,
-            csv_data,
+fn bond_evidence(args: &BondEvidenceArgs) -> Result<(), FailureReason> {
+    let io = |reason: &str, e: Box<dyn std::error::Error>| FailureReason::new(Outcome::Io, reason, e.to_string());
+    let bundle_bytes = untrusted::read(&args.bundle, untrusted::MAX_BUNDLE_BYTES)
+        .map_err(|e| io("bundle_unreadable", e.into()))?;
+    let prover_signature = DetachedSignature::load(&DetachedSignature::path_for(&args.bundle))
+        .map_err(|e| io("signature_unreadable", e))?;
+    let policy = Policy::load(&args.policy).map_err(|e| io("policy_unreadable", e))?;
+    let evidence = SlashingEvidence::new(&bundle_bytes, prover_signature, policy);
+    let out = args.out.clone().unwrap_or_else(|| SlashingEvidence::path_for(&args.bundle));
+    evidence.save(&out).map_err(|e| io("evidence_unwritable", e))?;
+    println!("🧾 Wrote slashing evidence to {}", out.display());
+    Ok(())
+}
+
+fn bond_slash(args: &BondSlashArgs) -> Result<(), FailureReason> {
+    let io = |reason: &str, e: Box<dyn std::error::Error>| FailureReason::new(Outcome::Io, reason, e.to_string());
+    let evidence = SlashingEvidence::load(&args.evidence).map_err(|e| io("evidence_unreadable", e))?;
+    let image_ids = match &args.allowlist {
+        Some(path) => ImageAllowlist::load(path)
+            .and_then(|allowlist| allowlist.digests())
+            .map_err(|e| io("allowlist_unreadable", e))?,
+        None => vec![GUEST_CODE_FOR_ZK_PROOF_ID.into()],
+    };
+    let ledger = ProofStore::open(&args.store).map_err(|e| io("store_unreadable", e))?.bond_ledger();
+    let slash = ledger.slash(&evidence, &image_ids, args.amount).map_err(|e| match e {
+        BondError::Evidence(e) => e.to_failure(),
+        BondError::AlreadySlashed { .. } => FailureReason::new(Outcome::PolicyReject, "already_slashed", e.to_string()),
+        BondError::NoBond { .. } => FailureReason::new(Outcome::PolicyReject, "no_bond", e.to_string()),
+        BondError::Io(e) => io("ledger_unwritable", e),
+    })?;
+    println!("⚔️  Slashed {} from {}", slash.amount, slash.offence.prover);
+    println!("  - Bundle: {}", slash.offence.bundle_digest);
+    println!("  - Offence: {} ({})", slash.offence.reason, slash.offence.detail);
+    println!("  - Bond left: {}", slash.remaining);
+    Ok(())
+}
+
+fn bond_show(store: &Path) -> Result<(), FailureReason> {
+    let io = |reason: &str, e: Box<dyn std::error::Error>| FailureReason::new(Outcome::Io, reason, e.to_string());
+    let ledger = ProofStore::open(store).map_err(|e| io("store_unreadable", e))?.bond_ledger();
+    let entries = ledger.entries().map_err(|e| io("ledger_unreadable", e))?;
+    let mut provers: Vec<&str> = entries
+        .iter()
+        .map(|entry| match entry {
+            LedgerEntry::Posted { prover, .. } | LedgerEntry::Slashed { prover, .. } => prover.as_str(),
+        })
+        .collect();
+    provers.sort();
+    provers.dedup();
+    for prover in provers {
+        let balance = ledger.balance(prover).map_err(|e| io("ledger_unreadable", e))?;
+        println!("🔐 {}: {}", prover, balance);
+    }
+    for entry in &entries {
+        match entry {
+            LedgerEntry::Posted { prover, amount, at } => println!("  - {} posted {} by {}", at, amount, prover),
+            LedgerEntry::Slashed { prover, amount, bundle_digest, reason, at } => {
+                println!("  - {} slashed {} from {} for {} ({})", at, amount, prover, bundle_digest, reason)
+            }
+        }
+    }
+    Ok(())
+}
+
+fn dispute_open(args: &DisputeOpenArgs) -> Result<(), FailureReason> {
+    let io = |reason: &str, e: Box<dyn std::error::Error>| FailureReason::new(Outcome::Io, reason, e.to_string());
+    let policy = Policy::load(&args.policy).map_err(|e| io("policy_unreadable", e))?;
+    let dispute = Dispute::open(&args.bundle, policy, args.counter_proof.as_deref(), &args.statement)
+        .map_err(|e| e.to_failure())?;
+    let out = args.out.clone().unwrap_or_else(|| Dispute::path_for(&args.bundle));
+    dispute.save(&out).map_err(|e| io("dispute_unwritable", e))?;
+    println!("⚖️  Dispute over a {:?} decision written to {}", dispute.decision.outcome, out.display());
+    if dispute.counter_proof.is_some() {
+        println!("  - With a counter-proof");
+    }
+    Ok(())
+}
+
+fn dispute_rule(args: &DisputeRuleArgs) -> Result<(), FailureReason> {
+    let io = |reason: &str, e: Box<dyn std::error::Error>| FailureReason::new(Outcome::Io, reason, e.to_string());
+    let dispute = Dispute::load(&args.dispute).map_err(|e| io("dispute_unreadable", e))?;
+    let key = AgentKey::load(&args.arbiter_key).map_err(|e| io("signing_key_unreadable", e))?;
+    let image_ids = match &args.allowlist {
+        Some(path) => ImageAllowlist::load(path)
+            .and_then(|allowlist| allowlist.digests())
+            .map_err(|e| io("allowlist_unreadable", e))?,
+        None => vec![GUEST_CODE_FOR_ZK_PROOF_ID.into()],
+    };
+    let mut arbiter = Arbiter::new(key, image_ids, args.trusted_verifiers.clone());
+    if args.allow_dev_mode {
+        arbiter = arbiter.allow_dev_mode();
+    }
+    let (ruling, signature) = arbiter.rule(&dispute).map_err(|e| e.to_failure())?;
+
+    let out = args.out.clone().unwrap_or_else(|| Ruling::path_for(&args.dispute.with_extension("")));
+    fs::write(&out, ruling.to_bytes()).map_err(|e| io("ruling_unwritable", e.into()))?;
+    signature
+        .save(&DetachedSignature::path_for(&out))
+        .map_err(|e| io("ruling_unwritable", e))?;
+    let entry = ProofStore::open(&args.store)
+        .and_then(|store| store.audit_log().append("ruling", &(&ruling, &signature)))
+        .map_err(|e| io("audit_log_unwritable", e))?;
+
+    println!("⚖️  Ruling: {:?} ({})", ruling.verdict, ruling.reason);
+    println!("  - {}", ruling.explanation);
+    println!("  - Written to {}", out.display());
+    println!("  - Audit log entry {} ({})", entry.seq, entry.hash);
+    Ok(())
+}
+
+fn audit_log_verify(store: &Path) -> Result<(), FailureReason> {
+    let io = |reason: &str, e: Box<dyn std::error::Error>| FailureReason::new(Outcome::Io, reason, e.to_string());
+    let entries = ProofStore::open(store)
+        .and_then(|store| store.audit_log().entries())
+        .map_err(|e| io("audit_log_unreadable", e))?;
+    let head = audit_log::verify(&entries)
+        .map_err(|e| FailureReason::new(Outcome::CryptoFailure, "audit_log_tampered", e.to_string()))?;
+    println!("📒 Audit log: VALID, {} entries", entries.len());
+    println!("  - Head: {}", hex::encode(head));
+    println!("  - Merkle root: {}", hex::encode(audit_log::root(&entries)));
+    Ok(())
+}
+
+fn audit_log_prove(seq: u64, store: &Path) -> Result<(), FailureReason> {
+    let io = |reason: &str, e: Box<dyn std::error::Error>| FailureReason::new(Outcome::Io, reason, e.to_string());
+    let entries = ProofStore::open(store)
+        .and_then(|store| store.audit_log().entries())
+        .map_err(|e| io("audit_log_unreadable", e))?;
+    audit_log::verify(&entries)
+        .map_err(|e| FailureReason::new(Outcome::CryptoFailure, "audit_log_tampered", e.to_string()))?;
+    let inclusion = audit_log::prove(&entries, seq).map_err(|e| io("audit_log_entry_missing", e.into()))?;
+    println!("{}", serde_json::to_string_pretty(&inclusion).expect("Inclusion is always serializable"));
+    Ok(())
+}
+
+fn attest_import(args: &AttestImportArgs) -> Result<(), FailureReason> {
+    let io = |reason: &str, e: Box<dyn std::error::Error>| FailureReason::new(Outcome::Io, reason, e.to_string());
+    let invalid = |e: AttestationError| FailureReason::new(Outcome::CryptoFailure, e.reason(), e.to_string());
+    let attestation = untrusted::read(&args.attestation, untrusted::MAX_SIDECAR_BYTES)
+        .map_err(|e| io("attestation_unreadable", e.into()))?;
+    let bundle_bytes = untrusted::read(&args.bundle, untrusted::MAX_BUNDLE_BYTES)
+        .map_err(|e| io("bundle_unreadable", e.into()))?;
+    let bundle = ProofBundle::from_bytes(&bundle_bytes).map_err(|e| io("bundle_unreadable", e))?;
+    let statement = Attestation::parse(&attestation)
+        .and_then(|attestation| attestation.statement(&args.signers, args.allow_unsigned))
+        .map_err(invalid)?;
+    statement.check(&bundle, &bundle_bytes).map_err(invalid)?;
+    // The receipt below is checked against this build's guest, which the
+    // statement must name too
+    if statement.predicate.image_id != risc0_zkvm::sha::Digest::from(GUEST_CODE_FOR_ZK_PROOF_ID).to_string() {
+        return Err(invalid(AttestationError::PredicateMismatch("imageId")));
+    }
+    bundle
+        .receipt
+        .verify(GUEST_CODE_FOR_ZK_PROOF_ID)
+        .map_err(|e| FailureReason::new(Outcome::CryptoFailure, "receipt_invalid", e.to_string()))?;
+    
+    println!("📜 Attestation: VALID for {}", args.bundle.display());
+    for subject in &statement.subject {
+        for (algorithm, digest) in &subject.digest {
+            println!("  - Subject: {} ({}:{})", subject.name, algorithm, digest);
+        }
+    }
+    println!("  - Proof: {:?}, policy {}", statement.predicate.proof_kind, statement.predicate.policy_version);
+    Ok(())
+}
+
+fn print_environment(json: bool) {
+    let manifest = EnvironmentManifest::current(GUEST_CODE_FOR_ZK_PROOF_ELF, GUEST_CODE_FOR_ZK_PROOF_ID);
+    if json {
+        println!("{}", String::from_utf8_lossy(&manifest.to_bytes()));
+        return;
+    }
+    println!("🧾 Environment {}", manifest.digest());
+    println!("  - Host {}, risc0-zkvm {}", manifest.host_version, manifest.risc0_version);
+    println!("  - Guest image: {}", manifest.image_id);
+    println!("  - Guest ELF SHA-256: {}", manifest.elf_sha256);
+    println!("  - Host toolchain: {}", manifest.host_toolchain);
+    println!("  - Guest toolchain: {}", manifest.guest_toolchain);
+    println!(
+        "  - {} host and {} guest crate(s) locked",
+        manifest.host_packages.len(),
+        manifest.guest_packages.len()
+    );
+}
+
+fn policy_test(args: &PolicyTestArgs) -> Result<(), FailureReason> {
+    let policy = Policy::load(&args.policy)
+        .map_err(|e| FailureReason::new(Outcome::Io, "policy_unreadable", e.to_string()))?;
+    let store = ProofStore::open(&args.against)
+        .map_err(|e| FailureReason::new(Outcome::Io, "store_unavailable", e.to_string()))?;
+    let report = rollout::dry_run(&policy, &store)
+        .map_err(|e| FailureReason::new(Outcome::Io, "store_unreadable", e.to_string()))?;
+    
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report).expect("RolloutReport is always serializable"));
+    } else {
+        println!("🧪 Policy {} against {}", report.policy_version, args.against.display());
+        for change in report.bundles.iter().filter(|change| change.changed()) {
+            println!(
+                "  - {} {}: {} → {}{}",
+                if change.accepted { "✅" } else { "❌" },
+                change.bundle,
+                if change.was_accepted { "accept" } else { "reject" },
+                if change.accepted { "accept" } else { "reject" },
+                if change.failed_rules.is_empty() {
+                    String::new()
+                } else {
+                    format!(" (fails {})", change.failed_rules.join(", "))
+                }
+            );
+        }
+        for path in &report.unreadable {
+            println!("  - ⚠️  {}: unreadable or sum opening fails; skipped", path);
+        }
+        println!("📋 Evaluated {} bundle(s):", report.bundles.len());
+        println!("  - Unchanged: {}", report.unchanged);
+        println!("  - Newly rejected: {}", report.newly_rejected);
+        println!("  - Newly accepted: {}", report.newly_accepted);
+        println!("  - Need a new proof under this policy: {}", report.needs_reproof);
+    }
+    
+    match report.changed() {
+        0 => Ok(()),
+        changed => Err(FailureReason::new(
+            Outcome::Conditional,
+            "decisions_would_change",
+            format!("{} of {} past decision(s) would change", changed, report.bundles.len()),
+        )
+        .with_policy_version(report.policy_version)),
+    }
+}
+
+fn policy_resolve(args: &PolicyResolveArgs) -> Result<(), FailureReason> {
+    let dialect = Dialect {
+        delimiter: args.delimiter,
+        quote: args.quote,
+        has_header: true,
+    };
+    dialect
+        .check()
+        .map_err(|e| FailureReason::new(Outcome::Io, "dialect_invalid", e))?;
+    let (policy, matches) = resolve_policy(&args.policy, &args.csv_file, false, &dialect, args.accept_fuzzy_columns)?;
+    if matches.is_empty() {
+        println!("🧭 {} names no columns; every column is already an index", args.policy.display());
+    }
+    if let Some(out) = &args.out {
+        let resolved = toml::to_string(&policy).expect("Policy is always serializable as TOML");
+        fs::write(out, resolved).map_err(|e| FailureReason::new(Outcome::Io, "policy_unwritable", e.to_string()))?;
+        println!("📝 Wrote {} (policy version {})", out.display(), hex::encode(policy.hash()));
+    }
+    Ok(())
+}
+
+fn keygen(out: &Path) -> Result<(), FailureReason> {
+    let key = AgentKey::generate(out).map_err(signing_failure)?;
+    println!("🔑 Wrote agent key: {}", out.display());
+    println!("  - Public key: {}", key.public_key());
+    Ok(())
+}
+
+fn verify_chain(args: &VerifyChainArgs) -> Result<(), FailureReason> {
+    let unreadable = |e: Box<dyn std::error::Error>| FailureReason::new(Outcome::Io, "chain_unreadable", e.to_string());
+    let bundle_bytes = untrusted::read(&args.bundle, untrusted::MAX_BUNDLE_BYTES).map_err(|e| unreadable(e.into()))?;
+    let prover_signature = DetachedSignature::load(&DetachedSignature::path_for(&args.bundle)).map_err(unreadable)?;
+    let decision_path = Decision::path_for(&args.bundle);
+    let decision: Decision = untrusted::read_to_string(&decision_path, untrusted::MAX_SIDECAR_BYTES)
+        .map_err(|e| unreadable(e.into()))
+        .and_then(|json| serde_json::from_str(&json).map_err(|e| unreadable(e.into())))?;
+    let verifier_signature = DetachedSignature::load(&DetachedSignature::path_for(&decision_path)).map_err(unreadable)?;
+
+    signing::verify_chain(
+        &bundle_bytes,
+        &prover_signature,
+        &decision,
+        &verifier_signature,
+        &args.trusted_provers,
+        &args.trusted_verifiers,
+    )
+    .map_err(|e| FailureReason::new(Outcome::CryptoFailure, "signature_chain_invalid", e.to_string()))?;
+
+    println!("🔗 Signature chain: VALID");
+    println!("  - Prover: {}", prover_signature.signer);
+    println!("  - Verifier: {}", verifier_signature.signer);
+    println!("  - Decision: {:?} ({})", decision.outcome, decision.explanation);
+    let transcript_path = Transcript::path_for(&args.bundle);
+    if let Ok(bytes) = untrusted::read(&transcript_path, untrusted::MAX_SIDECAR_BYTES) {
+        let invalid = |message: String| FailureReason::new(Outcome::CryptoFailure, "transcript_invalid", message);
+        let signature = DetachedSignature::load(&DetachedSignature::path_for(&transcript_path))
+            .map_err(|e| invalid(e.to_string()))?;
+        let transcript = Transcript::open(&bytes, &signature, &verifier_signature.signer, &prover_signature.digest)
+            .map_err(invalid)?;
+        let failed = transcript.steps.iter().filter(|step| !step.passed).count();
+        println!("  - Transcript: {} step(s), {} failed, {:?}", transcript.steps.len(), failed, transcript.outcome);
+    }
+    if let Ok(token) = untrusted::read(&timestamp::path_for(&args.bundle), untrusted::MAX_SIDECAR_BYTES) {
+        let invalid = |e: timestamp::TimestampError| {
+            FailureReason::new(Outcome::CryptoFailure, "timestamp_invalid", e.to_string())
         };
-        
-        // Build executor environment
-        let env = ExecutorEnv::builder()
-            .write(&input)?
-            .build()?;
-        
-        // Generate proof
-        println!("⚡ Generating zkVM proof...");
-        let prover = default_prover();
-        let prove_info = prover.prove(env, GUEST_CODE_FOR_ZK_PROOF_ELF)?;
-        
-        println!("✅ Proof generated successfully!");
-        Ok(prove_info.receipt)
+        match &args.tsa_cert {
+            Some(path) => {
+                let tsa = TsaCertificate::load(path)
+                    .map_err(|e| FailureReason::new(Outcome::Io, "tsa_cert_unreadable", e.to_string()))?;
+                let info = timestamp::verify(&token, &bundle_bytes, &tsa).map_err(invalid)?;
+                println!("  - Timestamped: {} (TSA policy {}, serial {})", info.time, info.policy, info.serial);
+            }
+            None => {
+                let info = timestamp::check_imprint(&token, &bundle_bytes).map_err(invalid)?;
+                println!(
+                    "  - Timestamp imprint matches, claims {} (signature not checked without --tsa-cert)",
+                    info.time
+                );
+            }
+        }
     }
+    Ok(())
 }
 
-impl AgentB {
-    fn verify_and_check_invariant(receipt: &Receipt, sum_threshold: u64) -> Result<VerificationResult, Box<dyn std::error::Error>> {
-        println!("🔍 Agent B: Verifying receipt and checking business invariant...");
-        
-        // Verify the receipt
-        let verification_passed = receipt.verify(GUEST_CODE_FOR_ZK_PROOF_ID).is_ok();
-        println!("🔐 Receipt verification: {}", if verification_passed { "PASSED" } else { "FAILED" });
-        
-        // Extract result from journal
-        let result: AgentResult = receipt.journal.decode()?;
-        
-        println!("📈 Extracted result:");
-        println!("  - CSV hash: {}", hex::encode(result.csv_hash));
-        println!("  - Column A sum: {}", result.column_a_sum);
-        println!("  - Column A hash: {}", hex::encode(result.column_a_hash));
-        println!("  - Entry count: {}", result.entry_count);
-        
-        // Check business invariant (sum under threshold)
-        let business_invariant_passed = result.column_a_sum <= sum_threshold;
-        println!("💼 Business invariant (sum <= {}): {}", 
-                sum_threshold, 
-                if business_invariant_passed { "PASSED" } else { "FAILED" });
-        
-        Ok(VerificationResult {
-            result,
-            verification_passed,
-            business_invariant_passed,
-            sum_threshold,
+fn verify_all(args: &VerifyAllArgs) -> Result<(), FailureReason> {
+    let io = |reason: &str, e: Box<dyn std::error::Error>| FailureReason::new(Outcome::Io, reason, e.to_string());
+    let policy = Policy::load(&args.policy).map_err(|e| io("policy_unreadable", e))?;
+    let image_ids = match &args.allowlist {
+        Some(path) => ImageAllowlist::load(path)
+            .and_then(|allowlist| allowlist.digests())
+            .map_err(|e| io("allowlist_unreadable", e))?,
+        None => vec![GUEST_CODE_FOR_ZK_PROOF_ID.into()],
+    };
+    if !args.dir.is_dir() {
+        return Err(io("store_unreadable", format!("{} is not a directory", args.dir.display()).into()));
+    }
+    let bundles = ProofStore::open(&args.dir)
+        .and_then(|store| store.bundles())
+        .map_err(|e| io("store_unreadable", e))?;
+    let jobs = args
+        .jobs
+        .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |cores| cores.get()))
+        .clamp(1, bundles.len().max(1));
+
+    println!("🔍 Verifying {} bundle(s) in {} on {} thread(s)", bundles.len(), args.dir.display(), jobs);
+    let verdicts = bulk::verify_all(&bundles, jobs, |path| {
+        let mut relying_party = RelyingParty::new(policy.clone());
+        for image_id in &image_ids {
+            relying_party = relying_party.allow_image(*image_id);
+        }
+        if args.allow_dev_mode {
+            relying_party = relying_party.allow_dev_mode();
+        }
+        if let Some(recorded) = RecordedManifest::load(path).map_err(|e| io("environment_unreadable", e))? {
+            relying_party = relying_party.environment(recorded);
+        }
+        Ok(relying_party)
+    });
+    let report = BulkReport::new(policy.version(), verdicts);
+    for verdict in report.bundles.iter().filter(|verdict| !verdict.passed()) {
+        println!(
+            "  - ❌ {}: {} ({})",
+            verdict.bundle,
+            verdict.reason.as_deref().unwrap_or_default(),
+            verdict.message.as_deref().unwrap_or_default()
+        );
+    }
+    println!("📋 {} passed, {} failed", report.passed, report.failed);
+    for (reason, count) in &report.failures {
+        println!("  - {}: {}", reason, count);
+    }
+    if let Some(path) = &args.report {
+        let json = serde_json::to_string_pretty(&report).expect("BulkReport is always serializable");
+        fs::write(path, json).map_err(|e| io("report_unwritable", e.into()))?;
+        println!("📝 Report written to {}", path.display());
+    }
+
+    match report.first_failure() {
+        None => {
+            println!("✅ Every bundle verified");
+            Ok(())
+        }
+        Some(first) => Err(FailureReason::new(
+            first.outcome,
+            "bundles_failed",
+            format!("{} of {} bundle(s) failed verification, first {}", report.failed, report.total, first.bundle),
+        )
+        .with_policy_version(policy.version())),
+    }
+}
+
+fn evaluate_prove(args: &EvaluateProveArgs) -> Result<(), FailureReason> {
+    let io = |reason: &str, e: Box<dyn std::error::Error>| FailureReason::new(Outcome::Io, reason, e.to_string());
+    let evaluation = Evaluation {
+        dialect: Dialect {
+            delimiter: args.delimiter,
+            quote: Dialect::CSV.quote,
+            has_header: !args.no_header,
+        },
+        id_column: args.id_column,
+        prediction_column: args.prediction_column,
+        label_column: args.label_column,
+        positive: args.positive.clone(),
+    };
+    evaluation.validate().map_err(|e| io("evaluation_invalid", e.into()))?;
+    let predictions = input::read_csv(&args.predictions).map_err(|e| io("csv_unreadable", e))?;
+    let labels = input::read_csv(&args.labels).map_err(|e| io("csv_unreadable", e))?;
+    
+    println!("🎯 Scoring {} against {}", args.predictions.display(), args.labels.display());
+    // Fail before proving when the files do not pair up
+    let counts = evaluation
+        .score(&predictions, &labels)
+        .map_err(|e| FailureReason::new(Outcome::UnexpectedData, "predictions_unpaired", e))?;
+    let input = EvaluationInput::new(predictions, labels, evaluation.clone());
+    let receipt = prove_framed(ZAIK_EVALUATE_ELF, &input, input.frames())?;
+    
+    // The host ran the same code; different counts mean the two builds differ
+    let result = EvaluationResult::from_bytes(&receipt.journal.bytes).ok_or_else(|| {
+        FailureReason::new(Outcome::CryptoFailure, "journal_decode_failed", "journal is not an evaluation journal")
+    })?;
+    if (result.rows, result.correct) != (counts.rows, counts.correct) {
+        return Err(FailureReason::new(
+            Outcome::CryptoFailure,
+            "journal_mismatch",
+            "the guest scored the files differently than the host",
+        ));
+    }
+    
+    let receipt_path = EvaluationReceipt::path_for(&args.predictions);
+    EvaluationReceipt {
+        evaluation,
+        receipt,
+    }
+    .save(&receipt_path)
+    .map_err(|e| io("receipt_unwritable", e))?;
+    println!(
+        "📊 {} of {} correct ({}), {} missing prediction(s)",
+        result.correct,
+        result.rows,
+        evaluation_proof::format_bps(result.accuracy_bps),
+        result.missing
+    );
+    if let Some(positive) = &args.positive {
+        println!(
+            "📊 Positive class '{}': {} true positive(s), {} false positive(s), {} false negative(s)",
+            positive, result.true_positives, result.false_positives, result.false_negatives
+        );
+    }
+    println!("💾 Wrote {}", receipt_path.display());
+    println!("🏷️  Proof kind: {}", ProofKind::current());
+    Ok(())
+}
+
+fn evaluate_verify(args: &EvaluateVerifyArgs) -> Result<(), FailureReason> {
+    let io = |reason: &str, e: Box<dyn std::error::Error>| FailureReason::new(Outcome::Io, reason, e.to_string());
+    let proof = EvaluationReceipt::load(&args.receipt).map_err(|e| io("receipt_unreadable", e))?;
+    let read = |path: &Option<PathBuf>| path.as_deref().map(input::read_csv).transpose();
+    let predictions = read(&args.predictions).map_err(|e| io("csv_unreadable", e))?;
+    let labels = read(&args.labels).map_err(|e| io("csv_unreadable", e))?;
+    
+    println!("🔍 Verifying evaluation {}", args.receipt.display());
+    let journal = evaluation_proof::verify(
+        &proof,
+        ZAIK_EVALUATE_ID,
+        EvaluationRequirements {
+            predictions: predictions.as_deref(),
+            labels: labels.as_deref(),
+            evaluation: None,
+            min_accuracy_bps: args.min_accuracy,
+            allow_dev_mode: args.allow_dev_mode,
+        },
+    )
+    .map_err(|rejection| rejection.to_failure())?;
+    let evaluation = &proof.evaluation;
+    println!("📈 Evaluation journal:");
+    print_journal(&journal);
+    println!(
+        "🎯 Scored prediction column {} against label column {}{}",
+        evaluation.prediction_column,
+        evaluation.label_column,
+        evaluation.id_column.map(|column| format!(", rows paired by ID column {}", column)).unwrap_or_default()
+    );
+    println!("🏷️  Proof kind: {}", ProofKind::of(&proof.receipt));
+    if labels.is_none() {
+        println!("⚠️  No --labels given; the accuracy is against labels only their hash identifies");
+    }
+    println!("✅ Accuracy {} over {} row(s)", evaluation_proof::format_bps(journal.accuracy_bps), journal.rows);
+    Ok(())
+}
+
+fn window_append(args: &WindowAppendArgs) -> Result<(), FailureReason> {
+    let io = |reason: &str, e: Box<dyn std::error::Error>| FailureReason::new(Outcome::Io, reason, e.to_string());
+    let state = WindowState::load(&args.state).map_err(|e| io("window_state_unreadable", e))?;
+    let spec = args.spec(state.as_ref().map(|state| state.latest.spec))?;
+    let rows = input::read_csv(&args.batch).map_err(|e| io("csv_unreadable", e))?;
+    let (previous, entries, assumptions) = match state {
+        Some(state) => {
+            let previous = state.latest.journal().ok_or_else(|| {
+                io("window_state_unreadable", "the state's receipt does not hold a window journal".into())
+            })?;
+            (Some(previous), state.window, vec![state.latest.receipt])
+        }
+        None => (None, Vec::new(), Vec::new()),
+    };
+    
+    match &previous {
+        Some(previous) => println!(
+            "🪟 Appending {} to the log in {} ({} row(s) so far)",
+            args.batch.display(),
+            args.state.display(),
+            previous.log_rows
+        ),
+        None => println!("🪟 Starting a log in {} with {}", args.state.display(), args.batch.display()),
+    }
+    let input = WindowInput {
+        spec,
+        cap: args.cap,
+        image_id: GUEST_CODE_FOR_ZK_PROOF_ID,
+        previous,
+        window: entries,
+        rows_hash: zaik_core::csv_hash(&rows),
+        rows,
+    };
+    // The same step natively first: a bad row fails here, not after executing
+    let (result, entries) = window::step(&input).map_err(|fault| FailureReason::new(Outcome::Io, fault.reason(), fault.to_string()))?;
+    
+    // Succinct, so the previous step's receipt is resolved and every step's receipt stays the same size
+    let input = GuestInput::Window(Box::new(input));
+    let prove_info = ProofSession::new(&input, GUEST_CODE_FOR_ZK_PROOF_ELF)
+        .with_options(ProverOptions {
+            receipt_kind: Some(ReceiptKind::Succinct),
+            ..ProverOptions::default()
         })
+        .with_assumptions(assumptions)
+        .observe(&print_progress)
+        .prove()
+        .map_err(|e| proving_failure(e.into()))?;
+    
+    let latest = WindowReceipt {
+        spec,
+        receipt: prove_info.receipt,
+    };
+    let receipt_path = WindowReceipt::path_for(&args.state);
+    latest.save(&receipt_path).map_err(|e| io("receipt_unwritable", e))?;
+    WindowState { latest, window: entries }
+        .save(&args.state)
+        .map_err(|e| io("window_state_unwritable", e))?;
+    println!("📈 Window journal:");
+    print_journal(&result);
+    println!("💾 Wrote {} and {}", args.state.display(), receipt_path.display());
+    println!("🏷️  Proof kind: {}", ProofKind::current());
+    if !result.within_cap {
+        println!("⚠️  Window sum {} is over the cap of {}", result.window_sum, result.cap);
     }
+    Ok(())
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Initialize tracing
-    tracing_subscriber::fmt()
-        .with_env_filter(tracing_subscriber::filter::EnvFilter::from_default_env())
-        .init();
+fn window_verify(args: &WindowVerifyArgs) -> Result<(), FailureReason> {
+    let proof = WindowReceipt::load(&args.receipt)
+        .map_err(|e| FailureReason::new(Outcome::Io, "receipt_unreadable", e.to_string()))?;
     
-    println!("🚀 Starting RISC Zero CSV Processing Demo");
-    println!("==========================================");
+    println!("🔍 Verifying window step {}", args.receipt.display());
+    let journal = window_proof::verify(
+        &proof,
+        GUEST_CODE_FOR_ZK_PROOF_ID,
+        WindowRequirements {
+            rows: args.rows,
+            seconds: args.seconds,
+            max_cap: args.max_cap,
+            allow_dev_mode: args.allow_dev_mode,
+        },
+    )
+    .map_err(|rejection| rejection.to_failure())?;
+    print_journal(&journal);
+    println!("🏷️  Proof kind: {}", ProofKind::of(&proof.receipt));
+    if args.max_cap.is_none() {
+        println!("⚠️  No --max-cap given; the cap is the one the prover chose");
+    }
+    println!(
+        "✅ Window sum {} over {} row(s) is within the cap of {}",
+        journal.window_sum, journal.window_count, journal.cap
+    );
+    Ok(())
+}
+
+fn transform_prove(args: &TransformProveArgs) -> Result<(), FailureReason> {
+    let transform = TransformSpec::load(&args.spec)
+        .map_err(|e| FailureReason::new(Outcome::Io, "transform_spec_invalid", e.to_string()))?;
+    let csv_data = input::read_csv(&args.csv)
+        .map_err(|e| FailureReason::new(Outcome::Io, "csv_unreadable", e.to_string()))?;
     
-    // Configuration
-    let csv_file_path = "test_data.csv";
-    let sum_threshold = 1000u64; // Business invariant: sum must be <= 1000
+    println!("🔀 Transforming {} into {} ({} field(s))", args.csv.display(), args.out.display(), transform.fields.len());
+    let (output, counts) = transform.output(&csv_data);
+    let receipt = prove_transform_guest(&TransformGuestInput::Transform(TransformInput::new(csv_data, transform.clone())))?;
     
-    // Agent A: Process CSV and generate proof
-    let receipt = AgentA::process_csv(csv_file_path)?;
+    // The host ran the same code; a different hash means the two builds differ
+    let result = TransformResult::from_bytes(&receipt.journal.bytes).ok_or_else(|| {
+        FailureReason::new(Outcome::CryptoFailure, "journal_decode_failed", "journal is not a transform journal")
+    })?;
+    if result.output_hash != <[u8; 32]>::from(Sha256::digest(output.as_bytes())) {
+        return Err(FailureReason::new(
+            Outcome::CryptoFailure,
+            "output_mismatch",
+            "the guest committed a different output than the host produced",
+        ));
+    }
     
-    println!("\n📋 Receipt Summary:");
-    println!("  - Receipt generated successfully");
+    let unwritable = |e: Box<dyn std::error::Error>| FailureReason::new(Outcome::Io, "output_unwritable", e.to_string());
+    store::write_atomic(&args.out, output.as_bytes()).map_err(|e| unwritable(e.into()))?;
+    let receipt_path = TransformReceipt::path_for(&args.out);
+    TransformReceipt {
+        transform,
+        receipt,
+    }
+    .save(&receipt_path)
+    .map_err(unwritable)?;
+    println!(
+        "📊 {} record(s), {} null cell(s), {} invalid cell(s)",
+        counts.records, counts.null_cells, counts.invalid_cells
+    );
+    println!("💾 Wrote {} and {}", args.out.display(), receipt_path.display());
+    println!("🏷️  Proof kind: {}", ProofKind::current());
+    Ok(())
+}
+
+/// Prove `input` with the transform guest.
+fn prove_transform_guest(input: &TransformGuestInput) -> Result<Receipt, FailureReason> {
+    prove_framed(ZAIK_TRANSFORM_ELF, input, input.frames())
+}
+
+/// Prove `input`, followed by `frames`, with the guest built as `elf`.
+fn prove_framed(elf: &[u8], input: &impl Serialize, frames: Vec<&[u8]>) -> Result<Receipt, FailureReason> {
+    let failed = |e: Box<dyn std::error::Error>| proving_failure(ProveError::classify(e).into());
+    let mut builder = ExecutorEnv::builder();
+    builder.write(input).map_err(|e| failed(e.into()))?;
+    for frame in frames {
+        session::write_frame(&mut builder, frame);
+    }
+    let env = builder.build().map_err(|e| failed(e.into()))?;
+    let started = Instant::now();
+    let receipt = default_prover().prove(env, elf).map_err(|e| failed(e.into()))?.receipt;
+    println!("⏱️  Proved in {:.2?}", started.elapsed());
+    Ok(receipt)
+}
+
+fn transform_migrate(args: &TransformMigrateArgs) -> Result<(), FailureReason> {
+    let io = |reason: &str, e: Box<dyn std::error::Error>| FailureReason::new(Outcome::Io, reason, e.to_string());
+    let migration = MigrationSpec::load(&args.spec).map_err(|e| io("migration_spec_invalid", e))?;
+    let old_data = input::read_csv(&args.old).map_err(|e| io("csv_unreadable", e))?;
+    let migrated = migration.output(&old_data).map_err(|e| io("migration_invalid", e.into()))?;
     
-    // Agent B: Verify receipt and check business invariant
-    let verification_result = AgentB::verify_and_check_invariant(&receipt, sum_threshold)?;
+    println!("🔀 Migrating {} into {}", args.old.display(), args.new.display());
+    for rename in &migration.renames {
+        println!("  - Rename {} → {}", rename.from, rename.to);
+    }
+    for added in &migration.added {
+        println!("  - Add {} (default '{}')", added.name, added.default);
+    }
+    if args.write {
+        store::write_atomic(&args.new, migrated.as_bytes()).map_err(|e| io("csv_unwritable", e.into()))?;
+        println!("💾 Wrote {}", args.new.display());
+    }
+    let new_data = input::read_csv(&args.new).map_err(|e| io("csv_unreadable", e))?;
     
-    println!("\n🎯 Final Results:");
-    println!("==================");
-    println!("✅ zkVM Proof verification: {}", verification_result.verification_passed);
-    println!("✅ Business invariant: {}", verification_result.business_invariant_passed);
-    println!("📊 Column A sum: {} (threshold: {})", 
-             verification_result.result.column_a_sum, 
-             verification_result.sum_threshold);
+    // Fail before proving, with the first line that differs
+    if new_data != migrated {
+        let line = new_data
+            .split_inclusive('\n')
+            .zip(migrated.split_inclusive('\n'))
+            .position(|(actual, expected)| actual != expected)
+            .unwrap_or_else(|| new_data.lines().count().min(migrated.lines().count()));
+        return Err(FailureReason::new(
+            Outcome::UnexpectedData,
+            "migration_mismatch",
+            format!("{} is not {} migrated; first difference at line {}", args.new.display(), args.old.display(), line + 1),
+        ));
+    }
     
-    let all_checks_passed = verification_result.verification_passed 
-        && verification_result.business_invariant_passed;
+    let receipt = prove_transform_guest(&TransformGuestInput::Migration(MigrationInput::new(
+        old_data,
+        new_data,
+        migration.clone(),
+    )))?;
+    let result = MigrationResult::from_bytes(&receipt.journal.bytes).ok_or_else(|| {
+        FailureReason::new(Outcome::CryptoFailure, "journal_decode_failed", "journal is not a migration journal")
+    })?;
+    let receipt_path = MigrationReceipt::path_for(&args.new);
+    MigrationReceipt {
+        migration,
+        receipt,
+    }
+    .save(&receipt_path)
+    .map_err(|e| io("receipt_unwritable", e))?;
+    println!("📊 {} row(s), {} column(s)", result.rows, result.columns);
+    println!("💾 Wrote {}", receipt_path.display());
+    println!("🏷️  Proof kind: {}", ProofKind::current());
+    Ok(())
+}
+
+fn transform_verify_migration(args: &TransformVerifyMigrationArgs) -> Result<(), FailureReason> {
+    let io = |reason: &str, e: Box<dyn std::error::Error>| FailureReason::new(Outcome::Io, reason, e.to_string());
+    let receipt_path = args.receipt.clone().unwrap_or_else(|| MigrationReceipt::path_for(&args.new));
+    let proof = MigrationReceipt::load(&receipt_path).map_err(|e| io("receipt_unreadable", e))?;
+    let new_data = input::read_csv(&args.new).map_err(|e| io("csv_unreadable", e))?;
+    let spec = args
+        .spec
+        .as_deref()
+        .map(MigrationSpec::load)
+        .transpose()
+        .map_err(|e| io("migration_spec_invalid", e))?;
+    let old_data = args
+        .old
+        .as_deref()
+        .map(input::read_csv)
+        .transpose()
+        .map_err(|e| io("csv_unreadable", e))?;
     
-    if all_checks_passed {
-        println!("🎉 SUCCESS: All checks passed!");
-        println!("   - ✅ Deterministic execution proven with RISC Zero zkVM");
-        println!("   - ✅ Business invariant verified within zkVM");
-        println!("   - ✅ CSV processing completed trustlessly");
+    println!("🔍 Verifying {} against {}", args.new.display(), receipt_path.display());
+    let journal = transform_proof::verify_migration(
+        &proof,
+        ZAIK_TRANSFORM_ID,
+        new_data.as_bytes(),
+        spec.as_ref(),
+        old_data.as_deref(),
+        args.allow_dev_mode,
+    )
+    .map_err(|rejection| rejection.to_failure())?;
+    println!("📈 Migration journal:");
+    print_journal(&journal);
+    println!("🏷️  Proof kind: {}", ProofKind::of(&proof.receipt));
+    if spec.is_none() {
+        println!("⚠️  No --spec given; the migration is the one stored with the receipt");
+    }
+    println!("✅ New file is the proven migration of the old one");
+    Ok(())
+}
+
+fn transform_verify(args: &TransformVerifyArgs) -> Result<(), FailureReason> {
+    let io = |reason: &str, e: Box<dyn std::error::Error>| FailureReason::new(Outcome::Io, reason, e.to_string());
+    let receipt_path = args.receipt.clone().unwrap_or_else(|| TransformReceipt::path_for(&args.output));
+    let proof = TransformReceipt::load(&receipt_path).map_err(|e| io("receipt_unreadable", e))?;
+    let output = fs::read(&args.output).map_err(|e| io("output_unreadable", e.into()))?;
+    let spec = args
+        .spec
+        .as_deref()
+        .map(TransformSpec::load)
+        .transpose()
+        .map_err(|e| io("transform_spec_invalid", e))?;
+    let csv_data = args
+        .csv
+        .as_deref()
+        .map(input::read_csv)
+        .transpose()
+        .map_err(|e| io("csv_unreadable", e))?;
+    
+    println!("🔍 Verifying {} against {}", args.output.display(), receipt_path.display());
+    let journal = transform_proof::verify(
+        &proof,
+        ZAIK_TRANSFORM_ID,
+        &output,
+        spec.as_ref(),
+        csv_data.as_deref(),
+        args.allow_dev_mode,
+    )
+    .map_err(|rejection| rejection.to_failure())?;
+    println!("📈 Transform journal:");
+    print_journal(&journal);
+    println!("🏷️  Proof kind: {}", ProofKind::of(&proof.receipt));
+    if spec.is_none() {
+        println!("⚠️  No --spec given; the transform is the one stored with the receipt");
+    }
+    println!("✅ Output is the proven transformation of the CSV");
+    Ok(())
+}
+
+fn replay(args: &ReplayArgs) -> Result<(), FailureReason> {
+    let bundle = ProofBundle::load(&args.bundle)
+        .map_err(|e| FailureReason::new(Outcome::Io, "bundle_unreadable", e.to_string()))?;
+    let bundled: AgentResult = journal::decode(&bundle.receipt.journal.bytes)
+        .map_err(|e| FailureReason::new(Outcome::CryptoFailure, "journal_decode_failed", e.to_string()))?;
+    let csv_data = input::read_csv(&args.csv)
+        .map_err(|e| FailureReason::new(Outcome::Io, "csv_unreadable", e.to_string()))?;
+    
+    println!("🔁 Replaying {} on {}", args.bundle.display(), args.csv.display());
+    print_metadata(&bundle.metadata);
+    if bundle.key.options.column_hash == ColumnHashMode::Chunked {
+        return Err(FailureReason::new(
+            Outcome::Io,
+            "chunked_unsupported",
+            "a chunked bundle is composed from chunk receipts; replay proves whole files only",
+        ));
+    }
+    if bundled.dataset_files > 0 {
+        return Err(FailureReason::new(
+            Outcome::Io,
+            "replay_unsupported",
+            "the bundle proves a dataset of several files; replay proves whole files only",
+        ));
+    }
+    if bundled.aux_tables_hash != [0u8; 32] {
+        return Err(FailureReason::new(
+            Outcome::Io,
+            "replay_unsupported",
+            "the bundle was proven with aux tables, which it does not store",
+        ));
+    }
+    if zaik_core::csv_hash(&csv_data) != bundle.key.csv_hash {
+        println!("⚠️  CSV hash differs from the one the bundle was proven over");
+    }
+    
+    // Same options and salt as the original run, so only the data can differ
+    let input = CsvProcessingInput {
+        sum_bound: SumBound {
+            op: bundled.sum_comparison,
+            threshold: bundled.sum_threshold,
+        },
+        invariants: bundled.invariants.iter().map(|result| result.invariant).collect(),
+        sum_salt: bundle.sum_opening.salt,
+        inline_limit: bundled.inline_limit,
+        max_rows: bundled.max_rows,
+        ..CsvProcessingInput::from_data(csv_data, bundle.key.options)
+    };
+    let replay_failed = |e: Box<dyn std::error::Error>| FailureReason::new(Outcome::Io, "replay_failed", e.to_string());
+    let (replayed, journal_bytes) = if args.native {
+        let result = zaik_core::process(&input).expect("input hash is computed from the data");
+        let bytes = result.to_bytes();
+        (result, bytes)
     } else {
-        println!("❌ FAILURE: Some checks failed!");
-        std::process::exit(1);
+        let mut builder = ExecutorEnv::builder();
+        session::write_input(&mut builder, &GuestInput::Csv(input)).map_err(replay_failed)?;
+        let env = builder.build().map_err(|e| replay_failed(e.into()))?;
+        let session = default_executor()
+            .execute(env, GUEST_CODE_FOR_ZK_PROOF_ELF)
+            .map_err(|e| proving_failure(ProveError::classify(e).into()))?;
+        let result: AgentResult = journal::decode(&session.journal.bytes).map_err(replay_failed)?;
+        (result, session.journal.bytes)
+    };
+    
+    let diffs = journal::diff(&bundled, &replayed);
+    if diffs.is_empty() && journal_bytes == bundle.receipt.journal.bytes {
+        println!("✅ Replayed journal matches the bundle");
+        return Ok(());
+    }
+    
+    println!("❌ Replayed journal diverges from the bundle:");
+    if let Some(offset) = journal_bytes
+        .iter()
+        .zip(&bundle.receipt.journal.bytes)
+        .position(|(a, b)| a != b)
+    {
+        println!("  - First differing journal byte: {}", offset);
+    }
+    for diff in &diffs {
+        println!("  - {}: bundled {}, replayed {}", diff.name, diff.expected, diff.actual);
+    }
+    Err(FailureReason::new(
+        Outcome::CryptoFailure,
+        "replay_diverged",
+        match diffs.first() {
+            Some(first) => format!("journal diverges starting at field {}", first.name),
+            None => "journal encodings differ".to_string(),
+        },
+    ))
+}
+
+/// Journal bytes as the guest commits them: risc0 serde words, little-endian.
+fn migrate(args: &MigrateArgs) -> Result<(), FailureReason> {
+    let unreadable = |e: Box<dyn std::error::Error>| FailureReason::new(Outcome::Io, "bundle_unreadable", e.to_string());
+    let bytes = untrusted::read(&args.bundle, untrusted::MAX_BUNDLE_BYTES).map_err(|e| unreadable(e.into()))?;
+    let migration = migrate::migrate(&bytes).map_err(unreadable)?;
+    
+    println!("📦 Migrating {}", args.bundle.display());
+    println!("  - Bundle layout: {}", migration.from);
+    println!("  - Journal layout: {}", migration.journal);
+    if migration.from == BundleLayout::Current {
+        println!("✅ Bundle is already in the current layout");
+    } else {
+        let out = args.out.clone().unwrap_or_else(|| {
+            args.bundle
+                .with_file_name(format!("{}.bundle", migration.bundle.key.digest()))
+        });
+        let encoded = bincode::serialize(&migration.bundle).map_err(|e| unreadable(e.into()))?;
+        store::write_atomic(&out, &encoded)
+            .map_err(|e| FailureReason::new(Outcome::Io, "bundle_unwritable", e.to_string()))?;
+        println!("💾 Wrote migrated bundle: {}", out.display());
+        if DetachedSignature::path_for(&args.bundle).exists() {
+            println!("⚠️  The original's signature does not cover the migrated file; sign it again");
+        }
+    }
+    
+    // The journal is proven and cannot be rewritten
+    if !migration.is_usable() {
+        return Err(FailureReason::new(
+            Outcome::Conditional,
+            "journal_layout_outdated",
+            format!(
+                "journal layout is {}; verify it with the release that wrote it or prove the data again",
+                migration.journal
+            ),
+        ));
+    }
+    Ok(())
+}
+
+fn proving_failure(e: Box<dyn std::error::Error>) -> FailureReason {
+    match e.downcast_ref::<ProveError>() {
+        Some(ProveError::DeadlineExceeded(exceeded)) => exceeded.to_failure(),
+        Some(error) => FailureReason::new(Outcome::Io, error.reason(), e.to_string()),
+        None => FailureReason::new(Outcome::Io, "proving_failed", e.to_string()),
+    }
+}
+
+fn backfill(args: &BackfillArgs) -> Result<(), FailureReason> {
+    let periods = Period::range(args.from, args.to)
+        .map_err(|e| FailureReason::new(Outcome::Io, "invalid_period_range", e))?;
+    let policy = args.proving.policy.load()?;
+    args.proving.processing.check_supported(&policy, false)?;
+    args.proving.check_dataset(false)?;
+    let store = args.proving.open_store()?;
+    let events = args.proving.open_events()?;
+    
+    println!("🗂️  Backfilling {} period(s) from {} to {}", periods.len(), args.from, args.to);
+    
+    let deadline = args.proving.deadline();
+    let mut chain = StateChain::default();
+    let mut records = Vec::new();
+    for period in &periods {
+        let file = args.period_file(period);
+        println!("\n📅 Period {}: {}", period, file);
+        // Periods past the deadline are reported rather than attempted
+        let status = if let Some(exceeded) = deadline.and_then(|d| d.check("backfill").err()) {
+            PeriodStatus::Failed {
+                reason: "deadline_exceeded".to_string(),
+                message: exceeded.to_string(),
+            }
+        } else if !Path::new(&file).exists() {
+            PeriodStatus::Missing
+        } else {
+            attest_period(period, &policy, args, &store, &events, deadline, &mut chain)
+        };
+        records.push(PeriodRecord {
+            period: period.to_string(),
+            file,
+            status,
+        });
+    }
+    
+    let report = BackfillReport {
+        from: args.from.to_string(),
+        to: args.to.to_string(),
+        policy_version: policy.version(),
+        attested: records.iter().filter(|r| matches!(r.status, PeriodStatus::Attested { .. })).count(),
+        chain_head: hex::encode(chain.head()),
+        periods: records,
+    };
+    
+    println!("\n📋 Backfill Summary:");
+    for record in &report.periods {
+        let status = match &record.status {
+            PeriodStatus::Attested { .. } => "✅ attested",
+            PeriodStatus::Rejected { .. } => "❌ rejected",
+            PeriodStatus::Missing => "⚠️  missing",
+            PeriodStatus::Failed { .. } => "❌ failed",
+        };
+        println!("  - {}: {}", record.period, status);
+    }
+    println!("🔗 Chain head: {}", report.chain_head);
+    
+    if let Some(path) = &args.report {
+        let json = serde_json::to_string_pretty(&report).expect("BackfillReport is always serializable");
+        fs::write(path, json)
+            .map_err(|e| FailureReason::new(Outcome::Io, "report_unwritable", e.to_string()))?;
+        println!("💾 Wrote backfill report: {}", path.display());
+    }
+    
+    let unattested = report.periods.len() - report.attested;
+    if unattested == 0 {
+        return Ok(());
+    }
+    let timed_out = report.count(|s| matches!(s, PeriodStatus::Failed { reason, .. } if reason == "deadline_exceeded"));
+    let outcome = if report.count(|s| matches!(s, PeriodStatus::Rejected { .. })) > 0 {
+        Outcome::PolicyReject
+    } else if timed_out > 0 {
+        Outcome::DeadlineExceeded
+    } else {
+        Outcome::Io
+    };
+    Err(FailureReason::new(
+        outcome,
+        if outcome == Outcome::DeadlineExceeded { "deadline_exceeded" } else { "backfill_incomplete" },
+        format!("{} of {} period(s) not attested", unattested, report.periods.len()),
+    )
+    .with_policy_version(policy.version()))
+}
+
+/// Prove and verify one period's file, linking it into `chain` if attested.
+fn attest_period(
+    period: &Period,
+    policy: &Policy,
+    args: &BackfillArgs,
+    store: &ProofStore,
+    events: &Option<JsonLinesEvents<fs::File>>,
+    deadline: Option<Deadline>,
+    chain: &mut StateChain,
+) -> PeriodStatus {
+    let failed = |failure: FailureReason| PeriodStatus::Failed {
+        reason: failure.reason,
+        message: failure.message,
+    };
+    let bundle = match AgentA::process_csv(
+        &args.period_file(period),
+        policy,
+        &args.proving,
+        store,
+        events.as_ref().map(|e| e as &(dyn ProverEvents + Sync)),
+        deadline,
+    ) {
+        Ok(bundle) => bundle,
+        Err(e) => return failed(proving_failure(e)),
+    };
+    let environment = match record_environment(None, &store.path(&bundle.key)) {
+        Ok(environment) => environment,
+        Err(failure) => return failed(failure),
+    };
+    let verification = match AgentB::verify_and_check_invariant(
+        &bundle,
+        policy,
+        false,
+        None,
+        Some(&environment),
+    ) {
+        Ok(verification) => verification,
+        Err(e) => return failed(FailureReason::new(Outcome::CryptoFailure, "journal_decode_failed", e.to_string())),
+    };
+    match &verification.rejection {
+        Some(Rejection::PolicyFailed(outcome)) => {
+            return PeriodStatus::Rejected {
+                explanation: explain::explain(outcome),
+            }
+        }
+        Some(rejection) => return failed(rejection.to_failure()),
+        None => {}
+    }
+    let journal_digest: [u8; 32] = Sha256::digest(&bundle.receipt.journal.bytes).into();
+    PeriodStatus::Attested {
+        csv_hash: hex::encode(verification.result.csv_hash),
+        journal_digest: hex::encode(journal_digest),
+        proof_kind: verification.proof_kind,
+        chain: hex::encode(chain.link(period, &journal_digest)),
+    }
+}
+
+fn gc(args: &GcArgs) -> Result<(), FailureReason> {
+    let store = ProofStore::open(&args.store)
+        .map_err(|e| FailureReason::new(Outcome::Io, "store_unavailable", e.to_string()))?;
+    let retention = RetentionPolicy {
+        keep_for: std::time::Duration::from_secs(args.keep_days * 24 * 60 * 60),
+        archive: args.archive.clone(),
+    };
+    let report = store
+        .gc(&retention, std::time::SystemTime::now(), args.dry_run)
+        .map_err(|e| FailureReason::new(Outcome::Io, "gc_failed", e.to_string()))?;
+    
+    println!("🧹 Proof store {}{}", args.store.display(), if args.dry_run { " (dry run)" } else { "" });
+    println!("  - Kept: {}", report.kept);
+    println!("  - Archived: {}", report.archived);
+    println!("  - Deleted: {}", report.deleted);
+    for path in &report.unreadable {
+        println!("  - ⚠️  Unreadable, left in place: {}", path);
+    }
+    Ok(())
+}
+
+/// Execute the guest on a one-row CSV, which needs both the guest ELF and a
+/// working executor backend.
+fn check_prover() -> Check {
+    let input = GuestInput::Csv(guest_input("a\n1\n".to_string(), ProcessingOptions::default(), &Policy::new(1), Vec::new()));
+    let mut builder = ExecutorEnv::builder();
+    let session = session::write_input(&mut builder, &input)
+        .and_then(|()| Ok(builder.build()?))
+        .and_then(|env| Ok(default_executor().execute(env, GUEST_CODE_FOR_ZK_PROOF_ELF)?));
+    match session {
+        Ok(session) => Check::pass("prover", format!("guest executed in {} cycles", session.cycles())),
+        Err(e) => Check::fail("prover", ProveError::classify(e).to_string()),
+    }
+}
+
+fn health(args: &HealthArgs) -> Result<(), FailureReason> {
+    let mut checks = vec![check_prover()];
+    checks.extend(health::check_bonsai());
+    checks.push(health::check_store(&args.store));
+    if let Some(path) = &args.prover_key {
+        checks.push(health::check_key("prover_key", path));
+    }
+    if let Some(path) = &args.verifier_key {
+        checks.push(health::check_key("verifier_key", path));
+    }
+    if let Some(path) = &args.policy {
+        checks.push(health::check_policy(path));
+    }
+    let report = HealthReport::new(checks);
+    
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report).expect("HealthReport is always serializable"));
+    } else {
+        println!("🩺 Health checks");
+        for check in &report.checks {
+            println!("  {} {}: {}", if check.ok { "✅" } else { "❌" }, check.name, check.detail);
+        }
+    }
+    
+    if report.ready {
+        Ok(())
+    } else {
+        let failing: Vec<&str> = report.failing().map(|check| check.name.as_str()).collect();
+        Err(FailureReason::new(
+            Outcome::Io,
+            "not_ready",
+            format!("failing checks: {}", failing.join(", ")),
+        ))
+    }
+}
+
+fn simulate(args: &SimulateArgs) -> Result<(), FailureReason> {
+    if !args.json {
+        println!("🧪 Simulating guest on {} (nothing is proven)", args.csv_file);
+    }
+    
+    let policy = args.policy.load_for(&args.csv_file, false, &args.processing.options().dialect)?;
+    args.processing.check_supported(&policy, false)?;
+    let csv_data = input::read_csv(&args.csv_file)
+        .map_err(|e| FailureReason::new(Outcome::Io, "csv_unreadable", e.to_string()))?;
+    let options = args.processing.options();
+    let aux_tables = args
+        .processing
+        .aux_tables()
+        .map_err(|e| FailureReason::new(Outcome::Io, "aux_table_unreadable", e.to_string()))?;
+    let input = guest_input(csv_data, options, &policy, aux_tables);
+    
+    // Same computation the guest runs, compiled for the host
+    let result = zaik_core::process(&input).expect("input hash is computed from the data");
+    
+    if args.json {
+        println!("{}", journal::to_json(&result));
+        return Ok(());
+    }
+    
+    println!("📈 Journal the guest would commit:");
+    print_journal(&result);
+    println!("🏷️  Proof kind: {}", ProofKind::Simulated);
+    
+    let (processed, _) = zaik_core::limit_rows(&input.csv_data, input.max_rows, options.dialect);
+    let column_a_sum = zaik_core::aggregate_column_a(processed, options.column_hash, options.dialect).sum;
+    if !result.sum_disclosed {
+        println!("🔑 Sum opening: sum {}, salt {}", column_a_sum, hex::encode(input.sum_salt));
+    }
+    let policy_outcome = policy.evaluate(column_a_sum, &result);
+    print_policy_trace(&policy_outcome);
+    println!("💬 Explanation: {}", explain::explain(&policy_outcome));
+    
+    if !policy_outcome.passed() {
+        return Err(FailureReason::new(
+            Outcome::PolicyReject,
+            "invariant_violated",
+            explain::explain(&policy_outcome),
+        )
+        .with_policy_version(policy.version()));
     }
     
     Ok(())
@@ -0,0 +1,257 @@
+//! Proof marketplace: what relying parties want proven and what prover
+//! agents offer to prove, matched and kept in a proof store.
+//!
+//! A [`ProofRequest`] names the dataset (its CSV hash and size), the
+//! invariant the proof must establish, the most the requester pays and when
+//! the bundle is due. A [`ProofOffer`] is a prover's standing price, the
+//! largest file it takes, the aggregates it proves, how long it needs and
+//! how many requests it takes on. [`Marketplace::match_pending`] pairs every
+//! open request with the cheapest offer that can meet it and records the
+//! [`Match`]. Listings and matches are JSON lines under `market/` in the
+//! store, served by the `registry` binary.
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use zaik_core::invariant::{Aggregate, Invariant};
+
+/// The data a proof must cover.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DatasetSpec {
+    /// Hex SHA-256 of the CSV.
+    pub csv_hash: String,
+    /// Data rows in the CSV, for offers that cap their input size.
+    pub rows: u64,
+}
+
+/// A relying party asking for a proof.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofRequest {
+    pub id: String,
+    /// Hex public key of the requesting agent.
+    pub requester: String,
+    pub dataset: DatasetSpec,
+    pub invariant: Invariant,
+    /// Most the requester pays, in the market's unit.
+    pub max_price: u64,
+    /// Seconds since the Unix epoch by which the bundle is due.
+    pub deadline: u64,
+}
+
+/// A prover agent's standing offer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofOffer {
+    pub id: String,
+    /// Hex public key the prover signs its bundles with; see
+    /// [`crate::signing`].
+    pub prover: String,
+    /// Price per proof, in the market's unit.
+    pub price: u64,
+    /// Largest dataset taken, in data rows.
+    pub max_rows: u64,
+    /// Aggregates this prover proves; empty for all of them.
+    #[serde(default)]
+    pub aggregates: Vec<Aggregate>,
+    /// Seconds the prover needs from a match to a bundle.
+    pub turnaround: u64,
+    /// Requests taken on before the offer is used up.
+    pub capacity: u32,
+}
+
+impl ProofOffer {
+    /// Whether this offer can meet `request` if matched at `now`.
+    pub fn fits(&self, request: &ProofRequest, now: u64) -> bool {
+        self.price <= request.max_price
+            && request.dataset.rows <= self.max_rows
+            && (self.aggregates.is_empty() || self.aggregates.contains(&request.invariant.aggregate))
+            && now.saturating_add(self.turnaround) <= request.deadline
+    }
+}
+
+/// A request paired with the offer that will prove it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Match {
+    pub request: String,
+    pub offer: String,
+    pub prover: String,
+    /// The offer's price.
+    pub price: u64,
+    /// The request's deadline.
+    pub due_at: u64,
+    /// Seconds since the Unix epoch.
+    pub matched_at: u64,
+}
+
+/// Pair open requests with offers, earliest deadline first. Each request
+/// gets the cheapest offer with capacity left that [`ProofOffer::fits`] it,
+/// ties going to the quicker turnaround and then the earlier listing.
+/// Requests already in `existing` or past their deadline are skipped, and
+/// matches in `existing` count against their offer's capacity.
+pub fn matches(requests: &[ProofRequest], offers: &[ProofOffer], existing: &[Match], now: u64) -> Vec<Match> {
+    let mut used: Vec<u32> = offers
+        .iter()
+        .map(|offer| existing.iter().filter(|m| m.offer == offer.id).count() as u32)
+        .collect();
+    let mut open: Vec<&ProofRequest> = requests
+        .iter()
+        .filter(|request| request.deadline > now && !existing.iter().any(|m| m.request == request.id))
+        .collect();
+    open.sort_by_key(|request| request.deadline);
+
+    let mut made = Vec::new();
+    for request in open {
+        let best = offers
+            .iter()
+            .enumerate()
+            .filter(|(i, offer)| used[*i] < offer.capacity && offer.fits(request, now))
+            .min_by_key(|(i, offer)| (offer.price, offer.turnaround, *i));
+        if let Some((i, offer)) = best {
+            used[i] += 1;
+            made.push(Match {
+                request: request.id.clone(),
+                offer: offer.id.clone(),
+                prover: offer.prover.clone(),
+                price: offer.price,
+                due_at: request.deadline,
+                matched_at: now,
+            });
+        }
+    }
+    made
+}
+
+/// Requests, offers and matches of one store, as JSON lines.
+pub struct Marketplace {
+    root: PathBuf,
+}
+
+impl Marketplace {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn requests(&self) -> Result<Vec<ProofRequest>, Box<dyn Error>> {
+        read_lines(&self.root.join("requests.jsonl"))
+    }
+
+    pub fn offers(&self) -> Result<Vec<ProofOffer>, Box<dyn Error>> {
+        read_lines(&self.root.join("offers.jsonl"))
+    }
+
+    pub fn matches(&self) -> Result<Vec<Match>, Box<dyn Error>> {
+        read_lines(&self.root.join("matches.jsonl"))
+    }
+
+    /// List `request`; its id must be new and its deadline after `now`.
+    pub fn post_request(&self, request: &ProofRequest, now: u64) -> Result<(), Box<dyn Error>> {
+        if request.id.is_empty() || self.requests()?.iter().any(|listed| listed.id == request.id) {
+            return Err(format!("request id '{}' is empty or already listed", request.id).into());
+        }
+        if request.deadline <= now {
+            return Err(format!("request '{}' is already past its deadline", request.id).into());
+        }
+        append(&self.root.join("requests.jsonl"), request)
+    }
+
+    /// List `offer`; its id must be new and it must take at least one request.
+    pub fn post_offer(&self, offer: &ProofOffer) -> Result<(), Box<dyn Error>> {
+        if offer.id.is_empty() || self.offers()?.iter().any(|listed| listed.id == offer.id) {
+            return Err(format!("offer id '{}' is empty or already listed", offer.id).into());
+        }
+        if offer.capacity == 0 {
+            return Err(format!("offer '{}' has no capacity", offer.id).into());
+        }
+        append(&self.root.join("offers.jsonl"), offer)
+    }
+
+    /// Match what is open at `now` and record the new matches. Callers
+    /// sharing a store must not run this concurrently.
+    pub fn match_pending(&self, now: u64) -> Result<Vec<Match>, Box<dyn Error>> {
+        let made = matches(&self.requests()?, &self.offers()?, &self.matches()?, now);
+        for made in &made {
+            append(&self.root.join("matches.jsonl"), made)?;
+        }
+        Ok(made)
+    }
+}
+
+fn read_lines<T: DeserializeOwned>(path: &Path) -> Result<Vec<T>, Box<dyn Error>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    fs::read_to_string(path)?
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| Ok(serde_json::from_str(line)?))
+        .collect()
+}
+
+fn append<T: Serialize>(path: &Path, value: &T) -> Result<(), Box<dyn Error>> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = fs::OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(value)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zaik_core::ComparisonOp;
+
+    fn request(id: &str, rows: u64, aggregate: Aggregate, max_price: u64, deadline: u64) -> ProofRequest {
+        ProofRequest {
+            id: id.into(),
+            requester: "aa".into(),
+            dataset: DatasetSpec { csv_hash: "00".repeat(32), rows },
+            invariant: Invariant { column: 0, aggregate, op: ComparisonOp::Le, bound: 1000 },
+            max_price,
+            deadline,
+        }
+    }
+
+    fn offer(id: &str, price: u64, max_rows: u64, aggregates: Vec<Aggregate>, turnaround: u64) -> ProofOffer {
+        ProofOffer {
+            id: id.into(),
+            prover: format!("prover-{}", id),
+            price,
+            max_rows,
+            aggregates,
+            turnaround,
+            capacity: 1,
+        }
+    }
+
+    #[test]
+    fn cheapest_fitting_offer_wins_and_capacity_is_used_up() {
+        let requests = [
+            request("late", 100, Aggregate::Sum, 50, 1000),
+            request("early", 100, Aggregate::Sum, 50, 500),
+        ];
+        let offers = [
+            offer("pricey", 60, 1000, vec![], 10),
+            offer("cheap", 20, 1000, vec![], 10),
+            offer("fair", 30, 1000, vec![Aggregate::Sum], 10),
+        ];
+        let made = matches(&requests, &offers, &[], 100);
+        let pairs: Vec<_> = made.iter().map(|m| (m.request.as_str(), m.offer.as_str())).collect();
+        // The earlier deadline takes the cheapest offer; the next goes to the runner-up
+        assert_eq!(pairs, [("early", "cheap"), ("late", "fair")]);
+        assert!(matches(&requests, &offers, &made, 100).is_empty());
+    }
+
+    #[test]
+    fn offers_that_cannot_meet_a_request_are_skipped() {
+        let requests = [request("r", 5000, Aggregate::Max, 50, 200)];
+        let too_small = offer("small", 10, 1000, vec![], 10);
+        let wrong_aggregate = offer("sums", 10, 10_000, vec![Aggregate::Sum], 10);
+        let too_slow = offer("slow", 10, 10_000, vec![], 150);
+        assert!(matches(&requests, &[too_small, wrong_aggregate, too_slow], &[], 100).is_empty());
+        // Past its deadline, a request is never matched
+        assert!(matches(&requests, &[offer("ok", 10, 10_000, vec![], 10)], &[], 200).is_empty());
+    }
+}
@@ -0,0 +1,125 @@
+//! Provenance recorded in every bundle: which host, zkVM, guest, and
+//! toolchain produced it, and the compressed file the CSV came from.
+
+use risc0_zkvm::sha::Digest as Digest32;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+const UNKNOWN: &str = "unknown";
+
+/// Versions and build fingerprint of the prover that wrote a bundle.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundleMetadata {
+    /// Version of this host crate.
+    pub host_version: String,
+    /// Version of `risc0-zkvm` the receipt was produced with.
+    pub risc0_version: String,
+    /// Hex guest image ID.
+    pub image_id: String,
+    /// `rustc -V` output, target triple and profile of the host build.
+    pub toolchain: String,
+    /// Hex SHA-256 over all of the above.
+    pub fingerprint: String,
+    /// The compressed file the CSV was read from; `None` for a plain CSV.
+    pub input: Option<CompressedInput>,
+}
+
+/// How a CSV input was compressed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    Gzip,
+    Zstd,
+}
+
+impl fmt::Display for Compression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Compression::Gzip => "gzip",
+            Compression::Zstd => "zstd",
+        })
+    }
+}
+
+/// A `.csv.gz` or `.csv.zst` input. The proof covers the decompressed CSV;
+/// the compressed hash ties it back to the file that was actually exported.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompressedInput {
+    pub compression: Compression,
+    /// Hex SHA-256 of the file as read.
+    pub compressed_sha256: String,
+    /// Hex SHA-256 of the decompressed CSV.
+    pub csv_sha256: String,
+}
+
+impl BundleMetadata {
+    /// Metadata for a bundle written by this build.
+    pub fn new(image_id: [u32; 8]) -> Self {
+        let toolchain = format!(
+            "{} ({}, {})",
+            env!("ZAIK_RUSTC_VERSION"),
+            env!("ZAIK_TARGET"),
+            env!("ZAIK_PROFILE")
+        );
+        Self::from_parts(env!("CARGO_PKG_VERSION"), risc0_zkvm::VERSION, image_id, &toolchain)
+    }
+
+    /// Metadata for a bundle from before metadata was recorded; only the
+    /// image ID, taken from its key, is known.
+    pub fn unknown(image_id: [u32; 8]) -> Self {
+        Self::from_parts(UNKNOWN, UNKNOWN, image_id, UNKNOWN)
+    }
+
+    /// Record the compressed file the CSV was read from.
+    pub fn with_input(mut self, input: Option<CompressedInput>) -> Self {
+        self.input = input;
+        self
+    }
+
+    fn from_parts(host_version: &str, risc0_version: &str, image_id: [u32; 8], toolchain: &str) -> Self {
+        let image_id = Digest32::from(image_id).to_string();
+        let mut hasher = Sha256::new();
+        for part in [host_version, risc0_version, &image_id, toolchain] {
+            hasher.update((part.len() as u32).to_le_bytes());
+            hasher.update(part.as_bytes());
+        }
+        Self {
+            host_version: host_version.to_string(),
+            risc0_version: risc0_version.to_string(),
+            image_id,
+            toolchain: toolchain.to_string(),
+            fingerprint: hex::encode(hasher.finalize()),
+            input: None,
+        }
+    }
+
+    /// Differences from `current` that matter when verifying or replaying
+    /// this bundle, most serious first. Empty when fully compatible.
+    pub fn compatibility(&self, current: &BundleMetadata) -> Vec<String> {
+        let mut notes = Vec::new();
+        if self.image_id != current.image_id {
+            notes.push(format!(
+                "guest image {} differs from this build's {}; the receipt will not verify here",
+                self.image_id, current.image_id
+            ));
+        }
+        if major(&self.risc0_version) != major(&current.risc0_version) {
+            notes.push(format!(
+                "proven with risc0-zkvm {}, this build uses {}; receipt formats may be incompatible",
+                self.risc0_version, current.risc0_version
+            ));
+        }
+        if self.host_version != current.host_version {
+            notes.push(format!("written by host {}, this is {}", self.host_version, current.host_version));
+        }
+        if self.toolchain != current.toolchain {
+            notes.push(format!("built with {}, this build uses {}", self.toolchain, current.toolchain));
+        }
+        notes
+    }
+}
+
+fn major(version: &str) -> &str {
+    version.split('.').next().unwrap_or(version)
+}
@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+
+/// Final verdict of a verification run. Each variant maps to a stable
+/// process exit code so orchestration systems can branch on it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Outcome {
+    /// Receipt verified and every invariant holds.
+    Accept,
+    /// Invariants hold, but the artifact carries reduced assurance.
+    Conditional,
+    /// Receipt verified, but a business invariant failed.
+    PolicyReject,
+    /// Receipt or journal failed cryptographic verification.
+    CryptoFailure,
+    /// The input or receipt could not be read or produced.
+    Io,
+    /// The request's deadline passed before it finished.
+    DeadlineExceeded,
+    /// The CSV is not one the verifier said it expects.
+    UnexpectedData,
+    /// Every check passed, but the sum breaks sharply from its history;
+    /// hold it for a human.
+    Investigate,
+}
+
+impl Outcome {
+    pub fn exit_code(self) -> i32 {
+        match self {
+            Outcome::Accept => 0,
+            Outcome::Conditional => 2,
+            Outcome::PolicyReject => 3,
+            Outcome::CryptoFailure => 4,
+            Outcome::Io => 5,
+            Outcome::DeadlineExceeded => 6,
+            Outcome::UnexpectedData => 7,
+            Outcome::Investigate => 8,
+        }
+    }
+}
+
+/// Machine-readable reason for any outcome other than [`Outcome::Accept`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailureReason {
+    pub outcome: Outcome,
+    pub exit_code: i32,
+    /// Stable identifier, e.g. `invariant_violated`.
+    pub reason: String,
+    pub message: String,
+    /// Hash of the policy in force when the decision was made.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub policy_version: Option<String>,
+}
+
+impl FailureReason {
+    pub fn new(outcome: Outcome, reason: &str, message: impl Into<String>) -> Self {
+        Self {
+            outcome,
+            exit_code: outcome.exit_code(),
+            reason: reason.to_string(),
+            message: message.into(),
+            policy_version: None,
+        }
+    }
+
+    pub fn with_policy_version(mut self, version: String) -> Self {
+        self.policy_version = Some(version);
+        self
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("FailureReason is always serializable")
+    }
+}
@@ -0,0 +1,489 @@
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use crate::alerts::AlertSeverities;
+use crate::assurance::{Assurance, AssuranceEvidence, AssuranceTier};
+use crate::budgets::Budget;
+use crate::canonical;
+use crate::cells::CellLimit;
+use crate::columns::{self, ColumnMatch};
+use crate::environment::{EnvironmentPolicy, RecordedManifest};
+use crate::privacy::PrivacyBudget;
+use crate::reputation::Reputation;
+use crate::templates::InvariantTemplate;
+use crate::trend::Trend;
+use zaik_core::invariant::{Invariant, MAX_INVARIANTS};
+use zaik_core::{AgentResult, ColumnHashMode, ComparisonOp, SumBound, MAX_INLINE_BYTES};
+
+/// Business rules Agent B applies to a verified journal.
+///
+/// Policies are loaded from TOML files:
+/// ```toml
+/// sum_threshold = 1000
+/// comparison = "le"  # or "lt"; defaults to "le"
+///
+/// # Optional: further invariants the guest evaluates in the same proof
+/// [[invariants]]
+/// column = 1
+/// aggregate = "max"  # sum, count, min, max, duplicates or decreases
+/// op = "le"          # lt, le, ge or gt
+/// bound = 500
+///
+/// # Or by name; see `InvariantTemplate`. Columns may also be named by
+/// # header, resolved against the CSV; see `columns`
+/// [[templates]]
+/// name = "no_duplicates"
+/// column = "invoice_id"
+///
+/// # Optional: reject files with too many cells that are blank or not
+/// # numbers, which the sum skips; see `CellLimit`
+/// [[cell_limits]]
+/// max_unparseable_percent = 1
+///
+/// # Optional: reject files whose header repeats a name, or with more
+/// # rows than this whose field count differs from the header's
+/// reject_duplicate_headers = true
+/// max_ragged_rows = 0
+///
+/// # Optional: commit CSVs up to this many bytes verbatim in the journal
+/// inline_max_bytes = 4096
+///
+/// # Optional: have the guest process at most this many data rows; runs
+/// # that had to skip rows are rejected unless truncation is allowed
+/// max_rows = 100000
+/// allow_truncated = false
+///
+/// # Optional: limit what overlapping disclosed releases give away; see
+/// # `PrivacyBudget`
+/// [privacy]
+/// min_row_difference = 10
+///
+/// # Optional: cap what each tenant's accepted sums add up to per
+/// # reporting period; see `Budget`
+/// [budget]
+/// cap = 50000
+///
+/// # Optional: how severe each failed outcome is, for the sinks given
+/// # with `--alerts`; see `AlertSeverities`
+/// [alerts]
+/// crypto-failure = "critical"
+///
+/// # Optional: sums above this also need a Groth16 receipt and human
+/// # review; see `Assurance`
+/// [assurance]
+/// high_value = 100000
+///
+/// # Optional: only accept proofs from approved, signed environments; see
+/// # `EnvironmentPolicy`
+/// [environment]
+/// signers = ["<prover public key hex>"]
+/// [[environment.approved]]
+/// name = "release-0.1.0"
+/// digest = "<manifest digest>"
+///
+/// # Optional: hold sums that move sharply from the tenant's history for
+/// # investigation; see `Trend`
+/// [trend]
+/// max_change_percent = 50
+///
+/// # Optional: bundles from provers whose reputation in the store scores
+/// # below this need human review; see `Reputation`
+/// [reputation]
+/// min_score = 80
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Policy {
+    /// Bound on column A's sum.
+    pub sum_threshold: u64,
+    /// How the sum is compared with `sum_threshold`.
+    #[serde(default)]
+    pub comparison: ComparisonOp,
+    /// Further invariants proven alongside the sum bound.
+    #[serde(default)]
+    pub invariants: Vec<Invariant>,
+    /// Named templates, expanded after `invariants`.
+    #[serde(default)]
+    pub templates: Vec<InvariantTemplate>,
+    /// Limits on the empty and unparseable cells the journal counts. Left
+    /// out of the hash when empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cell_limits: Vec<CellLimit>,
+    /// Reject journals that flag duplicate header names. Left out of the
+    /// hash when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reject_duplicate_headers: Option<bool>,
+    /// Most ragged rows accepted. Left out of the hash when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_ragged_rows: Option<u64>,
+    /// Inline CSVs up to this size in the journal. Left out of the hash
+    /// when unset, so existing policies keep their version.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inline_max_bytes: Option<u64>,
+    /// Disclosure limits across releases; checked by the prover before
+    /// proving, so not part of what the guest sees.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub privacy: Option<PrivacyBudget>,
+    /// Most data rows the guest processes. Left out of the hash when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_rows: Option<u64>,
+    /// Accept runs that stopped at `max_rows`; rejected otherwise.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allow_truncated: Option<bool>,
+    /// Cumulative cap per tenant and period; checked against the store's
+    /// ledger after verification, so not part of what the guest sees.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget: Option<Budget>,
+    /// Severity of each outcome worth an alert. Left out of the hash when
+    /// unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alerts: Option<AlertSeverities>,
+    /// Evidence required of high-value sums. Left out of the hash when
+    /// unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assurance: Option<Assurance>,
+    /// Environments proofs must come from. Left out of the hash when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub environment: Option<EnvironmentPolicy>,
+    /// Change from the tenant's history that is held for investigation;
+    /// checked against the store's history after verification, so not part
+    /// of what the guest sees.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trend: Option<Trend>,
+    /// Prover score below which a human must review the bundle. Left out
+    /// of the hash when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reputation: Option<Reputation>,
+}
+
+impl Policy {
+    pub fn new(sum_threshold: u64) -> Self {
+        Self {
+            sum_threshold,
+            comparison: ComparisonOp::default(),
+            invariants: Vec::new(),
+            templates: Vec::new(),
+            cell_limits: Vec::new(),
+            reject_duplicate_headers: None,
+            max_ragged_rows: None,
+            inline_max_bytes: None,
+            privacy: None,
+            max_rows: None,
+            allow_truncated: None,
+            budget: None,
+            alerts: None,
+            assurance: None,
+            environment: None,
+            trend: None,
+            reputation: None,
+        }
+    }
+
+    /// Every invariant handed to the guest, in journal order, with the rule
+    /// name it is reported under.
+    pub fn named_invariants(&self) -> Vec<(String, Invariant)> {
+        let explicit = self
+            .invariants
+            .iter()
+            .enumerate()
+            .map(|(index, invariant)| (format!("invariant_{}", index), *invariant));
+        let templated = self.templates.iter().flat_map(|template| {
+            template
+                .invariants()
+                .into_iter()
+                .map(|invariant| (template.name().to_string(), invariant))
+        });
+        explicit.chain(templated).collect()
+    }
+
+    /// The invariants handed to the guest, in journal order.
+    pub fn proven_invariants(&self) -> Vec<Invariant> {
+        self.named_invariants().into_iter().map(|(_, invariant)| invariant).collect()
+    }
+
+    /// The bound handed to the guest, which evaluates it in the proof.
+    pub fn sum_bound(&self) -> SumBound {
+        SumBound {
+            op: self.comparison,
+            threshold: self.sum_threshold,
+        }
+    }
+
+    /// The inline size limit handed to the guest; 0 when off.
+    pub fn inline_limit(&self) -> u64 {
+        self.inline_max_bytes.unwrap_or(0)
+    }
+
+    /// The row limit handed to the guest; 0 when off.
+    pub fn row_limit(&self) -> u64 {
+        self.max_rows.unwrap_or(0)
+    }
+
+    /// Load a policy whose columns are all given by index.
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let text = fs::read_to_string(path)?;
+        if let Some(name) = columns::named(&toml::from_str(&text)?).first() {
+            return Err(format!(
+                "column '{}' is named, which needs the CSV's header; give its index, or write the policy out \
+                 by index with `host policy resolve --out`",
+                name
+            )
+            .into());
+        }
+        toml::from_str::<Policy>(&text)?.checked()
+    }
+
+    /// Load a policy whose columns may be named, resolving the names against
+    /// the header `header` returns; it is only called if there are any.
+    /// Fuzzy matches are returned for the caller to confirm.
+    pub fn load_resolving(
+        path: &Path,
+        header: impl FnOnce() -> Result<Vec<String>, Box<dyn Error>>,
+    ) -> Result<(Self, Vec<ColumnMatch>), Box<dyn Error>> {
+        let mut value: toml::Value = toml::from_str(&fs::read_to_string(path)?)?;
+        let matches = if columns::named(&value).is_empty() {
+            Vec::new()
+        } else {
+            columns::resolve_policy(&mut value, &header()?)?
+        };
+        Ok((value.try_into::<Policy>()?.checked()?, matches))
+    }
+
+    fn checked(self) -> Result<Self, Box<dyn Error>> {
+        if self.proven_invariants().len() > MAX_INVARIANTS {
+            return Err(format!("at most {} invariants are supported", MAX_INVARIANTS).into());
+        }
+        if self.inline_limit() > MAX_INLINE_BYTES {
+            return Err(format!("inline_max_bytes is at most {}", MAX_INLINE_BYTES).into());
+        }
+        if let Some(limit) = self.cell_limits.iter().find(|limit| {
+            limit.max_unparseable_percent.is_none() && limit.max_empty_percent.is_none()
+        }) {
+            return Err(format!(
+                "cell limit on {} sets neither max_unparseable_percent nor max_empty_percent",
+                limit.column.map_or("every column".to_string(), |column| format!("column {}", column))
+            )
+            .into());
+        }
+        if self.max_rows == Some(0) {
+            return Err("max_rows must be at least 1; leave it out to process every row".into());
+        }
+        if self.reputation.is_some_and(|reputation| reputation.min_score > 100) {
+            return Err("reputation min_score is out of 100".into());
+        }
+        Ok(self)
+    }
+
+    /// SHA-256 over the policy's canonical JSON, so reordering fields in
+    /// the file or the struct does not change the version.
+    pub fn hash(&self) -> [u8; 32] {
+        canonical::digest(self).expect("Policy is always serializable")
+    }
+
+    /// Whether `journal` was proven against this policy's bound, invariants,
+    /// inline limit and row limit, with the guest reaching `sum_passed` on
+    /// the bound.
+    pub fn matches_journal(&self, journal: &AgentResult, sum_passed: bool) -> bool {
+        journal.sum_comparison == self.comparison
+            && journal.sum_threshold == self.sum_threshold
+            && journal.sum_within_bound == sum_passed
+            && self.guest_settings_match(journal)
+    }
+
+    /// Whether the guest was given this policy's invariants, inline limit
+    /// and row limit; unlike the sum bound, these cannot be re-checked
+    /// without a new proof.
+    pub fn guest_settings_match(&self, journal: &AgentResult) -> bool {
+        journal.inline_limit == self.inline_limit()
+            && journal.max_rows == self.row_limit()
+            && journal.invariants.iter().map(|result| result.invariant).eq(self.proven_invariants())
+    }
+
+    /// Check a verified journal's values against every rule. The sum is
+    /// passed separately since a concealed one comes from its opening;
+    /// invariant verdicts are the guest's, read from the journal's bitmask.
+    pub fn evaluate(&self, column_a_sum: u64, journal: &AgentResult) -> PolicyOutcome {
+        let mut rules = vec![RuleOutcome {
+            rule: "sum_threshold".to_string(),
+            passed: self.sum_bound().holds(column_a_sum),
+            actual: format!("column A sum {}", column_a_sum),
+            requirement: format!("{} {}", self.comparison.symbol(), self.sum_threshold),
+        }];
+        for (index, (rule, invariant)) in self.named_invariants().into_iter().enumerate() {
+            let result = journal.invariants.get(index).filter(|result| result.invariant == invariant);
+            let measured = format!("{}(column {})", invariant.aggregate.as_str(), invariant.column);
+            rules.push(RuleOutcome {
+                rule,
+                passed: result.is_some() && journal.invariants_passed & (1 << index) != 0,
+                actual: match result {
+                    Some(result) if journal.sum_disclosed => format!("{} {}", measured, result.value),
+                    Some(_) => format!("{} concealed", measured),
+                    None => "not proven".to_string(),
+                },
+                requirement: format!("{} {} {}", measured, invariant.op.symbol(), invariant.bound),
+            });
+        }
+        for limit in &self.cell_limits {
+            rules.extend(limit.evaluate(journal));
+        }
+        if self.reject_duplicate_headers == Some(true) {
+            rules.push(RuleOutcome {
+                rule: "duplicate_headers".to_string(),
+                passed: !journal.duplicate_headers,
+                actual: if journal.duplicate_headers { "header repeats a name" } else { "header names unique" }
+                    .to_string(),
+                requirement: "unique header names".to_string(),
+            });
+        }
+        if let Some(max_ragged_rows) = self.max_ragged_rows {
+            let checked = journal.column_hash_mode != ColumnHashMode::Chunked;
+            rules.push(RuleOutcome {
+                rule: "ragged_rows".to_string(),
+                passed: checked && journal.ragged_rows <= max_ragged_rows,
+                actual: if checked {
+                    format!("{} ragged row(s)", journal.ragged_rows)
+                } else {
+                    "rows not checked in chunked mode".to_string()
+                },
+                requirement: format!("at most {} row(s) with a field count other than the header's", max_ragged_rows),
+            });
+        }
+        if let Some(max_rows) = self.max_rows {
+            let allowed = self.allow_truncated == Some(true);
+            rules.push(RuleOutcome {
+                rule: "row_limit".to_string(),
+                passed: !journal.truncated || allowed,
+                actual: if journal.truncated {
+                    format!("stopped after {} rows", journal.max_rows)
+                } else {
+                    "every row processed".to_string()
+                },
+                requirement: if allowed {
+                    format!("at most {} rows", max_rows)
+                } else {
+                    format!("every row within a limit of {}", max_rows)
+                },
+            });
+        }
+        PolicyOutcome { rules }
+    }
+
+    /// The assurance tier `column_a_sum` falls in and the rules it adds on
+    /// top of [`evaluate`](Self::evaluate)'s; the standard tier, adding
+    /// none, without an `[assurance]` table.
+    pub fn assurance(&self, column_a_sum: u64, evidence: AssuranceEvidence) -> (AssuranceTier, Vec<RuleOutcome>) {
+        match &self.assurance {
+            Some(assurance) => assurance.evaluate(column_a_sum, evidence),
+            None => (AssuranceTier::Standard, Vec::new()),
+        }
+    }
+
+    /// The rule an `[environment]` table adds for the manifest `recorded`
+    /// next to a bundle proven with `image_id`; none without the table.
+    pub fn environment(&self, recorded: Option<&RecordedManifest>, image_id: &str) -> Vec<RuleOutcome> {
+        self.environment
+            .iter()
+            .map(|environment| environment.evaluate(recorded, image_id))
+            .collect()
+    }
+
+    /// The rule a `[reputation]` table adds for a prover scoring
+    /// `prover_score`; none without the table.
+    pub fn reputation(&self, prover_score: Option<u64>, human_reviewed: bool) -> Vec<RuleOutcome> {
+        self.reputation
+            .iter()
+            .map(|reputation| reputation.evaluate(prover_score, human_reviewed))
+            .collect()
+    }
+
+    /// Hex policy hash, recorded alongside every decision.
+    pub fn version(&self) -> String {
+        hex::encode(self.hash())
+    }
+}
+
+/// A policy file a long-running verifier can reload without restarting.
+///
+/// Clones share the same policy, so a reload from a signal handler is seen
+/// by every worker holding one. Each decision should be made on a single
+/// [`SharedPolicy::current`] so it matches the version it records.
+#[derive(Debug, Clone)]
+pub struct SharedPolicy {
+    path: PathBuf,
+    policy: Arc<RwLock<Policy>>,
+}
+
+impl SharedPolicy {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        Ok(Self {
+            path: path.to_path_buf(),
+            policy: Arc::new(RwLock::new(Policy::load(path)?)),
+        })
+    }
+
+    /// The policy in force now.
+    pub fn current(&self) -> Policy {
+        self.policy.read().unwrap_or_else(|poisoned| poisoned.into_inner()).clone()
+    }
+
+    /// Read the file again and swap it in, returning the new version. A
+    /// file that does not load or fails [`Policy::checked`] leaves the
+    /// policy in force untouched.
+    pub fn reload(&self) -> Result<String, Box<dyn Error>> {
+        let policy = Policy::load(&self.path)?;
+        let version = policy.version();
+        *self.policy.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = policy;
+        Ok(version)
+    }
+}
+
+/// Result of a single policy rule, with the values it was decided on.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuleOutcome {
+    pub rule: String,
+    pub passed: bool,
+    pub actual: String,
+    pub requirement: String,
+}
+
+/// Trace of every rule a policy evaluated.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolicyOutcome {
+    pub rules: Vec<RuleOutcome>,
+}
+
+impl PolicyOutcome {
+    pub fn passed(&self) -> bool {
+        self.rules.iter().all(|rule| rule.passed)
+    }
+
+    pub fn failed_rules(&self) -> impl Iterator<Item = &RuleOutcome> {
+        self.rules.iter().filter(|rule| !rule.passed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reload_swaps_a_valid_policy_and_keeps_the_old_one_otherwise() {
+        let path = std::env::temp_dir().join(format!("zaik-policy-reload-{}.toml", std::process::id()));
+        fs::write(&path, "sum_threshold = 1000\n").unwrap();
+        let shared = SharedPolicy::load(&path).unwrap();
+        let reader = shared.clone();
+        let first = reader.current().version();
+
+        fs::write(&path, "sum_threshold = 2000\n").unwrap();
+        let second = shared.reload().unwrap();
+        assert_ne!(first, second);
+        assert_eq!(reader.current().sum_threshold, 2000);
+        assert_eq!(reader.current().version(), second);
+
+        fs::write(&path, "sum_threshold = \"not a number\"\n").unwrap();
+        assert!(shared.reload().is_err());
+        assert_eq!(reader.current().version(), second);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,174 @@
+//! Cumulative disclosure tracking across proofs over overlapping data.
+//!
+//! Each proof that discloses its aggregates is a release over a set of rows.
+//! Two releases over nearly the same rows give away the rows they differ
+//! in (subtract one sum from the other), and a row that appears in many
+//! releases is exposed a little more each time. The [`DisclosureLedger`]
+//! records which rows every disclosed release covered, and [`assess`]
+//! checks a new release against the policy's [`PrivacyBudget`] before it
+//! is proven.
+//!
+//! Rows are identified by a truncated hash of their bytes, so the ledger
+//! holds no row contents, but low-entropy rows could be guessed from it:
+//! keep it with the proof store, not with the published receipts.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use zaik_core::dialect::Dialect;
+
+/// Limits a policy places on disclosure, in its `[privacy]` table:
+/// ```toml
+/// [privacy]
+/// min_row_difference = 10    # overlapping releases must differ by 10+ rows
+/// max_releases_per_row = 5   # no row in more than 5 disclosed releases
+/// action = "deny"            # or "warn" (the default)
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrivacyBudget {
+    /// Fewest rows two overlapping releases may differ by, unless they
+    /// cover exactly the same rows.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_row_difference: Option<usize>,
+    /// Most disclosed releases any one row may appear in.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_releases_per_row: Option<usize>,
+    #[serde(default)]
+    pub action: BudgetAction,
+}
+
+/// What to do with a release that would exceed the budget.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetAction {
+    #[default]
+    Warn,
+    Deny,
+}
+
+/// One disclosed release, as recorded in the ledger.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Release {
+    /// Hex CSV hash of the released file.
+    pub csv_hash: String,
+    /// Policy version the release was proven under.
+    pub policy: String,
+    /// Seconds since the Unix epoch.
+    pub recorded_at: u64,
+    /// [`row_id`] of every data row, sorted.
+    pub rows: Vec<String>,
+}
+
+/// Stable identifier of a data row: the first 8 bytes of its SHA-256, in hex.
+pub fn row_id(row: &str) -> String {
+    hex::encode(&Sha256::digest(row.as_bytes())[..8])
+}
+
+/// Identifiers of every data row (every line but the header).
+pub fn row_ids(csv_data: &str, dialect: Dialect) -> HashSet<String> {
+    dialect.rows(csv_data).map(row_id).collect()
+}
+
+/// A way a release would exceed the budget.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Violation {
+    /// The release differs from an earlier one by too few rows.
+    Differencing { csv_hash: String, rows: usize, min: usize },
+    /// Some row would appear in too many releases.
+    RowReleases { releases: usize, max: usize },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::Differencing { csv_hash, rows, min } => write!(
+                f,
+                "differs from release {} by {} row(s), fewer than {}; the difference could be recovered",
+                &csv_hash[..16.min(csv_hash.len())],
+                rows,
+                min
+            ),
+            Violation::RowReleases { releases, max } => {
+                write!(f, "a row would be disclosed in {} releases, more than {}", releases, max)
+            }
+        }
+    }
+}
+
+/// Check a release over `rows` against `budget` and the `prior` releases.
+/// A release of a file already released (same `csv_hash`) discloses
+/// nothing new and is not counted against it.
+pub fn assess(budget: &PrivacyBudget, prior: &[Release], csv_hash: &str, rows: &HashSet<String>) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    let mut seen = HashSet::new();
+    let mut row_releases: HashMap<&str, usize> = HashMap::new();
+    for release in prior {
+        if release.csv_hash == csv_hash || !seen.insert(release.csv_hash.as_str()) {
+            continue;
+        }
+        let overlap = release.rows.iter().filter(|row| rows.contains(*row)).count();
+        if overlap == 0 {
+            continue;
+        }
+        let difference = (rows.len() - overlap) + (release.rows.len() - overlap);
+        if let Some(min) = budget.min_row_difference {
+            if difference > 0 && difference < min {
+                violations.push(Violation::Differencing {
+                    csv_hash: release.csv_hash.clone(),
+                    rows: difference,
+                    min,
+                });
+            }
+        }
+        for row in &release.rows {
+            if rows.contains(row) {
+                *row_releases.entry(row).or_default() += 1;
+            }
+        }
+    }
+    if let Some(max) = budget.max_releases_per_row {
+        let releases = row_releases.values().max().copied().unwrap_or(0) + 1;
+        if releases > max {
+            violations.push(Violation::RowReleases { releases, max });
+        }
+    }
+    violations
+}
+
+/// Append-only JSON-lines record of disclosed releases.
+pub struct DisclosureLedger {
+    path: PathBuf,
+}
+
+impl DisclosureLedger {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Every recorded release, oldest first; none if the ledger is new.
+    pub fn releases(&self) -> Result<Vec<Release>, Box<dyn Error>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        fs::read_to_string(&self.path)?
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| Ok(serde_json::from_str(line)?))
+            .collect()
+    }
+
+    /// Record `release` unless its file was already released.
+    pub fn record(&self, release: &Release) -> Result<bool, Box<dyn Error>> {
+        if self.releases()?.iter().any(|prior| prior.csv_hash == release.csv_hash) {
+            return Ok(false);
+        }
+        let mut file = fs::OpenOptions::new().create(true).append(true).open(&self.path)?;
+        writeln!(file, "{}", serde_json::to_string(release)?)?;
+        Ok(true)
+    }
+}
@@ -0,0 +1,52 @@
+#[cfg(feature = "prove")]
+use risc0_zkvm::ProverOpts;
+use risc0_zkvm::{InnerReceipt, Receipt};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Level of assurance carried by a proof artifact.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ProofKind {
+    /// Receipt backed by a real zkVM seal.
+    Real,
+    /// Journal computed natively on the host; nothing was proven.
+    Simulated,
+    /// Fake receipt produced under `RISC0_DEV_MODE`; carries no cryptographic integrity.
+    DevMode,
+}
+
+impl ProofKind {
+    /// Classify a receipt by the kind of seal it carries.
+    pub fn of(receipt: &Receipt) -> Self {
+        match receipt.inner {
+            InnerReceipt::Fake(_) => ProofKind::DevMode,
+            _ => ProofKind::Real,
+        }
+    }
+
+    /// Kind of receipt the default prover produces in this process.
+    #[cfg(feature = "prove")]
+    pub fn current() -> Self {
+        if ProverOpts::default().dev_mode() {
+            ProofKind::DevMode
+        } else {
+            ProofKind::Real
+        }
+    }
+
+    /// Whether the artifact is backed by a cryptographic proof.
+    pub fn is_cryptographic(self) -> bool {
+        self == ProofKind::Real
+    }
+}
+
+impl fmt::Display for ProofKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ProofKind::Real => "real",
+            ProofKind::Simulated => "simulated",
+            ProofKind::DevMode => "dev-mode",
+        })
+    }
+}
@@ -0,0 +1,413 @@
+//! Preimage escrow: Agent A keeps the CSV until Agent B asks for it.
+//!
+//! Agent A hands over only the bundle. Once the proof checks out, Agent B
+//! signs a `conditional` [`Decision`] carrying a [`RevealRequest`] for the
+//! whole CSV or for a sample of rows drawn from the receipt's claim digest.
+//! Agent A [`answer`]s only a request whose signature chain verifies, and
+//! Agent B [`collect`]s the [`Reveal`] only if it hashes to the journal's
+//! `csv_hash` (or, for rows, opens its `row_merkle_root`). Both sides work
+//! from the files next to the bundle:
+//!
+//! ```text
+//! <digest>.bundle, .bundle.sig          Agent A
+//! <digest>.decision.json, .json.sig     Agent B: conditional, with the request
+//! <digest>.reveal.json                  Agent A: the answer
+//! ```
+//!
+//! The protocol also fixes what proves a prover misbehaved, for slashing
+//! its bond (see [`crate::bonding`]): [`SlashingEvidence`] is a bundle, the
+//! prover's signature over it and the policy its key claims, which together
+//! fail a check only a dishonest or broken prover fails.
+
+use crate::audit::{self, AuditError, RowOpening};
+use crate::journal;
+use crate::outcome::{FailureReason, Outcome};
+use crate::policy::Policy;
+use crate::relying_party::RelyingParty;
+use crate::signing::{self, ChainError, Decision, DetachedSignature, SignerRole};
+use crate::store::ProofBundle;
+use crate::untrusted;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use risc0_zkvm::sha::{Digest, Digestible};
+use risc0_zkvm::Receipt;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use zaik_core::{AgentResult, ColumnHashMode};
+
+/// What Agent B asks to see.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RevealScope {
+    /// The whole CSV file.
+    Csv,
+    /// This many data rows, with their inclusion paths.
+    Rows { samples: usize },
+}
+
+impl FromStr for RevealScope {
+    type Err = String;
+
+    /// `csv`, or `rows:N`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            None if s == "csv" => Ok(RevealScope::Csv),
+            Some(("rows", samples)) => match samples.parse() {
+                Ok(samples) if samples > 0 => Ok(RevealScope::Rows { samples }),
+                _ => Err(format!("'{}' is not a positive row count", samples)),
+            },
+            _ => Err(format!("unknown reveal '{}', expected csv or rows:N", s)),
+        }
+    }
+}
+
+impl fmt::Display for RevealScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RevealScope::Csv => write!(f, "the CSV"),
+            RevealScope::Rows { samples } => write!(f, "{} sampled row(s)", samples),
+        }
+    }
+}
+
+/// Agent B's request, signed as part of its conditional decision.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RevealRequest {
+    /// Hex `csv_hash` of the verified journal.
+    pub csv_hash: String,
+    /// Hex claim digest sampled rows are drawn from, so Agent A cannot pick them.
+    pub seed: String,
+    pub scope: RevealScope,
+}
+
+impl RevealRequest {
+    pub fn new(receipt: &Receipt, journal: &AgentResult, scope: RevealScope) -> Result<Self, Box<dyn Error>> {
+        let seed: [u8; 32] = receipt.claim()?.digest().into();
+        Ok(Self {
+            csv_hash: hex::encode(journal.csv_hash),
+            seed: hex::encode(seed),
+            scope,
+        })
+    }
+
+    fn seed(&self) -> Result<[u8; 32], RevealError> {
+        hex::decode(&self.seed)
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| RevealError::Io(format!("'{}' is not a 32-byte hex seed", self.seed).into()))
+    }
+}
+
+/// Agent A's answer, stored next to the bundle.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Reveal {
+    Csv { csv: String },
+    /// `rows` is Agent A's row count; the paths only verify for the true one.
+    Rows { rows: usize, openings: Vec<RowOpening> },
+}
+
+impl Reveal {
+    /// Path of the reveal for the bundle at `bundle_path`.
+    pub fn path_for(bundle_path: &Path) -> PathBuf {
+        bundle_path.with_extension("reveal.json")
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        Ok(serde_json::from_slice(&untrusted::read(path, untrusted::MAX_BUNDLE_BYTES)?)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// Why a reveal was not made or not accepted.
+#[derive(Debug)]
+pub enum RevealError {
+    /// The decision next to the bundle is not a conditional one asking for a reveal.
+    NotRequested,
+    /// The decision's signature chain does not verify.
+    Chain(ChainError),
+    /// The CSV does not hash to the `csv_hash` the request and journal name.
+    CsvMismatch,
+    /// The reveal answers a different request.
+    WrongScope,
+    /// `chunked` journals commit no row root to open rows against.
+    NoRowRoot,
+    /// The revealed rows do not open the committed row root.
+    Rows(AuditError),
+    Io(Box<dyn Error>),
+}
+
+impl RevealError {
+    pub fn to_failure(&self) -> FailureReason {
+        let (outcome, reason) = match self {
+            RevealError::NotRequested => (Outcome::Io, "reveal_not_requested"),
+            RevealError::Chain(_) => (Outcome::CryptoFailure, "signature_chain_invalid"),
+            RevealError::CsvMismatch | RevealError::WrongScope | RevealError::Rows(_) => {
+                (Outcome::CryptoFailure, "reveal_mismatch")
+            }
+            RevealError::NoRowRoot => (Outcome::Io, "chunked_unsupported"),
+            RevealError::Io(_) => (Outcome::Io, "reveal_unavailable"),
+        };
+        FailureReason::new(outcome, reason, self.to_string())
+    }
+}
+
+impl fmt::Display for RevealError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RevealError::NotRequested => write!(f, "the decision does not request a reveal"),
+            RevealError::Chain(e) => write!(f, "reveal request is not validly signed: {}", e),
+            RevealError::CsvMismatch => write!(f, "revealed CSV does not hash to the committed csv_hash"),
+            RevealError::WrongScope => write!(f, "reveal does not answer the request"),
+            RevealError::NoRowRoot => write!(f, "chunked journals commit no row root to reveal rows against"),
+            RevealError::Rows(e) => write!(f, "revealed rows do not match the journal: {}", e),
+            RevealError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for RevealError {}
+
+impl From<Box<dyn Error>> for RevealError {
+    fn from(e: Box<dyn Error>) -> Self {
+        RevealError::Io(e)
+    }
+}
+
+/// Agent A's side: what `request` asks of `csv_data`, the CSV behind `journal`.
+pub fn reveal(request: &RevealRequest, journal: &AgentResult, csv_data: &str) -> Result<Reveal, RevealError> {
+    if request.csv_hash != hex::encode(journal.csv_hash)
+        || journal.column_hash_mode.csv_hash(csv_data) != journal.csv_hash
+    {
+        return Err(RevealError::CsvMismatch);
+    }
+    match request.scope {
+        RevealScope::Csv => Ok(Reveal::Csv {
+            csv: csv_data.to_string(),
+        }),
+        RevealScope::Rows { samples } => {
+            if journal.column_hash_mode == ColumnHashMode::Chunked {
+                return Err(RevealError::NoRowRoot);
+            }
+            // The row root only covers the rows the guest processed
+            let (csv_data, _) = zaik_core::limit_rows(csv_data, journal.max_rows, journal.dialect);
+            let rows = audit::row_count(csv_data, journal.dialect);
+            let indices = audit::sample_indices(&request.seed()?, rows, samples);
+            let openings = audit::open_rows(csv_data, journal.dialect, &indices).map_err(RevealError::Rows)?;
+            Ok(Reveal::Rows { rows, openings })
+        }
+    }
+}
+
+/// Agent B's side: check that `reveal` answers `request` for `journal`.
+pub fn check(request: &RevealRequest, journal: &AgentResult, reveal: &Reveal) -> Result<(), RevealError> {
+    if request.csv_hash != hex::encode(journal.csv_hash) {
+        return Err(RevealError::CsvMismatch);
+    }
+    match (request.scope, reveal) {
+        (RevealScope::Csv, Reveal::Csv { csv }) => {
+            if journal.column_hash_mode.csv_hash(csv) == journal.csv_hash {
+                Ok(())
+            } else {
+                Err(RevealError::CsvMismatch)
+            }
+        }
+        (RevealScope::Rows { samples }, Reveal::Rows { rows, openings }) => {
+            if journal.column_hash_mode == ColumnHashMode::Chunked {
+                return Err(RevealError::NoRowRoot);
+            }
+            let requested = audit::sample_indices(&request.seed()?, *rows, samples);
+            audit::verify_openings(&journal.row_merkle_root, *rows, &requested, openings).map_err(RevealError::Rows)
+        }
+        _ => Err(RevealError::WrongScope),
+    }
+}
+
+/// The signed request next to the bundle at `bundle_path`, once the
+/// prover → decision → verifier chain verifies against the trusted keys.
+fn signed_request(
+    bundle_path: &Path,
+    trusted_provers: &[String],
+    trusted_verifiers: &[String],
+) -> Result<RevealRequest, RevealError> {
+    let bundle_bytes =
+        untrusted::read(bundle_path, untrusted::MAX_BUNDLE_BYTES).map_err(|e| RevealError::Io(e.into()))?;
+    let decision_path = Decision::path_for(bundle_path);
+    let decision = untrusted::read_to_string(&decision_path, untrusted::MAX_SIDECAR_BYTES)
+        .map_err(|e| RevealError::Io(e.into()))?;
+    let decision: Decision = serde_json::from_str(&decision).map_err(|e| RevealError::Io(e.into()))?;
+    signing::verify_chain(
+        &bundle_bytes,
+        &DetachedSignature::load(&DetachedSignature::path_for(bundle_path))?,
+        &decision,
+        &DetachedSignature::load(&DetachedSignature::path_for(&decision_path))?,
+        trusted_provers,
+        trusted_verifiers,
+    )
+    .map_err(RevealError::Chain)?;
+    match decision {
+        Decision {
+            outcome: Outcome::Conditional,
+            reveal: Some(request),
+            ..
+        } => Ok(request),
+        _ => Err(RevealError::NotRequested),
+    }
+}
+
+/// Agent A: answer the signed request next to the bundle at `bundle_path`
+/// from `csv_data`, writing the reveal next to the bundle.
+///
+/// `trusted_provers` holds Agent A's own keys and `trusted_verifiers` the
+/// verifiers it will reveal to; both must be non-empty, so the CSV never
+/// goes to whoever can write next to the bundle.
+pub fn answer(
+    bundle_path: &Path,
+    csv_data: &str,
+    trusted_provers: &[String],
+    trusted_verifiers: &[String],
+) -> Result<Reveal, RevealError> {
+    let request = signed_request(bundle_path, trusted_provers, trusted_verifiers)?;
+    let bundle = ProofBundle::load(bundle_path)?;
+    let journal: AgentResult = journal::decode(&bundle.receipt.journal.bytes)?;
+    let reveal = reveal(&request, &journal, csv_data)?;
+    reveal.save(&Reveal::path_for(bundle_path))?;
+    Ok(reveal)
+}
+
+/// Agent B: check the reveal next to the bundle at `bundle_path` against
+/// the request in its own conditional decision, made about `prover`'s bundle.
+pub fn collect(bundle_path: &Path, prover: &str, verifier: &str) -> Result<Reveal, RevealError> {
+    let request = signed_request(bundle_path, &[prover.to_string()], &[verifier.to_string()])?;
+    let bundle = ProofBundle::load(bundle_path)?;
+    let journal: AgentResult = journal::decode(&bundle.receipt.journal.bytes)?;
+    let reveal = Reveal::load(&Reveal::path_for(bundle_path))?;
+    check(&request, &journal, &reveal)?;
+    Ok(reveal)
+}
+
+/// A prover's signed bundle that does not hold up under the policy it
+/// claims, stored as `<digest>.evidence.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlashingEvidence {
+    /// The bundle file, base64.
+    pub bundle: String,
+    /// The prover's signature over the bundle file.
+    pub prover_signature: DetachedSignature,
+    /// The policy the bundle's key names by hash.
+    pub policy: Policy,
+}
+
+/// What verified evidence shows.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Offence {
+    /// Hex public key of the prover that signed the bundle.
+    pub prover: String,
+    /// Hex SHA-256 of the bundle file.
+    pub bundle_digest: String,
+    /// Stable reason of the check it failed, e.g. `bound_mismatch`.
+    pub reason: String,
+    pub detail: String,
+}
+
+/// Why evidence does not support a slash.
+#[derive(Debug)]
+pub enum EvidenceError {
+    Malformed(String),
+    /// The prover did not sign this bundle.
+    Signature(ChainError),
+    /// The bundle was proven under a different policy than the evidence's.
+    PolicyNotClaimed,
+    /// The bundle is accepted, or fails only checks a prover cannot be
+    /// blamed for, such as an image the ledger does not know.
+    NotMisbehavior(String),
+}
+
+impl fmt::Display for EvidenceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EvidenceError::Malformed(e) => write!(f, "evidence is malformed: {}", e),
+            EvidenceError::Signature(e) => write!(f, "bundle is not signed by the prover: {}", e),
+            EvidenceError::PolicyNotClaimed => write!(f, "the bundle was not proven under the evidence's policy"),
+            EvidenceError::NotMisbehavior(e) => write!(f, "the bundle shows no misbehavior: {}", e),
+        }
+    }
+}
+
+impl Error for EvidenceError {}
+
+impl EvidenceError {
+    pub fn to_failure(&self) -> FailureReason {
+        let (outcome, reason) = match self {
+            EvidenceError::Malformed(_) => (Outcome::Io, "evidence_malformed"),
+            EvidenceError::Signature(_) => (Outcome::CryptoFailure, "signature_chain_invalid"),
+            EvidenceError::PolicyNotClaimed => (Outcome::PolicyReject, "policy_mismatch"),
+            EvidenceError::NotMisbehavior(_) => (Outcome::PolicyReject, "no_misbehavior"),
+        };
+        FailureReason::new(outcome, reason, self.to_string())
+    }
+}
+
+impl SlashingEvidence {
+    pub fn new(bundle_bytes: &[u8], prover_signature: DetachedSignature, policy: Policy) -> Self {
+        Self {
+            bundle: BASE64.encode(bundle_bytes),
+            prover_signature,
+            policy,
+        }
+    }
+
+    /// Path of the evidence against the bundle at `bundle_path`.
+    pub fn path_for(bundle_path: &Path) -> PathBuf {
+        bundle_path.with_extension("evidence.json")
+    }
+
+    /// Read evidence, which carries a whole bundle in base64.
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        Ok(serde_json::from_slice(&untrusted::read(path, 2 * untrusted::MAX_BUNDLE_BYTES)?)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Check that the prover signed the bundle, that its key claims this
+    /// policy, and that a relying party trusting `image_ids` rejects it for
+    /// a reason that is the prover's fault; see
+    /// [`Rejection::is_prover_fault`](crate::relying_party::Rejection::is_prover_fault).
+    pub fn verify(&self, image_ids: &[Digest]) -> Result<Offence, EvidenceError> {
+        let bundle_bytes = BASE64
+            .decode(&self.bundle)
+            .map_err(|e| EvidenceError::Malformed(e.to_string()))?;
+        self.prover_signature
+            .verify(SignerRole::Prover, &bundle_bytes)
+            .map_err(EvidenceError::Signature)?;
+        let bundle = ProofBundle::from_bytes(&bundle_bytes).map_err(|e| EvidenceError::Malformed(e.to_string()))?;
+        if bundle.key.policy_hash != self.policy.hash() {
+            return Err(EvidenceError::PolicyNotClaimed);
+        }
+        let mut relying_party = RelyingParty::new(self.policy.clone());
+        for image_id in image_ids {
+            relying_party = relying_party.allow_image(*image_id);
+        }
+        match relying_party.accept(&bundle) {
+            Ok(_) => Err(EvidenceError::NotMisbehavior("the bundle is accepted under its policy".to_string())),
+            Err(rejection) if rejection.is_prover_fault() => Ok(Offence {
+                prover: self.prover_signature.signer.clone(),
+                bundle_digest: self.prover_signature.digest.clone(),
+                reason: rejection.to_failure().reason,
+                detail: rejection.to_string(),
+            }),
+            Err(rejection) => Err(EvidenceError::NotMisbehavior(rejection.to_string())),
+        }
+    }
+}
@@ -0,0 +1,300 @@
+//! Verifier-side checks for services that accept proof bundles from
+//! provers they do not control.
+//!
+//! A [`RelyingParty`] pins the guest image IDs it trusts and the policy it
+//! enforces, then re-derives the decision from the receipt itself instead of
+//! trusting the prover's verdict.
+
+use crate::assurance::{AssuranceEvidence, AssuranceTier};
+use crate::backend::{BackendError, NativeBackend, VerifierBackend};
+use crate::environment::RecordedManifest;
+use crate::expected::ExpectedHashes;
+use crate::metadata::BundleMetadata;
+use crate::outcome::{FailureReason, Outcome};
+use crate::policy::{Policy, PolicyOutcome};
+use crate::proof::ProofKind;
+use crate::store::ProofBundle;
+use risc0_zkvm::sha::Digest;
+use risc0_zkvm::InnerReceipt;
+use std::fmt;
+use zaik_core::{AgentResult, ColumnHashMode, SUPPORTED_SEMANTICS};
+
+/// Why a bundle was not accepted.
+#[derive(Debug)]
+pub enum Rejection {
+    /// The bundle was proven with a guest image outside the allowlist.
+    ImageNotAllowed(Digest),
+    /// A chunked bundle composed chunk receipts from an image outside the
+    /// allowlist.
+    ChunkImageNotAllowed(Digest),
+    /// The bundle is for a CSV hash the relying party did not publish.
+    UnexpectedCsvHash([u8; 32]),
+    /// The bundle was proven under a different policy.
+    PolicyMismatch { expected: String, actual: String },
+    /// Dev-mode or simulated receipts are not accepted.
+    NotCryptographic(ProofKind),
+    ReceiptInvalid(String),
+    /// The verifier backend could not check the receipt at all.
+    BackendUnavailable(String),
+    /// The journal is for a different CSV than the bundle claims.
+    JournalMismatch,
+    /// The journal was computed by parsing and aggregation rules this
+    /// build does not know.
+    UnsupportedSemantics(u32),
+    /// The sum opening does not match the journal's commitment.
+    SumOpeningInvalid,
+    /// The CSV inlined in the journal does not reproduce its digests.
+    InlineDataMismatch,
+    /// The guest checked a different bound, comparison or invariant list
+    /// than the policy's, or reached a different verdict on the sum.
+    BoundMismatch,
+    PolicyFailed(PolicyOutcome),
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Rejection::ImageNotAllowed(image_id) => write!(f, "image ID {} is not allowlisted", image_id),
+            Rejection::ChunkImageNotAllowed(image_id) => {
+                write!(f, "chunks were proven with image ID {}, which is not allowlisted", image_id)
+            }
+            Rejection::UnexpectedCsvHash(csv_hash) => {
+                write!(f, "CSV hash {} is not among the expected hashes", hex::encode(csv_hash))
+            }
+            Rejection::PolicyMismatch { expected, actual } => {
+                write!(f, "bundle was proven under policy {}, expected {}", actual, expected)
+            }
+            Rejection::NotCryptographic(kind) => write!(f, "receipt is {}, not a cryptographic proof", kind),
+            Rejection::ReceiptInvalid(reason) => write!(f, "receipt does not verify: {}", reason),
+            Rejection::BackendUnavailable(reason) => write!(f, "receipt could not be checked: {}", reason),
+            Rejection::JournalMismatch => write!(f, "journal CSV hash does not match the bundle key"),
+            Rejection::UnsupportedSemantics(version) => write!(
+                f,
+                "journal was computed by semantics version {}, this verifier supports {:?}",
+                version, SUPPORTED_SEMANTICS
+            ),
+            Rejection::SumOpeningInvalid => write!(f, "sum opening does not match the journal's commitment"),
+            Rejection::InlineDataMismatch => write!(f, "inline CSV does not match the journal's digests"),
+            Rejection::BoundMismatch => write!(f, "guest bound or invariant checks do not match the policy"),
+            Rejection::PolicyFailed(outcome) => write!(f, "{}", crate::explain::explain(outcome)),
+        }
+    }
+}
+
+impl std::error::Error for Rejection {}
+
+impl Rejection {
+    /// Whether only a dishonest or broken prover produces a bundle failing
+    /// this way, given the policy its key claims; grounds for slashing its
+    /// bond (see [`crate::protocol::SlashingEvidence`]). Failing the policy
+    /// itself is an honest result, not misbehavior.
+    pub fn is_prover_fault(&self) -> bool {
+        matches!(
+            self,
+            Rejection::NotCryptographic(_)
+                | Rejection::ReceiptInvalid(_)
+                | Rejection::JournalMismatch
+                | Rejection::SumOpeningInvalid
+                | Rejection::InlineDataMismatch
+                | Rejection::BoundMismatch
+        )
+    }
+
+    /// The outcome and stable reason a CLI reports for this rejection.
+    pub fn to_failure(&self) -> FailureReason {
+        let (outcome, reason) = match self {
+            Rejection::ImageNotAllowed(_) => (Outcome::CryptoFailure, "image_not_allowed"),
+            Rejection::ChunkImageNotAllowed(_) => (Outcome::CryptoFailure, "chunk_image_not_allowed"),
+            Rejection::UnexpectedCsvHash(_) => (Outcome::UnexpectedData, "unexpected_csv_hash"),
+            Rejection::PolicyMismatch { .. } => (Outcome::PolicyReject, "policy_mismatch"),
+            Rejection::NotCryptographic(_) => (Outcome::Conditional, "non_cryptographic_receipt"),
+            Rejection::ReceiptInvalid(_) => (Outcome::CryptoFailure, "receipt_verification_failed"),
+            Rejection::BackendUnavailable(_) => (Outcome::Io, "verifier_backend_failed"),
+            Rejection::JournalMismatch => (Outcome::CryptoFailure, "journal_mismatch"),
+            Rejection::UnsupportedSemantics(_) => (Outcome::PolicyReject, "semantics_unsupported"),
+            Rejection::SumOpeningInvalid => (Outcome::CryptoFailure, "sum_opening_mismatch"),
+            Rejection::InlineDataMismatch => (Outcome::CryptoFailure, "inline_data_mismatch"),
+            Rejection::BoundMismatch => (Outcome::CryptoFailure, "bound_mismatch"),
+            Rejection::PolicyFailed(_) => (Outcome::PolicyReject, "invariant_violated"),
+        };
+        FailureReason::new(outcome, reason, self.to_string())
+    }
+}
+
+/// A bundle that passed every check, with the values it was decided on.
+#[derive(Debug)]
+pub struct Acceptance {
+    pub journal: AgentResult,
+    pub column_a_sum: u64,
+    pub proof_kind: ProofKind,
+    pub policy_outcome: PolicyOutcome,
+    pub assurance_tier: AssuranceTier,
+    /// Differences between the prover's build and this one; informational
+    /// once the receipt has verified.
+    pub compatibility: Vec<String>,
+}
+
+/// Acceptance rules of one relying party.
+pub struct RelyingParty {
+    policy: Policy,
+    image_ids: Vec<Digest>,
+    expected: Option<ExpectedHashes>,
+    allow_dev_mode: bool,
+    human_reviewed: bool,
+    prover_score: Option<u64>,
+    environment: Option<RecordedManifest>,
+    backend: Box<dyn VerifierBackend>,
+}
+
+impl RelyingParty {
+    /// A relying party enforcing `policy` that trusts no guest image yet.
+    pub fn new(policy: Policy) -> Self {
+        Self {
+            policy,
+            image_ids: Vec::new(),
+            expected: None,
+            allow_dev_mode: false,
+            human_reviewed: false,
+            prover_score: None,
+            environment: None,
+            backend: Box::new(NativeBackend),
+        }
+    }
+
+    pub fn allow_image(mut self, image_id: impl Into<Digest>) -> Self {
+        self.image_ids.push(image_id.into());
+        self
+    }
+
+    /// Only accept bundles for the CSV hashes in `expected`.
+    pub fn expect(mut self, expected: ExpectedHashes) -> Self {
+        self.expected = Some(expected);
+        self
+    }
+
+    /// Accept dev-mode receipts, for test deployments only.
+    pub fn allow_dev_mode(mut self) -> Self {
+        self.allow_dev_mode = true;
+        self
+    }
+
+    /// A human has reviewed the bundle, as high-value sums require under a
+    /// policy with an `[assurance]` table.
+    pub fn human_reviewed(mut self) -> Self {
+        self.human_reviewed = true;
+        self
+    }
+
+    /// The bundle's prover scored `score`, checked when the policy has a
+    /// `[reputation]` table; see [`crate::reputation`].
+    pub fn prover_score(mut self, score: u64) -> Self {
+        self.prover_score = Some(score);
+        self
+    }
+
+    /// The environment manifest recorded with the bundle, checked when the
+    /// policy has an `[environment]` table.
+    pub fn environment(mut self, recorded: RecordedManifest) -> Self {
+        self.environment = Some(recorded);
+        self
+    }
+
+    /// Verify receipts with `backend` instead of in this process.
+    pub fn backend(mut self, backend: impl VerifierBackend + 'static) -> Self {
+        self.backend = Box::new(backend);
+        self
+    }
+
+    /// Run every check on `bundle`, cheapest first.
+    pub fn accept(&self, bundle: &ProofBundle) -> Result<Acceptance, Rejection> {
+        let image_id = Digest::from(bundle.key.image_id);
+        if !self.image_ids.contains(&image_id) {
+            return Err(Rejection::ImageNotAllowed(image_id));
+        }
+        // The journal is checked against the key below
+        if let Some(expected) = &self.expected {
+            expected
+                .check(&bundle.key.csv_hash, "relying party")
+                .map_err(|unexpected| Rejection::UnexpectedCsvHash(unexpected.csv_hash))?;
+        }
+        if bundle.key.policy_hash != self.policy.hash() {
+            return Err(Rejection::PolicyMismatch {
+                expected: self.policy.version(),
+                actual: hex::encode(bundle.key.policy_hash),
+            });
+        }
+
+        let proof_kind = ProofKind::of(&bundle.receipt);
+        if !proof_kind.is_cryptographic() && !self.allow_dev_mode {
+            return Err(Rejection::NotCryptographic(proof_kind));
+        }
+        self.backend.verify(&bundle.receipt, image_id).map_err(|e| match e {
+            BackendError::Invalid(reason) => Rejection::ReceiptInvalid(reason),
+            BackendError::Unavailable(reason) => Rejection::BackendUnavailable(reason),
+        })?;
+
+        let journal: AgentResult = crate::journal::decode(&bundle.receipt.journal.bytes)
+            .map_err(|e| Rejection::ReceiptInvalid(e.to_string()))?;
+        if journal.csv_hash != bundle.key.csv_hash {
+            return Err(Rejection::JournalMismatch);
+        }
+        if !SUPPORTED_SEMANTICS.contains(&journal.semantics_version) {
+            return Err(Rejection::UnsupportedSemantics(journal.semantics_version));
+        }
+        if journal.column_hash_mode == ColumnHashMode::Chunked {
+            let chunk_image_id = Digest::from(journal.chunk_image_id);
+            if !self.image_ids.contains(&chunk_image_id) {
+                return Err(Rejection::ChunkImageNotAllowed(chunk_image_id));
+            }
+        }
+        if journal.inline_matches() == Some(false) {
+            return Err(Rejection::InlineDataMismatch);
+        }
+        let column_a_sum = bundle
+            .sum_opening
+            .open(&journal)
+            .ok_or(Rejection::SumOpeningInvalid)?;
+
+        let mut policy_outcome = self.policy.evaluate(column_a_sum, &journal);
+        let evidence = AssuranceEvidence {
+            groth16: matches!(bundle.receipt.inner, InnerReceipt::Groth16(_)),
+            human_reviewed: self.human_reviewed,
+        };
+        let (assurance_tier, assurance_rules) = self.policy.assurance(column_a_sum, evidence);
+        policy_outcome.rules.extend(assurance_rules);
+        policy_outcome
+            .rules
+            .extend(self.policy.environment(self.environment.as_ref(), &image_id.to_string()));
+        policy_outcome
+            .rules
+            .extend(self.policy.reputation(self.prover_score, self.human_reviewed));
+        if !self.policy.matches_journal(&journal, self.policy.sum_bound().holds(column_a_sum)) {
+            return Err(Rejection::BoundMismatch);
+        }
+        if !policy_outcome.passed() {
+            return Err(Rejection::PolicyFailed(policy_outcome));
+        }
+        Ok(Acceptance {
+            journal,
+            column_a_sum,
+            proof_kind,
+            policy_outcome,
+            assurance_tier,
+            compatibility: bundle.metadata.compatibility(&BundleMetadata::new(bundle.key.image_id)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_semantics_are_rejected_not_accepted_conditionally() {
+        let unknown = SUPPORTED_SEMANTICS.iter().max().unwrap() + 1;
+        let failure = Rejection::UnsupportedSemantics(unknown).to_failure();
+        assert_eq!(failure.outcome, Outcome::PolicyReject);
+        assert_eq!(failure.reason, "semantics_unsupported");
+        assert!(!Rejection::UnsupportedSemantics(unknown).is_prover_fault());
+    }
+}
@@ -0,0 +1,157 @@
+//! Prover reputation: how each prover agent's bundles have fared.
+//!
+//! Stats come from a proof store alone: the decisions recorded next to its
+//! bundles, which name the prover's key, and the proving times logged to
+//! `slo.jsonl` (see [`crate::slo`]). The score is the share of decided
+//! bundles that were accepted, out of 100, smoothed so that a prover with
+//! little history starts near the middle rather than at either end:
+//! `100 * (accepted + 1) / (decided + 2)`. A policy's [`Reputation`] table
+//! makes provers below a score need human review:
+//! ```toml
+//! [reputation]
+//! min_score = 80   # lower-scoring provers need `--human-reviewed`
+//! ```
+
+use crate::history::{ProofRecord, Status};
+use crate::policy::RuleOutcome;
+use crate::store::ProofStore;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::error::Error;
+
+/// One prover's record in a store.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProverStats {
+    /// Hex public key the prover signs bundles with.
+    pub prover: String,
+    /// Bundles with a recorded decision.
+    pub decided: u64,
+    pub accepted: u64,
+    /// Bundles with any outcome [`Status::Rejected`] covers.
+    pub rejected: u64,
+    /// `accepted / decided`; 0 before any decision.
+    pub success_rate: f64,
+    /// Mean proving time of the bundles `slo.jsonl` has a sample for.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub average_proving_ms: Option<u64>,
+    /// 0 to 100; see the module docs.
+    pub score: u64,
+}
+
+impl ProverStats {
+    /// A prover with no decided bundles, scored 50.
+    pub fn new(prover: &str) -> Self {
+        Self {
+            prover: prover.to_string(),
+            decided: 0,
+            accepted: 0,
+            rejected: 0,
+            success_rate: 0.0,
+            average_proving_ms: None,
+            score: score(0, 0),
+        }
+    }
+}
+
+/// Smoothed acceptance score out of 100.
+pub fn score(accepted: u64, decided: u64) -> u64 {
+    100 * (accepted + 1) / (decided + 2)
+}
+
+/// Stats for every prover named by a decision in `store`, by key.
+pub fn stats(store: &ProofStore) -> Result<Vec<ProverStats>, Box<dyn Error>> {
+    let proving_ms: BTreeMap<String, u64> = store
+        .slo_log()
+        .samples()?
+        .into_iter()
+        .filter(|sample| !sample.bundle.is_empty())
+        .map(|sample| (sample.bundle, sample.proving_ms))
+        .collect();
+
+    let mut provers: BTreeMap<String, (ProverStats, Vec<u64>)> = BTreeMap::new();
+    for path in store.bundles()? {
+        // Bundles removed while the store is read are skipped
+        let Ok(record) = ProofRecord::load(&path) else {
+            continue;
+        };
+        let Some(prover) = &record.prover else {
+            continue;
+        };
+        let (stats, times) = provers
+            .entry(prover.clone())
+            .or_insert_with(|| (ProverStats::new(prover), Vec::new()));
+        stats.decided += 1;
+        match record.status {
+            Status::Accepted => stats.accepted += 1,
+            Status::Rejected => stats.rejected += 1,
+            _ => {}
+        }
+        times.extend(proving_ms.get(&record.digest));
+    }
+
+    Ok(provers
+        .into_values()
+        .map(|(mut stats, times)| {
+            stats.success_rate = stats.accepted as f64 / stats.decided as f64;
+            stats.average_proving_ms = (!times.is_empty()).then(|| times.iter().sum::<u64>() / times.len() as u64);
+            stats.score = score(stats.accepted, stats.decided);
+            stats
+        })
+        .collect())
+}
+
+/// Stats for `prover` in `store`; a prover without decisions gets
+/// [`ProverStats::new`].
+pub fn of(store: &ProofStore, prover: &str) -> Result<ProverStats, Box<dyn Error>> {
+    Ok(stats(store)?
+        .into_iter()
+        .find(|stats| stats.prover == prover)
+        .unwrap_or_else(|| ProverStats::new(prover)))
+}
+
+/// A policy's `[reputation]` table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Reputation {
+    /// Provers scoring below this need human review.
+    pub min_score: u64,
+}
+
+impl Reputation {
+    /// The `prover_reputation` rule for a prover scoring `score`; `None`
+    /// when the prover is unknown, e.g. for an unsigned bundle, which is
+    /// treated like a low score.
+    pub fn evaluate(&self, score: Option<u64>, human_reviewed: bool) -> RuleOutcome {
+        let trusted = score.is_some_and(|score| score >= self.min_score);
+        let actual = match score {
+            Some(score) => format!("score {}", score),
+            None => "unknown prover".to_string(),
+        };
+        RuleOutcome {
+            rule: "prover_reputation".to_string(),
+            passed: trusted || human_reviewed,
+            actual: format!("{}, {}", actual, if human_reviewed { "reviewed" } else { "not reviewed" }),
+            requirement: format!("score of at least {} or human review", self.min_score),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn score_starts_in_the_middle_and_follows_the_record() {
+        assert_eq!(score(0, 0), 50);
+        assert_eq!(score(9, 10), 83);
+        assert_eq!(score(0, 10), 8);
+    }
+
+    #[test]
+    fn low_or_unknown_reputation_needs_review() {
+        let reputation = Reputation { min_score: 80 };
+        assert!(reputation.evaluate(Some(83), false).passed);
+        assert!(!reputation.evaluate(Some(50), false).passed);
+        assert!(!reputation.evaluate(None, false).passed);
+        assert!(reputation.evaluate(None, true).passed);
+    }
+}
@@ -0,0 +1,118 @@
+//! Dry-run evaluation of a proposed policy over the bundles already in a
+//! store, to see which past decisions it would change before rolling it out.
+//!
+//! Each bundle's past verdict is its recorded [`Decision`] when there is
+//! one, and otherwise the guest's own verdict from the journal (sum within
+//! the bound it was proven against, every invariant passed). Receipts are
+//! not re-verified; this answers "what would the new rules say", not "is
+//! this bundle still valid".
+
+use crate::journal;
+use crate::outcome::Outcome;
+use crate::policy::Policy;
+use crate::signing::Decision;
+use crate::store::{ProofBundle, ProofStore};
+use crate::untrusted;
+use serde::Serialize;
+use std::error::Error;
+use std::path::Path;
+use zaik_core::AgentResult;
+
+/// Where a bundle's past verdict came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Baseline {
+    Decision,
+    Guest,
+}
+
+/// One bundle under the proposed policy.
+#[derive(Debug, Clone, Serialize)]
+pub struct BundleChange {
+    pub bundle: String,
+    pub csv_hash: String,
+    pub baseline: Baseline,
+    pub was_accepted: bool,
+    pub accepted: bool,
+    /// The proposed policy has the guest prove different invariants or
+    /// limits, so accepting this data under it takes a new proof.
+    pub needs_reproof: bool,
+    /// Rules the proposed policy fails, e.g. `sum_threshold`.
+    pub failed_rules: Vec<String>,
+}
+
+impl BundleChange {
+    pub fn changed(&self) -> bool {
+        self.was_accepted != self.accepted
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RolloutReport {
+    pub policy_version: String,
+    pub newly_accepted: usize,
+    pub newly_rejected: usize,
+    pub unchanged: usize,
+    pub needs_reproof: usize,
+    /// Bundles that could not be decoded or whose sum opening fails.
+    pub unreadable: Vec<String>,
+    pub bundles: Vec<BundleChange>,
+}
+
+impl RolloutReport {
+    pub fn changed(&self) -> usize {
+        self.newly_accepted + self.newly_rejected
+    }
+}
+
+/// Evaluate `policy` over every bundle in `store`.
+pub fn dry_run(policy: &Policy, store: &ProofStore) -> Result<RolloutReport, Box<dyn Error>> {
+    let mut report = RolloutReport {
+        policy_version: policy.version(),
+        ..RolloutReport::default()
+    };
+    for path in store.bundles()? {
+        let Some(change) = evaluate(policy, &path) else {
+            report.unreadable.push(path.display().to_string());
+            continue;
+        };
+        match (change.was_accepted, change.accepted) {
+            (false, true) => report.newly_accepted += 1,
+            (true, false) => report.newly_rejected += 1,
+            _ => report.unchanged += 1,
+        }
+        if change.needs_reproof {
+            report.needs_reproof += 1;
+        }
+        report.bundles.push(change);
+    }
+    Ok(report)
+}
+
+fn evaluate(policy: &Policy, path: &Path) -> Option<BundleChange> {
+    let bundle = ProofBundle::load(path).ok()?;
+    let result: AgentResult = journal::decode(&bundle.receipt.journal.bytes).ok()?;
+    let sum = bundle.sum_opening.open(&result)?;
+
+    let recorded = untrusted::read_to_string(&Decision::path_for(path), untrusted::MAX_SIDECAR_BYTES)
+        .ok()
+        .and_then(|decision| serde_json::from_str::<Decision>(&decision).ok());
+    let (baseline, was_accepted) = match recorded {
+        Some(decision) => (Baseline::Decision, decision.outcome == Outcome::Accept),
+        None => {
+            let all_passed = result.invariants_passed.count_ones() as usize == result.invariants.len();
+            (Baseline::Guest, result.sum_within_bound && all_passed)
+        }
+    };
+
+    let outcome = policy.evaluate(sum, &result);
+    Some(BundleChange {
+        bundle: path.display().to_string(),
+        csv_hash: hex::encode(result.csv_hash),
+        baseline,
+        was_accepted,
+        accepted: outcome.passed(),
+        needs_reproof: !policy.guest_settings_match(&result),
+        failed_rules: outcome.failed_rules().map(|rule| rule.rule.clone()).collect(),
+    })
+}
@@ -0,0 +1,384 @@
+use crate::deadline::{Deadline, DeadlineExceeded};
+use risc0_zkvm::{
+    default_executor, default_prover, ExecutorEnv, ExecutorEnvBuilder, ExitCode, ProveInfo, ProverOpts, Receipt, ReceiptKind,
+};
+use serde::Serialize;
+use std::error::Error;
+use std::fmt;
+use std::io::Write;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Instant;
+use zaik_core::fault::GuestFault;
+use zaik_core::{GuestInput, InputError};
+
+/// Progress notification emitted by a [`ProofSession`].
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ProverEvent {
+    ExecutionStarted,
+    /// One per segment, reported once execution has split the run.
+    SegmentExecuted { index: usize, po2: u32, user_cycles: u32 },
+    ExecutionFinished { segments: usize, user_cycles: u64, elapsed_ms: u128 },
+    ProvingStarted { segments: usize },
+    ProvingFinished {
+        segments: usize,
+        total_cycles: u64,
+        /// Cycles spent paging guest memory in and out of each segment.
+        paging_cycles: u64,
+        elapsed_ms: u128,
+    },
+}
+
+/// Resource limits applied to the executor environment, and the receipt
+/// kind to produce.
+///
+/// Prover memory grows with the segment size, so `segment_limit_po2` is the
+/// knob that caps memory on a shared prover; `session_limit` bounds the
+/// total cycles a single CSV may consume.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProverOptions {
+    pub segment_limit_po2: Option<u32>,
+    pub session_limit: Option<u64>,
+    /// Defaults to the prover's own default (composite).
+    pub receipt_kind: Option<ReceiptKind>,
+}
+
+/// Why a [`ProofSession`] could not produce a receipt.
+#[derive(Debug)]
+pub enum ProveError {
+    /// The guest exhausted its heap.
+    OutOfGuestMemory(String),
+    /// The guest ran past [`ProverOptions::session_limit`].
+    SessionLimitExceeded(String),
+    /// The guest panicked; `fault` is the condition it checked, if the
+    /// message names one, and `message` what it panicked with.
+    GuestPanicked { fault: Option<GuestFault>, message: String },
+    /// The guest halted with a non-zero exit code.
+    GuestExited(u32),
+    /// The input would make the guest abort; caught before executing it.
+    InvalidInput(InputError),
+    /// The request's [`Deadline`] passed before a receipt was produced.
+    DeadlineExceeded(DeadlineExceeded),
+    Other(Box<dyn Error + Send + Sync>),
+}
+
+impl ProveError {
+    /// Stable identifier used in failure reports.
+    pub fn reason(&self) -> &'static str {
+        match self {
+            ProveError::OutOfGuestMemory(_) => "out_of_guest_memory",
+            ProveError::SessionLimitExceeded(_) => "session_limit_exceeded",
+            ProveError::GuestPanicked { fault: Some(fault), .. } => fault.reason(),
+            ProveError::GuestPanicked { fault: None, .. } => "guest_panicked",
+            ProveError::GuestExited(_) => "guest_exited",
+            ProveError::InvalidInput(InputError::HashMismatch { .. }) => "input_hash_mismatch",
+            ProveError::InvalidInput(InputError::TooManyInvariants(_)) => "too_many_invariants",
+            ProveError::InvalidInput(InputError::DatasetMismatch) => "dataset_mismatch",
+            ProveError::InvalidInput(InputError::Unsupported(_)) => "input_unsupported",
+            ProveError::InvalidInput(InputError::Window(fault)) => fault.reason(),
+            ProveError::DeadlineExceeded(_) => "deadline_exceeded",
+            ProveError::Other(_) => "proving_failed",
+        }
+    }
+
+    /// Sort an executor or prover error into the conditions callers act on.
+    pub fn classify(err: impl Into<Box<dyn Error>>) -> Self {
+        let err = err.into();
+        let message = err.to_string();
+        if message.contains("Out of memory") {
+            ProveError::OutOfGuestMemory(message)
+        } else if message.contains("Session limit exceeded") {
+            ProveError::SessionLimitExceeded(message)
+        } else if let Some((_, panic)) = message.split_once("Guest panicked: ") {
+            ProveError::GuestPanicked {
+                fault: GuestFault::from_panic(panic),
+                message: panic.trim().to_string(),
+            }
+        } else {
+            ProveError::Other(message.into())
+        }
+    }
+}
+
+impl fmt::Display for ProveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProveError::OutOfGuestMemory(message) => write!(f, "guest ran out of memory: {}", message),
+            ProveError::SessionLimitExceeded(message) => write!(f, "{}", message),
+            ProveError::GuestPanicked { fault: Some(fault), .. } => write!(f, "guest aborted: {}", fault),
+            ProveError::GuestPanicked { fault: None, message } => write!(f, "guest panicked: {}", message),
+            ProveError::GuestExited(code) => write!(f, "guest exited with code {}", code),
+            ProveError::InvalidInput(InputError::HashMismatch { expected, actual }) => write!(
+                f,
+                "csv_hash {} does not match the CSV data, which hashes to {}",
+                hex::encode(expected),
+                hex::encode(actual)
+            ),
+            ProveError::InvalidInput(error) => write!(f, "{}", error),
+            ProveError::DeadlineExceeded(exceeded) => write!(f, "{}", exceeded),
+            ProveError::Other(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for ProveError {}
+
+/// Observer for long-running proofs: CLI progress, event streams, or an
+/// orchestrator polling for status.
+pub trait ProverEvents {
+    fn on_event(&self, event: &ProverEvent);
+}
+
+impl<F: Fn(&ProverEvent)> ProverEvents for F {
+    fn on_event(&self, event: &ProverEvent) {
+        self(event)
+    }
+}
+
+/// Writes every event as a JSON line.
+pub struct JsonLinesEvents<W: Write> {
+    writer: Mutex<W>,
+}
+
+impl<W: Write> JsonLinesEvents<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer: Mutex::new(writer) }
+    }
+}
+
+impl<W: Write> ProverEvents for JsonLinesEvents<W> {
+    fn on_event(&self, event: &ProverEvent) {
+        let mut writer = self.writer.lock().expect("event writer poisoned");
+        let line = serde_json::to_string(event).expect("ProverEvent is always serializable");
+        // Progress reporting must never abort a proof
+        let _ = writeln!(writer, "{}", line).and_then(|_| writer.flush());
+    }
+}
+
+/// One proving run of the guest over a CSV input, reporting progress to the
+/// registered observers.
+pub struct ProofSession<'a> {
+    input: &'a GuestInput,
+    elf: &'a [u8],
+    options: ProverOptions,
+    deadline: Option<Deadline>,
+    assumptions: Vec<Receipt>,
+    observers: Vec<&'a dyn ProverEvents>,
+}
+
+impl<'a> ProofSession<'a> {
+    pub fn new(input: &'a GuestInput, elf: &'a [u8]) -> Self {
+        Self {
+            input,
+            elf,
+            options: ProverOptions::default(),
+            deadline: None,
+            assumptions: Vec::new(),
+            observers: Vec::new(),
+        }
+    }
+
+    /// Receipts the guest verifies with `env::verify`, such as the chunk
+    /// receipts a [`GuestInput::Chunked`] run composes.
+    pub fn with_assumptions(mut self, assumptions: Vec<Receipt>) -> Self {
+        self.assumptions = assumptions;
+        self
+    }
+
+    pub fn with_options(mut self, options: ProverOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Give up with [`ProveError::DeadlineExceeded`] once `deadline` passes.
+    pub fn with_deadline(mut self, deadline: Option<Deadline>) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    pub fn observe(mut self, observer: &'a dyn ProverEvents) -> Self {
+        self.observers.push(observer);
+        self
+    }
+
+    fn emit(&self, event: ProverEvent) {
+        for observer in &self.observers {
+            observer.on_event(&event);
+        }
+    }
+
+    /// Check the input, execute the guest to learn its segment layout,
+    /// then prove it.
+    pub fn prove(&self) -> Result<ProveInfo, ProveError> {
+        self.input.validate().map_err(ProveError::InvalidInput)?;
+        self.check_deadline("execution")?;
+        self.emit(ProverEvent::ExecutionStarted);
+        let started = Instant::now();
+        let session = default_executor()
+            .execute(self.env()?, self.elf)
+            .map_err(ProveError::classify)?;
+        if let ExitCode::Halted(code @ 1..) = session.exit_code {
+            return Err(ProveError::GuestExited(code));
+        }
+        for (index, segment) in session.segments.iter().enumerate() {
+            self.emit(ProverEvent::SegmentExecuted {
+                index,
+                po2: segment.po2,
+                user_cycles: segment.cycles,
+            });
+        }
+        let segments = session.segments.len();
+        self.emit(ProverEvent::ExecutionFinished {
+            segments,
+            user_cycles: session.cycles(),
+            elapsed_ms: started.elapsed().as_millis(),
+        });
+
+        self.check_deadline("proving")?;
+        self.emit(ProverEvent::ProvingStarted { segments });
+        let started = Instant::now();
+        let prove_info = match self.deadline {
+            Some(deadline) => self.prove_within(deadline)?,
+            None => prove_receipt(self.input, &self.assumptions, self.elf, self.options).map_err(ProveError::classify)?,
+        };
+        self.emit(ProverEvent::ProvingFinished {
+            segments: prove_info.stats.segments,
+            total_cycles: prove_info.stats.total_cycles,
+            paging_cycles: prove_info.stats.paging_cycles,
+            elapsed_ms: started.elapsed().as_millis(),
+        });
+        Ok(prove_info)
+    }
+
+    fn check_deadline(&self, stage: &str) -> Result<(), ProveError> {
+        match self.deadline {
+            Some(deadline) => deadline.check(stage).map_err(ProveError::DeadlineExceeded),
+            None => Ok(()),
+        }
+    }
+
+    /// Prove on a worker thread and stop waiting when `deadline` passes.
+    /// The prover cannot be interrupted, so an abandoned worker runs on
+    /// until the process exits.
+    fn prove_within(&self, deadline: Deadline) -> Result<ProveInfo, ProveError> {
+        let (input, assumptions, elf, options) =
+            (self.input.clone(), self.assumptions.clone(), self.elf.to_vec(), self.options);
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let _ = sender.send(prove_receipt(&input, &assumptions, &elf, options).map_err(|e| e.to_string()));
+        });
+        match receiver.recv_timeout(deadline.remaining()) {
+            Ok(result) => result.map_err(ProveError::classify),
+            Err(RecvTimeoutError::Timeout) => Err(ProveError::DeadlineExceeded(deadline.exceeded("proving"))),
+            Err(RecvTimeoutError::Disconnected) => Err(ProveError::Other("prover thread panicked".into())),
+        }
+    }
+
+    fn env(&self) -> Result<ExecutorEnv<'static>, ProveError> {
+        executor_env(self.input, &self.assumptions, self.options).map_err(ProveError::classify)
+    }
+}
+
+/// Prove each of `inputs` with the same `elf`, `options` and `deadline` on
+/// up to `jobs` worker threads, for batches of independent receipts such as
+/// the chunks of a chunked run or one Groth16 receipt per file. Results are
+/// in input order, and one input failing, even by panicking, does not stop
+/// the others. Each worker drives its own prover; `observer` hears every
+/// proof's events, interleaved when `jobs` is above 1.
+pub fn prove_many(
+    inputs: &[GuestInput],
+    elf: &[u8],
+    options: ProverOptions,
+    deadline: Option<Deadline>,
+    observer: Option<&(dyn ProverEvents + Sync)>,
+    jobs: usize,
+) -> Vec<Result<ProveInfo, ProveError>> {
+    let next = AtomicUsize::new(0);
+    let finished: Vec<(usize, Result<ProveInfo, ProveError>)> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..jobs.clamp(1, inputs.len().max(1)))
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut done = Vec::new();
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(input) = inputs.get(index) else {
+                            return done;
+                        };
+                        let mut session = ProofSession::new(input, elf).with_options(options).with_deadline(deadline);
+                        if let Some(observer) = observer {
+                            session = session.observe(observer);
+                        }
+                        // A panic fails this input only, not the batch
+                        let result = panic::catch_unwind(AssertUnwindSafe(|| session.prove()))
+                            .unwrap_or_else(|_| Err(ProveError::Other("prover panicked".into())));
+                        done.push((index, result));
+                    }
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap_or_default())
+            .collect()
+    });
+    // Inputs a worker took before dying outside `prove` have no result
+    let mut results: Vec<Option<Result<ProveInfo, ProveError>>> = inputs.iter().map(|_| None).collect();
+    for (index, result) in finished {
+        results[index] = Some(result);
+    }
+    results
+        .into_iter()
+        .map(|result| result.unwrap_or_else(|| Err(ProveError::Other("prover panicked".into()))))
+        .collect()
+}
+
+/// Write `input` the way the guest reads it: serialized, then each frame
+/// as a u32 LE length and the bytes (the layout of risc0's unstable
+/// `write_frame`, written with the stable `write_slice`).
+pub fn write_input(builder: &mut ExecutorEnvBuilder<'_>, input: &GuestInput) -> Result<(), Box<dyn Error>> {
+    builder.write(input)?;
+    for frame in input.frames() {
+        write_frame(builder, frame);
+    }
+    Ok(())
+}
+
+/// Write one frame: a u32 LE length and the bytes.
+pub fn write_frame(builder: &mut ExecutorEnvBuilder<'_>, frame: &[u8]) {
+    builder.write_slice(&(frame.len() as u32).to_le_bytes());
+    builder.write_slice(frame);
+}
+
+fn executor_env(
+    input: &GuestInput,
+    assumptions: &[Receipt],
+    options: ProverOptions,
+) -> Result<ExecutorEnv<'static>, Box<dyn Error>> {
+    let mut builder = ExecutorEnv::builder();
+    write_input(&mut builder, input)?;
+    for receipt in assumptions {
+        builder.add_assumption(receipt.clone());
+    }
+    if let Some(po2) = options.segment_limit_po2 {
+        builder.segment_limit_po2(po2);
+    }
+    builder.session_limit(options.session_limit);
+    Ok(builder.build()?)
+}
+
+fn prove_receipt(
+    input: &GuestInput,
+    assumptions: &[Receipt],
+    elf: &[u8],
+    options: ProverOptions,
+) -> Result<ProveInfo, Box<dyn Error>> {
+    let opts = match options.receipt_kind {
+        Some(kind) => ProverOpts::default().with_receipt_kind(kind),
+        None => ProverOpts::default(),
+    };
+    Ok(default_prover().prove_with_opts(executor_env(input, assumptions, options)?, elf, &opts)?)
+}
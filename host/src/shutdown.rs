@@ -0,0 +1,80 @@
+//! Graceful stops for the daemons and workers.
+//!
+//! [`StopFlag::register`] turns SIGTERM and SIGINT into a flag the work loop
+//! checks between bundles, so a stop lets the bundle in hand finish and its
+//! records reach disk instead of being cut off mid-write. A second signal
+//! while draining exits at once. Off Unix the flag is never raised and the
+//! platform's default handling applies.
+
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How often [`StopFlag::sleep`] looks at the flag.
+const POLL: Duration = Duration::from_millis(200);
+
+/// Set once the process has been asked to stop.
+#[derive(Debug, Clone, Default)]
+pub struct StopFlag(Arc<AtomicBool>);
+
+impl StopFlag {
+    /// A flag raised by the first SIGTERM or SIGINT.
+    #[cfg(unix)]
+    pub fn register() -> io::Result<Self> {
+        use signal_hook::consts::{SIGINT, SIGTERM};
+        use signal_hook::flag;
+
+        let stop = Self::default();
+        for signal in [SIGTERM, SIGINT] {
+            // Registered first, so it sees the flag before this signal sets it
+            flag::register_conditional_shutdown(signal, 1, Arc::clone(&stop.0))?;
+            flag::register(signal, Arc::clone(&stop.0))?;
+        }
+        Ok(stop)
+    }
+
+    #[cfg(not(unix))]
+    pub fn register() -> io::Result<Self> {
+        Ok(Self::default())
+    }
+
+    pub fn is_set(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Sleep for `duration` or until the flag is raised; returns whether it was.
+    pub fn sleep(&self, duration: Duration) -> bool {
+        let until = Instant::now() + duration;
+        while !self.is_set() {
+            let left = until.saturating_duration_since(Instant::now());
+            if left.is_zero() {
+                return false;
+            }
+            thread::sleep(left.min(POLL));
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sleep_wakes_when_the_flag_is_raised() {
+        let stop = StopFlag::default();
+        assert!(!stop.sleep(Duration::from_millis(10)));
+
+        let raiser = stop.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            raiser.0.store(true, Ordering::Relaxed);
+        });
+        let started = Instant::now();
+        assert!(stop.sleep(Duration::from_secs(60)));
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(stop.is_set());
+    }
+}
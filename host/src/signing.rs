@@ -0,0 +1,335 @@
+//! Detached Ed25519 signatures over bundles and decisions.
+//!
+//! The prover signs the bundle file's digest; the verifier signs a
+//! [`Decision`] that names that bundle digest and the prover's key. A
+//! relying party holding both signatures and the keys it trusts for each
+//! role can check the whole chain with [`verify_chain`] without trusting
+//! whoever handed the files over.
+//!
+//! Each signature covers the signer's role tag followed by the digest, so a
+//! prover signature can never be passed off as a verifier's.
+
+use crate::assurance::AssuranceTier;
+use crate::outcome::Outcome;
+use crate::protocol::RevealRequest;
+use crate::store;
+use crate::untrusted;
+use ring::rand::SystemRandom;
+use ring::signature::{Ed25519KeyPair, KeyPair, UnparsedPublicKey, ED25519};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Who produced a signature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignerRole {
+    Prover,
+    Verifier,
+    /// A third agent ruling on a dispute; see [`crate::dispute`].
+    Arbiter,
+}
+
+impl SignerRole {
+    /// Domain tag prefixed to the digest before signing.
+    pub fn domain(self) -> &'static [u8] {
+        match self {
+            SignerRole::Prover => b"zaik.sig.prover.v1",
+            SignerRole::Verifier => b"zaik.sig.verifier.v1",
+            SignerRole::Arbiter => b"zaik.sig.arbiter.v1",
+        }
+    }
+}
+
+#[cfg(unix)]
+fn create_private(path: &Path) -> io::Result<fs::File> {
+    use std::os::unix::fs::OpenOptionsExt;
+    fs::OpenOptions::new().write(true).create_new(true).mode(0o600).open(path)
+}
+
+#[cfg(not(unix))]
+fn create_private(path: &Path) -> io::Result<fs::File> {
+    fs::OpenOptions::new().write(true).create_new(true).open(path)
+}
+
+/// The bytes a `role` signature over `digest` actually signs.
+fn signed_message(role: SignerRole, digest: &[u8; 32]) -> Vec<u8> {
+    [role.domain(), digest.as_slice()].concat()
+}
+
+/// An agent's Ed25519 key, stored as PKCS#8.
+pub struct AgentKey {
+    pair: Ed25519KeyPair,
+}
+
+impl AgentKey {
+    /// Create a new key and write it to `path`, readable only by its owner
+    /// on Unix. An existing file is never overwritten.
+    pub fn generate(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).map_err(|_| "key generation failed")?;
+        let mut file = create_private(path).map_err(|e| match e.kind() {
+            io::ErrorKind::AlreadyExists => format!("{} already exists; not overwriting a key", path.display()).into(),
+            _ => Box::<dyn Error>::from(e),
+        })?;
+        file.write_all(pkcs8.as_ref())?;
+        file.sync_all()?;
+        Self::load(path)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let pair = Ed25519KeyPair::from_pkcs8(&fs::read(path)?).map_err(|e| format!("invalid key file: {}", e))?;
+        Ok(Self { pair })
+    }
+
+    /// Hex public key, the signer's identity in signature files.
+    pub fn public_key(&self) -> String {
+        hex::encode(self.pair.public_key().as_ref())
+    }
+
+    /// Sign the SHA-256 of `payload` under `role`'s domain tag.
+    pub fn sign(&self, role: SignerRole, payload: &[u8]) -> DetachedSignature {
+        let digest: [u8; 32] = Sha256::digest(payload).into();
+        DetachedSignature {
+            role,
+            signer: self.public_key(),
+            digest: hex::encode(digest),
+            signature: hex::encode(self.pair.sign(&signed_message(role, &digest)).as_ref()),
+        }
+    }
+
+    /// Raw Ed25519 signature over `message` itself, for envelopes that fix
+    /// their own signed encoding (see [`crate::attestation`]).
+    pub fn sign_message(&self, message: &[u8]) -> Vec<u8> {
+        self.pair.sign(message).as_ref().to_vec()
+    }
+}
+
+/// Signature stored next to the file it covers.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DetachedSignature {
+    pub role: SignerRole,
+    pub signer: String,
+    /// Hex SHA-256 of the signed file.
+    pub digest: String,
+    pub signature: String,
+}
+
+impl DetachedSignature {
+    /// Sidecar path for a signature over `path`.
+    pub fn path_for(path: &Path) -> PathBuf {
+        let mut name = path.as_os_str().to_owned();
+        name.push(".sig");
+        PathBuf::from(name)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        Ok(serde_json::from_slice(&untrusted::read(path, untrusted::MAX_SIDECAR_BYTES)?)?)
+    }
+
+    /// Written atomically, so a reader never sees half a signature.
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        store::write_atomic(path, serde_json::to_string_pretty(self)?.as_bytes())?;
+        Ok(())
+    }
+
+    /// Check that this signature is `role`'s, covers `payload`, and is valid.
+    pub fn verify(&self, role: SignerRole, payload: &[u8]) -> Result<(), ChainError> {
+        if self.role != role {
+            return Err(ChainError::WrongRole { expected: role });
+        }
+        let digest: [u8; 32] = Sha256::digest(payload).into();
+        if self.digest != hex::encode(digest) {
+            return Err(ChainError::DigestMismatch { role });
+        }
+        let public_key = hex::decode(&self.signer).map_err(|_| ChainError::BadSignature { role })?;
+        let signature = hex::decode(&self.signature).map_err(|_| ChainError::BadSignature { role })?;
+        UnparsedPublicKey::new(&ED25519, public_key)
+            .verify(&signed_message(role, &digest), &signature)
+            .map_err(|_| ChainError::BadSignature { role })
+    }
+}
+
+/// A verifier's signed verdict on one bundle.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Decision {
+    /// Hex SHA-256 of the bundle file.
+    pub bundle_digest: String,
+    /// Public key of the prover whose bundle signature was checked.
+    pub prover: String,
+    pub policy_version: String,
+    pub outcome: Outcome,
+    pub explanation: String,
+    /// What a conditional decision asks the prover to reveal; see
+    /// [`crate::protocol`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reveal: Option<RevealRequest>,
+    /// Tier the sum fell in, under a policy with an `[assurance]` table.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assurance_tier: Option<AssuranceTier>,
+}
+
+impl Decision {
+    /// Path of the decision recorded for the bundle at `bundle_path`.
+    pub fn path_for(bundle_path: &Path) -> PathBuf {
+        bundle_path.with_extension("decision.json")
+    }
+
+    /// The exact bytes the verifier signs.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec_pretty(self).expect("Decision is always serializable")
+    }
+}
+
+/// Why a signature chain did not validate.
+#[derive(Debug)]
+pub enum ChainError {
+    WrongRole { expected: SignerRole },
+    DigestMismatch { role: SignerRole },
+    BadSignature { role: SignerRole },
+    UntrustedSigner { role: SignerRole, signer: String },
+    /// No trusted key was given for this role, so no signer can be accepted.
+    NoTrustAnchor { role: SignerRole },
+    /// The decision is about a different bundle or prover than the one signed.
+    DecisionMismatch,
+}
+
+impl fmt::Display for ChainError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChainError::WrongRole { expected } => write!(f, "expected a {:?} signature", expected),
+            ChainError::DigestMismatch { role } => write!(f, "{:?} signature covers different content", role),
+            ChainError::BadSignature { role } => write!(f, "{:?} signature is invalid", role),
+            ChainError::UntrustedSigner { role, signer } => write!(f, "{:?} key {} is not trusted", role, signer),
+            ChainError::NoTrustAnchor { role } => write!(f, "no trusted {:?} keys given; chain is UNTRUSTED", role),
+            ChainError::DecisionMismatch => write!(f, "decision does not refer to the signed bundle"),
+        }
+    }
+}
+
+impl Error for ChainError {}
+
+/// Validate prover signature → decision → verifier signature.
+///
+/// Each signer must be in the trust list for its role. An empty list is a
+/// [`ChainError::NoTrustAnchor`] rather than a pass: a chain nobody vouches
+/// for proves only that someone holds a key.
+pub fn verify_chain(
+    bundle_bytes: &[u8],
+    prover_signature: &DetachedSignature,
+    decision: &Decision,
+    verifier_signature: &DetachedSignature,
+    trusted_provers: &[String],
+    trusted_verifiers: &[String],
+) -> Result<(), ChainError> {
+    for (role, trusted) in [(SignerRole::Prover, trusted_provers), (SignerRole::Verifier, trusted_verifiers)] {
+        if trusted.is_empty() {
+            return Err(ChainError::NoTrustAnchor { role });
+        }
+    }
+    prover_signature.verify(SignerRole::Prover, bundle_bytes)?;
+    if !trusted_provers.contains(&prover_signature.signer) {
+        return Err(ChainError::UntrustedSigner {
+            role: SignerRole::Prover,
+            signer: prover_signature.signer.clone(),
+        });
+    }
+    if decision.bundle_digest != prover_signature.digest || decision.prover != prover_signature.signer {
+        return Err(ChainError::DecisionMismatch);
+    }
+    verifier_signature.verify(SignerRole::Verifier, &decision.to_bytes())?;
+    if !trusted_verifiers.contains(&verifier_signature.signer) {
+        return Err(ChainError::UntrustedSigner {
+            role: SignerRole::Verifier,
+            signer: verifier_signature.signer.clone(),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(name: &str) -> AgentKey {
+        let path = std::env::temp_dir().join(format!("zaik-signing-{}-{}.key", name, std::process::id()));
+        let _ = fs::remove_file(&path);
+        let key = AgentKey::generate(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        key
+    }
+
+    fn chain(prover: &AgentKey, verifier: &AgentKey) -> (Vec<u8>, DetachedSignature, Decision, DetachedSignature) {
+        let bundle = b"bundle bytes".to_vec();
+        let prover_signature = prover.sign(SignerRole::Prover, &bundle);
+        let decision = Decision {
+            bundle_digest: prover_signature.digest.clone(),
+            prover: prover.public_key(),
+            policy_version: "test".into(),
+            outcome: Outcome::Accept,
+            explanation: "ok".into(),
+            reveal: None,
+            assurance_tier: None,
+        };
+        let verifier_signature = verifier.sign(SignerRole::Verifier, &decision.to_bytes());
+        (bundle, prover_signature, decision, verifier_signature)
+    }
+
+    #[test]
+    fn chain_verifies_against_trusted_keys() {
+        let (prover, verifier) = (key("prover"), key("verifier"));
+        let (bundle, prover_signature, decision, verifier_signature) = chain(&prover, &verifier);
+        verify_chain(
+            &bundle,
+            &prover_signature,
+            &decision,
+            &verifier_signature,
+            &[prover.public_key()],
+            &[verifier.public_key()],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn chain_without_trust_anchor_is_untrusted() {
+        let (prover, verifier) = (key("prover"), key("verifier"));
+        let (bundle, prover_signature, decision, verifier_signature) = chain(&prover, &verifier);
+        let verify = |provers: &[String], verifiers: &[String]| {
+            verify_chain(&bundle, &prover_signature, &decision, &verifier_signature, provers, verifiers)
+        };
+        let result = verify(&[], &[verifier.public_key()]);
+        assert!(matches!(result, Err(ChainError::NoTrustAnchor { role: SignerRole::Prover })));
+        let result = verify(&[prover.public_key()], &[]);
+        assert!(matches!(result, Err(ChainError::NoTrustAnchor { role: SignerRole::Verifier })));
+    }
+
+    #[test]
+    fn prover_signature_cannot_be_replayed_as_verifier() {
+        let key = key("replay");
+        let payload = b"decision bytes";
+        let mut signature = key.sign(SignerRole::Prover, payload);
+        signature.role = SignerRole::Verifier;
+        assert!(matches!(
+            signature.verify(SignerRole::Verifier, payload),
+            Err(ChainError::BadSignature { role: SignerRole::Verifier })
+        ));
+    }
+
+    #[test]
+    fn generated_keys_are_private_and_never_overwritten() {
+        let path = std::env::temp_dir().join(format!("zaik-signing-private-{}.key", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let key = AgentKey::generate(&path).unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+        assert!(AgentKey::generate(&path).is_err());
+        assert_eq!(AgentKey::load(&path).unwrap().public_key(), key.public_key());
+        fs::remove_file(&path).unwrap();
+    }
+}
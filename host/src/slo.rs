@@ -0,0 +1,264 @@
+//! Service-level objectives for proving latency and bundle size.
+//!
+//! Every proof the host produces (not one reused from the store) is logged
+//! to `slo.jsonl` with how long proving took and how big the bundle is.
+//! [`Objectives`] set the bounds and the share of proofs that must meet
+//! them:
+//! ```toml
+//! proving_ms = 120000       # a proof should take at most this long...
+//! bundle_bytes = 2000000    # ...and its bundle be at most this big...
+//! target = 0.95             # ...for this share of the proofs in the window
+//! window = 200              # most recent proofs considered
+//! max_p95_growth = 1.25     # p95 proving time may grow 25% across a guest change
+//! severity = "warning"      # for `slo --alerts`
+//! ```
+//!
+//! A burn rate is the share of proofs in the window that missed an
+//! objective divided by the share allowed to (`1 - target`): above 1 the
+//! error budget runs out before the window does. A guest change is judged
+//! by comparing the p95 proving time of the newest image ID's proofs with
+//! that of the image before it.
+
+use crate::alerts::Severity;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// One produced proof.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Sample {
+    /// Hex guest image ID the proof was made with.
+    pub image_id: String,
+    /// [`ProofKey`](crate::store::ProofKey) digest of the bundle, so its
+    /// proving time can be put down to its prover; see
+    /// [`crate::reputation`]. Empty in samples from before it was logged.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub bundle: String,
+    pub proving_ms: u64,
+    pub bundle_bytes: u64,
+    pub total_cycles: u64,
+    /// Seconds since the Unix epoch.
+    pub recorded_at: u64,
+}
+
+impl Sample {
+    pub fn new(image_id: &str, bundle: &str, proving_ms: u64, bundle_bytes: u64, total_cycles: u64) -> Self {
+        Self {
+            image_id: image_id.to_string(),
+            bundle: bundle.to_string(),
+            proving_ms,
+            bundle_bytes,
+            total_cycles,
+            recorded_at: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+        }
+    }
+}
+
+/// Append-only JSON-lines log of [`Sample`]s.
+pub struct SloLog {
+    path: PathBuf,
+}
+
+impl SloLog {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Every sample, oldest first; none if nothing was proven yet.
+    pub fn samples(&self) -> Result<Vec<Sample>, Box<dyn Error>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        fs::read_to_string(&self.path)?
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| Ok(serde_json::from_str(line)?))
+            .collect()
+    }
+
+    pub fn record(&self, sample: &Sample) -> Result<(), Box<dyn Error>> {
+        let mut file = fs::OpenOptions::new().create(true).append(true).open(&self.path)?;
+        writeln!(file, "{}", serde_json::to_string(sample)?)?;
+        Ok(())
+    }
+}
+
+/// The `--objectives` file.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Objectives {
+    #[serde(default)]
+    pub proving_ms: Option<u64>,
+    #[serde(default)]
+    pub bundle_bytes: Option<u64>,
+    pub target: f64,
+    #[serde(default = "Objectives::default_window")]
+    pub window: usize,
+    #[serde(default)]
+    pub max_p95_growth: Option<f64>,
+    #[serde(default = "Objectives::default_severity")]
+    pub severity: Severity,
+}
+
+impl Objectives {
+    fn default_window() -> usize {
+        200
+    }
+
+    fn default_severity() -> Severity {
+        Severity::Warning
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let objectives: Self = toml::from_str(&fs::read_to_string(path)?)?;
+        if !(objectives.target > 0.0 && objectives.target < 1.0) {
+            return Err(format!("target {} must be between 0 and 1, exclusive", objectives.target).into());
+        }
+        if objectives.window == 0 {
+            return Err("window must cover at least one proof".into());
+        }
+        if objectives.max_p95_growth.is_some_and(|growth| growth < 1.0) {
+            return Err("max_p95_growth must be at least 1".into());
+        }
+        Ok(objectives)
+    }
+}
+
+/// How one objective fares over the window.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Burn {
+    pub objective: u64,
+    pub missed: usize,
+    pub burn_rate: f64,
+}
+
+/// p95 proving time before and after the latest guest change.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GuestChange {
+    pub previous_image_id: String,
+    pub image_id: String,
+    pub previous_p95_ms: u64,
+    pub p95_ms: u64,
+    pub growth: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SloReport {
+    pub samples: usize,
+    pub p50_ms: u64,
+    pub p95_ms: u64,
+    pub p95_bundle_bytes: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency: Option<Burn>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<Burn>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub guest_change: Option<GuestChange>,
+    /// Why the objectives are not met; empty when they are.
+    pub breaches: Vec<String>,
+}
+
+/// Nearest-rank percentile; 0 for no values.
+pub fn percentile(values: &[u64], p: f64) -> u64 {
+    let mut sorted = values.to_vec();
+    sorted.sort_unstable();
+    let rank = ((p * sorted.len() as f64).ceil() as usize).clamp(1, sorted.len().max(1));
+    sorted.get(rank - 1).copied().unwrap_or(0)
+}
+
+/// Judge the newest `objectives.window` samples.
+pub fn evaluate(objectives: &Objectives, samples: &[Sample]) -> SloReport {
+    let window = &samples[samples.len().saturating_sub(objectives.window)..];
+    let latencies: Vec<u64> = window.iter().map(|sample| sample.proving_ms).collect();
+    let sizes: Vec<u64> = window.iter().map(|sample| sample.bundle_bytes).collect();
+    let allowed = 1.0 - objectives.target;
+    let burn = |values: &[u64], objective: u64| {
+        let missed = values.iter().filter(|&&value| value > objective).count();
+        let share = if values.is_empty() { 0.0 } else { missed as f64 / values.len() as f64 };
+        Burn {
+            objective,
+            missed,
+            burn_rate: share / allowed,
+        }
+    };
+
+    let mut report = SloReport {
+        samples: window.len(),
+        p50_ms: percentile(&latencies, 0.50),
+        p95_ms: percentile(&latencies, 0.95),
+        p95_bundle_bytes: percentile(&sizes, 0.95),
+        latency: objectives.proving_ms.map(|objective| burn(&latencies, objective)),
+        size: objectives.bundle_bytes.map(|objective| burn(&sizes, objective)),
+        guest_change: guest_change(samples),
+        breaches: Vec::new(),
+    };
+    if let Some(latency) = report.latency.as_ref().filter(|burn| burn.burn_rate > 1.0) {
+        report.breaches.push(format!(
+            "proving time burn rate {:.2}: {} of {} proofs took over {} ms",
+            latency.burn_rate, latency.missed, report.samples, latency.objective
+        ));
+    }
+    if let Some(size) = report.size.as_ref().filter(|burn| burn.burn_rate > 1.0) {
+        report.breaches.push(format!(
+            "bundle size burn rate {:.2}: {} of {} bundles were over {} bytes",
+            size.burn_rate, size.missed, report.samples, size.objective
+        ));
+    }
+    if let (Some(change), Some(limit)) = (&report.guest_change, objectives.max_p95_growth) {
+        if change.growth > limit {
+            report.breaches.push(format!(
+                "p95 proving time grew {:.2}x ({} ms to {} ms) with guest {}, over the allowed {:.2}x",
+                change.growth, change.previous_p95_ms, change.p95_ms, change.image_id, limit
+            ));
+        }
+    }
+    report
+}
+
+/// Compare the newest image ID's proofs with the image's before it.
+fn guest_change(samples: &[Sample]) -> Option<GuestChange> {
+    let image_id = &samples.last()?.image_id;
+    let previous_image_id = &samples.iter().rev().find(|sample| &sample.image_id != image_id)?.image_id;
+    let p95_of = |image: &str| {
+        let latencies: Vec<u64> = samples
+            .iter()
+            .filter(|sample| sample.image_id == image)
+            .map(|sample| sample.proving_ms)
+            .collect();
+        percentile(&latencies, 0.95)
+    };
+    let (previous_p95_ms, p95_ms) = (p95_of(previous_image_id), p95_of(image_id));
+    Some(GuestChange {
+        previous_image_id: previous_image_id.clone(),
+        image_id: image_id.clone(),
+        previous_p95_ms,
+        p95_ms,
+        growth: p95_ms as f64 / previous_p95_ms.max(1) as f64,
+    })
+}
+
+/// The report in Prometheus text exposition format, for a node exporter's
+/// textfile collector.
+pub fn to_prometheus(report: &SloReport) -> String {
+    let mut out = String::new();
+    let mut gauge = |name: &str, help: &str, value: f64| {
+        out.push_str(&format!("# HELP {} {}\n# TYPE {} gauge\n{} {}\n", name, help, name, name, value));
+    };
+    gauge("zaik_slo_samples", "Proofs in the SLO window.", report.samples as f64);
+    gauge("zaik_proving_ms_p50", "Median proving time over the window.", report.p50_ms as f64);
+    gauge("zaik_proving_ms_p95", "95th percentile proving time over the window.", report.p95_ms as f64);
+    gauge("zaik_bundle_bytes_p95", "95th percentile bundle size over the window.", report.p95_bundle_bytes as f64);
+    if let Some(latency) = &report.latency {
+        gauge("zaik_proving_slo_burn_rate", "Proving time error budget burn rate.", latency.burn_rate);
+    }
+    if let Some(size) = &report.size {
+        gauge("zaik_bundle_size_slo_burn_rate", "Bundle size error budget burn rate.", size.burn_rate);
+    }
+    if let Some(change) = &report.guest_change {
+        gauge("zaik_guest_change_p95_growth", "p95 proving time growth across the last guest change.", change.growth);
+    }
+    out
+}
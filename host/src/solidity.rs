@@ -0,0 +1,341 @@
+//! Solidity sources for enforcing a policy on-chain.
+//!
+//! [`journal_library`] emits `ZaikJournal.sol`, a decoder for the journal
+//! byte layout generated from the same [`JournalSchema`] the guest commits,
+//! and [`verifier_contract`] emits `ZaikVerifier.sol`, which checks a seal
+//! with the RISC Zero verifier router and then applies a policy's bound,
+//! invariants, inline limit and row limit to the decoded journal. The checks mirror
+//! [`Policy::matches_journal`] plus the guest's own verdicts, so a contract
+//! accepts exactly the journals Agent B would. Concealed sums cannot be
+//! opened on-chain; the guest-evaluated `sum_within_bound` stands in for
+//! them.
+
+use crate::policy::Policy;
+use risc0_zkvm::sha::Digest;
+use std::error::Error;
+use zaik_core::invariant::Aggregate;
+use zaik_core::schema::{JournalField, JournalSchema, JOURNAL_FORMAT};
+use zaik_core::{ColumnHashMode, ComparisonOp};
+
+const HEADER: &str = "\
+// SPDX-License-Identifier: Apache-2.0
+// Generated by `host codegen`; do not edit.
+pragma solidity ^0.8.20;
+";
+
+/// Solidity type and `ZaikJournal` reader for a journal field type.
+fn solidity_type(field: &JournalField) -> Result<(&'static str, &'static str), Box<dyn Error>> {
+    Ok(match field.ty {
+        "[u8; 32]" | "[u32; 8]" => ("bytes32", "readBytes32(data, o)"),
+        "usize" | "u64" => ("uint64", "readU64(data, o)"),
+        "u32" => ("uint32", "readU32(data, o)"),
+        "bool" => ("bool", "readBool(data, o)"),
+        "ColumnHashMode" => ("uint8", "readTag(data, o, MODE_CHUNKED)"),
+        "ComparisonOp" => ("uint8", "readTag(data, o, OP_GT)"),
+        "InlineCsv" => ("bytes", "readBytes(data, o)"),
+        "Dialect" => ("Dialect", "readDialect(data, o)"),
+        "Vec<InvariantResult>" => ("Invariant[]", "readInvariants(data, o)"),
+        other => {
+            return Err(format!("no Solidity encoding for journal field `{}` of type `{}`", field.name, other).into())
+        }
+    })
+}
+
+/// `snake_case` to `camelCase`.
+fn camel_case(name: &str) -> String {
+    let mut out = String::new();
+    let mut upper = false;
+    for ch in name.chars() {
+        if ch == '_' {
+            upper = true;
+        } else if upper {
+            out.push(ch.to_ascii_uppercase());
+            upper = false;
+        } else {
+            out.push(ch);
+        }
+    }
+    out
+}
+
+fn column_hash_tag(mode: ColumnHashMode) -> u8 {
+    match mode {
+        ColumnHashMode::Joined => 0,
+        ColumnHashMode::Transcript => 1,
+        ColumnHashMode::RawBytes => 2,
+        ColumnHashMode::Chunked => 3,
+    }
+}
+
+fn comparison_tag(op: ComparisonOp) -> u8 {
+    match op {
+        ComparisonOp::Lt => 0,
+        ComparisonOp::Le => 1,
+        ComparisonOp::Ge => 2,
+        ComparisonOp::Gt => 3,
+    }
+}
+
+fn aggregate_tag(aggregate: Aggregate) -> u8 {
+    match aggregate {
+        Aggregate::Sum => 0,
+        Aggregate::Count => 1,
+        Aggregate::Min => 2,
+        Aggregate::Max => 3,
+        Aggregate::Duplicates => 4,
+        Aggregate::Decreases => 5,
+    }
+}
+
+/// `ZaikJournal.sol`: the journal struct and a decoder that rejects
+/// anything but exactly one journal of the current format.
+pub fn journal_library<J: JournalSchema>() -> Result<String, Box<dyn Error>> {
+    let mut fields = String::new();
+    let mut reads = String::new();
+    for field in J::FIELDS {
+        let (ty, reader) = solidity_type(field)?;
+        let name = camel_case(field.name);
+        fields.push_str(&format!("        // {}\n        {} {};\n", field.doc.trim().replace('\n', " "), ty, name));
+        reads.push_str(&format!("        (j.{}, o) = {};\n", name, reader));
+    }
+
+    let mut tags = String::new();
+    for mode in [
+        ColumnHashMode::Joined,
+        ColumnHashMode::Transcript,
+        ColumnHashMode::RawBytes,
+        ColumnHashMode::Chunked,
+    ] {
+        tags.push_str(&format!(
+            "    uint8 internal constant MODE_{} = {};\n",
+            mode.as_str().to_ascii_uppercase(),
+            column_hash_tag(mode)
+        ));
+    }
+    for op in [ComparisonOp::Lt, ComparisonOp::Le, ComparisonOp::Ge, ComparisonOp::Gt] {
+        tags.push_str(&format!(
+            "    uint8 internal constant OP_{} = {};\n",
+            op.as_str().to_ascii_uppercase(),
+            comparison_tag(op)
+        ));
+    }
+    for aggregate in [
+        Aggregate::Sum,
+        Aggregate::Count,
+        Aggregate::Min,
+        Aggregate::Max,
+        Aggregate::Duplicates,
+        Aggregate::Decreases,
+    ] {
+        tags.push_str(&format!(
+            "    uint8 internal constant AGG_{} = {};\n",
+            aggregate.as_str().to_ascii_uppercase(),
+            aggregate_tag(aggregate)
+        ));
+    }
+
+    Ok(format!(
+        r#"{HEADER}
+/// @notice Decodes zaik journals in byte-layout format {format}.
+library ZaikJournal {{
+    bytes4 internal constant MAGIC = "ZAIK";
+    uint32 internal constant FORMAT = {format};
+
+{tags}
+    struct Invariant {{
+        uint64 column;
+        uint8 aggregate;
+        uint8 op;
+        uint64 bound;
+        uint64 value;
+        bytes32 commitment;
+    }}
+
+    struct Dialect {{
+        uint8 delimiter;
+        uint8 quote;
+        bool hasHeader;
+    }}
+
+    struct Journal {{
+{fields}    }}
+
+    /// @notice Decode `data`, reverting unless it is exactly one journal.
+    function decode(bytes calldata data) internal pure returns (Journal memory j) {{
+        require(data.length >= 8 && bytes4(data[0:4]) == MAGIC, "ZaikJournal: not a zaik journal");
+        (uint32 format, uint256 o) = readU32(data, 4);
+        require(format == FORMAT, "ZaikJournal: unsupported format");
+{reads}        require(o == data.length, "ZaikJournal: trailing bytes");
+    }}
+
+    function readU8(bytes calldata data, uint256 o) private pure returns (uint8, uint256) {{
+        return (uint8(data[o]), o + 1);
+    }}
+
+    function readTag(bytes calldata data, uint256 o, uint8 max) private pure returns (uint8 tag, uint256) {{
+        (tag, o) = readU8(data, o);
+        require(tag <= max, "ZaikJournal: tag out of range");
+        return (tag, o);
+    }}
+
+    function readBool(bytes calldata data, uint256 o) private pure returns (bool, uint256) {{
+        (uint8 tag, uint256 next) = readTag(data, o, 1);
+        return (tag == 1, next);
+    }}
+
+    function readU32(bytes calldata data, uint256 o) private pure returns (uint32 v, uint256) {{
+        for (uint256 i = 0; i < 4; i++) {{
+            v |= uint32(uint8(data[o + i])) << uint32(8 * i);
+        }}
+        return (v, o + 4);
+    }}
+
+    function readU64(bytes calldata data, uint256 o) private pure returns (uint64 v, uint256) {{
+        for (uint256 i = 0; i < 8; i++) {{
+            v |= uint64(uint8(data[o + i])) << uint64(8 * i);
+        }}
+        return (v, o + 8);
+    }}
+
+    function readBytes32(bytes calldata data, uint256 o) private pure returns (bytes32, uint256) {{
+        return (bytes32(data[o:o + 32]), o + 32);
+    }}
+
+    function readBytes(bytes calldata data, uint256 o) private pure returns (bytes memory, uint256) {{
+        (uint32 len, uint256 start) = readU32(data, o);
+        return (data[start:start + len], start + len);
+    }}
+
+    function readDialect(bytes calldata data, uint256 o) private pure returns (Dialect memory d, uint256) {{
+        (d.delimiter, o) = readU8(data, o);
+        (d.quote, o) = readU8(data, o);
+        (d.hasHeader, o) = readBool(data, o);
+        require(
+            dialectByte(d.delimiter) && dialectByte(d.quote) && d.delimiter != d.quote,
+            "ZaikJournal: invalid dialect"
+        );
+        return (d, o);
+    }}
+
+    /// ASCII other than a line break, as the guest's parser requires.
+    function dialectByte(uint8 b) private pure returns (bool) {{
+        return b < 0x80 && b != 0x0a && b != 0x0d;
+    }}
+
+    function readInvariants(bytes calldata data, uint256 o) private pure returns (Invariant[] memory list, uint256) {{
+        uint32 count;
+        (count, o) = readU32(data, o);
+        require(count <= data.length - o, "ZaikJournal: invariant count out of range");
+        list = new Invariant[](count);
+        for (uint256 i = 0; i < count; i++) {{
+            Invariant memory inv = list[i];
+            (inv.column, o) = readU64(data, o);
+            (inv.aggregate, o) = readTag(data, o, AGG_DECREASES);
+            (inv.op, o) = readTag(data, o, OP_GT);
+            (inv.bound, o) = readU64(data, o);
+            (inv.value, o) = readU64(data, o);
+            (inv.commitment, o) = readBytes32(data, o);
+        }}
+        return (list, o);
+    }}
+}}
+"#,
+        format = JOURNAL_FORMAT,
+    ))
+}
+
+/// `ZaikVerifier.sol`: accepts receipts of `image_id` whose journal
+/// satisfies `policy`, which is baked in as constants.
+pub fn verifier_contract(policy: &Policy, image_id: Digest) -> String {
+    let invariants = policy.proven_invariants();
+    let mut checks = String::new();
+    for (index, invariant) in invariants.iter().enumerate() {
+        checks.push_str(&format!(
+            "        requireInvariant(j.invariants[{}], {}, {}, {}, {});\n",
+            index,
+            invariant.column,
+            aggregate_tag(invariant.aggregate),
+            comparison_tag(invariant.op),
+            invariant.bound
+        ));
+    }
+    let passed_mask = if invariants.is_empty() { 0u64 } else { (1u64 << invariants.len()) - 1 };
+
+    format!(
+        r#"{HEADER}
+import {{IRiscZeroVerifier}} from "risc0/IRiscZeroVerifier.sol";
+import {{ZaikJournal}} from "./ZaikJournal.sol";
+
+/// @notice Accepts zaik receipts proven under policy {policy_version}.
+contract ZaikVerifier {{
+    IRiscZeroVerifier public immutable verifier;
+
+    bytes32 public constant IMAGE_ID = 0x{image_id};
+    bytes32 public constant POLICY_HASH = 0x{policy_version};
+    uint8 public constant SUM_COMPARISON = {comparison};
+    uint64 public constant SUM_THRESHOLD = {threshold};
+    uint64 public constant INLINE_LIMIT = {inline_limit};
+    uint64 public constant MAX_ROWS = {max_rows};
+    bool public constant ALLOW_TRUNCATED = {allow_truncated};
+    uint256 public constant INVARIANT_COUNT = {invariant_count};
+    uint32 public constant INVARIANTS_PASSED = {passed_mask};
+
+    event Accepted(bytes32 indexed csvHash, bytes32 journalDigest);
+
+    constructor(IRiscZeroVerifier _verifier) {{
+        verifier = _verifier;
+    }}
+
+    /// @notice Verify the seal over `journal` and apply the policy; reverts
+    /// on any failure and returns the decoded journal otherwise.
+    function check(bytes calldata seal, bytes calldata journal) public view returns (ZaikJournal.Journal memory j) {{
+        verifier.verify(seal, IMAGE_ID, sha256(journal));
+        j = ZaikJournal.decode(journal);
+        require(
+            j.columnHashMode != ZaikJournal.MODE_CHUNKED || j.chunkImageId == IMAGE_ID,
+            "ZaikVerifier: chunk image not allowed"
+        );
+        require(
+            j.sumComparison == SUM_COMPARISON && j.sumThreshold == SUM_THRESHOLD,
+            "ZaikVerifier: proven against a different sum bound"
+        );
+        require(j.sumWithinBound, "ZaikVerifier: sum outside the bound");
+        require(j.inlineLimit == INLINE_LIMIT, "ZaikVerifier: proven with a different inline limit");
+        require(j.maxRows == MAX_ROWS, "ZaikVerifier: proven with a different row limit");
+        require(!j.truncated || ALLOW_TRUNCATED, "ZaikVerifier: input was truncated");
+        require(j.invariants.length == INVARIANT_COUNT, "ZaikVerifier: proven against different invariants");
+{checks}        require(j.invariantsPassed == INVARIANTS_PASSED, "ZaikVerifier: invariant failed");
+    }}
+
+    /// @notice `check`, recording the acceptance.
+    function accept(bytes calldata seal, bytes calldata journal) external returns (ZaikJournal.Journal memory j) {{
+        j = check(seal, journal);
+        emit Accepted(j.csvHash, sha256(journal));
+    }}
+
+    function requireInvariant(
+        ZaikJournal.Invariant memory inv,
+        uint64 column,
+        uint8 aggregate,
+        uint8 op,
+        uint64 bound
+    ) private pure {{
+        require(
+            inv.column == column && inv.aggregate == aggregate && inv.op == op && inv.bound == bound,
+            "ZaikVerifier: proven against different invariants"
+        );
+    }}
+}}
+"#,
+        image_id = image_id,
+        policy_version = policy.version(),
+        comparison = comparison_tag(policy.comparison),
+        threshold = policy.sum_threshold,
+        inline_limit = policy.inline_limit(),
+        max_rows = policy.row_limit(),
+        allow_truncated = policy.allow_truncated.unwrap_or(false),
+        invariant_count = invariants.len(),
+        passed_mask = passed_mask,
+        checks = checks,
+    )
+}
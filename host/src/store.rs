@@ -0,0 +1,316 @@
+use crate::audit_log::AuditLog;
+use crate::bonding::MockLedger;
+use crate::budgets::BudgetLedger;
+use crate::marketplace::Marketplace;
+use crate::metadata::BundleMetadata;
+use crate::privacy::DisclosureLedger;
+use crate::proof::ProofKind;
+use crate::signing::DetachedSignature;
+use crate::slo::SloLog;
+use crate::trend::SumHistory;
+use crate::untrusted;
+use risc0_zkvm::Receipt;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use zaik_core::dialect::Dialect;
+use zaik_core::{AgentResult, ProcessingOptions, SumOpening};
+
+/// Everything that determines a proof's journal. Two runs with the same key
+/// produce interchangeable receipts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofKey {
+    pub csv_hash: [u8; 32],
+    pub image_id: [u32; 8],
+    pub policy_hash: [u8; 32],
+    pub options: ProcessingOptions,
+}
+
+impl ProofKey {
+    /// Hex digest used as the bundle's file name.
+    pub fn digest(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.csv_hash);
+        for word in self.image_id {
+            hasher.update(word.to_le_bytes());
+        }
+        hasher.update(self.policy_hash);
+        hasher.update(self.options.column_hash.as_str());
+        hasher.update([self.options.conceal_sum as u8]);
+        // Left out for the default, so bundles from before dialects keep their names
+        let dialect = self.options.dialect;
+        if dialect != Dialect::CSV {
+            hasher.update([dialect.delimiter, dialect.quote, dialect.has_header as u8]);
+        }
+        hex::encode(hasher.finalize())
+    }
+}
+
+/// A receipt together with the key it was proven under and the opening of
+/// its sum commitment. Bundles therefore stay with the data owner; share the
+/// receipt, and the opening only with parties allowed to see the sum.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProofBundle {
+    pub key: ProofKey,
+    pub proof_kind: ProofKind,
+    pub receipt: Receipt,
+    pub sum_opening: SumOpening,
+    pub metadata: BundleMetadata,
+}
+
+impl ProofBundle {
+    /// Read a bundle file, e.g. one handed over by a prover, within the
+    /// limits of [`crate::untrusted`].
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        Self::from_bytes(&untrusted::read(path, untrusted::MAX_BUNDLE_BYTES)?)
+    }
+
+    /// Decode the contents of a bundle file.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Box<dyn Error>> {
+        Ok(untrusted::decode(bytes)?)
+    }
+}
+
+/// Directory of proof bundles addressed by [`ProofKey::digest`].
+pub struct ProofStore {
+    root: PathBuf,
+}
+
+impl ProofStore {
+    pub fn open(root: impl Into<PathBuf>) -> Result<Self, Box<dyn Error>> {
+        let root = root.into();
+        fs::create_dir_all(&root)?;
+        Ok(Self { root })
+    }
+
+    /// Running totals of accepted proofs against period budgets; see
+    /// [`crate::budgets`].
+    pub fn budget_ledger(&self) -> BudgetLedger {
+        BudgetLedger::new(self.root.join("budgets.jsonl"))
+    }
+
+    /// Sums of accepted proofs, for trend checks; see [`crate::trend`].
+    pub fn sum_history(&self) -> SumHistory {
+        SumHistory::new(self.root.join("history.jsonl"))
+    }
+
+    /// Proving time and size of every proof produced; see [`crate::slo`].
+    pub fn slo_log(&self) -> SloLog {
+        SloLog::new(self.root.join("slo.jsonl"))
+    }
+
+    /// Decision notifications that could not be delivered; see
+    /// [`crate::webhook`].
+    pub fn webhook_dead_letters(&self) -> PathBuf {
+        self.root.join("webhooks-dead.jsonl")
+    }
+
+    /// Hash-chained record of rulings and other agent verdicts; see
+    /// [`crate::audit_log`].
+    pub fn audit_log(&self) -> AuditLog {
+        AuditLog::new(self.root.join("audit.jsonl"))
+    }
+
+    /// Prover bonds and slashes, on the mock ledger; see [`crate::bonding`].
+    pub fn bond_ledger(&self) -> MockLedger {
+        MockLedger::new(self.root.join("bonds.jsonl"))
+    }
+
+    /// Proof requests, prover offers and their matches; see
+    /// [`crate::marketplace`].
+    pub fn marketplace(&self) -> Marketplace {
+        Marketplace::new(self.root.join("market"))
+    }
+
+    /// Where the bundle for `key` is (or would be) stored.
+    pub fn path(&self, key: &ProofKey) -> PathBuf {
+        self.bundle_path(&key.digest())
+    }
+
+    /// Where the bundle whose key has the hex `digest` is stored.
+    pub fn bundle_path(&self, digest: &str) -> PathBuf {
+        self.root.join(format!("{}.bundle", digest))
+    }
+
+    /// Every bundle in the store, by file name.
+    pub fn bundles(&self) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+        let mut bundles = Vec::new();
+        for entry in fs::read_dir(&self.root)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "bundle") {
+                bundles.push(path);
+            }
+        }
+        bundles.sort();
+        Ok(bundles)
+    }
+
+    pub fn get(&self, key: &ProofKey) -> Result<Option<ProofBundle>, Box<dyn Error>> {
+        let path = self.path(key);
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(ProofBundle::load(&path)?))
+    }
+
+    pub fn put(&self, bundle: &ProofBundle) -> Result<PathBuf, Box<dyn Error>> {
+        let path = self.path(&bundle.key);
+        write_atomic(&path, &bincode::serialize(bundle)?)?;
+        Ok(path)
+    }
+
+    /// Ledger of disclosed releases proven into this store; see
+    /// [`crate::privacy`].
+    pub fn disclosure_ledger(&self) -> DisclosureLedger {
+        DisclosureLedger::new(self.root.join("disclosures.jsonl"))
+    }
+
+    /// Where the receipt for the chunk hashing to `chunk_hash`, proven by
+    /// `image_id`, is (or would be) stored. Chunk receipts are shared by
+    /// every bundle that contains the chunk and are not touched by `gc`.
+    pub fn chunk_path(&self, image_id: [u32; 8], chunk_hash: &[u8; 32]) -> PathBuf {
+        self.root
+            .join("chunks")
+            .join(risc0_zkvm::sha::Digest::from(image_id).to_string())
+            .join(format!("{}.receipt", hex::encode(chunk_hash)))
+    }
+
+    pub fn get_chunk(&self, image_id: [u32; 8], chunk_hash: &[u8; 32]) -> Result<Option<Receipt>, Box<dyn Error>> {
+        let path = self.chunk_path(image_id, chunk_hash);
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(untrusted::decode(&untrusted::read(&path, untrusted::MAX_BUNDLE_BYTES)?)?))
+    }
+
+    pub fn put_chunk(&self, image_id: [u32; 8], chunk_hash: &[u8; 32], receipt: &Receipt) -> Result<PathBuf, Box<dyn Error>> {
+        let path = self.chunk_path(image_id, chunk_hash);
+        fs::create_dir_all(path.parent().expect("chunk paths have a parent"))?;
+        write_atomic(&path, &bincode::serialize(receipt)?)?;
+        Ok(path)
+    }
+}
+
+/// Lifecycle rules for stored bundles.
+///
+/// Receipts older than `keep_for` are moved to `archive` (cold storage) if
+/// set, otherwise deleted. A JSON record of each expired bundle's journal is
+/// always written to `journals/` first, so the attested values outlive the
+/// receipt. A bundle's signature travels with it; decisions and
+/// transcripts are kept.
+#[derive(Debug, Clone)]
+pub struct RetentionPolicy {
+    pub keep_for: Duration,
+    pub archive: Option<PathBuf>,
+}
+
+/// What a [`ProofStore::gc`] pass did, or would do in a dry run.
+#[derive(Debug, Default, Serialize)]
+pub struct GcReport {
+    pub kept: usize,
+    pub archived: usize,
+    pub deleted: usize,
+    /// Bundles left in place because they could not be decoded.
+    pub unreadable: Vec<String>,
+}
+
+/// Journal of an expired bundle, kept after the receipt is gone.
+#[derive(Debug, Serialize)]
+struct JournalRecord {
+    key: String,
+    proof_kind: ProofKind,
+    journal: serde_json::Value,
+}
+
+impl ProofStore {
+    /// Apply `retention` to every bundle, as of `now`.
+    pub fn gc(&self, retention: &RetentionPolicy, now: SystemTime, dry_run: bool) -> Result<GcReport, Box<dyn Error>> {
+        let mut report = GcReport::default();
+        let journals = self.root.join("journals");
+        if let (false, Some(archive)) = (dry_run, &retention.archive) {
+            fs::create_dir_all(archive)?;
+        }
+
+        for entry in fs::read_dir(&self.root)? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|ext| ext != "bundle") {
+                continue;
+            }
+            let age = now.duration_since(fs::metadata(&path)?.modified()?).unwrap_or_default();
+            if age <= retention.keep_for {
+                report.kept += 1;
+                continue;
+            }
+
+            let Some(record) = journal_record(&path) else {
+                report.unreadable.push(path.display().to_string());
+                continue;
+            };
+            if !dry_run {
+                fs::create_dir_all(&journals)?;
+                let name = path.with_extension("json");
+                let name = name.file_name().expect("bundle paths have a file name");
+                fs::write(journals.join(name), serde_json::to_string_pretty(&record)?)?;
+            }
+
+            match &retention.archive {
+                Some(archive) => {
+                    if !dry_run {
+                        let target = archive.join(path.file_name().expect("bundle paths have a file name"));
+                        move_file(&path, &target)?;
+                        let signature = DetachedSignature::path_for(&path);
+                        if signature.exists() {
+                            move_file(&signature, &DetachedSignature::path_for(&target))?;
+                        }
+                    }
+                    report.archived += 1;
+                }
+                None => {
+                    if !dry_run {
+                        fs::remove_file(&path)?;
+                        let signature = DetachedSignature::path_for(&path);
+                        if signature.exists() {
+                            fs::remove_file(signature)?;
+                        }
+                    }
+                    report.deleted += 1;
+                }
+            }
+        }
+        Ok(report)
+    }
+}
+
+/// Write `bytes` to a sibling temp file and rename it over `path`, so a
+/// process killed mid-write never leaves a truncated bundle behind.
+pub fn write_atomic(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    let mut file = fs::File::create(&tmp)?;
+    std::io::Write::write_all(&mut file, bytes)?;
+    file.sync_all()?;
+    fs::rename(&tmp, path)
+}
+
+/// `rename` fails across filesystems, so fall back to copy + delete.
+fn move_file(from: &Path, to: &Path) -> std::io::Result<()> {
+    if fs::rename(from, to).is_err() {
+        fs::copy(from, to)?;
+        fs::remove_file(from)?;
+    }
+    Ok(())
+}
+
+fn journal_record(path: &Path) -> Option<JournalRecord> {
+    let bundle = ProofBundle::load(path).ok()?;
+    let journal: AgentResult = crate::journal::decode(&bundle.receipt.journal.bytes).ok()?;
+    Some(JournalRecord {
+        key: bundle.key.digest(),
+        proof_kind: bundle.proof_kind,
+        journal: crate::journal::to_json(&journal),
+    })
+}
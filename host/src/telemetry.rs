@@ -0,0 +1,278 @@
+//! OpenTelemetry traces of a proof's journey from Agent A to Agent B.
+//!
+//! Every process that touches a bundle (the host proving and verifying it,
+//! the standalone `verify`, the relayer) records its stages as spans and
+//! exports them as OTLP/HTTP JSON to a collector's `/v1/traces`. The trace
+//! ID is derived from the bundle's store digest, so the processes land in
+//! one trace without passing anything along; each span also carries the
+//! digest as `zaik.bundle.digest`. A `TRACEPARENT` in the environment
+//! (W3C trace context) takes precedence, so a run can join a caller's trace.
+//!
+//! Export is plain HTTP, which is what collectors listen on locally (port
+//! 4318), and needs no HTTP client so the verifier-only build has it too.
+
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::error::Error;
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Attribute every span carries once the bundle is known.
+pub const BUNDLE_ATTRIBUTE: &str = "zaik.bundle.digest";
+
+/// The collector to export to: `--otlp` if given, else the standard
+/// `OTEL_EXPORTER_OTLP_ENDPOINT`.
+pub fn endpoint(flag: Option<&str>) -> Option<String> {
+    flag.map(str::to_string)
+        .or_else(|| std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT").ok())
+        .filter(|endpoint| !endpoint.is_empty())
+}
+
+/// Trace ID for the bundle stored under `digest`.
+pub fn trace_id_for(digest: &str) -> [u8; 16] {
+    let hash = Sha256::new().chain_update(b"zaik.trace.v1").chain_update(digest.as_bytes()).finalize();
+    hash[..16].try_into().expect("SHA-256 is longer than 16 bytes")
+}
+
+/// `00-<trace id>-<parent span id>-<flags>`.
+fn parse_traceparent(value: &str) -> Option<([u8; 16], [u8; 8])> {
+    let mut parts = value.trim().split('-');
+    let (version, trace_id, parent, _flags) = (parts.next()?, parts.next()?, parts.next()?, parts.next()?);
+    if version != "00" {
+        return None;
+    }
+    let trace_id: [u8; 16] = hex::decode(trace_id).ok()?.try_into().ok()?;
+    let parent: [u8; 8] = hex::decode(parent).ok()?.try_into().ok()?;
+    (trace_id != [0; 16] && parent != [0; 8]).then_some((trace_id, parent))
+}
+
+fn now_nanos() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos() as u64
+}
+
+#[derive(Debug, Clone)]
+struct SpanRecord {
+    name: String,
+    span_id: [u8; 8],
+    parent: Option<[u8; 8]>,
+    start: u64,
+    end: u64,
+    attributes: Vec<(String, String)>,
+    /// `None` for OK, else the error message.
+    error: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct TraceState {
+    trace_id: Option<[u8; 16]>,
+    /// Trace ID came from `TRACEPARENT`, so the bundle does not override it.
+    inherited: bool,
+    bundle: Option<String>,
+    spans: Vec<SpanRecord>,
+}
+
+/// Collects one process's spans until they are exported.
+#[derive(Debug)]
+pub struct Tracer {
+    service: String,
+    parent: Option<[u8; 8]>,
+    state: Mutex<TraceState>,
+}
+
+impl Tracer {
+    pub fn new(service: &str) -> Self {
+        let inherited = std::env::var("TRACEPARENT").ok().as_deref().and_then(parse_traceparent);
+        Self {
+            service: service.to_string(),
+            parent: inherited.map(|(_, parent)| parent),
+            state: Mutex::new(TraceState {
+                trace_id: inherited.map(|(trace_id, _)| trace_id),
+                inherited: inherited.is_some(),
+                ..TraceState::default()
+            }),
+        }
+    }
+
+    /// Tie the trace to the bundle stored under `digest`.
+    pub fn bind_bundle(&self, digest: &str) {
+        let mut state = self.state.lock().expect("trace state poisoned");
+        if !state.inherited {
+            state.trace_id = Some(trace_id_for(digest));
+        }
+        state.bundle = Some(digest.to_string());
+    }
+
+    /// Hex trace ID, once one is known.
+    pub fn trace_id(&self) -> Option<String> {
+        self.state.lock().expect("trace state poisoned").trace_id.map(hex::encode)
+    }
+
+    /// A top-level span, under the caller's span if `TRACEPARENT` named one.
+    pub fn span(&self, name: &str) -> Span<'_> {
+        Span::start(self, name, self.parent)
+    }
+
+    /// The spans as an OTLP `ExportTraceServiceRequest`; `None` until a
+    /// bundle or `TRACEPARENT` has given the trace an ID.
+    pub fn to_otlp(&self) -> Option<Value> {
+        let state = self.state.lock().expect("trace state poisoned");
+        let trace_id = hex::encode(state.trace_id?);
+        let spans: Vec<Value> = state
+            .spans
+            .iter()
+            .map(|span| {
+                let mut attributes = span.attributes.clone();
+                if let Some(bundle) = &state.bundle {
+                    attributes.push((BUNDLE_ATTRIBUTE.to_string(), bundle.clone()));
+                }
+                let mut value = json!({
+                    "traceId": trace_id,
+                    "spanId": hex::encode(span.span_id),
+                    "name": span.name,
+                    "kind": 1,
+                    "startTimeUnixNano": span.start.to_string(),
+                    "endTimeUnixNano": span.end.to_string(),
+                    "attributes": attributes
+                        .iter()
+                        .map(|(key, value)| json!({ "key": key, "value": { "stringValue": value } }))
+                        .collect::<Vec<_>>(),
+                    "status": match &span.error {
+                        None => json!({ "code": 1 }),
+                        Some(message) => json!({ "code": 2, "message": message }),
+                    },
+                });
+                if let Some(parent) = span.parent {
+                    value["parentSpanId"] = json!(hex::encode(parent));
+                }
+                value
+            })
+            .collect();
+        Some(json!({
+            "resourceSpans": [{
+                "resource": {
+                    "attributes": [{ "key": "service.name", "value": { "stringValue": self.service } }],
+                },
+                "scopeSpans": [{
+                    "scope": { "name": "zaik", "version": env!("CARGO_PKG_VERSION") },
+                    "spans": spans,
+                }],
+            }],
+        }))
+    }
+
+    /// POST the finished spans to `endpoint`'s `/v1/traces`. Returns how
+    /// many were sent.
+    pub fn export(&self, endpoint: &str) -> Result<usize, Box<dyn Error>> {
+        let Some(request) = self.to_otlp() else {
+            return Ok(0);
+        };
+        let count = self.state.lock().expect("trace state poisoned").spans.len();
+        post_json(&format!("{}/v1/traces", endpoint.trim_end_matches('/')), &serde_json::to_vec(&request)?)?;
+        Ok(count)
+    }
+}
+
+/// A span in progress. Dropping it without [`Span::ok`] or [`Span::fail`]
+/// records it as failed, so a stage cut short by `?` still shows up.
+#[derive(Debug)]
+pub struct Span<'a> {
+    tracer: &'a Tracer,
+    record: Option<SpanRecord>,
+    status: Option<Result<(), String>>,
+}
+
+impl<'a> Span<'a> {
+    fn start(tracer: &'a Tracer, name: &str, parent: Option<[u8; 8]>) -> Self {
+        Self {
+            tracer,
+            record: Some(SpanRecord {
+                name: name.to_string(),
+                span_id: rand::random(),
+                parent,
+                start: now_nanos(),
+                end: 0,
+                attributes: Vec::new(),
+                error: None,
+            }),
+            status: None,
+        }
+    }
+
+    pub fn tracer(&self) -> &'a Tracer {
+        self.tracer
+    }
+
+    pub fn child(&self, name: &str) -> Span<'a> {
+        Span::start(self.tracer, name, self.record.as_ref().map(|record| record.span_id))
+    }
+
+    pub fn attribute(&mut self, key: &str, value: impl ToString) {
+        if let Some(record) = &mut self.record {
+            record.attributes.push((key.to_string(), value.to_string()));
+        }
+    }
+
+    pub fn ok(mut self) {
+        self.status = Some(Ok(()));
+    }
+
+    pub fn fail(mut self, message: impl Into<String>) {
+        self.status = Some(Err(message.into()));
+    }
+}
+
+impl Drop for Span<'_> {
+    fn drop(&mut self) {
+        let Some(mut record) = self.record.take() else {
+            return;
+        };
+        record.end = now_nanos();
+        record.error = match self.status.take() {
+            Some(Ok(())) => None,
+            Some(Err(message)) => Some(message),
+            None => Some("stage did not finish".to_string()),
+        };
+        if let Ok(mut state) = self.tracer.state.lock() {
+            state.spans.push(record);
+        }
+    }
+}
+
+/// A bare HTTP/1.1 POST; `url` must be `http://host[:port]/path`.
+fn post_json(url: &str, body: &[u8]) -> Result<(), Box<dyn Error>> {
+    let rest = url
+        .strip_prefix("http://")
+        .ok_or("OTLP export needs an http:// collector endpoint, e.g. a local collector on port 4318")?;
+    let (authority, path) = match rest.find('/') {
+        Some(slash) => (&rest[..slash], &rest[slash..]),
+        None => (rest, "/"),
+    };
+    let address = if authority.contains(':') {
+        authority.to_string()
+    } else {
+        format!("{}:80", authority)
+    };
+    let address = address.to_socket_addrs()?.next().ok_or("collector address did not resolve")?;
+    let timeout = Duration::from_secs(10);
+    let mut stream = TcpStream::connect_timeout(&address, timeout)?;
+    stream.set_read_timeout(Some(timeout))?;
+    stream.set_write_timeout(Some(timeout))?;
+    write!(
+        stream,
+        "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        path,
+        authority,
+        body.len()
+    )?;
+    stream.write_all(body)?;
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response)?;
+    let response = String::from_utf8_lossy(&response);
+    let status = response.lines().next().unwrap_or_default();
+    match status.split_whitespace().nth(1) {
+        Some(code) if code.starts_with('2') => Ok(()),
+        _ => Err(format!("collector answered '{}'", status).into()),
+    }
+}
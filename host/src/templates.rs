@@ -0,0 +1,76 @@
+//! Named invariant templates for policy files.
+//!
+//! Each template expands to one or more [`Invariant`]s, so common rules can
+//! be written by name instead of as raw aggregate/op/bound triples:
+//! ```toml
+//! [[templates]]
+//! name = "values_in_range"
+//! column = 1
+//! min = 10
+//! max = 500
+//! ```
+
+use serde::{Deserialize, Serialize};
+use zaik_core::invariant::{Aggregate, Invariant};
+use zaik_core::ComparisonOp;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "name", rename_all = "snake_case", deny_unknown_fields)]
+pub enum InvariantTemplate {
+    /// The column's sum stays at or below `max`.
+    BudgetCap { column: usize, max: u64 },
+    /// At least one row has a numeric value in the column.
+    NonEmpty {
+        #[serde(default)]
+        column: usize,
+    },
+    /// No value appears twice in the column.
+    NoDuplicates { column: usize },
+    /// Every value lies in `min..=max`.
+    ValuesInRange { column: usize, min: u64, max: u64 },
+    /// The column (e.g. Unix timestamps) never decreases from row to row.
+    MonotonicTimestamps { column: usize },
+}
+
+fn invariant(column: usize, aggregate: Aggregate, op: ComparisonOp, bound: u64) -> Invariant {
+    Invariant {
+        column,
+        aggregate,
+        op,
+        bound,
+    }
+}
+
+impl InvariantTemplate {
+    pub fn name(&self) -> &'static str {
+        match self {
+            InvariantTemplate::BudgetCap { .. } => "budget_cap",
+            InvariantTemplate::NonEmpty { .. } => "non_empty",
+            InvariantTemplate::NoDuplicates { .. } => "no_duplicates",
+            InvariantTemplate::ValuesInRange { .. } => "values_in_range",
+            InvariantTemplate::MonotonicTimestamps { .. } => "monotonic_timestamps",
+        }
+    }
+
+    /// The invariants the guest evaluates for this template.
+    pub fn invariants(&self) -> Vec<Invariant> {
+        match *self {
+            InvariantTemplate::BudgetCap { column, max } => {
+                vec![invariant(column, Aggregate::Sum, ComparisonOp::Le, max)]
+            }
+            InvariantTemplate::NonEmpty { column } => {
+                vec![invariant(column, Aggregate::Count, ComparisonOp::Ge, 1)]
+            }
+            InvariantTemplate::NoDuplicates { column } => {
+                vec![invariant(column, Aggregate::Duplicates, ComparisonOp::Le, 0)]
+            }
+            InvariantTemplate::ValuesInRange { column, min, max } => vec![
+                invariant(column, Aggregate::Min, ComparisonOp::Ge, min),
+                invariant(column, Aggregate::Max, ComparisonOp::Le, max),
+            ],
+            InvariantTemplate::MonotonicTimestamps { column } => {
+                vec![invariant(column, Aggregate::Decreases, ComparisonOp::Le, 0)]
+            }
+        }
+    }
+}
@@ -0,0 +1,595 @@
+//! RFC 3161 timestamps over bundle files.
+//!
+//! A timestamp authority (TSA) signs the SHA-256 of a bundle together with
+//! the time it saw it. The token is stored next to the bundle as
+//! `<bundle>.tst`, like the prover signature, since a token inside the
+//! bundle could not cover the bundle's own bytes. [`verify`] checks that a
+//! token is about the file and that the TSA whose certificate the caller
+//! pins signed it: the signed attributes must name a TSTInfo, carry its
+//! SHA-256 and identify that certificate (ESSCertID), and the signature over
+//! them must verify with its RSA or P-256 key. No chain is built, so pin the
+//! TSA's own certificate, not its CA's. [`check_imprint`] only checks that
+//! the token names the file, for callers with no certificate to pin.
+
+use crate::untrusted;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::error::Error;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// DER of the SHA-256 algorithm identifier OID, 2.16.840.1.101.3.4.2.1.
+const SHA256_OID: &[u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01];
+/// DER of id-ct-TSTInfo, 1.2.840.113549.1.9.16.1.4.
+const TST_INFO_OID: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x09, 0x10, 0x01, 0x04];
+/// DER of the signed attribute OIDs: contentType 1.2.840.113549.1.9.3,
+/// messageDigest 1.2.840.113549.1.9.4, signingCertificate
+/// 1.2.840.113549.1.9.16.2.12 and signingCertificateV2 1.2.840.113549.1.9.16.2.47.
+const CONTENT_TYPE_OID: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x09, 0x03];
+const MESSAGE_DIGEST_OID: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x09, 0x04];
+const SIGNING_CERTIFICATE_OID: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x09, 0x10, 0x02, 0x0c];
+const SIGNING_CERTIFICATE_V2_OID: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x09, 0x10, 0x02, 0x2f];
+/// DER of rsaEncryption 1.2.840.113549.1.1.1 and sha256WithRSAEncryption
+/// 1.2.840.113549.1.1.11.
+const RSA_OID: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x01];
+const RSA_SHA256_OID: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x0b];
+/// DER of id-ecPublicKey 1.2.840.10045.2.1, prime256v1 1.2.840.10045.3.1.7
+/// and ecdsa-with-SHA256 1.2.840.10045.4.3.2.
+const EC_OID: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01];
+const P256_OID: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07];
+const ECDSA_SHA256_OID: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02];
+
+const INTEGER: u8 = 0x02;
+const OCTET_STRING: u8 = 0x04;
+const NULL: u8 = 0x05;
+const OID: u8 = 0x06;
+const BOOLEAN: u8 = 0x01;
+const BIT_STRING: u8 = 0x03;
+const GENERALIZED_TIME: u8 = 0x18;
+const SEQUENCE: u8 = 0x30;
+const SET: u8 = 0x31;
+const CONTEXT_0: u8 = 0xa0;
+const CONTEXT_1: u8 = 0xa1;
+
+/// What a token says about the file it covers.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TimestampInfo {
+    /// When the TSA saw the digest, RFC 3339 in UTC.
+    pub time: String,
+    /// Dotted OID of the TSA policy the token was issued under.
+    pub policy: String,
+    /// Hex serial number the TSA gave the token.
+    pub serial: String,
+    /// Hex SHA-256 the token covers.
+    pub digest: String,
+    pub nonce: Option<u64>,
+}
+
+/// Why a timestamp could not be obtained or does not hold.
+#[derive(Debug)]
+pub enum TimestampError {
+    Malformed(&'static str),
+    /// The TSA answered with a PKIStatus other than granted (0 or 1).
+    Refused { status: u64 },
+    UnsupportedHash,
+    DigestMismatch,
+    NonceMismatch,
+    /// A signed attribute the token needs is missing or does not match.
+    SignedAttribute(&'static str),
+    /// The token identifies a signer other than the pinned certificate.
+    SignerMismatch,
+    /// The certificate's key or the signature algorithm is not RSA with
+    /// SHA-256 or ECDSA P-256 with SHA-256.
+    UnsupportedKey,
+    BadSignature,
+}
+
+impl fmt::Display for TimestampError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimestampError::Malformed(what) => write!(f, "malformed timestamp: {}", what),
+            TimestampError::Refused { status } => write!(f, "timestamp authority refused the request (status {})", status),
+            TimestampError::UnsupportedHash => write!(f, "timestamp does not use SHA-256"),
+            TimestampError::DigestMismatch => write!(f, "timestamp covers different content"),
+            TimestampError::NonceMismatch => write!(f, "timestamp does not answer this request"),
+            TimestampError::SignedAttribute(name) => {
+                write!(f, "timestamp's signed {} attribute is missing or wrong", name)
+            }
+            TimestampError::SignerMismatch => write!(f, "timestamp was not signed by the pinned TSA certificate"),
+            TimestampError::UnsupportedKey => write!(f, "TSA key or signature algorithm is not supported"),
+            TimestampError::BadSignature => write!(f, "timestamp signature does not verify"),
+        }
+    }
+}
+
+impl Error for TimestampError {}
+
+/// Sidecar path for the token over `path`.
+pub fn path_for(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".tst");
+    PathBuf::from(name)
+}
+
+/// Certificate of a TSA whose tokens are trusted.
+#[derive(Debug, Clone)]
+pub struct TsaCertificate {
+    der: Vec<u8>,
+}
+
+impl TsaCertificate {
+    /// Read a PEM or DER certificate.
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        Ok(Self::from_bytes(&untrusted::read(path, untrusted::MAX_SIDECAR_BYTES)?)?)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, TimestampError> {
+        let der = match std::str::from_utf8(bytes) {
+            Ok(pem) if pem.contains("-----BEGIN CERTIFICATE-----") => {
+                let body: String = pem
+                    .lines()
+                    .skip_while(|line| !line.starts_with("-----BEGIN CERTIFICATE-----"))
+                    .skip(1)
+                    .take_while(|line| !line.starts_with("-----END CERTIFICATE-----"))
+                    .collect();
+                BASE64.decode(body.trim()).map_err(|_| TimestampError::Malformed("certificate PEM"))?
+            }
+            _ => bytes.to_vec(),
+        };
+        let certificate = Self { der };
+        certificate.public_key()?;
+        Ok(certificate)
+    }
+
+    /// The key's algorithm and its bytes: an RSAPublicKey for RSA, an
+    /// uncompressed point for P-256.
+    fn public_key(&self) -> Result<(&'static [u8], &[u8]), TimestampError> {
+        let mut tbs = Reader::new(&self.der).enter(SEQUENCE, "certificate")?.enter(SEQUENCE, "TBSCertificate")?;
+        if tbs.peek() == Some(CONTEXT_0) {
+            tbs.skip(CONTEXT_0, "certificate version")?;
+        }
+        tbs.skip(INTEGER, "certificate serial")?;
+        tbs.skip(SEQUENCE, "certificate signature algorithm")?;
+        tbs.skip(SEQUENCE, "certificate issuer")?;
+        tbs.skip(SEQUENCE, "certificate validity")?;
+        tbs.skip(SEQUENCE, "certificate subject")?;
+        let mut key_info = tbs.enter(SEQUENCE, "subject public key info")?;
+        let mut algorithm = key_info.enter(SEQUENCE, "public key algorithm")?;
+        let kind = match algorithm.read(OID, "public key algorithm")? {
+            RSA_OID => RSA_OID,
+            EC_OID if algorithm.peek() == Some(OID) && algorithm.read(OID, "curve")? == P256_OID => EC_OID,
+            _ => return Err(TimestampError::UnsupportedKey),
+        };
+        match key_info.read(BIT_STRING, "public key")? {
+            [0, key @ ..] => Ok((kind, key)),
+            _ => Err(TimestampError::Malformed("public key")),
+        }
+    }
+}
+
+/// DER TimeStampReq for `digest`, asking for the TSA's certificate in the
+/// token so it can be verified on its own.
+pub fn request(digest: &[u8; 32], nonce: u64) -> Vec<u8> {
+    let algorithm = tlv(SEQUENCE, &[tlv(OID, SHA256_OID), tlv(NULL, &[])].concat());
+    let imprint = tlv(SEQUENCE, &[algorithm, tlv(OCTET_STRING, digest)].concat());
+    let body = [
+        tlv(INTEGER, &[1]),
+        imprint,
+        tlv(INTEGER, &unsigned(nonce)),
+        tlv(BOOLEAN, &[0xff]),
+    ]
+    .concat();
+    tlv(SEQUENCE, &body)
+}
+
+/// The token out of a DER TimeStampResp.
+pub fn token_from_response(response: &[u8]) -> Result<Vec<u8>, TimestampError> {
+    let mut resp = Reader::new(response).enter(SEQUENCE, "response")?;
+    let mut status_info = resp.enter(SEQUENCE, "status")?;
+    let status = to_u64(status_info.read(INTEGER, "status")?)?;
+    if status > 1 {
+        return Err(TimestampError::Refused { status });
+    }
+    let start = resp.position;
+    resp.skip(SEQUENCE, "token")?;
+    Ok(resp.data[start..resp.position].to_vec())
+}
+
+/// Parse a token's TSTInfo without checking what it covers.
+pub fn parse(token: &[u8]) -> Result<TimestampInfo, TimestampError> {
+    decode(token).map(|(info, _)| info)
+}
+
+/// Check only that `token` names `payload`, not who signed it, and return
+/// what it says.
+pub fn check_imprint(token: &[u8], payload: &[u8]) -> Result<TimestampInfo, TimestampError> {
+    let info = parse(token)?;
+    if info.digest != hex::encode(Sha256::digest(payload)) {
+        return Err(TimestampError::DigestMismatch);
+    }
+    Ok(info)
+}
+
+/// Check that `token` covers `payload` and was signed by `tsa`, and return
+/// what it says.
+pub fn verify(token: &[u8], payload: &[u8], tsa: &TsaCertificate) -> Result<TimestampInfo, TimestampError> {
+    let info = check_imprint(token, payload)?;
+    let (_, signed) = decode(token)?;
+    signed.verify(tsa)?;
+    Ok(info)
+}
+
+/// The parts of a token its signature covers.
+struct Signed<'a> {
+    /// DER TSTInfo, the encapsulated content.
+    tst_info: &'a [u8],
+    /// Content of the one SignerInfo.
+    signer_info: &'a [u8],
+}
+
+impl Signed<'_> {
+    fn verify(&self, tsa: &TsaCertificate) -> Result<(), TimestampError> {
+        let mut signer_info = Reader::new(self.signer_info);
+        signer_info.read(INTEGER, "signer info version")?;
+        // Who signed is taken from the signingCertificate attribute below,
+        // which the signature covers, not from this unsigned identifier
+        match signer_info.peek() {
+            Some(SEQUENCE) => signer_info.skip(SEQUENCE, "signer identifier")?,
+            _ => signer_info.skip(0x80, "signer identifier")?,
+        }
+        let mut digest_algorithm = signer_info.enter(SEQUENCE, "signer digest algorithm")?;
+        if digest_algorithm.read(OID, "signer digest algorithm")? != SHA256_OID {
+            return Err(TimestampError::UnsupportedHash);
+        }
+        let start = signer_info.position;
+        let attributes = signer_info.read(CONTEXT_0, "signed attributes")?;
+        // The signature is over the attributes as a SET, not as the
+        // implicitly tagged field they are stored in
+        let mut signed_attributes = signer_info.data[start..signer_info.position].to_vec();
+        signed_attributes[0] = SET;
+        let mut signature_algorithm = signer_info.enter(SEQUENCE, "signature algorithm")?;
+        let signature_algorithm = signature_algorithm.read(OID, "signature algorithm")?;
+        let signature = signer_info.read(OCTET_STRING, "signature")?;
+
+        self.check_attributes(attributes, tsa)?;
+        let (key_kind, key) = tsa.public_key()?;
+        let algorithm: &dyn ring::signature::VerificationAlgorithm = match (key_kind, signature_algorithm) {
+            (RSA_OID, RSA_OID | RSA_SHA256_OID) => &ring::signature::RSA_PKCS1_2048_8192_SHA256,
+            (EC_OID, EC_OID | ECDSA_SHA256_OID) => &ring::signature::ECDSA_P256_SHA256_ASN1,
+            _ => return Err(TimestampError::UnsupportedKey),
+        };
+        ring::signature::UnparsedPublicKey::new(algorithm, key)
+            .verify(&signed_attributes, signature)
+            .map_err(|_| TimestampError::BadSignature)
+    }
+
+    /// Check that the signed attributes name a TSTInfo, carry its SHA-256
+    /// and identify `tsa` as the signer.
+    fn check_attributes(&self, attributes: &[u8], tsa: &TsaCertificate) -> Result<(), TimestampError> {
+        let (mut content_type, mut message_digest, mut signer) = (false, false, false);
+        let mut attributes = Reader::new(attributes);
+        while attributes.peek().is_some() {
+            let mut attribute = attributes.enter(SEQUENCE, "signed attribute")?;
+            let kind = attribute.read(OID, "signed attribute type")?;
+            let mut values = attribute.enter(SET, "signed attribute values")?;
+            match kind {
+                CONTENT_TYPE_OID => content_type = values.read(OID, "content type")? == TST_INFO_OID,
+                MESSAGE_DIGEST_OID => {
+                    message_digest = values.read(OCTET_STRING, "message digest")? == &Sha256::digest(self.tst_info)[..]
+                }
+                SIGNING_CERTIFICATE_V2_OID => {
+                    // The first ESSCertIDv2 is the signer's; SHA-256 unless it says otherwise
+                    let mut cert_id = values.enter(SEQUENCE, "signing certificate")?.enter(SEQUENCE, "certs")?;
+                    let mut cert_id = cert_id.enter(SEQUENCE, "ESSCertIDv2")?;
+                    if cert_id.peek() == Some(SEQUENCE) {
+                        let mut algorithm = cert_id.enter(SEQUENCE, "ESSCertIDv2 hash algorithm")?;
+                        if algorithm.read(OID, "ESSCertIDv2 hash algorithm")? != SHA256_OID {
+                            return Err(TimestampError::UnsupportedHash);
+                        }
+                    }
+                    let hash = cert_id.read(OCTET_STRING, "ESSCertIDv2 hash")?;
+                    signer = hash == &Sha256::digest(&tsa.der)[..];
+                }
+                SIGNING_CERTIFICATE_OID => {
+                    let mut cert_id = values.enter(SEQUENCE, "signing certificate")?.enter(SEQUENCE, "certs")?;
+                    let hash = cert_id.enter(SEQUENCE, "ESSCertID")?.read(OCTET_STRING, "ESSCertID hash")?;
+                    signer = hash == ring::digest::digest(&ring::digest::SHA1_FOR_LEGACY_USE_ONLY, &tsa.der).as_ref();
+                }
+                _ => {}
+            }
+        }
+        if !content_type {
+            return Err(TimestampError::SignedAttribute("contentType"));
+        }
+        if !message_digest {
+            return Err(TimestampError::SignedAttribute("messageDigest"));
+        }
+        if !signer {
+            return Err(TimestampError::SignerMismatch);
+        }
+        Ok(())
+    }
+}
+
+fn decode(token: &[u8]) -> Result<(TimestampInfo, Signed<'_>), TimestampError> {
+    let mut content_info = Reader::new(token).enter(SEQUENCE, "content info")?;
+    content_info.read(OID, "content type")?;
+    let mut signed_data = content_info.enter(CONTEXT_0, "signed data")?.enter(SEQUENCE, "signed data")?;
+    signed_data.read(INTEGER, "signed data version")?;
+    signed_data.skip(SET, "digest algorithms")?;
+    let mut encapsulated = signed_data.enter(SEQUENCE, "encapsulated content")?;
+    if encapsulated.read(OID, "encapsulated content type")? != TST_INFO_OID {
+        return Err(TimestampError::Malformed("token does not carry a TSTInfo"));
+    }
+    let tst_info = encapsulated.enter(CONTEXT_0, "TSTInfo")?.read(OCTET_STRING, "TSTInfo")?;
+    // The certificates and CRLs the TSA included are not trusted
+    if signed_data.peek() == Some(CONTEXT_0) {
+        signed_data.skip(CONTEXT_0, "certificates")?;
+    }
+    if signed_data.peek() == Some(CONTEXT_1) {
+        signed_data.skip(CONTEXT_1, "CRLs")?;
+    }
+    let mut signer_infos = signed_data.enter(SET, "signer infos")?;
+    let signer_info = signer_infos.read(SEQUENCE, "signer info")?;
+    if signer_infos.peek().is_some() {
+        return Err(TimestampError::Malformed("token has more than one signer"));
+    }
+
+    let mut info = Reader::new(tst_info).enter(SEQUENCE, "TSTInfo")?;
+    info.read(INTEGER, "TSTInfo version")?;
+    let policy = oid_string(info.read(OID, "policy")?);
+    let mut imprint = info.enter(SEQUENCE, "message imprint")?;
+    let mut algorithm = imprint.enter(SEQUENCE, "hash algorithm")?;
+    if algorithm.read(OID, "hash algorithm")? != SHA256_OID {
+        return Err(TimestampError::UnsupportedHash);
+    }
+    let digest = hex::encode(imprint.read(OCTET_STRING, "message digest")?);
+    let serial = hex::encode(info.read(INTEGER, "serial number")?);
+    let time = generalized_time(info.read(GENERALIZED_TIME, "time")?)?;
+    // accuracy and ordering may come before the nonce
+    if info.peek() == Some(SEQUENCE) {
+        info.skip(SEQUENCE, "accuracy")?;
+    }
+    if info.peek() == Some(BOOLEAN) {
+        info.skip(BOOLEAN, "ordering")?;
+    }
+    let nonce = match info.peek() {
+        Some(INTEGER) => Some(to_u64(info.read(INTEGER, "nonce")?)?),
+        _ => None,
+    };
+    let info = TimestampInfo {
+        time,
+        policy,
+        serial,
+        digest,
+        nonce,
+    };
+    Ok((info, Signed { tst_info, signer_info }))
+}
+
+/// Ask the TSA at `url` to timestamp `payload`; returns the DER token.
+#[cfg(feature = "prove")]
+pub fn obtain(url: &str, payload: &[u8]) -> Result<(Vec<u8>, TimestampInfo), Box<dyn Error>> {
+    let digest: [u8; 32] = Sha256::digest(payload).into();
+    // Top bit clear, so the nonce is never encoded as a negative INTEGER
+    let nonce = rand::random::<u64>() >> 1;
+    let response = reqwest::blocking::Client::new()
+        .post(url)
+        .header("Content-Type", "application/timestamp-query")
+        .body(request(&digest, nonce))
+        .send()?
+        .error_for_status()?
+        .bytes()?;
+    let token = token_from_response(&response)?;
+    let info = check_imprint(&token, payload)?;
+    if info.nonce != Some(nonce) {
+        return Err(TimestampError::NonceMismatch.into());
+    }
+    Ok((token, info))
+}
+
+fn tlv(tag: u8, value: &[u8]) -> Vec<u8> {
+    let mut out = vec![tag];
+    if value.len() < 0x80 {
+        out.push(value.len() as u8);
+    } else {
+        let length = unsigned(value.len() as u64);
+        let length = length.strip_prefix(&[0]).unwrap_or(&length);
+        out.push(0x80 | length.len() as u8);
+        out.extend_from_slice(length);
+    }
+    out.extend_from_slice(value);
+    out
+}
+
+/// Minimal big-endian DER INTEGER content for a non-negative value.
+fn unsigned(value: u64) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    let first = bytes.iter().position(|&b| b != 0).unwrap_or(7);
+    let mut out = bytes[first..].to_vec();
+    if out[0] & 0x80 != 0 {
+        out.insert(0, 0);
+    }
+    out
+}
+
+fn to_u64(integer: &[u8]) -> Result<u64, TimestampError> {
+    let integer = integer.strip_prefix(&[0]).unwrap_or(integer);
+    if integer.len() > 8 {
+        return Err(TimestampError::Malformed("integer out of range"));
+    }
+    Ok(integer.iter().fold(0, |acc, &b| acc << 8 | b as u64))
+}
+
+fn oid_string(oid: &[u8]) -> String {
+    let mut arcs = Vec::new();
+    let mut value = 0u64;
+    for &byte in oid {
+        value = value << 7 | (byte & 0x7f) as u64;
+        if byte & 0x80 == 0 {
+            if arcs.is_empty() {
+                let first = (value / 40).min(2);
+                arcs.push(first);
+                arcs.push(value - first * 40);
+            } else {
+                arcs.push(value);
+            }
+            value = 0;
+        }
+    }
+    arcs.iter().map(u64::to_string).collect::<Vec<_>>().join(".")
+}
+
+/// `YYYYMMDDHHMMSS[.fff]Z` as `YYYY-MM-DDTHH:MM:SS[.fff]Z`.
+fn generalized_time(value: &[u8]) -> Result<String, TimestampError> {
+    let text = std::str::from_utf8(value).map_err(|_| TimestampError::Malformed("time"))?;
+    if text.len() < 15 || !text.ends_with('Z') || !text[..14].bytes().all(|b| b.is_ascii_digit()) {
+        return Err(TimestampError::Malformed("time"));
+    }
+    Ok(format!(
+        "{}-{}-{}T{}:{}:{}{}",
+        &text[0..4],
+        &text[4..6],
+        &text[6..8],
+        &text[8..10],
+        &text[10..12],
+        &text[12..14],
+        &text[14..]
+    ))
+}
+
+/// Cursor over a run of DER TLVs.
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.data.get(self.position).copied()
+    }
+
+    /// Value of the next TLV, which must have `tag`.
+    fn read(&mut self, tag: u8, what: &'static str) -> Result<&'a [u8], TimestampError> {
+        let malformed = TimestampError::Malformed(what);
+        if self.peek() != Some(tag) {
+            return Err(malformed);
+        }
+        let mut at = self.position + 1;
+        let first = *self.data.get(at).ok_or(TimestampError::Malformed(what))? as usize;
+        at += 1;
+        let length = if first < 0x80 {
+            first
+        } else {
+            let count = first & 0x7f;
+            if count == 0 || count > 4 {
+                return Err(malformed);
+            }
+            let bytes = self.data.get(at..at + count).ok_or(TimestampError::Malformed(what))?;
+            at += count;
+            bytes.iter().fold(0usize, |acc, &b| acc << 8 | b as usize)
+        };
+        let value = self.data.get(at..at + length).ok_or(malformed)?;
+        self.position = at + length;
+        Ok(value)
+    }
+
+    fn enter(&mut self, tag: u8, what: &'static str) -> Result<Reader<'a>, TimestampError> {
+        self.read(tag, what).map(Reader::new)
+    }
+
+    fn skip(&mut self, tag: u8, what: &'static str) -> Result<(), TimestampError> {
+        self.read(tag, what).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A token from `openssl ts -reply` over [`PAYLOAD`], signed with the
+    /// P-256 key of [`TSA_CERT`] with an ESSCertIDv2 attribute.
+    const TOKEN: &str = concat!(
+        "MIIDcwYJKoZIhvcNAQcCoIIDZDCCA2ACAQMxDzANBglghkgBZQMEAgEFADByBgsqhkiG9w0BCRABBKBjBGEwXwIBAQYEKgME",
+        "ATAxMA0GCWCGSAFlAwQCAQUABCBx9tnuWMHgFPnienUk1gO/cc9qhS8de3q3u0JMCTGz9wIBAhgPMjAyNjEwMTYxNDU1MjBa",
+        "MAMCAQECCEEpUgacaJLwoIIBjDCCAYgwggEuoAMCAQICFCEeBFKdUrfDgXGVnN4WqZFVt1KkMAoGCCqGSM49BAMCMBgxFjAU",
+        "BgNVBAMMDXphaWsgdGVzdCBUU0EwIBcNMjYxMDE2MTQ1NTIwWhgPMjEyNjA5MjIxNDU1MjBaMBgxFjAUBgNVBAMMDXphaWsg",
+        "dGVzdCBUU0EwWTATBgcqhkjOPQIBBggqhkjOPQMBBwNCAATUg69C7fCvknUkLX4srfEQMFcEh/M1HyyMHtFUuQ/ryTJTuLxG",
+        "nHz7hsbHG/M+GvhB9DSax3W/1VDRH/W7UP87o1QwUjAJBgNVHRMEAjAAMA4GA1UdDwEB/wQEAwIHgDAWBgNVHSUBAf8EDDAK",
+        "BggrBgEFBQcDCDAdBgNVHQ4EFgQUuL+pm7To5pngCbcuPaH1hrp7BI0wCgYIKoZIzj0EAwIDSAAwRQIgQ3UMTCpktQShYHsk",
+        "8RMqhCoLhJTEK9UOlkUwaciFKcgCIQCvWksDZ71cZ0B9vIwY0qpKgk3yAd80GNdahLa3Dld+nzGCAUQwggFAAgEBMDAwGDEW",
+        "MBQGA1UEAwwNemFpayB0ZXN0IFRTQQIUIR4EUp1St8OBcZWc3hapkVW3UqQwDQYJYIZIAWUDBAIBBQCggaQwGgYJKoZIhvcN",
+        "AQkDMQ0GCyqGSIb3DQEJEAEEMBwGCSqGSIb3DQEJBTEPFw0yNjEwMTYxNDU1MjBaMC8GCSqGSIb3DQEJBDEiBCCIgUxqPPc0",
+        "rwmyGXczYh4z8FOnN0MyUMacHXX7TbSkXzA3BgsqhkiG9w0BCRACLzEoMCYwJDAiBCDQkou+E7QSjjkRUu3aQx9eLsILrvW/",
+        "m61Qho/pihuk1jAKBggqhkjOPQQDAgRHMEUCIGvT7V6pi8PeM1IFibTmc0eF07iWOhOdBvn3CjEPh6ntAiEAw5QZWoxXUZaL",
+        "aWl6q3EzEMFY1zfXjZQdKakKVfh9xAo=",
+    );
+    const TSA_CERT: &str = concat!(
+        "MIIBiDCCAS6gAwIBAgIUIR4EUp1St8OBcZWc3hapkVW3UqQwCgYIKoZIzj0EAwIwGDEWMBQGA1UEAwwNemFpayB0ZXN0IFRT",
+        "QTAgFw0yNjEwMTYxNDU1MjBaGA8yMTI2MDkyMjE0NTUyMFowGDEWMBQGA1UEAwwNemFpayB0ZXN0IFRTQTBZMBMGByqGSM49",
+        "AgEGCCqGSM49AwEHA0IABNSDr0Lt8K+SdSQtfiyt8RAwVwSH8zUfLIwe0VS5D+vJMlO4vEacfPuGxscb8z4a+EH0NJrHdb/V",
+        "UNEf9btQ/zujVDBSMAkGA1UdEwQCMAAwDgYDVR0PAQH/BAQDAgeAMBYGA1UdJQEB/wQMMAoGCCsGAQUFBwMIMB0GA1UdDgQW",
+        "BBS4v6mbtOjmmeAJty49ofWGunsEjTAKBggqhkjOPQQDAgNIADBFAiBDdQxMKmS1BKFgeyTxEyqEKguElMQr1Q6WRTBpyIUp",
+        "yAIhAK9aSwNnvVxnQH28jBjSqkqCTfIB3zQY11qEtrcOV36f",
+    );
+    /// Another certificate with the same subject.
+    const OTHER_CERT: &str = concat!(
+        "MIIBiTCCAS6gAwIBAgIUdViYICKUqytVeOcrelKGwgBdH98wCgYIKoZIzj0EAwIwGDEWMBQGA1UEAwwNemFpayB0ZXN0IFRT",
+        "QTAgFw0yNjEwMTYxNDU1MjBaGA8yMTI2MDkyMjE0NTUyMFowGDEWMBQGA1UEAwwNemFpayB0ZXN0IFRTQTBZMBMGByqGSM49",
+        "AgEGCCqGSM49AwEHA0IABPCiUP5808B99YPH+3rthcIUu6sMQRpcAt22nxs0cxcaEoibjW6Ql3g/kj9CEAtheKZyixa3VdcJ",
+        "PnK2AbNNh06jVDBSMAkGA1UdEwQCMAAwDgYDVR0PAQH/BAQDAgeAMBYGA1UdJQEB/wQMMAoGCCsGAQUFBwMIMB0GA1UdDgQW",
+        "BBSXW6J+wtdROgP9CjcBy2POAqGfYjAKBggqhkjOPQQDAgNJADBGAiEA0e4fO2UWQ90J/K3P5V7mKyOEVP5sHoffyHAZlyX1",
+        "xTQCIQDEq92WvFU+PICSh5kZfv3p3G+9kdKiJc+UZgLsOVpheQ==",
+    );
+    const PAYLOAD: &[u8] = b"zaik timestamp test bundle";
+
+    fn token() -> Vec<u8> {
+        BASE64.decode(TOKEN).unwrap()
+    }
+
+    fn certificate(base64: &str) -> TsaCertificate {
+        TsaCertificate::from_bytes(&BASE64.decode(base64).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn token_signed_by_the_pinned_tsa_verifies() {
+        let info = verify(&token(), PAYLOAD, &certificate(TSA_CERT)).unwrap();
+        assert_eq!(info.policy, "1.2.3.4.1");
+        assert_eq!(info.digest, hex::encode(Sha256::digest(PAYLOAD)));
+        assert_eq!(check_imprint(&token(), PAYLOAD).unwrap(), info);
+        let other_payload = verify(&token(), b"another bundle", &certificate(TSA_CERT));
+        assert!(matches!(other_payload, Err(TimestampError::DigestMismatch)));
+
+        // The same certificate as PEM
+        let pem = format!("-----BEGIN CERTIFICATE-----\n{}\n-----END CERTIFICATE-----\n", TSA_CERT);
+        verify(&token(), PAYLOAD, &TsaCertificate::from_bytes(pem.as_bytes()).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn tampered_or_foreign_tokens_are_rejected() {
+        // The imprint still matches, but the signature does not
+        let mut tampered = token();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert_eq!(check_imprint(&tampered, PAYLOAD).unwrap().digest, hex::encode(Sha256::digest(PAYLOAD)));
+        assert!(matches!(verify(&tampered, PAYLOAD, &certificate(TSA_CERT)), Err(TimestampError::BadSignature)));
+
+        // An edited time no longer matches the signed messageDigest
+        let mut backdated = token();
+        let year = backdated.windows(4).position(|window| window == b"2026").unwrap();
+        backdated[year + 3] = b'0';
+        assert!(check_imprint(&backdated, PAYLOAD).unwrap().time.starts_with("2020"));
+        assert!(matches!(
+            verify(&backdated, PAYLOAD, &certificate(TSA_CERT)),
+            Err(TimestampError::SignedAttribute("messageDigest"))
+        ));
+
+        // A genuine token from a TSA other than the pinned one
+        assert!(matches!(verify(&token(), PAYLOAD, &certificate(OTHER_CERT)), Err(TimestampError::SignerMismatch)));
+    }
+}
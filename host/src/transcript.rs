@@ -0,0 +1,132 @@
+//! Verification transcripts: every check Agent B ran on a bundle, in
+//! order, with what it looked at, what it found and how long it took.
+//!
+//! A [`Decision`](crate::signing::Decision) records the verdict; the
+//! transcript records how it was reached. It is written next to the bundle
+//! and signed with the verifier's key like a decision, so an auditor can
+//! see exactly what was checked without re-running the verifier.
+
+use crate::outcome::Outcome;
+use crate::policy::PolicyOutcome;
+use crate::signing::{DetachedSignature, SignerRole};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// One check and its result.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Step {
+    /// What was checked, e.g. `receipt` or `rule:sum_threshold`.
+    pub name: String,
+    /// What the check was run on or against.
+    pub input: String,
+    pub passed: bool,
+    /// What the check found.
+    pub result: String,
+    pub elapsed_us: u64,
+}
+
+impl Step {
+    /// A check that started at `started` and has just finished.
+    pub fn finished(
+        name: impl Into<String>,
+        input: impl Into<String>,
+        passed: bool,
+        result: impl Into<String>,
+        started: Instant,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            input: input.into(),
+            passed,
+            result: result.into(),
+            elapsed_us: started.elapsed().as_micros().min(u64::MAX as u128) as u64,
+        }
+    }
+
+    /// One step per rule of `outcome`, all timed from `started`, since
+    /// the rules are evaluated together.
+    pub fn rules(outcome: &PolicyOutcome, started: Instant) -> Vec<Self> {
+        outcome
+            .rules
+            .iter()
+            .map(|rule| {
+                Self::finished(
+                    format!("rule:{}", rule.rule),
+                    rule.requirement.clone(),
+                    rule.passed,
+                    rule.actual.clone(),
+                    started,
+                )
+            })
+            .collect()
+    }
+}
+
+/// Every step of one verification and the decision it led to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Transcript {
+    /// Hex SHA-256 of the bundle file.
+    pub bundle_digest: String,
+    /// Hex image ID the receipt was verified against.
+    pub image_id: String,
+    pub policy_version: String,
+    pub steps: Vec<Step>,
+    pub outcome: Outcome,
+    /// Why the outcome is not an accept.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    /// Seconds since the Unix epoch.
+    pub verified_at: u64,
+}
+
+impl Transcript {
+    pub fn new(
+        bundle_digest: String,
+        image_id: String,
+        policy_version: String,
+        steps: Vec<Step>,
+        outcome: Outcome,
+        reason: Option<String>,
+    ) -> Self {
+        Self {
+            bundle_digest,
+            image_id,
+            policy_version,
+            steps,
+            outcome,
+            reason,
+            verified_at: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+        }
+    }
+
+    /// Path of the transcript recorded for the bundle at `bundle_path`.
+    pub fn path_for(bundle_path: &Path) -> PathBuf {
+        bundle_path.with_extension("transcript.json")
+    }
+
+    /// The exact bytes the verifier signs.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec_pretty(self).expect("Transcript is always serializable")
+    }
+
+    /// Parse a recorded transcript, checking that `signature` is
+    /// `verifier`'s over `bytes` and that it is about the bundle with
+    /// `bundle_digest`.
+    pub fn open(
+        bytes: &[u8],
+        signature: &DetachedSignature,
+        verifier: &str,
+        bundle_digest: &str,
+    ) -> Result<Self, String> {
+        signature.verify(SignerRole::Verifier, bytes).map_err(|e| e.to_string())?;
+        if signature.signer != verifier {
+            return Err(format!("transcript signed by {}, not the deciding verifier", signature.signer));
+        }
+        let transcript: Self = serde_json::from_slice(bytes).map_err(|e| e.to_string())?;
+        if transcript.bundle_digest != bundle_digest {
+            return Err("transcript is about a different bundle".to_string());
+        }
+        Ok(transcript)
+    }
+}
@@ -0,0 +1,322 @@
+//! Transform specs and receipts for the transform guest (see
+//! [`zaik_core::transform`]).
+//!
+//! ```toml
+//! delimiter = ";"         # default ","
+//! quote = "'"             # default '"'
+//! has_header = true       # default true
+//!
+//! [[field]]
+//! column = 0              # zero-based
+//! name = "account"
+//! kind = "text"           # text, integer or boolean
+//!
+//! [[field]]
+//! column = 2
+//! name = "amount"
+//! kind = "integer"
+//! ```
+//!
+//! `host transform prove` writes the JSON Lines output and, next to it, a
+//! `.receipt` holding the receipt and the transform it was proven with.
+//! [`verify`] checks that receipt against the transform image, then that the
+//! output, and the spec and CSV when given, hash to what the journal commits.
+//!
+//! A schema migration (see [`zaik_core::migration`]) is declared the same
+//! way, with the same dialect keys:
+//!
+//! ```toml
+//! [[rename]]
+//! from = "amt"
+//! to = "amount"
+//!
+//! [[add]]
+//! name = "currency"
+//! default = "USD"
+//! ```
+//!
+//! `host transform migrate` proves a new file is the old one migrated and
+//! writes a [`MigrationReceipt`] next to the new file; [`verify_migration`]
+//! checks it.
+
+use crate::outcome::{FailureReason, Outcome};
+use crate::proof::ProofKind;
+use crate::store;
+use crate::untrusted;
+use risc0_zkvm::sha::Digest;
+use risc0_zkvm::Receipt;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use zaik_core::dialect::Dialect;
+use zaik_core::migration::{AddedColumn, Migration, MigrationResult, Rename};
+use zaik_core::schema::JournalSchema;
+use zaik_core::transform::{Field, Transform, TransformResult};
+
+/// A transform as written in a spec file.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TransformSpec {
+    #[serde(default = "default_delimiter")]
+    pub delimiter: char,
+    #[serde(default = "default_quote")]
+    pub quote: char,
+    #[serde(default = "default_has_header")]
+    pub has_header: bool,
+    #[serde(rename = "field")]
+    pub fields: Vec<Field>,
+}
+
+fn default_delimiter() -> char {
+    Dialect::CSV.delimiter as char
+}
+
+fn default_quote() -> char {
+    Dialect::CSV.quote as char
+}
+
+fn default_has_header() -> bool {
+    true
+}
+
+impl TransformSpec {
+    /// Read a spec and check the transform it declares.
+    pub fn load(path: &Path) -> Result<Transform, Box<dyn Error>> {
+        let spec: TransformSpec = toml::from_str(&fs::read_to_string(path)?)?;
+        spec.to_transform()
+    }
+
+    pub fn to_transform(&self) -> Result<Transform, Box<dyn Error>> {
+        let transform = Transform {
+            dialect: dialect(self.delimiter, self.quote, self.has_header)?,
+            fields: self.fields.clone(),
+        };
+        transform.validate()?;
+        Ok(transform)
+    }
+}
+
+/// A schema migration as written in a spec file.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MigrationSpec {
+    #[serde(default = "default_delimiter")]
+    pub delimiter: char,
+    #[serde(default = "default_quote")]
+    pub quote: char,
+    #[serde(default, rename = "rename")]
+    pub renames: Vec<Rename>,
+    #[serde(default, rename = "add")]
+    pub added: Vec<AddedColumn>,
+}
+
+impl MigrationSpec {
+    /// Read a spec and check the migration it declares.
+    pub fn load(path: &Path) -> Result<Migration, Box<dyn Error>> {
+        let spec: MigrationSpec = toml::from_str(&fs::read_to_string(path)?)?;
+        spec.to_migration()
+    }
+
+    pub fn to_migration(&self) -> Result<Migration, Box<dyn Error>> {
+        let migration = Migration {
+            dialect: dialect(self.delimiter, self.quote, true)?,
+            renames: self.renames.clone(),
+            added: self.added.clone(),
+        };
+        migration.validate()?;
+        Ok(migration)
+    }
+}
+
+fn dialect(delimiter: char, quote: char, has_header: bool) -> Result<Dialect, String> {
+    let ascii = |name: &str, c: char| {
+        u8::try_from(c)
+            .ok()
+            .filter(u8::is_ascii)
+            .ok_or_else(|| format!("the {} must be one ASCII character", name))
+    };
+    Ok(Dialect {
+        delimiter: ascii("delimiter", delimiter)?,
+        quote: ascii("quote", quote)?,
+        has_header,
+    })
+}
+
+/// What `host transform prove` writes next to the output.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransformReceipt {
+    pub transform: Transform,
+    pub receipt: Receipt,
+}
+
+impl TransformReceipt {
+    /// `<output>.receipt`.
+    pub fn path_for(output: &Path) -> PathBuf {
+        let mut path = output.as_os_str().to_owned();
+        path.push(".receipt");
+        PathBuf::from(path)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let bytes = untrusted::read(path, untrusted::MAX_BUNDLE_BYTES)?;
+        Ok(untrusted::decode(&bytes)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        store::write_atomic(path, &bincode::serialize(self)?)?;
+        Ok(())
+    }
+}
+
+/// What `host transform migrate` writes next to the new file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrationReceipt {
+    pub migration: Migration,
+    pub receipt: Receipt,
+}
+
+impl MigrationReceipt {
+    /// `<new file>.receipt`.
+    pub fn path_for(new_file: &Path) -> PathBuf {
+        TransformReceipt::path_for(new_file)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let bytes = untrusted::read(path, untrusted::MAX_BUNDLE_BYTES)?;
+        Ok(untrusted::decode(&bytes)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        store::write_atomic(path, &bincode::serialize(self)?)?;
+        Ok(())
+    }
+}
+
+/// Why a transform or migration receipt was not accepted.
+#[derive(Debug)]
+pub enum TransformRejection {
+    /// Dev-mode receipts are not accepted.
+    NotCryptographic(ProofKind),
+    ReceiptInvalid(String),
+    /// The journal is not a transform or migration journal of this format.
+    JournalUnreadable,
+    /// The journal was committed for a different transform or migration
+    /// than the one stored with the receipt.
+    TransformMismatch,
+    /// The transform or migration is not the one in the spec the verifier
+    /// holds.
+    SpecMismatch,
+    /// The output, or a migration's new file, does not hash to what the
+    /// journal commits.
+    OutputMismatch,
+    /// The CSV, or a migration's old file, does not hash to what the
+    /// journal commits.
+    CsvMismatch,
+}
+
+impl fmt::Display for TransformRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransformRejection::NotCryptographic(kind) => write!(f, "receipt is {}, not a cryptographic proof", kind),
+            TransformRejection::ReceiptInvalid(reason) => write!(f, "receipt does not verify: {}", reason),
+            TransformRejection::JournalUnreadable => write!(f, "journal is not a transform or migration journal of this format"),
+            TransformRejection::TransformMismatch => {
+                write!(f, "journal was committed for a different spec than the receipt file holds")
+            }
+            TransformRejection::SpecMismatch => write!(f, "receipt was proven with a different spec than the one given"),
+            TransformRejection::OutputMismatch => write!(f, "output does not match the hash the journal commits"),
+            TransformRejection::CsvMismatch => write!(f, "input CSV does not match the hash the journal commits"),
+        }
+    }
+}
+
+impl Error for TransformRejection {}
+
+impl TransformRejection {
+    /// The outcome and stable reason a CLI reports for this rejection.
+    pub fn to_failure(&self) -> FailureReason {
+        let (outcome, reason) = match self {
+            TransformRejection::NotCryptographic(_) => (Outcome::Conditional, "non_cryptographic_receipt"),
+            TransformRejection::ReceiptInvalid(_) => (Outcome::CryptoFailure, "receipt_verification_failed"),
+            TransformRejection::JournalUnreadable => (Outcome::CryptoFailure, "journal_decode_failed"),
+            TransformRejection::TransformMismatch => (Outcome::CryptoFailure, "journal_mismatch"),
+            TransformRejection::SpecMismatch => (Outcome::PolicyReject, "transform_mismatch"),
+            TransformRejection::OutputMismatch => (Outcome::CryptoFailure, "output_mismatch"),
+            TransformRejection::CsvMismatch => (Outcome::UnexpectedData, "csv_mismatch"),
+        };
+        FailureReason::new(outcome, reason, self.to_string())
+    }
+}
+
+/// Check `proof` against `image_id` and `output`, and against `spec` and
+/// `csv_data` when the verifier holds them.
+pub fn verify(
+    proof: &TransformReceipt,
+    image_id: impl Into<Digest>,
+    output: &[u8],
+    spec: Option<&Transform>,
+    csv_data: Option<&str>,
+    allow_dev_mode: bool,
+) -> Result<TransformResult, TransformRejection> {
+    check_receipt(&proof.receipt, image_id, allow_dev_mode)?;
+    let journal =
+        TransformResult::from_bytes(&proof.receipt.journal.bytes).ok_or(TransformRejection::JournalUnreadable)?;
+    if journal.transform_hash != proof.transform.hash() {
+        return Err(TransformRejection::TransformMismatch);
+    }
+    if spec.is_some_and(|spec| spec.hash() != journal.transform_hash) {
+        return Err(TransformRejection::SpecMismatch);
+    }
+    if <[u8; 32]>::from(Sha256::digest(output)) != journal.output_hash {
+        return Err(TransformRejection::OutputMismatch);
+    }
+    if csv_data.is_some_and(|csv_data| zaik_core::csv_hash(csv_data) != journal.csv_hash) {
+        return Err(TransformRejection::CsvMismatch);
+    }
+    Ok(journal)
+}
+
+/// Check `proof` against `image_id` and the new file, and against `spec`
+/// and the old file when the verifier holds them.
+pub fn verify_migration(
+    proof: &MigrationReceipt,
+    image_id: impl Into<Digest>,
+    new_data: &[u8],
+    spec: Option<&Migration>,
+    old_data: Option<&str>,
+    allow_dev_mode: bool,
+) -> Result<MigrationResult, TransformRejection> {
+    check_receipt(&proof.receipt, image_id, allow_dev_mode)?;
+    let journal =
+        MigrationResult::from_bytes(&proof.receipt.journal.bytes).ok_or(TransformRejection::JournalUnreadable)?;
+    if journal.migration_hash != proof.migration.hash() {
+        return Err(TransformRejection::TransformMismatch);
+    }
+    if spec.is_some_and(|spec| spec.hash() != journal.migration_hash) {
+        return Err(TransformRejection::SpecMismatch);
+    }
+    if <[u8; 32]>::from(Sha256::digest(new_data)) != journal.new_hash {
+        return Err(TransformRejection::OutputMismatch);
+    }
+    if old_data.is_some_and(|old_data| zaik_core::csv_hash(old_data) != journal.old_hash) {
+        return Err(TransformRejection::CsvMismatch);
+    }
+    Ok(journal)
+}
+
+fn check_receipt(
+    receipt: &Receipt,
+    image_id: impl Into<Digest>,
+    allow_dev_mode: bool,
+) -> Result<(), TransformRejection> {
+    let kind = ProofKind::of(receipt);
+    if !kind.is_cryptographic() && !allow_dev_mode {
+        return Err(TransformRejection::NotCryptographic(kind));
+    }
+    receipt
+        .verify(image_id)
+        .map_err(|e| TransformRejection::ReceiptInvalid(e.to_string()))
+}
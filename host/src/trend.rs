@@ -0,0 +1,186 @@
+//! Trend checks: sums that pass every absolute rule but move sharply from
+//! the tenant's history are held for investigation.
+//!
+//! Every accepted proof's verified sum is appended to the store's
+//! [`SumHistory`]. A policy with a [`Trend`] table compares the next sum
+//! with the mean of the tenant's last `window` sums from earlier periods;
+//! a change beyond `max_change_percent` turns the decision into
+//! [`Outcome::Investigate`](crate::outcome::Outcome::Investigate) instead
+//! of an accept.
+
+use crate::backfill::Period;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A policy's `[trend]` table:
+/// ```toml
+/// [trend]
+/// max_change_percent = 50   # investigate sums more than 50% off the baseline
+/// window = 3                # baseline: mean of the last 3 earlier sums
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Trend {
+    pub max_change_percent: u64,
+    #[serde(default = "default_window")]
+    pub window: usize,
+}
+
+fn default_window() -> usize {
+    1
+}
+
+/// One accepted proof's sum.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub tenant: String,
+    /// Reporting period, e.g. `2024-03`; `None` when run without one.
+    pub period: Option<String>,
+    /// Hex CSV hash of the proven file.
+    pub csv_hash: String,
+    /// Hex SHA-256 of the proof's journal.
+    pub journal_digest: String,
+    pub sum: u64,
+    /// Seconds since the Unix epoch.
+    pub recorded_at: u64,
+}
+
+/// How a sum compares with its baseline.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TrendCheck {
+    pub sum: u64,
+    /// Mean of the sums compared against; `None` without any history.
+    pub baseline: Option<u64>,
+    /// How many earlier sums the baseline covers.
+    pub history: usize,
+    /// Absolute change from the baseline in percent, rounded down; `None`
+    /// without history, or against a zero baseline.
+    pub change_percent: Option<u64>,
+    /// Whether the change exceeds the policy's limit. A nonzero sum after a
+    /// zero baseline always does.
+    pub anomalous: bool,
+    pub max_change_percent: u64,
+}
+
+impl fmt::Display for TrendCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.baseline, self.change_percent) {
+            (None, _) => write!(f, "sum {} has no earlier history to compare with", self.sum),
+            (Some(baseline), Some(change)) => write!(
+                f,
+                "sum {} is {}% off the baseline {} of {} earlier sum(s) (limit {}%)",
+                self.sum, change, baseline, self.history, self.max_change_percent
+            ),
+            (Some(baseline), None) => write!(
+                f,
+                "sum {} against a baseline of {} over {} earlier sum(s)",
+                self.sum, baseline, self.history
+            ),
+        }
+    }
+}
+
+impl Trend {
+    /// Compare `sum` with the mean of `history`, oldest first.
+    pub fn check(&self, sum: u64, history: &[u64]) -> TrendCheck {
+        let recent = &history[history.len().saturating_sub(self.window.max(1))..];
+        let baseline = (!recent.is_empty())
+            .then(|| (recent.iter().map(|&sum| sum as u128).sum::<u128>() / recent.len() as u128) as u64);
+        let change_percent = baseline
+            .filter(|&baseline| baseline > 0)
+            .map(|baseline| (sum.abs_diff(baseline) as u128 * 100 / baseline as u128).min(u64::MAX as u128) as u64);
+        let anomalous = match (baseline, change_percent) {
+            (Some(_), Some(change)) => change > self.max_change_percent,
+            (Some(_), None) => sum > 0,
+            (None, _) => false,
+        };
+        TrendCheck {
+            sum,
+            baseline,
+            history: recent.len(),
+            change_percent,
+            anomalous,
+            max_change_percent: self.max_change_percent,
+        }
+    }
+}
+
+/// Append-only JSON-lines record of accepted sums.
+pub struct SumHistory {
+    path: PathBuf,
+}
+
+impl SumHistory {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Every recorded entry, oldest first; none if the history is new.
+    pub fn entries(&self) -> Result<Vec<HistoryEntry>, Box<dyn Error>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        fs::read_to_string(&self.path)?
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| Ok(serde_json::from_str(line)?))
+            .collect()
+    }
+
+    /// `tenant`'s sums to compare a proof of `csv_hash` with, oldest first:
+    /// those from periods before `period`, or every other file's without
+    /// one. Re-proving a file never compares it with itself.
+    pub fn baseline_sums(
+        &self,
+        tenant: &str,
+        period: Option<&Period>,
+        csv_hash: &str,
+    ) -> Result<Vec<u64>, Box<dyn Error>> {
+        Ok(self
+            .entries()?
+            .into_iter()
+            .filter(|entry| entry.tenant == tenant && entry.csv_hash != csv_hash)
+            .filter(|entry| match (period, &entry.period) {
+                (Some(current), Some(earlier)) => earlier.parse::<Period>().is_ok_and(|earlier| earlier < *current),
+                (Some(_), None) => false,
+                (None, _) => true,
+            })
+            .map(|entry| entry.sum)
+            .collect())
+    }
+
+    /// Add an accepted proof's sum, unless the same journal is already
+    /// recorded for the tenant. Returns whether it was added.
+    pub fn record(
+        &self,
+        tenant: &str,
+        period: Option<&Period>,
+        csv_hash: &str,
+        journal_digest: &[u8; 32],
+        sum: u64,
+    ) -> Result<bool, Box<dyn Error>> {
+        let journal_digest = hex::encode(journal_digest);
+        if self
+            .entries()?
+            .iter()
+            .any(|entry| entry.tenant == tenant && entry.journal_digest == journal_digest)
+        {
+            return Ok(false);
+        }
+        let entry = HistoryEntry {
+            tenant: tenant.to_string(),
+            period: period.map(|period| period.to_string()),
+            csv_hash: csv_hash.to_string(),
+            journal_digest,
+            sum,
+            recorded_at: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+        };
+        let mut file = fs::OpenOptions::new().create(true).append(true).open(&self.path)?;
+        writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+        Ok(true)
+    }
+}
@@ -0,0 +1,331 @@
+//! Reading artifacts handed over by agents a verifier does not trust.
+//!
+//! Bundles, chunk receipts and their JSON sidecars are written by provers,
+//! so everything a verifier reads from them goes through here. Files are
+//! refused before they are read if they are over a size limit, and bincode
+//! is decoded with a bound on how deeply values nest: a receipt can carry
+//! receipts for its assumptions, and a few hundred kilobytes of nested
+//! composite receipts is otherwise enough to overflow the decoder's stack.
+//! Trailing bytes after a bundle are rejected rather than ignored.
+//!
+//! Lengths inside bincode need no extra bound: decoding from a slice never
+//! reserves more than the bytes left in it can fill. JSON is parsed by
+//! `serde_json`, which has its own nesting limit.
+//!
+//! The `fuzz` directory next to this crate holds `cargo fuzz` targets for
+//! these decoders and for the receipt worker.
+
+use serde::de::{self, DeserializeOwned, DeserializeSeed, Deserializer, EnumAccess, MapAccess, SeqAccess, VariantAccess, Visitor};
+use std::cell::Cell;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read};
+use std::marker::PhantomData;
+use std::path::Path;
+
+/// Largest bundle or receipt file a verifier reads.
+pub const MAX_BUNDLE_BYTES: u64 = 64 << 20;
+
+/// Largest signature, decision, transcript, manifest, attestation or
+/// timestamp file a verifier reads.
+pub const MAX_SIDECAR_BYTES: u64 = 1 << 20;
+
+/// Deepest nesting of values in a decoded bundle. Each composite receipt
+/// proving an assumption of another takes three levels, so this admits
+/// chains of 40.
+pub const MAX_DEPTH: usize = 128;
+
+/// Read the file at `path`, failing with [`io::ErrorKind::InvalidData`]
+/// if it is over `limit` bytes.
+pub fn read(path: &Path, limit: u64) -> io::Result<Vec<u8>> {
+    let file = File::open(path)?;
+    let too_large = || {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} is over the {} byte limit", path.display(), limit),
+        )
+    };
+    let len = file.metadata()?.len();
+    if len > limit {
+        return Err(too_large());
+    }
+    // The file can grow after the check; never read past the limit
+    let mut bytes = Vec::with_capacity(len as usize);
+    file.take(limit + 1).read_to_end(&mut bytes)?;
+    if bytes.len() as u64 > limit {
+        return Err(too_large());
+    }
+    Ok(bytes)
+}
+
+/// [`read`] for text files.
+pub fn read_to_string(path: &Path, limit: u64) -> io::Result<String> {
+    String::from_utf8(read(path, limit)?).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Decode bincode written by `bincode::serialize`, bounding nesting to
+/// [`MAX_DEPTH`] and rejecting trailing bytes.
+pub fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, bincode::Error> {
+    use bincode::Options;
+    let depth = Cell::new(0);
+    bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .reject_trailing_bytes()
+        .deserialize_seed(
+            Bounded {
+                inner: PhantomData::<T>,
+                depth: &depth,
+            },
+            bytes,
+        )
+}
+
+/// Wraps a serde deserializer, visitor, access or seed so that every value
+/// decoded through it counts towards one shared nesting depth.
+struct Bounded<'a, T> {
+    inner: T,
+    depth: &'a Cell<usize>,
+}
+
+impl<'a, T> Bounded<'a, T> {
+    fn wrap<U>(&self, inner: U) -> Bounded<'a, U> {
+        Bounded {
+            inner,
+            depth: self.depth,
+        }
+    }
+}
+
+impl<'de, S: DeserializeSeed<'de>> DeserializeSeed<'de> for Bounded<'_, S> {
+    type Value = S::Value;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        let deserializer = self.wrap(deserializer);
+        self.inner.deserialize(deserializer)
+    }
+}
+
+macro_rules! nested {
+    ($($method:ident($($arg:ident: $ty:ty),*);)*) => {$(
+        fn $method<V: Visitor<'de>>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, Self::Error> {
+            let depth = self.depth;
+            if depth.get() >= MAX_DEPTH {
+                return Err(de::Error::custom(format!("values nest deeper than {} levels", MAX_DEPTH)));
+            }
+            depth.set(depth.get() + 1);
+            let value = self.inner.$method($($arg,)* Bounded { inner: visitor, depth });
+            depth.set(depth.get() - 1);
+            value
+        }
+    )*};
+}
+
+impl<'de, D: Deserializer<'de>> Deserializer<'de> for Bounded<'_, D> {
+    type Error = D::Error;
+
+    nested! {
+        deserialize_any();
+        deserialize_bool();
+        deserialize_i8();
+        deserialize_i16();
+        deserialize_i32();
+        deserialize_i64();
+        deserialize_i128();
+        deserialize_u8();
+        deserialize_u16();
+        deserialize_u32();
+        deserialize_u64();
+        deserialize_u128();
+        deserialize_f32();
+        deserialize_f64();
+        deserialize_char();
+        deserialize_str();
+        deserialize_string();
+        deserialize_bytes();
+        deserialize_byte_buf();
+        deserialize_option();
+        deserialize_unit();
+        deserialize_unit_struct(name: &'static str);
+        deserialize_newtype_struct(name: &'static str);
+        deserialize_seq();
+        deserialize_tuple(len: usize);
+        deserialize_tuple_struct(name: &'static str, len: usize);
+        deserialize_map();
+        deserialize_struct(name: &'static str, fields: &'static [&'static str]);
+        deserialize_enum(name: &'static str, variants: &'static [&'static str]);
+        deserialize_identifier();
+        deserialize_ignored_any();
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.inner.is_human_readable()
+    }
+}
+
+macro_rules! forward_visits {
+    ($($method:ident($ty:ty);)*) => {$(
+        fn $method<E: de::Error>(self, v: $ty) -> Result<Self::Value, E> {
+            self.inner.$method(v)
+        }
+    )*};
+}
+
+impl<'de, V: Visitor<'de>> Visitor<'de> for Bounded<'_, V> {
+    type Value = V::Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.expecting(f)
+    }
+
+    forward_visits! {
+        visit_bool(bool);
+        visit_i8(i8);
+        visit_i16(i16);
+        visit_i32(i32);
+        visit_i64(i64);
+        visit_i128(i128);
+        visit_u8(u8);
+        visit_u16(u16);
+        visit_u32(u32);
+        visit_u64(u64);
+        visit_u128(u128);
+        visit_f32(f32);
+        visit_f64(f64);
+        visit_char(char);
+        visit_str(&str);
+        visit_borrowed_str(&'de str);
+        visit_string(String);
+        visit_bytes(&[u8]);
+        visit_borrowed_bytes(&'de [u8]);
+        visit_byte_buf(Vec<u8>);
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+        self.inner.visit_none()
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        self.inner.visit_unit()
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        let deserializer = self.wrap(deserializer);
+        self.inner.visit_some(deserializer)
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        let deserializer = self.wrap(deserializer);
+        self.inner.visit_newtype_struct(deserializer)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+        let seq = self.wrap(seq);
+        self.inner.visit_seq(seq)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+        let map = self.wrap(map);
+        self.inner.visit_map(map)
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<Self::Value, A::Error> {
+        let data = self.wrap(data);
+        self.inner.visit_enum(data)
+    }
+}
+
+impl<'de, A: SeqAccess<'de>> SeqAccess<'de> for Bounded<'_, A> {
+    type Error = A::Error;
+
+    fn next_element_seed<S: DeserializeSeed<'de>>(&mut self, seed: S) -> Result<Option<S::Value>, A::Error> {
+        let seed = self.wrap(seed);
+        self.inner.next_element_seed(seed)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
+
+impl<'de, A: MapAccess<'de>> MapAccess<'de> for Bounded<'_, A> {
+    type Error = A::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, A::Error> {
+        let seed = self.wrap(seed);
+        self.inner.next_key_seed(seed)
+    }
+
+    fn next_value_seed<S: DeserializeSeed<'de>>(&mut self, seed: S) -> Result<S::Value, A::Error> {
+        let seed = self.wrap(seed);
+        self.inner.next_value_seed(seed)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
+
+impl<'a, 'de, A: EnumAccess<'de>> EnumAccess<'de> for Bounded<'a, A> {
+    type Error = A::Error;
+    type Variant = Bounded<'a, A::Variant>;
+
+    fn variant_seed<S: DeserializeSeed<'de>>(self, seed: S) -> Result<(S::Value, Self::Variant), A::Error> {
+        let depth = self.depth;
+        let (value, variant) = self.inner.variant_seed(Bounded { inner: seed, depth })?;
+        Ok((value, Bounded { inner: variant, depth }))
+    }
+}
+
+impl<'de, A: VariantAccess<'de>> VariantAccess<'de> for Bounded<'_, A> {
+    type Error = A::Error;
+
+    fn unit_variant(self) -> Result<(), A::Error> {
+        self.inner.unit_variant()
+    }
+
+    fn newtype_variant_seed<S: DeserializeSeed<'de>>(self, seed: S) -> Result<S::Value, A::Error> {
+        let seed = self.wrap(seed);
+        self.inner.newtype_variant_seed(seed)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, A::Error> {
+        let visitor = self.wrap(visitor);
+        self.inner.tuple_variant(len, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, A::Error> {
+        let visitor = self.wrap(visitor);
+        self.inner.struct_variant(fields, visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A sparse file of `len` zero bytes.
+    fn file_of(name: &str, len: u64) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("zaik-untrusted-{}-{}", name, std::process::id()));
+        File::create(&path).unwrap().set_len(len).unwrap();
+        path
+    }
+
+    #[test]
+    fn files_at_the_limit_are_read_and_one_byte_more_is_refused() {
+        for (name, limit) in [("bundle", MAX_BUNDLE_BYTES), ("sidecar", MAX_SIDECAR_BYTES)] {
+            let at_limit = file_of(&format!("{}-at", name), limit);
+            assert_eq!(read(&at_limit, limit).unwrap().len() as u64, limit);
+
+            let over = file_of(&format!("{}-over", name), limit + 1);
+            assert_eq!(read(&over, limit).unwrap_err().kind(), io::ErrorKind::InvalidData);
+            assert_eq!(read_to_string(&over, limit).unwrap_err().kind(), io::ErrorKind::InvalidData);
+
+            std::fs::remove_file(&at_limit).unwrap();
+            std::fs::remove_file(&over).unwrap();
+        }
+    }
+}
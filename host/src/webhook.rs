@@ -0,0 +1,258 @@
+//! Decision notifications posted to a webhook.
+//!
+//! Each [`Notification`] is sent as a JSON body with two headers:
+//!
+//! ```text
+//! X-Zaik-Timestamp: 1718000000
+//! X-Zaik-Signature: sha256=<hex HMAC-SHA256 of "<timestamp>.<body>">
+//! ```
+//!
+//! so a receiver holding the shared secret can check both the payload and
+//! its freshness with [`verify_signature`]. Failed deliveries are retried
+//! with exponential backoff; a notification that still cannot be delivered
+//! goes to a dead-letter file, from which [`Webhook::redeliver`] retries it.
+
+use crate::outcome::Outcome;
+use ring::hmac;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs;
+#[cfg(feature = "prove")]
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub const SIGNATURE_HEADER: &str = "X-Zaik-Signature";
+pub const TIMESTAMP_HEADER: &str = "X-Zaik-Timestamp";
+
+/// How far ahead of the receiver's clock a timestamp may be, for skew.
+pub const MAX_CLOCK_SKEW: Duration = Duration::from_secs(60);
+
+/// What Agent B decided about one proof.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Notification {
+    /// Hex CSV hash from the journal.
+    pub csv_hash: String,
+    /// Path of the bundle in the prover's store.
+    pub bundle: String,
+    pub policy_version: String,
+    pub outcome: Outcome,
+    /// Stable failure reason; absent on accept.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    pub explanation: String,
+    /// Seconds since the Unix epoch.
+    pub decided_at: u64,
+}
+
+/// `sha256=<hex>` over `<timestamp>.<body>`.
+pub fn sign(secret: &[u8], timestamp: u64, body: &[u8]) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret);
+    let mut context = hmac::Context::with_key(&key);
+    context.update(timestamp.to_string().as_bytes());
+    context.update(b".");
+    context.update(body);
+    format!("sha256={}", hex::encode(context.sign().as_ref()))
+}
+
+/// Receiver side: check the signature header and that the timestamp is
+/// no older than `max_age` and no further ahead than [`MAX_CLOCK_SKEW`],
+/// in constant time.
+pub fn verify_signature(
+    secret: &[u8],
+    timestamp: u64,
+    body: &[u8],
+    signature: &str,
+    max_age: Duration,
+) -> Result<(), Box<dyn Error>> {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    if now.saturating_sub(timestamp) > max_age.as_secs() {
+        return Err(format!("webhook timestamp {} is older than {:?}", timestamp, max_age).into());
+    }
+    if timestamp > now.saturating_add(MAX_CLOCK_SKEW.as_secs()) {
+        return Err(format!("webhook timestamp {} is in the future", timestamp).into());
+    }
+    let tag = signature
+        .strip_prefix("sha256=")
+        .and_then(|tag| hex::decode(tag).ok())
+        .ok_or("webhook signature is not sha256=<hex>")?;
+    let mut message = format!("{}.", timestamp).into_bytes();
+    message.extend_from_slice(body);
+    hmac::verify(&hmac::Key::new(hmac::HMAC_SHA256, secret), &message, &tag)
+        .map_err(|_| "webhook signature does not match".into())
+}
+
+/// A notification that exhausted its retries, as kept in the dead-letter file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadLetter {
+    pub url: String,
+    pub notification: Notification,
+    pub attempts: u32,
+    pub last_error: String,
+    /// Seconds since the Unix epoch.
+    pub failed_at: u64,
+}
+
+/// Where and how to deliver notifications.
+#[derive(Debug, Clone)]
+pub struct Webhook {
+    pub url: String,
+    /// Shared HMAC secret; unsigned when `None`.
+    pub secret: Option<Vec<u8>>,
+    pub max_attempts: u32,
+    /// Wait before the second attempt; doubled after each further failure.
+    pub backoff: Duration,
+    pub dead_letters: PathBuf,
+}
+
+/// Why one delivery attempt failed, and whether another could succeed.
+#[cfg(feature = "prove")]
+struct AttemptError {
+    message: String,
+    retryable: bool,
+}
+
+impl Webhook {
+    pub fn new(url: impl Into<String>, secret: Option<Vec<u8>>, dead_letters: impl Into<PathBuf>) -> Self {
+        Self {
+            url: url.into(),
+            secret,
+            max_attempts: 5,
+            backoff: Duration::from_secs(1),
+            dead_letters: dead_letters.into(),
+        }
+    }
+
+    /// Deliver `notification`, retrying transient failures. A notification
+    /// that cannot be delivered is dead-lettered and the error returned.
+    #[cfg(feature = "prove")]
+    pub fn send(&self, notification: &Notification) -> Result<u32, Box<dyn Error>> {
+        let client = reqwest::blocking::Client::builder().timeout(Duration::from_secs(30)).build()?;
+        let body = serde_json::to_vec(notification)?;
+        let mut backoff = self.backoff;
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            let error = match self.attempt(&client, &body) {
+                Ok(()) => return Ok(attempts),
+                Err(error) => error,
+            };
+            if !error.retryable || attempts >= self.max_attempts {
+                self.dead_letter(notification, attempts, &error.message)?;
+                return Err(format!(
+                    "webhook delivery failed after {} attempt(s): {}; kept in {}",
+                    attempts,
+                    error.message,
+                    self.dead_letters.display()
+                )
+                .into());
+            }
+            std::thread::sleep(backoff);
+            backoff *= 2;
+        }
+    }
+
+    #[cfg(feature = "prove")]
+    fn attempt(&self, client: &reqwest::blocking::Client, body: &[u8]) -> Result<(), AttemptError> {
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        let mut request = client
+            .post(&self.url)
+            .header("Content-Type", "application/json")
+            .header(TIMESTAMP_HEADER, timestamp.to_string())
+            .body(body.to_vec());
+        if let Some(secret) = &self.secret {
+            request = request.header(SIGNATURE_HEADER, sign(secret, timestamp, body));
+        }
+        let status = request
+            .send()
+            .map_err(|e| AttemptError {
+                message: e.to_string(),
+                retryable: true,
+            })?
+            .status();
+        if status.is_success() {
+            return Ok(());
+        }
+        // Server errors and rate limits may clear up; other client errors will not
+        Err(AttemptError {
+            message: format!("webhook answered {}", status),
+            retryable: status.is_server_error() || status.as_u16() == 429,
+        })
+    }
+
+    #[cfg(feature = "prove")]
+    fn dead_letter(&self, notification: &Notification, attempts: u32, error: &str) -> Result<(), Box<dyn Error>> {
+        let letter = DeadLetter {
+            url: self.url.clone(),
+            notification: notification.clone(),
+            attempts,
+            last_error: error.to_string(),
+            failed_at: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+        };
+        let mut file = fs::OpenOptions::new().create(true).append(true).open(&self.dead_letters)?;
+        writeln!(file, "{}", serde_json::to_string(&letter)?)?;
+        Ok(())
+    }
+
+    /// Send every dead letter in this webhook's file again, each to the URL
+    /// it was meant for. Letters that fail again stay in the file; returns
+    /// how many were delivered and how many remain.
+    #[cfg(feature = "prove")]
+    pub fn redeliver(&self) -> Result<(usize, usize), Box<dyn Error>> {
+        let letters = dead_letters(&self.dead_letters)?;
+        // Failures are appended afresh, so start from an empty file
+        let pending = self.dead_letters.with_extension("jsonl.retrying");
+        if !letters.is_empty() {
+            fs::rename(&self.dead_letters, &pending)?;
+        }
+        let mut delivered = 0;
+        for letter in &letters {
+            let webhook = Webhook {
+                url: letter.url.clone(),
+                ..self.clone()
+            };
+            if webhook.send(&letter.notification).is_ok() {
+                delivered += 1;
+            }
+        }
+        if !letters.is_empty() {
+            fs::remove_file(&pending)?;
+        }
+        Ok((delivered, letters.len() - delivered))
+    }
+}
+
+/// Every letter in the dead-letter file at `path`, oldest first.
+pub fn dead_letters(path: &Path) -> Result<Vec<DeadLetter>, Box<dyn Error>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    fs::read_to_string(path)?
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| Ok(serde_json::from_str(line)?))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signatures_are_only_fresh_within_max_age_and_skew() {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        let max_age = Duration::from_secs(300);
+        let check = |timestamp: u64| {
+            let signature = sign(b"secret", timestamp, b"{}");
+            verify_signature(b"secret", timestamp, b"{}", &signature, max_age)
+        };
+        assert!(check(now).is_ok());
+        assert!(check(now - 200).is_ok());
+        assert!(check(now + 30).is_ok());
+        assert!(check(now - 400).is_err());
+        // A signed payload dated ahead cannot be replayed until then
+        assert!(check(now + 3600).is_err());
+        let signature = sign(b"other", now, b"{}");
+        assert!(verify_signature(b"secret", now, b"{}", &signature, max_age).is_err());
+    }
+}
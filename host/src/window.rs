@@ -0,0 +1,180 @@
+//! Sliding-window proofs over an append-only log (see [`zaik_core::window`]).
+//!
+//! The prover keeps a [`WindowState`]: the latest step's receipt and the
+//! entries in the window, which the next step needs and which never leave
+//! the prover. What it hands to verifiers is the [`WindowReceipt`] written
+//! next to the state, which [`verify`] checks.
+
+use crate::outcome::{FailureReason, Outcome};
+use crate::proof::ProofKind;
+use crate::store;
+use crate::untrusted;
+use risc0_zkvm::sha::Digest;
+use risc0_zkvm::Receipt;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use zaik_core::schema::JournalSchema;
+use zaik_core::window::{WindowEntry, WindowResult, WindowSpec};
+
+/// The latest step of a log, as a verifier receives it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WindowReceipt {
+    pub spec: WindowSpec,
+    pub receipt: Receipt,
+}
+
+impl WindowReceipt {
+    /// `<state>.receipt`.
+    pub fn path_for(state: &Path) -> PathBuf {
+        let mut path = state.as_os_str().to_owned();
+        path.push(".receipt");
+        PathBuf::from(path)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let bytes = untrusted::read(path, untrusted::MAX_BUNDLE_BYTES)?;
+        Ok(untrusted::decode(&bytes)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        store::write_atomic(path, &bincode::serialize(self)?)?;
+        Ok(())
+    }
+
+    /// The step's journal, unverified.
+    pub fn journal(&self) -> Option<WindowResult> {
+        WindowResult::from_bytes(&self.receipt.journal.bytes)
+    }
+}
+
+/// What the prover keeps between steps.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WindowState {
+    pub latest: WindowReceipt,
+    /// The entries `latest` committed to, oldest first.
+    pub window: Vec<WindowEntry>,
+}
+
+impl WindowState {
+    /// The state at `path`, or `None` before the log's first step.
+    pub fn load(path: &Path) -> Result<Option<Self>, Box<dyn Error>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let bytes = untrusted::read(path, untrusted::MAX_BUNDLE_BYTES)?;
+        Ok(Some(untrusted::decode(&bytes)?))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        store::write_atomic(path, &bincode::serialize(self)?)?;
+        Ok(())
+    }
+}
+
+/// What a verifier requires of a step beyond its receipt.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WindowRequirements {
+    /// Rows the window must keep.
+    pub rows: Option<u64>,
+    /// Seconds the window must keep.
+    pub seconds: Option<u64>,
+    /// Largest cap the guest may have checked the sum against.
+    pub max_cap: Option<u64>,
+    pub allow_dev_mode: bool,
+}
+
+/// Why a window step was not accepted.
+#[derive(Debug)]
+pub enum WindowRejection {
+    /// Dev-mode receipts are not accepted.
+    NotCryptographic(ProofKind),
+    ReceiptInvalid(String),
+    /// The journal is not a window journal of this format.
+    JournalUnreadable,
+    /// An earlier step of the log was proven with another image.
+    ImageMismatch([u32; 8]),
+    /// The journal was committed under a different spec than the receipt
+    /// file holds, or for a window of another size than required.
+    SpecMismatch,
+    /// The guest checked the sum against a looser cap than required.
+    CapTooLoose { cap: u64, max_cap: u64 },
+    /// The window's sum is over the cap.
+    CapExceeded { sum: u64, cap: u64 },
+}
+
+impl fmt::Display for WindowRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WindowRejection::NotCryptographic(kind) => write!(f, "receipt is {}, not a cryptographic proof", kind),
+            WindowRejection::ReceiptInvalid(reason) => write!(f, "receipt does not verify: {}", reason),
+            WindowRejection::JournalUnreadable => write!(f, "journal is not a window journal of this format"),
+            WindowRejection::ImageMismatch(image_id) => {
+                write!(f, "an earlier step was proven with image ID {}", Digest::from(*image_id))
+            }
+            WindowRejection::SpecMismatch => write!(f, "the log was kept under a different window spec or size"),
+            WindowRejection::CapTooLoose { cap, max_cap } => {
+                write!(f, "the sum was checked against a cap of {}, more than the {} required", cap, max_cap)
+            }
+            WindowRejection::CapExceeded { sum, cap } => write!(f, "window sum {} is over the cap of {}", sum, cap),
+        }
+    }
+}
+
+impl Error for WindowRejection {}
+
+impl WindowRejection {
+    /// The outcome and stable reason a CLI reports for this rejection.
+    pub fn to_failure(&self) -> FailureReason {
+        let (outcome, reason) = match self {
+            WindowRejection::NotCryptographic(_) => (Outcome::Conditional, "non_cryptographic_receipt"),
+            WindowRejection::ReceiptInvalid(_) => (Outcome::CryptoFailure, "receipt_verification_failed"),
+            WindowRejection::JournalUnreadable => (Outcome::CryptoFailure, "journal_decode_failed"),
+            WindowRejection::ImageMismatch(_) => (Outcome::CryptoFailure, "image_not_allowed"),
+            WindowRejection::SpecMismatch => (Outcome::PolicyReject, "window_spec_mismatch"),
+            WindowRejection::CapTooLoose { .. } => (Outcome::PolicyReject, "window_cap_mismatch"),
+            WindowRejection::CapExceeded { .. } => (Outcome::PolicyReject, "window_cap_exceeded"),
+        };
+        FailureReason::new(outcome, reason, self.to_string())
+    }
+}
+
+/// Check the latest step of a log against `image_id`, which every step
+/// must have been proven with, and `requirements`.
+pub fn verify(
+    proof: &WindowReceipt,
+    image_id: impl Into<Digest>,
+    requirements: WindowRequirements,
+) -> Result<WindowResult, WindowRejection> {
+    let image_id = image_id.into();
+    let kind = ProofKind::of(&proof.receipt);
+    if !kind.is_cryptographic() && !requirements.allow_dev_mode {
+        return Err(WindowRejection::NotCryptographic(kind));
+    }
+    proof
+        .receipt
+        .verify(image_id)
+        .map_err(|e| WindowRejection::ReceiptInvalid(e.to_string()))?;
+    let journal = proof.journal().ok_or(WindowRejection::JournalUnreadable)?;
+    // Each step checked the one before it was proven with this image
+    if journal.image_id != [0; 8] && Digest::from(journal.image_id) != image_id {
+        return Err(WindowRejection::ImageMismatch(journal.image_id));
+    }
+    if journal.spec_hash != proof.spec.hash()
+        || requirements.rows.is_some_and(|rows| rows != journal.window_rows)
+        || requirements.seconds.is_some_and(|seconds| seconds != journal.window_seconds)
+    {
+        return Err(WindowRejection::SpecMismatch);
+    }
+    if let Some(max_cap) = requirements.max_cap.filter(|max_cap| journal.cap > *max_cap) {
+        return Err(WindowRejection::CapTooLoose { cap: journal.cap, max_cap });
+    }
+    if !journal.within_cap {
+        return Err(WindowRejection::CapExceeded {
+            sum: journal.window_sum,
+            cap: journal.cap,
+        });
+    }
+    Ok(journal)
+}
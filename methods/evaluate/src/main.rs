@@ -0,0 +1,23 @@
+use risc0_zkvm::guest::env;
+use zaik_core::{evaluation::{self, EvaluationInput}, fault::GuestFault, schema::JournalSchema};
+
+fn main() {
+    // The predictions then the labels follow the input as raw frames
+    let mut input: EvaluationInput = env::read();
+    input.read_frames(read_frame).unwrap_or_else(|_| GuestFault::FrameNotUtf8.abort());
+
+    // Check both hashes, pair the rows up and score them
+    let result = evaluation::process(&input).unwrap_or_else(|fault| fault.abort());
+
+    env::commit_slice(&result.to_bytes());
+}
+
+/// A u32 LE length and that many bytes, as the host's `write_frame` sends
+/// them.
+fn read_frame() -> Vec<u8> {
+    let mut len = [0u8; 4];
+    env::read_slice(&mut len);
+    let mut bytes = vec![0u8; u32::from_le_bytes(len) as usize];
+    env::read_slice(&mut bytes);
+    bytes
+}
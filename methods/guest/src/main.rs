@@ -1,67 +1,50 @@
 use risc0_zkvm::guest::env;
-use sha2::{Sha256, Digest};
-use serde::{Deserialize, Serialize};
-
-#[derive(Debug, Serialize, Deserialize)]
-struct CsvProcessingInput {
-    csv_hash: [u8; 32],
-    csv_data: String,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-struct AgentResult {
-    csv_hash: [u8; 32],
-    column_a_sum: u64,
-    column_a_hash: [u8; 32],
-    entry_count: usize,
-}
+use zaik_core::{chunk, dataset, fault::GuestFault, schema::JournalSchema, window, AgentResult, GuestInput};
 
 fn main() {
-    // Read the CSV processing input
-    let input: CsvProcessingInput = env::read();
-    
-    // Verify the CSV hash matches what we received
-    let mut hasher = Sha256::new();
-    hasher.update(input.csv_data.as_bytes());
-    let computed_hash = hasher.finalize();
-    
-    assert_eq!(computed_hash.as_slice(), &input.csv_hash, "CSV hash mismatch");
-    
-    // Parse CSV and process column A
-    let mut column_a_sum: u64 = 0;
-    let mut column_a_values = Vec::new();
-    let mut entry_count = 0;
+    // Bulk payloads follow the input as raw frames
+    let mut input: GuestInput = env::read();
+    input.read_frames(read_frame).unwrap_or_else(|_| GuestFault::FrameNotUtf8.abort());
     
-    // Simple CSV parsing (assumes first column is column A)
-    for (i, line) in input.csv_data.lines().enumerate() {
-        if i == 0 {
-            // Skip header
-            continue;
+    let result: AgentResult = match input {
+        // Verify the CSV hash and process column A
+        GuestInput::Csv(input) => zaik_core::process(&input).unwrap_or_else(|| GuestFault::CsvHashMismatch.abort()),
+        GuestInput::Chunk(input) => {
+            env::commit(&chunk::process_chunk(&input.data));
+            return;
         }
-        
-        if let Some(first_field) = line.split(',').next() {
-            if let Ok(value) = first_field.parse::<u64>() {
-                column_a_sum += value;
-                column_a_values.push(value.to_string());
-                entry_count += 1;
+        // Each chunk's journal must come from a verified receipt
+        GuestInput::Chunked(input) => {
+            for result in &input.chunks {
+                let words = risc0_zkvm::serde::to_vec(result).expect("chunk journals always serialize");
+                let journal: Vec<u8> = words.iter().flat_map(|word| word.to_le_bytes()).collect();
+                env::verify(input.chunk_image_id, &journal).unwrap_or_else(|_| GuestFault::ChunkReceiptMissing.abort());
             }
+            chunk::compose(&input)
+        }
+        // Every file must match its hash and the dataset root
+        GuestInput::Dataset(input) => dataset::process(&input).unwrap_or_else(|| GuestFault::DatasetMismatch.abort()),
+        // The previous step must come from a verified receipt
+        GuestInput::Window(input) => {
+            if let Some(previous) = &input.previous {
+                env::verify(input.image_id, &previous.to_bytes()).unwrap_or_else(|_| GuestFault::WindowMismatch.abort());
+            }
+            let (result, _) = window::step(&input).unwrap_or_else(|fault| fault.abort());
+            env::commit_slice(&result.to_bytes());
+            return;
         }
-    }
-    
-    // Compute SHA256 of column A values concatenated
-    let column_a_concat = column_a_values.join(",");
-    let mut hasher = Sha256::new();
-    hasher.update(column_a_concat.as_bytes());
-    let column_a_hash = hasher.finalize().into();
-    
-    // Create result
-    let result = AgentResult {
-        csv_hash: input.csv_hash,
-        column_a_sum,
-        column_a_hash,
-        entry_count,
     };
     
-    // Commit result to journal for verification
-    env::commit(&result);
+    // Commit result to journal for verification, in its explicit byte layout
+    env::commit_slice(&result.to_bytes());
+}
+
+/// A u32 LE length and that many bytes, as the host's `write_input` sends
+/// them.
+fn read_frame() -> Vec<u8> {
+    let mut len = [0u8; 4];
+    env::read_slice(&mut len);
+    let mut bytes = vec![0u8; u32::from_le_bytes(len) as usize];
+    env::read_slice(&mut bytes);
+    bytes
 }
@@ -0,0 +1,27 @@
+use risc0_zkvm::guest::env;
+use zaik_core::{fault::GuestFault, migration, schema::JournalSchema, transform::{self, TransformGuestInput}};
+
+fn main() {
+    // The CSV, or both files of a migration, follow the input as raw frames
+    let mut input: TransformGuestInput = env::read();
+    input.read_frames(read_frame).unwrap_or_else(|_| GuestFault::FrameNotUtf8.abort());
+
+    let journal = match input {
+        // Check the CSV hash, transform every row and hash the records
+        TransformGuestInput::Transform(input) => transform::process(&input).map(|result| result.to_bytes()),
+        // Check both hashes and that the new file is the old one migrated
+        TransformGuestInput::Migration(input) => migration::process(&input).map(|result| result.to_bytes()),
+    };
+
+    env::commit_slice(&journal.unwrap_or_else(|fault| fault.abort()));
+}
+
+/// A u32 LE length and that many bytes, as the host's `write_frame` sends
+/// them.
+fn read_frame() -> Vec<u8> {
+    let mut len = [0u8; 4];
+    env::read_slice(&mut len);
+    let mut bytes = vec![0u8; u32::from_le_bytes(len) as usize];
+    env::read_slice(&mut bytes);
+    bytes
+}
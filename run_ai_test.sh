@@ -22,7 +22,6 @@ echo "2. Computes SHA256 hash of CSV content for verification"
 echo "3. Parses column A values and computes sum"
 echo "4. Generates cryptographic proof of execution"
 echo "5. Agent B verifies proof and checks business invariant"
-echo "6. Optional: Custom SNARK enforces sum <= threshold constraint"
 echo
 
 echo "🏃 Running with dev mode for faster testing..."
@@ -0,0 +1,33 @@
+#!/usr/bin/env python3
+"""
+Run a declarative attestation workflow (see ai_agents/dag.py for the format)
+"""
+
+import asyncio
+import sys
+from pathlib import Path
+
+sys.path.append(str(Path(__file__).parent))
+
+from ai_agents.dag import DagEngine, WorkflowDag, WorkflowDefinitionError
+
+async def main():
+    if len(sys.argv) != 2:
+        print("usage: run_dag.py <workflow.yaml>")
+        sys.exit(1)
+
+    try:
+        dag = WorkflowDag.load(sys.argv[1])
+    except WorkflowDefinitionError as e:
+        print(f"❌ Invalid workflow: {e}")
+        sys.exit(1)
+
+    results = await DagEngine().run(dag)
+    failed = [name for name, result in results.items() if result.status != "passed"]
+    if failed:
+        print(f"\n❌ Workflow finished with failures: {', '.join(failed)}")
+        sys.exit(1)
+    print("\n🎉 Workflow completed successfully!")
+
+if __name__ == "__main__":
+    asyncio.run(main())
@@ -12,13 +12,7 @@ echo
 echo "📋 Test 2: Processing test_data_large.csv (sum should exceed threshold)"
 echo "Expected sum: 1550 (400+500+300+200+150), threshold: 1000"
 
-# Temporarily modify main.rs to use test_data_large.csv
-sed -i.bak 's/test_data.csv/test_data_large.csv/' host/src/main.rs
-
-cargo run --release
-
-# Restore original file
-mv host/src/main.rs.bak host/src/main.rs
+cargo run --release -- test_data_large.csv
 
 echo
 echo "✅ Demo completed!"
\ No newline at end of file
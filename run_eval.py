@@ -0,0 +1,56 @@
+#!/usr/bin/env python3
+"""
+Score the AI verification agent against a labeled corpus
+"""
+
+import argparse
+import asyncio
+import json
+import sys
+from pathlib import Path
+
+sys.path.append(str(Path(__file__).parent))
+
+from ai_agents.evaluation import default_corpus, evaluate, load_corpus, regressions
+
+async def main():
+    parser = argparse.ArgumentParser(description=__doc__)
+    parser.add_argument("--corpus", help="JSON list of labeled cases (default: built-in quadrant corpus)")
+    parser.add_argument("--report", help="Write the full report here")
+    parser.add_argument("--baseline", help="Earlier report; fail if a case it got right is now wrong")
+    parser.add_argument("--min-accuracy", type=float, default=0.0, help="Fail below this accuracy")
+    parser.add_argument("--parallel", type=int, default=4)
+    args = parser.parse_args()
+
+    corpus = load_corpus(args.corpus) if args.corpus else default_corpus()
+    if not corpus:
+        print("❌ Corpus has no cases")
+        sys.exit(1)
+    print(f"🧪 Evaluating {len(corpus)} labeled cases")
+    report = await evaluate(corpus, max_concurrency=args.parallel)
+    metrics = report["metrics"]
+
+    print(f"\n📊 {report['model']} with {report['template']}")
+    print(f"   Accuracy: {metrics['accuracy']:.2%} ({metrics['unparseable']} without a decision)")
+    for action, stats in metrics["per_action"].items():
+        print(f"   {action}: precision {stats['precision']}, recall {stats['recall']} (n={stats['support']})")
+    for cell, stats in metrics["per_cell"].items():
+        print(f"   {cell}: {stats['correct']}/{stats['cases']}")
+
+    if args.report:
+        Path(args.report).write_text(json.dumps(report, indent=2))
+        print(f"📄 Report written to {args.report}")
+
+    failed = False
+    if metrics["accuracy"] < args.min_accuracy:
+        print(f"❌ Accuracy below {args.min_accuracy:.2%}")
+        failed = True
+    if args.baseline:
+        regressed = regressions(report, json.loads(Path(args.baseline).read_text()))
+        if regressed:
+            print(f"❌ Regressed against baseline: {', '.join(regressed)}")
+            failed = True
+    sys.exit(1 if failed else 0)
+
+if __name__ == "__main__":
+    asyncio.run(main())
@@ -0,0 +1,29 @@
+#!/usr/bin/env python3
+"""
+Run YAML workflows on cron schedules (see ai_agents/scheduler.py for the format)
+"""
+
+import asyncio
+import sys
+from pathlib import Path
+
+sys.path.append(str(Path(__file__).parent))
+
+from ai_agents.scheduler import Scheduler
+
+async def main():
+    if len(sys.argv) != 2:
+        print("usage: run_schedule.py <schedule.yaml>")
+        sys.exit(1)
+
+    scheduler = Scheduler.load(sys.argv[1])
+    print(f"🗓️  Scheduling {len(scheduler.jobs)} job(s); history in {scheduler.history_path}")
+    scheduler.install_signal_handlers()
+    await scheduler.run_forever()
+    print("👋 Scheduler stopped")
+
+if __name__ == "__main__":
+    try:
+        asyncio.run(main())
+    except KeyboardInterrupt:
+        print("\n👋 Scheduler stopped")
@@ -0,0 +1,85 @@
+#!/usr/bin/env python3
+"""
+Golden-vector test for the journal byte layout
+Decodes core/golden/journal-v7.json with the Python decoder and checks it
+against the host's JSON rendering of the same journal. If the host binary is
+built, its `schema --golden` output must also match the committed vector, so
+a layout change cannot land without updating the vector
+"""
+
+import json
+import subprocess
+import sys
+from pathlib import Path
+
+sys.path.append(str(Path(__file__).parent))
+
+from ai_agents.journal_codec import JournalDecodeError, decode
+
+ROOT = Path(__file__).parent
+GOLDEN = ROOT / "core" / "golden" / "journal-v7.json"
+SYMBOLS = {"lt": "<", "le": "<=", "ge": ">=", "gt": ">"}
+
+def show(char):
+    """A dialect character as `host` renders it"""
+    if char == "\t":
+        return "\\t"
+    return char if 0x21 <= ord(char) <= 0x7E else f"\\x{ord(char):02x}"
+
+def render(journal):
+    """The decoded journal as `host` renders it in JSON"""
+    rendered = dict(journal)
+    rendered["sum_comparison"] = SYMBOLS[journal["sum_comparison"]]
+    rendered["invariants"] = [
+        f"{inv['aggregate']}(column {inv['column']}) {SYMBOLS[inv['op']]} {inv['bound']} (value {inv['value']})"
+        for inv in journal["invariants"]
+    ]
+    rendered["chunk_image_id"] = b"".join(word.to_bytes(4, "little") for word in journal["chunk_image_id"]).hex()
+    rendered["inline_csv"] = f"{len(journal['inline_csv'].encode())} bytes" if journal["inline_csv"] else "none"
+    rendered["cell_counts"] = [
+        f"{counts['empty']} empty, {counts['unparseable']} unparseable" for counts in journal["cell_counts"]
+    ]
+    dialect = journal["dialect"]
+    rendered["dialect"] = (
+        f"delimiter '{show(dialect['delimiter'])}', quote '{show(dialect['quote'])}', "
+        + ("header" if dialect["has_header"] else "no header")
+    )
+    return rendered
+
+def test_golden_decodes():
+    golden = json.loads(GOLDEN.read_text())
+    journal = decode(bytes.fromhex(golden["bytes"]))
+    assert render(journal) == golden["journal"], "decoded journal differs from the golden rendering"
+    assert journal["inline_csv"].startswith("value_a;value_b\n")
+    print("✅ Golden vector decodes to the host's rendering")
+
+def test_malformed_rejected():
+    data = bytes.fromhex(json.loads(GOLDEN.read_text())["bytes"])
+    for name, bad in [
+        ("truncated", data[:-1]),
+        ("trailing byte", data + b"\0"),
+        ("bad magic", b"ZAIX" + data[4:]),
+        ("unknown format", data[:4] + (8).to_bytes(4, "little") + data[8:]),
+    ]:
+        try:
+            decode(bad)
+        except JournalDecodeError:
+            continue
+        raise AssertionError(f"{name} journal was accepted")
+    print("✅ Malformed journals are rejected")
+
+def test_host_matches_golden():
+    host = next((path for path in [ROOT / "target" / "release" / "host", ROOT / "target" / "debug" / "host"]
+                 if path.exists()), None)
+    if host is None:
+        print("⏭️  Host binary not built; skipping the host comparison")
+        return
+    output = subprocess.run([str(host), "schema", "--golden"], capture_output=True, text=True, check=True)
+    assert json.loads(output.stdout) == json.loads(GOLDEN.read_text()), \
+        "host golden vector changed; regenerate core/golden/journal-v7.json and bump the format if intended"
+    print("✅ Host golden vector matches the committed one")
+
+if __name__ == "__main__":
+    test_golden_decodes()
+    test_malformed_rejected()
+    test_host_matches_golden()